@@ -151,6 +151,7 @@ impl DeployExt for Deploy {
             timestamp,
             ttl,
             gas_price,
+            0,
             dependencies,
             chain_name,
             payment,
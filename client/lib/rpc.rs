@@ -83,6 +83,7 @@ impl RpcCall {
         })?;
         let params = GetDeployParams {
             deploy_hash: DeployHash::new(hash),
+            omit_effects: false,
         };
         GetDeploy::request_with_map_params(self, params)
     }
@@ -114,6 +115,7 @@ impl RpcCall {
             state_root_hash,
             key: key.to_formatted_string(),
             path: path.clone(),
+            proof_only: false,
         };
         let response = GetItem::request_with_map_params(self, params)?;
         validation::validate_query_response(&response, &state_root_hash, &key, &path)?;
@@ -1,6 +1,7 @@
 use casper_types::{Key, URef, U512};
 
 use crate::{
+    core::engine_state::Error,
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
     storage::trie::merkle_proof::TrieMerkleProof,
 };
@@ -52,3 +53,33 @@ impl BalanceRequest {
         self.purse_uref
     }
 }
+
+/// A request for the balances of several purses at a single state root.
+///
+/// Each key is either the `URef` of a purse, or an account whose main purse is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceBatchRequest {
+    state_hash: Blake2bHash,
+    keys: Vec<Key>,
+}
+
+impl BalanceBatchRequest {
+    pub fn new(state_hash: Blake2bHash, keys: Vec<Key>) -> Self {
+        BalanceBatchRequest { state_hash, keys }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+}
+
+#[derive(Debug)]
+pub enum BalanceBatchResult {
+    RootNotFound,
+    /// The balance, or the error encountered reading it, for each requested key in request order.
+    Success(Vec<(Key, Result<U512, Error>)>),
+}
@@ -0,0 +1,67 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_types::ProtocolVersion;
+
+use crate::shared::{
+    host_function_costs::HostFunctionCosts, opcode_costs::OpcodeCosts, storage_costs::StorageCosts,
+    wasm_config::WasmConfig,
+};
+
+/// The gas costs charged by the engine under a given protocol version.
+///
+/// Allows contract developers to estimate the cost of executing their contracts on a particular
+/// network version.  The costs are taken verbatim from the protocol data, so the same protocol
+/// version always yields the same table.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CostTable {
+    /// The protocol version these costs apply to.
+    pub protocol_version: ProtocolVersion,
+    /// The costs of wasm opcodes, per class of instruction.
+    pub opcode_costs: OpcodeCosts,
+    /// The cost of storing data in the global state.
+    pub storage_costs: StorageCosts,
+    /// The costs of the host functions available to contracts.
+    pub host_function_costs: HostFunctionCosts,
+}
+
+impl CostTable {
+    /// Creates the cost table for `protocol_version` from its wasm config.
+    pub fn new(protocol_version: ProtocolVersion, wasm_config: &WasmConfig) -> Self {
+        CostTable {
+            protocol_version,
+            opcode_costs: wasm_config.opcode_costs(),
+            storage_costs: wasm_config.storage_costs(),
+            host_function_costs: wasm_config.take_host_function_costs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_take_costs_from_wasm_config() {
+        let protocol_version = ProtocolVersion::from_parts(1, 2, 3);
+        let wasm_config = WasmConfig::default();
+        let cost_table = CostTable::new(protocol_version, &wasm_config);
+
+        assert_eq!(cost_table.protocol_version, protocol_version);
+        assert_eq!(cost_table.opcode_costs, wasm_config.opcode_costs());
+        assert_eq!(cost_table.storage_costs, wasm_config.storage_costs());
+        assert_eq!(
+            cost_table.host_function_costs,
+            wasm_config.take_host_function_costs()
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_json() {
+        let cost_table = CostTable::new(ProtocolVersion::V1_0_0, &WasmConfig::default());
+        let json = serde_json::to_string(&cost_table).unwrap();
+        let decoded: CostTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, cost_table);
+    }
+}
@@ -13,6 +13,8 @@ pub struct DeployItem {
     pub session: ExecutableDeployItem,
     pub payment: ExecutableDeployItem,
     pub gas_price: GasPrice,
+    /// Motes paid to the proposer on top of the execution cost, as far as the payment covers it.
+    pub priority_fee: u64,
     pub authorization_keys: BTreeSet<AccountHash>,
     pub deploy_hash: DeployHash,
 }
@@ -24,6 +26,7 @@ impl DeployItem {
         session: ExecutableDeployItem,
         payment: ExecutableDeployItem,
         gas_price: GasPrice,
+        priority_fee: u64,
         authorization_keys: BTreeSet<AccountHash>,
         deploy_hash: DeployHash,
     ) -> Self {
@@ -32,6 +35,7 @@ impl DeployItem {
             session,
             payment,
             gas_price,
+            priority_fee,
             authorization_keys,
             deploy_hash,
         }
@@ -1,7 +1,15 @@
+use crate::{
+    shared::{system_config::SystemConfig, wasm_config::WasmConfig},
+    storage::protocol_data::ProtocolData,
+};
+
 /// The runtime configuration of the execution engine
 #[derive(Debug, Copy, Clone, Default)]
 pub struct EngineConfig {
-    // feature flags go here
+    /// Wasm config used for execution instead of the one stored in the protocol data, if set.
+    wasm_config_override: Option<WasmConfig>,
+    /// System config used for execution instead of the one stored in the protocol data, if set.
+    system_config_override: Option<SystemConfig>,
 }
 
 impl EngineConfig {
@@ -9,4 +17,76 @@ impl EngineConfig {
     pub fn new() -> EngineConfig {
         Default::default()
     }
+
+    /// Returns the configuration with `wasm_config` overriding the stored one during execution.
+    ///
+    /// Overrides are intended for validating candidate configurations alongside the active one,
+    /// and must not be used by an engine whose results affect consensus.
+    pub fn with_wasm_config_override(mut self, wasm_config: WasmConfig) -> EngineConfig {
+        self.wasm_config_override = Some(wasm_config);
+        self
+    }
+
+    /// Returns the configuration with `system_config` overriding the stored one during execution.
+    ///
+    /// See [`EngineConfig::with_wasm_config_override`].
+    pub fn with_system_config_override(mut self, system_config: SystemConfig) -> EngineConfig {
+        self.system_config_override = Some(system_config);
+        self
+    }
+
+    /// Applies the configured overrides to the stored `protocol_data`.
+    pub(crate) fn apply_overrides(&self, protocol_data: ProtocolData) -> ProtocolData {
+        if self.wasm_config_override.is_none() && self.system_config_override.is_none() {
+            return protocol_data;
+        }
+        ProtocolData::new(
+            self.wasm_config_override
+                .unwrap_or(*protocol_data.wasm_config()),
+            self.system_config_override
+                .unwrap_or(*protocol_data.system_config()),
+            protocol_data.mint(),
+            protocol_data.handle_payment(),
+            protocol_data.standard_payment(),
+            protocol_data.auction(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ContractHash;
+
+    use super::*;
+
+    #[test]
+    fn should_only_override_configured_parts_of_protocol_data() {
+        let protocol_data = ProtocolData::new(
+            WasmConfig::default(),
+            SystemConfig::default(),
+            ContractHash::new([1; 32]),
+            ContractHash::new([2; 32]),
+            ContractHash::new([3; 32]),
+            ContractHash::new([4; 32]),
+        );
+        assert_eq!(
+            EngineConfig::new().apply_overrides(protocol_data),
+            protocol_data
+        );
+
+        let system_config = SystemConfig::new(
+            1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let overridden = EngineConfig::new()
+            .with_system_config_override(system_config)
+            .apply_overrides(protocol_data);
+        assert_eq!(*overridden.system_config(), system_config);
+        assert_eq!(overridden.wasm_config(), protocol_data.wasm_config());
+        assert_eq!(overridden.mint(), protocol_data.mint());
+        assert_eq!(overridden.auction(), protocol_data.auction());
+    }
 }
@@ -1,4 +1,5 @@
 pub mod balance;
+pub mod cost_table;
 pub mod deploy_item;
 pub mod engine_config;
 pub mod era_validators;
@@ -18,6 +19,7 @@ pub mod upgrade;
 
 use std::{
     cell::RefCell,
+    cmp,
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     iter::FromIterator,
@@ -46,7 +48,8 @@ use casper_types::{
 };
 
 pub use self::{
-    balance::{BalanceRequest, BalanceResult},
+    balance::{BalanceBatchRequest, BalanceBatchResult, BalanceRequest, BalanceResult},
+    cost_table::CostTable,
     deploy_item::DeployItem,
     engine_config::EngineConfig,
     era_validators::{GetEraValidatorsError, GetEraValidatorsRequest},
@@ -56,7 +59,10 @@ pub use self::{
     execution::Error as ExecError,
     execution_result::{ExecutionResult, ExecutionResults, ForcedTransferResult},
     genesis::{ExecConfig, GenesisAccount, GenesisResult},
-    query::{GetBidsRequest, GetBidsResult, QueryRequest, QueryResult},
+    query::{
+        GetBidsRequest, GetBidsResult, GetKeysByPrefixRequest, GetKeysByPrefixResult,
+        QueryBatchRequest, QueryBatchResult, QueryRequest, QueryResult,
+    },
     step::{RewardItem, SlashItem, StepRequest, StepResult},
     system_contract_cache::SystemContractCache,
     transfer::{TransferArgs, TransferRuntimeArgsBuilder, TransferTargetMode},
@@ -69,7 +75,7 @@ use crate::{
             genesis::GenesisInstaller, upgrade::SystemUpgrader,
         },
         execution::{self, DirectSystemContractCall, Executor},
-        tracking_copy::{TrackingCopy, TrackingCopyExt},
+        tracking_copy::{TrackingCopy, TrackingCopyExt, TrackingCopyQueryResult},
     },
     shared::{
         account::Account,
@@ -82,7 +88,7 @@ use crate::{
         wasm_prep::Preprocessor,
     },
     storage::{
-        global_state::{CommitResult, StateProvider},
+        global_state::{CommitResult, StateProvider, StateReader},
         protocol_data::ProtocolData,
         trie::Trie,
     },
@@ -133,6 +139,17 @@ where
         }
     }
 
+    /// Returns the gas costs charged for wasm opcodes, storage and host functions under the given
+    /// protocol version, or `None` if the protocol version is unknown.
+    pub fn get_cost_table(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<CostTable>, Error> {
+        let maybe_protocol_data = self.get_protocol_data(protocol_version)?;
+        Ok(maybe_protocol_data
+            .map(|protocol_data| CostTable::new(protocol_version, protocol_data.wasm_config())))
+    }
+
     pub fn commit_genesis(
         &self,
         correlation_id: CorrelationId,
@@ -420,6 +437,43 @@ where
             .into())
     }
 
+    /// Runs several queries against a single state root, sharing one tracking copy between them.
+    ///
+    /// The batch is abandoned as soon as the values and proofs found so far exceed the response
+    /// size limit, rather than reading all of them first.
+    pub fn run_query_batch(
+        &self,
+        correlation_id: CorrelationId,
+        query_batch_request: QueryBatchRequest,
+    ) -> Result<QueryBatchResult, Error> {
+        let max_queries = query_batch_request.max_queries();
+        if query_batch_request.queries().len() > max_queries {
+            return Ok(QueryBatchResult::TooManyQueries { max_queries });
+        }
+
+        let tracking_copy = match self.tracking_copy(query_batch_request.state_hash())? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(QueryBatchResult::RootNotFound),
+        };
+
+        let max_response_size = query_batch_request.max_response_size();
+        let mut response_size = 0;
+        let mut results = Vec::with_capacity(query_batch_request.queries().len());
+        for (key, path) in query_batch_request.queries() {
+            let result = tracking_copy
+                .query(correlation_id, *key, path)
+                .map_err(|err| Error::Exec(err.into()))?;
+            if let TrackingCopyQueryResult::Success { value, proofs } = &result {
+                response_size += value.serialized_length() + proofs.serialized_length();
+                if response_size > max_response_size {
+                    return Ok(QueryBatchResult::ResponseTooLarge { max_response_size });
+                }
+            }
+            results.push(result.into());
+        }
+        Ok(QueryBatchResult::Success(results))
+    }
+
     pub fn run_execute(
         &self,
         correlation_id: CorrelationId,
@@ -514,6 +568,48 @@ where
         Ok(BalanceResult::Success { motes, proof })
     }
 
+    /// Reads the balances of several purses at a single state root, sharing one reader between
+    /// them.
+    pub fn get_purse_balances(
+        &self,
+        correlation_id: CorrelationId,
+        balance_batch_request: BalanceBatchRequest,
+    ) -> Result<BalanceBatchResult, Error> {
+        let mut tracking_copy = match self.tracking_copy(balance_batch_request.state_hash())? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(BalanceBatchResult::RootNotFound),
+        };
+        let balances = balance_batch_request
+            .keys()
+            .iter()
+            .map(|key| {
+                let balance = Self::read_balance(&mut tracking_copy, correlation_id, *key);
+                (*key, balance)
+            })
+            .collect();
+        Ok(BalanceBatchResult::Success(balances))
+    }
+
+    /// Reads the balance of the purse identified by `key`, which is either a purse `URef` or an
+    /// account, in which case its main purse is read.
+    fn read_balance(
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+        correlation_id: CorrelationId,
+        key: Key,
+    ) -> Result<U512, Error> {
+        let purse_uref = match key {
+            Key::URef(uref) => uref,
+            Key::Account(account_hash) => tracking_copy
+                .get_account(correlation_id, account_hash)?
+                .main_purse(),
+            _ => return Err(Error::InvalidKeyVariant),
+        };
+        let purse_balance_key =
+            tracking_copy.get_purse_balance_key(correlation_id, purse_uref.into())?;
+        let balance = tracking_copy.get_purse_balance(correlation_id, purse_balance_key)?;
+        Ok(balance.value())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn transfer(
         &self,
@@ -526,7 +622,7 @@ where
         proposer: PublicKey,
     ) -> Result<ExecutionResult, RootNotFound> {
         let protocol_data = match self.state.get_protocol_data(protocol_version) {
-            Ok(Some(protocol_data)) => protocol_data,
+            Ok(Some(protocol_data)) => self.config.apply_overrides(protocol_data),
             Ok(None) => {
                 let error = Error::InvalidProtocolVersion(protocol_version);
                 return Ok(ExecutionResult::precondition_failure(error));
@@ -1060,7 +1156,7 @@ where
         // Obtain current protocol data for given version
         // do this first, as there is no reason to proceed if protocol version is invalid
         let protocol_data = match self.state.get_protocol_data(protocol_version) {
-            Ok(Some(protocol_data)) => protocol_data,
+            Ok(Some(protocol_data)) => self.config.apply_overrides(protocol_data),
             Ok(None) => {
                 let error = Error::InvalidProtocolVersion(protocol_version);
                 return Ok(ExecutionResult::precondition_failure(error));
@@ -1574,6 +1670,16 @@ where
                     None => return Ok(ExecutionResult::precondition_failure(Error::GasConversionOverflow)),
                 };
 
+                // The priority fee goes to the proposer along with the cost, but only as far as
+                // the payment purse covers it once the cost is paid.
+                let finalize_cost_motes = {
+                    let unspent = payment_purse_balance
+                        .value()
+                        .saturating_sub(finalize_cost_motes.value());
+                    let priority_fee = cmp::min(U512::from(deploy_item.priority_fee), unspent);
+                    finalize_cost_motes + Motes::new(priority_fee)
+                };
+
                 let maybe_runtime_args = RuntimeArgs::try_new(|args| {
                     args.insert(handle_payment::ARG_AMOUNT, finalize_cost_motes.value())?;
                     args.insert(handle_payment::ARG_ACCOUNT, account_hash)?;
@@ -1826,13 +1932,42 @@ where
         Ok(GetBidsResult::Success { bids })
     }
 
+    pub fn get_keys_by_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        get_keys_request: GetKeysByPrefixRequest,
+    ) -> Result<GetKeysByPrefixResult, Error> {
+        let reader = match self
+            .state
+            .checkout(get_keys_request.state_hash())
+            .map_err(Into::into)?
+        {
+            Some(reader) => reader,
+            None => return Ok(GetKeysByPrefixResult::RootNotFound),
+        };
+
+        let all_keys = reader
+            .keys_with_prefix(correlation_id, get_keys_request.prefix().as_bytes())
+            .map_err(|err| Error::Exec(err.into()))?;
+        let total = all_keys.len();
+
+        let keys: Vec<Key> = all_keys
+            .into_iter()
+            .skip(get_keys_request.offset())
+            .take(get_keys_request.limit())
+            .collect();
+        let has_more = get_keys_request.offset().saturating_add(keys.len()) < total;
+
+        Ok(GetKeysByPrefixResult::Success { keys, has_more })
+    }
+
     pub fn commit_step(
         &self,
         correlation_id: CorrelationId,
         step_request: StepRequest,
     ) -> Result<StepResult, Error> {
         let protocol_data = match self.state.get_protocol_data(step_request.protocol_version) {
-            Ok(Some(protocol_data)) => protocol_data,
+            Ok(Some(protocol_data)) => self.config.apply_overrides(protocol_data),
             Ok(None) => {
                 return Ok(StepResult::InvalidProtocolVersion);
             }
@@ -2,7 +2,7 @@ use casper_types::{system::auction::Bids, Key};
 
 use crate::{
     core::tracking_copy::TrackingCopyQueryResult,
-    shared::{newtypes::Blake2bHash, stored_value::StoredValue},
+    shared::{key_prefix::KeyPrefix, newtypes::Blake2bHash, stored_value::StoredValue},
     storage::trie::merkle_proof::TrieMerkleProof,
 };
 
@@ -61,6 +61,63 @@ impl From<TrackingCopyQueryResult> for QueryResult {
     }
 }
 
+/// A request for several values at a single state root, each given by a base key and a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryBatchRequest {
+    state_hash: Blake2bHash,
+    queries: Vec<(Key, Vec<String>)>,
+    max_queries: usize,
+    max_response_size: usize,
+}
+
+impl QueryBatchRequest {
+    /// Creates a request for the values at `queries`, to be rejected if there are more than
+    /// `max_queries` of them, or if the values and proofs found exceed `max_response_size` bytes
+    /// in serialized form.
+    pub fn new(
+        state_hash: Blake2bHash,
+        queries: Vec<(Key, Vec<String>)>,
+        max_queries: usize,
+        max_response_size: usize,
+    ) -> Self {
+        QueryBatchRequest {
+            state_hash,
+            queries,
+            max_queries,
+            max_response_size,
+        }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    pub fn queries(&self) -> &[(Key, Vec<String>)] {
+        &self.queries
+    }
+
+    pub fn max_queries(&self) -> usize {
+        self.max_queries
+    }
+
+    pub fn max_response_size(&self) -> usize {
+        self.max_response_size
+    }
+}
+
+#[derive(Debug)]
+pub enum QueryBatchResult {
+    RootNotFound,
+    TooManyQueries {
+        max_queries: usize,
+    },
+    ResponseTooLarge {
+        max_response_size: usize,
+    },
+    /// The result of each query in request order.  None of them is `QueryResult::RootNotFound`.
+    Success(Vec<QueryResult>),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetBidsRequest {
     state_hash: Blake2bHash,
@@ -94,3 +151,50 @@ impl GetBidsResult {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetKeysByPrefixRequest {
+    state_hash: Blake2bHash,
+    prefix: KeyPrefix,
+    offset: usize,
+    limit: usize,
+}
+
+impl GetKeysByPrefixRequest {
+    /// Creates a request for at most `limit` keys starting with `prefix`, skipping the first
+    /// `offset` of them.
+    pub fn new(state_hash: Blake2bHash, prefix: KeyPrefix, offset: usize, limit: usize) -> Self {
+        GetKeysByPrefixRequest {
+            state_hash,
+            prefix,
+            offset,
+            limit,
+        }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    pub fn prefix(&self) -> &KeyPrefix {
+        &self.prefix
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+#[derive(Debug)]
+pub enum GetKeysByPrefixResult {
+    RootNotFound,
+    /// A page of keys, along with whether further keys follow it.
+    Success {
+        keys: Vec<Key>,
+        has_more: bool,
+    },
+}
@@ -200,6 +200,10 @@ impl ModuleImportResolver for RuntimeModuleImportResolver {
                 Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
                 FunctionIndex::Blake2b.into(),
             ),
+            "casper_verify_signature" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 7][..], Some(ValueType::I32)),
+                FunctionIndex::VerifySignature.into(),
+            ),
             "casper_record_transfer" => FuncInstance::alloc_host(
                 Signature::new(&[ValueType::I32; 10][..], Some(ValueType::I32)),
                 FunctionIndex::RecordTransfer.into(),
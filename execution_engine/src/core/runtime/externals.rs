@@ -926,6 +926,48 @@ where
                 Ok(Some(RuntimeValue::I32(0)))
             }
 
+            FunctionIndex::VerifySignature => {
+                // args(0) = algorithm of the signature and public key
+                // args(1) = pointer to message
+                // args(2) = size of message
+                // args(3) = pointer to signature
+                // args(4) = size of signature
+                // args(5) = pointer to public key
+                // args(6) = size of public key
+                let (
+                    algorithm,
+                    message_ptr,
+                    message_size,
+                    signature_ptr,
+                    signature_size,
+                    public_key_ptr,
+                    public_key_size,
+                ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.verify_signature,
+                    [
+                        algorithm,
+                        message_ptr,
+                        message_size,
+                        signature_ptr,
+                        signature_size,
+                        public_key_ptr,
+                        public_key_size,
+                    ],
+                )?;
+                scoped_instrumenter.add_property("message_size", message_size.to_string());
+                let is_valid = self.verify_signature(
+                    algorithm,
+                    message_ptr,
+                    message_size,
+                    signature_ptr,
+                    signature_size,
+                    public_key_ptr,
+                    public_key_size,
+                )?;
+                Ok(Some(RuntimeValue::I32(i32::from(is_valid))))
+            }
+
             FunctionIndex::RecordTransfer => {
                 // RecordTransfer is a special cased internal host function only callable by the
                 // mint contract and for accounting purposes it isn't represented in protocol data.
@@ -4,6 +4,7 @@ mod externals;
 mod handle_payment_internal;
 mod mint_internal;
 mod scoped_instrumenter;
+mod signature;
 mod standard_payment_internal;
 
 use std::{
@@ -1083,6 +1084,29 @@ where
         Ok(self.context.validate_uref(&uref).is_ok())
     }
 
+    /// Verifies the signature of a message against a public key, all read from Wasm memory.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_signature(
+        &self,
+        algorithm: u32,
+        message_ptr: u32,
+        message_size: u32,
+        signature_ptr: u32,
+        signature_size: u32,
+        public_key_ptr: u32,
+        public_key_size: u32,
+    ) -> Result<bool, Trap> {
+        let message = self.bytes_from_mem(message_ptr, message_size as usize)?;
+        let signature = self.bytes_from_mem(signature_ptr, signature_size as usize)?;
+        let public_key = self.bytes_from_mem(public_key_ptr, public_key_size as usize)?;
+        Ok(signature::verify(
+            algorithm,
+            &message,
+            &signature,
+            &public_key,
+        ))
+    }
+
     /// Load the uref known by the given name into the Wasm memory
     fn load_key(
         &mut self,
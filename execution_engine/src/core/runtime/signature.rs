@@ -0,0 +1,137 @@
+//! Verification of signatures on behalf of contracts.
+
+use std::convert::TryFrom;
+
+use k256::ecdsa::{self, signature::Verifier};
+
+use casper_types::{AsymmetricType, PublicKey, Signature, ED25519_TAG, SECP256K1_TAG};
+
+/// Verifies `signature` of `message` against `public_key`, both given as raw bytes of the
+/// algorithm identified by `algorithm`, i.e. `ED25519_TAG` or `SECP256K1_TAG`.
+///
+/// Returns `false` if the signature is invalid, if either the signature or the public key is
+/// malformed, or if the algorithm is unknown.  Verification is strict, so the result only depends
+/// on the inputs.
+pub(super) fn verify(algorithm: u32, message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    match u8::try_from(algorithm) {
+        Ok(ED25519_TAG) => verify_ed25519(message, signature, public_key),
+        Ok(SECP256K1_TAG) => verify_secp256k1(message, signature, public_key),
+        _ => false,
+    }
+}
+
+fn verify_ed25519(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    let public_key = match ed25519_dalek::PublicKey::from_bytes(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let signature = match ed25519_dalek::Signature::try_from(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    public_key.verify_strict(message, &signature).is_ok()
+}
+
+fn verify_secp256k1(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    let public_key = match PublicKey::secp256k1_from_bytes(public_key) {
+        Ok(PublicKey::Secp256k1(public_key)) => public_key,
+        _ => return false,
+    };
+    let signature = match Signature::secp256k1_from_bytes(signature) {
+        Ok(Signature::Secp256k1(signature)) => signature,
+        _ => return false,
+    };
+    match ecdsa::Verifier::new(&public_key) {
+        Ok(verifier) => verifier.verify(message, &signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::ExpandedSecretKey;
+    use k256::ecdsa::signature::RandomizedSigner;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use casper_types::{SecretKey, SYSTEM_TAG};
+
+    use super::*;
+
+    const MESSAGE: &[u8] = b"message to be signed";
+
+    fn ed25519_fixture() -> (Vec<u8>, Vec<u8>) {
+        let secret_key = ed25519_dalek::SecretKey::from_bytes(&[1; 32]).unwrap();
+        let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+        let signature = ExpandedSecretKey::from(&secret_key).sign(MESSAGE, &public_key);
+        (
+            signature.to_bytes().to_vec(),
+            public_key.to_bytes().to_vec(),
+        )
+    }
+
+    fn secp256k1_fixture() -> (Vec<u8>, Vec<u8>) {
+        let secret_key = SecretKey::secp256k1([1; 32]);
+        let public_key = PublicKey::from(&secret_key);
+        let signer = match &secret_key {
+            SecretKey::Secp256k1(secret_key) => ecdsa::Signer::new(secret_key).unwrap(),
+            _ => unreachable!(),
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let signature = Signature::Secp256k1(signer.sign_with_rng(&mut rng, MESSAGE));
+        (signature.as_ref().to_vec(), public_key.as_ref().to_vec())
+    }
+
+    #[test]
+    fn should_verify_valid_signatures() {
+        let (signature, public_key) = ed25519_fixture();
+        assert!(verify(ED25519_TAG.into(), MESSAGE, &signature, &public_key));
+
+        let (signature, public_key) = secp256k1_fixture();
+        assert!(verify(
+            SECP256K1_TAG.into(),
+            MESSAGE,
+            &signature,
+            &public_key
+        ));
+    }
+
+    #[test]
+    fn should_reject_invalid_signatures() {
+        for (algorithm, (mut signature, public_key)) in vec![
+            (ED25519_TAG, ed25519_fixture()),
+            (SECP256K1_TAG, secp256k1_fixture()),
+        ] {
+            let algorithm = u32::from(algorithm);
+            assert!(!verify(
+                algorithm,
+                b"other message",
+                &signature,
+                &public_key
+            ));
+            assert!(!verify(algorithm, MESSAGE, &signature[1..], &public_key));
+            assert!(!verify(algorithm, MESSAGE, &signature, &public_key[1..]));
+            assert!(!verify(algorithm, MESSAGE, &[], &[]));
+            signature[0] ^= 1;
+            assert!(!verify(algorithm, MESSAGE, &signature, &public_key));
+        }
+    }
+
+    #[test]
+    fn should_reject_mismatched_or_unknown_algorithm() {
+        let (signature, public_key) = ed25519_fixture();
+        assert!(!verify(
+            SECP256K1_TAG.into(),
+            MESSAGE,
+            &signature,
+            &public_key
+        ));
+        assert!(!verify(SYSTEM_TAG.into(), MESSAGE, &signature, &public_key));
+        assert!(!verify(
+            256 + u32::from(ED25519_TAG),
+            MESSAGE,
+            &signature,
+            &public_key
+        ));
+    }
+}
@@ -21,6 +21,7 @@ use crate::{
     core::engine_state::{execution_effect::ExecutionEffect, op::Op},
     shared::{
         additive_map::AdditiveMap,
+        key_prefix::KeyPrefix,
         newtypes::{Blake2bHash, CorrelationId},
         stored_value::StoredValue,
         transform::{self, Transform},
@@ -278,7 +279,7 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
                 let key_tag = key_tag.to_owned();
                 let keys = self
                     .reader
-                    .keys_with_prefix(correlation_id, &[key_tag as u8])?;
+                    .keys_with_prefix(correlation_id, KeyPrefix::from(key_tag).as_bytes())?;
                 ret.extend(keys);
                 self.cache.insert_key_tag_read(key_tag, ret.to_owned())
             }
@@ -289,6 +290,29 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         Ok(ret)
     }
 
+    /// Returns all keys starting with `prefix`, including keys written through this tracking copy.
+    ///
+    /// Unlike [`TrackingCopy::get_keys`], the keys read are not cached, since composite prefixes
+    /// are rarely queried more than once.
+    pub fn get_keys_with_prefix(
+        &mut self,
+        correlation_id: CorrelationId,
+        prefix: &KeyPrefix,
+    ) -> Result<BTreeSet<Key>, R::Error> {
+        let mut ret: BTreeSet<Key> = self
+            .reader
+            .keys_with_prefix(correlation_id, prefix.as_bytes())?
+            .into_iter()
+            .collect();
+        ret.extend(
+            self.cache
+                .muts_cached
+                .keys()
+                .filter(|key| prefix.matches(key)),
+        );
+        Ok(ret)
+    }
+
     pub fn read(
         &mut self,
         correlation_id: CorrelationId,
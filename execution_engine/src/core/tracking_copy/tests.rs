@@ -17,6 +17,7 @@ use crate::{
     core::{engine_state::op::Op, ValidationError},
     shared::{
         account::{Account, AssociatedKeys},
+        key_prefix::KeyPrefix,
         newtypes::{Blake2bHash, CorrelationId},
         stored_value::{gens::stored_value_arb, StoredValue},
         transform::Transform,
@@ -1000,3 +1001,39 @@ fn get_keys_should_handle_reads_from_empty_trie() {
     assert!(key_set.contains(&uref_3_key.normalize()));
     assert!(!key_set.contains(&account_key));
 }
+
+#[test]
+fn get_keys_with_prefix_should_match_composite_prefixes() {
+    let value = StoredValue::CLValue(CLValue::from_t(U512::zero()).expect("should convert"));
+    let uref_1_key = Key::URef(URef::new([8; 32], AccessRights::READ_ADD_WRITE));
+    let uref_2_key = Key::URef(URef::new([9; 32], AccessRights::READ_ADD_WRITE));
+
+    let correlation_id = CorrelationId::new();
+    let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+        correlation_id,
+        &[(uref_1_key, value.clone()), (uref_2_key, value.clone())],
+    )
+    .unwrap();
+
+    let view = global_state
+        .checkout(root_hash)
+        .expect("should checkout")
+        .expect("should have view");
+
+    let mut tracking_copy = TrackingCopy::new(view);
+
+    let mut uref_3_addr = [8; 32];
+    uref_3_addr[31] = 0;
+    let uref_3_key = Key::URef(URef::new(uref_3_addr, AccessRights::READ_ADD_WRITE));
+    tracking_copy.write(uref_3_key, value);
+
+    let prefix = KeyPrefix::new(KeyTag::URef).then([8]);
+    let key_set = tracking_copy
+        .get_keys_with_prefix(correlation_id, &prefix)
+        .unwrap();
+
+    assert_eq!(key_set.len(), 2);
+    assert!(key_set.contains(&uref_1_key.normalize()));
+    assert!(key_set.contains(&uref_3_key.normalize()));
+    assert!(!key_set.contains(&uref_2_key.normalize()));
+}
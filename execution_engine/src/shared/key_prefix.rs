@@ -0,0 +1,78 @@
+//! Prefixes of serialized [`Key`]s, used to enumerate keys in global state.
+//!
+//! Keys are stored in the trie under their serialized form, which starts with the key's tag
+//! followed by its payload.  A prefix therefore always starts with a [`KeyTag`] and may be
+//! narrowed down further by appending leading bytes of the payload, e.g. the first bytes of an
+//! account hash.
+
+use casper_types::{bytesrepr::ToBytes, Key, KeyTag};
+
+/// A prefix of serialized keys, made of a key tag and optionally some leading payload bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyPrefix(Vec<u8>);
+
+impl KeyPrefix {
+    /// Creates a prefix matching all keys with the given tag.
+    pub fn new(key_tag: KeyTag) -> Self {
+        KeyPrefix(vec![key_tag as u8])
+    }
+
+    /// Returns a composite prefix, additionally requiring the payload to continue with `bytes`.
+    pub fn then<T: AsRef<[u8]>>(mut self, bytes: T) -> Self {
+        self.0.extend_from_slice(bytes.as_ref());
+        self
+    }
+
+    /// Returns whether `key` starts with this prefix.
+    pub fn matches(&self, key: &Key) -> bool {
+        key.to_bytes()
+            .map(|bytes| bytes.starts_with(&self.0))
+            .unwrap_or(false)
+    }
+
+    /// Returns the prefix as raw bytes, as used to walk the trie.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<KeyTag> for KeyPrefix {
+    fn from(key_tag: KeyTag) -> Self {
+        KeyPrefix::new(key_tag)
+    }
+}
+
+impl AsRef<[u8]> for KeyPrefix {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{account::AccountHash, AccessRights, URef};
+
+    use super::*;
+
+    #[test]
+    fn should_match_keys_by_tag() {
+        let prefix = KeyPrefix::from(KeyTag::Bid);
+        assert!(prefix.matches(&Key::Bid(AccountHash::new([1; 32]))));
+        assert!(!prefix.matches(&Key::Account(AccountHash::new([1; 32]))));
+        assert!(!prefix.matches(&Key::URef(URef::new([1; 32], AccessRights::READ))));
+    }
+
+    #[test]
+    fn should_match_keys_by_composite_prefix() {
+        let mut addr = [0; 32];
+        addr[0] = 0xab;
+        addr[1] = 0xcd;
+        let prefix = KeyPrefix::new(KeyTag::Account).then([0xab, 0xcd]);
+        assert_eq!(prefix.as_bytes(), &[KeyTag::Account as u8, 0xab, 0xcd]);
+        assert!(prefix.matches(&Key::Account(AccountHash::new(addr))));
+
+        addr[1] = 0xce;
+        assert!(!prefix.matches(&Key::Account(AccountHash::new(addr))));
+        assert!(!prefix.matches(&Key::Bid(AccountHash::new([0xab; 32]))));
+    }
+}
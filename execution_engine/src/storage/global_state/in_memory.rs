@@ -223,6 +223,7 @@ impl StateProvider for InMemoryGlobalState {
             correlation_id,
             prestate_hash,
             effects,
+            None,
         )?;
         Ok(commit_result)
     }
@@ -1,5 +1,7 @@
 use std::{ops::Deref, sync::Arc};
 
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
 use crate::shared::{
     additive_map::AdditiveMap,
     newtypes::{Blake2bHash, CorrelationId},
@@ -29,6 +31,8 @@ pub struct LmdbGlobalState {
     pub trie_store: Arc<LmdbTrieStore>,
     pub protocol_data_store: Arc<LmdbProtocolDataStore>,
     pub empty_root_hash: Blake2bHash,
+    /// Pool used to hash and serialize trie nodes in parallel during `commit`.
+    hashing_pool: Option<Arc<ThreadPool>>,
 }
 
 /// Represents a "view" of global state at a particular root hash.
@@ -73,8 +77,28 @@ impl LmdbGlobalState {
             trie_store,
             protocol_data_store,
             empty_root_hash,
+            hashing_pool: None,
         }
     }
+
+    /// Spreads the hashing of trie nodes during `commit` over a pool of `threads` worker threads.
+    ///
+    /// With fewer than two threads, nodes are hashed on the committing thread.
+    pub fn with_commit_hashing_threads(
+        mut self,
+        threads: usize,
+    ) -> Result<Self, ThreadPoolBuildError> {
+        self.hashing_pool = if threads < 2 {
+            None
+        } else {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|index| format!("trie-hashing-{}", index))
+                .build()?;
+            Some(Arc::new(pool))
+        };
+        Ok(self)
+    }
 }
 
 impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
@@ -182,6 +206,7 @@ impl StateProvider for LmdbGlobalState {
             correlation_id,
             prestate_hash,
             effects,
+            self.hashing_pool.as_deref(),
         )?;
         Ok(commit_result)
     }
@@ -443,4 +468,55 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn commit_with_hashing_pool_matches_serial_writes() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let (state, root_hash) = create_test_state();
+        let state = state.with_commit_hashing_threads(4).unwrap();
+
+        let expected_hash = {
+            let mut txn = state.environment.create_read_write_txn().unwrap();
+            let mut current_root = root_hash;
+            for TestPair { key, value } in &test_pairs_updated {
+                if let WriteResult::Written(root_hash) =
+                    write::<_, _, _, LmdbTrieStore, error::Error>(
+                        correlation_id,
+                        &mut txn,
+                        &state.trie_store,
+                        &current_root,
+                        key,
+                        value,
+                    )
+                    .unwrap()
+                {
+                    current_root = root_hash;
+                }
+            }
+            // Dropping the transaction leaves the serially written nodes out of the store.
+            drop(txn);
+            current_root
+        };
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+
+        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
+            CommitResult::Success { state_root, .. } => state_root,
+            _ => panic!("commit failed"),
+        };
+        assert_eq!(updated_hash, expected_hash);
+
+        let missing_keys = state
+            .missing_trie_keys(correlation_id, vec![updated_hash])
+            .unwrap();
+        assert!(missing_keys.is_empty());
+    }
 }
@@ -3,6 +3,8 @@ pub mod lmdb;
 
 use std::{fmt, hash::BuildHasher};
 
+use rayon::ThreadPool;
+
 use crate::shared::{
     additive_map::AdditiveMap,
     newtypes::{Blake2bHash, CorrelationId},
@@ -17,7 +19,8 @@ use crate::storage::{
     transaction_source::{Transaction, TransactionSource},
     trie::{merkle_proof::TrieMerkleProof, Trie},
     trie_store::{
-        operations::{read, write, ReadResult, WriteResult},
+        operations::{hash_tries, read, write_with_leaf_hash, ReadResult, WriteResult},
+        write_batch::WriteBatchTrieStore,
         TrieStore,
     },
 };
@@ -132,12 +135,19 @@ pub trait StateProvider {
     ) -> Result<Vec<Blake2bHash>, Self::Error>;
 }
 
+/// Applies `effects` to the state at `prestate_hash` and persists the result in a single
+/// read-write transaction.
+///
+/// The new trie nodes are buffered in memory while the effects are written, and only the nodes
+/// reachable from the resulting state root are stored.  If `hashing_pool` is given, the new leaves
+/// are hashed and the stored nodes serialized on it in parallel.
 pub fn commit<'a, R, S, H, E>(
     environment: &'a R,
     store: &S,
     correlation_id: CorrelationId,
     prestate_hash: Blake2bHash,
     effects: AdditiveMap<Key, Transform, H>,
+    hashing_pool: Option<&ThreadPool>,
 ) -> Result<CommitResult, E>
 where
     R: TransactionSource<'a, Handle = S::Handle>,
@@ -155,8 +165,11 @@ where
         return Ok(CommitResult::RootNotFound);
     };
 
+    // Each key appears only once in `effects`, so all current values can be read from the
+    // prestate before anything is written.
+    let mut leaves = Vec::new();
     for (key, transform) in effects.into_iter() {
-        let read_result = read::<_, _, _, _, E>(correlation_id, &txn, store, &state_root, &key)?;
+        let read_result = read::<_, _, _, _, E>(correlation_id, &txn, store, &prestate_hash, &key)?;
 
         let value = match (read_result, transform) {
             (ReadResult::NotFound, Transform::Write(new_value)) => new_value,
@@ -170,8 +183,26 @@ where
             _x @ (ReadResult::RootNotFound, _) => panic!(stringify!(_x._1)),
         };
 
-        let write_result =
-            write::<_, _, _, _, E>(correlation_id, &mut txn, store, &state_root, &key, &value)?;
+        leaves.push(Trie::Leaf { key, value });
+    }
+
+    let leaf_hashes = hash_tries(&leaves, hashing_pool)?;
+
+    let batch = WriteBatchTrieStore::new(store);
+    for (leaf, leaf_hash) in leaves.iter().zip(leaf_hashes) {
+        let (key, value) = match leaf {
+            Trie::Leaf { key, value } => (key, value),
+            _ => unreachable!("only leaves are collected"),
+        };
+        let write_result = write_with_leaf_hash::<_, _, _, _, E>(
+            correlation_id,
+            &mut txn,
+            &batch,
+            &state_root,
+            key,
+            value,
+            Some(leaf_hash),
+        )?;
 
         match write_result {
             WriteResult::Written(root_hash) => {
@@ -181,6 +212,7 @@ where
             _x @ WriteResult::RootNotFound => panic!(stringify!(_x)),
         }
     }
+    batch.flush(&mut txn, &state_root, hashing_pool)?;
 
     txn.commit()?;
 
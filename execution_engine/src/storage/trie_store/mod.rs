@@ -7,6 +7,7 @@ pub mod lmdb;
 pub(crate) mod operations;
 #[cfg(test)]
 mod tests;
+pub(crate) mod write_batch;
 
 use crate::shared::newtypes::Blake2bHash;
 
@@ -3,6 +3,7 @@ mod tests;
 
 use std::{cmp, collections::VecDeque, convert::TryInto, mem};
 
+use rayon::{prelude::*, ThreadPool};
 use tracing::warn;
 
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
@@ -391,6 +392,7 @@ where
 #[allow(clippy::type_complexity)]
 fn rehash<K, V>(
     mut tip: Trie<K, V>,
+    maybe_tip_hash: Option<Blake2bHash>,
     parents: Parents<K, V>,
 ) -> Result<Vec<(Blake2bHash, Trie<K, V>)>, bytesrepr::Error>
 where
@@ -398,9 +400,12 @@ where
     V: ToBytes + Clone,
 {
     let mut ret: Vec<(Blake2bHash, Trie<K, V>)> = Vec::new();
-    let mut tip_hash = {
-        let node_bytes = tip.to_bytes()?;
-        Blake2bHash::new(&node_bytes)
+    let mut tip_hash = match maybe_tip_hash {
+        Some(tip_hash) => tip_hash,
+        None => {
+            let node_bytes = tip.to_bytes()?;
+            Blake2bHash::new(&node_bytes)
+        }
     };
     ret.push((tip_hash, tip.to_owned()));
 
@@ -643,6 +648,28 @@ pub fn write<K, V, T, S, E>(
     key: &K,
     value: &V,
 ) -> Result<WriteResult, E>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq,
+    T: Readable<Handle = S::Handle> + Writable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    write_with_leaf_hash::<K, V, T, S, E>(correlation_id, txn, store, root, key, value, None)
+}
+
+/// Like [`write`], but takes the hash of the new leaf if it has already been computed, e.g. by
+/// [`hash_tries`].
+pub fn write_with_leaf_hash<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &mut T,
+    store: &S,
+    root: &Blake2bHash,
+    key: &K,
+    value: &V,
+    leaf_hash: Option<Blake2bHash>,
+) -> Result<WriteResult, E>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq,
@@ -672,7 +699,9 @@ where
                 Trie::Leaf {
                     key: ref leaf_key,
                     value: ref leaf_value,
-                } if key == leaf_key && value != leaf_value => rehash(new_leaf, parents)?,
+                } if key == leaf_key && value != leaf_value => {
+                    rehash(new_leaf, leaf_hash, parents)?
+                }
                 // If the "tip" is an existing leaf with a different key than
                 // the new leaf, then we are in a situation where the new leaf
                 // shares some common prefix with the existing leaf.
@@ -683,7 +712,7 @@ where
                     let existing_leaf_path = existing_leaf_key.to_bytes()?;
                     let (new_node, parents) = reparent_leaf(&path, &existing_leaf_path, parents)?;
                     let parents = add_node_to_parents(&path, new_node, parents);
-                    rehash(new_leaf, parents)?
+                    rehash(new_leaf, leaf_hash, parents)?
                 }
                 // This case is unreachable, but the compiler can't figure
                 // that out.
@@ -692,7 +721,7 @@ where
                 // to the new leaf to the node's pointer block.
                 node @ Trie::Node { .. } => {
                     let parents = add_node_to_parents(&path, node, parents);
-                    rehash(new_leaf, parents)?
+                    rehash(new_leaf, leaf_hash, parents)?
                 }
                 // If the "tip" is an extension node, then we must modify or
                 // replace it, adding a node where necessary.
@@ -705,10 +734,10 @@ where
                     let parents = add_node_to_parents(&path, new_node, parents);
                     if let Some(hashed_extension) = maybe_hashed_child_extension {
                         let mut ret = vec![hashed_extension];
-                        ret.extend(rehash(new_leaf, parents)?);
+                        ret.extend(rehash(new_leaf, leaf_hash, parents)?);
                         ret
                     } else {
-                        rehash(new_leaf, parents)?
+                        rehash(new_leaf, leaf_hash, parents)?
                     }
                 }
             };
@@ -747,6 +776,25 @@ where
     Ok(trie_hash)
 }
 
+/// Computes the hashes of the given tries, spreading the work over `pool` if one is given.
+pub fn hash_tries<K, V>(
+    tries: &[Trie<K, V>],
+    pool: Option<&ThreadPool>,
+) -> Result<Vec<Blake2bHash>, bytesrepr::Error>
+where
+    K: ToBytes + Sync,
+    V: ToBytes + Sync,
+{
+    let hash_trie = |trie: &Trie<K, V>| -> Result<Blake2bHash, bytesrepr::Error> {
+        let trie_bytes = trie.to_bytes()?;
+        Ok(Blake2bHash::new(&trie_bytes))
+    };
+    match pool {
+        Some(pool) => pool.install(|| tries.par_iter().map(hash_trie).collect()),
+        None => tries.iter().map(hash_trie).collect(),
+    }
+}
+
 enum KeysIteratorState<K, V, S: TrieStore<K, V>> {
     /// Iterate normally
     Ok,
@@ -0,0 +1,149 @@
+//! A trie store which collects writes in memory and flushes them in a single batch.
+//!
+//! Writing a sequence of keys to a trie produces a new path of nodes for every write, most of
+//! which are superseded by a later write before the whole sequence is done.  Buffering the writes
+//! allows only the nodes which are still reachable from the final root to be persisted.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use rayon::{prelude::*, ThreadPool};
+
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    shared::newtypes::Blake2bHash,
+    storage::{
+        store::Store,
+        transaction_source::{Readable, Writable},
+        trie::Trie,
+        trie_store::TrieStore,
+    },
+};
+
+/// A [`TrieStore`] which buffers written tries in memory on top of an underlying store.
+///
+/// Reads see the buffered tries first.  Nothing reaches the underlying store until
+/// [`flush`](WriteBatchTrieStore::flush) is called.
+pub(crate) struct WriteBatchTrieStore<'a, K, V, S> {
+    store: &'a S,
+    cache: RefCell<HashMap<Blake2bHash, Trie<K, V>>>,
+}
+
+impl<'a, K, V, S> WriteBatchTrieStore<'a, K, V, S>
+where
+    K: ToBytes + Sync,
+    V: ToBytes + Sync,
+    S: TrieStore<K, V>,
+{
+    pub(crate) fn new(store: &'a S) -> Self {
+        WriteBatchTrieStore {
+            store,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Writes all buffered tries reachable from `root` to the underlying store, discarding the
+    /// rest.
+    ///
+    /// The tries are serialized in parallel on `pool` if one is given.
+    pub(crate) fn flush<T>(
+        self,
+        txn: &mut T,
+        root: &Blake2bHash,
+        pool: Option<&ThreadPool>,
+    ) -> Result<(), S::Error>
+    where
+        T: Writable<Handle = S::Handle>,
+        S::Error: From<T::Error>,
+    {
+        let WriteBatchTrieStore { store, cache } = self;
+        let mut cache = cache.into_inner();
+
+        // Tries which are not in the cache were already persisted, and so were their descendants.
+        let mut reachable = Vec::with_capacity(cache.len());
+        let mut pending = vec![*root];
+        while let Some(hash) = pending.pop() {
+            if let Some(trie) = cache.remove(&hash) {
+                match &trie {
+                    Trie::Leaf { .. } => (),
+                    Trie::Node { pointer_block } => pending.extend(
+                        pointer_block
+                            .to_indexed_pointers()
+                            .map(|(_, pointer)| pointer.into_hash()),
+                    ),
+                    Trie::Extension { pointer, .. } => pending.push(*pointer.hash()),
+                }
+                reachable.push((hash, trie));
+            }
+        }
+
+        let serialize = |(hash, trie): &(Blake2bHash, Trie<K, V>)| -> Result<
+            (Vec<u8>, Vec<u8>),
+            bytesrepr::Error,
+        > { Ok((hash.to_bytes()?, trie.to_bytes()?)) };
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = match pool {
+            Some(pool) => pool.install(|| {
+                reachable
+                    .par_iter()
+                    .map(serialize)
+                    .collect::<Result<_, bytesrepr::Error>>()
+            })?,
+            None => reachable
+                .iter()
+                .map(serialize)
+                .collect::<Result<_, bytesrepr::Error>>()?,
+        };
+
+        for (key_bytes, value_bytes) in entries {
+            txn.write(store.handle(), &key_bytes, &value_bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V, S> Store<Blake2bHash, Trie<K, V>> for WriteBatchTrieStore<'a, K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: TrieStore<K, V>,
+{
+    type Error = S::Error;
+
+    type Handle = S::Handle;
+
+    fn handle(&self) -> Self::Handle {
+        self.store.handle()
+    }
+
+    fn get<T>(&self, txn: &T, key: &Blake2bHash) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        if let Some(trie) = self.cache.borrow().get(key) {
+            return Ok(Some(trie.clone()));
+        }
+        self.store.get(txn, key)
+    }
+
+    fn put<T>(&self, _txn: &mut T, key: &Blake2bHash, value: &Trie<K, V>) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.cache.borrow_mut().insert(*key, value.clone());
+        Ok(())
+    }
+}
+
+impl<'a, K, V, S> TrieStore<K, V> for WriteBatchTrieStore<'a, K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: TrieStore<K, V>,
+{
+}
@@ -16,6 +16,7 @@ struct DeployItemData {
     pub payment_code: Option<ExecutableDeployItem>,
     pub session_code: Option<ExecutableDeployItem>,
     pub gas_price: u64,
+    pub priority_fee: u64,
     pub authorization_keys: BTreeSet<AccountHash>,
     pub deploy_hash: DeployHash,
 }
@@ -199,6 +200,11 @@ impl DeployItemBuilder {
         self
     }
 
+    pub fn with_priority_fee(mut self, priority_fee: u64) -> Self {
+        self.deploy_item.priority_fee = priority_fee;
+        self
+    }
+
     pub fn with_deploy_hash(mut self, hash: [u8; 32]) -> Self {
         let digest: Blake2bHash = hash.into();
         self.deploy_item.deploy_hash = DeployHash::new(digest.value());
@@ -220,6 +226,7 @@ impl DeployItemBuilder {
                 .payment_code
                 .expect("should have payment code"),
             gas_price: self.deploy_item.gas_price,
+            priority_fee: self.deploy_item.priority_fee,
             authorization_keys: self.deploy_item.authorization_keys,
             deploy_hash: self.deploy_item.deploy_hash,
         }
@@ -21,7 +21,8 @@ use casper_execution_engine::{
             execution_result::ExecutionResult,
             run_genesis_request::RunGenesisRequest,
             step::{StepRequest, StepResult},
-            BalanceResult, EngineConfig, EngineState, GenesisResult, GetBidsRequest, QueryRequest,
+            BalanceBatchRequest, BalanceBatchResult, BalanceResult, EngineConfig, EngineState,
+            GenesisResult, GetBidsRequest, QueryBatchRequest, QueryBatchResult, QueryRequest,
             QueryResult, UpgradeConfig, UpgradeResult, SYSTEM_ACCOUNT_ADDR,
         },
         execution,
@@ -727,6 +728,32 @@ where
             .expect("should get purse balance")
     }
 
+    pub fn query_batch_result(
+        &self,
+        queries: Vec<(Key, Vec<String>)>,
+        max_queries: usize,
+        max_response_size: usize,
+    ) -> QueryBatchResult {
+        let correlation_id = CorrelationId::new();
+        let state_root_hash: Blake2bHash =
+            self.post_state_hash.expect("should have post_state_hash");
+        let query_batch_request =
+            QueryBatchRequest::new(state_root_hash, queries, max_queries, max_response_size);
+        self.engine_state
+            .run_query_batch(correlation_id, query_batch_request)
+            .expect("should run query batch")
+    }
+
+    pub fn get_purse_balances_result(&self, keys: Vec<Key>) -> BalanceBatchResult {
+        let correlation_id = CorrelationId::new();
+        let state_root_hash: Blake2bHash =
+            self.post_state_hash.expect("should have post_state_hash");
+        let balance_batch_request = BalanceBatchRequest::new(state_root_hash, keys);
+        self.engine_state
+            .get_purse_balances(correlation_id, balance_batch_request)
+            .expect("should get purse balances")
+    }
+
     pub fn get_proposer_purse_balance(&self) -> U512 {
         let proposer_account = self
             .get_account(*DEFAULT_PROPOSER_ADDR)
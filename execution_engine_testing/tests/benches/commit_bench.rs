@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use lmdb::DatabaseFlags;
+use tempfile::TempDir;
+
+use casper_execution_engine::{
+    shared::{
+        additive_map::AdditiveMap, newtypes::CorrelationId, stored_value::StoredValue,
+        transform::Transform,
+    },
+    storage::{
+        global_state::{lmdb::LmdbGlobalState, CommitResult, StateProvider},
+        protocol_data_store::lmdb::LmdbProtocolDataStore,
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
+    },
+};
+use casper_types::{account::AccountHash, CLValue, Key};
+
+const MAP_SIZE: usize = 4 * 1024 * 1024 * 1024;
+const MAX_READERS: u32 = 512;
+const EFFECT_COUNTS: [u64; 3] = [100, 1_000, 10_000];
+const HASHING_THREADS: [usize; 3] = [1, 2, 4];
+
+fn make_global_state(data_dir: &TempDir, hashing_threads: usize) -> LmdbGlobalState {
+    let environment =
+        Arc::new(LmdbEnvironment::new(data_dir.path(), MAP_SIZE, MAX_READERS).unwrap());
+    let trie_store =
+        Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+    let protocol_data_store =
+        Arc::new(LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+    LmdbGlobalState::empty(environment, trie_store, protocol_data_store)
+        .unwrap()
+        .with_commit_hashing_threads(hashing_threads)
+        .unwrap()
+}
+
+fn make_effects(count: u64) -> AdditiveMap<Key, Transform> {
+    let mut effects = AdditiveMap::new();
+    for i in 0..count {
+        let mut account_hash = [0; 32];
+        account_hash[24..].copy_from_slice(&i.to_be_bytes());
+        let value = StoredValue::CLValue(CLValue::from_t(i).unwrap());
+        effects.insert(
+            Key::Account(AccountHash::new(account_hash)),
+            Transform::Write(value),
+        );
+    }
+    effects
+}
+
+fn commit_bench(c: &mut Criterion) {
+    let correlation_id = CorrelationId::new();
+    let mut group = c.benchmark_group("commit");
+
+    for &effect_count in EFFECT_COUNTS.iter() {
+        group.throughput(Throughput::Elements(effect_count));
+        for &hashing_threads in HASHING_THREADS.iter() {
+            let data_dir = TempDir::new().expect("should create temp dir");
+            let global_state = make_global_state(&data_dir, hashing_threads);
+            let prestate_hash = global_state.empty_root_hash;
+
+            group.bench_function(
+                format!("{}_effects_{}_threads", effect_count, hashing_threads),
+                |b| {
+                    b.iter_batched(
+                        || make_effects(effect_count),
+                        |effects| match global_state
+                            .commit(correlation_id, prestate_hash, effects)
+                            .unwrap()
+                        {
+                            CommitResult::Success { .. } => (),
+                            other => panic!("commit failed: {}", other),
+                        },
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, commit_bench);
+criterion_main!(benches);
@@ -0,0 +1,118 @@
+use ed25519_dalek::ExpandedSecretKey;
+use k256::ecdsa::{self, signature::RandomizedSigner};
+use rand::{rngs::StdRng, SeedableRng};
+
+use casper_engine_test_support::{
+    internal::{ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST},
+    DEFAULT_ACCOUNT_ADDR,
+};
+use casper_execution_engine::shared::gas::Gas;
+use casper_types::{
+    bytesrepr::ToBytes, runtime_args, PublicKey, RuntimeArgs, SecretKey, Signature,
+};
+
+const VERIFY_SIGNATURE_WASM: &str = "verify_signature.wasm";
+const ARG_MESSAGE: &str = "message";
+const ARG_SIGNATURE: &str = "signature";
+const ARG_PUBLIC_KEY: &str = "public_key";
+const VERIFICATION_RESULT: &str = "verification_result";
+
+const MESSAGE: &[u8] = b"price of CSPR at block 42";
+
+fn sign(secret_key: &SecretKey, message: &[u8]) -> Signature {
+    match secret_key {
+        SecretKey::Ed25519(secret_key) => {
+            let public_key = ed25519_dalek::PublicKey::from(secret_key);
+            let signature = ExpandedSecretKey::from(secret_key).sign(message, &public_key);
+            Signature::Ed25519(signature.to_bytes())
+        }
+        SecretKey::Secp256k1(secret_key) => {
+            let signer = ecdsa::Signer::new(secret_key).expect("should create signer");
+            let mut rng = StdRng::seed_from_u64(0);
+            Signature::Secp256k1(signer.sign_with_rng(&mut rng, message))
+        }
+        SecretKey::System => panic!("cannot sign with the system key"),
+    }
+}
+
+fn secret_keys() -> Vec<SecretKey> {
+    vec![SecretKey::ed25519([1; 32]), SecretKey::secp256k1([1; 32])]
+}
+
+/// Runs the verifying contract in a fresh builder, returning its result and the gas it cost.
+fn verify(message: &[u8], signature: &Signature, public_key: &PublicKey) -> (bool, Gas) {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        VERIFY_SIGNATURE_WASM,
+        runtime_args! {
+            ARG_MESSAGE => message.to_vec(),
+            ARG_SIGNATURE => signature.to_bytes().expect("should serialize signature"),
+            ARG_PUBLIC_KEY => public_key.clone(),
+        },
+    )
+    .build();
+    builder.exec(exec_request).commit().expect_success();
+
+    let account = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account");
+    let uref = account
+        .named_keys()
+        .get(VERIFICATION_RESULT)
+        .expect("should have value");
+    let is_valid = builder
+        .query(None, *uref, &[])
+        .expect("should query")
+        .as_cl_value()
+        .cloned()
+        .expect("should be CLValue")
+        .into_t()
+        .expect("should convert");
+    (is_valid, builder.last_exec_gas_cost())
+}
+
+#[ignore]
+#[test]
+fn should_verify_signatures() {
+    for secret_key in secret_keys() {
+        let public_key = PublicKey::from(&secret_key);
+        let signature = sign(&secret_key, MESSAGE);
+
+        let (is_valid, _) = verify(MESSAGE, &signature, &public_key);
+        assert!(is_valid, "{} should verify", signature);
+
+        let (is_valid, _) = verify(b"price of CSPR at block 43", &signature, &public_key);
+        assert!(!is_valid, "{} should not verify another message", signature);
+    }
+}
+
+#[ignore]
+#[test]
+fn should_reject_signature_of_other_algorithm() {
+    let ed25519_key = SecretKey::ed25519([1; 32]);
+    let secp256k1_key = SecretKey::secp256k1([1; 32]);
+    let signature = sign(&ed25519_key, MESSAGE);
+
+    let (is_valid, _) = verify(MESSAGE, &signature, &PublicKey::from(&secp256k1_key));
+    assert!(!is_valid);
+}
+
+#[ignore]
+#[test]
+fn should_verify_deterministically() {
+    for secret_key in secret_keys() {
+        let public_key = PublicKey::from(&secret_key);
+        let signature = sign(&secret_key, MESSAGE);
+
+        let first = verify(MESSAGE, &signature, &public_key);
+        let second = verify(MESSAGE, &signature, &public_key);
+        assert_eq!(first, second);
+
+        // The cost depends only on the sizes of the inputs, not on whether the signature is valid.
+        let (_, invalid_cost) = verify(&[0; MESSAGE.len()], &signature, &public_key);
+        assert_eq!(first.1, invalid_cost);
+    }
+}
@@ -0,0 +1,62 @@
+use casper_engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_KEY,
+        DEFAULT_PAYMENT, DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR, DEFAULT_ACCOUNT_INITIAL_BALANCE,
+};
+use casper_types::{runtime_args, RuntimeArgs, U512};
+
+const ARG_AMOUNT: &str = "amount";
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+
+fn run_with_priority_fee(priority_fee: u64) -> (U512, U512) {
+    let exec_request = {
+        let deploy = DeployItemBuilder::new()
+            .with_address(*DEFAULT_ACCOUNT_ADDR)
+            .with_session_code(DO_NOTHING_WASM, RuntimeArgs::default())
+            .with_empty_payment_bytes(runtime_args! {
+                ARG_AMOUNT => *DEFAULT_PAYMENT,
+            })
+            .with_priority_fee(priority_fee)
+            .with_authorization_keys(&[*DEFAULT_ACCOUNT_KEY])
+            .with_deploy_hash([1; 32])
+            .build();
+
+        ExecuteRequestBuilder::new().push_deploy(deploy).build()
+    };
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let proposer_reward_starting_balance = builder.get_proposer_purse_balance();
+
+    builder.exec(exec_request).expect_success().commit();
+
+    let transaction_fee = builder.get_proposer_purse_balance() - proposer_reward_starting_balance;
+    let default_account = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should get genesis account");
+    let account_balance = builder.get_purse_balance(default_account.main_purse());
+
+    (transaction_fee, account_balance)
+}
+
+#[ignore]
+#[test]
+fn should_pay_priority_fee_to_proposer_without_exceeding_payment() {
+    let initial_balance = U512::from(DEFAULT_ACCOUNT_INITIAL_BALANCE);
+
+    for priority_fee in &[0, 1_000, u64::MAX] {
+        let (transaction_fee, account_balance) = run_with_priority_fee(*priority_fee);
+        assert!(
+            transaction_fee <= *DEFAULT_PAYMENT,
+            "the proposer should never receive more than the payment"
+        );
+        assert_eq!(
+            initial_balance,
+            transaction_fee + account_balance,
+            "no net resources should be gained or lost post-distribution"
+        );
+    }
+}
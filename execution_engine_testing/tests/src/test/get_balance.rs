@@ -4,7 +4,14 @@ use casper_engine_test_support::{
     internal::{ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST},
     DEFAULT_ACCOUNT_ADDR,
 };
-use casper_execution_engine::{core, core::ValidationError, shared::newtypes::Blake2bHash};
+use casper_execution_engine::{
+    core,
+    core::{
+        engine_state::{BalanceBatchResult, Error},
+        ValidationError,
+    },
+    shared::newtypes::Blake2bHash,
+};
 use casper_types::{
     account::AccountHash, runtime_args, AccessRights, Key, PublicKey, RuntimeArgs, SecretKey, URef,
     U512,
@@ -104,3 +111,54 @@ fn get_balance_should_work() {
         Err(ValidationError::UnexpectedValue)
     );
 }
+
+#[ignore]
+#[test]
+fn get_balance_batch_should_work() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let transfer_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            TRANSFER_ARG_TARGET => *ALICE_ADDR,
+            TRANSFER_ARG_AMOUNT => *TRANSFER_AMOUNT_1,
+            TRANSFER_ARG_ID => <Option<u64>>::None,
+        },
+    )
+    .build();
+
+    builder.exec(transfer_request).commit().expect_success();
+
+    let alice_main_purse = builder
+        .get_account(*ALICE_ADDR)
+        .expect("should have Alice's account")
+        .main_purse();
+    let default_account_main_purse = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should have default account")
+        .main_purse();
+
+    let keys = vec![
+        Key::URef(alice_main_purse),
+        Key::Account(*DEFAULT_ACCOUNT_ADDR),
+        Key::Hash([1u8; 32]),
+    ];
+
+    let balances = match builder.get_purse_balances_result(keys.clone()) {
+        BalanceBatchResult::Success(balances) => balances,
+        BalanceBatchResult::RootNotFound => panic!("should find state root"),
+    };
+
+    assert_eq!(balances.len(), keys.len());
+    assert_eq!(balances[0].0, keys[0]);
+    assert_eq!(
+        balances[0].1.as_ref().expect("should read Alice's balance"),
+        &*TRANSFER_AMOUNT_1
+    );
+    assert_eq!(
+        balances[1].1.as_ref().expect("should read default balance"),
+        &builder.get_purse_balance(default_account_main_purse)
+    );
+    assert!(matches!(balances[2].1, Err(Error::InvalidKeyVariant)));
+}
@@ -18,10 +18,11 @@ use casper_execution_engine::{
         engine_state::{
             self,
             genesis::{GenesisAccount, GenesisValidator},
+            GetKeysByPrefixRequest, GetKeysByPrefixResult,
         },
         execution,
     },
-    shared::motes::Motes,
+    shared::{key_prefix::KeyPrefix, motes::Motes, newtypes::CorrelationId},
 };
 use casper_types::{
     self,
@@ -37,7 +38,7 @@ use casper_types::{
             UNBONDING_PURSES_KEY,
         },
     },
-    PublicKey, RuntimeArgs, SecretKey, U512,
+    Key, KeyTag, PublicKey, RuntimeArgs, SecretKey, U512,
 };
 
 const ARG_TARGET: &str = "target";
@@ -2637,3 +2638,67 @@ fn should_release_vfta_holder_stake() {
 
     assert_eq!(DELEGATOR_1_STAKE, total_unbonded);
 }
+
+#[ignore]
+#[test]
+fn should_page_through_bid_keys() {
+    let accounts = {
+        let mut tmp: Vec<GenesisAccount> = DEFAULT_ACCOUNTS.clone();
+        tmp.push(GenesisAccount::account(
+            *ACCOUNT_1_PK,
+            Motes::new(ACCOUNT_1_BALANCE.into()),
+            Some(GenesisValidator::new(
+                Motes::new(ACCOUNT_1_BOND.into()),
+                DelegationRate::zero(),
+            )),
+        ));
+        tmp.push(GenesisAccount::account(
+            *ACCOUNT_2_PK,
+            Motes::new(ACCOUNT_2_BALANCE.into()),
+            Some(GenesisValidator::new(
+                Motes::new(ACCOUNT_2_BOND.into()),
+                DelegationRate::zero(),
+            )),
+        ));
+        tmp
+    };
+
+    let run_genesis_request = utils::create_run_genesis_request(accounts);
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&run_genesis_request);
+
+    let bids = builder.get_bids();
+    let state_hash = builder.get_post_state_hash();
+
+    let mut bid_keys = Vec::new();
+    loop {
+        let request = GetKeysByPrefixRequest::new(
+            state_hash,
+            KeyPrefix::from(KeyTag::Bid),
+            bid_keys.len(),
+            1,
+        );
+        let result = builder
+            .get_engine_state()
+            .get_keys_by_prefix(CorrelationId::new(), request)
+            .expect("should get keys");
+        let (keys, has_more) = match result {
+            GetKeysByPrefixResult::Success { keys, has_more } => (keys, has_more),
+            GetKeysByPrefixResult::RootNotFound => panic!("should find root"),
+        };
+        assert!(keys.len() <= 1, "should respect the page size");
+        bid_keys.extend(keys);
+        if !has_more {
+            break;
+        }
+    }
+
+    assert_eq!(bid_keys.len(), bids.len());
+    let expected_keys: BTreeSet<Key> = bids
+        .values()
+        .map(|bid| Key::Bid(AccountHash::from(bid.validator_public_key())))
+        .collect();
+    assert_eq!(BTreeSet::from_iter(bid_keys), expected_keys);
+}
@@ -0,0 +1,80 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use casper_node::{
+    decode_message, encode_message,
+    protocol::Message,
+    types::{SharedBytes, Tag},
+    SmallNetworkMessage,
+};
+
+/// The size of the serialized item in the response, just below the maximum frame length.
+const ITEM_SIZE: usize = 8 * 1024 * 1024 - 64;
+
+/// An allocator counting the number of bytes allocated through it.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the number of bytes allocated while running `f`.
+fn bytes_allocated<T, F: FnOnce() -> T>(f: F) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    drop(f());
+    ALLOCATED.load(Ordering::Relaxed) - before
+}
+
+/// Returns a frame holding a `GetResponse` with a large serialized item.
+fn large_get_response_frame() -> Bytes {
+    let message = SmallNetworkMessage::Payload(Message::GetResponse {
+        tag: Tag::Block,
+        serialized_item: SharedBytes::from(vec![0xab; ITEM_SIZE]),
+    });
+    encode_message(&message).unwrap()
+}
+
+/// Decodes the frame from a plain byte slice, copying the serialized item.
+fn decode_copying(frame: &Bytes) -> SmallNetworkMessage<Message> {
+    rmp_serde::from_read_ref(&frame[..]).unwrap()
+}
+
+/// Decodes the frame as the small network does, sharing it with the serialized item.
+fn decode_shared(frame: &Bytes) -> SmallNetworkMessage<Message> {
+    decode_message(frame.clone()).unwrap()
+}
+
+fn decode_large_get_response(c: &mut Criterion) {
+    let frame = large_get_response_frame();
+    println!(
+        "decoding a frame of {} bytes allocates {} bytes when copying, {} bytes when shared",
+        frame.len(),
+        bytes_allocated(|| decode_copying(&frame)),
+        bytes_allocated(|| decode_shared(&frame)),
+    );
+
+    let mut group = c.benchmark_group("decode_large_get_response");
+    group.bench_function("copying", |b| b.iter(|| decode_copying(&frame)));
+    group.bench_function("shared", |b| b.iter(|| decode_shared(&frame)));
+    group.finish();
+}
+
+criterion_group!(benches, decode_large_get_response);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::SeedableRng;
+
+use casper_node::{
+    crypto::{self, SignatureVerifier},
+    NodeRng,
+};
+use casper_types::{PublicKey, Signature};
+
+const BATCH_SIZES: [usize; 3] = [10, 100, 1000];
+
+fn signed_messages(count: usize) -> Vec<(Vec<u8>, Signature, PublicKey)> {
+    let mut rng = NodeRng::seed_from_u64(0);
+    (0..count)
+        .map(|index| {
+            let (secret_key, public_key) = crypto::generate_ed25519_keypair();
+            let message = (index as u64).to_le_bytes().to_vec();
+            let signature = crypto::sign(&message, &secret_key, &public_key, &mut rng);
+            (message, signature, public_key)
+        })
+        .collect()
+}
+
+fn verify_finality_signatures(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_finality_signatures");
+    let verifier = SignatureVerifier::default();
+    for &batch_size in BATCH_SIZES.iter() {
+        let items = signed_messages(batch_size);
+        group.bench_with_input(
+            BenchmarkId::new("sequential", batch_size),
+            &items,
+            |b, items| {
+                b.iter(|| {
+                    items
+                        .iter()
+                        .map(|(message, signature, public_key)| {
+                            crypto::verify(message, signature, public_key)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("pooled", batch_size),
+            &items,
+            |b, items| {
+                b.iter(|| {
+                    verifier.verify_batch(items, |(message, signature, public_key)| {
+                        crypto::verify(message, signature, public_key)
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify_finality_signatures);
+criterion_main!(benches);
@@ -13,4 +13,10 @@ fn main() {
         "cargo:rustc-env=NODE_BUILD_PROFILE={}",
         env::var("PROFILE").unwrap()
     );
+
+    // Generate the gRPC server for the node query service.
+    tonic_build::configure()
+        .build_client(false)
+        .compile(&["proto/node_query.proto"], &["proto"])
+        .expect("should compile the node query protobuf definitions");
 }
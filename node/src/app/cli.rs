@@ -20,10 +20,11 @@ use tracing::{error, info, trace};
 use crate::config;
 use casper_node::{
     logging,
-    reactor::{initializer, joiner, validator, ReactorExit, Runner},
+    reactor::{initializer, joiner, storage_rpc, validator, ReactorExit, Runner},
     setup_signal_hooks,
     types::ExitCode,
     utils::WithDir,
+    PreflightReport,
 };
 use prometheus::Registry;
 
@@ -56,6 +57,50 @@ pub enum Cli {
         /// <SECTION>.<KEY>=<VALUE>.  For example, '-C=node.chainspec_config_path=chainspec.toml'
         config_ext: Vec<ConfigExt>,
     },
+    /// Check the environment the node would run in, without starting it.
+    ///
+    /// Validates the config, chainspec, secret keys, listening ports, free disk space, existing
+    /// database and clock drift, then prints a JSON report to stdout.  Exits with a non-zero code
+    /// if any check failed, so it can serve as a pre-start hook.
+    Preflight {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.
+        config_ext: Vec<ConfigExt>,
+    },
+    /// Serve the JSON-RPC read endpoints from an offline copy of a node's storage.
+    ///
+    /// Runs only the storage and the RPC server configured in the given configuration file, with
+    /// no networking or consensus, so that the copy can be queried with the same API as a live
+    /// node.
+    StorageRpc {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        /// Path to the directory holding the copy of the node's storage.
+        #[structopt(long)]
+        data_dir: PathBuf,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.
+        config_ext: Vec<ConfigExt>,
+    },
     /// Migrate modified values from the old config as required after an upgrade.
     MigrateConfig {
         /// Path to configuration file of previous version of node.
@@ -76,7 +121,7 @@ pub enum Cli {
     },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// Command line extension to be applied to TOML-based config file values.
 pub struct ConfigExt {
     section: String,
@@ -145,7 +190,7 @@ impl Cli {
                 // Setup UNIX signal hooks.
                 setup_signal_hooks();
 
-                let validator_config = Self::init(&config, config_ext)?;
+                let validator_config = Self::init(&config, config_ext.clone())?;
                 info!(version = %casper_node::VERSION_STRING.as_str(), "node starting up");
 
                 // We use a `ChaCha20Rng` for the production node. For one, we want to completely
@@ -191,13 +236,31 @@ impl Cli {
                 .await?;
                 match joiner_runner.run(&mut rng).await {
                     ReactorExit::ProcessShouldExit(ExitCode::Success) => return Ok(()),
-                    ReactorExit::ProcessShouldExit(exit_code) => process::exit(exit_code as i32),
+                    ReactorExit::ProcessShouldExit(exit_code) => {
+                        // Keep the progress made syncing the linear chain for the next start.
+                        if let Err(error) = joiner_runner.reactor().checkpoint_linear_chain_sync() {
+                            error!(%error, "failed to save linear chain sync state");
+                        }
+                        process::exit(exit_code as i32)
+                    }
                     ReactorExit::ProcessShouldContinue => info!("finished joining"),
                 }
 
-                let config = joiner_runner.into_inner().into_validator_config().await?;
-                let mut validator_runner =
-                    Runner::<validator::Reactor>::with_metrics(config, &mut rng, &registry).await?;
+                let validator_config = joiner_runner.into_inner().into_validator_config().await?;
+                let mut validator_runner = Runner::<validator::Reactor>::with_metrics(
+                    validator_config,
+                    &mut rng,
+                    &registry,
+                )
+                .await?;
+
+                // Allow the safe-to-change settings to be reloaded from the same sources.
+                validator_runner
+                    .reactor_mut()
+                    .set_config_loader(validator::ConfigLoader::new(move || {
+                        let (root, validator_config) = Self::load(&config, config_ext.clone())?;
+                        Ok(WithDir::new(root, validator_config))
+                    }));
 
                 match validator_runner.run(&mut rng).await {
                     ReactorExit::ProcessShouldExit(ExitCode::Success) => (),
@@ -209,6 +272,52 @@ impl Cli {
                     reactor_exit => error!("validator should not exit with {:?}", reactor_exit),
                 }
             }
+            Cli::Preflight { config, config_ext } => {
+                // Logging is left uninitialized, so that stdout only carries the report.
+                let report = match Self::load(&config, config_ext) {
+                    Ok((root, validator_config)) => {
+                        casper_node::run_preflight_checks(WithDir::new(root, validator_config))
+                    }
+                    Err(error) => PreflightReport::config_not_loaded(format!("{:#}", error)),
+                };
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if !report.passed {
+                    process::exit(1);
+                }
+            }
+            Cli::StorageRpc {
+                config,
+                data_dir,
+                config_ext,
+            } => {
+                // Setup UNIX signal hooks.
+                setup_signal_hooks();
+
+                let node_config = Self::init(&config, config_ext)?;
+                let data_dir = env::current_dir()?.join(data_dir);
+                info!(
+                    version = %casper_node::VERSION_STRING.as_str(),
+                    data_dir = %data_dir.display(),
+                    "storage RPC server starting up"
+                );
+
+                let mut rng = casper_node::new_rng();
+                let mut runner = Runner::<storage_rpc::Reactor>::new(
+                    storage_rpc::Config {
+                        node_config,
+                        data_dir,
+                    },
+                    &mut rng,
+                )
+                .await?;
+                match runner.run(&mut rng).await {
+                    ReactorExit::ProcessShouldExit(ExitCode::Success) => (),
+                    ReactorExit::ProcessShouldExit(exit_code) => process::exit(exit_code as i32),
+                    reactor_exit => {
+                        error!("storage RPC server should not exit with {:?}", reactor_exit)
+                    }
+                }
+            }
             Cli::MigrateConfig {
                 old_config,
                 new_config,
@@ -255,6 +364,19 @@ impl Cli {
         config: &Path,
         config_ext: Vec<ConfigExt>,
     ) -> anyhow::Result<WithDir<validator::Config>> {
+        let (root, validator_config) = Self::load(config, config_ext)?;
+        logging::init_with_config(&validator_config.logging)?;
+        casper_node::crash_report::install(&root, &validator_config.node);
+        trace!("{}", config::to_string(&validator_config)?);
+        Ok(WithDir::new(root, validator_config))
+    }
+
+    /// Parses the config file for the current version of casper-node, applying any command line
+    /// overrides.  Returns the parsed config along with the directory containing the config file.
+    fn load(
+        config: &Path,
+        config_ext: Vec<ConfigExt>,
+    ) -> anyhow::Result<(PathBuf, validator::Config)> {
         // Determine the parent directory of the configuration file, if any.
         // Otherwise, we default to `/`.
         let root = config
@@ -277,10 +399,11 @@ impl Cli {
         }
 
         // Create validator config, including any overridden values.
-        let validator_config: validator::Config = config_table.try_into()?;
-        logging::init_with_config(&validator_config.logging)?;
-        trace!("{}", config::to_string(&validator_config)?);
+        let validator_config: validator::Config = config_table
+            .try_into()
+            .with_context(|| format!("could not parse {}", config.display()))?;
+        validator_config.validate()?;
 
-        Ok(WithDir::new(root, validator_config))
+        Ok((root, validator_config))
     }
 }
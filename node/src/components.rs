@@ -3,14 +3,18 @@
 //! Components are the building blocks of the whole application, wired together inside a reactor.
 //! Each component has a unified interface, expressed by the `Component` trait.
 pub(crate) mod block_executor;
+pub(crate) mod block_exporter;
 pub(crate) mod block_proposer;
 pub(crate) mod block_validator;
 pub(crate) mod chainspec_loader;
+pub(crate) mod clock_monitor;
 #[cfg(test)]
 pub(crate) mod collector;
 pub(crate) mod consensus;
 pub mod contract_runtime;
 pub(crate) mod deploy_acceptor;
+pub(crate) mod deploy_watcher;
+pub(crate) mod disk_watchdog;
 pub(crate) mod event_stream_server;
 pub(crate) mod fetcher;
 pub(crate) mod gossiper;
@@ -29,6 +33,7 @@ pub(crate) mod network;
 pub(crate) mod networking_metrics;
 pub(crate) mod small_network;
 pub(crate) mod storage;
+pub(crate) mod webhook_notifier;
 
 use crate::{
     effect::{EffectBuilder, Effects},
@@ -1,9 +1,10 @@
 //! Block executor component.
+mod config;
 mod event;
 mod metrics;
 
 use std::{
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     convert::Infallible,
     fmt::Debug,
 };
@@ -13,7 +14,7 @@ use itertools::Itertools;
 use prometheus::Registry;
 use semver::Version;
 use smallvec::SmallVec;
-use tracing::{debug, error, trace};
+use tracing::{debug, error, info, trace, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
@@ -22,6 +23,7 @@ use casper_execution_engine::{
         execution_result::{ExecutionResult as EngineExecutionResult, ExecutionResults},
         step::{EvictItem, RewardItem, SlashItem, StepRequest, StepResult},
     },
+    shared::newtypes::Blake2bHash,
     storage::global_state::CommitResult,
 };
 use casper_types::{ExecutionResult, ProtocolVersion, PublicKey, U512};
@@ -29,6 +31,8 @@ use casper_types::{ExecutionResult, ProtocolVersion, PublicKey, U512};
 use crate::{
     components::{
         block_executor::{event::State, metrics::BlockExecutorMetrics},
+        consensus::EraId,
+        contract_runtime::{ShadowExecutionError, ShadowExecutionRequest},
         Component,
     },
     crypto::hash::Digest,
@@ -41,11 +45,12 @@ use crate::{
         EffectBuilder, EffectExt, Effects,
     },
     types::{
-        Block, BlockHash, BlockHeader, BlockLike, Deploy, DeployHash, DeployHeader, FinalizedBlock,
-        NodeId,
+        Block, BlockHash, BlockHeader, BlockLike, ChecksumRegistry, Deploy, DeployHash,
+        DeployHeader, FinalizedBlock, NodeId, Timestamp,
     },
     NodeRng,
 };
+pub use config::Config;
 pub(crate) use event::Event;
 
 /// A helper trait whose bounds represent the requirements for a reactor event that `BlockExecutor`
@@ -112,6 +117,7 @@ type BlockHeight = u64;
 /// The Block executor component.
 #[derive(DataSize, Debug, Default)]
 pub(crate) struct BlockExecutor {
+    config: Config,
     initial_state: InitialState,
     protocol_version: ProtocolVersion,
     /// A mapping from proto block to executed block's ID and post-state hash, to allow
@@ -123,6 +129,12 @@ pub(crate) struct BlockExecutor {
     parent_map: HashMap<BlockHeight, ExecutedBlockSummary>,
     /// Finalized blocks waiting for their pre-state hash to start executing.
     exec_queue: HashMap<BlockHeight, (FinalizedBlock, VecDeque<Deploy>)>,
+    /// Heights of the blocks requested to be executed which have not been executed yet.
+    pending_heights: BTreeSet<BlockHeight>,
+    /// Whether blocks are additionally executed by the contract runtime's shadow engine.
+    shadow_execution: bool,
+    /// The first era whose blocks write a checksum registry to global state.
+    checksum_registry_activation_era: EraId,
     /// Metrics to track current chain height.
     #[data_size(skip)]
     metrics: BlockExecutorMetrics,
@@ -130,13 +142,16 @@ pub(crate) struct BlockExecutor {
 
 impl BlockExecutor {
     pub(crate) fn new(
+        config: Config,
         initial_state_root_hash: Digest,
         initial_block_header: Option<&BlockHeader>,
         protocol_version: Version,
+        checksum_registry_activation_era: EraId,
         registry: Registry,
     ) -> Self {
         let metrics = BlockExecutorMetrics::new(registry).unwrap();
         BlockExecutor {
+            config,
             initial_state: InitialState::new(initial_state_root_hash, initial_block_header),
             protocol_version: ProtocolVersion::from_parts(
                 protocol_version.major as u32,
@@ -145,10 +160,23 @@ impl BlockExecutor {
             ),
             parent_map: HashMap::new(),
             exec_queue: HashMap::new(),
+            pending_heights: BTreeSet::new(),
+            shadow_execution: false,
+            checksum_registry_activation_era,
             metrics,
         }
     }
 
+    /// Enables or disables shadow execution.
+    ///
+    /// With shadow execution enabled, every executed block is executed again by the contract
+    /// runtime's shadow engine, and the resulting state root hash is compared with the actual one.
+    /// The outcome is only logged and never affects the created block.
+    pub(crate) fn with_shadow_execution(mut self, shadow_execution: bool) -> Self {
+        self.shadow_execution = shadow_execution;
+        self
+    }
+
     /// Adds the "parent map" to the instance of `BlockExecutor`.
     ///
     /// When transitioning from `joiner` to `validator` states we need
@@ -172,6 +200,42 @@ impl BlockExecutor {
         self
     }
 
+    /// Returns whether the number of blocks awaiting execution reached the configured maximum.
+    fn is_queue_full(&self) -> bool {
+        self.pending_heights.len() as u64 >= self.config.max_queue_depth
+    }
+
+    /// Records a block as awaiting execution.
+    fn enqueue(&mut self, height: BlockHeight) {
+        let was_full = self.is_queue_full();
+        self.pending_heights.insert(height);
+        self.metrics
+            .exec_queue_depth
+            .set(self.pending_heights.len() as i64);
+        if !was_full && self.is_queue_full() {
+            warn!(
+                depth = self.pending_heights.len(),
+                lowest_pending_height = ?self.pending_heights.iter().next(),
+                "execution is falling behind, the execution queue is full"
+            );
+        }
+    }
+
+    /// Records a block as executed.
+    fn dequeue(&mut self, height: BlockHeight) {
+        let was_full = self.is_queue_full();
+        self.pending_heights.remove(&height);
+        self.metrics
+            .exec_queue_depth
+            .set(self.pending_heights.len() as i64);
+        if was_full && !self.is_queue_full() {
+            info!(
+                depth = self.pending_heights.len(),
+                "execution caught up, the execution queue is no longer full"
+            );
+        }
+    }
+
     /// Gets the deploy(s) of the given finalized block from storage.
     fn get_deploys<REv: ReactorEventT>(
         &mut self,
@@ -220,12 +284,15 @@ impl BlockExecutor {
     fn finalize_block_execution<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
-        state: Box<State>,
+        mut state: Box<State>,
         next_era_validator_weights: Option<BTreeMap<PublicKey, U512>>,
     ) -> Effects<Event> {
+        let mut effects = self.shadow_execute(effect_builder, &mut state);
         // The state hash of the last execute-commit cycle is used as the block's post state
         // hash.
         let next_height = state.finalized_block.height() + 1;
+        let execution_duration = Timestamp::now().saturating_diff(state.execution_started);
+        self.dequeue(state.finalized_block.height());
         // Update the metric.
         self.metrics
             .chain_height
@@ -236,9 +303,11 @@ impl BlockExecutor {
             next_era_validator_weights,
         );
 
-        let mut effects = effect_builder
-            .announce_linear_chain_block(block, state.execution_results)
-            .ignore();
+        effects.extend(
+            effect_builder
+                .announce_linear_chain_block(block, state.execution_results, execution_duration)
+                .ignore(),
+        );
         // If the child is already finalized, start execution.
         if let Some((finalized_block, deploys)) = self.exec_queue.remove(&next_height) {
             effects.extend(self.handle_get_deploys_result(
@@ -260,37 +329,12 @@ impl BlockExecutor {
         let next_deploy = match state.remaining_deploys.pop_front() {
             Some(deploy) => deploy,
             None => {
-                let era_end = match state.finalized_block.era_report() {
-                    Some(era_end) => era_end,
+                let request = match self.step_request(&state.finalized_block, state.state_root_hash)
+                {
+                    Some(request) => request,
                     // Not at a switch block, so we don't need to have next_era_validators when
                     // constructing the next block
-                    None => return self.finalize_block_execution(effect_builder, state, None),
-                };
-                let reward_items = era_end
-                    .rewards
-                    .iter()
-                    .map(|(&vid, &value)| RewardItem::new(vid, value))
-                    .collect();
-                let slash_items = era_end
-                    .equivocators
-                    .iter()
-                    .map(|&vid| SlashItem::new(vid))
-                    .collect();
-                let evict_items = era_end
-                    .inactive_validators
-                    .iter()
-                    .map(|&vid| EvictItem::new(vid))
-                    .collect();
-                let era_end_timestamp_millis = state.finalized_block.timestamp().millis();
-                let request = StepRequest {
-                    pre_state_hash: state.state_root_hash.into(),
-                    protocol_version: self.protocol_version,
-                    reward_items,
-                    slash_items,
-                    evict_items,
-                    run_auction: true,
-                    next_era_id: state.finalized_block.era_id().successor().into(),
-                    era_end_timestamp_millis,
+                    None => return self.commit_checksum_registry(effect_builder, state, None),
                 };
                 return effect_builder
                     .run_step(request)
@@ -324,6 +368,175 @@ impl BlockExecutor {
             })
     }
 
+    /// Writes the checksum registry of the executed block to global state, or creates the block
+    /// right away if the block is from before the registry's activation.
+    fn commit_checksum_registry<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state: Box<State>,
+        next_era_validator_weights: Option<BTreeMap<PublicKey, U512>>,
+    ) -> Effects<Event> {
+        let registry = match &state.checksum_registry_deploy_hashes {
+            Some(deploy_hashes) => ChecksumRegistry::new(deploy_hashes.iter().map(|deploy_hash| {
+                let (_, execution_result) = &state.execution_results[deploy_hash];
+                (deploy_hash, execution_result)
+            })),
+            None => {
+                return self.finalize_block_execution(
+                    effect_builder,
+                    state,
+                    next_era_validator_weights,
+                )
+            }
+        };
+        debug!(
+            height = state.finalized_block.height(),
+            execution_results_checksum = %registry.execution_results(),
+            "committing checksum registry"
+        );
+        effect_builder
+            .request_commit(state.state_root_hash, registry.effects())
+            .event(|commit_result| Event::CommitChecksumRegistry {
+                state,
+                next_era_validator_weights,
+                commit_result,
+            })
+    }
+
+    /// Creates the request to run the end of era step on `pre_state_hash`, or `None` if
+    /// `finalized_block` is not a switch block.
+    fn step_request(
+        &self,
+        finalized_block: &FinalizedBlock,
+        pre_state_hash: Digest,
+    ) -> Option<StepRequest> {
+        let era_end = finalized_block.era_report()?;
+        let reward_items = era_end
+            .rewards
+            .iter()
+            .map(|(&vid, &value)| RewardItem::new(vid, value))
+            .collect();
+        let slash_items = era_end
+            .equivocators
+            .iter()
+            .map(|&vid| SlashItem::new(vid))
+            .collect();
+        let evict_items = era_end
+            .inactive_validators
+            .iter()
+            .map(|&vid| EvictItem::new(vid))
+            .collect();
+        let era_end_timestamp_millis = finalized_block.timestamp().millis();
+        Some(StepRequest {
+            pre_state_hash: pre_state_hash.into(),
+            protocol_version: self.protocol_version,
+            reward_items,
+            slash_items,
+            evict_items,
+            run_auction: true,
+            next_era_id: finalized_block.era_id().successor().into(),
+            era_end_timestamp_millis,
+        })
+    }
+
+    /// Creates the state of the execute-commit cycle of `finalized_block` on `state_root_hash`
+    /// and starts executing its deploys.
+    fn start_execution<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        finalized_block: FinalizedBlock,
+        deploys: VecDeque<Deploy>,
+        state_root_hash: Digest,
+    ) -> Effects<Event> {
+        let shadow_deploy_items = if self.shadow_execution {
+            Some(deploys.iter().cloned().map(DeployItem::from).collect())
+        } else {
+            None
+        };
+        let checksum_registry_deploy_hashes =
+            if finalized_block.era_id() >= self.checksum_registry_activation_era {
+                Some(deploys.iter().map(|deploy| *deploy.id()).collect())
+            } else {
+                // Blocks before the activation era were committed without a registry, and must be
+                // re-executed the same way.
+                None
+            };
+        let state = Box::new(State {
+            finalized_block,
+            remaining_deploys: deploys,
+            execution_results: HashMap::new(),
+            state_root_hash,
+            pre_state_hash: state_root_hash,
+            shadow_deploy_items,
+            checksum_registry_deploy_hashes,
+            execution_started: Timestamp::now(),
+        });
+        self.execute_next_deploy_or_create_block(effect_builder, state)
+    }
+
+    /// Requests the shadow engine to execute the block of `state`, if shadow execution is enabled.
+    ///
+    /// Must be called once all of the block's deploys were executed.
+    fn shadow_execute<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        state: &mut State,
+    ) -> Effects<Event> {
+        let deploy_items = match state.shadow_deploy_items.take() {
+            Some(deploy_items) => deploy_items,
+            None => return Effects::new(),
+        };
+        let finalized_block = &state.finalized_block;
+        let request = ShadowExecutionRequest {
+            pre_state_hash: state.pre_state_hash.into(),
+            block_time: finalized_block.timestamp().millis(),
+            proposer: finalized_block.proposer(),
+            protocol_version: self.protocol_version,
+            deploy_items,
+            step_request: self.step_request(finalized_block, state.pre_state_hash),
+            checksum_registry_deploy_hashes: state.checksum_registry_deploy_hashes.clone(),
+        };
+        let height = finalized_block.height();
+        let state_root_hash = state.state_root_hash;
+        effect_builder
+            .shadow_execute_block(request)
+            .event(move |result| Event::ShadowExecutionResult {
+                height,
+                state_root_hash,
+                result,
+            })
+    }
+
+    /// Compares the state root hash resulting from shadow execution with the actual one.
+    fn handle_shadow_execution_result(
+        &self,
+        height: BlockHeight,
+        state_root_hash: Digest,
+        result: Option<Result<Blake2bHash, ShadowExecutionError>>,
+    ) {
+        match result {
+            Some(Ok(shadow_state_root_hash)) => {
+                let shadow_state_root_hash = Digest::from(shadow_state_root_hash);
+                if shadow_state_root_hash == state_root_hash {
+                    debug!(%height, %state_root_hash, "shadow execution matches");
+                } else {
+                    self.metrics.shadow_mismatches.inc();
+                    warn!(
+                        %height,
+                        %state_root_hash,
+                        %shadow_state_root_hash,
+                        "shadow execution resulted in a different state root hash"
+                    );
+                }
+            }
+            Some(Err(error)) => {
+                self.metrics.shadow_failures.inc();
+                warn!(%height, %error, "shadow execution failed");
+            }
+            None => debug!(%height, "shadow execution is not enabled in the contract runtime"),
+        }
+    }
+
     fn handle_get_deploys_result<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -331,13 +544,7 @@ impl BlockExecutor {
         deploys: VecDeque<Deploy>,
     ) -> Effects<Event> {
         if let Some(state_root_hash) = self.pre_state_hash(&finalized_block) {
-            let state = Box::new(State {
-                finalized_block,
-                remaining_deploys: deploys,
-                execution_results: HashMap::new(),
-                state_root_hash,
-            });
-            self.execute_next_deploy_or_create_block(effect_builder, state)
+            self.start_execution(effect_builder, finalized_block, deploys, state_root_hash)
         } else {
             // Didn't find parent in the `parent_map` cache.
             // Read it from the storage.
@@ -371,13 +578,7 @@ impl BlockExecutor {
                 debug!("no pre-state hash for height {}", height);
                 // re-check the parent map - the parent might have been executed in the meantime!
                 if let Some(state_root_hash) = self.pre_state_hash(&finalized_block) {
-                    let state = Box::new(State {
-                        finalized_block,
-                        remaining_deploys: deploys,
-                        execution_results: HashMap::new(),
-                        state_root_hash,
-                    });
-                    self.execute_next_deploy_or_create_block(effect_builder, state)
+                    self.start_execution(effect_builder, finalized_block, deploys, state_root_hash)
                 } else {
                     // The parent block has not been executed yet; delay handling.
                     self.exec_queue.insert(height, (finalized_block, deploys));
@@ -535,7 +736,13 @@ impl<REv: ReactorEventT> Component<REv> for BlockExecutor {
             }
             // If we haven't executed the block before in the past (for example during
             // joining), do it now.
-            Event::BlockIsNew(finalized_block) => self.get_deploys(effect_builder, finalized_block),
+            Event::BlockIsNew(finalized_block) => {
+                self.enqueue(finalized_block.height());
+                self.get_deploys(effect_builder, finalized_block)
+            }
+            Event::Request(BlockExecutorRequest::IsQueueFull(responder)) => {
+                responder.respond(self.is_queue_full()).ignore()
+            }
             Event::GetDeploysResult {
                 finalized_block,
                 deploys,
@@ -615,7 +822,7 @@ impl<REv: ReactorEventT> Component<REv> for BlockExecutor {
                         next_era_validators,
                     }) => {
                         state.state_root_hash = post_state_hash.into();
-                        self.finalize_block_execution(
+                        self.commit_checksum_registry(
                             effect_builder,
                             state,
                             Some(next_era_validators),
@@ -628,6 +835,58 @@ impl<REv: ReactorEventT> Component<REv> for BlockExecutor {
                     }
                 }
             }
+
+            Event::CommitChecksumRegistry {
+                mut state,
+                next_era_validator_weights,
+                commit_result,
+            } => match commit_result {
+                Ok(CommitResult::Success { state_root }) => {
+                    debug!(?state_root, "checksum registry commit succeeded");
+                    state.state_root_hash = state_root.into();
+                    self.finalize_block_execution(effect_builder, state, next_era_validator_weights)
+                }
+                _ => {
+                    // Without the registry, the block's state root hash would differ from the
+                    // other nodes'.
+                    error!(
+                        ?commit_result,
+                        "checksum registry commit failed - internal contract runtime error"
+                    );
+                    panic!("unable to commit checksum registry");
+                }
+            },
+
+            Event::ShadowExecutionResult {
+                height,
+                state_root_hash,
+                result,
+            } => {
+                self.handle_shadow_execution_result(height, state_root_hash, result);
+                Effects::new()
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_full_queue_until_blocks_are_executed() {
+        let mut block_executor = BlockExecutor {
+            config: Config { max_queue_depth: 2 },
+            ..Default::default()
+        };
+        block_executor.enqueue(1);
+        assert!(!block_executor.is_queue_full());
+        block_executor.enqueue(2);
+        assert!(block_executor.is_queue_full());
+        assert_eq!(block_executor.metrics.exec_queue_depth.get(), 2);
+
+        block_executor.dequeue(1);
+        assert!(!block_executor.is_queue_full());
+        assert_eq!(block_executor.metrics.exec_queue_depth.get(), 1);
+    }
+}
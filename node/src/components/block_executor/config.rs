@@ -0,0 +1,33 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::ConfigSection;
+
+const DEFAULT_MAX_QUEUE_DEPTH: u64 = 10;
+
+/// Configuration options for block execution.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The number of blocks awaiting execution at which the execution queue is considered full.
+    ///
+    /// While the queue is full, the node warns that it is falling behind and the linear chain
+    /// synchronizer holds back further blocks.
+    pub max_queue_depth: u64,
+}
+
+impl Config {
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("max_queue_depth", self.max_queue_depth, 1);
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_queue_depth: DEFAULT_MAX_QUEUE_DEPTH,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     fmt::Display,
 };
 
@@ -8,16 +8,18 @@ use derive_more::From;
 use casper_execution_engine::{
     core::{
         engine_state,
-        engine_state::{step::StepResult, ExecutionResults, RootNotFound},
+        engine_state::{deploy_item::DeployItem, step::StepResult, ExecutionResults, RootNotFound},
     },
+    shared::newtypes::Blake2bHash,
     storage::global_state::CommitResult,
 };
-use casper_types::ExecutionResult;
+use casper_types::{ExecutionResult, PublicKey, U512};
 
 use crate::{
+    components::contract_runtime::ShadowExecutionError,
     crypto::hash::Digest,
     effect::requests::BlockExecutorRequest,
-    types::{Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalizedBlock},
+    types::{Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalizedBlock, Timestamp},
 };
 
 /// Block executor component event.
@@ -71,6 +73,24 @@ pub enum Event {
         /// The result.
         result: Result<StepResult, engine_state::Error>,
     },
+    /// The result of committing the checksum registry after executing a block.
+    CommitChecksumRegistry {
+        /// State of this request.
+        state: Box<State>,
+        /// The validator weights of the next era, if the block is a switch block.
+        next_era_validator_weights: Option<BTreeMap<PublicKey, U512>>,
+        /// Commit result for the registry.
+        commit_result: Result<CommitResult, engine_state::Error>,
+    },
+    /// The result of executing a block with the shadow engine.
+    ShadowExecutionResult {
+        /// The height of the block.
+        height: u64,
+        /// The actual state root hash resulting from executing the block.
+        state_root_hash: Digest,
+        /// The state root hash resulting from shadow execution, if enabled.
+        result: Option<Result<Blake2bHash, ShadowExecutionError>>,
+    },
 }
 
 impl Display for Event {
@@ -152,10 +172,27 @@ impl Display for Event {
                 state.state_root_hash,
                 result
             ),
+            Event::CommitChecksumRegistry {
+                state,
+                commit_result,
+                ..
+            } => write!(
+                f,
+                "commit checksum registry of finalized block with height {} with \
+                pre-state hash {}: {:?}",
+                state.finalized_block.height(),
+                state.state_root_hash,
+                commit_result,
+            ),
             Event::BlockAlreadyExists(block) => {
                 write!(f, "Block at height {} was executed before", block.height())
             }
             Event::BlockIsNew(fb) => write!(f, "Block at height {} is new", fb.height(),),
+            Event::ShadowExecutionResult { height, result, .. } => write!(
+                f,
+                "shadow execution result for block at height {}: {:?}",
+                height, result
+            ),
         }
     }
 }
@@ -171,4 +208,13 @@ pub struct State {
     /// Current state root hash of global storage.  Is initialized with the parent block's
     /// state hash, and is updated after each commit.
     pub state_root_hash: Digest,
+    /// The parent block's state hash, which the block is executed on.
+    pub pre_state_hash: Digest,
+    /// The block's deploys to be executed by the shadow engine, if shadow execution is enabled.
+    pub shadow_deploy_items: Option<Vec<DeployItem>>,
+    /// The hashes of the block's deploys, in execution order, if the block writes a checksum
+    /// registry.
+    pub checksum_registry_deploy_hashes: Option<Vec<DeployHash>>,
+    /// When execution of the block started.
+    pub execution_started: Timestamp,
 }
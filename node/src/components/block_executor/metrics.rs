@@ -1,4 +1,4 @@
-use prometheus::{IntGauge, Registry};
+use prometheus::{IntCounter, IntGauge, Registry};
 
 use crate::unregister_metric;
 
@@ -6,6 +6,12 @@ use crate::unregister_metric;
 pub(super) struct BlockExecutorMetrics {
     /// The current chain height.
     pub(super) chain_height: IntGauge,
+    /// The number of blocks awaiting execution.
+    pub(super) exec_queue_depth: IntGauge,
+    /// The number of blocks for which shadow execution resulted in a different state root hash.
+    pub(super) shadow_mismatches: IntCounter,
+    /// The number of blocks for which shadow execution failed.
+    pub(super) shadow_failures: IntCounter,
     /// registry component.
     registry: Registry,
 }
@@ -13,9 +19,27 @@ pub(super) struct BlockExecutorMetrics {
 impl BlockExecutorMetrics {
     pub(super) fn new(registry: Registry) -> Result<Self, prometheus::Error> {
         let chain_height = IntGauge::new("chain_height", "current chain height")?;
+        let exec_queue_depth = IntGauge::new(
+            "exec_queue_depth",
+            "number of finalized blocks awaiting execution",
+        )?;
+        let shadow_mismatches = IntCounter::new(
+            "shadow_execution_mismatches",
+            "number of blocks for which shadow execution resulted in a different state root hash",
+        )?;
+        let shadow_failures = IntCounter::new(
+            "shadow_execution_failures",
+            "number of blocks for which shadow execution failed",
+        )?;
         registry.register(Box::new(chain_height.clone()))?;
+        registry.register(Box::new(exec_queue_depth.clone()))?;
+        registry.register(Box::new(shadow_mismatches.clone()))?;
+        registry.register(Box::new(shadow_failures.clone()))?;
         Ok(BlockExecutorMetrics {
             chain_height,
+            exec_queue_depth,
+            shadow_mismatches,
+            shadow_failures,
             registry,
         })
     }
@@ -24,6 +48,9 @@ impl BlockExecutorMetrics {
 impl Drop for BlockExecutorMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.chain_height);
+        unregister_metric!(self.registry, self.exec_queue_depth);
+        unregister_metric!(self.registry, self.shadow_mismatches);
+        unregister_metric!(self.registry, self.shadow_failures);
     }
 }
 
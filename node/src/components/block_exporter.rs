@@ -0,0 +1,350 @@
+//! Block exporter.
+//!
+//! Writes every executed block, together with its deploys and their execution results, as a line
+//! of JSON to files in the configured folder, so that the chain can be fed into other systems
+//! without querying the RPC server for each block.  A new file, named after the height of its
+//! first block, is started whenever the current one exceeds the configured size.
+//!
+//! The files are written by a dedicated thread.  Blocks are handed to it through a bounded queue,
+//! and skipped with a warning while the queue is full, so that a slow disk never delays
+//! execution.  Finality signatures are not exported, as they are usually collected only after a
+//! block was executed.
+
+mod config;
+mod metrics;
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread,
+};
+
+use datasize::DataSize;
+use prometheus::{IntCounter, Registry};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use casper_types::ExecutionResult;
+
+use crate::{
+    components::Component,
+    effect::{requests::StorageRequest, EffectBuilder, EffectExt, Effects, Multiple},
+    types::{Block, BlockSignatures, Deploy, DeployHash, JsonBlock},
+    utils::WithDir,
+    NodeRng,
+};
+pub use config::Config;
+use metrics::BlockExporterMetrics;
+
+/// Error creating the block exporter.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The export folder could not be created.
+    #[error("failed to create block export folder {}: {error}", path.display())]
+    CreateDir {
+        /// The export folder.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// The writer thread could not be started.
+    #[error("failed to start block export thread: {0}")]
+    SpawnThread(io::Error),
+    /// Metrics-related error.
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
+/// Block exporter events.
+#[derive(Debug)]
+pub enum Event {
+    /// A block was executed.
+    BlockExecuted {
+        block: Box<Block>,
+        execution_results: HashMap<DeployHash, ExecutionResult>,
+    },
+    /// The deploys of an executed block were loaded from storage.
+    DeploysLoaded {
+        block: Box<Block>,
+        execution_results: HashMap<DeployHash, ExecutionResult>,
+        deploys: Vec<Option<Deploy>>,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::BlockExecuted { block, .. } => {
+                write!(formatter, "executed block {}", block.hash())
+            }
+            Event::DeploysLoaded { block, .. } => {
+                write!(formatter, "loaded deploys of block {}", block.hash())
+            }
+        }
+    }
+}
+
+/// The execution result of a single deploy, as exported.
+#[derive(Debug, Serialize)]
+struct ExportedExecutionResult {
+    deploy_hash: DeployHash,
+    result: ExecutionResult,
+}
+
+/// An executed block, as exported.
+#[derive(Debug, Serialize)]
+struct ExportedBlock {
+    block: JsonBlock,
+    deploys: Vec<Deploy>,
+    execution_results: Vec<ExportedExecutionResult>,
+}
+
+/// Block exporter component.
+#[derive(DataSize, Debug)]
+pub(crate) struct BlockExporter {
+    /// The queue of blocks to be written, `None` if exporting is disabled.
+    #[data_size(skip)]
+    sender: Option<SyncSender<(u64, ExportedBlock)>>,
+    #[data_size(skip)]
+    metrics: BlockExporterMetrics,
+}
+
+impl BlockExporter {
+    /// Creates a new block exporter, starting the writer thread if exporting is enabled.
+    pub(crate) fn new(config: WithDir<Config>, registry: &Registry) -> Result<Self, Error> {
+        let metrics = BlockExporterMetrics::new(registry)?;
+        let config_value = config.value();
+        if !config_value.enabled {
+            return Ok(BlockExporter {
+                sender: None,
+                metrics,
+            });
+        }
+
+        let path = config.with_dir(config_value.path.clone());
+        fs::create_dir_all(&path).map_err(|error| Error::CreateDir {
+            path: path.clone(),
+            error,
+        })?;
+        info!(path = %path.display(), "exporting executed blocks");
+
+        let (sender, receiver) = mpsc::sync_channel(config_value.queue_size);
+        let files = ExportFiles::new(path, config_value.max_file_size);
+        let exported_blocks = metrics.exported_blocks.clone();
+        let failed_blocks = metrics.failed_blocks.clone();
+        thread::Builder::new()
+            .name("block-export".to_string())
+            .spawn(move || write_blocks(receiver, files, exported_blocks, failed_blocks))
+            .map_err(Error::SpawnThread)?;
+
+        Ok(BlockExporter {
+            sender: Some(sender),
+            metrics,
+        })
+    }
+
+    /// Returns whether executed blocks are exported.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Queues a block with its deploys and execution results to be written.
+    fn export(
+        &mut self,
+        block: Block,
+        mut execution_results: HashMap<DeployHash, ExecutionResult>,
+        deploys: Vec<Option<Deploy>>,
+    ) {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return,
+        };
+        let height = block.height();
+
+        let mut exported_deploys = Vec::with_capacity(deploys.len());
+        let mut exported_results = Vec::with_capacity(deploys.len());
+        let deploy_hashes = block.deploy_hashes().iter().chain(block.transfer_hashes());
+        for (deploy_hash, maybe_deploy) in deploy_hashes.zip(deploys) {
+            match maybe_deploy {
+                Some(deploy) => exported_deploys.push(deploy),
+                None => warn!(%deploy_hash, %height, "deploy of executed block not in storage"),
+            }
+            if let Some(result) = execution_results.remove(deploy_hash) {
+                exported_results.push(ExportedExecutionResult {
+                    deploy_hash: *deploy_hash,
+                    result,
+                });
+            }
+        }
+
+        let signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+        let exported_block = ExportedBlock {
+            block: JsonBlock::new(block, signatures),
+            deploys: exported_deploys,
+            execution_results: exported_results,
+        };
+        match sender.try_send((height, exported_block)) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                self.metrics.skipped_blocks.inc();
+                warn!(%height, "block export queue full, skipping block");
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                error!(%height, "block export thread stopped, no longer exporting blocks");
+                self.sender = None;
+            }
+        }
+    }
+}
+
+/// Writes the blocks from `receiver` until the exporter is dropped.
+fn write_blocks(
+    receiver: Receiver<(u64, ExportedBlock)>,
+    mut files: ExportFiles,
+    exported_blocks: IntCounter,
+    failed_blocks: IntCounter,
+) {
+    for (height, exported_block) in receiver {
+        let result = serde_json::to_vec(&exported_block)
+            .map_err(io::Error::from)
+            .and_then(|line| files.append(height, &line));
+        match result {
+            Ok(()) => exported_blocks.inc(),
+            Err(error) => {
+                failed_blocks.inc();
+                warn!(%error, %height, "failed to export block");
+            }
+        }
+    }
+}
+
+/// The export files in a folder, of which the newest is appended to.
+struct ExportFiles {
+    path: PathBuf,
+    max_file_size: u64,
+    /// The file currently appended to and its size.
+    current: Option<(BufWriter<File>, u64)>,
+}
+
+impl ExportFiles {
+    fn new(path: PathBuf, max_file_size: u64) -> Self {
+        ExportFiles {
+            path,
+            max_file_size,
+            current: None,
+        }
+    }
+
+    /// Returns the path of the file starting with the block at `height`.
+    fn file_path(path: &Path, height: u64) -> PathBuf {
+        path.join(format!("blocks-{:020}.jsonl", height))
+    }
+
+    /// Appends `line` to the current file, starting a new one first if the current one is full.
+    ///
+    /// After an error, the next line starts a new file, so that a partially written line is never
+    /// followed by another one.
+    fn append(&mut self, height: u64, line: &[u8]) -> io::Result<()> {
+        let (mut writer, size) = match self.current.take() {
+            Some((writer, size)) if size < self.max_file_size => (writer, size),
+            _ => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(Self::file_path(&self.path, height))?;
+                let size = file.metadata()?.len();
+                (BufWriter::new(file), size)
+            }
+        };
+        writer.write_all(line)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        self.current = Some((writer, size + line.len() as u64 + 1));
+        Ok(())
+    }
+}
+
+impl<REv> Component<REv> for BlockExporter
+where
+    REv: From<StorageRequest> + Send,
+{
+    type Event = Event;
+    type ConstructionError = Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::BlockExecuted {
+                block,
+                execution_results,
+            } => {
+                if !self.is_enabled() {
+                    return Effects::new();
+                }
+                let deploy_hashes: Multiple<DeployHash> = block
+                    .deploy_hashes()
+                    .iter()
+                    .chain(block.transfer_hashes())
+                    .copied()
+                    .collect();
+                effect_builder
+                    .get_deploys_from_storage(deploy_hashes)
+                    .event(move |deploys| Event::DeploysLoaded {
+                        block,
+                        execution_results,
+                        deploys,
+                    })
+            }
+            Event::DeploysLoaded {
+                block,
+                execution_results,
+                deploys,
+            } => {
+                self.export(*block, execution_results, deploys);
+                Effects::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_lines(path: &Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn should_start_new_file_once_full() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = ExportFiles::new(dir.path().to_path_buf(), 10);
+
+        files.append(0, b"{\"a\":0}").unwrap();
+        files.append(1, b"{\"a\":1}").unwrap();
+        files.append(2, b"{\"a\":2}").unwrap();
+
+        assert_eq!(
+            read_lines(&ExportFiles::file_path(dir.path(), 0)),
+            vec!["{\"a\":0}", "{\"a\":1}"]
+        );
+        assert_eq!(
+            read_lines(&ExportFiles::file_path(dir.path(), 2)),
+            vec!["{\"a\":2}"]
+        );
+        assert!(!ExportFiles::file_path(dir.path(), 1).exists());
+    }
+}
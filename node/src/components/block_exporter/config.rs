@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PATH: &str = "block_export";
+const DEFAULT_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+const DEFAULT_QUEUE_SIZE: usize = 1024;
+
+/// Configuration options for exporting executed blocks to JSON files.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether executed blocks are exported.
+    pub enabled: bool,
+
+    /// The folder the export files are written to, absolute or relative to the config file.
+    ///
+    /// If the folder doesn't exist, it and any required parents will be created.
+    pub path: PathBuf,
+
+    /// The size in bytes after which a new export file is started.
+    pub max_file_size: u64,
+
+    /// The number of blocks waiting to be written before further blocks are skipped.
+    pub queue_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: false,
+            path: PathBuf::from(DEFAULT_PATH),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            queue_size: DEFAULT_QUEUE_SIZE,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use prometheus::{IntCounter, Registry};
+
+use crate::unregister_metric;
+
+/// Metrics for the block exporter.
+#[derive(Debug)]
+pub(super) struct BlockExporterMetrics {
+    /// Number of blocks written to the export files.
+    pub(super) exported_blocks: IntCounter,
+    /// Number of blocks skipped because the export queue was full.
+    pub(super) skipped_blocks: IntCounter,
+    /// Number of blocks which failed to be written.
+    pub(super) failed_blocks: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl BlockExporterMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let exported_blocks = IntCounter::new(
+            "block_export_exported_blocks",
+            "number of executed blocks written to the export files",
+        )?;
+        let skipped_blocks = IntCounter::new(
+            "block_export_skipped_blocks",
+            "number of executed blocks not exported because the export queue was full",
+        )?;
+        let failed_blocks = IntCounter::new(
+            "block_export_failed_blocks",
+            "number of executed blocks which failed to be written to the export files",
+        )?;
+        registry.register(Box::new(exported_blocks.clone()))?;
+        registry.register(Box::new(skipped_blocks.clone()))?;
+        registry.register(Box::new(failed_blocks.clone()))?;
+
+        Ok(BlockExporterMetrics {
+            exported_blocks,
+            skipped_blocks,
+            failed_blocks,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for BlockExporterMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.exported_blocks);
+        unregister_metric!(self.registry, self.skipped_blocks);
+        unregister_metric!(self.registry, self.failed_blocks);
+    }
+}
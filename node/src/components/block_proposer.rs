@@ -3,37 +3,50 @@
 //! The block proposer stores deploy hashes in memory, tracking their suitability for inclusion into
 //! a new block. Upon request, it returns a list of candidates that can be included.
 
+mod config;
 mod deploy_sets;
 mod event;
 mod metrics;
+mod signature_rewards;
 
 #[cfg(test)]
 mod tests;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     convert::Infallible,
     time::Duration,
 };
 
 use datasize::DataSize;
 use prometheus::{self, Registry};
+use serde_json::{json, Value};
 use tracing::{debug, error, info, trace, warn};
 
 use crate::{
     components::Component,
     effect::{
-        requests::{BlockProposerRequest, ProtoBlockRequest, StateStoreRequest, StorageRequest},
+        requests::{
+            BlockProposerRequest, ContractRuntimeRequest, DumpStateRequest, ProtoBlockRequest,
+            StateStoreRequest, StorageRequest,
+        },
         EffectBuilder, EffectExt, Effects,
     },
-    types::{chainspec::DeployConfig, Chainspec, DeployHash, DeployHeader, ProtoBlock, Timestamp},
+    types::{
+        chainspec::DeployConfig, Chainspec, DeployHash, DeployHeader, ProtoBlock,
+        RewardedSignatures, TimeDiff, Timestamp,
+    },
+    utils::{Job, Scheduler},
     NodeRng,
 };
 use casper_execution_engine::shared::gas::Gas;
+use casper_types::{account::AccountHash, U512};
+pub use config::Config;
 pub(crate) use deploy_sets::BlockProposerDeploySets;
 pub(crate) use event::{DeployType, Event};
 use metrics::BlockProposerMetrics;
 use num_traits::Zero;
+use signature_rewards::SignatureRewardsConfig;
 
 /// Block proposer component.
 #[derive(DataSize, Debug)]
@@ -43,11 +56,20 @@ pub(crate) struct BlockProposer {
 
     /// Metrics, present in all states.
     metrics: BlockProposerMetrics,
+
+    /// Scheduler of the periodic pruning.
+    #[data_size(skip)]
+    scheduler: Scheduler,
 }
 
-/// Interval after which a pruning of the internal sets is triggered.
-// TODO: Make configurable.
-const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+/// The periodic pruning of the internal sets.
+// TODO: Make the interval configurable.
+const PRUNE_JOB: Job =
+    Job::new("block_proposer_prune", Duration::from_secs(10)).with_jitter(Duration::from_secs(1));
+
+/// How long deploys which expired while pending are remembered, so that their status can still be
+/// reported after they were pruned.
+const EXPIRED_DEPLOY_RETENTION: Duration = Duration::from_secs(60 * 60);
 
 /// Experimentally, deploys are in the range of 270-280 bytes, we use this to determine if we are
 /// within a threshold to break iteration of `pending` early.
@@ -66,6 +88,19 @@ type FinalizationQueue = HashMap<BlockHeight, Vec<DeployHash>>;
 /// fulfill the corresponding requests.
 type RequestQueue = HashMap<BlockHeight, Vec<ProtoBlockRequest>>;
 
+/// What the block proposer knows about a deploy.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq)]
+pub enum DeployStatus {
+    /// The deploy was never buffered, or it expired too long ago to be remembered.
+    Unknown,
+    /// The deploy is pending for inclusion in a block.
+    Pending,
+    /// The deploy was included in a finalized block.
+    Finalized,
+    /// The deploy expired while pending, without being included in a finalized block.
+    Expired,
+}
+
 /// Current operational state of a block proposer.
 #[derive(DataSize, Debug)]
 #[allow(clippy::large_enum_variant)]
@@ -78,6 +113,10 @@ enum BlockProposerState {
         state_key: Vec<u8>,
         /// The deploy config from the current chainspec.
         deploy_config: DeployConfig,
+        /// The signature rewards parameters from the current chainspec.
+        signature_rewards: SignatureRewardsConfig,
+        /// The block proposer configuration.
+        config: Config,
     },
     /// Normal operation.
     Ready(BlockProposerReady),
@@ -90,6 +129,8 @@ impl BlockProposer {
         effect_builder: EffectBuilder<REv>,
         next_finalized_block: BlockHeight,
         chainspec: &Chainspec,
+        config: Config,
+        scheduler: Scheduler,
     ) -> Result<(Self, Effects<Event>), prometheus::Error>
     where
         REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send + 'static,
@@ -114,17 +155,52 @@ impl BlockProposer {
                 pending: Vec::new(),
                 state_key,
                 deploy_config: chainspec.deploy_config,
+                signature_rewards: SignatureRewardsConfig::new(&chainspec.core_config),
+                config,
             },
             metrics: BlockProposerMetrics::new(registry)?,
+            scheduler,
         };
 
         Ok((block_proposer, effects))
     }
+
+    /// Returns a snapshot of the internal state for debugging purposes.
+    ///
+    /// Only counts and heights are included, not the deploys themselves.
+    fn dump_state(&self) -> Value {
+        match &self.state {
+            BlockProposerState::Initializing { pending, .. } => json!({
+                "state": "initializing",
+                "buffered_events": pending.len(),
+            }),
+            BlockProposerState::Ready(ready_state) => {
+                let sets = &ready_state.sets;
+                let queued_heights: Vec<_> = sets.finalization_queue.keys().collect();
+                let queued_requests: usize = ready_state.request_queue.values().map(Vec::len).sum();
+                json!({
+                    "state": "ready",
+                    "pending_deploys": sets.pending.len(),
+                    "finalized_deploys": sets.finalized_deploys.len(),
+                    "expired_deploys": ready_state.expired.len(),
+                    "next_finalized": sets.next_finalized,
+                    "finalization_queue": queued_heights,
+                    "unhandled_finalized": ready_state.unhandled_finalized.len(),
+                    "queued_requests": queued_requests,
+                })
+            }
+        }
+    }
 }
 
 impl<REv> Component<REv> for BlockProposer
 where
-    REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send + 'static,
+    REv: From<Event>
+        + From<StorageRequest>
+        + From<StateStoreRequest>
+        + From<ContractRuntimeRequest>
+        + Send
+        + 'static,
 {
     type Event = Event;
     type ConstructionError = Infallible;
@@ -135,6 +211,12 @@ where
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
+        // State dumps are answered in either state, rather than buffered until initialization is
+        // complete.
+        if let Event::DumpStateRequest(DumpStateRequest { responder, .. }) = event {
+            return responder.respond(Some(self.dump_state())).ignore();
+        }
+
         let mut effects = Effects::new();
 
         // We handle two different states in the block proposer, but our "ready" state is
@@ -146,6 +228,8 @@ where
                     ref mut pending,
                     state_key,
                     deploy_config,
+                    signature_rewards,
+                    config,
                 },
                 Event::Loaded {
                     sets,
@@ -157,7 +241,10 @@ where
                         .unwrap_or_default()
                         .with_next_finalized(next_finalized_block),
                     unhandled_finalized: Default::default(),
+                    expired: Default::default(),
                     deploy_config: *deploy_config,
+                    signature_rewards: *signature_rewards,
+                    config: *config,
                     state_key: state_key.clone(),
                     request_queue: Default::default(),
                 };
@@ -171,9 +258,8 @@ where
 
                 // Start pruning deploys after delay.
                 effects.extend(
-                    effect_builder
-                        .set_timeout(PRUNE_INTERVAL)
-                        .event(|_| Event::Prune),
+                    self.scheduler
+                        .schedule(effect_builder, PRUNE_JOB, || Event::Prune),
                 );
             }
             (
@@ -187,8 +273,17 @@ where
             }
 
             (BlockProposerState::Ready(ref mut ready_state), event) => {
+                let is_prune = matches!(event, Event::Prune);
                 effects.extend(ready_state.handle_event(effect_builder, event));
 
+                // Re-trigger the pruning after its period.
+                if is_prune {
+                    effects.extend(
+                        self.scheduler
+                            .schedule(effect_builder, PRUNE_JOB, || Event::Prune),
+                    );
+                }
+
                 // Update metrics after the effects have been applied.
                 self.metrics
                     .pending_deploys
@@ -209,8 +304,14 @@ struct BlockProposerReady {
     /// seen but were reported as reported to `finalized_deploys()`. They are used to
     /// filter deploys for proposal, similar to `self.sets.finalized_deploys`.
     unhandled_finalized: HashSet<DeployHash>,
+    /// Deploys which expired while pending, with the time they expired at.
+    expired: HashMap<DeployHash, Timestamp>,
     /// We don't need the whole Chainspec here, just the deploy config.
     deploy_config: DeployConfig,
+    /// The signature rewards parameters from the chainspec.
+    signature_rewards: SignatureRewardsConfig,
+    /// The block proposer configuration.
+    config: Config,
     /// Key for storing the block proposer state.
     state_key: Vec<u8>,
     /// The queue of requests awaiting being handled.
@@ -224,7 +325,7 @@ impl BlockProposerReady {
         event: Event,
     ) -> Effects<Event>
     where
-        REv: Send + From<StateStoreRequest>,
+        REv: Send + From<StateStoreRequest> + From<StorageRequest> + From<ContractRuntimeRequest>,
     {
         match event {
             Event::Request(BlockProposerRequest::RequestProtoBlock(request)) => {
@@ -241,38 +342,26 @@ impl BlockProposerReady {
                     Effects::new()
                 } else {
                     info!(%request.next_finalized, "proposing a proto block");
-                    request
-                        .responder
-                        .respond(self.propose_proto_block(
-                            self.deploy_config,
-                            request.current_instant,
-                            request.past_deploys,
-                            request.random_bit,
-                        ))
-                        .ignore()
+                    self.handle_proto_block_request(effect_builder, request)
                 }
             }
+            Event::Request(BlockProposerRequest::GetDeployStatus {
+                deploy_hash,
+                responder,
+            }) => responder.respond(self.deploy_status(&deploy_hash)).ignore(),
             Event::BufferDeploy { hash, deploy_type } => {
-                self.add_deploy_or_transfer(Timestamp::now(), hash, *deploy_type);
+                self.add_deploy_or_transfer(effect_builder.now(), hash, *deploy_type);
                 Effects::new()
             }
             Event::Prune => {
-                let pruned = self.prune(Timestamp::now());
+                let pruned = self.prune(effect_builder.now());
                 debug!(%pruned, "pruned deploys from buffer");
 
-                // After pruning, we store a state snapshot.
-                let mut effects = effect_builder
+                // After pruning, we store a state snapshot.  The pruning is re-triggered by the
+                // outer component.
+                effect_builder
                     .save_state(self.state_key.clone().into(), self.sets.clone())
-                    .ignore();
-
-                // Re-trigger timer after `PRUNE_INTERVAL`.
-                effects.extend(
-                    effect_builder
-                        .set_timeout(PRUNE_INTERVAL)
-                        .event(|_| Event::Prune),
-                );
-
-                effects
+                    .ignore()
             }
             Event::Loaded { sets, .. } => {
                 // This should never happen, but we can just ignore the event and carry on.
@@ -282,8 +371,50 @@ impl BlockProposerReady {
                 );
                 Effects::new()
             }
+            Event::DumpStateRequest(req) => {
+                // State dumps are answered by the outer component before reaching this state.
+                error!(%req, "got state dump request in ready state");
+                Effects::new()
+            }
+            Event::SignatureWindowRead { request, window } => {
+                let rewarded_signatures = signature_rewards::select(
+                    &self.signature_rewards,
+                    &window,
+                    &request.past_rewarded_signatures,
+                );
+                debug!(%rewarded_signatures, "selected rewarded signatures");
+                self.propose_with_rewarded_signatures(effect_builder, request, rewarded_signatures)
+            }
+            Event::AccountBalancesRead {
+                request,
+                rewarded_signatures,
+                balances,
+            } => {
+                let proto_block = match balances {
+                    Some(balances) => self.propose_proto_block_within_balances(
+                        self.deploy_config,
+                        request.current_instant,
+                        request.past_deploys,
+                        request.random_bit,
+                        &balances,
+                    ),
+                    None => {
+                        warn!("proposing a proto block without checking payment");
+                        self.propose_proto_block(
+                            self.deploy_config,
+                            request.current_instant,
+                            request.past_deploys,
+                            request.random_bit,
+                        )
+                    }
+                };
+                request
+                    .responder
+                    .respond(proto_block.with_rewarded_signatures(rewarded_signatures))
+                    .ignore()
+            }
             Event::FinalizedProtoBlock { block, mut height } => {
-                let (_, mut deploys, transfers, _) = block.destructure();
+                let (_, mut deploys, transfers, _, _) = block.destructure();
                 deploys.extend(transfers);
 
                 if height > self.sets.next_finalized {
@@ -340,7 +471,7 @@ impl BlockProposerReady {
         if self.sets.finalized_deploys.contains_key(&hash) {
             info!(%hash, "deploy rejected from the buffer");
         } else {
-            self.sets.pending.insert(hash, deploy_or_transfer);
+            self.sets.add_pending(hash, deploy_or_transfer);
             info!(%hash, "added deploy to the buffer");
         }
     }
@@ -352,10 +483,10 @@ impl BlockProposerReady {
     {
         for deploy_hash in deploys.into_iter() {
             match self.sets.pending.remove(&deploy_hash) {
-                Some(deploy_type) => {
+                Some(pending) => {
                     self.sets
                         .finalized_deploys
-                        .insert(deploy_hash, deploy_type.take_header());
+                        .insert(deploy_hash, pending.deploy_type.take_header());
                 }
                 // If we haven't seen this deploy before, we still need to take note of it.
                 _ => {
@@ -368,12 +499,13 @@ impl BlockProposerReady {
     /// Handles finalization of a block.
     fn handle_finalized_block<I, REv>(
         &mut self,
-        _effect_builder: EffectBuilder<REv>,
+        effect_builder: EffectBuilder<REv>,
         height: BlockHeight,
         deploys: I,
     ) -> Effects<Event>
     where
         I: IntoIterator<Item = DeployHash>,
+        REv: Send + From<StorageRequest> + From<ContractRuntimeRequest>,
     {
         self.finalized_deploys(deploys);
         self.sets.next_finalized = height + 1;
@@ -382,23 +514,99 @@ impl BlockProposerReady {
             info!(height = %(height + 1), "handling queued requests");
             requests
                 .into_iter()
-                .flat_map(|request| {
-                    request
-                        .responder
-                        .respond(self.propose_proto_block(
-                            self.deploy_config,
-                            request.current_instant,
-                            request.past_deploys,
-                            request.random_bit,
-                        ))
-                        .ignore()
-                })
+                .flat_map(|request| self.handle_proto_block_request(effect_builder, request))
                 .collect()
         } else {
             Effects::new()
         }
     }
 
+    /// Responds to a request for a proto block.
+    ///
+    /// The most recent finalized blocks and their signatures are read first, to select the
+    /// signatures to reward, unless signature rewards are disabled.
+    fn handle_proto_block_request<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        request: ProtoBlockRequest,
+    ) -> Effects<Event>
+    where
+        REv: Send + From<StorageRequest> + From<ContractRuntimeRequest>,
+    {
+        let heights = self.signature_rewards.window(request.next_finalized);
+        if heights.is_empty() {
+            return self.propose_with_rewarded_signatures(
+                effect_builder,
+                request,
+                RewardedSignatures::default(),
+            );
+        }
+
+        effect_builder
+            .get_blocks_with_signatures_from_storage(heights)
+            .event(move |window| Event::SignatureWindowRead { request, window })
+    }
+
+    /// Proposes a proto block rewarding the given signatures.
+    ///
+    /// If payment checks are enabled, the balances of the accounts of all candidate deploys are
+    /// read first, and the proto block is proposed once they are available.
+    fn propose_with_rewarded_signatures<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        request: ProtoBlockRequest,
+        rewarded_signatures: RewardedSignatures,
+    ) -> Effects<Event>
+    where
+        REv: Send + From<StorageRequest> + From<ContractRuntimeRequest>,
+    {
+        if !self.config.check_payment {
+            let proto_block = self
+                .propose_proto_block(
+                    self.deploy_config,
+                    request.current_instant,
+                    request.past_deploys,
+                    request.random_bit,
+                )
+                .with_rewarded_signatures(rewarded_signatures);
+            return request.responder.respond(proto_block).ignore();
+        }
+
+        let accounts = self.candidate_accounts(request.current_instant, &request.past_deploys);
+        effect_builder
+            .get_account_balances(accounts)
+            .event(move |balances| Event::AccountBalancesRead {
+                request,
+                rewarded_signatures,
+                balances,
+            })
+    }
+
+    /// Returns the accounts of all pending deploys which could be included in a block with the
+    /// given timestamp.
+    fn candidate_accounts(
+        &self,
+        block_timestamp: Timestamp,
+        past_deploys: &HashSet<DeployHash>,
+    ) -> Vec<AccountHash> {
+        self.sets
+            .pending
+            .iter()
+            .filter(|(hash, pending)| {
+                !past_deploys.contains(*hash)
+                    && self.is_deploy_valid(
+                        pending.deploy_type.header(),
+                        block_timestamp,
+                        &self.deploy_config,
+                        past_deploys,
+                    )
+            })
+            .map(|(_, pending)| pending.deploy_type.header().account().to_account_hash())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
     /// Checks if a deploy is valid (for inclusion into the next block).
     fn is_deploy_valid(
         &self,
@@ -423,6 +631,42 @@ impl BlockProposerReady {
         block_timestamp: Timestamp,
         past_deploys: HashSet<DeployHash>,
         random_bit: bool,
+    ) -> ProtoBlock {
+        self.select_deploys(
+            deploy_config,
+            block_timestamp,
+            past_deploys,
+            random_bit,
+            None,
+        )
+    }
+
+    /// Returns a list of candidates for inclusion into a block, leaving out deploys whose accounts
+    /// can't pay for them given their `balances`.
+    fn propose_proto_block_within_balances(
+        &mut self,
+        deploy_config: DeployConfig,
+        block_timestamp: Timestamp,
+        past_deploys: HashSet<DeployHash>,
+        random_bit: bool,
+        balances: &HashMap<AccountHash, U512>,
+    ) -> ProtoBlock {
+        self.select_deploys(
+            deploy_config,
+            block_timestamp,
+            past_deploys,
+            random_bit,
+            Some(PaymentReservations::new(balances)),
+        )
+    }
+
+    fn select_deploys(
+        &mut self,
+        deploy_config: DeployConfig,
+        block_timestamp: Timestamp,
+        past_deploys: HashSet<DeployHash>,
+        random_bit: bool,
+        mut reservations: Option<PaymentReservations<'_>>,
     ) -> ProtoBlock {
         let max_transfers = deploy_config.block_max_transfer_count as usize;
         let max_deploys = deploy_config.block_max_deploy_count as usize;
@@ -434,7 +678,14 @@ impl BlockProposerReady {
         let mut block_gas_running_total = Gas::zero();
         let mut block_size_running_total = 0usize;
 
-        for (hash, deploy_type) in self.sets.pending.iter() {
+        // Consider deploys by priority, which favors older ones unless configured otherwise, so
+        // that deploys are included roughly in the order they arrived in.  Priority fees only
+        // decide between deploys at the same gas price.
+        let max_priority_fee = deploy_config.max_priority_fee;
+        for (hash, deploy_type) in self
+            .sets
+            .pending_by_priority(self.config.fairness(), max_priority_fee)
+        {
             let at_max_transfers = transfers.len() == max_transfers;
             let at_max_deploys = wasm_deploys.len() == max_deploys
                 || (deploy_type.is_wasm()
@@ -455,8 +706,17 @@ impl BlockProposerReady {
                 continue;
             }
 
+            let mut can_pay = || match reservations.as_mut() {
+                Some(reservations) => reservations.reserve(deploy_type),
+                None => true,
+            };
+
             // always include wasm-less transfers if we are under the max for them
             if deploy_type.is_transfer() && !at_max_transfers {
+                if !can_pay() {
+                    debug!(%hash, "account can't pay for transfer");
+                    continue;
+                }
                 transfers.push(*hash);
             } else if deploy_type.is_wasm() && !at_max_deploys {
                 if block_size_running_total + deploy_type.size() > max_block_size_bytes {
@@ -484,6 +744,10 @@ impl BlockProposerReady {
                 if gas_running_total > block_gas_limit {
                     continue;
                 }
+                if !can_pay() {
+                    debug!(%hash, "account can't pay for deploy");
+                    continue;
+                }
                 wasm_deploys.push(*hash);
                 block_gas_running_total = gas_running_total;
                 block_size_running_total += deploy_type.size();
@@ -494,11 +758,72 @@ impl BlockProposerReady {
     }
 
     /// Prunes expired deploy information from the BlockProposer, returns the total deploys pruned.
+    ///
+    /// Pending deploys which expired are remembered for `EXPIRED_DEPLOY_RETENTION`.
     fn prune(&mut self, current_instant: Timestamp) -> usize {
+        for (hash, pending) in &self.sets.pending {
+            let header = pending.deploy_type.header();
+            if header.expired(current_instant) {
+                self.expired.insert(*hash, header.expires());
+            }
+        }
+        let retention: TimeDiff = EXPIRED_DEPLOY_RETENTION.into();
+        self.expired
+            .retain(|_, expires| *expires + retention >= current_instant);
         self.sets.prune(current_instant)
     }
 
+    /// Returns what is known about the given deploy.
+    fn deploy_status(&self, deploy_hash: &DeployHash) -> DeployStatus {
+        if self.sets.pending.contains_key(deploy_hash) {
+            DeployStatus::Pending
+        } else if self.contains_finalized(deploy_hash) {
+            DeployStatus::Finalized
+        } else if self.expired.contains_key(deploy_hash) {
+            DeployStatus::Expired
+        } else {
+            DeployStatus::Unknown
+        }
+    }
+
     fn contains_finalized(&self, dep: &DeployHash) -> bool {
         self.sets.finalized_deploys.contains_key(dep) || self.unhandled_finalized.contains(dep)
     }
 }
+
+/// Payment amounts reserved against account balances while selecting deploys for a block.
+struct PaymentReservations<'a> {
+    /// The balances of the accounts' main purses.
+    balances: &'a HashMap<AccountHash, U512>,
+    /// The sum of the payment amounts of the deploys selected so far, by account.
+    reserved: HashMap<AccountHash, U512>,
+}
+
+impl<'a> PaymentReservations<'a> {
+    fn new(balances: &'a HashMap<AccountHash, U512>) -> Self {
+        PaymentReservations {
+            balances,
+            reserved: HashMap::new(),
+        }
+    }
+
+    /// Reserves the payment amount of the deploy against the balance of its account.
+    ///
+    /// Returns `false` without reserving anything if the remaining balance doesn't cover it, or if
+    /// the balance of the account is unknown.
+    fn reserve(&mut self, deploy_type: &DeployType) -> bool {
+        let account = deploy_type.header().account().to_account_hash();
+        let balance = match self.balances.get(&account) {
+            Some(balance) => *balance,
+            None => return false,
+        };
+        let reserved = self.reserved.entry(account).or_insert_with(U512::zero);
+        match reserved.checked_add(deploy_type.payment_amount().value()) {
+            Some(total) if total <= balance => {
+                *reserved = total;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::ConfigSection;
+
+const DEFAULT_FAIRNESS: f64 = 1.0;
+
+/// Configuration options for proposing blocks.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// How strongly pending deploys are ordered by age rather than by gas price when proposing a
+    /// block, between 0.0 and 1.0.
+    ///
+    /// At 1.0, deploys are proposed strictly in the order they arrived in.  At 0.0, deploys with a
+    /// higher gas price are always proposed first, which can starve low-priced deploys under
+    /// constant load.  Values in between blend both orders, so that a deploy is eventually
+    /// proposed once enough newer deploys arrived after it.
+    pub fairness: f64,
+    /// Whether to check that the accounts of proposed deploys can pay for them.
+    ///
+    /// If enabled, the balances of the accounts of pending deploys are read from the global state
+    /// of the highest block before proposing, and payment amounts are reserved against them as
+    /// deploys are selected.  Deploys whose accounts can't cover their payment are left out, so
+    /// that no block space is wasted on deploys which will fail to execute.
+    pub check_payment: bool,
+}
+
+impl Config {
+    /// Returns the fairness, clamped to the range from 0.0 to 1.0.
+    pub(super) fn fairness(&self) -> f64 {
+        if self.fairness.is_nan() {
+            return DEFAULT_FAIRNESS;
+        }
+        self.fairness.max(0.0).min(1.0)
+    }
+
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.in_range("fairness", self.fairness, 0.0..=1.0);
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            fairness: DEFAULT_FAIRNESS,
+            check_payment: false,
+        }
+    }
+}
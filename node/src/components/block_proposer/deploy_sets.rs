@@ -1,4 +1,5 @@
 use std::{
+    cmp::{self, Ordering, Reverse},
     collections::HashMap,
     fmt::{self, Display, Formatter},
 };
@@ -9,11 +10,22 @@ use serde::{Deserialize, Serialize};
 use super::{event::DeployType, BlockHeight, FinalizationQueue};
 use crate::types::{Chainspec, DeployHash, DeployHeader, Timestamp};
 
+/// A deploy pending for inclusion in a block.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+pub(super) struct PendingDeploy {
+    /// The deploy.
+    pub(super) deploy_type: DeployType,
+    /// The position of the deploy in the order of arrival.
+    pub(super) arrival: u64,
+}
+
 /// Stores the internal state of the BlockProposer.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 pub struct BlockProposerDeploySets {
     /// The collection of deploys pending for inclusion in a block.
-    pub(super) pending: HashMap<DeployHash, DeployType>,
+    pub(super) pending: HashMap<DeployHash, PendingDeploy>,
+    /// The position in the order of arrival of the next pending deploy.
+    pub(super) next_arrival: u64,
     /// The deploys that have already been included in a finalized block.
     pub(super) finalized_deploys: HashMap<DeployHash, DeployHeader>,
     /// The next block height we expect to be finalized.
@@ -29,11 +41,13 @@ pub struct BlockProposerDeploySets {
 impl Default for BlockProposerDeploySets {
     fn default() -> Self {
         let pending = HashMap::new();
+        let next_arrival = 0;
         let finalized_deploys = Default::default();
         let next_finalized = Default::default();
         let finalization_queue = Default::default();
         BlockProposerDeploySets {
             pending,
+            next_arrival,
             finalized_deploys,
             next_finalized,
             finalization_queue,
@@ -48,6 +62,64 @@ impl BlockProposerDeploySets {
             ..self
         }
     }
+
+    /// Adds a deploy to the pending deploys, behind all deploys which arrived before it.
+    pub(super) fn add_pending(&mut self, hash: DeployHash, deploy_type: DeployType) {
+        let arrival = self.next_arrival;
+        self.next_arrival += 1;
+        self.pending.insert(
+            hash,
+            PendingDeploy {
+                deploy_type,
+                arrival,
+            },
+        );
+    }
+
+    /// Returns the pending deploys in the order in which they should be considered for a block.
+    ///
+    /// Each deploy is ranked once by age, oldest first, and once by gas price, highest first.
+    /// Deploys at the same gas price are ranked by their priority fee, highest first, counting
+    /// at most `max_priority_fee`.  The deploys are then ordered by the average of both ranks,
+    /// weighted by `fairness` and `1 - fairness` respectively, with ties going to the older deploy.
+    pub(super) fn pending_by_priority(
+        &self,
+        fairness: f64,
+        max_priority_fee: u64,
+    ) -> Vec<(&DeployHash, &DeployType)> {
+        let mut by_age: Vec<_> = self.pending.iter().collect();
+        by_age.sort_by_key(|(_, pending)| pending.arrival);
+
+        // The rank by price of each deploy, indexed by its rank by age.
+        let mut by_price: Vec<usize> = (0..by_age.len()).collect();
+        by_price.sort_by_key(|&age_rank| {
+            let header = by_age[age_rank].1.deploy_type.header();
+            let priority_fee = cmp::min(header.priority_fee(), max_priority_fee);
+            (Reverse(header.gas_price()), Reverse(priority_fee), age_rank)
+        });
+        let mut price_ranks = vec![0; by_age.len()];
+        for (price_rank, age_rank) in by_price.into_iter().enumerate() {
+            price_ranks[age_rank] = price_rank;
+        }
+
+        let score = |age_rank: usize| {
+            fairness * age_rank as f64 + (1.0 - fairness) * price_ranks[age_rank] as f64
+        };
+        let mut order: Vec<usize> = (0..by_age.len()).collect();
+        order.sort_by(|&left, &right| {
+            score(left)
+                .partial_cmp(&score(right))
+                .unwrap_or(Ordering::Equal)
+                .then(left.cmp(&right))
+        });
+        order
+            .into_iter()
+            .map(|age_rank| {
+                let (hash, pending) = by_age[age_rank];
+                (hash, &pending.deploy_type)
+            })
+            .collect()
+    }
 }
 
 impl Display for BlockProposerDeploySets {
@@ -65,9 +137,12 @@ impl Display for BlockProposerDeploySets {
 ///
 /// We namespace based on a chainspec to prevent validators from loading data for a different chain
 /// if they forget to clear their state.
+///
+/// The key also carries the version of the format of the sets, so that sets stored in an older
+/// format are ignored rather than failing to deserialize.
 pub fn create_storage_key(chainspec: &Chainspec) -> Vec<u8> {
     format!(
-        "block_proposer_deploy_sets:version={},chain_name={}",
+        "block_proposer_deploy_sets_v3:version={},chain_name={}",
         chainspec.protocol_config.version, chainspec.network_config.name
     )
     .into()
@@ -97,10 +172,10 @@ pub(super) fn prune_deploys(
 /// Prunes expired deploy information from an individual pending deploy collection, returns the
 /// total deploys pruned
 pub(super) fn prune_pending_deploys(
-    deploys: &mut HashMap<DeployHash, DeployType>,
+    deploys: &mut HashMap<DeployHash, PendingDeploy>,
     current_instant: Timestamp,
 ) -> usize {
     let initial_len = deploys.len();
-    deploys.retain(|_hash, wrapper| !wrapper.header().expired(current_instant));
+    deploys.retain(|_hash, pending| !pending.deploy_type.header().expired(current_instant));
     initial_len - deploys.len()
 }
@@ -1,4 +1,7 @@
-use std::fmt::{self, Formatter};
+use std::{
+    collections::HashMap,
+    fmt::{self, Formatter},
+};
 
 use datasize::DataSize;
 use derive_more::From;
@@ -7,10 +10,11 @@ use serde::{Deserialize, Serialize};
 
 use super::{BlockHeight, BlockProposerDeploySets};
 use crate::{
-    effect::requests::BlockProposerRequest,
-    types::{DeployHash, DeployHeader, ProtoBlock},
+    effect::requests::{BlockProposerRequest, DumpStateRequest, ProtoBlockRequest},
+    types::{BlockWithSignatures, DeployHash, DeployHeader, ProtoBlock, RewardedSignatures},
 };
 use casper_execution_engine::shared::motes::Motes;
+use casper_types::{account::AccountHash, U512};
 
 /// A wrapper over `DeployHeader` to differentiate between wasm-less transfers and wasm headers.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -98,6 +102,29 @@ pub enum Event {
         block: ProtoBlock,
         height: BlockHeight,
     },
+    /// The most recent finalized blocks and their signatures have been read for a proto block
+    /// request, to select the signatures to reward.
+    #[data_size(skip)]
+    SignatureWindowRead {
+        /// The request to respond to.
+        request: ProtoBlockRequest,
+        /// The blocks of the signature rewards window found in storage, by ascending height.
+        window: Vec<BlockWithSignatures>,
+    },
+    /// The balances of the accounts of pending deploys have been read for a proto block request.
+    #[data_size(skip)]
+    AccountBalancesRead {
+        /// The request to respond to.
+        request: ProtoBlockRequest,
+        /// The signatures to reward in the proto block.
+        rewarded_signatures: RewardedSignatures,
+        /// The balances by account, or `None` if they couldn't be read.
+        balances: Option<HashMap<AccountHash, U512>>,
+    },
+    /// A request to dump a snapshot of the internal state.
+    #[from]
+    #[data_size(skip)]
+    DumpStateRequest(DumpStateRequest),
 }
 
 impl Display for Event {
@@ -130,6 +157,23 @@ impl Display for Event {
                     block, height
                 )
             }
+            Event::SignatureWindowRead { window, .. } => write!(
+                f,
+                "block-proposer read {} blocks to reward signatures of",
+                window.len()
+            ),
+            Event::AccountBalancesRead {
+                balances: Some(balances),
+                ..
+            } => write!(
+                f,
+                "block-proposer read balances of {} accounts",
+                balances.len()
+            ),
+            Event::AccountBalancesRead { balances: None, .. } => {
+                write!(f, "block-proposer failed to read account balances")
+            }
+            Event::DumpStateRequest(req) => write!(f, "block-proposer {}", req),
         }
     }
 }
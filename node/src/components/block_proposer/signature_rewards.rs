@@ -0,0 +1,113 @@
+//! Selection of the finality signatures rewarded in a proposed block.
+//!
+//! The signatures are selected from the `max_delay` most recent finalized blocks, as stored
+//! locally.  A block's signatures are only rewarded once its signers together hold at least the
+//! threshold fraction of its era's total weight, and then all of them which haven't been rewarded
+//! before are cited at once.  The selection depends on nothing but these blocks and the
+//! citations of the proposed block's ancestors, so proposers with the same view of the chain
+//! select the same signatures.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    ops::Range,
+};
+
+use datasize::DataSize;
+use num::rational::Ratio;
+
+use casper_types::{PublicKey, U512};
+
+use crate::types::{
+    chainspec::CoreConfig, meets_weight_threshold, BlockHash, BlockWithSignatures,
+    RewardedSignatures, SignatureCitation,
+};
+
+/// The chainspec parameters of signature rewards.
+///
+/// The default disables signature rewards.
+#[derive(Copy, Clone, DataSize, Debug, Default)]
+pub(super) struct SignatureRewardsConfig {
+    /// The number of most recent finalized blocks whose signatures are rewarded.
+    pub(super) max_delay: u64,
+    /// The fraction of the total weight that must have signed a block before its signatures are
+    /// rewarded.
+    #[data_size(skip)]
+    pub(super) weight_threshold: Ratio<u64>,
+}
+
+impl SignatureRewardsConfig {
+    pub(super) fn new(core_config: &CoreConfig) -> Self {
+        SignatureRewardsConfig {
+            max_delay: core_config.signature_rewards_max_delay,
+            weight_threshold: core_config.signature_rewards_weight_threshold,
+        }
+    }
+
+    /// Returns the heights of the blocks whose signatures may be rewarded in a block proposed
+    /// while `next_finalized` is the height of the next block to be finalized.
+    pub(super) fn window(&self, next_finalized: u64) -> Range<u64> {
+        next_finalized.saturating_sub(self.max_delay)..next_finalized
+    }
+
+    /// Returns whether signers with `signed_weight` out of `total_weight` meet the threshold.
+    fn meets_threshold(&self, signed_weight: U512, total_weight: U512) -> bool {
+        meets_weight_threshold(signed_weight, total_weight, self.weight_threshold)
+    }
+}
+
+/// Selects the signatures to reward from `window`, the blocks of the window with their signatures
+/// in ascending order of height.
+///
+/// Signatures already cited by blocks of the window or by `past_rewarded_signatures`, the
+/// citations of the proposed block's non-finalized ancestors, are left out.  Blocks whose era's
+/// validator weights are unknown are skipped.
+pub(super) fn select(
+    config: &SignatureRewardsConfig,
+    window: &[BlockWithSignatures],
+    past_rewarded_signatures: &[RewardedSignatures],
+) -> RewardedSignatures {
+    let already_cited: HashSet<(BlockHash, PublicKey)> = window
+        .iter()
+        .map(|block_with_signatures| block_with_signatures.block.body().rewarded_signatures())
+        .chain(past_rewarded_signatures)
+        .flat_map(RewardedSignatures::cited)
+        .map(|(block_hash, signer)| (*block_hash, *signer))
+        .collect();
+
+    let citations = window
+        .iter()
+        .filter_map(|block_with_signatures| {
+            let weights = block_with_signatures.era_validator_weights.as_ref()?;
+            let block_hash = *block_with_signatures.block.hash();
+            let signers: Vec<&PublicKey> = block_with_signatures
+                .signatures
+                .proofs
+                .keys()
+                .filter(|signer| weights.contains_key(signer))
+                .collect();
+            let total_weight = weights
+                .values()
+                .fold(U512::zero(), |sum, weight| sum + *weight);
+            let signed_weight = signers
+                .iter()
+                .fold(U512::zero(), |sum, signer| sum + weights[*signer]);
+            if !config.meets_threshold(signed_weight, total_weight) {
+                return None;
+            }
+            let new_signers: BTreeSet<PublicKey> = signers
+                .into_iter()
+                .filter(|signer| !already_cited.contains(&(block_hash, **signer)))
+                .copied()
+                .collect();
+            if new_signers.is_empty() {
+                return None;
+            }
+            Some(SignatureCitation {
+                block_hash,
+                block_height: block_with_signatures.block.height(),
+                signers: new_signers,
+            })
+        })
+        .collect();
+    RewardedSignatures::new(citations)
+}
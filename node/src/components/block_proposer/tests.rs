@@ -1,16 +1,25 @@
+use std::{collections::BTreeMap, iter};
+
 use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
 use casper_types::{
     bytesrepr::{Bytes, ToBytes},
     runtime_args,
     system::standard_payment::ARG_AMOUNT,
-    RuntimeArgs, SecretKey,
+    PublicKey, RuntimeArgs, SecretKey, Signature,
 };
+use num::rational::Ratio;
 
 use super::*;
 use crate::{
+    components::consensus::EraId,
     crypto::AsymmetricKeyExt,
-    testing::TestRng,
-    types::{BlockLike, Deploy, DeployHash, TimeDiff},
+    effect::time_source::ManualClock,
+    testing::{ComponentHarness, TestRng},
+    types::{
+        Block, BlockLike, BlockSignatures, BlockWithSignatures, Deploy, DeployHash,
+        SignatureCitation, TimeDiff,
+    },
+    utils,
 };
 
 const DEFAULT_TEST_GAS_PRICE: u64 = 1;
@@ -46,6 +55,7 @@ fn generate_transfer(
         timestamp,
         ttl,
         gas_price,
+        0,
         dependencies,
         chain_name,
         payment,
@@ -62,6 +72,26 @@ fn generate_deploy(
     dependencies: Vec<DeployHash>,
     payment_amount: Gas,
     gas_price: u64,
+) -> Deploy {
+    generate_deploy_with_priority_fee(
+        rng,
+        timestamp,
+        ttl,
+        dependencies,
+        payment_amount,
+        gas_price,
+        0,
+    )
+}
+
+fn generate_deploy_with_priority_fee(
+    rng: &mut TestRng,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    dependencies: Vec<DeployHash>,
+    payment_amount: Gas,
+    gas_price: u64,
+    priority_fee: u64,
 ) -> Deploy {
     let secret_key = SecretKey::random(rng);
     let chain_name = "chain".to_string();
@@ -81,6 +111,7 @@ fn generate_deploy(
         timestamp,
         ttl,
         gas_price,
+        priority_fee,
         dependencies,
         chain_name,
         payment,
@@ -94,9 +125,12 @@ fn create_test_proposer() -> BlockProposerReady {
     BlockProposerReady {
         sets: Default::default(),
         deploy_config: Default::default(),
+        signature_rewards: Default::default(),
+        config: Default::default(),
         state_key: b"block-proposer-test".to_vec(),
         request_queue: Default::default(),
         unhandled_finalized: Default::default(),
+        expired: Default::default(),
     }
 }
 
@@ -114,6 +148,12 @@ impl From<StateStoreRequest> for Event {
     }
 }
 
+impl From<ContractRuntimeRequest> for Event {
+    fn from(_: ContractRuntimeRequest) -> Self {
+        unreachable!("no contract runtime requests in block proposer unit tests")
+    }
+}
+
 #[test]
 fn should_add_and_take_deploys() {
     let creation_time = Timestamp::from(100);
@@ -316,6 +356,88 @@ fn should_successfully_prune() {
     assert_eq!(proposer.sets.finalized_deploys.len(), 0);
 }
 
+#[test]
+fn should_prune_at_the_time_of_the_reactor_clock() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let clock = utils::leak(ManualClock::new(creation_time));
+    let harness = ComponentHarness::<Event>::builder()
+        .manual_clock(clock)
+        .build();
+
+    let mut rng = crate::new_rng();
+    let deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let mut proposer = create_test_proposer();
+
+    let event = Event::BufferDeploy {
+        hash: *deploy.id(),
+        deploy_type: Box::new(deploy.deploy_type().unwrap()),
+    };
+    let _ = proposer.handle_event(harness.effect_builder, event);
+    assert_eq!(proposer.sets.pending.len(), 1);
+
+    // Pruning doesn't wait for the deploy to expire in real time.
+    clock.advance(Duration::from_millis(101));
+    let _ = proposer.handle_event(harness.effect_builder, Event::Prune);
+    assert!(proposer.sets.pending.is_empty());
+    assert_eq!(proposer.deploy_status(deploy.id()), DeployStatus::Expired);
+}
+
+#[test]
+fn should_remember_deploys_which_expired_while_pending() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let expired_time = Timestamp::from(201);
+
+    let mut rng = crate::new_rng();
+    let mut generate = || {
+        generate_deploy(
+            &mut rng,
+            creation_time,
+            ttl,
+            vec![],
+            default_gas_payment(),
+            DEFAULT_TEST_GAS_PRICE,
+        )
+    };
+    let finalized = generate();
+    let expired = generate();
+    let unknown = generate();
+    let mut proposer = create_test_proposer();
+
+    proposer.add_deploy_or_transfer(
+        creation_time,
+        *finalized.id(),
+        finalized.deploy_type().unwrap(),
+    );
+    proposer.add_deploy_or_transfer(creation_time, *expired.id(), expired.deploy_type().unwrap());
+    proposer.finalized_deploys(vec![*finalized.id()]);
+    assert_eq!(proposer.deploy_status(expired.id()), DeployStatus::Pending);
+    assert_eq!(
+        proposer.deploy_status(finalized.id()),
+        DeployStatus::Finalized
+    );
+
+    assert_eq!(proposer.prune(expired_time), 2);
+    assert_eq!(proposer.deploy_status(expired.id()), DeployStatus::Expired);
+    assert_eq!(
+        proposer.deploy_status(finalized.id()),
+        DeployStatus::Unknown
+    );
+    assert_eq!(proposer.deploy_status(unknown.id()), DeployStatus::Unknown);
+
+    // Expired deploys are forgotten after the retention period.
+    proposer.prune(expired_time + EXPIRED_DEPLOY_RETENTION.into());
+    assert_eq!(proposer.deploy_status(expired.id()), DeployStatus::Unknown);
+}
+
 #[test]
 fn should_keep_track_of_unhandled_deploys() {
     let creation_time = Timestamp::from(100);
@@ -670,3 +792,274 @@ fn should_return_deploy_dependencies() {
     assert_eq!(deploys2.len(), 1);
     assert!(deploys2.contains(deploy2.id()));
 }
+
+/// Adds `count` deploys with the given gas price to `proposer`, returning their hashes.
+fn add_deploys_with_gas_price(
+    rng: &mut TestRng,
+    proposer: &mut BlockProposerReady,
+    count: usize,
+    gas_price: u64,
+    timestamp: Timestamp,
+) -> Vec<DeployHash> {
+    add_deploys_with_priority_fee(rng, proposer, count, gas_price, 0, timestamp)
+}
+
+/// Adds `count` deploys with the given gas price and priority fee to `proposer`, returning their
+/// hashes.
+fn add_deploys_with_priority_fee(
+    rng: &mut TestRng,
+    proposer: &mut BlockProposerReady,
+    count: usize,
+    gas_price: u64,
+    priority_fee: u64,
+    timestamp: Timestamp,
+) -> Vec<DeployHash> {
+    let ttl = TimeDiff::from(Duration::from_secs(3600));
+    (0..count)
+        .map(|_| {
+            let deploy = generate_deploy_with_priority_fee(
+                rng,
+                timestamp,
+                ttl,
+                vec![],
+                default_gas_payment(),
+                gas_price,
+                priority_fee,
+            );
+            proposer.add_deploy_or_transfer(timestamp, *deploy.id(), deploy.deploy_type().unwrap());
+            *deploy.id()
+        })
+        .collect()
+}
+
+/// Proposes a block with at most `max_deploys` wasm deploys and finalizes it, returning the
+/// proposed deploys.
+fn propose_and_finalize(
+    proposer: &mut BlockProposerReady,
+    max_deploys: u32,
+    block_time: Timestamp,
+) -> Vec<DeployHash> {
+    let mut deploy_config = DeployConfig::default();
+    deploy_config.block_max_deploy_count = max_deploys;
+    let block = proposer.propose_proto_block(deploy_config, block_time, HashSet::new(), true);
+    let deploys = block.wasm_deploys().clone();
+    proposer.finalized_deploys(deploys.iter().copied());
+    deploys
+}
+
+#[test]
+fn should_propose_deploys_in_order_of_arrival() {
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer();
+    let timestamp = Timestamp::from(100);
+    let block_time = Timestamp::from(120);
+
+    let mut arrived = add_deploys_with_gas_price(&mut rng, &mut proposer, 3, 1, timestamp);
+    arrived.extend(add_deploys_with_gas_price(
+        &mut rng,
+        &mut proposer,
+        3,
+        10,
+        timestamp,
+    ));
+
+    // Despite their higher gas price, the later deploys have to wait.
+    for expected in arrived.chunks(2) {
+        assert_eq!(
+            propose_and_finalize(&mut proposer, 2, block_time),
+            expected.to_vec()
+        );
+    }
+}
+
+#[test]
+fn should_propose_deploys_by_gas_price_without_fairness() {
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer();
+    proposer.config.fairness = 0.0;
+    let timestamp = Timestamp::from(100);
+    let block_time = Timestamp::from(120);
+
+    let cheap = add_deploys_with_gas_price(&mut rng, &mut proposer, 2, 1, timestamp);
+    let expensive = add_deploys_with_gas_price(&mut rng, &mut proposer, 2, 10, timestamp);
+
+    assert_eq!(
+        propose_and_finalize(&mut proposer, 2, block_time),
+        expensive
+    );
+    assert_eq!(propose_and_finalize(&mut proposer, 2, block_time), cheap);
+}
+
+#[test]
+fn should_propose_deploys_by_priority_fee_at_the_same_gas_price() {
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer();
+    proposer.config.fairness = 0.0;
+    let timestamp = Timestamp::from(100);
+    let block_time = Timestamp::from(120);
+
+    let no_fee = add_deploys_with_priority_fee(&mut rng, &mut proposer, 1, 10, 0, timestamp);
+    let low_fee = add_deploys_with_priority_fee(&mut rng, &mut proposer, 1, 10, 5, timestamp);
+    // Fees above the limit don't buy more priority than the limit itself.
+    let excessive_fee =
+        add_deploys_with_priority_fee(&mut rng, &mut proposer, 1, 10, 1_000, timestamp);
+    let max_fee = add_deploys_with_priority_fee(&mut rng, &mut proposer, 1, 10, 100, timestamp);
+    // A higher gas price still beats any priority fee.
+    let expensive = add_deploys_with_gas_price(&mut rng, &mut proposer, 1, 11, timestamp);
+
+    let mut deploy_config = DeployConfig::default();
+    deploy_config.max_priority_fee = 100;
+    let block = proposer.propose_proto_block(deploy_config, block_time, HashSet::new(), true);
+    let expected: Vec<_> = [expensive, excessive_fee, max_fee, low_fee, no_fee].concat();
+    assert_eq!(*block.wasm_deploys(), expected);
+}
+
+/// Keeps the proposer under constant load: for each block, as many high-priced deploys arrive as
+/// fit into it.  Returns the number of blocks after which a single low-priced deploy which arrived
+/// first was proposed, or `None` if it still wasn't after `max_blocks` blocks.
+fn blocks_until_low_priced_deploy_proposed(fairness: f64, max_blocks: usize) -> Option<usize> {
+    const MAX_DEPLOYS: u32 = 2;
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer();
+    proposer.config.fairness = fairness;
+    let timestamp = Timestamp::from(100);
+    let block_time = Timestamp::from(120);
+
+    let low_priced = add_deploys_with_gas_price(&mut rng, &mut proposer, 1, 1, timestamp)[0];
+    (1..=max_blocks).find(|_| {
+        add_deploys_with_gas_price(&mut rng, &mut proposer, MAX_DEPLOYS as usize, 10, timestamp);
+        propose_and_finalize(&mut proposer, MAX_DEPLOYS, block_time).contains(&low_priced)
+    })
+}
+
+#[test]
+fn should_not_starve_low_priced_deploys_under_constant_load() {
+    assert_eq!(blocks_until_low_priced_deploy_proposed(1.0, 10), Some(1));
+    assert!(blocks_until_low_priced_deploy_proposed(0.5, 10).is_some());
+    // Ordering by gas price alone starves the low-priced deploy.
+    assert_eq!(blocks_until_low_priced_deploy_proposed(0.0, 10), None);
+}
+
+#[test]
+fn should_not_propose_deploys_whose_accounts_cannot_pay() {
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer();
+    let timestamp = Timestamp::from(100);
+    let block_time = Timestamp::from(120);
+
+    let deploys = add_deploys_with_gas_price(&mut rng, &mut proposer, 3, 1, timestamp);
+    let account = |hash: &DeployHash| {
+        proposer.sets.pending[hash]
+            .deploy_type
+            .header()
+            .account()
+            .to_account_hash()
+    };
+    // The first account can pay, the second can't and the third doesn't exist.
+    let mut balances = HashMap::new();
+    balances.insert(account(&deploys[0]), U512::from(1u64));
+    balances.insert(account(&deploys[1]), U512::zero());
+
+    let block = proposer.propose_proto_block_within_balances(
+        DeployConfig::default(),
+        block_time,
+        HashSet::new(),
+        true,
+        &balances,
+    );
+    assert_eq!(block.wasm_deploys(), &vec![deploys[0]]);
+    assert!(block.transfers().is_empty());
+}
+
+#[test]
+fn should_reserve_payment_across_deploys_of_an_account() {
+    let mut rng = crate::new_rng();
+    let ttl = TimeDiff::from(Duration::from_secs(3600));
+    let deploy = generate_deploy(
+        &mut rng,
+        Timestamp::from(100),
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let deploy_type = deploy.deploy_type().unwrap();
+    let payment = deploy_type.payment_amount().value();
+
+    let mut balances = HashMap::new();
+    // The balance covers a single payment only.
+    balances.insert(deploy.header().account().to_account_hash(), payment);
+    let mut reservations = PaymentReservations::new(&balances);
+    assert!(reservations.reserve(&deploy_type));
+    assert!(!reservations.reserve(&deploy_type));
+}
+
+#[test]
+fn should_select_rewarded_signatures_deterministically() {
+    let mut rng = crate::new_rng();
+    let validators: Vec<PublicKey> =
+        iter::repeat_with(|| PublicKey::from(&SecretKey::random(&mut rng)))
+            .take(4)
+            .collect();
+    let weights: BTreeMap<PublicKey, U512> = validators
+        .iter()
+        .map(|validator| (*validator, U512::from(100)))
+        .collect();
+    let mut signed_block = |height: u64, signers: &[PublicKey]| {
+        let block = Block::random_with_specifics(&mut rng, EraId(1), height, false);
+        let mut signatures = BlockSignatures::new(*block.hash(), EraId(1));
+        for signer in signers {
+            let signature = Signature::ed25519([0; Signature::ED25519_LENGTH]).unwrap();
+            signatures.insert_proof(*signer, signature);
+        }
+        BlockWithSignatures {
+            block,
+            signatures,
+            era_validator_weights: Some(weights.clone()),
+        }
+    };
+    let config = SignatureRewardsConfig {
+        max_delay: 3,
+        weight_threshold: Ratio::new(1, 2),
+    };
+    assert_eq!(config.window(2), 0..2);
+    assert_eq!(config.window(13), 10..13);
+
+    // Block 11 lacks signatures of half the weight, and an ancestor of the proposed block already
+    // rewarded the first validator's signature of block 12.
+    let mut window = vec![
+        signed_block(10, &validators[..3]),
+        signed_block(11, &validators[..1]),
+        signed_block(12, &validators),
+    ];
+    let past_rewarded_signatures = vec![RewardedSignatures::new(vec![SignatureCitation {
+        block_hash: *window[2].block.hash(),
+        block_height: 12,
+        signers: iter::once(validators[0]).collect(),
+    }])];
+
+    let selected = signature_rewards::select(&config, &window, &past_rewarded_signatures);
+    let citations = selected.citations();
+    assert_eq!(citations.len(), 2);
+    assert_eq!(citations[0].block_hash, *window[0].block.hash());
+    assert_eq!(citations[0].block_height, 10);
+    assert_eq!(
+        citations[0].signers,
+        validators[..3].iter().copied().collect()
+    );
+    assert_eq!(citations[1].block_height, 12);
+    assert_eq!(
+        citations[1].signers,
+        validators[1..].iter().copied().collect()
+    );
+    assert_eq!(
+        signature_rewards::select(&config, &window, &past_rewarded_signatures),
+        selected
+    );
+
+    // Signatures of blocks of eras with unknown validator weights are not rewarded.
+    for block_with_signatures in &mut window {
+        block_with_signatures.era_validator_weights = None;
+    }
+    assert!(signature_rewards::select(&config, &window, &[]).is_empty());
+}
@@ -6,8 +6,17 @@
 //! When multiple requests are made to validate the same proto block, they will eagerly return true
 //! if valid, but only fail if all sources have been exhausted. This is only relevant when calling
 //! for validation of the same protoblock multiple times at the same time.
+//!
+//! Where the block's deploys don't need to be available locally afterwards, only their headers are
+//! fetched, as these suffice to check the deploys against the chainspec.
+//!
+//! Validation which takes longer than the configured `validation_timeout` is aborted. A failed
+//! validation reports which deploys could not be obtained and which peers were asked for them, so
+//! that the caller can penalize the proposer or retry selectively.
 
+mod config;
 mod keyed_counter;
+mod tests;
 
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
@@ -19,17 +28,21 @@ use std::{
 use datasize::DataSize;
 use derive_more::{Display, From};
 use smallvec::{smallvec, SmallVec};
-use tracing::info;
+use tracing::{debug, info};
 
 use crate::{
     components::Component,
     effect::{
         requests::{BlockValidationRequest, FetcherRequest, StorageRequest},
-        EffectBuilder, EffectExt, EffectOptionExt, Effects, Responder,
+        EffectBuilder, EffectExt, EffectResultExt, Effects, Responder,
+    },
+    types::{
+        BlockLike, Chainspec, Deploy, DeployHash, DeployHeader, DeployHeaderById, TimeDiff,
+        Timestamp,
     },
-    types::{BlockLike, Chainspec, Deploy, DeployHash, Timestamp},
     NodeRng,
 };
+pub use config::Config;
 use keyed_counter::KeyedCounter;
 
 use super::fetcher::FetchResult;
@@ -52,6 +65,64 @@ pub enum Event<T, I> {
     /// Deploy was invalid. Failed the chainspec test.
     #[display(fmt = "deploy {} invalid", _0)]
     DeployInvalid(DeployHash),
+
+    /// The time allowed for validating the block has elapsed.
+    #[display(fmt = "block validation timed out")]
+    ValidationTimedOut(T),
+}
+
+/// What the block validator fetches of each deploy of a block.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq)]
+pub(crate) enum DeployFetchMode {
+    /// Only the deploy headers.
+    Headers,
+    /// The full deploys, for blocks whose deploys must be stored locally once validated, e.g. in
+    /// order to execute them.
+    FullDeploys,
+}
+
+/// A deploy which could not be obtained during block validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingDeploy<I> {
+    /// The hash of the deploy.
+    pub deploy_hash: DeployHash,
+    /// The peers which were asked for the deploy, in the order they were asked.
+    pub asked_peers: Vec<I>,
+}
+
+/// The outcome of a block validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationOutcome<I> {
+    /// All deploys were found and are valid.
+    Valid,
+    /// The deploy with the given hash failed validation against the chainspec.
+    InvalidDeploy(DeployHash),
+    /// The given deploys could not be fetched from any of the peers asked.
+    MissingDeploys(Vec<MissingDeploy<I>>),
+    /// The validation timed out with the given deploys still missing.
+    TimedOut(Vec<MissingDeploy<I>>),
+}
+
+impl<I> ValidationOutcome<I> {
+    /// Returns whether the block was found to be valid.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ValidationOutcome::Valid)
+    }
+}
+
+/// The result of a block validation request.
+#[derive(Debug)]
+pub struct BlockValidationResult<T, I> {
+    /// The block which was validated, unchanged.
+    pub block: T,
+    /// The outcome of the validation.
+    pub outcome: ValidationOutcome<I>,
+}
+
+impl<T, I> BlockValidationResult<T, I> {
+    fn new(block: T, outcome: ValidationOutcome<I>) -> Self {
+        BlockValidationResult { block, outcome }
+    }
 }
 
 /// State of the current process of block validation.
@@ -62,10 +133,14 @@ pub(crate) struct BlockValidationState<T, I> {
     /// The deploys that have not yet been "crossed off" the list of potential misses.
     missing_deploys: HashSet<DeployHash>,
     /// A list of responders that are awaiting an answer.
-    responders: SmallVec<[Responder<(bool, T)>; 2]>,
+    responders: SmallVec<[Responder<BlockValidationResult<T, I>>; 2]>,
     /// Peers that should have the data.
     sources: VecDeque<I>,
+    /// Peers that were asked for each deploy.
+    asked_peers: HashMap<DeployHash, Vec<I>>,
     context: (Arc<Chainspec>, Timestamp),
+    /// The time at which validation started.
+    started: Timestamp,
 }
 
 impl<T, I> BlockValidationState<T, I>
@@ -87,6 +162,30 @@ where
     fn source(&mut self) -> Option<I> {
         self.sources.pop_front()
     }
+
+    /// Records that `peer` was asked for the deploy.
+    fn record_asked(&mut self, deploy_hash: DeployHash, peer: I) {
+        self.asked_peers.entry(deploy_hash).or_default().push(peer);
+    }
+
+    /// Returns the given deploys along with the peers which were asked for them.
+    fn missing<'a, D>(&self, deploy_hashes: D) -> Vec<MissingDeploy<I>>
+    where
+        D: IntoIterator<Item = &'a DeployHash>,
+        I: Clone,
+    {
+        deploy_hashes
+            .into_iter()
+            .map(|deploy_hash| MissingDeploy {
+                deploy_hash: *deploy_hash,
+                asked_peers: self
+                    .asked_peers
+                    .get(deploy_hash)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
 }
 
 #[derive(DataSize, Debug)]
@@ -94,10 +193,14 @@ pub(crate) struct BlockValidator<T, I> {
     /// Chainspec loaded for deploy validation.
     #[data_size(skip)]
     chainspec: Arc<Chainspec>,
+    /// The maximum time a block validation may take.
+    validation_timeout: TimeDiff,
     /// State of validation of a specific block.
     validation_states: HashMap<T, BlockValidationState<T, I>>,
     /// Number of requests for a specific deploy hash still in flight.
     in_flight: KeyedCounter<DeployHash>,
+    /// What is fetched of each deploy.
+    deploy_fetch_mode: DeployFetchMode,
 }
 
 impl<T, I> BlockValidator<T, I>
@@ -106,11 +209,17 @@ where
     I: Clone + Send + 'static + Send,
 {
     /// Creates a new block validator instance.
-    pub(crate) fn new(chainspec: Arc<Chainspec>) -> Self {
+    pub(crate) fn new(
+        chainspec: Arc<Chainspec>,
+        config: Config,
+        deploy_fetch_mode: DeployFetchMode,
+    ) -> Self {
         BlockValidator {
             chainspec,
+            validation_timeout: config.validation_timeout,
             validation_states: HashMap::new(),
             in_flight: KeyedCounter::default(),
+            deploy_fetch_mode,
         }
     }
 }
@@ -122,6 +231,7 @@ where
     REv: From<Event<T, I>>
         + From<BlockValidationRequest<T, I>>
         + From<FetcherRequest<I, Deploy>>
+        + From<FetcherRequest<I, DeployHeaderById>>
         + From<StorageRequest>
         + Send,
 {
@@ -149,7 +259,9 @@ where
                     .collect::<HashSet<_>>();
                 if block_deploys.is_empty() {
                     // If there are no deploys, return early.
-                    return responder.respond((true, block)).ignore();
+                    return responder
+                        .respond(BlockValidationResult::new(block, ValidationOutcome::Valid))
+                        .ignore();
                 }
 
                 // TODO: Clean this up to use `or_insert_with_key` once
@@ -160,7 +272,14 @@ where
                         if entry.get().missing_deploys.is_empty() {
                             // Block has already been validated successfully, early return to
                             // caller.
-                            effects.extend(responder.respond((true, entry.key().clone())).ignore());
+                            effects.extend(
+                                responder
+                                    .respond(BlockValidationResult::new(
+                                        entry.key().clone(),
+                                        ValidationOutcome::Valid,
+                                    ))
+                                    .ignore(),
+                            );
                         } else {
                             // We register ourselves as someone interested in the ultimate
                             // validation result.
@@ -175,6 +294,7 @@ where
                             entry.key().deploys().iter().map(|hash| **hash).collect();
 
                         let in_flight = &mut self.in_flight;
+                        let deploy_fetch_mode = self.deploy_fetch_mode;
                         let chainspec = Arc::clone(&self.chainspec);
                         let fetch_effects: Effects<Event<T, I>> = block_deploys
                             .iter()
@@ -184,6 +304,7 @@ where
                                 // ...then request it.
                                 fetch_deploy(
                                     effect_builder,
+                                    deploy_fetch_mode,
                                     Arc::clone(&chainspec),
                                     block_timestamp,
                                     *deploy_hash,
@@ -193,12 +314,25 @@ where
                             .collect();
                         effects.extend(fetch_effects);
 
+                        let block = entry.key().clone();
+                        effects.extend(
+                            effect_builder
+                                .set_timeout(self.validation_timeout.into())
+                                .event(move |_| Event::ValidationTimedOut(block)),
+                        );
+
+                        let asked_peers = block_deploys
+                            .iter()
+                            .map(|deploy_hash| (*deploy_hash, vec![sender.clone()]))
+                            .collect();
                         entry.insert(BlockValidationState {
                             missing_deploys,
                             responders: smallvec![responder],
                             sources: VecDeque::new(), /* This is empty b/c we create the first
                                                        * request using `sender`. */
+                            asked_peers,
                             context: (chainspec, block_timestamp),
+                            started: effect_builder.now(),
                         });
                     }
                 }
@@ -217,7 +351,14 @@ where
                     if state.missing_deploys.is_empty() {
                         // This one is done and valid.
                         state.responders.drain(..).for_each(|responder| {
-                            effects.extend(responder.respond((true, key.clone())).ignore());
+                            effects.extend(
+                                responder
+                                    .respond(BlockValidationResult::new(
+                                        key.clone(),
+                                        ValidationOutcome::Valid,
+                                    ))
+                                    .ignore(),
+                            );
                         });
                         false
                     } else {
@@ -235,6 +376,7 @@ where
 
                 // Flag indicating whether we've retried fetching the deploy.
                 let mut retried = false;
+                let deploy_fetch_mode = self.deploy_fetch_mode;
 
                 self.validation_states.retain(|key, state| {
                     if !state.missing_deploys.contains(&deploy_hash) {
@@ -251,11 +393,13 @@ where
                             let (chainspec, block_timestamp) = &state.context;
                             effects.extend(
                                 fetch_deploy(effect_builder,
+                                    deploy_fetch_mode,
                                     Arc::clone(chainspec),
                                     *block_timestamp,
                                     deploy_hash,
-                                    peer,
+                                    peer.clone(),
                                 ));
+                            state.record_asked(deploy_hash, peer);
                             retried = true;
                             true
                         },
@@ -264,8 +408,14 @@ where
                             info!(block=?key, %deploy_hash, "could not validate the deploy. block is invalid");
                             // This validation state contains a failed deploy hash, it can never
                             // succeed.
+                            let missing = state.missing(&[deploy_hash]);
                             state.responders.drain(..).for_each(|responder| {
-                                effects.extend(responder.respond((false, key.clone())).ignore());
+                                let outcome = ValidationOutcome::MissingDeploys(missing.clone());
+                                effects.extend(
+                                    responder
+                                        .respond(BlockValidationResult::new(key.clone(), outcome))
+                                        .ignore(),
+                                );
                             });
                             false
                         }
@@ -290,7 +440,14 @@ where
                         // This validation state contains a failed deploy hash, it can never
                         // succeed.
                         state.responders.drain(..).for_each(|responder| {
-                            effects.extend(responder.respond((false, key.clone())).ignore());
+                            effects.extend(
+                                responder
+                                    .respond(BlockValidationResult::new(
+                                        key.clone(),
+                                        ValidationOutcome::InvalidDeploy(deploy_hash),
+                                    ))
+                                    .ignore(),
+                            );
                         });
                         false
                     } else {
@@ -298,14 +455,49 @@ where
                     }
                 });
             }
+            Event::ValidationTimedOut(block) => {
+                let timed_out = match self.validation_states.get(&block) {
+                    // The state may have been recreated by a later request after this timeout was
+                    // scheduled, in which case its own timeout applies.
+                    Some(state) => {
+                        effect_builder.now().saturating_diff(state.started)
+                            >= self.validation_timeout
+                    }
+                    None => false,
+                };
+                if !timed_out {
+                    debug!(?block, "ignoring outdated block validation timeout");
+                    return Effects::new();
+                }
+
+                // Requests still in flight are left untouched: the fetcher will time them out and
+                // any resulting events will no longer find a validation state.
+                if let Some(mut state) = self.validation_states.remove(&block) {
+                    let missing = state.missing(&state.missing_deploys);
+                    info!(
+                        ?block,
+                        missing_deploys = missing.len(),
+                        "block validation timed out"
+                    );
+                    state.responders.drain(..).for_each(|responder| {
+                        let outcome = ValidationOutcome::TimedOut(missing.clone());
+                        effects.extend(
+                            responder
+                                .respond(BlockValidationResult::new(block.clone(), outcome))
+                                .ignore(),
+                        );
+                    });
+                }
+            }
         }
         effects
     }
 }
 
-/// Returns effects that fetch the deploy and validate it.
+/// Returns effects that fetch the deploy, or only its header, and validate it.
 fn fetch_deploy<REv, T, I>(
     effect_builder: EffectBuilder<REv>,
+    deploy_fetch_mode: DeployFetchMode,
     chainspec: Arc<Chainspec>,
     block_timestamp: Timestamp,
     deploy_hash: DeployHash,
@@ -316,24 +508,37 @@ where
         + From<BlockValidationRequest<T, I>>
         + From<StorageRequest>
         + From<FetcherRequest<I, Deploy>>
+        + From<FetcherRequest<I, DeployHeaderById>>
         + Send,
     T: BlockLike + Debug + Send + Clone + 'static,
     I: Clone + Send + PartialEq + Eq + 'static,
 {
-    let validate_deploy = move |result: FetchResult<Deploy, I>| match result {
-        FetchResult::FromStorage(deploy) | FetchResult::FromPeer(deploy, _) => {
-            if deploy
-                .header()
-                .is_valid(&chainspec.deploy_config, block_timestamp)
-            {
-                Event::DeployFound(deploy_hash)
-            } else {
-                Event::DeployInvalid(deploy_hash)
-            }
+    let validate_header = move |header: &DeployHeader| {
+        if header.is_valid(&chainspec.deploy_config, block_timestamp) {
+            Event::DeployFound(deploy_hash)
+        } else {
+            Event::DeployInvalid(deploy_hash)
         }
     };
 
-    effect_builder
-        .fetch_deploy(deploy_hash, sender)
-        .map_or_else(validate_deploy, move || Event::DeployMissing(deploy_hash))
+    match deploy_fetch_mode {
+        DeployFetchMode::Headers => effect_builder
+            .fetch_deploy_header(deploy_hash, sender)
+            .result(
+                move |result: FetchResult<DeployHeaderById, I>| match result {
+                    FetchResult::FromStorage(item) | FetchResult::FromPeer(item, _) => {
+                        validate_header(item.header())
+                    }
+                },
+                move |_| Event::DeployMissing(deploy_hash),
+            ),
+        DeployFetchMode::FullDeploys => effect_builder.fetch_deploy(deploy_hash, sender).result(
+            move |result: FetchResult<Deploy, I>| match result {
+                FetchResult::FromStorage(deploy) | FetchResult::FromPeer(deploy, _) => {
+                    validate_header(deploy.header())
+                }
+            },
+            move |_| Event::DeployMissing(deploy_hash),
+        ),
+    }
 }
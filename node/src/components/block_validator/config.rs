@@ -0,0 +1,23 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::TimeDiff;
+
+const DEFAULT_VALIDATION_TIMEOUT: &str = "2min";
+
+/// Configuration options for block validation.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The maximum time to wait for the deploys of a block before considering it invalid.
+    pub validation_timeout: TimeDiff,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            validation_timeout: DEFAULT_VALIDATION_TIMEOUT.parse().unwrap(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use futures::{channel::oneshot, future};
+
+use super::*;
+use crate::{
+    effect::time_source::ManualClock,
+    testing::{ComponentHarness, TestRng},
+    types::{NodeId, ProtoBlock},
+    utils,
+};
+
+/// The validation timeout of the validators under test.
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, From)]
+enum ReactorEvent {
+    #[from]
+    BlockValidator(Event<ProtoBlock, NodeId>),
+    #[from]
+    BlockValidationRequest(BlockValidationRequest<ProtoBlock, NodeId>),
+    #[from]
+    DeployFetcher(FetcherRequest<NodeId, Deploy>),
+    #[from]
+    DeployHeaderFetcher(FetcherRequest<NodeId, DeployHeaderById>),
+    #[from]
+    Storage(StorageRequest),
+}
+
+type Receiver = oneshot::Receiver<BlockValidationResult<ProtoBlock, NodeId>>;
+
+fn new_validator() -> BlockValidator<ProtoBlock, NodeId> {
+    let config = Config {
+        validation_timeout: VALIDATION_TIMEOUT.into(),
+    };
+    BlockValidator::new(
+        Arc::new(Chainspec::from_resources("local")),
+        config,
+        DeployFetchMode::Headers,
+    )
+}
+
+fn new_block(rng: &mut TestRng, deploy_count: usize) -> ProtoBlock {
+    let deploy_hashes = (0..deploy_count).map(|_| DeployHash::random(rng)).collect();
+    ProtoBlock::new(deploy_hashes, vec![], false)
+}
+
+/// Asks the validator to validate `block` on behalf of `sender`, and returns the effects along
+/// with a receiver for the result.
+fn validate(
+    harness: &mut ComponentHarness<ReactorEvent>,
+    validator: &mut BlockValidator<ProtoBlock, NodeId>,
+    block: &ProtoBlock,
+    sender: &NodeId,
+) -> (Effects<Event<ProtoBlock, NodeId>>, Receiver) {
+    let (result_sender, receiver) = oneshot::channel();
+    let request = BlockValidationRequest {
+        block: block.clone(),
+        sender: sender.clone(),
+        responder: Responder::create(result_sender),
+        block_timestamp: Timestamp::now(),
+    };
+    let effects = harness.send_event(validator, request.into());
+    (effects, receiver)
+}
+
+/// Returns the outcome sent to `receiver`, which must have been responded to already.
+fn outcome(receiver: &mut Receiver) -> ValidationOutcome<NodeId> {
+    receiver
+        .try_recv()
+        .expect("should not be canceled")
+        .expect("should have responded")
+        .outcome
+}
+
+#[test]
+fn should_report_peers_asked_for_missing_deploys() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let mut validator = new_validator();
+    let block = new_block(&mut harness.rng, 1);
+    let deploy_hash = *block.deploys()[0];
+    let proposer = NodeId::random(&mut harness.rng);
+    let other_peer = NodeId::random(&mut harness.rng);
+
+    // The deploy is fetched from the proposer. A second request for the same block only adds the
+    // other peer as a source.
+    let (_, mut first_receiver) = validate(&mut harness, &mut validator, &block, &proposer);
+    let (effects, mut second_receiver) =
+        validate(&mut harness, &mut validator, &block, &other_peer);
+    assert!(effects.is_empty());
+
+    // Once the proposer doesn't deliver, the deploy is fetched from the other peer.
+    let effects = harness.send_event(&mut validator, Event::DeployMissing(deploy_hash));
+    assert_eq!(effects.len(), 1);
+
+    // Once the other peer doesn't deliver either, both requests fail, naming both peers.
+    let effects = harness.send_event(&mut validator, Event::DeployMissing(deploy_hash));
+    harness.runtime.block_on(future::join_all(effects));
+    let expected = ValidationOutcome::MissingDeploys(vec![MissingDeploy {
+        deploy_hash,
+        asked_peers: vec![proposer, other_peer],
+    }]);
+    assert_eq!(outcome(&mut first_receiver), expected);
+    assert_eq!(outcome(&mut second_receiver), expected);
+    assert!(validator.validation_states.is_empty());
+}
+
+#[test]
+fn should_time_out_with_deploys_still_missing() {
+    let clock = utils::leak(ManualClock::new(Timestamp::from(1_000)));
+    let mut harness = ComponentHarness::<ReactorEvent>::builder()
+        .manual_clock(clock)
+        .build();
+    let mut validator = new_validator();
+    let block = new_block(&mut harness.rng, 2);
+    let found = *block.deploys()[0];
+    let missing = *block.deploys()[1];
+    let proposer = NodeId::random(&mut harness.rng);
+
+    let (effects, mut receiver) = validate(&mut harness, &mut validator, &block, &proposer);
+    let mut pending: Vec<_> = effects
+        .into_iter()
+        .map(|effect| harness.runtime.spawn(effect))
+        .collect();
+    let effects = harness.send_event(&mut validator, Event::DeployFound(found));
+    assert!(effects.is_empty());
+
+    // A timeout firing before the validation's own deadline, as if scheduled by an earlier
+    // validation of the same block, is ignored.
+    clock.advance(VALIDATION_TIMEOUT - Duration::from_millis(1));
+    assert_eq!(clock.pending_timeouts(), 1);
+    let effects = harness.send_event(&mut validator, Event::ValidationTimedOut(block.clone()));
+    assert!(effects.is_empty());
+    assert!(receiver
+        .try_recv()
+        .expect("should not be canceled")
+        .is_none());
+
+    // The deploy fetches are never answered, so the first effect to finish is the timeout.
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(clock.pending_timeouts(), 0);
+    let (events, _, _) = harness.runtime.block_on(future::select_all(&mut pending));
+    for event in events.expect("should not panic") {
+        let effects = harness.send_event(&mut validator, event);
+        harness.runtime.block_on(future::join_all(effects));
+    }
+    let expected = ValidationOutcome::TimedOut(vec![MissingDeploy {
+        deploy_hash: missing,
+        asked_peers: vec![proposer],
+    }]);
+    assert_eq!(outcome(&mut receiver), expected);
+    assert!(validator.validation_states.is_empty());
+}
@@ -11,6 +11,7 @@
 #![allow(clippy::field_reassign_with_default)]
 
 use std::{
+    borrow::Cow,
     fmt::{self, Display, Formatter},
     fs,
     path::{Path, PathBuf},
@@ -47,7 +48,7 @@ use crate::{
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, StateStoreRequest, StorageRequest,
         },
-        EffectBuilder, EffectExt, Effects,
+        EffectBuilder, EffectExt, Effects, Responder,
     },
     reactor::ReactorExit,
     types::{
@@ -59,6 +60,14 @@ use crate::{
 };
 
 const STORAGE_KEY: &str = "chainspec loader cached protocol version";
+const RAW_CHAINSPEC_STORAGE_KEY_PREFIX: &str = "chainspec loader raw chainspec ";
+
+/// Returns the state storage key under which the raw chainspec of the given version is kept.
+fn raw_chainspec_storage_key(version: &Version) -> Cow<'static, [u8]> {
+    format!("{}{}", RAW_CHAINSPEC_STORAGE_KEY_PREFIX, version)
+        .into_bytes()
+        .into()
+}
 
 /// `ChainspecHandler` events.
 #[derive(Debug, From, Serialize)]
@@ -161,6 +170,8 @@ impl Display for NextUpgrade {
 #[derive(Clone, DataSize, Debug)]
 pub struct ChainspecLoader {
     chainspec: Arc<Chainspec>,
+    /// The contents of the chainspec file, as read from disk.
+    chainspec_raw_bytes: Arc<Vec<u8>>,
     /// The path to the folder where all chainspec and upgrade_point files will be stored in
     /// subdirs corresponding to their versions.
     root_dir: PathBuf,
@@ -181,8 +192,11 @@ impl ChainspecLoader {
         P: AsRef<Path>,
         REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send,
     {
+        let chainspec_raw_bytes = utils::read_file(chainspec_dir.as_ref().join(CHAINSPEC_NAME))
+            .map_err(Error::LoadChainspec)?;
         Ok(Self::new_with_chainspec_and_path(
             Arc::new(Chainspec::from_path(&chainspec_dir.as_ref())?),
+            chainspec_raw_bytes,
             chainspec_dir,
             effect_builder,
         ))
@@ -196,11 +210,20 @@ impl ChainspecLoader {
     where
         REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send,
     {
-        Self::new_with_chainspec_and_path(chainspec, &RESOURCES_PATH.join("local"), effect_builder)
+        let chainspec_dir = RESOURCES_PATH.join("local");
+        let chainspec_raw_bytes = utils::read_file(chainspec_dir.join(CHAINSPEC_NAME))
+            .expect("should read local chainspec");
+        Self::new_with_chainspec_and_path(
+            chainspec,
+            chainspec_raw_bytes,
+            chainspec_dir,
+            effect_builder,
+        )
     }
 
     fn new_with_chainspec_and_path<P, REv>(
         chainspec: Arc<Chainspec>,
+        chainspec_raw_bytes: Vec<u8>,
         chainspec_dir: P,
         effect_builder: EffectBuilder<REv>,
     ) -> (Self, Effects<Event>)
@@ -257,6 +280,7 @@ impl ChainspecLoader {
 
         let chainspec_loader = ChainspecLoader {
             chainspec,
+            chainspec_raw_bytes: Arc::new(chainspec_raw_bytes),
             root_dir,
             reactor_exit,
             initial_state_root_hash: Digest::default(),
@@ -333,6 +357,16 @@ impl ChainspecLoader {
                 self.chainspec.protocol_config.version.clone(),
             )
             .ignore();
+        // Keep the current chainspec, so that it can still be served once its files have been
+        // removed from the config dir.
+        effects.extend(
+            effect_builder
+                .save_state(
+                    raw_chainspec_storage_key(&self.chainspec.protocol_config.version),
+                    self.chainspec_raw_bytes.to_vec(),
+                )
+                .ignore(),
+        );
 
         let highest_block = match maybe_block {
             Some(block) => {
@@ -558,6 +592,43 @@ impl ChainspecLoader {
         )
     }
 
+    /// Responds with the raw chainspec of the given protocol version, looking it up in the state
+    /// storage and falling back to the version's subdir of the config dir.
+    fn get_chainspec_raw_bytes<REv>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        protocol_version: Version,
+        responder: Responder<Option<Vec<u8>>>,
+    ) -> Effects<Event>
+    where
+        REv: From<StateStoreRequest> + Send,
+    {
+        if protocol_version == self.chainspec.protocol_config.version {
+            return responder
+                .respond(Some(self.chainspec_raw_bytes.to_vec()))
+                .ignore();
+        }
+        let root_dir = self.root_dir.clone();
+        async move {
+            let maybe_stored = effect_builder
+                .load_state(raw_chainspec_storage_key(&protocol_version))
+                .await;
+            let maybe_raw_bytes = match maybe_stored {
+                Some(raw_bytes) => Some(raw_bytes),
+                None => task::spawn_blocking(move || {
+                    installed_chainspec_raw_bytes(&root_dir, &protocol_version)
+                })
+                .await
+                .unwrap_or_else(|error| {
+                    warn!(%error, "failed to join tokio task");
+                    None
+                }),
+            };
+            responder.respond(maybe_raw_bytes).await
+        }
+        .ignore()
+    }
+
     fn check_for_next_upgrade<REv>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event>
     where
         REv: From<ChainspecLoaderAnnouncement> + Send,
@@ -626,6 +697,13 @@ where
             Event::Request(ChainspecLoaderRequest::GetChainspecInfo(responder)) => {
                 responder.respond(self.new_chainspec_info()).ignore()
             }
+            Event::Request(ChainspecLoaderRequest::GetChainspec(responder)) => {
+                responder.respond(Arc::clone(&self.chainspec)).ignore()
+            }
+            Event::Request(ChainspecLoaderRequest::GetChainspecRawBytes {
+                protocol_version,
+                responder,
+            }) => self.get_chainspec_raw_bytes(effect_builder, protocol_version, responder),
             Event::CheckForNextUpgrade => self.check_for_next_upgrade(effect_builder),
             Event::GotNextUpgrade(next_upgrade) => self.handle_got_next_upgrade(next_upgrade),
             Event::PutToStorage { version } => {
@@ -713,6 +791,21 @@ fn next_installed_version(dir: &Path, current_version: &Version) -> Result<Versi
     Ok(next_version)
 }
 
+/// Reads the chainspec file installed in the subdir of `dir` for the given version, returning
+/// `None` if it can't be read.
+fn installed_chainspec_raw_bytes(dir: &Path, version: &Version) -> Option<Vec<u8>> {
+    let path = dir
+        .join(dir_name_from_version(version))
+        .join(CHAINSPEC_NAME);
+    match utils::read_file(&path) {
+        Ok(raw_bytes) => Some(raw_bytes),
+        Err(error) => {
+            debug!(path=%path.display(), %error, "failed to read installed chainspec");
+            None
+        }
+    }
+}
+
 /// Uses `next_installed_version()` to find the next versioned subdir.  If it exists, reads the
 /// UpgradePoint file from there and returns its version and activation point.  Returns `None` if
 /// there is no greater version available, or if any step errors.
@@ -930,4 +1023,32 @@ mod tests {
         fs::remove_file(&path_v1_0_0).unwrap();
         assert!(maybe_next_point(&current).is_none());
     }
+
+    #[test]
+    fn should_read_installed_chainspec_raw_bytes() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_0_0 = Version::new(1, 0, 0);
+        install_chainspec(&mut rng, tempdir.path(), &v1_0_0);
+        let installed = fs::read(
+            tempdir
+                .path()
+                .join(dir_name_from_version(&v1_0_0))
+                .join(CHAINSPEC_NAME),
+        )
+        .unwrap();
+        assert_eq!(
+            installed_chainspec_raw_bytes(tempdir.path(), &v1_0_0),
+            Some(installed)
+        );
+
+        let v1_0_1 = Version::new(1, 0, 1);
+        assert!(installed_chainspec_raw_bytes(tempdir.path(), &v1_0_1).is_none());
+
+        assert_ne!(
+            raw_chainspec_storage_key(&v1_0_0),
+            raw_chainspec_storage_key(&v1_0_1)
+        );
+    }
 }
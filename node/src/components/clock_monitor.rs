@@ -0,0 +1,207 @@
+//! Clock monitor.
+//!
+//! Consensus relies on the local clock being close to those of the other validators.  The clock
+//! monitor periodically queries the configured NTP servers to estimate the drift of the local
+//! clock, exports it as a metric and warns if it exceeds the configured threshold.  If the drift
+//! exceeds the configured limit for proposing, it announces this, so that the node refrains from
+//! proposing blocks with timestamps the other validators would consider invalid.
+
+mod config;
+mod metrics;
+mod sntp;
+
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use prometheus::Registry;
+use tokio::task;
+use tracing::{debug, info, warn};
+
+use crate::{
+    components::Component,
+    effect::{announcements::ClockMonitorAnnouncement, EffectBuilder, EffectExt, Effects},
+    utils::{Job, Scheduler},
+    NodeRng,
+};
+pub use config::Config;
+use metrics::ClockMonitorMetrics;
+
+/// Clock monitor events.
+#[derive(Debug)]
+pub enum Event {
+    /// Time to check the drift of the local clock.
+    CheckDrift,
+    /// The drift of the local clock was measured, in milliseconds.  `None` if no NTP server
+    /// responded.
+    DriftMeasured(Option<i64>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::CheckDrift => write!(formatter, "check clock drift"),
+            Event::DriftMeasured(Some(drift_ms)) => {
+                write!(formatter, "measured clock drift of {} ms", drift_ms)
+            }
+            Event::DriftMeasured(None) => write!(formatter, "failed to measure clock drift"),
+        }
+    }
+}
+
+/// Clock monitor component.
+#[derive(DataSize, Debug)]
+pub(crate) struct ClockMonitor {
+    config: Config,
+    /// Whether the last measured drift exceeded `max_drift_to_propose`.
+    exceeds_proposal_limit: bool,
+    #[data_size(skip)]
+    metrics: ClockMonitorMetrics,
+    /// Scheduler of the periodic checks.
+    #[data_size(skip)]
+    scheduler: Scheduler,
+}
+
+impl ClockMonitor {
+    /// Creates a new clock monitor, scheduling the first check if any NTP servers are configured.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        registry: &Registry,
+        effect_builder: EffectBuilder<REv>,
+        scheduler: Scheduler,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        let effects = if config.ntp_servers.is_empty() {
+            info!("no NTP servers configured, not monitoring clock drift");
+            Effects::new()
+        } else {
+            effect_builder.immediately().event(|_| Event::CheckDrift)
+        };
+        let clock_monitor = ClockMonitor {
+            config,
+            exceeds_proposal_limit: false,
+            metrics: ClockMonitorMetrics::new(registry)?,
+            scheduler,
+        };
+        Ok((clock_monitor, effects))
+    }
+
+    /// Queries all NTP servers and returns the median of the measured drifts, in milliseconds.
+    pub(crate) fn measure_drift(servers: Vec<String>, timeout: Duration) -> Option<i64> {
+        let mut drifts: Vec<i64> = servers
+            .iter()
+            .filter_map(|server| match sntp::query_drift(server, timeout) {
+                Ok(drift_ms) => {
+                    debug!(%server, %drift_ms, "queried NTP server");
+                    Some(drift_ms)
+                }
+                Err(error) => {
+                    debug!(%server, %error, "failed to query NTP server");
+                    None
+                }
+            })
+            .collect();
+        if drifts.is_empty() {
+            return None;
+        }
+        drifts.sort_unstable();
+        Some(drifts[drifts.len() / 2])
+    }
+
+    fn handle_drift_measured<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        maybe_drift_ms: Option<i64>,
+    ) -> Effects<Event>
+    where
+        REv: From<ClockMonitorAnnouncement> + Send,
+    {
+        let job = Job::new(
+            "clock_monitor_check_drift",
+            self.config.check_interval.into(),
+        );
+        let mut effects = self
+            .scheduler
+            .schedule(effect_builder, job, || Event::CheckDrift);
+
+        let drift_ms = match maybe_drift_ms {
+            Some(drift_ms) => drift_ms,
+            None => {
+                warn!("failed to query any NTP server, clock drift unknown");
+                self.metrics.failed_checks.inc();
+                return effects;
+            }
+        };
+        self.metrics.drift.set(drift_ms);
+
+        let abs_drift_ms = drift_ms.saturating_abs() as u64;
+        if abs_drift_ms > self.config.warn_threshold.millis() {
+            warn!(
+                %drift_ms,
+                warn_threshold = %self.config.warn_threshold,
+                "local clock drift exceeds threshold, please check the time synchronization"
+            );
+        } else {
+            debug!(%drift_ms, "measured clock drift");
+        }
+
+        if let Some(max_drift) = self.config.max_drift_to_propose {
+            let exceeds_proposal_limit = abs_drift_ms > max_drift.millis();
+            if exceeds_proposal_limit != self.exceeds_proposal_limit {
+                self.exceeds_proposal_limit = exceeds_proposal_limit;
+                if exceeds_proposal_limit {
+                    warn!(%drift_ms, %max_drift, "clock drift too large, suspending proposing");
+                    effects.extend(
+                        effect_builder
+                            .announce_excessive_clock_drift(drift_ms)
+                            .ignore(),
+                    );
+                } else {
+                    info!(%drift_ms, "clock drift back within limit, resuming proposing");
+                    effects.extend(
+                        effect_builder
+                            .announce_clock_drift_recovered(drift_ms)
+                            .ignore(),
+                    );
+                }
+            }
+        }
+        effects
+    }
+}
+
+impl<REv> Component<REv> for ClockMonitor
+where
+    REv: From<ClockMonitorAnnouncement> + Send,
+{
+    type Event = Event;
+    type ConstructionError = Infallible;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::CheckDrift => {
+                let servers = self.config.ntp_servers.clone();
+                let timeout = self.config.query_timeout.into();
+                async move {
+                    task::spawn_blocking(move || Self::measure_drift(servers, timeout))
+                        .await
+                        .unwrap_or_else(|error| {
+                            warn!(%error, "failed to join tokio task");
+                            None
+                        })
+                }
+                .event(Event::DriftMeasured)
+            }
+            Event::DriftMeasured(maybe_drift_ms) => {
+                self.handle_drift_measured(effect_builder, maybe_drift_ms)
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::TimeDiff;
+
+const DEFAULT_CHECK_INTERVAL: &str = "5min";
+const DEFAULT_QUERY_TIMEOUT: &str = "5sec";
+const DEFAULT_WARN_THRESHOLD: &str = "500ms";
+
+/// Configuration options for monitoring the drift of the local clock.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Addresses of the NTP servers to query, in the form "host:port".  The clock is not monitored
+    /// if this is empty.
+    pub ntp_servers: Vec<String>,
+
+    /// The interval between two checks of the clock drift.
+    pub check_interval: TimeDiff,
+
+    /// The maximum time to wait for a response from an NTP server.
+    pub query_timeout: TimeDiff,
+
+    /// The drift above which a warning is logged.
+    pub warn_threshold: TimeDiff,
+
+    /// The drift above which the node stops proposing blocks, until the clock is corrected.  If
+    /// unset, the node always proposes blocks.
+    pub max_drift_to_propose: Option<TimeDiff>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            ntp_servers: vec![],
+            check_interval: DEFAULT_CHECK_INTERVAL.parse().unwrap(),
+            query_timeout: DEFAULT_QUERY_TIMEOUT.parse().unwrap(),
+            warn_threshold: DEFAULT_WARN_THRESHOLD.parse().unwrap(),
+            max_drift_to_propose: None,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use prometheus::{IntCounter, IntGauge, Registry};
+
+use crate::unregister_metric;
+
+/// Metrics for the clock monitor.
+#[derive(Debug)]
+pub(super) struct ClockMonitorMetrics {
+    /// The last measured drift of the local clock, in milliseconds.
+    pub(super) drift: IntGauge,
+    /// Number of checks which failed to get a response from any NTP server.
+    pub(super) failed_checks: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl ClockMonitorMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let drift = IntGauge::new(
+            "clock_drift_ms",
+            "last measured drift of the local clock relative to the NTP servers, in milliseconds",
+        )?;
+        let failed_checks = IntCounter::new(
+            "clock_drift_failed_checks",
+            "number of clock drift checks without a response from any NTP server",
+        )?;
+        registry.register(Box::new(drift.clone()))?;
+        registry.register(Box::new(failed_checks.clone()))?;
+
+        Ok(ClockMonitorMetrics {
+            drift,
+            failed_checks,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for ClockMonitorMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.drift);
+        unregister_metric!(self.registry, self.failed_checks);
+    }
+}
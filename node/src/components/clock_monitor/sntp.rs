@@ -0,0 +1,158 @@
+//! A minimal SNTP client (RFC 4330), used to measure the offset of the local clock.
+
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+/// The length of an NTP packet without extensions.
+const PACKET_LENGTH: usize = 48;
+/// The first byte of a request: leap indicator 0, version 4, mode 3 (client).
+const REQUEST_HEADER: u8 = 0x23;
+/// The mode of a server response.
+const SERVER_MODE: u8 = 4;
+/// The number of seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+/// An error querying an NTP server.
+#[derive(Debug, Error)]
+pub(super) enum Error {
+    /// Failed to send the request or receive the response.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The server address did not resolve.
+    #[error("address did not resolve")]
+    Unresolved,
+    /// The response was malformed or unusable.
+    #[error("invalid response: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// Queries the given NTP server and returns the drift of the local clock in milliseconds.
+///
+/// The drift is positive if the local clock is ahead of the server's.
+pub(super) fn query_drift(server: &str, timeout: Duration) -> Result<i64, Error> {
+    let address = server.to_socket_addrs()?.next().ok_or(Error::Unresolved)?;
+    let local_address: SocketAddr = match address {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local_address)?;
+    socket.set_read_timeout(Some(timeout))?;
+    socket.set_write_timeout(Some(timeout))?;
+    socket.connect(address)?;
+
+    let mut request = [0u8; PACKET_LENGTH];
+    request[0] = REQUEST_HEADER;
+    let sent = ntp_timestamp(SystemTime::now());
+    request[40..48].copy_from_slice(&sent.to_be_bytes());
+    socket.send(&request)?;
+
+    let mut response = [0u8; PACKET_LENGTH];
+    let length = socket.recv(&mut response)?;
+    let received = ntp_timestamp(SystemTime::now());
+    if length < PACKET_LENGTH {
+        return Err(Error::InvalidResponse("packet too short"));
+    }
+    drift_millis(sent, &response, received)
+}
+
+/// Computes the drift of the local clock from a server response, given the local times at which
+/// the request was sent and the response received.
+fn drift_millis(sent: u64, response: &[u8; PACKET_LENGTH], received: u64) -> Result<i64, Error> {
+    if response[0] & 0x07 != SERVER_MODE {
+        return Err(Error::InvalidResponse("not a server response"));
+    }
+    // Stratum 0 is a "kiss-o'-death" packet, e.g. asking us to back off.
+    if response[1] == 0 {
+        return Err(Error::InvalidResponse("kiss-o'-death"));
+    }
+    if read_timestamp(response, 24) != sent {
+        return Err(Error::InvalidResponse(
+            "origin timestamp does not match request",
+        ));
+    }
+    let server_received = read_timestamp(response, 32);
+    let server_sent = read_timestamp(response, 40);
+    if server_sent == 0 {
+        return Err(Error::InvalidResponse("missing transmit timestamp"));
+    }
+
+    // The offset of the server's clock relative to ours, in units of 2^-32 seconds.
+    let offset = ((i128::from(server_received) - i128::from(sent))
+        + (i128::from(server_sent) - i128::from(received)))
+        / 2;
+    Ok(((-offset * 1000) >> 32) as i64)
+}
+
+fn read_timestamp(packet: &[u8; PACKET_LENGTH], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&packet[offset..offset + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Converts a system time into a 64-bit NTP timestamp, with 32 bits each for the seconds and the
+/// fraction of a second.
+fn ntp_timestamp(time: SystemTime) -> u64 {
+    let since_unix_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_unix_epoch.as_secs() + NTP_UNIX_OFFSET_SECS;
+    let fraction = (u64::from(since_unix_epoch.subsec_nanos()) << 32) / 1_000_000_000;
+    (secs << 32) | fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(sent: u64, server_received: u64, server_sent: u64) -> [u8; PACKET_LENGTH] {
+        let mut packet = [0u8; PACKET_LENGTH];
+        packet[0] = 0x24;
+        packet[1] = 2;
+        packet[24..32].copy_from_slice(&sent.to_be_bytes());
+        packet[32..40].copy_from_slice(&server_received.to_be_bytes());
+        packet[40..48].copy_from_slice(&server_sent.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn should_compute_drift() {
+        let now = SystemTime::now();
+        let at = |millis: i64| {
+            if millis >= 0 {
+                ntp_timestamp(now + Duration::from_millis(millis as u64))
+            } else {
+                ntp_timestamp(now - Duration::from_millis(-millis as u64))
+            }
+        };
+
+        // The server's clock is 1.5 seconds behind ours, with 20 ms of latency each way.
+        let sent = at(0);
+        let packet = response(sent, at(20 - 1_500), at(21 - 1_500));
+        let drift = drift_millis(sent, &packet, at(41)).unwrap();
+        assert!((1_499..=1_501).contains(&drift), "drift: {}", drift);
+
+        // The server's clock is 300 ms ahead of ours.
+        let packet = response(sent, at(10 + 300), at(10 + 300));
+        let drift = drift_millis(sent, &packet, at(20)).unwrap();
+        assert!((-301..=-299).contains(&drift), "drift: {}", drift);
+    }
+
+    #[test]
+    fn should_reject_invalid_responses() {
+        let sent = ntp_timestamp(SystemTime::now());
+
+        let mut packet = response(sent, sent, sent);
+        packet[1] = 0;
+        assert!(drift_millis(sent, &packet, sent).is_err());
+
+        let packet = response(sent + 1, sent, sent);
+        assert!(drift_millis(sent, &packet, sent).is_err());
+
+        let mut packet = response(sent, sent, sent);
+        packet[0] = REQUEST_HEADER;
+        assert!(drift_millis(sent, &packet, sent).is_err());
+    }
+}
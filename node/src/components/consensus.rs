@@ -4,6 +4,7 @@ mod candidate_block;
 mod cl_context;
 mod config;
 mod consensus_protocol;
+mod deferred_messages;
 mod era_supervisor;
 #[macro_use]
 mod highway_core;
@@ -38,16 +39,17 @@ use crate::{
             ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, LinearChainRequest,
             NetworkRequest, StorageRequest,
         },
-        EffectBuilder, Effects,
+        EffectBuilder, Effects, Responder,
     },
     protocol::Message,
-    types::{ActivationPoint, Block, BlockHash, BlockHeader, ProtoBlock, Timestamp},
+    types::{ActivationPoint, Block, BlockHash, BlockHeader, ProtoBlock, TimeDiff, Timestamp},
     NodeRng,
 };
 
-pub use config::Config;
+pub use config::{CatchUpConfig, Config};
 pub(crate) use consensus_protocol::{BlockContext, EraReport};
-pub(crate) use era_supervisor::{EraId, EraSupervisor};
+pub(crate) use deferred_messages::DeferredMessages;
+pub(crate) use era_supervisor::{EraId, EraSupervisor, SigningKey, SigningKeyStatus};
 pub(crate) use protocols::highway::HighwayProtocol;
 use traits::NodeIdT;
 
@@ -60,6 +62,16 @@ pub enum ConsensusMessage {
     EvidenceRequest { era_id: EraId, pub_key: PublicKey },
 }
 
+impl ConsensusMessage {
+    /// Returns the era the message belongs to.
+    pub(crate) fn era_id(&self) -> EraId {
+        match self {
+            ConsensusMessage::Protocol { era_id, .. }
+            | ConsensusMessage::EvidenceRequest { era_id, .. } => *era_id,
+        }
+    }
+}
+
 /// An ID to distinguish different timers. What they are used for is specific to each consensus
 /// protocol implementation.
 #[derive(DataSize, Clone, Copy, Debug, Eq, PartialEq)]
@@ -128,8 +140,26 @@ pub enum Event<I> {
     Shutdown,
     /// An event fired when the joiner reactor transitions into validator.
     FinishedJoining(Timestamp),
+    /// The signing key was cross-checked against the validator set and the bids after joining.
+    SigningKeyChecked {
+        joined_at: Timestamp,
+        status: SigningKeyStatus,
+    },
+    /// The secret key to be staged as the signing key from the given era on was loaded.
+    SigningKeyLoaded {
+        activation_era: EraId,
+        #[data_size(skip)]
+        result: Result<SigningKey, String>,
+        responder: Responder<Result<PublicKey, String>>,
+    },
     /// Got the result of checking for an upgrade activation point.
     GotUpgradeActivationPoint(ActivationPoint),
+    /// Stop or resume proposing blocks, e.g. because of excessive local clock drift.
+    SuspendProposing(bool),
+    /// The catch-up thresholds were changed by reloading the config.
+    CatchUpConfigUpdated(CatchUpConfig),
+    /// A finalized block was executed, taking the given time.
+    BlockExecuted(TimeDiff),
 }
 
 impl Debug for ConsensusMessage {
@@ -228,9 +258,21 @@ impl<I: Debug> Display for Event<I> {
             Event::FinishedJoining(timestamp) => {
                 write!(f, "The node finished joining the network at {}", timestamp)
             }
+            Event::SigningKeyChecked { status, .. } => write!(f, "signing key is {}", status),
+            Event::SigningKeyLoaded { activation_era, .. } => write!(
+                f,
+                "loaded signing key to be staged from {} on",
+                activation_era
+            ),
             Event::GotUpgradeActivationPoint(activation_point) => {
                 write!(f, "new upgrade activation point: {:?}", activation_point)
             }
+            Event::SuspendProposing(true) => write!(f, "suspend proposing blocks"),
+            Event::SuspendProposing(false) => write!(f, "resume proposing blocks"),
+            Event::CatchUpConfigUpdated(config) => {
+                write!(f, "catch-up config updated: {:?}", config)
+            }
+            Event::BlockExecuted(latency) => write!(f, "block executed in {}", latency),
         }
     }
 }
@@ -354,16 +396,35 @@ where
                 effects
             }
             Event::Shutdown => handling_es.shutdown_if_necessary(),
-            Event::FinishedJoining(timestamp) => handling_es.finished_joining(timestamp),
+            Event::FinishedJoining(timestamp) => handling_es.check_signing_key(timestamp),
+            Event::SigningKeyChecked { joined_at, status } => {
+                handling_es.signing_key_checked(joined_at, status)
+            }
+            Event::SigningKeyLoaded {
+                activation_era,
+                result,
+                responder,
+            } => handling_es.signing_key_loaded(activation_era, result, responder),
             Event::GotUpgradeActivationPoint(activation_point) => {
                 handling_es.got_upgrade_activation_point(activation_point)
             }
+            Event::SuspendProposing(suspended) => handling_es.suspend_proposing(suspended),
+            Event::CatchUpConfigUpdated(config) => handling_es.update_catch_up_config(config),
+            Event::BlockExecuted(latency) => handling_es.record_execution_latency(latency),
             Event::ConsensusRequest(ConsensusRequest::IsBondedValidator(era_id, pk, responder)) => {
                 handling_es.is_bonded_validator(era_id, pk, responder)
             }
+            Event::ConsensusRequest(ConsensusRequest::ValidatorWeights(era_id, responder)) => {
+                handling_es.validator_weights(era_id, responder)
+            }
             Event::ConsensusRequest(ConsensusRequest::Status(responder)) => {
                 handling_es.status(responder)
             }
+            Event::ConsensusRequest(ConsensusRequest::StageSigningKey(
+                secret_key_path,
+                activation_era,
+                responder,
+            )) => handling_es.stage_signing_key(secret_key_path, activation_era, responder),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use datasize::DataSize;
+use num::rational::Ratio;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +28,58 @@ pub struct Config {
     /// The maximum number of blocks by which execution is allowed to lag behind finalization.
     /// If it is more than that, consensus will pause, and resume once the executor has caught up.
     pub max_execution_delay: u64,
+    /// When to stop proposing blocks because execution fell behind finalization.  Can be changed
+    /// at runtime by reloading the config.
+    pub catch_up: CatchUpConfig,
+    /// The maximum number of future eras for which consensus messages received while joining
+    /// are kept until the node starts participating.
+    pub max_deferred_eras: usize,
+    /// The maximum number of consensus messages kept per era while joining.
+    pub max_deferred_messages_per_era: usize,
+    /// A new signing key to switch to at the start of a future era, if any.
+    #[serde(default)]
+    pub key_rotation: Option<KeyRotationConfig>,
+    /// Whether to exit instead of participating in consensus if the signing key isn't bonded in
+    /// the current era, or has no active bid.
+    pub require_bonded_signing_key: bool,
+}
+
+/// Thresholds for a validator to stop proposing while its block execution catches up.
+///
+/// Proposing is suspended once more than `start_lag` finalized blocks are awaiting execution, and
+/// resumed once at most `resume_lag` are left.
+#[derive(Copy, DataSize, Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CatchUpConfig {
+    /// Whether to suspend proposing while catching up at all.
+    pub enabled: bool,
+    /// The number of finalized blocks awaiting execution above which we start catching up.
+    pub start_lag: u64,
+    /// The number of finalized blocks awaiting execution at or below which we resume proposing.
+    pub resume_lag: u64,
+}
+
+impl Default for CatchUpConfig {
+    fn default() -> Self {
+        CatchUpConfig {
+            enabled: true,
+            start_lag: 10,
+            resume_lag: 1,
+        }
+    }
+}
+
+/// A signing key to be used from a given era on.
+#[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct KeyRotationConfig {
+    /// Path to the new secret key file.
+    pub secret_key_path: External<SecretKey>,
+    /// The first era in which the new key is used instead of `secret_key_path`.
+    ///
+    /// The new key has to be bonded as a validator from this era on, and the old one should be
+    /// unbonded from it, since the old key isn't used for voting anymore.
+    pub activation_era: EraId,
 }
 
 impl Default for Config {
@@ -36,6 +89,11 @@ impl Default for Config {
             unit_hashes_folder: Default::default(),
             pending_vertex_timeout: "10sec".parse().unwrap(),
             max_execution_delay: 3,
+            catch_up: CatchUpConfig::default(),
+            max_deferred_eras: 2,
+            max_deferred_messages_per_era: 10_000,
+            key_rotation: None,
+            require_bonded_signing_key: false,
         }
     }
 }
@@ -44,6 +102,10 @@ impl Default for Config {
 #[derive(DataSize, Debug)]
 pub(crate) struct ProtocolConfig {
     pub(crate) highway_config: HighwayConfig,
+    /// The fraction of the total weight that faulty validators may have without compromising
+    /// finality.
+    #[data_size(skip)]
+    pub(crate) finality_threshold_fraction: Ratio<u64>,
     pub(crate) era_duration: TimeDiff,
     pub(crate) minimum_era_height: u64,
     /// Number of eras before an auction actually defines the set of validators.
@@ -62,12 +124,19 @@ pub(crate) struct ProtocolConfig {
     pub(crate) genesis_timestamp: Option<Timestamp>,
     /// The chainspec hash: All nodes in the network agree on it, and it's unique to this network.
     pub(crate) chainspec_hash: Digest,
+    /// The number of most recent finalized blocks whose finality signatures a proposer rewards.
+    pub(crate) signature_rewards_max_delay: u64,
+    /// The fraction of the total weight that must have signed a block before its signatures are
+    /// rewarded.
+    #[data_size(skip)]
+    pub(crate) signature_rewards_weight_threshold: Ratio<u64>,
 }
 
 impl From<&Chainspec> for ProtocolConfig {
     fn from(chainspec: &Chainspec) -> Self {
         ProtocolConfig {
             highway_config: chainspec.highway_config,
+            finality_threshold_fraction: chainspec.core_config.finality_threshold_fraction,
             era_duration: chainspec.core_config.era_duration,
             minimum_era_height: chainspec.core_config.minimum_era_height,
             auction_delay: chainspec.core_config.auction_delay,
@@ -80,6 +149,10 @@ impl From<&Chainspec> for ProtocolConfig {
                 .activation_point
                 .genesis_timestamp(),
             chainspec_hash: chainspec.hash(),
+            signature_rewards_max_delay: chainspec.core_config.signature_rewards_max_delay,
+            signature_rewards_weight_threshold: chainspec
+                .core_config
+                .signature_rewards_weight_threshold,
         }
     }
 }
@@ -192,6 +192,10 @@ pub(crate) trait ConsensusProtocol<I, C: Context>: Send {
     /// Sets the pause status: While paused we don't create consensus messages other than pings.
     fn set_paused(&mut self, paused: bool);
 
+    /// Sets the recent time it took to execute a finalized block, so that the protocol can avoid
+    /// finalizing blocks faster than this node can execute them.
+    fn set_execution_latency(&mut self, latency: TimeDiff);
+
     /// Returns the list of all validators that were observed as faulty in this consensus instance.
     fn validators_with_evidence(&self) -> Vec<&C::ValidatorId>;
 
@@ -0,0 +1,108 @@
+//! A bounded buffer of consensus messages which cannot be handled yet.
+//!
+//! While the node is joining, consensus messages arrive for eras the era supervisor has not
+//! created yet.  Instead of being dropped, they are kept here and handed to the consensus
+//! component once it is running.
+
+use std::collections::BTreeMap;
+
+use datasize::DataSize;
+use tracing::debug;
+
+use super::{ConsensusMessage, EraId};
+
+/// Consensus messages deferred until their era can be handled, grouped by era.
+#[derive(DataSize, Debug)]
+pub(crate) struct DeferredMessages<I> {
+    /// The deferred messages and their senders, by era.
+    messages: BTreeMap<EraId, Vec<(I, ConsensusMessage)>>,
+    /// The maximum number of eras for which messages are kept.
+    max_eras: usize,
+    /// The maximum number of messages kept per era.
+    max_messages_per_era: usize,
+}
+
+impl<I> DeferredMessages<I> {
+    pub(crate) fn new(max_eras: usize, max_messages_per_era: usize) -> Self {
+        DeferredMessages {
+            messages: BTreeMap::new(),
+            max_eras,
+            max_messages_per_era,
+        }
+    }
+
+    /// Defers a message, returning `false` if it was dropped because the buffer is full.
+    ///
+    /// When the number of eras is at its limit, messages for an earlier era displace those of the
+    /// latest buffered era, since earlier eras will be needed first.
+    pub(crate) fn push(&mut self, sender: I, msg: ConsensusMessage) -> bool {
+        let era_id = msg.era_id();
+        if !self.messages.contains_key(&era_id) && self.messages.len() >= self.max_eras {
+            match self.messages.keys().next_back().copied() {
+                Some(latest_era_id) if latest_era_id > era_id => {
+                    debug!(
+                        era = latest_era_id.0,
+                        "dropping deferred consensus messages for a later era"
+                    );
+                    self.messages.remove(&latest_era_id);
+                }
+                _ => return false,
+            }
+        }
+        let era_messages = self.messages.entry(era_id).or_default();
+        if era_messages.len() >= self.max_messages_per_era {
+            return false;
+        }
+        era_messages.push((sender, msg));
+        true
+    }
+
+    /// Discards all messages for eras before `era_id`.
+    pub(crate) fn discard_before(&mut self, era_id: EraId) {
+        self.messages = self.messages.split_off(&era_id);
+    }
+
+    /// Returns the total number of deferred messages.
+    pub(crate) fn len(&self) -> usize {
+        self.messages.values().map(Vec::len).sum()
+    }
+
+    /// Returns all deferred messages, ordered by era and, within an era, by arrival.
+    pub(crate) fn into_messages(self) -> impl Iterator<Item = (I, ConsensusMessage)> {
+        self.messages.into_iter().flat_map(|(_, messages)| messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(era_id: u64) -> ConsensusMessage {
+        EraId(era_id).message(vec![])
+    }
+
+    fn era_ids(deferred: DeferredMessages<u8>) -> Vec<u64> {
+        deferred
+            .into_messages()
+            .map(|(_, msg)| msg.era_id().0)
+            .collect()
+    }
+
+    #[test]
+    fn should_cap_messages_per_era_and_prefer_earlier_eras() {
+        let mut deferred = DeferredMessages::new(2, 2);
+
+        assert!(deferred.push(0, message(5)));
+        assert!(deferred.push(1, message(5)));
+        assert!(!deferred.push(2, message(5)));
+
+        assert!(deferred.push(0, message(7)));
+        // A third era is only accepted if it precedes the latest one, which it then displaces.
+        assert!(!deferred.push(0, message(8)));
+        assert!(deferred.push(0, message(6)));
+        assert_eq!(deferred.len(), 3);
+
+        deferred.discard_before(EraId(6));
+        assert_eq!(era_ids(deferred), vec![6]);
+    }
+}
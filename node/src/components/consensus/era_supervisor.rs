@@ -5,15 +5,18 @@
 //! it assumes is the concept of era/epoch and that each era runs separate consensus instance.
 //! Most importantly, it doesn't care about what messages it's forwarding.
 
+mod catch_up;
 mod era;
 mod era_id;
+mod signing_keys;
+mod stake_check;
 
 use std::{
-    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     convert::TryInto,
     fmt::{self, Debug, Formatter},
+    ops::Range,
     path::PathBuf,
-    sync::Arc,
     time::Duration,
 };
 
@@ -25,11 +28,20 @@ use blake2::{
 use datasize::DataSize;
 use futures::FutureExt;
 use itertools::Itertools;
+use num::rational::Ratio;
 use prometheus::Registry;
 use rand::Rng;
+use tokio::task;
 use tracing::{debug, error, info, trace, warn};
 
-use casper_types::{AsymmetricType, ProtocolVersion, PublicKey, SecretKey, U512};
+use casper_execution_engine::{
+    core::engine_state::{QueryRequest, QueryResult},
+    shared::stored_value::StoredValue,
+};
+use casper_types::{
+    system::auction::ValidatorWeights, AsymmetricType, Key, ProtocolVersion, PublicKey, SecretKey,
+    U512,
+};
 
 use crate::{
     components::{
@@ -43,41 +55,60 @@ use crate::{
             },
             metrics::ConsensusMetrics,
             traits::NodeIdT,
-            ActionId, Config, ConsensusMessage, Event, ReactorEventT, TimerId,
+            ActionId, CatchUpConfig, Config, ConsensusMessage, Event, ReactorEventT, TimerId,
         },
         contract_runtime::EraValidatorsRequest,
     },
-    crypto::hash::Digest,
-    effect::{requests::ConsensusRequest, EffectBuilder, EffectExt, Effects, Responder},
+    crypto::{hash::Digest, AsymmetricKeyExt},
+    effect::{
+        requests::{ConsensusRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects, Responder,
+    },
     fatal,
     types::{
-        ActivationPoint, Block, BlockHash, BlockHeader, BlockLike, FinalitySignature,
-        FinalizedBlock, ProtoBlock, TimeDiff, Timestamp,
+        ActivationPoint, Block, BlockHash, BlockHeader, BlockLike, ExitCode, FinalitySignature,
+        FinalizedBlock, InvalidRewardedSignatures, ProtoBlock, RewardedSignatures, SyncState,
+        SyncStatus, TimeDiff, Timestamp,
     },
     utils::WithDir,
     NodeRng,
 };
 
+use self::{catch_up::CatchUp, signing_keys::SigningKeys};
 pub use self::{era::Era, era_id::EraId};
+pub(crate) use self::{signing_keys::SigningKey, stake_check::SigningKeyStatus};
 
 /// The delay in milliseconds before we shutdown after the number of faulty validators exceeded the
 /// fault tolerance threshold.
 const FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS: u64 = 60 * 1000;
 
+/// How strongly the execution latency is smoothed: Each new measurement contributes
+/// `1 / EXECUTION_LATENCY_SMOOTHING` to the moving average.
+const EXECUTION_LATENCY_SMOOTHING: u64 = 8;
+
+/// The delay in milliseconds before checking again whether the blocks and signatures cited by a
+/// proposed block have been stored.
+const CITED_SIGNATURES_RETRY_DELAY_MILLIS: u64 = 1000;
+
+/// How many times we check again for cited blocks and signatures before considering the citations
+/// invalid.
+const CITED_SIGNATURES_MAX_RETRIES: u32 = 300;
+
 type ConsensusConstructor<I> = dyn Fn(
-    Digest,                                       // the era's unique instance ID
-    BTreeMap<PublicKey, U512>,                    // validator weights
-    &HashSet<PublicKey>,                          // slashed validators that are banned in this era
-    &ProtocolConfig,                              // the network's chainspec
-    &Config,                                      // The consensus part of the node config.
-    Option<&dyn ConsensusProtocol<I, ClContext>>, // previous era's consensus instance
-    Timestamp,                                    // start time for this era
-    u64,                                          // random seed
-    Timestamp,                                    // now timestamp
-) -> (
-    Box<dyn ConsensusProtocol<I, ClContext>>,
-    Vec<ProtocolOutcome<I, ClContext>>,
-) + Send;
+        Digest,                    // the era's unique instance ID
+        BTreeMap<PublicKey, U512>, // validator weights
+        &HashSet<PublicKey>,       /* slashed validators that are banned in
+                                    * this era */
+        &ProtocolConfig,                              // the network's chainspec
+        &Config,                                      // The consensus part of the node config.
+        Option<&dyn ConsensusProtocol<I, ClContext>>, // previous era's consensus instance
+        Timestamp,                                    // start time for this era
+        u64,                                          // random seed
+        Timestamp,                                    // now timestamp
+    ) -> (
+        Box<dyn ConsensusProtocol<I, ClContext>>,
+        Vec<ProtocolOutcome<I, ClContext>>,
+    ) + Send;
 
 #[derive(DataSize)]
 pub struct EraSupervisor<I> {
@@ -87,8 +118,8 @@ pub struct EraSupervisor<I> {
     /// This map always contains exactly `2 * bonded_eras + 1` entries, with the last one being the
     /// current one.
     active_eras: HashMap<EraId, Era<I>>,
-    secret_signing_key: Arc<SecretKey>,
-    pub(super) public_signing_key: PublicKey,
+    /// Our signing keys, by the era from which on they are used.
+    signing_keys: SigningKeys,
     current_era: EraId,
     protocol_config: ProtocolConfig,
     config: Config,
@@ -122,6 +153,12 @@ pub struct EraSupervisor<I> {
     next_upgrade_activation_point: Option<ActivationPoint>,
     /// If true, the process should stop execution to allow an upgrade to proceed.
     stop_for_upgrade: bool,
+    /// If true, we don't propose any blocks, e.g. because the local clock is too far off.
+    proposing_suspended: bool,
+    /// Whether we stopped proposing until execution catches up with finalization.
+    catch_up: CatchUp,
+    /// The moving average of the time it took to execute recent blocks, if any were executed yet.
+    execution_latency: Option<TimeDiff>,
     /// Set to true when InitializeEras is handled.
     /// TODO: A temporary field. Shouldn't be needed once the Joiner doesn't have a consensus
     /// component.
@@ -163,9 +200,16 @@ where
         }
         let unit_hashes_folder = config.with_dir(config.value().unit_hashes_folder.clone());
         let (root, config) = config.into_parts();
-        let secret_signing_key = Arc::new(config.secret_key_path.clone().load(root)?);
-        let public_signing_key = PublicKey::from(secret_signing_key.as_ref());
-        info!(our_id = %public_signing_key, "EraSupervisor pubkey",);
+        let signing_key = SigningKey::new(config.secret_key_path.clone().load(&root)?);
+        info!(our_id = %signing_key.public, "EraSupervisor pubkey",);
+        let mut signing_keys = SigningKeys::new(signing_key);
+        if let Some(key_rotation) = &config.key_rotation {
+            let next_key = SigningKey::new(key_rotation.secret_key_path.clone().load(&root)?);
+            let activation_era = key_rotation.activation_era;
+            info!(next_id = %next_key.public, %activation_era, "staging signing key from config");
+            // Staging is relative to the first era, since eras are only instantiated later on.
+            signing_keys.stage(next_key, activation_era, EraId(0))?;
+        }
         let bonded_eras: u64 = protocol_config.unbonding_delay - protocol_config.auction_delay;
         let metrics = ConsensusMetrics::new(registry)
             .expect("failure to setup and register ConsensusMetrics");
@@ -176,11 +220,11 @@ where
             protocol_config.protocol_version.patch as u32,
         );
         let activation_era_id = protocol_config.last_activation_point;
+        let catch_up = CatchUp::new(config.catch_up, Timestamp::now());
 
         let era_supervisor = Self {
             active_eras: Default::default(),
-            secret_signing_key,
-            public_signing_key,
+            signing_keys,
             current_era,
             protocol_config,
             config,
@@ -193,6 +237,9 @@ where
             unit_hashes_folder,
             next_upgrade_activation_point,
             stop_for_upgrade: false,
+            proposing_suspended: false,
+            catch_up,
+            execution_latency: None,
             next_executed_height: 0,
             is_initialized: false,
             enqueued_requests: Default::default(),
@@ -359,7 +406,15 @@ where
 
         // Activate the era if this node was already running when the era began, it is still
         // ongoing based on its minimum duration, and we are one of the validators.
-        let our_id = self.public_signing_key;
+        let SigningKey {
+            secret: our_secret,
+            public: our_id,
+        } = self.signing_keys.for_era(era_id).clone();
+        for other_id in validators.keys().filter(|id| **id != our_id) {
+            if self.signing_keys.contains(other_id) {
+                warn!(era = era_id.0, %other_id, %our_id, "our other key is bonded, not using it");
+            }
+        }
         let should_activate = if !validators.contains_key(&our_id) {
             info!(era = era_id.0, %our_id, "not voting; not a validator");
             false
@@ -388,11 +443,11 @@ where
         );
 
         if should_activate {
-            let secret = Keypair::new(self.secret_signing_key.clone(), our_id);
+            let secret = Keypair::new(our_secret, our_id);
             let unit_hash_file = self.unit_hashes_folder.join(format!(
                 "unit_hash_{:?}_{}.dat",
                 instance_id,
-                self.public_signing_key.to_hex()
+                our_id.to_hex()
             ));
             outcomes.extend(consensus.activate_validator(
                 our_id,
@@ -402,6 +457,10 @@ where
             ))
         }
 
+        if let Some(latency) = self.execution_latency {
+            consensus.set_execution_latency(latency);
+        }
+
         let era = Era::new(
             consensus,
             start_time,
@@ -418,6 +477,9 @@ where
             trace!(era = obsolete_era_id.0, "removing obsolete era");
             self.active_eras.remove(&obsolete_era_id);
         }
+        // Forget the signing keys which none of the remaining eras use anymore.
+        self.signing_keys
+            .prune(era_id.saturating_sub(2 * self.bonded_eras));
         // Clear the obsolete data from the era whose validators are unbonded now. We only retain
         // the information necessary to validate evidence that units in still-bonded eras may refer
         // to for cross-era slashing.
@@ -436,6 +498,17 @@ where
         era_id.0 + self.bonded_eras >= self.current_era.0 && era_id <= self.current_era
     }
 
+    /// Returns the heights of the finalized blocks whose signatures a proposed block may cite.
+    ///
+    /// A proposer cites signatures of the `signature_rewards_max_delay` blocks before the next one
+    /// it knows to be finalized.  Since the proposer may have known fewer finalized blocks than we
+    /// do, citations up to that many blocks older than our own window are accepted, too.
+    fn signature_rewards_validation_window(&self) -> Range<u64> {
+        let max_delay = self.protocol_config.signature_rewards_max_delay;
+        self.next_block_height
+            .saturating_sub(max_delay.saturating_mul(2))..self.next_block_height
+    }
+
     /// Returns whether the validator with the given public key is bonded in that era.
     fn is_validator_in(&self, pub_key: &PublicKey, era_id: EraId) -> bool {
         let has_validator = |era: &Era<I>| era.validators().contains_key(&pub_key);
@@ -451,8 +524,11 @@ where
     /// To be called when we transition from the joiner to the validator reactor.
     pub(crate) fn finished_joining(&mut self, now: Timestamp) -> ProtocolOutcomes<I, ClContext> {
         self.finished_joining = true;
-        let secret = Keypair::new(self.secret_signing_key.clone(), self.public_signing_key);
-        let public_key = self.public_signing_key;
+        let SigningKey {
+            secret,
+            public: public_key,
+        } = self.signing_keys.for_era(self.current_era).clone();
+        let secret = Keypair::new(secret, public_key);
         let unit_hashes_folder = self.unit_hashes_folder.clone();
         self.active_eras
             .get_mut(&self.current_era)
@@ -486,11 +562,23 @@ where
 
     /// Pauses or unpauses consensus: Whenever the last executed block is too far behind the last
     /// finalized block, we suspend consensus.
+    ///
+    /// Also starts or stops catching up, depending on the configured thresholds.
     fn update_consensus_pause(&mut self) {
-        let paused = self
+        let blocks_behind = self
             .next_block_height
-            .saturating_sub(self.next_executed_height)
-            > self.config.max_execution_delay;
+            .saturating_sub(self.next_executed_height);
+        match self.catch_up.update(blocks_behind, Timestamp::now()) {
+            Some(SyncState::CatchingUp) => warn!(
+                %blocks_behind,
+                "execution fell behind finalization; not proposing blocks until caught up"
+            ),
+            Some(SyncState::Participating) => {
+                info!(%blocks_behind, "caught up with finalization; resuming proposing blocks")
+            }
+            None => (),
+        }
+        let paused = blocks_behind > self.config.max_execution_delay;
         match self.active_eras.get_mut(&self.current_era) {
             Some(era) => era.set_paused(paused),
             None => error!(era = self.current_era.0, "current era not initialized"),
@@ -727,9 +815,11 @@ where
                 ));
             return Effects::new();
         }
-        let our_pk = self.era_supervisor.public_signing_key;
-        let our_sk = self.era_supervisor.secret_signing_key.clone();
         let era_id = block.header().era_id();
+        let SigningKey {
+            secret: our_sk,
+            public: our_pk,
+        } = self.era_supervisor.signing_keys.for_era(era_id).clone();
         self.era_supervisor.executed_block(block.header());
         let maybe_fin_sig = if self.era_supervisor.is_validator_in(&our_pk, era_id) {
             let block_hash = block.hash();
@@ -841,6 +931,7 @@ where
             _ => {
                 return fatal!(
                     self.effect_builder,
+                    ExitCode::ConsensusFailure,
                     "attempted to create a new era with a non-switch block header: {}",
                     block
                 )
@@ -983,15 +1074,36 @@ where
                 block_context,
                 past_values,
             } => {
+                if self.era_supervisor.proposing_suspended {
+                    warn!(
+                        %era_id,
+                        timestamp = %block_context.timestamp(),
+                        "not proposing a block while proposing is suspended"
+                    );
+                    return Effects::new();
+                }
+                if self.era_supervisor.catch_up.is_catching_up() {
+                    warn!(
+                        %era_id,
+                        timestamp = %block_context.timestamp(),
+                        "not proposing a block while catching up with finalization"
+                    );
+                    return Effects::new();
+                }
                 let past_deploys = past_values
                     .iter()
                     .flat_map(|candidate| BlockLike::deploys(candidate.proto_block()))
                     .cloned()
                     .collect();
+                let past_rewarded_signatures = past_values
+                    .iter()
+                    .map(|candidate| candidate.proto_block().rewarded_signatures().clone())
+                    .collect();
                 self.effect_builder
                     .request_proto_block(
                         block_context,
                         past_deploys,
+                        past_rewarded_signatures,
                         self.era_supervisor.next_block_height,
                         self.rng.gen(),
                     )
@@ -1081,16 +1193,54 @@ where
                 }
                 self.era_mut(era_id)
                     .add_candidate(candidate_block, missing_evidence);
+                let effect_builder = self.effect_builder;
+                let signature_window = self.era_supervisor.signature_rewards_validation_window();
+                let weight_threshold = self
+                    .era_supervisor
+                    .protocol_config
+                    .signature_rewards_weight_threshold;
+                let validation_sender = sender.clone();
                 effects.extend(
-                    self.effect_builder
-                        .validate_block(sender.clone(), proto_block, timestamp)
-                        .event(move |(valid, proto_block)| Event::ResolveValidity {
+                    async move {
+                        if let Err(error) = validate_rewarded_signatures(
+                            effect_builder,
+                            proto_block.rewarded_signatures(),
+                            signature_window,
+                            weight_threshold,
+                        )
+                        .await
+                        {
+                            info!(
+                                era = era_id.0,
+                                sender = ?validation_sender,
+                                %error,
+                                "proposed block cites invalid signatures"
+                            );
+                            return (proto_block, false);
+                        }
+                        let result = effect_builder
+                            .validate_block(validation_sender.clone(), proto_block, timestamp)
+                            .await;
+                        let valid = result.outcome.is_valid();
+                        if !valid {
+                            info!(
+                                era = era_id.0,
+                                sender = ?validation_sender,
+                                outcome = ?result.outcome,
+                                "proposed block failed validation"
+                            );
+                        }
+                        (result.block, valid)
+                    }
+                    .event(move |(proto_block, valid)| {
+                        Event::ResolveValidity {
                             era_id,
                             sender,
                             proto_block,
                             timestamp,
                             valid,
-                        }),
+                        }
+                    }),
                 );
                 effects
             }
@@ -1129,7 +1279,9 @@ where
             ProtocolOutcome::FttExceeded => {
                 let eb = self.effect_builder;
                 eb.set_timeout(Duration::from_millis(FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS))
-                    .then(move |_| fatal!(eb, "too many faulty validators"))
+                    .then(move |_| {
+                        fatal!(eb, ExitCode::ConsensusFailure, "too many faulty validators")
+                    })
                     .ignore()
             }
         }
@@ -1145,6 +1297,7 @@ where
         if should_emit_error {
             fatal!(
                 self.effect_builder,
+                ExitCode::ConsensusFailure,
                 "Consensus shutting down due to inability to participate in the network; \
                 inactive era = {}",
                 self.era_supervisor.current_era
@@ -1155,7 +1308,84 @@ where
         }
     }
 
-    pub(crate) fn finished_joining(&mut self, now: Timestamp) -> Effects<Event<I>> {
+    /// Cross-checks the signing key of the current era against the era's validators and the
+    /// bids in the global state of the highest block, before participating in consensus.
+    pub(crate) fn check_signing_key(&mut self, now: Timestamp) -> Effects<Event<I>> {
+        let current_era = self.era_supervisor.current_era;
+        let validators = match self.era_supervisor.active_eras.get(&current_era) {
+            Some(era) => era.validators().clone(),
+            None => {
+                debug!(
+                    era = current_era.0,
+                    "current era not initialized; not checking signing key"
+                );
+                return self.finished_joining(now);
+            }
+        };
+        let public_key = self.era_supervisor.signing_keys.for_era(current_era).public;
+        let effect_builder = self.effect_builder;
+        async move {
+            let bid = match effect_builder.get_highest_block_from_storage().await {
+                Some(block) => {
+                    let query = QueryRequest::new(
+                        (*block.state_root_hash()).into(),
+                        Key::Bid(public_key.to_account_hash()),
+                        vec![],
+                    );
+                    match effect_builder.query_global_state(query).await {
+                        Ok(QueryResult::Success { value, .. }) => match *value {
+                            StoredValue::Bid(bid) => Some(Some(bid)),
+                            _ => None,
+                        },
+                        Ok(QueryResult::ValueNotFound(_)) => Some(None),
+                        Ok(_) | Err(_) => None,
+                    }
+                }
+                None => None,
+            };
+            SigningKeyStatus::new(&public_key, &validators, bid.as_ref().map(Option::as_deref))
+        }
+        .event(move |status| Event::SigningKeyChecked {
+            joined_at: now,
+            status,
+        })
+    }
+
+    /// Handles the result of checking the signing key: Logs a mismatch, and either exits or starts
+    /// participating in consensus.
+    pub(crate) fn signing_key_checked(
+        &mut self,
+        joined_at: Timestamp,
+        status: SigningKeyStatus,
+    ) -> Effects<Event<I>> {
+        let public_key = self
+            .era_supervisor
+            .signing_keys
+            .for_era(self.era_supervisor.current_era)
+            .public;
+        if !status.is_mismatch() {
+            info!(%public_key, "signing key is bonded");
+        } else if self.era_supervisor.config.require_bonded_signing_key {
+            return fatal!(
+                self.effect_builder,
+                ExitCode::SigningKeyNotBonded,
+                "refusing to participate in consensus: signing key {} is {}",
+                public_key,
+                status
+            )
+            .ignore();
+        } else {
+            error!(
+                %public_key,
+                %status,
+                "SIGNING KEY MISMATCH: this node will not take part in consensus with its \
+                configured signing key"
+            );
+        }
+        self.finished_joining(joined_at)
+    }
+
+    fn finished_joining(&mut self, now: Timestamp) -> Effects<Event<I>> {
         let outcomes = self.era_supervisor.finished_joining(now);
         self.handle_consensus_outcomes(self.era_supervisor.current_era, outcomes)
     }
@@ -1170,6 +1400,51 @@ where
         Effects::new()
     }
 
+    /// Handles suspending or resuming the proposing of blocks.
+    pub(super) fn suspend_proposing(&mut self, suspended: bool) -> Effects<Event<I>> {
+        if suspended {
+            warn!("suspending proposing blocks");
+        } else {
+            info!("resuming proposing blocks");
+        }
+        self.era_supervisor.proposing_suspended = suspended;
+        Effects::new()
+    }
+
+    /// Handles the catch-up thresholds being changed by reloading the config.
+    pub(super) fn update_catch_up_config(&mut self, config: CatchUpConfig) -> Effects<Event<I>> {
+        info!(?config, "updated catch-up config");
+        self.era_supervisor.config.catch_up = config;
+        self.era_supervisor.catch_up.set_config(config);
+        if self.era_supervisor.is_initialized {
+            self.era_supervisor.update_consensus_pause();
+        }
+        Effects::new()
+    }
+
+    /// Handles a block having been executed in `latency`: Updates the average execution latency,
+    /// which the current era uses as a lower bound for its round length.
+    pub(super) fn record_execution_latency(&mut self, latency: TimeDiff) -> Effects<Event<I>> {
+        let average = match self.era_supervisor.execution_latency {
+            Some(average) => {
+                (average * (EXECUTION_LATENCY_SMOOTHING - 1) + latency)
+                    / EXECUTION_LATENCY_SMOOTHING
+            }
+            None => latency,
+        };
+        trace!(
+            latency = latency.millis(),
+            average = average.millis(),
+            "block executed"
+        );
+        self.era_supervisor.execution_latency = Some(average);
+        let current_era = self.era_supervisor.current_era;
+        if let Some(era) = self.era_supervisor.active_eras.get_mut(&current_era) {
+            era.set_execution_latency(average);
+        }
+        Effects::new()
+    }
+
     /// Returns whether validator is bonded in an era.
     pub(super) fn is_bonded_validator(
         &self,
@@ -1185,17 +1460,84 @@ where
         responder.respond(is_bonded).ignore()
     }
 
+    /// Returns the validators of an era and their weights, if the era is active.
+    pub(super) fn validator_weights(
+        &self,
+        era_id: EraId,
+        responder: Responder<Option<ValidatorWeights>>,
+    ) -> Effects<Event<I>> {
+        let validator_weights = self
+            .era_supervisor
+            .active_eras
+            .get(&era_id)
+            .map(|era| era.validators().clone());
+        responder.respond(validator_weights).ignore()
+    }
+
     pub(super) fn status(
         &self,
-        responder: Responder<(PublicKey, Option<TimeDiff>)>,
+        responder: Responder<(PublicKey, Option<TimeDiff>, SyncStatus)>,
     ) -> Effects<Event<I>> {
-        let public_key = self.era_supervisor.public_signing_key;
+        let public_key = self
+            .era_supervisor
+            .signing_keys
+            .for_era(self.era_supervisor.current_era)
+            .public;
         let round_length = self
             .era_supervisor
             .active_eras
             .get(&self.era_supervisor.current_era)
             .and_then(|era| era.consensus.next_round_length());
-        responder.respond((public_key, round_length)).ignore()
+        let sync_status = self.era_supervisor.catch_up.status();
+        responder
+            .respond((public_key, round_length, sync_status))
+            .ignore()
+    }
+
+    /// Loads the secret key at `secret_key_path` on a blocking thread, to be staged to be used
+    /// from `activation_era` on.
+    pub(super) fn stage_signing_key(
+        &mut self,
+        secret_key_path: PathBuf,
+        activation_era: EraId,
+        responder: Responder<Result<PublicKey, String>>,
+    ) -> Effects<Event<I>> {
+        async move {
+            match task::spawn_blocking(move || SecretKey::from_file(&secret_key_path)).await {
+                Ok(Ok(secret_key)) => Ok(SigningKey::new(secret_key)),
+                Ok(Err(error)) => Err(format!("failed to load secret key: {}", error)),
+                Err(error) => Err(format!("failed to join tokio task: {}", error)),
+            }
+        }
+        .event(move |result| Event::SigningKeyLoaded {
+            activation_era,
+            result,
+            responder,
+        })
+    }
+
+    /// Stages the loaded signing key to be used from `activation_era` on.  Responds with the new
+    /// public key, or the reason why it couldn't be loaded or staged.
+    pub(super) fn signing_key_loaded(
+        &mut self,
+        activation_era: EraId,
+        result: Result<SigningKey, String>,
+        responder: Responder<Result<PublicKey, String>>,
+    ) -> Effects<Event<I>> {
+        let current_era = self.era_supervisor.current_era;
+        let signing_keys = &mut self.era_supervisor.signing_keys;
+        let result = result.and_then(|key| {
+            let public_key = key.public;
+            signing_keys
+                .stage(key, activation_era, current_era)
+                .map(|()| public_key)
+                .map_err(|error| error.to_string())
+        });
+        match &result {
+            Ok(public_key) => info!(%public_key, %activation_era, "staged signing key"),
+            Err(error) => warn!(%error, %activation_era, "failed to stage signing key"),
+        }
+        responder.respond(result).ignore()
     }
 
     fn disconnect(&self, sender: I) -> Effects<Event<I>> {
@@ -1212,6 +1554,64 @@ where
     }
 }
 
+/// Checks the signatures cited by a proposed block against the blocks and signatures in storage.
+///
+/// Citations of blocks below `window` are invalid.  If cited blocks or signatures are not stored
+/// yet, e.g. because the proposer executed blocks faster than we did, the check is repeated until
+/// they are, up to `CITED_SIGNATURES_MAX_RETRIES` times.
+async fn validate_rewarded_signatures<REv>(
+    effect_builder: EffectBuilder<REv>,
+    rewarded_signatures: &RewardedSignatures,
+    window: Range<u64>,
+    weight_threshold: Ratio<u64>,
+) -> Result<(), InvalidRewardedSignatures>
+where
+    REv: From<StorageRequest>,
+{
+    if rewarded_signatures.is_empty() {
+        return Ok(());
+    }
+    if let Some(citation) = rewarded_signatures
+        .citations()
+        .iter()
+        .find(|citation| citation.block_height < window.start)
+    {
+        return Err(InvalidRewardedSignatures::UnknownBlock {
+            block_hash: citation.block_hash,
+            block_height: citation.block_height,
+        });
+    }
+    // The proposer may have known blocks we haven't finalized yet.
+    let later_heights: BTreeSet<u64> = rewarded_signatures
+        .cited_heights()
+        .filter(|height| *height >= window.end)
+        .collect();
+    let mut retries = 0;
+    loop {
+        let mut blocks = effect_builder
+            .get_blocks_with_signatures_from_storage(window.clone())
+            .await;
+        for height in &later_heights {
+            let heights = *height..height.saturating_add(1);
+            blocks.extend(
+                effect_builder
+                    .get_blocks_with_signatures_from_storage(heights)
+                    .await,
+            );
+        }
+        match rewarded_signatures.validate(&blocks, weight_threshold) {
+            Err(error) if error.is_pending() && retries < CITED_SIGNATURES_MAX_RETRIES => {
+                trace!(%error, "waiting for cited blocks and signatures to be stored");
+                retries += 1;
+                effect_builder
+                    .set_timeout(Duration::from_millis(CITED_SIGNATURES_RETRY_DELAY_MILLIS))
+                    .await;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Computes the instance ID for an era, given the era ID and the chainspec hash.
 fn instance_id(protocol_config: &ProtocolConfig, era_id: EraId) -> Digest {
     let mut result = [0; Digest::LENGTH];
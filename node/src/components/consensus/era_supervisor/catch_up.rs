@@ -0,0 +1,121 @@
+//! Tracking whether a validator has to catch up with finalization.
+//!
+//! If a validator's block execution lags far behind finalization, the blocks it would propose are
+//! based on stale state, and executing them only adds to its backlog.  Once the lag exceeds the
+//! configured start threshold, the validator stops proposing until the backlog has shrunk to the
+//! resume threshold.  Using two thresholds keeps a node hovering around a single limit from
+//! flapping between the two states.
+
+use datasize::DataSize;
+
+use crate::{
+    components::consensus::config::CatchUpConfig,
+    types::{SyncState, SyncStatus, Timestamp},
+};
+
+#[derive(DataSize, Debug)]
+pub(super) struct CatchUp {
+    config: CatchUpConfig,
+    #[data_size(skip)]
+    state: SyncState,
+    blocks_behind: u64,
+    since: Timestamp,
+    transitions: u64,
+}
+
+impl CatchUp {
+    pub(super) fn new(config: CatchUpConfig, now: Timestamp) -> Self {
+        CatchUp {
+            config,
+            state: SyncState::Participating,
+            blocks_behind: 0,
+            since: now,
+            transitions: 0,
+        }
+    }
+
+    /// Replaces the thresholds.  They are applied on the next call to `update`.
+    pub(super) fn set_config(&mut self, config: CatchUpConfig) {
+        self.config = config;
+    }
+
+    /// Records the current number of finalized blocks awaiting execution.
+    ///
+    /// Returns the new state if it changed.
+    pub(super) fn update(&mut self, blocks_behind: u64, now: Timestamp) -> Option<SyncState> {
+        self.blocks_behind = blocks_behind;
+        let new_state = match self.state {
+            _ if !self.config.enabled => SyncState::Participating,
+            SyncState::Participating if blocks_behind > self.config.start_lag => {
+                SyncState::CatchingUp
+            }
+            SyncState::CatchingUp if blocks_behind <= self.config.resume_lag => {
+                SyncState::Participating
+            }
+            state => state,
+        };
+        if new_state == self.state {
+            return None;
+        }
+        self.state = new_state;
+        self.since = now;
+        self.transitions += 1;
+        Some(new_state)
+    }
+
+    /// Returns whether we are catching up, and shouldn't propose blocks.
+    pub(super) fn is_catching_up(&self) -> bool {
+        self.state == SyncState::CatchingUp
+    }
+
+    pub(super) fn status(&self) -> SyncStatus {
+        SyncStatus {
+            state: self.state,
+            blocks_behind: self.blocks_behind,
+            since: self.since,
+            transitions: self.transitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_catch_up_with_hysteresis() {
+        let config = CatchUpConfig {
+            enabled: true,
+            start_lag: 10,
+            resume_lag: 2,
+        };
+        let mut catch_up = CatchUp::new(config, Timestamp::zero());
+
+        assert_eq!(catch_up.update(10, 1.into()), None);
+        assert_eq!(catch_up.update(11, 2.into()), Some(SyncState::CatchingUp));
+        assert!(catch_up.is_catching_up());
+
+        // Still catching up while between the two thresholds.
+        assert_eq!(catch_up.update(5, 3.into()), None);
+        assert_eq!(catch_up.update(2, 4.into()), Some(SyncState::Participating));
+
+        let status = catch_up.status();
+        assert_eq!(status.state, SyncState::Participating);
+        assert_eq!(status.blocks_behind, 2);
+        assert_eq!(status.since, Timestamp::from(4));
+        assert_eq!(status.transitions, 2);
+
+        // Disabling catching up at runtime resumes proposing on the next update.
+        catch_up.update(20, 5.into());
+        assert!(catch_up.is_catching_up());
+        catch_up.set_config(CatchUpConfig {
+            enabled: false,
+            ..config
+        });
+        assert_eq!(
+            catch_up.update(20, 6.into()),
+            Some(SyncState::Participating)
+        );
+        assert_eq!(catch_up.update(30, 7.into()), None);
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
         candidate_block::CandidateBlock, cl_context::ClContext,
         consensus_protocol::ConsensusProtocol, protocols::highway::HighwayProtocol,
     },
-    types::{ProtoBlock, Timestamp},
+    types::{ProtoBlock, TimeDiff, Timestamp},
 };
 
 const CASPER_ENABLE_DETAILED_CONSENSUS_METRICS_ENV_VAR: &str =
@@ -185,6 +185,11 @@ impl<I> Era<I> {
         self.consensus.set_paused(paused);
     }
 
+    /// Sets the recent time it took to execute a finalized block.
+    pub(crate) fn set_execution_latency(&mut self, latency: TimeDiff) {
+        self.consensus.set_execution_latency(latency);
+    }
+
     /// Removes and returns all candidate blocks with no missing dependencies.
     fn remove_complete_candidates(&mut self) -> Vec<CandidateBlock> {
         let (complete, candidates): (Vec<_>, Vec<_>) = self
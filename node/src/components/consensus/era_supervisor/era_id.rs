@@ -14,6 +14,7 @@ use crate::components::consensus::ConsensusMessage;
 #[derive(
     DataSize,
     Debug,
+    Default,
     Clone,
     Copy,
     Hash,
@@ -0,0 +1,217 @@
+//! The keys this node signs consensus messages and finality signatures with.
+//!
+//! A validator can rotate its key by staging a new one together with the era from which on it is
+//! used.  Each era is assigned exactly one key: the one with the latest activation era not after
+//! it.  That way the switch happens precisely at the era boundary, and the node never votes with
+//! both keys in the same era, even if both are bonded in it.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+
+use datasize::DataSize;
+use thiserror::Error;
+
+use casper_types::{PublicKey, SecretKey};
+
+use super::EraId;
+
+/// A secret key together with its public key.
+#[derive(DataSize, Clone)]
+pub struct SigningKey {
+    pub(crate) secret: Arc<SecretKey>,
+    pub(crate) public: PublicKey,
+}
+
+impl SigningKey {
+    pub(crate) fn new(secret: SecretKey) -> Self {
+        let public = PublicKey::from(&secret);
+        SigningKey {
+            secret: Arc::new(secret),
+            public,
+        }
+    }
+}
+
+impl Debug for SigningKey {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        // Never print the secret key.
+        write!(formatter, "SigningKey({})", self.public)
+    }
+}
+
+/// Error staging a new signing key.
+#[derive(Debug, Error)]
+pub(crate) enum StageKeyError {
+    /// Keys can only be switched at the start of a future era.
+    #[error("activation era {activation_era} is not after the current era {current_era}")]
+    ActivationEraNotInFuture {
+        activation_era: EraId,
+        current_era: EraId,
+    },
+    /// The key would already be in use at the activation era.
+    #[error("{0} would already be the signing key in the activation era")]
+    AlreadyActive(PublicKey),
+}
+
+/// The signing keys by the era from which on they are used.
+#[derive(DataSize, Debug)]
+pub(crate) struct SigningKeys {
+    keys: BTreeMap<EraId, SigningKey>,
+}
+
+impl SigningKeys {
+    /// Creates a set of signing keys, using `key` in all eras.
+    pub(crate) fn new(key: SigningKey) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(EraId(0), key);
+        SigningKeys { keys }
+    }
+
+    /// Returns the key to be used in the given era.
+    pub(crate) fn for_era(&self, era_id: EraId) -> &SigningKey {
+        self.keys
+            .range(..=era_id)
+            .next_back()
+            .map(|(_, key)| key)
+            .expect("should always have a key for era 0")
+    }
+
+    /// Returns whether `public_key` is one of our keys, whether or not it is used in a given era.
+    pub(crate) fn contains(&self, public_key: &PublicKey) -> bool {
+        self.keys.values().any(|key| key.public == *public_key)
+    }
+
+    /// Returns the key staged to be used from a future era on, if any, with its activation era.
+    pub(crate) fn staged(&self, current_era: EraId) -> Option<(EraId, &SigningKey)> {
+        self.keys
+            .range(current_era.successor()..)
+            .next()
+            .map(|(era_id, key)| (*era_id, key))
+    }
+
+    /// Stages `key` to be used from `activation_era` on, which must be after `current_era`.
+    ///
+    /// Any key staged previously which is not active yet is discarded, unless staging `key` fails.
+    pub(crate) fn stage(
+        &mut self,
+        key: SigningKey,
+        activation_era: EraId,
+        current_era: EraId,
+    ) -> Result<(), StageKeyError> {
+        if activation_era <= current_era {
+            return Err(StageKeyError::ActivationEraNotInFuture {
+                activation_era,
+                current_era,
+            });
+        }
+        // With the staged keys discarded, the current era's key would be used in the activation
+        // era.
+        if self.for_era(current_era).public == key.public {
+            return Err(StageKeyError::AlreadyActive(key.public));
+        }
+        let _ = self.keys.split_off(&current_era.successor());
+        self.keys.insert(activation_era, key);
+        Ok(())
+    }
+
+    /// Forgets the keys which are not used in `oldest_era` or any later era.
+    pub(crate) fn prune(&mut self, oldest_era: EraId) {
+        let mut retained = self.keys.split_off(&oldest_era.successor());
+        if let Some((_, key)) = self.keys.range(..=oldest_era).next_back() {
+            retained.insert(EraId(0), key.clone());
+        }
+        self.keys = retained;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crypto::AsymmetricKeyExt, testing::TestRng};
+
+    fn random_key(rng: &mut TestRng) -> SigningKey {
+        SigningKey::new(SecretKey::random(rng))
+    }
+
+    #[test]
+    fn should_switch_keys_at_activation_era() {
+        let mut rng = TestRng::new();
+        let old_key = random_key(&mut rng);
+        let new_key = random_key(&mut rng);
+        let mut keys = SigningKeys::new(old_key.clone());
+
+        keys.stage(new_key.clone(), EraId(5), EraId(3)).unwrap();
+        assert_eq!(keys.for_era(EraId(3)).public, old_key.public);
+        assert_eq!(keys.for_era(EraId(4)).public, old_key.public);
+        assert_eq!(keys.for_era(EraId(5)).public, new_key.public);
+        assert_eq!(keys.for_era(EraId(6)).public, new_key.public);
+        assert_eq!(keys.staged(EraId(3)).unwrap().0, EraId(5));
+        assert!(keys.staged(EraId(5)).is_none());
+        assert!(keys.contains(&old_key.public));
+        assert!(keys.contains(&new_key.public));
+
+        // The old key is still needed while era 4 is active.
+        keys.prune(EraId(4));
+        assert_eq!(keys.for_era(EraId(4)).public, old_key.public);
+        keys.prune(EraId(5));
+        assert!(!keys.contains(&old_key.public));
+        assert_eq!(keys.for_era(EraId(0)).public, new_key.public);
+    }
+
+    #[test]
+    fn should_only_stage_keys_for_future_eras() {
+        let mut rng = TestRng::new();
+        let old_key = random_key(&mut rng);
+        let mut keys = SigningKeys::new(old_key.clone());
+
+        assert!(matches!(
+            keys.stage(random_key(&mut rng), EraId(3), EraId(3)),
+            Err(StageKeyError::ActivationEraNotInFuture { .. })
+        ));
+        assert!(matches!(
+            keys.stage(old_key.clone(), EraId(4), EraId(3)),
+            Err(StageKeyError::AlreadyActive(_))
+        ));
+        assert_eq!(keys.for_era(EraId(4)).public, old_key.public);
+    }
+
+    #[test]
+    fn should_keep_staged_key_if_staging_fails() {
+        let mut rng = TestRng::new();
+        let old_key = random_key(&mut rng);
+        let new_key = random_key(&mut rng);
+        let mut keys = SigningKeys::new(old_key.clone());
+
+        keys.stage(new_key.clone(), EraId(5), EraId(3)).unwrap();
+        assert!(matches!(
+            keys.stage(old_key.clone(), EraId(6), EraId(3)),
+            Err(StageKeyError::AlreadyActive(_))
+        ));
+        assert!(matches!(
+            keys.stage(random_key(&mut rng), EraId(2), EraId(3)),
+            Err(StageKeyError::ActivationEraNotInFuture { .. })
+        ));
+        let (activation_era, staged_key) = keys.staged(EraId(3)).expect("should keep staged key");
+        assert_eq!(activation_era, EraId(5));
+        assert_eq!(staged_key.public, new_key.public);
+        assert_eq!(keys.for_era(EraId(4)).public, old_key.public);
+    }
+
+    #[test]
+    fn should_replace_previously_staged_key() {
+        let mut rng = TestRng::new();
+        let old_key = random_key(&mut rng);
+        let first_key = random_key(&mut rng);
+        let second_key = random_key(&mut rng);
+        let mut keys = SigningKeys::new(old_key.clone());
+
+        keys.stage(first_key.clone(), EraId(5), EraId(3)).unwrap();
+        keys.stage(second_key.clone(), EraId(7), EraId(3)).unwrap();
+        assert!(!keys.contains(&first_key.public));
+        assert_eq!(keys.for_era(EraId(6)).public, old_key.public);
+        assert_eq!(keys.for_era(EraId(7)).public, second_key.public);
+    }
+}
@@ -0,0 +1,127 @@
+//! Cross-checking the signing key against the validator set and the bids.
+//!
+//! A validator whose configured signing key isn't bonded, or whose stake is bid under another key,
+//! runs without ever voting, which is easy to miss.  So once the node finishes joining, its signing
+//! key is looked up in the current era's validator set and in the bids of the latest global state,
+//! and a mismatch is logged as an error.  If `consensus.require_bonded_signing_key` is set, the
+//! node exits instead of starting to participate in consensus.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+
+use casper_types::{system::auction::Bid, PublicKey, U512};
+
+/// How the signing key relates to the current era's validator set and the bids.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq)]
+pub enum SigningKeyStatus {
+    /// The key is bonded in the current era, and its bid is active, as far as it could be read.
+    Bonded,
+    /// The key isn't bonded in the current era.
+    Unbonded {
+        /// Whether the key has an active bid, which could win a slot in a future era.
+        has_bid: bool,
+    },
+    /// The key is bonded in the current era, but has no active bid, so the stake is presumably bid
+    /// under a different key, and the key will be unbonded in a future era.
+    NoActiveBid,
+}
+
+impl SigningKeyStatus {
+    /// Returns the status of `public_key`, given the current era's validator weights and its bid.
+    ///
+    /// `bid` is `None` if the bids couldn't be read, and `Some(None)` if the key has no bid.
+    pub(crate) fn new(
+        public_key: &PublicKey,
+        validators: &BTreeMap<PublicKey, U512>,
+        bid: Option<Option<&Bid>>,
+    ) -> Self {
+        let is_bonded = validators.contains_key(public_key);
+        let has_bid = bid.map(|maybe_bid| {
+            maybe_bid.map_or(false, |bid| {
+                !bid.inactive() && !bid.staked_amount().is_zero()
+            })
+        });
+        match (is_bonded, has_bid) {
+            (true, Some(false)) => SigningKeyStatus::NoActiveBid,
+            (true, _) => SigningKeyStatus::Bonded,
+            (false, has_bid) => SigningKeyStatus::Unbonded {
+                has_bid: has_bid.unwrap_or(false),
+            },
+        }
+    }
+
+    /// Returns whether the key isn't set up to participate in consensus.
+    pub(crate) fn is_mismatch(&self) -> bool {
+        *self != SigningKeyStatus::Bonded
+    }
+}
+
+impl Display for SigningKeyStatus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningKeyStatus::Bonded => write!(formatter, "bonded"),
+            SigningKeyStatus::Unbonded { has_bid: true } => {
+                write!(
+                    formatter,
+                    "not bonded in the current era, despite an active bid"
+                )
+            }
+            SigningKeyStatus::Unbonded { has_bid: false } => {
+                write!(
+                    formatter,
+                    "not bonded in the current era and without an active bid"
+                )
+            }
+            SigningKeyStatus::NoActiveBid => write!(
+                formatter,
+                "bonded in the current era, but without an active bid; the stake may be bid \
+                under a different key"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{AccessRights, SecretKey, URef};
+
+    use super::*;
+
+    #[test]
+    fn should_detect_mismatched_signing_key() {
+        let public_key = PublicKey::from(&SecretKey::ed25519([1; SecretKey::ED25519_LENGTH]));
+        let other_key = PublicKey::from(&SecretKey::ed25519([2; SecretKey::ED25519_LENGTH]));
+        let purse = URef::new([0; 32], AccessRights::READ_ADD_WRITE);
+        let bid = Bid::unlocked(public_key, purse, U512::from(10), 0);
+        let mut inactive_bid = bid.clone();
+        inactive_bid.deactivate();
+
+        let bonded: BTreeMap<_, _> = vec![(public_key, U512::from(10))].into_iter().collect();
+        let unbonded: BTreeMap<_, _> = vec![(other_key, U512::from(10))].into_iter().collect();
+
+        let status = |validators: &BTreeMap<PublicKey, U512>, bid: Option<Option<&Bid>>| {
+            SigningKeyStatus::new(&public_key, validators, bid)
+        };
+        assert_eq!(status(&bonded, Some(Some(&bid))), SigningKeyStatus::Bonded);
+        assert_eq!(status(&bonded, None), SigningKeyStatus::Bonded);
+        assert_eq!(status(&bonded, Some(None)), SigningKeyStatus::NoActiveBid);
+        assert_eq!(
+            status(&bonded, Some(Some(&inactive_bid))),
+            SigningKeyStatus::NoActiveBid
+        );
+        assert_eq!(
+            status(&unbonded, Some(Some(&bid))),
+            SigningKeyStatus::Unbonded { has_bid: true }
+        );
+        assert_eq!(
+            status(&unbonded, Some(None)),
+            SigningKeyStatus::Unbonded { has_bid: false }
+        );
+        assert!(!SigningKeyStatus::Bonded.is_mismatch());
+        assert!(SigningKeyStatus::NoActiveBid.is_mismatch());
+    }
+}
@@ -108,7 +108,7 @@ impl<I: NodeIdT, C: Context + 'static> HighwayProtocol<I, C> {
         let highway_config = &protocol_config.highway_config;
 
         let total_weight = u128::from(validators.total_weight());
-        let ftt_fraction = highway_config.finality_threshold_fraction;
+        let ftt_fraction = protocol_config.finality_threshold_fraction;
         let ftt = ((total_weight * *ftt_fraction.numer() as u128 / *ftt_fraction.denom() as u128)
             as u64)
             .into();
@@ -748,6 +748,11 @@ where
         self.highway.set_paused(paused);
     }
 
+    /// Keeps our round exponent high enough for rounds to last at least `latency`.
+    fn set_execution_latency(&mut self, latency: TimeDiff) {
+        self.round_success_meter.set_execution_latency(latency);
+    }
+
     fn validators_with_evidence(&self) -> Vec<&C::ValidatorId> {
         self.highway.validators_with_evidence().collect()
     }
@@ -8,7 +8,7 @@ use crate::{
         highway_core::{finality_detector::FinalityDetector, round_id, State, Weight},
         traits::Context,
     },
-    types::Timestamp,
+    types::{TimeDiff, Timestamp},
 };
 
 mod config;
@@ -27,6 +27,8 @@ where
     min_round_exp: u8,
     max_round_exp: u8,
     current_round_exp: u8,
+    // the lowest round exponent with rounds at least as long as it takes us to execute a block
+    execution_round_exp: u8,
 }
 
 impl<C: Context> RoundSuccessMeter<C> {
@@ -39,9 +41,30 @@ impl<C: Context> RoundSuccessMeter<C> {
             min_round_exp,
             max_round_exp,
             current_round_exp: round_exp,
+            execution_round_exp: min_round_exp,
         }
     }
 
+    /// Sets the recent time it took to execute a block.  We won't speed up to rounds shorter than
+    /// that, and slow down if our rounds are already shorter, within the configured bounds.
+    ///
+    /// Otherwise, if execution is consistently slower than the rounds, finalized blocks would pile
+    /// up faster than the node can execute them.
+    pub fn set_execution_latency(&mut self, latency: TimeDiff) {
+        let mut exp = self.min_round_exp;
+        while exp < self.max_round_exp && (1u64 << exp) < latency.millis() {
+            exp += 1;
+        }
+        if exp != self.execution_round_exp {
+            trace!(
+                latency = latency.millis(),
+                %exp,
+                "minimum round exponent for execution latency changed"
+            );
+        }
+        self.execution_round_exp = exp;
+    }
+
     fn change_exponent(&mut self, new_exp: u8, timestamp: Timestamp) {
         self.rounds = VecDeque::with_capacity(NUM_ROUNDS_TO_CONSIDER);
         self.current_round_exp = new_exp;
@@ -149,6 +172,7 @@ impl<C: Context> RoundSuccessMeter<C> {
             min_round_exp: self.min_round_exp,
             max_round_exp: self.max_round_exp,
             current_round_exp: self.current_round_exp,
+            execution_round_exp: self.execution_round_exp,
         }
     }
 
@@ -165,10 +189,14 @@ impl<C: Context> RoundSuccessMeter<C> {
     fn new_exponent(&self) -> u8 {
         let current_round_index = self.current_round_id >> self.current_round_exp;
         let num_failures = self.count_failures();
-        if num_failures > MAX_FAILED_ROUNDS && self.current_round_exp < self.max_round_exp {
+        // rounds shorter than our execution latency count as too short, too
+        let min_round_exp = self.min_round_exp.max(self.execution_round_exp);
+        if (num_failures > MAX_FAILED_ROUNDS || self.current_round_exp < min_round_exp)
+            && self.current_round_exp < self.max_round_exp
+        {
             self.current_round_exp + 1
         } else if current_round_index % ACCELERATION_PARAMETER == 0
-            && self.current_round_exp > self.min_round_exp
+            && self.current_round_exp > min_round_exp
             // we will only accelerate if we collected data about enough rounds
             && self.rounds.len() == NUM_ROUNDS_TO_CONSIDER
             && num_failures < MAX_FAILURES_FOR_ACCELERATION
@@ -281,4 +309,36 @@ mod tests {
         }
         assert_eq!(round_success_meter.new_exponent(), TEST_MIN_ROUND_EXP);
     }
+
+    #[test]
+    fn new_exponent_slow_down_because_of_execution_latency() {
+        let mut round_success_meter: super::RoundSuccessMeter<ClContext> =
+            super::RoundSuccessMeter::new(
+                TEST_ROUND_EXP,
+                TEST_MIN_ROUND_EXP,
+                TEST_MAX_ROUND_EXP,
+                crate::types::Timestamp::now(),
+            );
+        // All rounds succeeded, but executing a block takes longer than a round.
+        round_success_meter.rounds = vec![true; NUM_ROUNDS_TO_CONSIDER].into();
+        let round_len = 1u64 << TEST_ROUND_EXP;
+        round_success_meter.set_execution_latency((round_len + 1).into());
+        assert_eq!(round_success_meter.new_exponent(), TEST_ROUND_EXP + 1);
+
+        // Even at an acceleration round, we don't speed up to rounds shorter than execution.
+        round_success_meter.set_execution_latency(round_len.into());
+        loop {
+            let current_round_index =
+                round_success_meter.current_round_id >> round_success_meter.current_round_exp;
+            if current_round_index % ACCELERATION_PARAMETER == 0 {
+                break;
+            };
+            round_success_meter.current_round_id += 1;
+        }
+        assert_eq!(round_success_meter.new_exponent(), TEST_ROUND_EXP);
+
+        // The latency can't push the exponent above the maximum.
+        round_success_meter.set_execution_latency(u64::MAX.into());
+        assert_eq!(round_success_meter.execution_round_exp, TEST_MAX_ROUND_EXP);
+    }
 }
@@ -73,6 +73,11 @@ where
         unit_hashes_folder: Default::default(),
         pending_vertex_timeout: "1min".parse().unwrap(),
         max_execution_delay: 3,
+        catch_up: Default::default(),
+        max_deferred_eras: 2,
+        max_deferred_messages_per_era: 10_000,
+        key_rotation: None,
+        require_bonded_signing_key: false,
     };
     // Timestamp of the genesis era start and test start.
     let start_timestamp: Timestamp = 0.into();
@@ -3,7 +3,10 @@ mod config;
 mod types;
 
 pub use config::Config;
-pub use types::{EraValidatorsRequest, ValidatorWeightsByEraIdRequest};
+pub use types::{
+    EraValidatorsRequest, PurgeEstimate, ShadowExecutionError, ShadowExecutionRequest,
+    ValidatorWeightsByEraIdRequest,
+};
 
 use std::{
     fmt::{self, Debug, Display, Formatter},
@@ -15,30 +18,35 @@ use datasize::DataSize;
 use derive_more::From;
 use lmdb::DatabaseFlags;
 use prometheus::{self, Histogram, HistogramOpts, Registry};
+use rayon::ThreadPoolBuildError;
 use serde::Serialize;
 use thiserror::Error;
 use tokio::task;
-use tracing::{error, trace};
+use tracing::{error, info, trace};
 
 use casper_execution_engine::{
     core::engine_state::{
-        genesis::GenesisResult, EngineConfig, EngineState, Error, GetEraValidatorsError,
-        GetEraValidatorsRequest,
+        execute_request::ExecuteRequest, genesis::GenesisResult, step::StepResult, EngineConfig,
+        EngineState, Error, GetEraValidatorsError, GetEraValidatorsRequest,
     },
     shared::newtypes::{Blake2bHash, CorrelationId},
     storage::{
-        error::lmdb::Error as StorageLmdbError, global_state::lmdb::LmdbGlobalState,
+        error::{lmdb::Error as StorageLmdbError, Error as StorageError},
+        global_state::{lmdb::LmdbGlobalState, CommitResult, StateProvider},
         protocol_data_store::lmdb::LmdbProtocolDataStore,
-        transaction_source::lmdb::LmdbEnvironment, trie_store::lmdb::LmdbTrieStore,
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
     },
 };
-use casper_types::{system::auction::ValidatorWeights, ProtocolVersion};
+use casper_types::{
+    bytesrepr::ToBytes, system::auction::ValidatorWeights, ExecutionResult, Key, ProtocolVersion,
+};
 
 use crate::{
     components::Component,
     effect::{requests::ContractRuntimeRequest, EffectBuilder, EffectExt, Effects},
-    types::Chainspec,
-    utils::WithDir,
+    types::{chainspec, Chainspec, ChecksumRegistry},
+    utils::{Loadable, WithDir},
     NodeRng, StorageConfig,
 };
 
@@ -46,6 +54,8 @@ use crate::{
 #[derive(DataSize)]
 pub struct ContractRuntime {
     engine_state: Arc<EngineState<LmdbGlobalState>>,
+    /// Engine executing blocks with a candidate configuration, if shadow execution is enabled.
+    shadow_engine_state: Option<Arc<EngineState<LmdbGlobalState>>>,
     metrics: Arc<ContractRuntimeMetrics>,
 }
 
@@ -78,15 +88,18 @@ pub struct ContractRuntimeMetrics {
     apply_effect: Histogram,
     commit_upgrade: Histogram,
     run_query: Histogram,
+    run_query_batch: Histogram,
     commit_step: Histogram,
     get_balance: Histogram,
+    get_balance_batch: Histogram,
     get_validator_weights: Histogram,
     get_era_validators: Histogram,
     get_era_validator_weights_by_era_id: Histogram,
-    get_bids: Histogram,
+    get_keys_by_prefix: Histogram,
     missing_trie_keys: Histogram,
     put_trie: Histogram,
     read_trie: Histogram,
+    shadow_execute: Histogram,
 }
 
 /// Value of upper bound of histogram.
@@ -107,12 +120,16 @@ const APPLY_EFFECT_NAME: &str = "contract_runtime_apply_commit";
 const APPLY_EFFECT_HELP: &str = "tracking run of engine_state.apply_effect in seconds.";
 const RUN_QUERY_NAME: &str = "contract_runtime_run_query";
 const RUN_QUERY_HELP: &str = "tracking run of engine_state.run_query in seconds.";
+const RUN_QUERY_BATCH_NAME: &str = "contract_runtime_run_query_batch";
+const RUN_QUERY_BATCH_HELP: &str = "tracking run of engine_state.run_query_batch in seconds.";
 const COMMIT_STEP_NAME: &str = "contract_runtime_commit_step";
 const COMMIT_STEP_HELP: &str = "tracking run of engine_state.commit_step in seconds.";
 const COMMIT_UPGRADE_NAME: &str = "contract_runtime_commit_upgrade";
 const COMMIT_UPGRADE_HELP: &str = "tracking run of engine_state.commit_upgrade in seconds";
 const GET_BALANCE_NAME: &str = "contract_runtime_get_balance";
 const GET_BALANCE_HELP: &str = "tracking run of engine_state.get_balance in seconds.";
+const GET_BALANCE_BATCH_NAME: &str = "contract_runtime_get_balance_batch";
+const GET_BALANCE_BATCH_HELP: &str = "tracking run of engine_state.get_purse_balances in seconds.";
 const GET_VALIDATOR_WEIGHTS_NAME: &str = "contract_runtime_get_validator_weights";
 const GET_VALIDATOR_WEIGHTS_HELP: &str =
     "tracking run of engine_state.get_validator_weights in seconds.";
@@ -122,14 +139,16 @@ const GET_ERA_VALIDATORS_WEIGHT_BY_ERA_ID_NAME: &str =
     "contract_runtime_get_era_validator_weights_by_era_id";
 const GET_ERA_VALIDATORS_WEIGHT_BY_ERA_ID_HELP: &str =
     "tracking run of engine_state.get_era_validator_weights_by_era_id in seconds.";
-const GET_BIDS_NAME: &str = "contract_runtime_get_bids";
-const GET_BIDS_HELP: &str = "tracking run of engine_state.get_bids in seconds.";
+const GET_KEYS_BY_PREFIX_NAME: &str = "contract_runtime_get_keys_by_prefix";
+const GET_KEYS_BY_PREFIX_HELP: &str = "tracking run of engine_state.get_keys_by_prefix in seconds.";
 const READ_TRIE_NAME: &str = "contract_runtime_read_trie";
 const READ_TRIE_HELP: &str = "tracking run of engine_state.read_trie in seconds.";
 const PUT_TRIE_NAME: &str = "contract_runtime_put_trie";
 const PUT_TRIE_HELP: &str = "tracking run of engine_state.put_trie in seconds.";
 const MISSING_TRIE_KEYS_NAME: &str = "contract_runtime_missing_trie_keys";
 const MISSING_TRIE_KEYS_HELP: &str = "tracking run of engine_state.missing_trie_keys in seconds.";
+const SHADOW_EXECUTE_NAME: &str = "contract_runtime_shadow_execute";
+const SHADOW_EXECUTE_HELP: &str = "tracking run of executing a block in shadow mode in seconds.";
 
 /// Create prometheus Histogram and register.
 fn register_histogram_metric(
@@ -159,6 +178,11 @@ impl ContractRuntimeMetrics {
                 APPLY_EFFECT_HELP,
             )?,
             run_query: register_histogram_metric(registry, RUN_QUERY_NAME, RUN_QUERY_HELP)?,
+            run_query_batch: register_histogram_metric(
+                registry,
+                RUN_QUERY_BATCH_NAME,
+                RUN_QUERY_BATCH_HELP,
+            )?,
             commit_step: register_histogram_metric(registry, COMMIT_STEP_NAME, COMMIT_STEP_HELP)?,
             commit_upgrade: register_histogram_metric(
                 registry,
@@ -166,6 +190,11 @@ impl ContractRuntimeMetrics {
                 COMMIT_UPGRADE_HELP,
             )?,
             get_balance: register_histogram_metric(registry, GET_BALANCE_NAME, GET_BALANCE_HELP)?,
+            get_balance_batch: register_histogram_metric(
+                registry,
+                GET_BALANCE_BATCH_NAME,
+                GET_BALANCE_BATCH_HELP,
+            )?,
             get_validator_weights: register_histogram_metric(
                 registry,
                 GET_VALIDATOR_WEIGHTS_NAME,
@@ -181,7 +210,11 @@ impl ContractRuntimeMetrics {
                 GET_ERA_VALIDATORS_WEIGHT_BY_ERA_ID_NAME,
                 GET_ERA_VALIDATORS_WEIGHT_BY_ERA_ID_HELP,
             )?,
-            get_bids: register_histogram_metric(registry, GET_BIDS_NAME, GET_BIDS_HELP)?,
+            get_keys_by_prefix: register_histogram_metric(
+                registry,
+                GET_KEYS_BY_PREFIX_NAME,
+                GET_KEYS_BY_PREFIX_HELP,
+            )?,
             read_trie: register_histogram_metric(registry, READ_TRIE_NAME, READ_TRIE_HELP)?,
             put_trie: register_histogram_metric(registry, PUT_TRIE_NAME, PUT_TRIE_HELP)?,
             missing_trie_keys: register_histogram_metric(
@@ -189,6 +222,11 @@ impl ContractRuntimeMetrics {
                 MISSING_TRIE_KEYS_NAME,
                 MISSING_TRIE_KEYS_HELP,
             )?,
+            shadow_execute: register_histogram_metric(
+                registry,
+                SHADOW_EXECUTE_NAME,
+                SHADOW_EXECUTE_HELP,
+            )?,
         })
     }
 }
@@ -218,6 +256,13 @@ where
 
                 responder.respond(result).ignore()
             }
+            Event::Request(ContractRuntimeRequest::GetCostTable {
+                protocol_version,
+                responder,
+            }) => {
+                let result = self.engine_state.get_cost_table(protocol_version);
+                responder.respond(result).ignore()
+            }
             Event::Request(ContractRuntimeRequest::CommitGenesis {
                 chainspec,
                 responder,
@@ -321,6 +366,31 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::QueryBatch {
+                query_batch_request,
+                responder,
+            }) => {
+                trace!(?query_batch_request, "query batch");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result =
+                            engine_state.run_query_batch(correlation_id, query_batch_request);
+                        metrics
+                            .run_query_batch
+                            .observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "query batch result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
             Event::Request(ContractRuntimeRequest::GetBalance {
                 balance_request,
                 responder,
@@ -347,6 +417,31 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::GetBalanceBatch {
+                balance_batch_request,
+                responder,
+            }) => {
+                trace!(?balance_batch_request, "balance batch");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result =
+                            engine_state.get_purse_balances(correlation_id, balance_batch_request);
+                        metrics
+                            .get_balance_batch
+                            .observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "balance batch result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
             Event::Request(ContractRuntimeRequest::IsBonded {
                 state_root_hash,
                 era_id,
@@ -443,24 +538,27 @@ where
                 }
                 .ignore()
             }
-            Event::Request(ContractRuntimeRequest::GetBids {
-                get_bids_request,
+            Event::Request(ContractRuntimeRequest::GetKeysByPrefix {
+                get_keys_request,
                 responder,
             }) => {
-                trace!(?get_bids_request, "get bids request");
+                trace!(?get_keys_request, "get keys by prefix request");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
                     let correlation_id = CorrelationId::new();
                     let result = task::spawn_blocking(move || {
                         let start = Instant::now();
-                        let result = engine_state.get_bids(correlation_id, get_bids_request);
-                        metrics.get_bids.observe(start.elapsed().as_secs_f64());
+                        let result =
+                            engine_state.get_keys_by_prefix(correlation_id, get_keys_request);
+                        metrics
+                            .get_keys_by_prefix
+                            .observe(start.elapsed().as_secs_f64());
                         result
                     })
                     .await
                     .expect("should run");
-                    trace!(?result, "get bids result");
+                    trace!(?result, "get keys by prefix result");
                     responder.respond(result).await
                 }
                 .ignore()
@@ -536,6 +634,25 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::EstimatePurge {
+                state_root_hash,
+                keys,
+                responder,
+            }) => {
+                trace!(%state_root_hash, key_count = keys.len(), "estimate purge request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        estimate_purge(&engine_state, correlation_id, state_root_hash, &keys)
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "estimate purge response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
             Event::Request(ContractRuntimeRequest::MissingTrieKeys {
                 trie_key,
                 responder,
@@ -558,10 +675,119 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::ShadowExecute { request, responder }) => {
+                trace!(?request, "shadow execute request");
+                let shadow_engine_state = match &self.shadow_engine_state {
+                    Some(shadow_engine_state) => Arc::clone(shadow_engine_state),
+                    None => return responder.respond(None).ignore(),
+                };
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result = shadow_execute(&shadow_engine_state, *request);
+                        metrics
+                            .shadow_execute
+                            .observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "shadow execute response");
+                    responder.respond(Some(result)).await
+                }
+                .ignore()
+            }
         }
     }
 }
 
+/// Reads the values stored under `keys` in the global state at `state_root_hash`, summing up what
+/// would be removed by purging them.
+///
+/// Returns `None` if the state root is not known.
+fn estimate_purge<S>(
+    engine_state: &EngineState<S>,
+    correlation_id: CorrelationId,
+    state_root_hash: Blake2bHash,
+    keys: &[Key],
+) -> Result<Option<PurgeEstimate>, Error>
+where
+    S: StateProvider<Error = StorageError>,
+{
+    let mut tracking_copy = match engine_state.tracking_copy(state_root_hash)? {
+        Some(tracking_copy) => tracking_copy,
+        None => return Ok(None),
+    };
+    let mut estimate = PurgeEstimate::default();
+    for key in keys {
+        match tracking_copy.get(correlation_id, key)? {
+            Some(value) => {
+                estimate.present_keys += 1;
+                estimate.total_bytes +=
+                    (key.serialized_length() + value.serialized_length()) as u64;
+            }
+            None => estimate.absent_keys += 1,
+        }
+    }
+    Ok(Some(estimate))
+}
+
+/// Executes the deploys of a block one by one, followed by the step if given, committing the
+/// effects of each to the global state, writes the block's checksum registry if requested, and
+/// returns the resulting state root hash.
+///
+/// This mirrors the execution of blocks by the block executor.  The trie store is shared with the
+/// active engine, but as tries are content-addressed, the committed nodes are only ever reachable
+/// from the state root hashes returned here.
+fn shadow_execute(
+    engine_state: &EngineState<LmdbGlobalState>,
+    request: ShadowExecutionRequest,
+) -> Result<Blake2bHash, ShadowExecutionError> {
+    let correlation_id = CorrelationId::new();
+    let mut state_root_hash = request.pre_state_hash;
+    let mut block_execution_results = Vec::with_capacity(request.deploy_items.len());
+    for deploy_item in request.deploy_items {
+        let execute_request = ExecuteRequest::new(
+            state_root_hash,
+            request.block_time,
+            vec![Ok(deploy_item)],
+            request.protocol_version,
+            request.proposer.clone(),
+        );
+        let execution_results = engine_state
+            .run_execute(correlation_id, execute_request)
+            .map_err(ShadowExecutionError::RootNotFound)?;
+        for execution_result in execution_results {
+            block_execution_results.push(ExecutionResult::from(&execution_result));
+            let transforms = execution_result.effect().transforms.clone();
+            state_root_hash =
+                match engine_state.apply_effect(correlation_id, state_root_hash, transforms)? {
+                    CommitResult::Success { state_root } => state_root,
+                    commit_result => return Err(ShadowExecutionError::Commit(commit_result)),
+                };
+        }
+    }
+    if let Some(mut step_request) = request.step_request {
+        step_request.pre_state_hash = state_root_hash;
+        state_root_hash = match engine_state.commit_step(correlation_id, step_request)? {
+            StepResult::Success {
+                post_state_hash, ..
+            } => post_state_hash,
+            step_result => return Err(ShadowExecutionError::Step(step_result)),
+        };
+    }
+    if let Some(deploy_hashes) = request.checksum_registry_deploy_hashes {
+        let registry = ChecksumRegistry::new(deploy_hashes.iter().zip(&block_execution_results));
+        state_root_hash =
+            match engine_state.apply_effect(correlation_id, state_root_hash, registry.effects())? {
+                CommitResult::Success { state_root } => state_root,
+                commit_result => return Err(ShadowExecutionError::Commit(commit_result)),
+            };
+    }
+    Ok(state_root_hash)
+}
+
 /// Error returned from mis-configuring the contract runtime component.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -571,6 +797,12 @@ pub enum ConfigError {
     /// Error initializing metrics.
     #[error("failed to initialize metrics for contract runtime: {0}")]
     Prometheus(#[from] prometheus::Error),
+    /// Error creating the thread pool used for hashing during commits.
+    #[error("failed to create trie hashing thread pool for contract runtime: {0}")]
+    ThreadPool(#[from] ThreadPoolBuildError),
+    /// Error loading the candidate chainspec for shadow execution.
+    #[error("failed to load shadow chainspec for contract runtime: {0}")]
+    ShadowChainspec(#[from] chainspec::Error),
 }
 
 impl ContractRuntime {
@@ -598,7 +830,33 @@ impl ContractRuntime {
             DatabaseFlags::empty(),
         )?);
 
-        let global_state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store)?;
+        let shadow_engine_state = match contract_runtime_config.shadow_chainspec_path() {
+            Some(shadow_chainspec_path) => {
+                let shadow_chainspec = Chainspec::from_path(
+                    storage_config.with_dir(shadow_chainspec_path.to_path_buf()),
+                )?;
+                info!(
+                    path = %shadow_chainspec_path.display(),
+                    "shadow execution enabled"
+                );
+                let shadow_global_state = LmdbGlobalState::empty(
+                    Arc::clone(&environment),
+                    Arc::clone(&trie_store),
+                    Arc::clone(&protocol_data_store),
+                )?;
+                let shadow_engine_config = EngineConfig::new()
+                    .with_wasm_config_override(shadow_chainspec.wasm_config)
+                    .with_system_config_override(shadow_chainspec.system_costs_config);
+                Some(Arc::new(EngineState::new(
+                    shadow_global_state,
+                    shadow_engine_config,
+                )))
+            }
+            None => None,
+        };
+
+        let global_state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store)?
+            .with_commit_hashing_threads(contract_runtime_config.commit_hashing_threads())?;
         let engine_config = EngineConfig::new();
 
         let engine_state = Arc::new(EngineState::new(global_state, engine_config));
@@ -606,6 +864,7 @@ impl ContractRuntime {
         let metrics = Arc::new(ContractRuntimeMetrics::new(registry)?);
         Ok(ContractRuntime {
             engine_state,
+            shadow_engine_state,
             metrics,
         })
     }
@@ -641,3 +900,56 @@ impl ContractRuntime {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::{
+        shared::stored_value::StoredValue, storage::global_state::in_memory::InMemoryGlobalState,
+    };
+    use casper_types::CLValue;
+
+    use super::*;
+
+    #[test]
+    fn should_estimate_purge_of_known_keys() {
+        let correlation_id = CorrelationId::new();
+        let present = vec![
+            (
+                Key::Hash([1; 32]),
+                StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
+            ),
+            (
+                Key::Hash([2; 32]),
+                StoredValue::CLValue(CLValue::from_t("value".to_string()).unwrap()),
+            ),
+        ];
+        let (state, state_root_hash) =
+            InMemoryGlobalState::from_pairs(correlation_id, &present).unwrap();
+        let engine_state = EngineState::new(state, EngineConfig::default());
+
+        let absent = Key::Hash([3; 32]);
+        let keys = vec![present[0].0, present[1].0, absent];
+        let estimate = estimate_purge(&engine_state, correlation_id, state_root_hash, &keys)
+            .unwrap()
+            .expect("state root should be known");
+        let expected_bytes: usize = present
+            .iter()
+            .map(|(key, value)| key.serialized_length() + value.serialized_length())
+            .sum();
+        assert_eq!(
+            estimate,
+            PurgeEstimate {
+                present_keys: 2,
+                absent_keys: 1,
+                total_bytes: expected_bytes as u64,
+            }
+        );
+
+        let unknown_root = Blake2bHash::new(b"unknown");
+        assert!(
+            estimate_purge(&engine_state, correlation_id, unknown_root, &keys)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
@@ -5,9 +7,10 @@ use casper_execution_engine::shared::utils;
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
+const DEFAULT_COMMIT_HASHING_THREADS: usize = 4;
 
 /// Contract runtime configuration.
-#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -21,6 +24,17 @@ pub struct Config {
     ///
     /// Defaults to 512.
     max_readers: Option<u32>,
+    /// The number of worker threads used to hash and serialize trie nodes when committing to the
+    /// global state.  0 or 1 disables parallel hashing.
+    ///
+    /// Defaults to 4.
+    commit_hashing_threads: Option<usize>,
+    /// Path to a directory holding a candidate chainspec.  If set, a participating node
+    /// additionally executes every block in "shadow mode" using the wasm and system configs of
+    /// the candidate, and logs if the resulting state root hash differs from the actual one.
+    ///
+    /// Shadow execution never affects consensus.  Defaults to `None`.
+    shadow_chainspec_path: Option<PathBuf>,
 }
 
 impl Config {
@@ -35,6 +49,15 @@ impl Config {
     pub(crate) fn max_readers(&self) -> u32 {
         self.max_readers.unwrap_or(DEFAULT_MAX_READERS)
     }
+
+    pub(crate) fn commit_hashing_threads(&self) -> usize {
+        self.commit_hashing_threads
+            .unwrap_or(DEFAULT_COMMIT_HASHING_THREADS)
+    }
+
+    pub(crate) fn shadow_chainspec_path(&self) -> Option<&Path> {
+        self.shadow_chainspec_path.as_deref()
+    }
 }
 
 impl Default for Config {
@@ -42,6 +65,8 @@ impl Default for Config {
         Config {
             max_global_state_size: Some(DEFAULT_MAX_GLOBAL_STATE_SIZE),
             max_readers: Some(DEFAULT_MAX_READERS),
+            commit_hashing_threads: Some(DEFAULT_COMMIT_HASHING_THREADS),
+            shadow_chainspec_path: None,
         }
     }
 }
@@ -1,8 +1,19 @@
-use crate::components::consensus::EraId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{components::consensus::EraId, types::DeployHash};
 use casper_execution_engine::{
-    core::engine_state::GetEraValidatorsRequest, shared::newtypes::Blake2bHash,
+    core::engine_state::{
+        self,
+        deploy_item::DeployItem,
+        step::{StepRequest, StepResult},
+        GetEraValidatorsRequest, RootNotFound,
+    },
+    shared::newtypes::Blake2bHash,
+    storage::global_state::CommitResult,
 };
-use casper_types::ProtocolVersion;
+use casper_types::{ProtocolVersion, PublicKey};
 
 /// Request for validator weights for a specific era.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -76,3 +87,56 @@ impl From<EraValidatorsRequest> for GetEraValidatorsRequest {
         GetEraValidatorsRequest::new(input.state_hash, input.protocol_version)
     }
 }
+
+/// The amount of global state data stored under a set of keys, i.e. what would be removed by
+/// purging these keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PurgeEstimate {
+    /// The number of keys which hold a value.
+    pub present_keys: u64,
+    /// The number of keys which hold no value, and would therefore be unaffected.
+    pub absent_keys: u64,
+    /// The total serialized size in bytes of the present keys and their values.  This excludes the
+    /// trie nodes storing them, so it only approximates the space freed by purging the keys.
+    pub total_bytes: u64,
+}
+
+/// A block to be executed by the shadow engine, which runs a candidate configuration alongside the
+/// active one.
+#[derive(Debug)]
+pub struct ShadowExecutionRequest {
+    /// The state root hash the block is executed on.
+    pub pre_state_hash: Blake2bHash,
+    /// The block's timestamp in milliseconds.
+    pub block_time: u64,
+    /// The block's proposer.
+    pub proposer: PublicKey,
+    /// The protocol version to execute the block with.
+    pub protocol_version: ProtocolVersion,
+    /// The block's deploys, in execution order.
+    pub deploy_items: Vec<DeployItem>,
+    /// The end of era step to run after the deploys on a switch block.
+    ///
+    /// Its pre-state hash is replaced by the state root hash resulting from executing the deploys.
+    pub step_request: Option<StepRequest>,
+    /// The hashes of the block's deploys, in execution order, if the block writes a checksum
+    /// registry after the step.
+    pub checksum_registry_deploy_hashes: Option<Vec<DeployHash>>,
+}
+
+/// Error executing a block with the shadow engine.
+#[derive(Debug, Error)]
+pub enum ShadowExecutionError {
+    /// The pre-state hash is not known.
+    #[error("root not found: {0:?}")]
+    RootNotFound(RootNotFound),
+    /// The engine failed.
+    #[error(transparent)]
+    Engine(#[from] engine_state::Error),
+    /// Committing the effects of a deploy failed.
+    #[error("commit failed: {0:?}")]
+    Commit(CommitResult),
+    /// Running the end of era step failed.
+    #[error("step failed: {0:?}")]
+    Step(StepResult),
+}
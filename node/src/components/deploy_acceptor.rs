@@ -1,7 +1,11 @@
 mod config;
 mod event;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::Infallible,
+    fmt::Debug,
+};
 
 use thiserror::Error;
 use tracing::{debug, error, info};
@@ -13,7 +17,10 @@ use crate::{
         requests::{ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
-    types::{chainspec::DeployConfig, Chainspec, Deploy, DeployValidationFailure, NodeId},
+    types::{
+        chainspec::DeployConfig, Approval, Chainspec, Deploy, DeployHash, DeployHeader,
+        DeployValidationFailure, NodeId, TimeDiff, Timestamp,
+    },
     utils::Source,
     NodeRng,
 };
@@ -34,6 +41,171 @@ pub enum Error {
     /// A deploy was sent from account with insufficient balance.
     #[error("insufficient balance")]
     InsufficientBalance,
+    /// The deploy's time-to-live is below the minimum accepted by this node.
+    #[error("time-to-live of {got} is below the minimum of {min_ttl}")]
+    InsufficientTimeToLive {
+        /// The minimum time-to-live.
+        min_ttl: TimeDiff,
+        /// The received time-to-live.
+        got: TimeDiff,
+    },
+    /// The deploy's time-to-live exceeds the maximum accepted by this node.
+    #[error("time-to-live of {got} exceeds the maximum of {max_ttl}")]
+    ExcessiveTimeToLive {
+        /// The maximum time-to-live.
+        max_ttl: TimeDiff,
+        /// The received time-to-live.
+        got: TimeDiff,
+    },
+    /// The deploy's timestamp is too far ahead of this node's clock.
+    #[error(
+        "timestamp {deploy_timestamp} is more than {max_leeway} ahead of the node's current time \
+         {current_timestamp}"
+    )]
+    TimestampInFuture {
+        /// The deploy's timestamp.
+        deploy_timestamp: Timestamp,
+        /// The node's current time.
+        current_timestamp: Timestamp,
+        /// How far ahead of the node's clock a timestamp may be.
+        max_leeway: TimeDiff,
+    },
+    /// The deploy is approved by keys not associated with its account.
+    #[error("approvals from keys not associated with the account")]
+    UnauthorizedApprovals,
+    /// Approvals were submitted for a deploy which isn't awaiting approvals.
+    #[error("{0} is not awaiting approvals")]
+    NotAwaitingApprovals(DeployHash),
+    /// The deploy lacks approvals, but no more deploys can be held awaiting approvals.
+    #[error("the limit of {max} deploys awaiting approvals is reached")]
+    TooManyDeploysAwaitingApprovals {
+        /// The maximum number of deploys awaiting approvals.
+        max: usize,
+    },
+    /// The node is in read-only mode because its storage volume is almost full.
+    #[error("the node is not accepting deploys as it is low on disk space")]
+    ReadOnlyMode,
+    /// The node only serves an offline copy of its storage.
+    #[error("the node is not accepting deploys as it only serves an offline copy of its storage")]
+    OfflineNode,
+}
+
+impl Error {
+    /// Returns the code identifying the kind of this error to clients.
+    pub fn code(&self) -> AcceptanceErrorCode {
+        match self {
+            Error::InvalidDeploy(failure) => match failure {
+                DeployValidationFailure::InvalidChainName { .. } => {
+                    AcceptanceErrorCode::InvalidChainName
+                }
+                DeployValidationFailure::ExcessiveDependencies { .. } => {
+                    AcceptanceErrorCode::ExcessiveDependencies
+                }
+                DeployValidationFailure::ExcessiveTimeToLive { .. } => {
+                    AcceptanceErrorCode::ExcessiveTimeToLive
+                }
+                DeployValidationFailure::InvalidBodyHash => AcceptanceErrorCode::InvalidBodyHash,
+                DeployValidationFailure::InvalidDeployHash => {
+                    AcceptanceErrorCode::InvalidDeployHash
+                }
+                DeployValidationFailure::InvalidApproval { .. } => {
+                    AcceptanceErrorCode::InvalidApproval
+                }
+                DeployValidationFailure::ExcessiveSessionArgsLength { .. } => {
+                    AcceptanceErrorCode::ExcessiveSessionArgsLength
+                }
+                DeployValidationFailure::ExcessivePaymentArgsLength { .. } => {
+                    AcceptanceErrorCode::ExcessivePaymentArgsLength
+                }
+                DeployValidationFailure::MissingTransferAmount => {
+                    AcceptanceErrorCode::MissingTransferAmount
+                }
+                DeployValidationFailure::InvalidTransferAmount => {
+                    AcceptanceErrorCode::InvalidTransferAmount
+                }
+                DeployValidationFailure::InsufficientTransferAmount { .. } => {
+                    AcceptanceErrorCode::InsufficientTransferAmount
+                }
+                DeployValidationFailure::ExcessivePriorityFee { .. } => {
+                    AcceptanceErrorCode::ExcessivePriorityFee
+                }
+            },
+            Error::InvalidAccount => AcceptanceErrorCode::InvalidAccount,
+            Error::InsufficientBalance => AcceptanceErrorCode::InsufficientBalance,
+            Error::InsufficientTimeToLive { .. } => AcceptanceErrorCode::InsufficientTimeToLive,
+            Error::ExcessiveTimeToLive { .. } => AcceptanceErrorCode::ExcessiveTimeToLive,
+            Error::TimestampInFuture { .. } => AcceptanceErrorCode::TimestampInFuture,
+            Error::UnauthorizedApprovals => AcceptanceErrorCode::UnauthorizedApprovals,
+            Error::NotAwaitingApprovals(_) => AcceptanceErrorCode::NotAwaitingApprovals,
+            Error::TooManyDeploysAwaitingApprovals { .. } => {
+                AcceptanceErrorCode::TooManyDeploysAwaitingApprovals
+            }
+            Error::ReadOnlyMode => AcceptanceErrorCode::ReadOnlyMode,
+            Error::OfflineNode => AcceptanceErrorCode::OfflineNode,
+        }
+    }
+}
+
+/// A numeric code identifying why a deploy or its approvals were rejected, returned to clients
+/// alongside the error message.
+///
+/// The values are part of the node's public API: existing codes must never be changed or reused,
+/// and new kinds of errors get new codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum AcceptanceErrorCode {
+    /// The deploy's chain name doesn't match the network's.
+    InvalidChainName = 1,
+    /// The deploy has more dependencies than allowed.
+    ExcessiveDependencies = 2,
+    /// The deploy's time-to-live exceeds the maximum allowed by the chainspec or this node.
+    ExcessiveTimeToLive = 3,
+    /// The deploy's time-to-live is below the minimum accepted by this node.
+    InsufficientTimeToLive = 4,
+    /// The deploy's timestamp is too far ahead of the node's clock.
+    TimestampInFuture = 5,
+    /// The deploy's body hash doesn't match its body.
+    InvalidBodyHash = 6,
+    /// The deploy's hash doesn't match its header.
+    InvalidDeployHash = 7,
+    /// One of the deploy's approvals has an invalid signature.
+    InvalidApproval = 8,
+    /// The deploy's serialized session args exceed the size limit.
+    ExcessiveSessionArgsLength = 9,
+    /// The deploy's serialized payment args exceed the size limit.
+    ExcessivePaymentArgsLength = 10,
+    /// The transfer has no amount.
+    MissingTransferAmount = 11,
+    /// The transfer's amount can't be parsed.
+    InvalidTransferAmount = 12,
+    /// The transfer's amount is below the minimum.
+    InsufficientTransferAmount = 13,
+    /// The deploy's account doesn't exist.
+    InvalidAccount = 14,
+    /// The deploy's account has insufficient balance.
+    InsufficientBalance = 15,
+    /// The deploy is approved by keys not associated with its account.
+    UnauthorizedApprovals = 16,
+    /// Approvals were submitted for a deploy which isn't awaiting approvals.
+    NotAwaitingApprovals = 17,
+    /// No more deploys can be held awaiting approvals.
+    TooManyDeploysAwaitingApprovals = 18,
+    /// The node isn't accepting deploys as it is low on disk space.
+    ReadOnlyMode = 19,
+    /// The deploy's priority fee exceeds the maximum allowed by the chainspec.
+    ExcessivePriorityFee = 20,
+    /// The node only serves an offline copy of its storage.
+    OfflineNode = 21,
+}
+
+/// The outcome of accepting a deploy or approvals from a client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Acceptance {
+    /// The deploy was accepted, and will be gossiped and proposed.
+    Accepted,
+    /// The approvals of the deploy don't meet its account's deployment threshold yet.  It is held,
+    /// neither gossiped nor proposed, until further approvals are added.
+    AwaitingApprovals,
 }
 
 /// A helper trait constraining `DeployAcceptor` compatible reactor events.
@@ -65,6 +237,14 @@ pub struct DeployAcceptor {
     chain_name: String,
     deploy_config: DeployConfig,
     verify_accounts: bool,
+    min_ttl: TimeDiff,
+    max_ttl: Option<TimeDiff>,
+    max_timestamp_leeway: TimeDiff,
+    max_deploys_awaiting_approvals: usize,
+    /// Deploys from clients whose approvals don't meet their account's deployment threshold yet.
+    awaiting_approvals: HashMap<DeployHash, Box<Deploy>>,
+    /// Whether new deploys are rejected because the node is low on disk space.
+    read_only: bool,
 }
 
 impl DeployAcceptor {
@@ -73,9 +253,47 @@ impl DeployAcceptor {
             chain_name: chainspec.network_config.name.clone(),
             deploy_config: chainspec.deploy_config,
             verify_accounts: config.verify_accounts(),
+            min_ttl: config.min_ttl(),
+            max_ttl: config.max_ttl(),
+            max_timestamp_leeway: config.max_timestamp_leeway(),
+            max_deploys_awaiting_approvals: config.max_deploys_awaiting_approvals(),
+            awaiting_approvals: HashMap::new(),
+            read_only: false,
         }
     }
 
+    /// Checks the timestamp and time-to-live of a deploy received from a client against the bounds
+    /// configured for this node.
+    ///
+    /// This rejects deploys which would otherwise only be dropped later, e.g. by the block
+    /// proposer.
+    fn check_timing(
+        &self,
+        header: &DeployHeader,
+        current_timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let ttl = header.ttl();
+        if ttl < self.min_ttl {
+            return Err(Error::InsufficientTimeToLive {
+                min_ttl: self.min_ttl,
+                got: ttl,
+            });
+        }
+        if let Some(max_ttl) = self.max_ttl {
+            if ttl > max_ttl {
+                return Err(Error::ExcessiveTimeToLive { max_ttl, got: ttl });
+            }
+        }
+        if header.timestamp() > current_timestamp + self.max_timestamp_leeway {
+            return Err(Error::TimestampInFuture {
+                deploy_timestamp: header.timestamp(),
+                current_timestamp,
+                max_leeway: self.max_timestamp_leeway,
+            });
+        }
+        Ok(())
+    }
+
     /// Handles receiving a new `Deploy` from a peer or client.
     /// In the case of a peer, there should be no responder and the variant should be `None`
     /// In the case of a client, there should be a responder to communicate the validity of the
@@ -85,8 +303,16 @@ impl DeployAcceptor {
         effect_builder: EffectBuilder<REv>,
         deploy: Box<Deploy>,
         source: Source<NodeId>,
-        maybe_responder: Option<Responder<Result<(), Error>>>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
     ) -> Effects<Event> {
+        if self.read_only {
+            debug!(deploy_hash = %deploy.id(), %source, "rejecting deploy in read-only mode");
+            return match maybe_responder {
+                Some(responder) => responder.respond(Err(Error::ReadOnlyMode)).ignore(),
+                None => Effects::new(),
+            };
+        }
+
         let mut cloned_deploy = deploy.clone();
         let mut effects = Effects::new();
         let is_acceptable = cloned_deploy.is_acceptable(&self.chain_name, &self.deploy_config);
@@ -104,6 +330,21 @@ impl DeployAcceptor {
             return effects;
         }
 
+        if source.from_client() {
+            if let Err(error) = self.check_timing(deploy.header(), Timestamp::now()) {
+                info!(deploy_hash = %deploy.id(), %error, "rejecting deploy from client");
+                if let Some(responder) = maybe_responder {
+                    effects.extend(responder.respond(Err(error)).ignore());
+                }
+                effects.extend(
+                    effect_builder
+                        .announce_invalid_deploy(deploy, source)
+                        .ignore(),
+                );
+                return effects;
+            }
+        }
+
         let account_key = deploy.header().account().to_account_hash().into();
 
         // skip account verification if deploy not received from client or node is configured to
@@ -138,27 +379,26 @@ impl DeployAcceptor {
         source: Source<NodeId>,
         account_key: Key,
         verified: Option<bool>,
-        maybe_responder: Option<Responder<Result<(), Error>>>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
     ) -> Effects<Event> {
         let mut effects = Effects::new();
 
         match verified {
             Some(true) => {
-                // The client submitted a valid deploy. Return an Ok status to the RPC component via
-                // the responder.
-                if let Some(responder) = maybe_responder {
-                    effects.extend(responder.respond(Ok(())).ignore());
-                }
-
-                effects.extend(effect_builder.put_deploy_to_storage(deploy.clone()).event(
-                    move |is_new| Event::PutToStorageResult {
+                // Deploys from clients are only accepted once sufficiently approved, which can only
+                // be checked if accounts are verified.
+                if source.from_client()
+                    && self.verify_accounts
+                    && self.max_deploys_awaiting_approvals > 0
+                {
+                    return self.check_authorization(
+                        effect_builder,
                         deploy,
                         source,
-                        is_new,
-                    },
-                ));
-
-                return effects;
+                        maybe_responder,
+                    );
+                }
+                return self.store(effect_builder, deploy, source, maybe_responder);
             }
 
             Some(false) => {
@@ -192,6 +432,170 @@ impl DeployAcceptor {
         effects
     }
 
+    /// Checks whether the approvals of the deploy meet its account's deployment threshold.
+    fn check_authorization<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        deploy: Box<Deploy>,
+        source: Source<NodeId>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
+    ) -> Effects<Event> {
+        let account_key = deploy.header().account().to_account_hash().into();
+        let authorization_keys: BTreeSet<_> = deploy
+            .approvals()
+            .iter()
+            .map(|approval| approval.signer().to_account_hash())
+            .collect();
+        effect_builder
+            .check_deploy_authorization(account_key, authorization_keys)
+            .event(move |authorized| Event::AuthorizationResult {
+                deploy,
+                source,
+                authorized,
+                maybe_responder,
+            })
+    }
+
+    fn handle_authorization_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        mut deploy: Box<Deploy>,
+        source: Source<NodeId>,
+        authorized: Option<bool>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
+    ) -> Effects<Event> {
+        match authorized {
+            Some(true) => {
+                self.awaiting_approvals.remove(deploy.id());
+                self.store(effect_builder, deploy, source, maybe_responder)
+            }
+            Some(false) => {
+                // Approvals may have been added to the held copy in the meantime, in which case the
+                // combined approvals need to be checked.
+                if let Some(held) = self.awaiting_approvals.get(deploy.id()) {
+                    if deploy.add_approvals(held.approvals().iter().cloned()) > 0 {
+                        return self.check_authorization(
+                            effect_builder,
+                            deploy,
+                            source,
+                            maybe_responder,
+                        );
+                    }
+                }
+                self.await_approvals(deploy, maybe_responder, Timestamp::now())
+            }
+            None => {
+                info!(deploy_hash = %deploy.id(), "received deploy with unauthorized approvals");
+                let mut effects = Effects::new();
+                if let Some(responder) = maybe_responder {
+                    effects.extend(
+                        responder
+                            .respond(Err(Error::UnauthorizedApprovals))
+                            .ignore(),
+                    );
+                }
+                // If held, only the additional approvals were invalid.
+                if !self.awaiting_approvals.contains_key(deploy.id()) {
+                    effects.extend(
+                        effect_builder
+                            .announce_invalid_deploy(deploy, source)
+                            .ignore(),
+                    );
+                }
+                effects
+            }
+        }
+    }
+
+    /// Holds the deploy until it is sufficiently approved, unless too many deploys are held
+    /// already.
+    fn await_approvals(
+        &mut self,
+        deploy: Box<Deploy>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
+        now: Timestamp,
+    ) -> Effects<Event> {
+        self.awaiting_approvals
+            .retain(|_, held| !held.header().expired(now));
+        let result = if self.awaiting_approvals.contains_key(deploy.id())
+            || self.awaiting_approvals.len() < self.max_deploys_awaiting_approvals
+        {
+            debug!(deploy_hash = %deploy.id(), "holding deploy awaiting approvals");
+            self.awaiting_approvals.insert(*deploy.id(), deploy);
+            Ok(Acceptance::AwaitingApprovals)
+        } else {
+            info!(deploy_hash = %deploy.id(), "too many deploys awaiting approvals");
+            Err(Error::TooManyDeploysAwaitingApprovals {
+                max: self.max_deploys_awaiting_approvals,
+            })
+        };
+        match maybe_responder {
+            Some(responder) => responder.respond(result).ignore(),
+            None => Effects::new(),
+        }
+    }
+
+    /// Handles receiving further approvals for a deploy from a client.
+    fn add_approvals<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+        responder: Responder<Result<Acceptance, Error>>,
+    ) -> Effects<Event> {
+        if self.read_only {
+            return responder.respond(Err(Error::ReadOnlyMode)).ignore();
+        }
+        let now = Timestamp::now();
+        self.awaiting_approvals
+            .retain(|_, held| !held.header().expired(now));
+        let mut deploy = match self.awaiting_approvals.get(&deploy_hash) {
+            Some(held) => held.clone(),
+            None => {
+                return responder
+                    .respond(Err(Error::NotAwaitingApprovals(deploy_hash)))
+                    .ignore()
+            }
+        };
+        if deploy.add_approvals(approvals) == 0 {
+            return responder
+                .respond(Ok(Acceptance::AwaitingApprovals))
+                .ignore();
+        }
+        if let Err(error) = deploy.is_valid() {
+            info!(%deploy_hash, %error, "received invalid approvals");
+            return responder.respond(Err(Error::InvalidDeploy(error))).ignore();
+        }
+        self.check_authorization(effect_builder, deploy, Source::Client, Some(responder))
+    }
+
+    /// Stores a deploy which passed all checks, responding to the client if it came from one.
+    fn store<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        deploy: Box<Deploy>,
+        source: Source<NodeId>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        // The client submitted a valid deploy. Return an Ok status to the RPC component via the
+        // responder.
+        if let Some(responder) = maybe_responder {
+            effects.extend(responder.respond(Ok(Acceptance::Accepted)).ignore());
+        }
+
+        effects.extend(
+            effect_builder
+                .put_deploy_to_storage(deploy.clone())
+                .event(move |is_new| Event::PutToStorageResult {
+                    deploy,
+                    source,
+                    is_new,
+                }),
+        );
+        effects
+    }
+
     fn handle_put_to_storage<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -204,7 +608,32 @@ impl DeployAcceptor {
                 .announce_new_deploy_accepted(deploy, source)
                 .ignore();
         }
-        Effects::new()
+        // The deploy was validated, so any approvals it carries beyond the stored ones are valid,
+        // too.
+        let deploy_hash = *deploy.id();
+        effect_builder
+            .merge_deploy_approvals_in_storage(deploy_hash, deploy.approvals().to_vec())
+            .event(move |merged_approvals| Event::MergeApprovalsResult {
+                deploy_hash,
+                merged_approvals,
+            })
+    }
+
+    fn handle_merge_approvals_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        deploy_hash: DeployHash,
+        merged_approvals: Option<Vec<Approval>>,
+    ) -> Effects<Event> {
+        match merged_approvals {
+            Some(approvals) => {
+                debug!(%deploy_hash, approvals = approvals.len(), "merged approvals");
+                effect_builder
+                    .announce_deploy_approvals_updated::<NodeId>(deploy_hash, approvals)
+                    .ignore()
+            }
+            None => Effects::new(),
+        }
     }
 }
 
@@ -244,6 +673,151 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
                 verified,
                 maybe_responder,
             ),
+            Event::AuthorizationResult {
+                deploy,
+                source,
+                authorized,
+                maybe_responder,
+            } => self.handle_authorization_result(
+                effect_builder,
+                deploy,
+                source,
+                authorized,
+                maybe_responder,
+            ),
+            Event::AddApprovals {
+                deploy_hash,
+                approvals,
+                responder,
+            } => self.add_approvals(effect_builder, deploy_hash, approvals, responder),
+            Event::MergeApprovalsResult {
+                deploy_hash,
+                merged_approvals,
+            } => self.handle_merge_approvals_result(effect_builder, deploy_hash, merged_approvals),
+            Event::SetReadOnly(read_only) => {
+                self.read_only = read_only;
+                Effects::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    fn new_acceptor(config: Config) -> DeployAcceptor {
+        let mut rng = TestRng::new();
+        DeployAcceptor::new(config, &Chainspec::random(&mut rng))
+    }
+
+    #[test]
+    fn should_check_deploy_timing() {
+        let mut rng = TestRng::new();
+        let deploy = Deploy::random(&mut rng);
+        let header = deploy.header();
+        let acceptor = new_acceptor(Config::default());
+
+        assert!(acceptor.check_timing(header, header.timestamp()).is_ok());
+
+        // A timestamp slightly ahead of the current time is tolerated, but not beyond the leeway.
+        let leeway = Config::default().max_timestamp_leeway();
+        let now = header.timestamp() - leeway;
+        assert!(acceptor.check_timing(header, now).is_ok());
+        let now = header.timestamp() - leeway - TimeDiff::from(1);
+        assert!(matches!(
+            acceptor.check_timing(header, now),
+            Err(Error::TimestampInFuture { .. })
+        ));
+    }
+
+    #[test]
+    fn should_reject_ttl_out_of_bounds() {
+        let mut rng = TestRng::new();
+        let deploy = Deploy::random(&mut rng);
+        let header = deploy.header();
+        let ttl = header.ttl();
+
+        let mut acceptor = new_acceptor(Config::default());
+        acceptor.min_ttl = ttl + TimeDiff::from(1);
+        assert!(matches!(
+            acceptor.check_timing(header, header.timestamp()),
+            Err(Error::InsufficientTimeToLive { got, .. }) if got == ttl
+        ));
+
+        let mut acceptor = new_acceptor(Config::default());
+        acceptor.max_ttl = Some(ttl - TimeDiff::from(1));
+        assert!(matches!(
+            acceptor.check_timing(header, header.timestamp()),
+            Err(Error::ExcessiveTimeToLive { got, .. }) if got == ttl
+        ));
+    }
+
+    #[test]
+    fn acceptance_error_codes_should_be_stable() {
+        let ttl = TimeDiff::from(1);
+        let codes = [
+            (
+                Error::InvalidDeploy(DeployValidationFailure::InvalidApproval {
+                    index: 0,
+                    error_msg: String::new(),
+                }),
+                8,
+            ),
+            (Error::InvalidAccount, 14),
+            (Error::InsufficientBalance, 15),
+            (
+                Error::ExcessiveTimeToLive {
+                    max_ttl: ttl,
+                    got: ttl,
+                },
+                3,
+            ),
+            (
+                Error::InvalidDeploy(DeployValidationFailure::ExcessiveTimeToLive {
+                    max_ttl: ttl,
+                    got: ttl,
+                }),
+                3,
+            ),
+            (Error::ReadOnlyMode, 19),
+            (
+                Error::InvalidDeploy(DeployValidationFailure::ExcessivePriorityFee {
+                    max_priority_fee: 0,
+                    got: 1,
+                }),
+                20,
+            ),
+            (Error::OfflineNode, 21),
+        ];
+        for (error, code) in codes.iter() {
+            assert_eq!(error.code() as u16, *code, "unexpected code for {}", error);
         }
     }
+
+    #[test]
+    fn should_limit_deploys_awaiting_approvals() {
+        let mut rng = TestRng::new();
+        let mut acceptor = new_acceptor(Config::default());
+        acceptor.max_deploys_awaiting_approvals = 1;
+
+        let deploy = Box::new(Deploy::random(&mut rng));
+        let now = deploy.header().timestamp();
+        let _ = acceptor.await_approvals(deploy.clone(), None, now);
+        assert!(acceptor.awaiting_approvals.contains_key(deploy.id()));
+
+        // Holding the same deploy again replaces it, but no further deploys are held.
+        let _ = acceptor.await_approvals(deploy.clone(), None, now);
+        let other_deploy = Box::new(Deploy::random(&mut rng));
+        let _ = acceptor.await_approvals(other_deploy.clone(), None, now);
+        assert_eq!(acceptor.awaiting_approvals.len(), 1);
+        assert!(!acceptor.awaiting_approvals.contains_key(other_deploy.id()));
+
+        // Once the held deploy expires, it makes room for others.
+        let later = deploy.header().expires() + TimeDiff::from(1);
+        let _ = acceptor.await_approvals(other_deploy.clone(), None, later);
+        assert_eq!(acceptor.awaiting_approvals.len(), 1);
+        assert!(acceptor.awaiting_approvals.contains_key(other_deploy.id()));
+    }
 }
@@ -1,28 +1,74 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::{types::TimeDiff, utils::ConfigSection};
+
+const DEFAULT_MIN_TTL: &str = "1min";
+const DEFAULT_MAX_TIMESTAMP_LEEWAY: &str = "2sec";
+const DEFAULT_MAX_DEPLOYS_AWAITING_APPROVALS: usize = 1_000;
+
 /// Configuration options for fetching.
 #[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     verify_accounts: bool,
+    min_ttl: TimeDiff,
+    max_ttl: Option<TimeDiff>,
+    max_timestamp_leeway: TimeDiff,
+    max_deploys_awaiting_approvals: usize,
 }
 
 impl Config {
     /// Constructor for deploy_acceptor config.
     pub fn new(verify_accounts: bool) -> Self {
-        Config { verify_accounts }
+        Config {
+            verify_accounts,
+            ..Config::default()
+        }
     }
 
     /// Get verify_accounts setting.
     pub(crate) fn verify_accounts(&self) -> bool {
         self.verify_accounts
     }
+
+    /// Get the minimum time-to-live of deploys received from clients.
+    pub(crate) fn min_ttl(&self) -> TimeDiff {
+        self.min_ttl
+    }
+
+    /// Get the maximum time-to-live of deploys received from clients, if stricter than the
+    /// chainspec's.
+    pub(crate) fn max_ttl(&self) -> Option<TimeDiff> {
+        self.max_ttl
+    }
+
+    /// Get how far in the future the timestamp of deploys received from clients may be.
+    pub(crate) fn max_timestamp_leeway(&self) -> TimeDiff {
+        self.max_timestamp_leeway
+    }
+
+    /// Get the maximum number of deploys from clients held while awaiting further approvals.
+    pub(crate) fn max_deploys_awaiting_approvals(&self) -> usize {
+        self.max_deploys_awaiting_approvals
+    }
+
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        if let Some(max_ttl) = self.max_ttl {
+            section.at_least("max_ttl", max_ttl, self.min_ttl);
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             verify_accounts: true,
+            min_ttl: DEFAULT_MIN_TTL.parse().unwrap(),
+            max_ttl: None,
+            max_timestamp_leeway: DEFAULT_MAX_TIMESTAMP_LEEWAY.parse().unwrap(),
+            max_deploys_awaiting_approvals: DEFAULT_MAX_DEPLOYS_AWAITING_APPROVALS,
         }
     }
 }
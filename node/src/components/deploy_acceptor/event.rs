@@ -4,9 +4,9 @@ use serde::Serialize;
 
 use super::Source;
 use crate::{
-    components::deploy_acceptor::Error,
+    components::deploy_acceptor::{Acceptance, Error},
     effect::{announcements::RpcServerAnnouncement, Responder},
-    types::{Deploy, NodeId},
+    types::{Approval, Deploy, DeployHash, NodeId},
 };
 use casper_types::Key;
 
@@ -17,7 +17,7 @@ pub enum Event {
     Accept {
         deploy: Box<Deploy>,
         source: Source<NodeId>,
-        responder: Option<Responder<Result<(), Error>>>,
+        responder: Option<Responder<Result<Acceptance, Error>>>,
     },
     /// The result of the `DeployAcceptor` putting a `Deploy` to the storage component.
     PutToStorageResult {
@@ -31,8 +31,30 @@ pub enum Event {
         source: Source<NodeId>,
         account_key: Key,
         verified: Option<bool>,
-        maybe_responder: Option<Responder<Result<(), Error>>>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
     },
+    /// The result of checking whether the approvals of a `Deploy` meet its account's deployment
+    /// threshold.
+    AuthorizationResult {
+        deploy: Box<Deploy>,
+        source: Source<NodeId>,
+        authorized: Option<bool>,
+        maybe_responder: Option<Responder<Result<Acceptance, Error>>>,
+    },
+    /// Further approvals for a `Deploy` awaiting approvals.
+    AddApprovals {
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+        responder: Responder<Result<Acceptance, Error>>,
+    },
+    /// The result of merging the approvals of a `Deploy` which was already stored.
+    MergeApprovalsResult {
+        deploy_hash: DeployHash,
+        /// All approvals of the stored deploy, if any were added.
+        merged_approvals: Option<Vec<Approval>>,
+    },
+    /// Enters or leaves read-only mode, in which new deploys are rejected.
+    SetReadOnly(bool),
 }
 
 impl From<RpcServerAnnouncement> for Event {
@@ -43,6 +65,15 @@ impl From<RpcServerAnnouncement> for Event {
                 source: Source::<NodeId>::Client,
                 responder,
             },
+            RpcServerAnnouncement::DeployApprovalsReceived {
+                deploy_hash,
+                approvals,
+                responder,
+            } => Event::AddApprovals {
+                deploy_hash,
+                approvals,
+                responder,
+            },
         }
     }
 }
@@ -75,6 +106,37 @@ impl Display for Event {
                     account_key
                 )
             }
+            Event::AuthorizationResult {
+                deploy, authorized, ..
+            } => match authorized {
+                Some(true) => write!(formatter, "{} is sufficiently approved", deploy.id()),
+                Some(false) => write!(formatter, "{} is awaiting approvals", deploy.id()),
+                None => write!(formatter, "{} has unauthorized approvals", deploy.id()),
+            },
+            Event::AddApprovals {
+                deploy_hash,
+                approvals,
+                ..
+            } => write!(
+                formatter,
+                "add {} approvals to {}",
+                approvals.len(),
+                deploy_hash
+            ),
+            Event::MergeApprovalsResult {
+                deploy_hash,
+                merged_approvals,
+            } => match merged_approvals {
+                Some(approvals) => write!(
+                    formatter,
+                    "{} now has {} approvals",
+                    deploy_hash,
+                    approvals.len()
+                ),
+                None => write!(formatter, "no approvals added to {}", deploy_hash),
+            },
+            Event::SetReadOnly(true) => write!(formatter, "enter read-only mode"),
+            Event::SetReadOnly(false) => write!(formatter, "leave read-only mode"),
         }
     }
 }
@@ -0,0 +1,253 @@
+//! Deploy watcher.
+//!
+//! Clients submitting a deploy otherwise have to poll until it shows up in a block.  The deploy
+//! watcher keeps track of every accepted deploy and periodically sweeps over those whose TTL plus
+//! a grace period has elapsed.  For each of them it checks the deploy's metadata in storage, and if
+//! the deploy has not been executed in any block, it asks the block proposer about it and announces
+//! that the deploy was not included, along with its last known status.
+
+mod config;
+
+use std::{
+    collections::BTreeSet,
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use futures::future;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    components::{block_proposer::DeployStatus, Component},
+    effect::{
+        announcements::DeployWatcherAnnouncement,
+        requests::{BlockProposerRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects,
+    },
+    types::{DeployHash, Timestamp},
+    utils::{Job, Scheduler},
+    NodeRng,
+};
+pub use config::Config;
+
+/// The last known status of a deploy which was not included in a block.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotIncludedStatus {
+    /// The block proposer never had the deploy pending, e.g. because it had already expired when
+    /// it arrived.
+    NeverSeenByProposer,
+    /// The block proposer had the deploy pending, but it expired before being included.
+    SeenButExpired,
+}
+
+impl Display for NotIncludedStatus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NotIncludedStatus::NeverSeenByProposer => {
+                write!(formatter, "never seen by block proposer")
+            }
+            NotIncludedStatus::SeenButExpired => write!(formatter, "seen but expired"),
+        }
+    }
+}
+
+/// Deploy watcher events.
+#[derive(Debug)]
+pub enum Event {
+    /// A new deploy was accepted and should be watched.
+    DeployAccepted {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The time at which the deploy's TTL elapses.
+        expires: Timestamp,
+    },
+    /// Time to check the deploys which are due.
+    Sweep,
+    /// The due deploys were checked, yielding those which were not included.
+    Swept(Vec<(DeployHash, NotIncludedStatus)>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::DeployAccepted { deploy_hash, .. } => {
+                write!(formatter, "watch deploy {}", deploy_hash)
+            }
+            Event::Sweep => write!(formatter, "sweep watched deploys"),
+            Event::Swept(not_included) => write!(
+                formatter,
+                "swept watched deploys, {} not included",
+                not_included.len()
+            ),
+        }
+    }
+}
+
+/// Deploy watcher component.
+#[derive(DataSize, Debug)]
+pub(crate) struct DeployWatcher {
+    config: Config,
+    /// The watched deploys, along with the time at which they are due to be checked.
+    watched: BTreeSet<(Timestamp, DeployHash)>,
+    /// Scheduler of the periodic sweeps.
+    #[data_size(skip)]
+    scheduler: Scheduler,
+}
+
+impl DeployWatcher {
+    /// Creates a new deploy watcher, scheduling the first sweep.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        effect_builder: EffectBuilder<REv>,
+        scheduler: Scheduler,
+    ) -> (Self, Effects<Event>) {
+        let deploy_watcher = DeployWatcher {
+            config,
+            watched: BTreeSet::new(),
+            scheduler,
+        };
+        let effects = deploy_watcher.schedule_sweep(effect_builder);
+        (deploy_watcher, effects)
+    }
+
+    /// Schedules the next sweep after the configured interval.
+    fn schedule_sweep<REv: Send>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event> {
+        let job = Job::new("deploy_watcher_sweep", self.config.sweep_interval.into());
+        self.scheduler
+            .schedule(effect_builder, job, || Event::Sweep)
+    }
+
+    /// Starts watching a deploy which expires at the given time.
+    fn watch(&mut self, deploy_hash: DeployHash, expires: Timestamp) {
+        self.watched
+            .insert((expires + self.config.grace_period, deploy_hash));
+    }
+
+    /// Stops watching and returns the deploys which are due at the given time.
+    fn take_due(&mut self, now: Timestamp) -> Vec<DeployHash> {
+        let mut due = Vec::new();
+        while let Some(&(due_at, deploy_hash)) = self.watched.iter().next() {
+            if due_at > now {
+                break;
+            }
+            self.watched.remove(&(due_at, deploy_hash));
+            due.push(deploy_hash);
+        }
+        due
+    }
+}
+
+/// Returns the last known status of the deploy if it was not included in a block.
+async fn check_inclusion<REv>(
+    effect_builder: EffectBuilder<REv>,
+    deploy_hash: DeployHash,
+) -> Option<(DeployHash, NotIncludedStatus)>
+where
+    REv: From<StorageRequest> + From<BlockProposerRequest>,
+{
+    let (_, metadata) = effect_builder
+        .get_deploy_and_metadata_from_storage(deploy_hash)
+        .await?;
+    if !metadata.execution_results.is_empty() {
+        return None;
+    }
+    let status = match effect_builder
+        .get_deploy_status_from_proposer(deploy_hash)
+        .await
+    {
+        DeployStatus::Unknown => NotIncludedStatus::NeverSeenByProposer,
+        DeployStatus::Pending | DeployStatus::Expired => NotIncludedStatus::SeenButExpired,
+        // Finalized, but not executed yet.
+        DeployStatus::Finalized => return None,
+    };
+    Some((deploy_hash, status))
+}
+
+impl<REv> Component<REv> for DeployWatcher
+where
+    REv: From<StorageRequest> + From<BlockProposerRequest> + From<DeployWatcherAnnouncement> + Send,
+{
+    type Event = Event;
+    type ConstructionError = Infallible;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::DeployAccepted {
+                deploy_hash,
+                expires,
+            } => {
+                self.watch(deploy_hash, expires);
+                Effects::new()
+            }
+            Event::Sweep => {
+                let due = self.take_due(Timestamp::now());
+                debug!(
+                    due = due.len(),
+                    watched = self.watched.len(),
+                    "sweeping deploys"
+                );
+                async move {
+                    let checks = due
+                        .into_iter()
+                        .map(|deploy_hash| check_inclusion(effect_builder, deploy_hash));
+                    future::join_all(checks)
+                        .await
+                        .into_iter()
+                        .flatten()
+                        .collect()
+                }
+                .event(Event::Swept)
+            }
+            Event::Swept(not_included) => {
+                let mut effects = self.schedule_sweep(effect_builder);
+                for (deploy_hash, status) in not_included {
+                    debug!(%deploy_hash, %status, "deploy not included");
+                    effects.extend(
+                        effect_builder
+                            .announce_deploy_not_included(deploy_hash, status)
+                            .ignore(),
+                    );
+                }
+                effects
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_take_deploys_once_grace_period_elapsed() {
+        let mut rng = TestRng::new();
+        let config = Config {
+            grace_period: "100ms".parse().unwrap(),
+            ..Config::default()
+        };
+        let mut watcher = DeployWatcher {
+            config,
+            watched: BTreeSet::new(),
+            scheduler: Scheduler::new(&mut rng),
+        };
+        let early = DeployHash::random(&mut rng);
+        let late = DeployHash::random(&mut rng);
+        watcher.watch(late, Timestamp::from(2000));
+        watcher.watch(early, Timestamp::from(1000));
+
+        assert!(watcher.take_due(Timestamp::from(1099)).is_empty());
+        assert_eq!(watcher.take_due(Timestamp::from(1100)), vec![early]);
+        assert!(watcher.take_due(Timestamp::from(1100)).is_empty());
+        assert_eq!(watcher.take_due(Timestamp::from(5000)), vec![late]);
+        assert!(watcher.watched.is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::TimeDiff;
+
+const DEFAULT_GRACE_PERIOD: &str = "5min";
+const DEFAULT_SWEEP_INTERVAL: &str = "1min";
+
+/// Configuration options for watching whether deploys get included in a block.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The time to wait after a deploy's TTL elapsed before reporting that it was not included.
+    ///
+    /// The block proposer only remembers deploys which expired while pending for an hour, so with
+    /// a longer grace period such deploys are reported as never seen by the proposer.
+    pub grace_period: TimeDiff,
+
+    /// The interval between two checks for deploys which are due to be reported.
+    pub sweep_interval: TimeDiff,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            grace_period: DEFAULT_GRACE_PERIOD.parse().unwrap(),
+            sweep_interval: DEFAULT_SWEEP_INTERVAL.parse().unwrap(),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Disk watchdog.
+//!
+//! LMDB can be left corrupted if the disk fills up while it is writing.  The disk watchdog
+//! periodically samples the free space on the storage volume, exports it as a metric and warns once
+//! it falls below the configured threshold.  If it falls below the read-only threshold, it
+//! announces this, so that the node stops accepting new deploys until space is freed up again.
+
+mod config;
+mod metrics;
+
+use std::{
+    convert::Infallible,
+    ffi::CString,
+    fmt::{self, Display, Formatter},
+    io,
+    mem::MaybeUninit,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use datasize::DataSize;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use tokio::task;
+use tracing::{debug, info, warn};
+
+use crate::{
+    components::Component,
+    effect::{announcements::DiskWatchdogAnnouncement, EffectBuilder, EffectExt, Effects},
+    utils::{Job, Scheduler},
+    NodeRng,
+};
+pub use config::Config;
+use metrics::DiskWatchdogMetrics;
+
+/// The free and total space of a file system, in bytes.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, Serialize)]
+pub struct DiskSpace {
+    /// The space available to the node.
+    pub available: u64,
+    /// The total size of the file system.
+    pub total: u64,
+}
+
+/// How much free space is left on the storage volume.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskSpaceStatus {
+    /// There is enough free space.
+    Ok,
+    /// The free space is below the warning threshold.
+    Low,
+    /// The free space is below the read-only threshold, and the node is in read-only mode.
+    Critical,
+}
+
+impl Display for DiskSpaceStatus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskSpaceStatus::Ok => write!(formatter, "ok"),
+            DiskSpaceStatus::Low => write!(formatter, "low"),
+            DiskSpaceStatus::Critical => write!(formatter, "critical"),
+        }
+    }
+}
+
+/// Disk watchdog events.
+#[derive(Debug)]
+pub enum Event {
+    /// Time to check the free disk space.
+    CheckDiskSpace,
+    /// The free disk space was measured.  `None` if the measurement failed.
+    DiskSpaceMeasured(Option<DiskSpace>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::CheckDiskSpace => write!(formatter, "check disk space"),
+            Event::DiskSpaceMeasured(Some(disk_space)) => write!(
+                formatter,
+                "measured {} of {} bytes available",
+                disk_space.available, disk_space.total
+            ),
+            Event::DiskSpaceMeasured(None) => write!(formatter, "failed to measure disk space"),
+        }
+    }
+}
+
+/// Disk watchdog component.
+#[derive(DataSize, Debug)]
+pub(crate) struct DiskWatchdog {
+    config: Config,
+    /// The directory on the monitored volume.
+    path: PathBuf,
+    /// The status derived from the last measurement.
+    status: DiskSpaceStatus,
+    #[data_size(skip)]
+    metrics: DiskWatchdogMetrics,
+    /// Scheduler of the periodic checks.
+    #[data_size(skip)]
+    scheduler: Scheduler,
+}
+
+impl DiskWatchdog {
+    /// Creates a new disk watchdog monitoring the volume containing `path`, and schedules the first
+    /// check.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        path: PathBuf,
+        registry: &Registry,
+        effect_builder: EffectBuilder<REv>,
+        scheduler: Scheduler,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        if config.read_only_threshold > config.warn_threshold {
+            warn!(
+                read_only_threshold = config.read_only_threshold,
+                warn_threshold = config.warn_threshold,
+                "disk read-only threshold exceeds warning threshold"
+            );
+        }
+        let effects = effect_builder
+            .immediately()
+            .event(|_| Event::CheckDiskSpace);
+        let disk_watchdog = DiskWatchdog {
+            config,
+            path,
+            status: DiskSpaceStatus::Ok,
+            metrics: DiskWatchdogMetrics::new(registry)?,
+            scheduler,
+        };
+        Ok((disk_watchdog, effects))
+    }
+
+    /// Returns the status following `self.status` given the available space.
+    ///
+    /// Once critical, the status only recovers when the available space exceeds the warning
+    /// threshold, so that the node doesn't flip in and out of read-only mode.
+    fn next_status(&self, available: u64) -> DiskSpaceStatus {
+        if available < self.config.read_only_threshold {
+            DiskSpaceStatus::Critical
+        } else if available < self.config.warn_threshold {
+            if self.status == DiskSpaceStatus::Critical {
+                DiskSpaceStatus::Critical
+            } else {
+                DiskSpaceStatus::Low
+            }
+        } else {
+            DiskSpaceStatus::Ok
+        }
+    }
+
+    fn handle_disk_space_measured<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        maybe_disk_space: Option<DiskSpace>,
+    ) -> Effects<Event>
+    where
+        REv: From<DiskWatchdogAnnouncement> + Send,
+    {
+        let job = Job::new("disk_watchdog_check", self.config.check_interval.into());
+        let mut effects = self
+            .scheduler
+            .schedule(effect_builder, job, || Event::CheckDiskSpace);
+
+        let disk_space = match maybe_disk_space {
+            Some(disk_space) => disk_space,
+            None => {
+                self.metrics.failed_checks.inc();
+                return effects;
+            }
+        };
+        let available = disk_space.available;
+        self.metrics.available_bytes.set(available as i64);
+
+        let status = self.next_status(available);
+        match status {
+            DiskSpaceStatus::Ok => debug!(%available, "measured free disk space"),
+            DiskSpaceStatus::Low => warn!(
+                %available,
+                warn_threshold = %self.config.warn_threshold,
+                "free disk space below threshold, please free up space on the storage volume"
+            ),
+            DiskSpaceStatus::Critical => warn!(
+                %available,
+                read_only_threshold = %self.config.read_only_threshold,
+                "free disk space critically low, node is in read-only mode"
+            ),
+        }
+
+        if status != self.status {
+            match (self.status, status) {
+                (_, DiskSpaceStatus::Critical) => {
+                    warn!(%available, "entering read-only mode, no longer accepting deploys")
+                }
+                (DiskSpaceStatus::Critical, _) => {
+                    info!(%available, "free disk space recovered, leaving read-only mode")
+                }
+                _ => (),
+            }
+            self.status = status;
+            self.metrics
+                .read_only
+                .set((status == DiskSpaceStatus::Critical) as i64);
+            effects.extend(
+                effect_builder
+                    .announce_disk_space_status(status, disk_space)
+                    .ignore(),
+            );
+        }
+        effects
+    }
+}
+
+/// Returns the free and total space of the file system containing `path`.
+pub(crate) fn measure_disk_space(path: &Path) -> io::Result<DiskSpace> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let mut dest: MaybeUninit<libc::statvfs> = MaybeUninit::zeroed();
+
+    let outcome = unsafe { libc::statvfs(c_path.as_ptr(), dest.as_mut_ptr()) };
+    if outcome != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let stat = unsafe { dest.assume_init() };
+    let fragment_size = stat.f_frsize as u64;
+    Ok(DiskSpace {
+        available: stat.f_bavail as u64 * fragment_size,
+        total: stat.f_blocks as u64 * fragment_size,
+    })
+}
+
+impl<REv> Component<REv> for DiskWatchdog
+where
+    REv: From<DiskWatchdogAnnouncement> + Send,
+{
+    type Event = Event;
+    type ConstructionError = Infallible;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::CheckDiskSpace => {
+                let path = self.path.clone();
+                async move {
+                    task::spawn_blocking(move || match measure_disk_space(&path) {
+                        Ok(disk_space) => Some(disk_space),
+                        Err(error) => {
+                            warn!(%error, path = %path.display(), "failed to measure disk space");
+                            None
+                        }
+                    })
+                    .await
+                    .unwrap_or_else(|error| {
+                        warn!(%error, "failed to join tokio task");
+                        None
+                    })
+                }
+                .event(Event::DiskSpaceMeasured)
+            }
+            Event::DiskSpaceMeasured(maybe_disk_space) => {
+                self.handle_disk_space_measured(effect_builder, maybe_disk_space)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_only_leave_read_only_mode_above_warn_threshold() {
+        let config = Config {
+            warn_threshold: 100,
+            read_only_threshold: 10,
+            ..Config::default()
+        };
+        let mut watchdog = DiskWatchdog {
+            config,
+            path: PathBuf::new(),
+            status: DiskSpaceStatus::Ok,
+            metrics: DiskWatchdogMetrics::new(&Registry::new()).unwrap(),
+            scheduler: Scheduler::new(&mut TestRng::new()),
+        };
+        let mut step = |available| {
+            watchdog.status = watchdog.next_status(available);
+            watchdog.status
+        };
+
+        assert_eq!(step(100), DiskSpaceStatus::Ok);
+        assert_eq!(step(99), DiskSpaceStatus::Low);
+        assert_eq!(step(9), DiskSpaceStatus::Critical);
+        assert_eq!(step(50), DiskSpaceStatus::Critical);
+        assert_eq!(step(100), DiskSpaceStatus::Ok);
+        assert_eq!(step(50), DiskSpaceStatus::Low);
+    }
+
+    #[test]
+    fn should_measure_disk_space() {
+        let disk_space = measure_disk_space(&std::env::temp_dir()).unwrap();
+        assert!(disk_space.total > 0);
+        assert!(disk_space.available <= disk_space.total);
+    }
+}
@@ -0,0 +1,48 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::{types::TimeDiff, utils::ConfigSection};
+
+const DEFAULT_CHECK_INTERVAL: &str = "1min";
+const DEFAULT_WARN_THRESHOLD: u64 = 10 * 1024 * 1024 * 1024;
+const DEFAULT_READ_ONLY_THRESHOLD: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Configuration options for monitoring the free space on the storage volume.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The interval between two checks of the free disk space.
+    pub check_interval: TimeDiff,
+
+    /// The free space in bytes below which a warning is logged and announced.
+    pub warn_threshold: u64,
+
+    /// The free space in bytes below which the node switches to read-only mode.
+    ///
+    /// The node only leaves read-only mode once the free space exceeds `warn_threshold` again.
+    pub read_only_threshold: u64,
+}
+
+impl Config {
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("check_interval", self.check_interval, TimeDiff::from(1));
+        section.check(
+            "read_only_threshold",
+            self.read_only_threshold <= self.warn_threshold,
+            self.read_only_threshold,
+            format!("at most warn_threshold ({})", self.warn_threshold),
+        );
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            check_interval: DEFAULT_CHECK_INTERVAL.parse().unwrap(),
+            warn_threshold: DEFAULT_WARN_THRESHOLD,
+            read_only_threshold: DEFAULT_READ_ONLY_THRESHOLD,
+        }
+    }
+}
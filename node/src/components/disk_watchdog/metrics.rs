@@ -0,0 +1,51 @@
+use prometheus::{IntCounter, IntGauge, Registry};
+
+use crate::unregister_metric;
+
+/// Metrics for the disk watchdog.
+#[derive(Debug)]
+pub(super) struct DiskWatchdogMetrics {
+    /// The last measured free space on the storage volume, in bytes.
+    pub(super) available_bytes: IntGauge,
+    /// Whether the node is in read-only mode, as 1 or 0.
+    pub(super) read_only: IntGauge,
+    /// Number of checks which failed to measure the free space.
+    pub(super) failed_checks: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl DiskWatchdogMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let available_bytes = IntGauge::new(
+            "disk_available_bytes",
+            "last measured free space on the storage volume, in bytes",
+        )?;
+        let read_only = IntGauge::new(
+            "disk_read_only_mode",
+            "whether the node is in read-only mode due to low disk space (1) or not (0)",
+        )?;
+        let failed_checks = IntCounter::new(
+            "disk_space_failed_checks",
+            "number of checks which failed to measure the free space on the storage volume",
+        )?;
+        registry.register(Box::new(available_bytes.clone()))?;
+        registry.register(Box::new(read_only.clone()))?;
+        registry.register(Box::new(failed_checks.clone()))?;
+
+        Ok(DiskWatchdogMetrics {
+            available_bytes,
+            read_only,
+            failed_checks,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for DiskWatchdogMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.available_bytes);
+        unregister_metric!(self.registry, self.read_only);
+        unregister_metric!(self.registry, self.failed_checks);
+    }
+}
@@ -9,17 +9,26 @@
 //! a component implementation that interfaces with other components via being plugged into a
 //! reactor, and an external facing http server that manages SSE subscriptions on a single endpoint.
 //!
-//! This component is passive and receives announcements made by other components while never making
-//! a request of other components itself. The handled announcements are serialized to JSON and
-//! pushed to subscribers.
+//! This component is mostly passive and receives announcements made by other components. The
+//! handled announcements are serialized to JSON and pushed to subscribers. The only requests it
+//! makes are for the previous switch block and the bids when a switch block is added, to derive
+//! the `AuctionDelta` event.
 //!
 //! This component uses a ring buffer for outbound events providing some robustness against
 //! unintended subscriber disconnects, if a disconnected subscriber re-subscribes before the buffer
 //! has advanced past their last received event.
 //!
+//! Subscribers can restrict their stream to the activity of a set of accounts, in which case they
+//! only receive `AccountActivity` events derived from the deploys sent by or transferring funds
+//! from or to these accounts.
+//!
+//! Operators can disable types of events, e.g. the high-volume `FinalitySignature` events, in which
+//! case they are not sent to any client.  The enabled types are listed in the node's status.
+//!
 //! For details about the SSE model and a list of supported SSEs, see:
 //! <https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs>
 
+mod auction_delta;
 mod config;
 mod event;
 mod http_server;
@@ -33,20 +42,30 @@ use tokio::sync::mpsc::{self, UnboundedSender};
 
 use super::Component;
 use crate::{
-    effect::{EffectBuilder, Effects},
+    effect::{
+        requests::{ContractRuntimeRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects,
+    },
     utils::{self, ListeningError},
     NodeRng,
 };
 
+pub use auction_delta::{AuctionDelta, StakeChange};
 pub use config::Config;
 pub(crate) use event::Event;
-pub use sse_server::SseData;
+pub use sse_server::{SseData, SseEventType};
 
 /// A helper trait whose bounds represent the requirements for a reactor event that `run_server` can
 /// work with.
-pub trait ReactorEventT: From<Event> + Send {}
+pub trait ReactorEventT:
+    From<Event> + From<StorageRequest> + From<ContractRuntimeRequest> + Send
+{
+}
 
-impl<REv> ReactorEventT for REv where REv: From<Event> + Send + 'static {}
+impl<REv> ReactorEventT for REv where
+    REv: From<Event> + From<StorageRequest> + From<ContractRuntimeRequest> + Send + 'static
+{
+}
 
 #[derive(DataSize, Debug)]
 pub(crate) struct EventStreamServer {
@@ -54,6 +73,8 @@ pub(crate) struct EventStreamServer {
     // TODO - this should not be skipped.  Awaiting support for `UnboundedSender` in datasize crate.
     #[data_size(skip)]
     sse_data_sender: UnboundedSender<SseData>,
+    /// The configuration the server was started with.
+    config: Config,
 }
 
 impl EventStreamServer {
@@ -61,18 +82,27 @@ impl EventStreamServer {
         let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
         let builder = utils::start_listening(&config.address)?;
         tokio::spawn(http_server::run(
-            config,
+            config.clone(),
             api_version,
             builder,
             sse_data_receiver,
         ));
 
-        Ok(EventStreamServer { sse_data_sender })
+        Ok(EventStreamServer {
+            sse_data_sender,
+            config,
+        })
     }
 
-    /// Broadcasts the SSE data to all clients connected to the event stream.
+    /// Broadcasts the SSE data to all clients connected to the event stream, unless no client
+    /// can receive events of its type.
     fn broadcast(&mut self, sse_data: SseData) -> Effects<Event> {
-        let _ = self.sse_data_sender.send(sse_data);
+        let is_needed = sse_data
+            .event_type()
+            .map_or(true, |event_type| self.config.is_needed(event_type));
+        if is_needed {
+            let _ = self.sse_data_sender.send(sse_data);
+        }
         Effects::new()
     }
 }
@@ -86,15 +116,27 @@ where
 
     fn handle_event(
         &mut self,
-        _effect_builder: EffectBuilder<REv>,
+        effect_builder: EffectBuilder<REv>,
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::BlockAdded { block_hash, block } => self.broadcast(SseData::BlockAdded {
-                block_hash,
-                block: Box::new(*block),
-            }),
+            Event::BlockAdded { block_hash, block } => {
+                let mut effects = Effects::new();
+                if block.header().is_switch_block()
+                    && self.config.is_needed(SseEventType::AuctionDelta)
+                {
+                    effects.extend(
+                        auction_delta::compute(effect_builder, block.clone())
+                            .map_some(|delta| Event::AuctionDelta(Box::new(delta))),
+                    );
+                }
+                effects.extend(self.broadcast(SseData::BlockAdded {
+                    block_hash,
+                    block: Box::new(*block),
+                }));
+                effects
+            }
             Event::DeployProcessed {
                 deploy_hash,
                 deploy_header,
@@ -109,6 +151,27 @@ where
                 block_hash: Box::new(block_hash),
                 execution_result,
             }),
+            Event::DeployNotIncluded {
+                deploy_hash,
+                status,
+            } => self.broadcast(SseData::DeployNotIncluded {
+                deploy_hash: Box::new(deploy_hash),
+                status,
+            }),
+            Event::DeployApprovalsUpdated {
+                deploy_hash,
+                approvals,
+            } => self.broadcast(SseData::DeployApprovalsUpdated {
+                deploy_hash: Box::new(deploy_hash),
+                approvals,
+            }),
+            Event::DiskSpaceStatus { status, disk_space } => {
+                self.broadcast(SseData::DiskSpaceStatus {
+                    status,
+                    available_bytes: disk_space.available,
+                    total_bytes: disk_space.total,
+                })
+            }
             Event::Fault {
                 era_id,
                 public_key,
@@ -119,6 +182,7 @@ where
                 timestamp,
             }),
             Event::FinalitySignature(fs) => self.broadcast(SseData::FinalitySignature(fs)),
+            Event::AuctionDelta(delta) => self.broadcast(SseData::AuctionDelta(delta)),
         }
     }
 }
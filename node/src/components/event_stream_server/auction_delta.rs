@@ -0,0 +1,234 @@
+//! Summaries of the auction changes taking effect at an era transition.
+//!
+//! When a switch block is added, the validator weights it lists for the next era are compared to
+//! the ones listed by the previous era's switch block.  Likewise, the stake delegated to each
+//! validator is compared between the bids in the global state after either block.  The differences
+//! are sent as an `AuctionDelta` event, so clients don't need to diff the full auction state
+//! themselves.
+//!
+//! No event is sent for the first era's switch block, or if the previous switch block or either of
+//! the bids can't be read.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use casper_execution_engine::core::engine_state::GetBidsResult;
+use casper_types::{system::auction::Bids, PublicKey, U512};
+
+use crate::{
+    components::consensus::EraId,
+    effect::{
+        requests::{ContractRuntimeRequest, StorageRequest},
+        EffectBuilder,
+    },
+    types::{Block, BlockHash},
+};
+
+/// A change of a validator's weight, or of the total stake delegated to a validator.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct StakeChange {
+    /// The validator's public key.
+    pub public_key: PublicKey,
+    /// The amount before the era transition, zero if there was none.
+    pub old_amount: U512,
+    /// The amount after the era transition, zero if there is none.
+    pub new_amount: U512,
+}
+
+/// The auction changes between two consecutive switch blocks.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct AuctionDelta {
+    /// The era the new validator weights apply to.
+    pub era_id: EraId,
+    /// The hash of the switch block ending the previous era.
+    pub block_hash: BlockHash,
+    /// The validators which weren't validators in the previous era.
+    pub added_validators: Vec<PublicKey>,
+    /// The validators of the previous era which aren't validators anymore.
+    pub removed_validators: Vec<PublicKey>,
+    /// The changed validator weights, including the added and removed validators.
+    pub weight_changes: Vec<StakeChange>,
+    /// The validators ejected during the previous era, as equivocators or for being inactive.
+    pub ejected_validators: Vec<PublicKey>,
+    /// The changed totals of the stake delegated to each validator.
+    pub delegation_changes: Vec<StakeChange>,
+}
+
+impl AuctionDelta {
+    /// Returns the changes between the previous switch block and `switch_block`, given the
+    /// previous validator weights and the bids after either block.
+    ///
+    /// Returns `None` if `switch_block` isn't a switch block.
+    fn new(
+        switch_block: &Block,
+        previous_weights: &BTreeMap<PublicKey, U512>,
+        previous_bids: &Bids,
+        bids: &Bids,
+    ) -> Option<Self> {
+        let header = switch_block.header();
+        let weights = header.next_era_validator_weights()?;
+        let era_report = header.era_end()?;
+
+        let added_validators = weights
+            .keys()
+            .filter(|public_key| !previous_weights.contains_key(public_key))
+            .cloned()
+            .collect();
+        let removed_validators = previous_weights
+            .keys()
+            .filter(|public_key| !weights.contains_key(public_key))
+            .cloned()
+            .collect();
+        let ejected_validators: BTreeSet<PublicKey> = era_report
+            .equivocators
+            .iter()
+            .chain(&era_report.inactive_validators)
+            .cloned()
+            .collect();
+
+        Some(AuctionDelta {
+            era_id: header.era_id().successor(),
+            block_hash: *switch_block.hash(),
+            added_validators,
+            removed_validators,
+            weight_changes: stake_changes(previous_weights, weights),
+            ejected_validators: ejected_validators.into_iter().collect(),
+            delegation_changes: stake_changes(
+                &delegation_totals(previous_bids),
+                &delegation_totals(bids),
+            ),
+        })
+    }
+}
+
+/// Returns the entries which differ between `old` and `new`, treating missing ones as zero.
+fn stake_changes(
+    old: &BTreeMap<PublicKey, U512>,
+    new: &BTreeMap<PublicKey, U512>,
+) -> Vec<StakeChange> {
+    let public_keys: BTreeSet<&PublicKey> = old.keys().chain(new.keys()).collect();
+    public_keys
+        .into_iter()
+        .filter_map(|public_key| {
+            let old_amount = old.get(public_key).copied().unwrap_or_default();
+            let new_amount = new.get(public_key).copied().unwrap_or_default();
+            (old_amount != new_amount).then(|| StakeChange {
+                public_key: *public_key,
+                old_amount,
+                new_amount,
+            })
+        })
+        .collect()
+}
+
+/// Returns the total stake delegated to each validator with a bid.
+fn delegation_totals(bids: &Bids) -> BTreeMap<PublicKey, U512> {
+    bids.iter()
+        .map(|(public_key, bid)| {
+            let total = bid
+                .delegators()
+                .values()
+                .fold(U512::zero(), |total, delegator| {
+                    total + *delegator.staked_amount()
+                });
+            (*public_key, total)
+        })
+        .collect()
+}
+
+/// Reads the previous switch block and the bids after either block, and returns the auction
+/// changes up to `switch_block`.
+pub(super) async fn compute<REv>(
+    effect_builder: EffectBuilder<REv>,
+    switch_block: Box<Block>,
+) -> Option<AuctionDelta>
+where
+    REv: From<StorageRequest> + From<ContractRuntimeRequest>,
+{
+    let era_id = switch_block.header().era_id();
+    let previous_era_id = match era_id.checked_sub(1) {
+        Some(previous_era_id) => previous_era_id,
+        None => {
+            debug!(%era_id, "no auction delta for the first era");
+            return None;
+        }
+    };
+    let previous_switch_block = match effect_builder
+        .get_switch_block_at_era_id_from_storage(previous_era_id)
+        .await
+    {
+        Some(block) => block,
+        None => {
+            warn!(%previous_era_id, "previous switch block not found, no auction delta sent");
+            return None;
+        }
+    };
+    let previous_weights = previous_switch_block
+        .header()
+        .next_era_validator_weights()?;
+
+    let previous_bids = read_bids(effect_builder, &previous_switch_block).await?;
+    let bids = read_bids(effect_builder, &switch_block).await?;
+    AuctionDelta::new(&switch_block, previous_weights, &previous_bids, &bids)
+}
+
+/// Reads the bids in the global state after `block`.
+async fn read_bids<REv>(effect_builder: EffectBuilder<REv>, block: &Block) -> Option<Bids>
+where
+    REv: From<ContractRuntimeRequest>,
+{
+    let state_root_hash = *block.header().state_root_hash();
+    match effect_builder.get_bids(state_root_hash.into()).await {
+        Ok(GetBidsResult::Success { bids }) => Some(bids),
+        Ok(GetBidsResult::RootNotFound) => {
+            warn!(%state_root_hash, "state root not found, no auction delta sent");
+            None
+        }
+        Err(error) => {
+            warn!(%error, %state_root_hash, "failed to read bids, no auction delta sent");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn should_list_changed_stakes() {
+        let key = |byte| PublicKey::from(&SecretKey::ed25519([byte; SecretKey::ED25519_LENGTH]));
+        let (kept, changed, removed, added) = (key(1), key(2), key(3), key(4));
+        let old: BTreeMap<_, _> = vec![
+            (kept, U512::from(10)),
+            (changed, U512::from(20)),
+            (removed, U512::from(30)),
+        ]
+        .into_iter()
+        .collect();
+        let new: BTreeMap<_, _> = vec![
+            (kept, U512::from(10)),
+            (changed, U512::from(25)),
+            (added, U512::from(40)),
+        ]
+        .into_iter()
+        .collect();
+
+        let changes: BTreeMap<_, _> = stake_changes(&old, &new)
+            .into_iter()
+            .map(|change| (change.public_key, (change.old_amount, change.new_amount)))
+            .collect();
+        let expected: BTreeMap<_, _> = vec![
+            (changed, (U512::from(20), U512::from(25))),
+            (removed, (U512::from(30), U512::zero())),
+            (added, (U512::zero(), U512::from(40))),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(changes, expected);
+    }
+}
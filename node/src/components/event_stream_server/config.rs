@@ -1,6 +1,10 @@
+use std::collections::BTreeSet;
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use super::SseEventType;
+
 /// Default binding address for the SSE HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -33,6 +37,9 @@ pub struct Config {
 
     /// Rate limit for queries per second.
     pub qps_limit: u64,
+
+    /// The types of events not sent to clients.
+    pub disabled_events: BTreeSet<SseEventType>,
 }
 
 impl Config {
@@ -43,8 +50,28 @@ impl Config {
             event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
             broadcast_channel_size: DEFAULT_BROADCAST_CHANNEL_SIZE,
             qps_limit: DEFAULT_QPS_LIMIT,
+            disabled_events: BTreeSet::new(),
         }
     }
+
+    /// Returns the types of events sent to clients.
+    pub(crate) fn enabled_events(&self) -> Vec<SseEventType> {
+        SseEventType::ALL
+            .iter()
+            .filter(|event_type| !self.disabled_events.contains(event_type))
+            .copied()
+            .collect()
+    }
+
+    /// Returns whether events of the given type are needed by any client: either they are enabled
+    /// themselves, or `AccountActivity` events are derived from them.
+    pub(super) fn is_needed(&self, event_type: SseEventType) -> bool {
+        !self.disabled_events.contains(&event_type)
+            || (event_type == SseEventType::DeployProcessed
+                && !self
+                    .disabled_events
+                    .contains(&SseEventType::AccountActivity))
+    }
 }
 
 impl Default for Config {
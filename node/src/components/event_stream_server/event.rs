@@ -2,9 +2,14 @@ use std::fmt::{self, Display, Formatter};
 
 use casper_types::{ExecutionResult, PublicKey};
 
+use super::AuctionDelta;
 use crate::{
-    components::consensus::EraId,
-    types::{Block, BlockHash, DeployHash, DeployHeader, FinalitySignature, Timestamp},
+    components::{
+        consensus::EraId,
+        deploy_watcher::NotIncludedStatus,
+        disk_watchdog::{DiskSpace, DiskSpaceStatus},
+    },
+    types::{Approval, Block, BlockHash, DeployHash, DeployHeader, FinalitySignature, Timestamp},
 };
 
 #[derive(Debug)]
@@ -19,12 +24,25 @@ pub enum Event {
         block_hash: BlockHash,
         execution_result: Box<ExecutionResult>,
     },
+    DeployNotIncluded {
+        deploy_hash: DeployHash,
+        status: NotIncludedStatus,
+    },
+    DeployApprovalsUpdated {
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+    },
+    DiskSpaceStatus {
+        status: DiskSpaceStatus,
+        disk_space: DiskSpace,
+    },
     Fault {
         era_id: EraId,
         public_key: PublicKey,
         timestamp: Timestamp,
     },
     FinalitySignature(Box<FinalitySignature>),
+    AuctionDelta(Box<AuctionDelta>),
 }
 
 impl Display for Event {
@@ -34,6 +52,22 @@ impl Display for Event {
             Event::DeployProcessed { deploy_hash, .. } => {
                 write!(formatter, "deploy processed {}", deploy_hash)
             }
+            Event::DeployNotIncluded {
+                deploy_hash,
+                status,
+            } => write!(formatter, "deploy not included {}: {}", deploy_hash, status),
+            Event::DeployApprovalsUpdated {
+                deploy_hash,
+                approvals,
+            } => write!(
+                formatter,
+                "deploy approvals updated {}: {}",
+                deploy_hash,
+                approvals.len()
+            ),
+            Event::DiskSpaceStatus { status, .. } => {
+                write!(formatter, "disk space status {}", status)
+            }
             Event::Fault {
                 era_id,
                 public_key,
@@ -44,6 +78,9 @@ impl Display for Event {
                 public_key, timestamp, era_id,
             ),
             Event::FinalitySignature(fs) => write!(formatter, "finality signature {}", fs),
+            Event::AuctionDelta(delta) => {
+                write!(formatter, "auction delta for era {}", delta.era_id)
+            }
         }
     }
 }
@@ -31,7 +31,10 @@ pub(super) async fn run(
 ) {
     // Event stream channels and filter.
     let (broadcaster, mut new_subscriber_info_receiver, sse_filter) =
-        sse_server::create_channels_and_filter(config.broadcast_channel_size);
+        sse_server::create_channels_and_filter(
+            config.broadcast_channel_size,
+            config.disabled_events.clone(),
+        );
 
     let service = warp_json_rpc::service(sse_filter);
 
@@ -1,9 +1,12 @@
 //! Types and functions used by the http server to manage the event-stream.
 
+use std::collections::BTreeSet;
+
 use datasize::DataSize;
-use futures::{Stream, StreamExt};
+use futures::{future, Stream, StreamExt};
+use schemars::JsonSchema;
 use semver::Version;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize};
 use tokio::sync::{
     broadcast::{self, RecvError},
     mpsc,
@@ -11,15 +14,19 @@ use tokio::sync::{
 use tracing::{error, info, trace};
 use warp::{
     filters::BoxedFilter,
+    http::StatusCode,
     sse::{self, ServerSentEvent as WarpServerSentEvent},
     Filter, Reply,
 };
 
-use casper_types::{ExecutionResult, PublicKey};
+use casper_types::{account::AccountHash, ExecutionResult, PublicKey, Transfer, Transform};
 
+use super::AuctionDelta;
 use crate::{
-    components::consensus::EraId,
-    types::{Block, BlockHash, DeployHash, FinalitySignature, TimeDiff, Timestamp},
+    components::{
+        consensus::EraId, deploy_watcher::NotIncludedStatus, disk_watchdog::DiskSpaceStatus,
+    },
+    types::{Approval, Block, BlockHash, DeployHash, FinalitySignature, TimeDiff, Timestamp},
 };
 
 /// The URL path.
@@ -51,6 +58,25 @@ pub enum SseData {
         #[data_size(skip)]
         execution_result: Box<ExecutionResult>,
     },
+    /// The given deploy was not included in any block by the time its TTL and a grace period
+    /// elapsed.
+    DeployNotIncluded {
+        deploy_hash: Box<DeployHash>,
+        status: NotIncludedStatus,
+    },
+    /// Further approvals were received for the given deploy after it had been stored.  Carries
+    /// all of the deploy's approvals now held by this node.
+    DeployApprovalsUpdated {
+        deploy_hash: Box<DeployHash>,
+        approvals: Vec<Approval>,
+    },
+    /// The free space on the node's storage volume crossed one of the configured thresholds.
+    /// While the status is critical, the node does not accept deploys.
+    DiskSpaceStatus {
+        status: DiskSpaceStatus,
+        available_bytes: u64,
+        total_bytes: u64,
+    },
     /// Generic representation of validator's fault in an era.
     Fault {
         era_id: EraId,
@@ -59,6 +85,97 @@ pub enum SseData {
     },
     /// New finality signature received.
     FinalitySignature(Box<FinalitySignature>),
+    /// The given deploy, which has been executed and forms part of the given block, was sent by or
+    /// transferred funds from or to at least one of the accounts a client subscribed to.
+    ///
+    /// Only sent to clients which subscribed to a set of accounts, in place of the corresponding
+    /// `DeployProcessed` event and with the same event ID.
+    AccountActivity {
+        deploy_hash: Box<DeployHash>,
+        block_hash: Box<BlockHash>,
+        /// The subscribed accounts involved in the deploy.
+        accounts: Vec<AccountHash>,
+        /// The transfers made by the deploy from or to any of the subscribed accounts.
+        #[data_size(skip)]
+        transfers: Vec<Transfer>,
+    },
+    /// The auction changes taking effect in the given era: the validators added, removed or
+    /// ejected, and the changed validator weights and delegated stakes, compared to the previous
+    /// era.
+    AuctionDelta(#[data_size(skip)] Box<AuctionDelta>),
+}
+
+/// The types of events which can be disabled on the event stream, named like their `SseData`
+/// variants.
+///
+/// The initial `ApiVersion` event is always sent.
+#[derive(
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Debug,
+    DataSize,
+    JsonSchema,
+)]
+pub enum SseEventType {
+    /// `BlockAdded` events.
+    BlockAdded,
+    /// `DeployProcessed` events.
+    DeployProcessed,
+    /// `DeployNotIncluded` events.
+    DeployNotIncluded,
+    /// `DeployApprovalsUpdated` events.
+    DeployApprovalsUpdated,
+    /// `DiskSpaceStatus` events.
+    DiskSpaceStatus,
+    /// `Fault` events.
+    Fault,
+    /// `FinalitySignature` events.
+    FinalitySignature,
+    /// `AccountActivity` events, sent to clients subscribed to a set of accounts.
+    AccountActivity,
+    /// `AuctionDelta` events.
+    AuctionDelta,
+}
+
+impl SseEventType {
+    /// All event types.
+    pub const ALL: [SseEventType; 9] = [
+        SseEventType::BlockAdded,
+        SseEventType::DeployProcessed,
+        SseEventType::DeployNotIncluded,
+        SseEventType::DeployApprovalsUpdated,
+        SseEventType::DiskSpaceStatus,
+        SseEventType::Fault,
+        SseEventType::FinalitySignature,
+        SseEventType::AccountActivity,
+        SseEventType::AuctionDelta,
+    ];
+}
+
+impl SseData {
+    /// Returns the type of the event, or `None` for the `ApiVersion` event.
+    pub(super) fn event_type(&self) -> Option<SseEventType> {
+        let event_type = match self {
+            SseData::ApiVersion(_) => return None,
+            SseData::BlockAdded { .. } => SseEventType::BlockAdded,
+            SseData::DeployProcessed { .. } => SseEventType::DeployProcessed,
+            SseData::DeployNotIncluded { .. } => SseEventType::DeployNotIncluded,
+            SseData::DeployApprovalsUpdated { .. } => SseEventType::DeployApprovalsUpdated,
+            SseData::DiskSpaceStatus { .. } => SseEventType::DiskSpaceStatus,
+            SseData::Fault { .. } => SseEventType::Fault,
+            SseData::FinalitySignature(_) => SseEventType::FinalitySignature,
+            SseData::AccountActivity { .. } => SseEventType::AccountActivity,
+            SseData::AuctionDelta(_) => SseEventType::AuctionDelta,
+        };
+        Some(event_type)
+    }
 }
 
 /// The components of a single SSE.
@@ -77,6 +194,66 @@ impl ServerSentEvent {
             data: SseData::ApiVersion(client_api_version),
         }
     }
+
+    /// Returns the event to be sent to a client subscribed to the given accounts, if any.
+    ///
+    /// Apart from the initial `ApiVersion` event, such clients only receive `AccountActivity`
+    /// events synthesized from `DeployProcessed` events involving any of their accounts.
+    fn for_accounts(&self, watched_accounts: &BTreeSet<AccountHash>) -> Option<Self> {
+        let (deploy_hash, account, block_hash, execution_result) = match &self.data {
+            SseData::ApiVersion(_) => return Some(self.clone()),
+            SseData::DeployProcessed {
+                deploy_hash,
+                account,
+                block_hash,
+                execution_result,
+                ..
+            } => (deploy_hash, account, block_hash, execution_result),
+            _ => return None,
+        };
+
+        let transfers: Vec<Transfer> = written_transfers(execution_result)
+            .filter(|transfer| {
+                watched_accounts.contains(&transfer.from)
+                    || transfer
+                        .to
+                        .map_or(false, |to| watched_accounts.contains(&to))
+            })
+            .collect();
+        let accounts: BTreeSet<AccountHash> = transfers
+            .iter()
+            .flat_map(|transfer| transfer.to.into_iter().chain(Some(transfer.from)))
+            .chain(Some(account.to_account_hash()))
+            .filter(|account_hash| watched_accounts.contains(account_hash))
+            .collect();
+        if accounts.is_empty() {
+            return None;
+        }
+
+        Some(ServerSentEvent {
+            id: self.id,
+            data: SseData::AccountActivity {
+                deploy_hash: deploy_hash.clone(),
+                block_hash: block_hash.clone(),
+                accounts: accounts.into_iter().collect(),
+                transfers,
+            },
+        })
+    }
+}
+
+/// Returns the transfers written to global state by executing a deploy.
+fn written_transfers(execution_result: &ExecutionResult) -> impl Iterator<Item = &Transfer> {
+    let effect = match execution_result {
+        ExecutionResult::Failure { effect, .. } | ExecutionResult::Success { effect, .. } => effect,
+    };
+    effect
+        .transforms
+        .iter()
+        .filter_map(|entry| match &entry.transform {
+            Transform::WriteTransfer(transfer) => Some(transfer),
+            _ => None,
+        })
 }
 
 /// The messages sent via the tokio broadcast channel to the handler of each client's SSE stream.
@@ -102,15 +279,39 @@ pub(super) struct NewSubscriberInfo {
 }
 
 /// The endpoint's query string, e.g. `http://localhost:22777/events?start_from=999`
+///
+/// Clients only interested in particular accounts can list their formatted hashes, separated by
+/// commas, e.g. `http://localhost:22777/events?accounts=account-hash-01..01,account-hash-02..02`
+/// (hashes abbreviated).
 #[derive(Deserialize, Debug)]
 struct Query {
     start_from: Option<Id>,
+    #[serde(default, deserialize_with = "deserialize_accounts")]
+    accounts: Option<BTreeSet<AccountHash>>,
+}
+
+fn deserialize_accounts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BTreeSet<AccountHash>>, D::Error> {
+    let accounts = String::deserialize(deserializer)?;
+    accounts
+        .split(',')
+        .map(|account| {
+            AccountHash::from_formatted_str(account)
+                .map_err(|error| SerdeError::custom(format!("invalid account hash: {}", error)))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
 }
 
 /// Creates the message-passing channels required to run the event-stream server and the warp filter
 /// for the event-stream server.
+///
+/// Events of the `disabled_events` types are not sent to clients, and subscribing to a set of
+/// accounts is refused if `AccountActivity` events are disabled.
 pub(super) fn create_channels_and_filter(
     broadcast_channel_size: usize,
+    disabled_events: BTreeSet<SseEventType>,
 ) -> (
     broadcast::Sender<BroadcastChannelMessage>,
     mpsc::UnboundedReceiver<NewSubscriberInfo>,
@@ -127,6 +328,15 @@ pub(super) fn create_channels_and_filter(
     let filter = warp::get()
         .and(warp::path(SSE_API_PATH))
         .and(warp::query().map(move |query: Query| {
+            if query.accounts.is_some() && disabled_events.contains(&SseEventType::AccountActivity)
+            {
+                return warp::reply::with_status(
+                    "account activity events are disabled on this node",
+                    StatusCode::BAD_REQUEST,
+                )
+                .into_response();
+            }
+
             // Create a channel for the client's handler to receive the stream of initial events.
             let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
 
@@ -149,7 +359,10 @@ pub(super) fn create_channels_and_filter(
             sse::reply(sse::keep_alive().stream(stream_to_client(
                 initial_events_receiver,
                 ongoing_events_receiver,
+                query.accounts,
+                disabled_events.clone(),
             )))
+            .into_response()
         }))
         .boxed();
 
@@ -167,13 +380,39 @@ pub(super) fn create_channels_and_filter(
 /// either the client disconnects, or the server shuts down (indicated by sending a `Shutdown`
 /// variant via the channel).  This channel will receive all SSEs created from the moment the client
 /// subscribed to the server's event stream.
+///
+/// If the client subscribed to a set of accounts, the events of both channels are filtered down to
+/// the ones involving these accounts.  Events of the `disabled_events` types are left out.
 fn stream_to_client(
     initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
     ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
+    watched_accounts: Option<BTreeSet<AccountHash>>,
+    disabled_events: BTreeSet<SseEventType>,
 ) -> impl Stream<Item = Result<impl WarpServerSentEvent, RecvError>> + 'static {
     initial_events
         .map(|event| Ok(BroadcastChannelMessage::ServerSentEvent(event)))
         .chain(ongoing_events)
+        .filter_map(move |result| {
+            let result = match (result, &watched_accounts) {
+                (Ok(BroadcastChannelMessage::ServerSentEvent(event)), Some(accounts)) => {
+                    match event.for_accounts(accounts) {
+                        Some(event) => Ok(BroadcastChannelMessage::ServerSentEvent(event)),
+                        None => return future::ready(None),
+                    }
+                }
+                (result, _) => result,
+            };
+            if let Ok(BroadcastChannelMessage::ServerSentEvent(event)) = &result {
+                let is_disabled = event
+                    .data
+                    .event_type()
+                    .map_or(false, |event_type| disabled_events.contains(&event_type));
+                if is_disabled {
+                    return future::ready(None);
+                }
+            }
+            future::ready(Some(result))
+        })
         .map(|result| {
             trace!(?result);
             match result {
@@ -182,7 +421,12 @@ fn stream_to_client(
                         (None, &SseData::ApiVersion { .. }) => Ok(sse::json(event.data).boxed()),
                         (Some(id), &SseData::BlockAdded { .. })
                         | (Some(id), &SseData::DeployProcessed { .. })
+                        | (Some(id), &SseData::DeployNotIncluded { .. })
+                        | (Some(id), &SseData::DeployApprovalsUpdated { .. })
+                        | (Some(id), &SseData::DiskSpaceStatus { .. })
                         | (Some(id), &SseData::FinalitySignature(_))
+                        | (Some(id), &SseData::AccountActivity { .. })
+                        | (Some(id), &SseData::AuctionDelta(_))
                         | (Some(id), &SseData::Fault { .. }) => {
                             Ok((sse::id(id), sse::json(event.data)).boxed())
                         }
@@ -202,3 +446,104 @@ fn stream_to_client(
             }
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{ExecutionEffect, SecretKey, TransformEntry, U512};
+
+    use super::*;
+    use crate::{
+        components::event_stream_server::Config, crypto::AsymmetricKeyExt, testing::TestRng,
+    };
+
+    fn deploy_processed(account: PublicKey, transfers: Vec<Transfer>) -> ServerSentEvent {
+        let mut rng = TestRng::new();
+        let transforms = transfers
+            .into_iter()
+            .map(|transfer| TransformEntry {
+                key: String::from("transfer"),
+                transform: Transform::WriteTransfer(transfer),
+            })
+            .collect();
+        ServerSentEvent {
+            id: Some(7),
+            data: SseData::DeployProcessed {
+                deploy_hash: Box::new(DeployHash::random(&mut rng)),
+                account,
+                timestamp: Timestamp::now(),
+                ttl: TimeDiff::from(60_000),
+                dependencies: vec![],
+                block_hash: Box::new(BlockHash::random(&mut rng)),
+                execution_result: Box::new(ExecutionResult::Success {
+                    effect: ExecutionEffect {
+                        operations: vec![],
+                        transforms,
+                    },
+                    transfers: vec![],
+                    cost: U512::zero(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn should_only_pass_activity_of_watched_accounts() {
+        let mut rng = TestRng::new();
+        let sender = PublicKey::from(&SecretKey::random(&mut rng));
+        let other = AccountHash::new([1; 32]);
+        let recipient = AccountHash::new([2; 32]);
+        let transfer = Transfer {
+            from: sender.to_account_hash(),
+            to: Some(recipient),
+            ..Transfer::default()
+        };
+        let event = deploy_processed(sender, vec![transfer]);
+
+        let watched = vec![other].into_iter().collect();
+        assert!(event.for_accounts(&watched).is_none());
+
+        let watched = vec![other, recipient].into_iter().collect();
+        let activity = event.for_accounts(&watched).unwrap();
+        assert_eq!(activity.id, event.id);
+        match activity.data {
+            SseData::AccountActivity {
+                accounts,
+                transfers,
+                ..
+            } => {
+                assert_eq!(accounts, vec![recipient]);
+                assert_eq!(transfers, vec![transfer]);
+            }
+            data => panic!("unexpected data {:?}", data),
+        }
+
+        // Deploys without transfers still involve their sender.
+        let event = deploy_processed(sender, vec![]);
+        let watched = vec![sender.to_account_hash()].into_iter().collect();
+        assert!(event.for_accounts(&watched).is_some());
+
+        let initial_event = ServerSentEvent::initial_event(Version::new(1, 0, 0));
+        assert_eq!(initial_event.for_accounts(&watched), Some(initial_event));
+    }
+
+    #[test]
+    fn should_keep_deploy_processed_events_for_account_activity() {
+        let mut config = Config::new();
+        config.disabled_events = vec![
+            SseEventType::DeployProcessed,
+            SseEventType::FinalitySignature,
+        ]
+        .into_iter()
+        .collect();
+        assert!(config.is_needed(SseEventType::DeployProcessed));
+        assert!(!config.is_needed(SseEventType::FinalitySignature));
+        assert!(config.is_needed(SseEventType::BlockAdded));
+        assert!(!config
+            .enabled_events()
+            .contains(&SseEventType::DeployProcessed));
+
+        config.disabled_events.insert(SseEventType::AccountActivity);
+        assert!(!config.is_needed(SseEventType::DeployProcessed));
+        assert_eq!(config.enabled_events().len(), SseEventType::ALL.len() - 3);
+    }
+}
@@ -8,18 +8,23 @@ use std::{collections::HashMap, fmt::Debug, time::Duration};
 use datasize::DataSize;
 use prometheus::Registry;
 use smallvec::smallvec;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use casper_execution_engine::shared::newtypes::Blake2bHash;
 
 use crate::{
     components::{fetcher::event::FetchResponder, Component},
     effect::{
+        announcements::NetworkAnnouncement,
         requests::{ContractRuntimeRequest, LinearChainRequest, NetworkRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
-    types::{Block, BlockByHeight, BlockHash, Deploy, DeployHash, Item, NodeId},
+    types::{
+        Block, BlockByHeight, BlockExecutionResults, BlockHash, BlockHeader, Chunk, ChunkError,
+        ChunkId, Deploy, DeployChunk, DeployHash, DeployHeaderById, Item, NodeId, PartialItem,
+        Timestamp,
+    },
     utils::Source,
     NodeRng,
 };
@@ -27,13 +32,21 @@ use crate::{
 use casper_execution_engine::{shared::stored_value::StoredValue, storage::trie::Trie};
 use casper_types::Key;
 pub use config::Config;
-pub use event::{Event, FetchResult};
+pub use event::{Event, FetchFailure, FetchResult};
 use metrics::FetcherMetrics;
 
+/// The number of peer timeouts after which a chunked item without progress is discarded.
+const ACQUISITION_RETENTION_TIMEOUTS: u32 = 20;
+
+/// The greatest number of distinct chunk hash lists kept for a single chunked item.
+const MAX_CHUNK_HASH_LISTS: usize = 4;
+
 /// A helper trait constraining `Fetcher` compatible reactor events.
 pub trait ReactorEventT<T>:
     From<Event<T>>
     + From<NetworkRequest<NodeId, Message>>
+    // Reassembled chunked items are handed back to the reactor like a `GetResponse`.
+    + From<NetworkAnnouncement<NodeId, Message>>
     + From<StorageRequest>
     + From<ContractRuntimeRequest>
     // Won't be needed when we implement "get block by height" feature in storage.
@@ -52,6 +65,7 @@ where
     <T as Item>::Id: 'static,
     REv: From<Event<T>>
         + From<NetworkRequest<NodeId, Message>>
+        + From<NetworkAnnouncement<NodeId, Message>>
         + From<StorageRequest>
         + From<ContractRuntimeRequest>
         + From<LinearChainRequest<NodeId>>
@@ -65,9 +79,26 @@ pub trait ItemFetcher<T: Item + 'static> {
 
     fn peer_timeout(&self) -> Duration;
 
+    /// Returns a request for a chunk of the item, or `None` if items of this type are never sent
+    /// in chunks.
+    fn new_chunk_request(_id: &ChunkId<T::Id>) -> Option<Result<Message, bincode::Error>> {
+        None
+    }
+
+    /// Returns whether the item is already being fetched from `peer`.
+    fn is_pending(&mut self, id: &T::Id, peer: &NodeId) -> bool {
+        self.responders()
+            .get(id)
+            .and_then(|peer_responders| peer_responders.get(peer))
+            .map_or(false, |responders| !responders.is_empty())
+    }
+
     /// We've been asked to fetch the item by another component of this node.  We'll try to get it
     /// from our own storage component first, and if that fails, we'll send a request to `peer` for
     /// the item.
+    ///
+    /// If the item is already being fetched from `peer`, the responder is answered along with the
+    /// pending ones, without checking storage or asking `peer` again.
     fn fetch<REv: ReactorEventT<T>>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -75,6 +106,8 @@ pub trait ItemFetcher<T: Item + 'static> {
         peer: NodeId,
         responder: FetchResponder<T>,
     ) -> Effects<Event<T>> {
+        let already_pending = self.is_pending(&id, &peer);
+
         // Capture responder for later signalling.
         let responders = self.responders();
         responders
@@ -84,6 +117,10 @@ pub trait ItemFetcher<T: Item + 'static> {
             .or_default()
             .push(responder);
 
+        if already_pending {
+            return Effects::new();
+        }
+
         // Get the item from the storage component.
         self.get_from_storage(effect_builder, id, peer)
     }
@@ -101,7 +138,7 @@ pub trait ItemFetcher<T: Item + 'static> {
     fn got_from_storage(&mut self, item: T, peer: NodeId) -> Effects<Event<T>> {
         self.signal(
             item.id(),
-            Some(FetchResult::FromStorage(Box::new(item))),
+            Ok(FetchResult::FromStorage(Box::new(item))),
             peer,
         )
     }
@@ -128,34 +165,35 @@ pub trait ItemFetcher<T: Item + 'static> {
             }
             Err(error) => {
                 error!("failed to construct get request: {}", error);
-                self.signal(id, None, peer)
+                // No response can arrive for a request we couldn't send.
+                self.signal(id, Err(FetchFailure::TimedOut), peer)
             }
         }
     }
 
-    /// Handles signalling responders with the item or `None`.
+    /// Handles signalling responders with the item or the reason it couldn't be fetched.
     fn signal(
         &mut self,
         id: T::Id,
-        result: Option<FetchResult<T, NodeId>>,
+        result: Result<FetchResult<T, NodeId>, FetchFailure>,
         peer: NodeId,
     ) -> Effects<Event<T>> {
         let mut effects = Effects::new();
         let mut all_responders = self.responders().remove(&id).unwrap_or_default();
         match result {
-            Some(ret) => {
+            Ok(ret) => {
                 // signal all responders waiting for this item
                 for (_, responders) in all_responders {
                     for responder in responders {
-                        effects.extend(responder.respond(Some(ret.clone())).ignore());
+                        effects.extend(responder.respond(Ok(ret.clone())).ignore());
                     }
                 }
             }
-            None => {
+            Err(failure) => {
                 // remove only the peer specific responders for this id
                 if let Some(responders) = all_responders.remove(&peer) {
                     for responder in responders {
-                        effects.extend(responder.respond(None).ignore());
+                        effects.extend(responder.respond(Err(failure)).ignore());
                     }
                 }
                 if !all_responders.is_empty() {
@@ -167,7 +205,82 @@ pub trait ItemFetcher<T: Item + 'static> {
     }
 }
 
+/// An item being received in chunks.
+///
+/// The chunk size is fixed by the chainspec, so honest peers list the same chunk hashes for an
+/// item, and differing lists mean that some peer forged its list.  Which one can only be told once
+/// all chunks of a list have arrived, so chunks are kept apart by the list they carry: a forged
+/// list neither locks out honest peers nor spoils the chunks they sent.
+#[derive(DataSize, Debug)]
+struct Acquisition {
+    /// The chunks received so far, one partial item per distinct list of chunk hashes.
+    partial_items: Vec<PartialItem>,
+    /// The peer which sent the last chunk.
+    last_peer: NodeId,
+    /// The time the last chunk was received.
+    last_progress: Timestamp,
+}
+
+impl Acquisition {
+    fn new(peer: NodeId) -> Self {
+        Acquisition {
+            partial_items: Vec::new(),
+            last_peer: peer,
+            last_progress: Timestamp::now(),
+        }
+    }
+
+    /// Adds a chunk received from `peer`, and returns the index of the partial item it was added
+    /// to.
+    ///
+    /// A chunk listing chunk hashes not seen before starts another partial item.  If there are
+    /// `MAX_CHUNK_HASH_LISTS` already, the one with the fewest chunks is discarded.
+    fn add<I: Copy>(&mut self, chunk: Chunk<I>, peer: NodeId) -> Result<usize, ChunkError> {
+        let position = self
+            .partial_items
+            .iter()
+            .position(|partial_item| partial_item.has_chunk_hashes_of(&chunk));
+        let index = match position {
+            Some(index) => {
+                self.partial_items[index].add(chunk)?;
+                index
+            }
+            None => {
+                let partial_item = PartialItem::new(chunk)?;
+                if self.partial_items.len() >= MAX_CHUNK_HASH_LISTS {
+                    let fewest = self
+                        .partial_items
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, partial_item)| partial_item.progress().0)
+                        .map(|(index, _)| index);
+                    if let Some(fewest) = fewest {
+                        self.partial_items.swap_remove(fewest);
+                    }
+                }
+                self.partial_items.push(partial_item);
+                self.partial_items.len() - 1
+            }
+        };
+        self.last_peer = peer;
+        self.last_progress = Timestamp::now();
+        Ok(index)
+    }
+
+    /// Returns the index of the next chunk to request of the partial item with the most chunks.
+    fn next_missing_chunk(&self) -> Option<u32> {
+        self.partial_items
+            .iter()
+            .max_by_key(|partial_item| partial_item.progress().0)
+            .and_then(|partial_item| partial_item.missing_chunks().next())
+    }
+}
+
 /// The component which fetches an item from local storage or asks a peer if it's not in storage.
+///
+/// Items too large to be sent in one piece arrive in chunks.  The chunks received so far are kept
+/// across timeouts and failed fetches, so fetching the item again, from any peer, only requests the
+/// missing ones.
 #[derive(DataSize, Debug)]
 pub struct Fetcher<T>
 where
@@ -175,6 +288,7 @@ where
 {
     get_from_peer_timeout: Duration,
     responders: HashMap<T::Id, HashMap<NodeId, Vec<FetchResponder<T>>>>,
+    acquisitions: HashMap<T::Id, Acquisition>,
     #[data_size(skip)]
     metrics: FetcherMetrics,
 }
@@ -188,11 +302,130 @@ impl<T: Item> Fetcher<T> {
         Ok(Fetcher {
             get_from_peer_timeout: Duration::from_secs(config.get_from_peer_timeout()),
             responders: HashMap::new(),
+            acquisitions: HashMap::new(),
             metrics: FetcherMetrics::new(name, registry)?,
         })
     }
 }
 
+impl<T> Fetcher<T>
+where
+    Fetcher<T>: ItemFetcher<T>,
+    T: Item + 'static,
+{
+    /// Requests chunk `index` of the item from `peer`.
+    fn request_chunk<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        index: u32,
+        peer: NodeId,
+    ) -> Effects<Event<T>> {
+        let chunk_id = ChunkId { item_id: id, index };
+        match Self::new_chunk_request(&chunk_id) {
+            Some(Ok(message)) => {
+                let mut effects = effect_builder.send_message(peer.clone(), message).ignore();
+                effects.extend(
+                    effect_builder
+                        .set_timeout(self.peer_timeout())
+                        .event(move |_| Event::TimeoutPeer { id, peer }),
+                );
+                effects
+            }
+            Some(Err(error)) => {
+                error!("failed to construct chunk request: {}", error);
+                self.signal(id, Err(FetchFailure::TimedOut), peer)
+            }
+            None => {
+                debug!(%id, "items of this type are not fetched in chunks");
+                Effects::new()
+            }
+        }
+    }
+
+    /// Handles a chunk of the item received from `peer`, requesting the next missing one from the
+    /// same peer, or handing the reassembled item to the reactor once all chunks are present.
+    fn got_chunk<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        chunk: Chunk<T::Id>,
+        peer: NodeId,
+    ) -> Effects<Event<T>> {
+        let id = chunk.item_id();
+        let requested = self
+            .responders
+            .get(&id)
+            .map_or(false, |peers| peers.contains_key(&peer));
+        if !requested {
+            debug!(%chunk, %peer, "received chunk of an item not being fetched from the peer");
+            return Effects::new();
+        }
+        let acquisition = self
+            .acquisitions
+            .entry(id)
+            .or_insert_with(|| Acquisition::new(peer.clone()));
+        let partial_item = match acquisition.add(chunk, peer.clone()) {
+            Ok(index) => acquisition.partial_items.swap_remove(index),
+            Err(error) => {
+                warn!(%id, %peer, %error, "received invalid chunk");
+                if acquisition.partial_items.is_empty() {
+                    self.acquisitions.remove(&id);
+                }
+                return Effects::new();
+            }
+        };
+
+        match partial_item.assemble() {
+            Ok(serialized_item) => {
+                let matches_id = bincode::deserialize::<T>(&serialized_item)
+                    .map_or(false, |item| item.id() == id);
+                if !matches_id {
+                    // The chunks carrying other chunk hash lists, if any, may still complete.
+                    warn!(%id, %peer, "reassembled item doesn't match its ID; discarding chunks");
+                    if acquisition.partial_items.is_empty() {
+                        self.acquisitions.remove(&id);
+                    }
+                    return Effects::new();
+                }
+                self.acquisitions.remove(&id);
+                // Handle the item like one received in a single piece, so that it is validated
+                // and stored before the responders are answered.
+                let message = Message::GetResponse {
+                    tag: T::TAG,
+                    serialized_item: serialized_item.into(),
+                };
+                effect_builder
+                    .announce_message_received(peer, message)
+                    .ignore()
+            }
+            Err(partial_item) => {
+                let (received, total) = partial_item.progress();
+                debug!(%id, %peer, received, total, "received chunk");
+                let next_index = partial_item.missing_chunks().next().unwrap_or_default();
+                acquisition.partial_items.push(partial_item);
+                self.request_chunk(effect_builder, id, next_index, peer)
+            }
+        }
+    }
+
+    /// Returns whether `peer` has sent a chunk of the item within the last peer timeout, so a
+    /// timeout set for an earlier request to it is superseded.
+    fn is_sending_chunks(&self, id: &T::Id, peer: &NodeId) -> bool {
+        self.acquisitions.get(id).map_or(false, |acquisition| {
+            acquisition.last_peer == *peer
+                && Duration::from(acquisition.last_progress.elapsed()) < self.get_from_peer_timeout
+        })
+    }
+
+    /// Discards the chunks of items without progress for a long time.
+    fn prune_acquisitions(&mut self) {
+        let retention = self.get_from_peer_timeout * ACQUISITION_RETENTION_TIMEOUTS;
+        self.acquisitions.retain(|_, acquisition| {
+            Duration::from(acquisition.last_progress.elapsed()) < retention
+        });
+    }
+}
+
 impl ItemFetcher<Deploy> for Fetcher<Deploy> {
     fn responders(
         &mut self,
@@ -204,6 +437,10 @@ impl ItemFetcher<Deploy> for Fetcher<Deploy> {
         self.get_from_peer_timeout
     }
 
+    fn new_chunk_request(id: &ChunkId<DeployHash>) -> Option<Result<Message, bincode::Error>> {
+        Some(Message::new_get_request::<DeployChunk>(id))
+    }
+
     /// Gets a `Deploy` from the storage component.
     fn get_from_storage<REv: ReactorEventT<Deploy>>(
         &mut self,
@@ -221,6 +458,39 @@ impl ItemFetcher<Deploy> for Fetcher<Deploy> {
     }
 }
 
+impl ItemFetcher<DeployHeaderById> for Fetcher<DeployHeaderById> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<DeployHash, HashMap<NodeId, Vec<FetchResponder<DeployHeaderById>>>> {
+        &mut self.responders
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Gets a deploy's header from the storage component.
+    fn get_from_storage<REv: ReactorEventT<DeployHeaderById>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: DeployHash,
+        peer: NodeId,
+    ) -> Effects<Event<DeployHeaderById>> {
+        effect_builder
+            .get_deploy_headers_from_storage(smallvec![id])
+            .event(move |mut results| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(
+                    results
+                        .pop()
+                        .expect("can only contain one result")
+                        .map(|header| DeployHeaderById::new(id, header)),
+                ),
+            })
+    }
+}
+
 impl ItemFetcher<Block> for Fetcher<Block> {
     fn responders(
         &mut self,
@@ -248,6 +518,62 @@ impl ItemFetcher<Block> for Fetcher<Block> {
     }
 }
 
+impl ItemFetcher<BlockHeader> for Fetcher<BlockHeader> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<BlockHash, HashMap<NodeId, Vec<FetchResponder<BlockHeader>>>> {
+        &mut self.responders
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Gets a block's header from the storage component.
+    fn get_from_storage<REv: ReactorEventT<BlockHeader>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: BlockHash,
+        peer: NodeId,
+    ) -> Effects<Event<BlockHeader>> {
+        effect_builder
+            .get_block_header_from_storage(id)
+            .event(move |result| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(result),
+            })
+    }
+}
+
+impl ItemFetcher<BlockExecutionResults> for Fetcher<BlockExecutionResults> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<BlockHash, HashMap<NodeId, Vec<FetchResponder<BlockExecutionResults>>>> {
+        &mut self.responders
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Gets the execution results of a block's deploys from the storage component.
+    fn get_from_storage<REv: ReactorEventT<BlockExecutionResults>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: BlockHash,
+        peer: NodeId,
+    ) -> Effects<Event<BlockExecutionResults>> {
+        effect_builder
+            .get_block_execution_results_from_storage(id)
+            .event(move |result| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(result),
+            })
+    }
+}
+
 impl ItemFetcher<BlockByHeight> for Fetcher<BlockByHeight> {
     fn responders(
         &mut self,
@@ -325,7 +651,12 @@ where
                 id,
                 peer,
                 responder,
-            } => self.fetch(effect_builder, id, peer, responder),
+            } => {
+                if self.is_pending(&id, &peer) {
+                    self.metrics.deduplicated_requests.inc();
+                }
+                self.fetch(effect_builder, id, peer, responder)
+            }
             Event::GetFromStorageResult {
                 id,
                 peer,
@@ -335,15 +666,26 @@ where
                     self.metrics.found_in_storage.inc();
                     self.got_from_storage(item, peer)
                 }
-                None => self.failed_to_get_from_storage(effect_builder, id, peer),
+                None => {
+                    // Resume fetching a chunked item where an earlier attempt left off.
+                    let next_index = self
+                        .acquisitions
+                        .get(&id)
+                        .and_then(Acquisition::next_missing_chunk);
+                    match next_index {
+                        Some(index) => self.request_chunk(effect_builder, id, index, peer),
+                        None => self.failed_to_get_from_storage(effect_builder, id, peer),
+                    }
+                }
             },
             Event::GotRemotely { item, source } => {
+                self.acquisitions.remove(&item.id());
                 match source {
                     Source::Peer(peer) => {
                         self.metrics.found_on_peer.inc();
                         self.signal(
                             item.id(),
-                            Some(FetchResult::FromPeer(item, peer.clone())),
+                            Ok(FetchResult::FromPeer(item, peer.clone())),
                             peer,
                         )
                     }
@@ -357,12 +699,17 @@ where
             Event::RejectedRemotely { .. } => Effects::new(),
             Event::AbsentRemotely { id, peer } => {
                 info!(%id, %peer, "element absent on the remote node");
-                self.signal(id, None, peer)
+                self.signal(id, Err(FetchFailure::Absent), peer)
             }
+            Event::GotChunk { chunk, peer } => self.got_chunk(effect_builder, *chunk, peer),
             Event::TimeoutPeer { id, peer } => {
+                self.prune_acquisitions();
+                if self.is_sending_chunks(&id, &peer) {
+                    return Effects::new();
+                }
                 info!(%id, %peer, "request timed out");
                 self.metrics.timeouts.inc();
-                self.signal(id, None, peer)
+                self.signal(id, Err(FetchFailure::TimedOut), peer)
             }
         }
     }
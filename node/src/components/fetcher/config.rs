@@ -1,6 +1,8 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::ConfigSection;
+
 const DEFAULT_GET_FROM_PEER_TIMEOUT_SECS: u64 = 3;
 
 /// Configuration options for fetching.
@@ -13,6 +15,11 @@ impl Config {
     pub(crate) fn get_from_peer_timeout(&self) -> u64 {
         self.get_from_peer_timeout
     }
+
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("get_from_peer_timeout", self.get_from_peer_timeout, 1);
+    }
 }
 
 impl Default for Config {
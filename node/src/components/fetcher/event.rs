@@ -5,8 +5,8 @@ use serde::Serialize;
 
 use super::Item;
 use crate::{
-    effect::{announcements::DeployAcceptorAnnouncement, requests::FetcherRequest, Responder},
-    types::{Deploy, NodeId},
+    effect::{requests::FetcherRequest, Responder},
+    types::{Chunk, NodeId},
     utils::Source,
 };
 
@@ -16,7 +16,16 @@ pub enum FetchResult<T, I> {
     FromPeer(Box<T>, I),
 }
 
-pub(crate) type FetchResponder<T> = Responder<Option<FetchResult<T, NodeId>>>;
+/// The reason an item could not be fetched from a peer.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq)]
+pub enum FetchFailure {
+    /// The peer told us it doesn't have the item.
+    Absent,
+    /// The peer didn't respond in time.
+    TimedOut,
+}
+
+pub(crate) type FetchResponder<T> = Responder<Result<FetchResult<T, NodeId>, FetchFailure>>;
 
 /// `Fetcher` events.
 #[derive(Debug, Serialize)]
@@ -46,6 +55,11 @@ pub enum Event<T: Item> {
         item: Box<T>,
         source: Source<NodeId>,
     },
+    /// A peer sent a chunk of an item too large to be sent in one piece.
+    GotChunk {
+        chunk: Box<Chunk<T::Id>>,
+        peer: NodeId,
+    },
     /// An item was not available on the remote peer.
     AbsentRemotely { id: T::Id, peer: NodeId },
     /// The timeout has elapsed and we should clean up state.
@@ -68,27 +82,6 @@ impl<T: Item> From<FetcherRequest<NodeId, T>> for Event<T> {
     }
 }
 
-// A deploy fetcher knows how to update its state if deploys are coming in via the deploy acceptor.
-impl From<DeployAcceptorAnnouncement<NodeId>> for Event<Deploy> {
-    #[inline]
-    fn from(announcement: DeployAcceptorAnnouncement<NodeId>) -> Self {
-        match announcement {
-            DeployAcceptorAnnouncement::AcceptedNewDeploy { deploy, source } => {
-                Event::GotRemotely {
-                    item: deploy,
-                    source,
-                }
-            }
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
-                Event::RejectedRemotely {
-                    item: deploy,
-                    source,
-                }
-            }
-        }
-    }
-}
-
 impl<T: Item> Display for Event<T> {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -109,6 +102,7 @@ impl<T: Item> Display for Event<T> {
                 item.id(),
                 source
             ),
+            Event::GotChunk { chunk, peer } => write!(formatter, "got {} from {}", chunk, peer),
             Event::TimeoutPeer { id, peer } => write!(
                 formatter,
                 "check get from peer timeout for {} with {}",
@@ -10,6 +10,8 @@ pub(super) struct FetcherMetrics {
     pub(super) found_on_peer: IntCounter,
     /// Number of fetch requests that timed out.
     pub(super) timeouts: IntCounter,
+    /// Number of fetch requests answered by an already pending request for the same item.
+    pub(super) deduplicated_requests: IntCounter,
     /// Reference to the registry for unregistering.
     registry: Registry,
 }
@@ -31,14 +33,23 @@ impl FetcherMetrics {
             format!("{}_timeouts", name),
             format!("number of {} fetch requests that timed out", name),
         )?;
+        let deduplicated_requests = IntCounter::new(
+            format!("{}_deduplicated_requests", name),
+            format!(
+                "number of {} fetch requests answered by an already pending request",
+                name
+            ),
+        )?;
         registry.register(Box::new(found_in_storage.clone()))?;
         registry.register(Box::new(found_on_peer.clone()))?;
         registry.register(Box::new(timeouts.clone()))?;
+        registry.register(Box::new(deduplicated_requests.clone()))?;
 
         Ok(FetcherMetrics {
             found_in_storage,
             found_on_peer,
             timeouts,
+            deduplicated_requests,
             registry: registry.clone(),
         })
     }
@@ -49,5 +60,6 @@ impl Drop for FetcherMetrics {
         unregister_metric!(self.registry, self.found_in_storage);
         unregister_metric!(self.registry, self.found_on_peer);
         unregister_metric!(self.registry, self.timeouts);
+        unregister_metric!(self.registry, self.deduplicated_requests);
     }
 }
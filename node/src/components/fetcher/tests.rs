@@ -4,14 +4,21 @@
 use std::sync::{Arc, Mutex};
 
 use casper_node_macros::reactor;
-use futures::FutureExt;
+use futures::{
+    channel::oneshot,
+    future::{self, FutureExt},
+};
 use tempfile::TempDir;
 use thiserror::Error;
 use tokio::time;
 
 use super::*;
 use crate::{
-    components::{deploy_acceptor, in_memory_network::NetworkController, storage},
+    components::{
+        deploy_acceptor,
+        in_memory_network::{Latency, LinkConditions, NetworkController},
+        storage,
+    },
     effect::{
         announcements::{DeployAcceptorAnnouncement, NetworkAnnouncement},
         Responder,
@@ -20,7 +27,7 @@ use crate::{
     reactor::{Reactor as ReactorTrait, Runner},
     testing::{
         network::{Network, NetworkedReactor},
-        ConditionCheckReactor, TestRng,
+        ComponentHarness, ConditionCheckReactor, TestRng,
     },
     types::{Deploy, DeployHash, NodeId},
     utils::{WithDir, RESOURCES_PATH},
@@ -97,7 +104,7 @@ reactor!(Reactor {
 
     announcements: {
         // The deploy fetcher needs to be notified about new deploys.
-        DeployAcceptorAnnouncement<NodeId> -> [deploy_fetcher];
+        DeployAcceptorAnnouncement<NodeId> -> [fn handle_deploy_acceptor_announcement];
         NetworkAnnouncement<NodeId, Message> -> [fn handle_message];
         // Currently the RpcServerAnnouncement is misnamed - it solely tells of new deploys arriving
         // from a client.
@@ -107,6 +114,30 @@ reactor!(Reactor {
 });
 
 impl Reactor {
+    fn handle_deploy_acceptor_announcement(
+        &mut self,
+        effect_builder: EffectBuilder<ReactorEvent>,
+        rng: &mut NodeRng,
+        announcement: DeployAcceptorAnnouncement<NodeId>,
+    ) -> Effects<ReactorEvent> {
+        let event = match announcement {
+            DeployAcceptorAnnouncement::AcceptedNewDeploy { deploy, source } => {
+                Event::GotRemotely {
+                    item: deploy,
+                    source,
+                }
+            }
+            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
+                Event::RejectedRemotely {
+                    item: deploy,
+                    source,
+                }
+            }
+            DeployAcceptorAnnouncement::ApprovalsUpdated { .. } => return Effects::new(),
+        };
+        self.dispatch_event(effect_builder, rng, ReactorEvent::DeployFetcher(event))
+    }
+
     fn handle_message(
         &mut self,
         effect_builder: EffectBuilder<ReactorEvent>,
@@ -187,7 +218,7 @@ impl NetworkedReactor for Reactor {
 
 fn announce_deploy_received(
     deploy: Deploy,
-    responder: Option<Responder<Result<(), deploy_acceptor::Error>>>,
+    responder: Option<Responder<Result<deploy_acceptor::Acceptance, deploy_acceptor::Error>>>,
 ) -> impl FnOnce(EffectBuilder<ReactorEvent>) -> Effects<ReactorEvent> {
     |effect_builder: EffectBuilder<ReactorEvent>| {
         effect_builder
@@ -209,7 +240,7 @@ fn fetch_deploy(
             .then(move |maybe_deploy| async move {
                 let mut result = fetched.lock().unwrap();
                 result.0 = true;
-                result.1 = maybe_deploy;
+                result.1 = maybe_deploy.ok();
             })
             .ignore()
     }
@@ -220,7 +251,7 @@ async fn store_deploy(
     deploy: &Deploy,
     node_id: &NodeId,
     network: &mut Network<Reactor>,
-    responder: Option<Responder<Result<(), deploy_acceptor::Error>>>,
+    responder: Option<Responder<Result<deploy_acceptor::Acceptance, deploy_acceptor::Error>>>,
     mut rng: &mut TestRng,
 ) {
     network
@@ -457,3 +488,239 @@ async fn should_timeout_fetch_from_peer() {
 
     NetworkController::<Message>::remove_active();
 }
+
+#[tokio::test]
+async fn should_fetch_from_peer_over_slow_link() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    NetworkController::<Message>::set_link_conditions(LinkConditions {
+        latency: Latency::Uniform {
+            min: Duration::from_millis(10),
+            max: Duration::from_millis(100),
+        },
+        drop_probability: 0.0,
+        reorder: true,
+    });
+    let (mut network, mut rng, node_ids) = {
+        let mut network = Network::<Reactor>::new();
+        let mut rng = TestRng::new();
+        let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+        (network, rng, node_ids)
+    };
+
+    let deploy = Deploy::random(&mut rng);
+    let node_with_deploy = &node_ids[0];
+    store_deploy(&deploy, node_with_deploy, &mut network, None, &mut rng).await;
+
+    let node_without_deploy = &node_ids[1];
+    let deploy_hash = *deploy.id();
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            node_without_deploy,
+            fetch_deploy(deploy_hash, node_with_deploy.clone(), Arc::clone(&fetched)),
+        )
+        .await;
+
+    // The request and the response are delayed, but well within the fetcher's timeout.
+    let expected_result = Some(FetchResult::FromPeer(
+        Box::new(deploy),
+        node_with_deploy.clone(),
+    ));
+    assert_settled(
+        node_without_deploy,
+        deploy_hash,
+        expected_result,
+        fetched,
+        &mut network,
+        &mut rng,
+        TIMEOUT,
+    )
+    .await;
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_timeout_fetch_over_lossy_link() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let (mut network, mut rng, node_ids) = {
+        let mut network = Network::<Reactor>::new();
+        let mut rng = TestRng::new();
+        let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+        (network, rng, node_ids)
+    };
+
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    let holding_node = node_ids[0].clone();
+    let requesting_node = node_ids[1].clone();
+    store_deploy(&deploy, &holding_node, &mut network, None, &mut rng).await;
+
+    // Every response from the holding node gets lost.
+    NetworkController::<Message>::set_link_conditions_between(
+        &holding_node,
+        &requesting_node,
+        LinkConditions {
+            drop_probability: 1.0,
+            ..LinkConditions::default()
+        },
+    );
+
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            &requesting_node,
+            fetch_deploy(deploy_hash, holding_node.clone(), Arc::clone(&fetched)),
+        )
+        .await;
+
+    // Crank until the request is sent, then until the holding node sends its response.
+    network
+        .crank_until(
+            &requesting_node,
+            &mut rng,
+            move |event: &ReactorEvent| {
+                if let ReactorEvent::NetworkRequest(NetworkRequest::SendMessage {
+                    payload, ..
+                }) = event
+                {
+                    matches!(**payload, Message::GetRequest { .. })
+                } else {
+                    false
+                }
+            },
+            TIMEOUT,
+        )
+        .await;
+    network
+        .crank_until(
+            &holding_node,
+            &mut rng,
+            move |event: &ReactorEvent| {
+                if let ReactorEvent::NetworkRequest(NetworkRequest::SendMessage {
+                    payload, ..
+                }) = event
+                {
+                    matches!(**payload, Message::GetResponse { .. })
+                } else {
+                    false
+                }
+            },
+            TIMEOUT,
+        )
+        .await;
+
+    // Advance time past the fetcher's timeout.
+    let secs_to_advance = Config::default().get_from_peer_timeout();
+    time::pause();
+    time::advance(Duration::from_secs(secs_to_advance + 10)).await;
+    time::resume();
+
+    assert_settled(
+        &requesting_node,
+        deploy_hash,
+        None,
+        fetched,
+        &mut network,
+        &mut rng,
+        TIMEOUT,
+    )
+    .await;
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[test]
+fn should_not_duplicate_pending_fetches() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let mut fetcher =
+        Fetcher::<Deploy>::new("deploy", Config::default(), &Registry::new()).unwrap();
+    let deploy = Deploy::random(&mut harness.rng);
+    let deploy_hash = *deploy.id();
+    let peer = NodeId::random(&mut harness.rng);
+    let other_peer = NodeId::random(&mut harness.rng);
+
+    let effect_builder = harness.effect_builder;
+    let mut receivers = Vec::new();
+    let mut fetch = |peer: &NodeId| {
+        let (sender, receiver) = oneshot::channel();
+        receivers.push(receiver);
+        fetcher
+            .fetch(
+                effect_builder,
+                deploy_hash,
+                peer.clone(),
+                Responder::create(sender),
+            )
+            .len()
+    };
+
+    // Only the first fetch from a peer looks the deploy up in storage; a second one waits for the
+    // pending lookup.  A fetch from another peer is handled independently.
+    assert_eq!(fetch(&peer), 1);
+    assert_eq!(fetch(&peer), 0);
+    assert_eq!(fetch(&other_peer), 1);
+
+    // Once the deploy is found, all of the requests are answered.
+    let effects = fetcher.got_from_storage(deploy.clone(), peer);
+    harness.runtime.block_on(future::join_all(effects));
+    for mut receiver in receivers {
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            Some(Ok(FetchResult::FromStorage(Box::new(deploy.clone()))))
+        );
+    }
+}
+
+#[test]
+fn should_keep_chunks_apart_by_chunk_hash_list() {
+    let mut rng = TestRng::new();
+    let honest_peer = NodeId::random(&mut rng);
+    let forging_peer = NodeId::random(&mut rng);
+    let item: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let forged_item = vec![0u8; 1000];
+    let chunk =
+        |serialized_item: &[u8], index| Chunk::new(7u64, serialized_item, index, 400).unwrap();
+
+    let mut acquisition = Acquisition::new(forging_peer.clone());
+    assert_eq!(
+        acquisition.add(chunk(&forged_item, 0), forging_peer.clone()),
+        Ok(0)
+    );
+    assert_eq!(
+        acquisition.add(chunk(&forged_item, 1), forging_peer.clone()),
+        Ok(0)
+    );
+
+    // A chunk with a different list from another peer doesn't disturb the forged chunks, nor is it
+    // rejected because of them.
+    assert_eq!(acquisition.add(chunk(&item, 2), honest_peer.clone()), Ok(1));
+    assert_eq!(acquisition.last_peer, honest_peer);
+    assert_eq!(acquisition.next_missing_chunk(), Some(2));
+    for index in 0..2 {
+        assert_eq!(
+            acquisition.add(chunk(&item, index), honest_peer.clone()),
+            Ok(1)
+        );
+    }
+    let partial_item = acquisition.partial_items.swap_remove(1);
+    assert_eq!(partial_item.assemble().expect("should be complete"), item);
+    assert_eq!(acquisition.partial_items[0].progress(), (2, 3));
+
+    // Beyond the limit, new lists replace the one with the fewest chunks.
+    for filler in 1..=MAX_CHUNK_HASH_LISTS as u8 {
+        let other_item = vec![filler; 1000];
+        acquisition
+            .add(chunk(&other_item, 0), forging_peer.clone())
+            .expect("should add chunk");
+    }
+    assert_eq!(acquisition.partial_items.len(), MAX_CHUNK_HASH_LISTS);
+    assert!(acquisition
+        .partial_items
+        .iter()
+        .any(|partial_item| partial_item.progress() == (2, 3)));
+}
@@ -7,7 +7,7 @@ mod metrics;
 mod tests;
 
 use datasize::DataSize;
-use futures::FutureExt;
+use futures::{future::BoxFuture, FutureExt};
 use prometheus::Registry;
 use smallvec::smallvec;
 use std::{
@@ -69,28 +69,24 @@ where
 
 /// This function can be passed in to `Gossiper::new()` as the `get_from_holder` arg when
 /// constructing a `Gossiper<Deploy>`.
-pub(crate) fn get_deploy_from_storage<T: Item + 'static, REv: ReactorEventT<T>>(
+pub(crate) fn get_deploy_from_storage<REv: From<StorageRequest> + Send>(
     effect_builder: EffectBuilder<REv>,
     deploy_hash: DeployHash,
-    sender: NodeId,
-) -> Effects<Event<Deploy>> {
-    effect_builder
-        .get_deploys_from_storage(smallvec![deploy_hash])
-        .event(move |mut results| {
-            let result = if results.len() == 1 {
-                results
-                    .pop()
-                    .unwrap()
-                    .ok_or_else(|| String::from("failed to get deploy from storage"))
-            } else {
-                Err(String::from("expected a single result"))
-            };
-            Event::GetFromHolderResult {
-                item_id: deploy_hash,
-                requester: sender,
-                result: Box::new(result),
-            }
-        })
+) -> BoxFuture<'static, Result<Deploy, String>> {
+    async move {
+        let mut results = effect_builder
+            .get_deploys_from_storage(smallvec![deploy_hash])
+            .await;
+        if results.len() == 1 {
+            results
+                .pop()
+                .unwrap()
+                .ok_or_else(|| String::from("failed to get deploy from storage"))
+        } else {
+            Err(String::from("expected a single result"))
+        }
+    }
+    .boxed()
 }
 
 /// The component which gossips to peers and handles incoming gossip messages from peers.
@@ -102,11 +98,15 @@ where
     REv: ReactorEventT<T>,
 {
     table: GossipTable<T::Id>,
+    /// The configuration currently in effect.
+    config: Config,
     gossip_timeout: Duration,
     get_from_peer_timeout: Duration,
     #[data_size(skip)] // Not well supported by datasize.
-    get_from_holder:
-        Box<dyn Fn(EffectBuilder<REv>, T::Id, NodeId) -> Effects<Event<T>> + Send + 'static>,
+    get_from_holder: Box<
+        dyn Fn(EffectBuilder<REv>, T::Id) -> BoxFuture<'static, Result<T, String>> + Send + 'static,
+    >,
+    full_item_gossip_threshold_bytes: u32,
     #[data_size(skip)]
     metrics: GossiperMetrics,
 }
@@ -117,7 +117,9 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     /// contain just the identifiers.
     ///
     /// `get_from_holder` is called by the gossiper when handling either a `Message::GossipResponse`
-    /// where the sender indicates it needs the full item, or a `Message::GetRequest`.
+    /// where the sender indicates it needs the full item, or a `Message::GetRequest`.  It is also
+    /// called before gossiping an item if `config.full_item_gossip_threshold_bytes()` is non-zero,
+    /// to decide whether the item is small enough to be gossiped in full.
     ///
     /// For an example of how `get_from_holder` should be implemented, see
     /// `gossiper::get_deploy_from_store()` which is used by `Gossiper<Deploy>`.
@@ -127,7 +129,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     pub(crate) fn new_for_partial_items(
         name: &str,
         config: Config,
-        get_from_holder: impl Fn(EffectBuilder<REv>, T::Id, NodeId) -> Effects<Event<T>>
+        get_from_holder: impl Fn(EffectBuilder<REv>, T::Id) -> BoxFuture<'static, Result<T, String>>
             + Send
             + 'static,
         registry: &Registry,
@@ -138,9 +140,11 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         );
         Ok(Gossiper {
             table: GossipTable::new(config),
+            config,
             gossip_timeout: Duration::from_secs(config.gossip_request_timeout_secs()),
             get_from_peer_timeout: Duration::from_secs(config.get_remainder_timeout_secs()),
             get_from_holder: Box::new(get_from_holder),
+            full_item_gossip_threshold_bytes: config.full_item_gossip_threshold_bytes(),
             metrics: GossiperMetrics::new(name, registry)?,
         })
     }
@@ -161,11 +165,13 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         );
         Ok(Gossiper {
             table: GossipTable::new(config),
+            config,
             gossip_timeout: Duration::from_secs(config.gossip_request_timeout_secs()),
             get_from_peer_timeout: Duration::from_secs(config.get_remainder_timeout_secs()),
-            get_from_holder: Box::new(|_, item, _| {
+            get_from_holder: Box::new(|_, item| {
                 panic!("gossiper should never try to get {}", item)
             }),
+            full_item_gossip_threshold_bytes: 0,
             metrics: GossiperMetrics::new(name, registry)?,
         })
     }
@@ -192,7 +198,10 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         }
     }
 
-    /// Gossips the given item ID to `count` random peers excluding the indicated ones.
+    /// Gossips the given item to `count` random peers excluding the indicated ones.
+    ///
+    /// If full items may be gossiped, the item is first retrieved from the component holding it to
+    /// find its size; otherwise only its ID is gossiped.
     fn gossip(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -200,7 +209,67 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         count: usize,
         exclude_peers: HashSet<NodeId>,
     ) -> Effects<Event<T>> {
-        let message = Message::Gossip(item_id);
+        if !T::ID_IS_COMPLETE_ITEM && self.full_item_gossip_threshold_bytes > 0 {
+            return (self.get_from_holder)(effect_builder, item_id).event(move |result| {
+                Event::GotItemToGossip {
+                    item_id,
+                    count,
+                    exclude_peers,
+                    result: Box::new(result),
+                }
+            });
+        }
+        self.send_gossip(
+            effect_builder,
+            Message::Gossip(item_id),
+            item_id,
+            count,
+            exclude_peers,
+        )
+    }
+
+    /// Handles the `Ok` case for a `Result` of attempting to get the item from the component
+    /// responsible for holding it in order to gossip it.  Gossips the full item if it is within the
+    /// configured size threshold, or just its ID if not.
+    fn got_item_to_gossip(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item: T,
+        count: usize,
+        exclude_peers: HashSet<NodeId>,
+    ) -> Effects<Event<T>> {
+        let item_id = item.id();
+        let message = match bincode::serialized_size(&item) {
+            Ok(size) if size <= u64::from(self.full_item_gossip_threshold_bytes) => {
+                self.metrics.full_items_gossiped.inc();
+                self.metrics
+                    .full_item_bytes_gossiped
+                    .inc_by((size * count as u64) as i64);
+                Message::GossipItem(Box::new(item))
+            }
+            Ok(size) => {
+                self.metrics
+                    .id_only_bytes_saved
+                    .inc_by((size * count as u64) as i64);
+                Message::Gossip(item_id)
+            }
+            Err(error) => {
+                warn!("failed to get serialized size of {}: {}", item_id, error);
+                Message::Gossip(item_id)
+            }
+        };
+        self.send_gossip(effect_builder, message, item_id, count, exclude_peers)
+    }
+
+    /// Sends the given gossip message to `count` random peers excluding the indicated ones.
+    fn send_gossip(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        message: Message<T>,
+        item_id: T::Id,
+        count: usize,
+        exclude_peers: HashSet<NodeId>,
+    ) -> Effects<Event<T>> {
         effect_builder
             .gossip_message(message, count, exclude_peers)
             .event(move |peers| Event::GossipedTo {
@@ -324,12 +393,30 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     }
 
     /// Handles an incoming gossip request from a peer on the network.
+    ///
+    /// `maybe_item` holds the full item if the peer gossiped it rather than just its ID.
     fn handle_gossip(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         item_id: T::Id,
+        maybe_item: Option<Box<T>>,
         sender: NodeId,
     ) -> Effects<Event<T>> {
+        let item_was_sent = maybe_item.is_some();
+        let response = move |is_already_held| {
+            if item_was_sent {
+                Message::GossipItemResponse {
+                    item_id,
+                    is_already_held,
+                }
+            } else {
+                Message::GossipResponse {
+                    item_id,
+                    is_already_held,
+                }
+            }
+        };
+
         let action = if T::ID_IS_COMPLETE_ITEM {
             self.table
                 .new_complete_data(&item_id, Some(sender.clone()))
@@ -359,22 +446,25 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
                 }
 
                 // Send a response to the sender indicating whether we already hold the item.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: should_gossip.is_already_held,
-                };
+                let reply = response(should_gossip.is_already_held);
                 effects.extend(effect_builder.send_message(sender, reply).ignore());
                 effects
             }
             GossipAction::GetRemainder { .. } => {
                 self.metrics.items_received.inc();
-                // Send a response to the sender indicating we want the full item from them, and set
-                // a timeout for this response.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: false,
-                };
+                // Send a response to the sender indicating we don't hold the item.  If they didn't
+                // send it, this asks them for it.
+                let reply = response(false);
                 let mut effects = effect_builder.send_message(sender.clone(), reply).ignore();
+                // If they sent it, announce it so it can be validated.
+                if let Some(item) = maybe_item {
+                    effects.extend(
+                        effect_builder
+                            .announce_item_body_received_via_gossip(item, sender.clone())
+                            .ignore(),
+                    );
+                }
+                // Set a timeout for the item to be received and become complete.
                 effects.extend(
                     effect_builder
                         .set_timeout(self.get_from_peer_timeout)
@@ -387,34 +477,38 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
             }
             GossipAction::Noop | GossipAction::AwaitingRemainder => {
                 // Send a response to the sender indicating we already hold the item.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: true,
-                };
+                let reply = response(true);
                 effect_builder.send_message(sender, reply).ignore()
             }
         }
     }
 
     /// Handles an incoming gossip response from a peer on the network.
+    ///
+    /// `item_was_sent` indicates whether we gossiped the full item to the peer, in which case it
+    /// needn't be sent again.
     fn handle_gossip_response(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         item_id: T::Id,
         is_already_held: bool,
+        item_was_sent: bool,
         sender: NodeId,
     ) -> Effects<Event<T>> {
         let mut effects: Effects<_> = Effects::new();
         let action = if is_already_held {
             self.table.already_infected(&item_id, sender)
         } else {
-            if !T::ID_IS_COMPLETE_ITEM {
+            if !T::ID_IS_COMPLETE_ITEM && !item_was_sent {
                 // `sender` doesn't hold the full item; get the item from the component responsible
                 // for holding it, then send it to `sender`.
-                effects.extend((self.get_from_holder)(
-                    effect_builder,
-                    item_id,
-                    sender.clone(),
+                let requester = sender.clone();
+                effects.extend((self.get_from_holder)(effect_builder, item_id).event(
+                    move |result| Event::GetFromHolderResult {
+                        item_id,
+                        requester,
+                        result: Box::new(result),
+                    },
                 ));
             }
             self.table.we_infected(&item_id, sender)
@@ -459,6 +553,28 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         }
     }
 
+    /// Applies the timeouts and full-item threshold of a reloaded configuration.
+    ///
+    /// The update is rejected as a whole if any of the gossip table settings was changed, since
+    /// the table can't be rebuilt while gossiping is in progress.
+    fn handle_config_updated(&mut self, config: Config) -> Effects<Event<T>> {
+        if self.config.has_different_table_settings(&config) {
+            warn!(
+                "rejecting reloaded gossip config: infection_target, saturation_limit_percent \
+                and finished_entry_duration_secs can only be changed by restarting the node"
+            );
+            return Effects::new();
+        }
+        self.gossip_timeout = Duration::from_secs(config.gossip_request_timeout_secs());
+        self.get_from_peer_timeout = Duration::from_secs(config.get_remainder_timeout_secs());
+        if !T::ID_IS_COMPLETE_ITEM {
+            self.full_item_gossip_threshold_bytes = config.full_item_gossip_threshold_bytes();
+        }
+        debug!(?config, "updated gossip config");
+        self.config = config;
+        Effects::new()
+    }
+
     /// Handles the `Err` case for a `Result` of attempting to get the item from the component
     /// responsible for holding it.
     fn failed_to_get_from_holder(&mut self, item_id: T::Id, error: String) -> Effects<Event<T>> {
@@ -514,11 +630,32 @@ where
                 self.check_get_from_peer_timeout(effect_builder, item_id, peer)
             }
             Event::MessageReceived { message, sender } => match message {
-                Message::Gossip(item_id) => self.handle_gossip(effect_builder, item_id, sender),
+                Message::Gossip(item_id) => {
+                    self.handle_gossip(effect_builder, item_id, None, sender)
+                }
                 Message::GossipResponse {
                     item_id,
                     is_already_held,
-                } => self.handle_gossip_response(effect_builder, item_id, is_already_held, sender),
+                } => self.handle_gossip_response(
+                    effect_builder,
+                    item_id,
+                    is_already_held,
+                    false,
+                    sender,
+                ),
+                Message::GossipItem(item) => {
+                    self.handle_gossip(effect_builder, item.id(), Some(item), sender)
+                }
+                Message::GossipItemResponse {
+                    item_id,
+                    is_already_held,
+                } => self.handle_gossip_response(
+                    effect_builder,
+                    item_id,
+                    is_already_held,
+                    true,
+                    sender,
+                ),
             },
             Event::GetFromHolderResult {
                 item_id,
@@ -528,6 +665,16 @@ where
                 Ok(item) => self.got_from_holder(effect_builder, item, requester),
                 Err(error) => self.failed_to_get_from_holder(item_id, error),
             },
+            Event::GotItemToGossip {
+                item_id,
+                count,
+                exclude_peers,
+                result,
+            } => match *result {
+                Ok(item) => self.got_item_to_gossip(effect_builder, item, count, exclude_peers),
+                Err(error) => self.failed_to_get_from_holder(item_id, error),
+            },
+            Event::ConfigUpdated(config) => self.handle_config_updated(config),
         };
         self.update_gossip_table_metrics();
         effects
@@ -541,6 +688,10 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Debug for Gossiper<T, REv> {
             .field("table", &self.table)
             .field("gossip_timeout", &self.gossip_timeout)
             .field("get_from_peer_timeout", &self.get_from_peer_timeout)
+            .field(
+                "full_item_gossip_threshold_bytes",
+                &self.full_item_gossip_threshold_bytes,
+            )
             .finish()
     }
 }
@@ -5,6 +5,8 @@ use serde::{
 };
 use tracing::error;
 
+use crate::utils::ConfigSection;
+
 #[cfg(test)]
 use super::Error;
 
@@ -14,6 +16,7 @@ pub(super) const MAX_SATURATION_LIMIT_PERCENT: u8 = 99;
 pub(super) const DEFAULT_FINISHED_ENTRY_DURATION_SECS: u64 = 60;
 const DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS: u64 = 10;
 const DEFAULT_GET_REMAINDER_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_FULL_ITEM_GOSSIP_THRESHOLD_BYTES: u32 = 2048;
 #[cfg(test)]
 const SMALL_TIMEOUTS_FINISHED_ENTRY_DURATION_SECS: u64 = 2;
 #[cfg(test)]
@@ -46,6 +49,12 @@ pub struct Config {
     /// The timeout duration in seconds for retrieving the remaining part(s) of newly-discovered
     /// data from a peer which gossiped information about that data to this node.
     get_remainder_timeout_secs: u64,
+    /// The maximum serialized size in bytes of an item which is gossiped along with its full
+    /// payload.  Larger items are gossiped by ID only, and peers which need them request them from
+    /// us.  A value of 0 means all items are gossiped by ID only.
+    ///
+    /// Has no effect for items whose ID is the complete item.
+    full_item_gossip_threshold_bytes: u32,
 }
 
 impl Config {
@@ -56,6 +65,7 @@ impl Config {
         finished_entry_duration_secs: u64,
         gossip_request_timeout_secs: u64,
         get_remainder_timeout_secs: u64,
+        full_item_gossip_threshold_bytes: u32,
     ) -> Result<Self, Error> {
         if saturation_limit_percent > MAX_SATURATION_LIMIT_PERCENT {
             return Err(Error::InvalidSaturationLimit);
@@ -66,6 +76,7 @@ impl Config {
             finished_entry_duration_secs,
             gossip_request_timeout_secs,
             get_remainder_timeout_secs,
+            full_item_gossip_threshold_bytes,
         })
     }
 
@@ -79,6 +90,15 @@ impl Config {
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn with_full_item_gossip_threshold_bytes(
+        mut self,
+        full_item_gossip_threshold_bytes: u32,
+    ) -> Self {
+        self.full_item_gossip_threshold_bytes = full_item_gossip_threshold_bytes;
+        self
+    }
+
     pub(crate) fn infection_target(&self) -> u8 {
         self.infection_target
     }
@@ -98,6 +118,38 @@ impl Config {
     pub(crate) fn get_remainder_timeout_secs(&self) -> u64 {
         self.get_remainder_timeout_secs
     }
+
+    pub(crate) fn full_item_gossip_threshold_bytes(&self) -> u32 {
+        self.full_item_gossip_threshold_bytes
+    }
+
+    /// Returns whether `other` differs from `self` in any of the settings which shape the gossip
+    /// table, none of which can be changed while the gossiper is running.
+    pub(super) fn has_different_table_settings(&self, other: &Config) -> bool {
+        self.infection_target != other.infection_target
+            || self.saturation_limit_percent != other.saturation_limit_percent
+            || self.finished_entry_duration_secs != other.finished_entry_duration_secs
+    }
+
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("infection_target", self.infection_target, 1);
+        section.in_range(
+            "saturation_limit_percent",
+            self.saturation_limit_percent,
+            0..=MAX_SATURATION_LIMIT_PERCENT,
+        );
+        section.at_least(
+            "gossip_request_timeout_secs",
+            self.gossip_request_timeout_secs,
+            1,
+        );
+        section.at_least(
+            "get_remainder_timeout_secs",
+            self.get_remainder_timeout_secs,
+            1,
+        );
+    }
 }
 
 impl Default for Config {
@@ -108,6 +160,7 @@ impl Default for Config {
             finished_entry_duration_secs: DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             gossip_request_timeout_secs: DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             get_remainder_timeout_secs: DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
+            full_item_gossip_threshold_bytes: DEFAULT_FULL_ITEM_GOSSIP_THRESHOLD_BYTES,
         }
     }
 }
@@ -145,6 +198,7 @@ mod tests {
             finished_entry_duration_secs: DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             gossip_request_timeout_secs: DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             get_remainder_timeout_secs: DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
+            full_item_gossip_threshold_bytes: DEFAULT_FULL_ITEM_GOSSIP_THRESHOLD_BYTES,
         };
 
         // Parsing should fail.
@@ -158,7 +212,27 @@ mod tests {
             DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
+            DEFAULT_FULL_ITEM_GOSSIP_THRESHOLD_BYTES,
         )
         .is_err())
     }
+
+    #[test]
+    fn should_only_treat_table_settings_as_fixed() {
+        let config = Config::default();
+
+        let new_timeouts = Config {
+            gossip_request_timeout_secs: SMALL_TIMEOUTS_GOSSIP_REQUEST_TIMEOUT_SECS,
+            get_remainder_timeout_secs: SMALL_TIMEOUTS_GET_REMAINDER_TIMEOUT_SECS,
+            full_item_gossip_threshold_bytes: 0,
+            ..config
+        };
+        assert!(!config.has_different_table_settings(&new_timeouts));
+
+        let new_infection_target = Config {
+            infection_target: DEFAULT_INFECTION_TARGET + 1,
+            ..config
+        };
+        assert!(config.has_different_table_settings(&new_infection_target));
+    }
 }
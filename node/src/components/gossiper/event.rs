@@ -5,7 +5,7 @@ use std::{
 
 use serde::Serialize;
 
-use super::{Item, Message};
+use super::{Config, Item, Message};
 use crate::{
     types::NodeId,
     utils::{DisplayIter, Source},
@@ -40,6 +40,16 @@ pub enum Event<T: Item> {
         requester: NodeId,
         result: Box<Result<T, String>>,
     },
+    /// The result of the gossiper getting an item from the component responsible for holding it,
+    /// in order to decide whether to gossip the full item or only its ID.
+    GotItemToGossip {
+        item_id: T::Id,
+        count: usize,
+        exclude_peers: HashSet<NodeId>,
+        result: Box<Result<T, String>>,
+    },
+    /// The node's configuration was reloaded.
+    ConfigUpdated(Config),
 }
 
 impl<T: Item> Display for Event<T> {
@@ -76,6 +86,20 @@ impl<T: Item> Display for Event<T> {
                     write!(formatter, "failed to get {} from holder component", item_id)
                 }
             }
+            Event::GotItemToGossip {
+                item_id, result, ..
+            } => {
+                if result.is_ok() {
+                    write!(formatter, "got {} to gossip from holder component", item_id)
+                } else {
+                    write!(
+                        formatter,
+                        "failed to get {} to gossip from holder component",
+                        item_id
+                    )
+                }
+            }
+            Event::ConfigUpdated(_) => write!(formatter, "config updated"),
         }
     }
 }
@@ -15,6 +15,15 @@ pub enum Message<T: Item> {
         item_id: T::Id,
         is_already_held: bool,
     },
+    /// Gossiped out to random peers along with the full item, in place of `Gossip`, when the item
+    /// is small enough that sending it outright is cheaper than having peers request it.
+    GossipItem(Box<T>),
+    /// Response to a `GossipItem` message.  The item has already been sent, so the recipient
+    /// needn't send it again regardless of `is_already_held`.
+    GossipItemResponse {
+        item_id: T::Id,
+        is_already_held: bool,
+    },
 }
 
 impl<T: Item> Display for Message<T> {
@@ -29,6 +38,15 @@ impl<T: Item> Display for Message<T> {
                 "gossip-response({}, {})",
                 item_id, is_already_held
             ),
+            Message::GossipItem(item) => write!(formatter, "gossip-item({})", item.id()),
+            Message::GossipItemResponse {
+                item_id,
+                is_already_held,
+            } => write!(
+                formatter,
+                "gossip-item-response({}, {})",
+                item_id, is_already_held
+            ),
         }
     }
 }
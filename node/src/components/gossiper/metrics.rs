@@ -17,6 +17,13 @@ pub struct GossiperMetrics {
     pub(super) table_items_current: IntGauge,
     /// Number of items in the gossip table that are finished.
     pub(super) table_items_finished: IntGauge,
+    /// Number of times an item was gossiped along with its full payload.
+    pub(super) full_items_gossiped: IntCounter,
+    /// Total payload bytes pushed to peers by gossiping full items, per peer gossiped to.
+    pub(super) full_item_bytes_gossiped: IntCounter,
+    /// Total payload bytes not pushed to peers since the items exceeded the full-item threshold
+    /// and were gossiped by ID only, per peer gossiped to.
+    pub(super) id_only_bytes_saved: IntCounter,
     /// Reference to the registry for unregistering.
     registry: Registry,
 }
@@ -60,6 +67,28 @@ impl GossiperMetrics {
                 name
             ),
         )?;
+        let full_items_gossiped = IntCounter::new(
+            format!("{}_full_items_gossiped", name),
+            format!(
+                "number of times the {} gossiped an item along with its full payload",
+                name
+            ),
+        )?;
+        let full_item_bytes_gossiped = IntCounter::new(
+            format!("{}_full_item_bytes_gossiped", name),
+            format!(
+                "total bytes of items the {} gossiped along with their full payload",
+                name
+            ),
+        )?;
+        let id_only_bytes_saved = IntCounter::new(
+            format!("{}_id_only_bytes_saved", name),
+            format!(
+                "total bytes of items the {} gossiped by ID only rather than with their full \
+                payload",
+                name
+            ),
+        )?;
 
         registry.register(Box::new(items_received.clone()))?;
         registry.register(Box::new(times_gossiped.clone()))?;
@@ -67,6 +96,9 @@ impl GossiperMetrics {
         registry.register(Box::new(table_items_paused.clone()))?;
         registry.register(Box::new(table_items_current.clone()))?;
         registry.register(Box::new(table_items_finished.clone()))?;
+        registry.register(Box::new(full_items_gossiped.clone()))?;
+        registry.register(Box::new(full_item_bytes_gossiped.clone()))?;
+        registry.register(Box::new(id_only_bytes_saved.clone()))?;
 
         Ok(GossiperMetrics {
             items_received,
@@ -75,6 +107,9 @@ impl GossiperMetrics {
             table_items_paused,
             table_items_current,
             table_items_finished,
+            full_items_gossiped,
+            full_item_bytes_gossiped,
+            id_only_bytes_saved,
             registry: registry.clone(),
         })
     }
@@ -88,5 +123,8 @@ impl Drop for GossiperMetrics {
         unregister_metric!(self.registry, self.table_items_paused);
         unregister_metric!(self.registry, self.table_items_current);
         unregister_metric!(self.registry, self.table_items_finished);
+        unregister_metric!(self.registry, self.full_items_gossiped);
+        unregister_metric!(self.registry, self.full_item_bytes_gossiped);
+        unregister_metric!(self.registry, self.id_only_bytes_saved);
     }
 }
@@ -28,6 +28,7 @@ use crate::{
             RpcServerAnnouncement,
         },
         requests::ContractRuntimeRequest,
+        time_source::ManualClock,
         Responder,
     },
     protocol::Message as NodeMessage,
@@ -36,8 +37,8 @@ use crate::{
         network::{Network, NetworkedReactor},
         ConditionCheckReactor, TestRng,
     },
-    types::{Chainspec, Deploy, NodeId, Tag},
-    utils::{Loadable, WithDir},
+    types::{Chainspec, Deploy, NodeId, Tag, Timestamp},
+    utils::{self, Loadable, WithDir},
     NodeRng,
 };
 
@@ -293,6 +294,18 @@ impl reactor::Reactor for Reactor {
                 };
                 self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
             }
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployApprovalsReceived {
+                deploy_hash,
+                approvals,
+                responder,
+            }) => {
+                let event = deploy_acceptor::Event::AddApprovals {
+                    deploy_hash,
+                    approvals,
+                    responder,
+                };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
+            }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::AcceptedNewDeploy {
                 deploy,
                 source,
@@ -306,9 +319,23 @@ impl reactor::Reactor for Reactor {
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy: _,
                 source: _,
+            })
+            | Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::ApprovalsUpdated {
+                ..
             }) => Effects::new(),
-            Event::DeployGossiperAnnouncement(_ann) => {
-                unreachable!("the deploy gossiper should never make an announcement")
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewItemBody {
+                item,
+                sender,
+            }) => {
+                let event = deploy_acceptor::Event::Accept {
+                    deploy: item,
+                    source: Source::Peer(sender),
+                    responder: None,
+                };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(_)) => {
+                unreachable!("the deploy gossiper should never announce a new complete item")
             }
             Event::Network(event) => reactor::wrap_effects(
                 Event::Network,
@@ -335,9 +362,24 @@ impl NetworkedReactor for Reactor {
     }
 }
 
+/// Adds `count` new nodes to the network which gossip deploys by ID only, and returns their IDs.
+async fn add_nodes_gossiping_ids_only(
+    network: &mut Network<Reactor>,
+    rng: &mut TestRng,
+    count: usize,
+) -> Vec<NodeId> {
+    let config = Config::default().with_full_item_gossip_threshold_bytes(0);
+    let mut node_ids = vec![];
+    for _ in 0..count {
+        let (node_id, _runner) = network.add_node_with_config(config, rng).await.unwrap();
+        node_ids.push(node_id);
+    }
+    node_ids
+}
+
 fn announce_deploy_received(
     deploy: Box<Deploy>,
-    responder: Option<Responder<Result<(), deploy_acceptor::Error>>>,
+    responder: Option<Responder<Result<deploy_acceptor::Acceptance, deploy_acceptor::Error>>>,
 ) -> impl FnOnce(EffectBuilder<Event>) -> Effects<Event> {
     |effect_builder: EffectBuilder<Event>| {
         effect_builder
@@ -411,8 +453,9 @@ async fn should_get_from_alternate_source() {
     let mut network = Network::<Reactor>::new();
     let mut rng = crate::new_rng();
 
-    // Add `NETWORK_SIZE` nodes.
-    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+    // Add `NETWORK_SIZE` nodes.  Node 2 must learn of the deploy by ID only so that it has to get
+    // it from one of the others.
+    let node_ids = add_nodes_gossiping_ids_only(&mut network, &mut rng, NETWORK_SIZE).await;
 
     // Create random deploy.
     let deploy = Box::new(Deploy::random(&mut rng));
@@ -481,11 +524,11 @@ async fn should_get_from_alternate_source() {
 
 #[tokio::test]
 async fn should_timeout_gossip_response() {
-    const PAUSE_DURATION: Duration = Duration::from_millis(50);
     const TIMEOUT: Duration = Duration::from_secs(2);
 
     NetworkController::<NodeMessage>::create_active();
-    let mut network = Network::<Reactor>::new();
+    let clock = utils::leak(ManualClock::new(Timestamp::now()));
+    let mut network = Network::<Reactor>::with_manual_clock(clock);
     let mut rng = crate::new_rng();
 
     // The target number of peers to infect with a given piece of data.
@@ -515,8 +558,6 @@ async fn should_timeout_gossip_response() {
     network
         .crank_until(&node_ids[0], &mut rng, made_gossip_request, TIMEOUT)
         .await;
-    // Give node 0 time to set the timeouts before advancing the clock.
-    time::delay_for(PAUSE_DURATION).await;
 
     // Replace all nodes except node 0 with new nodes.
     for node_id in node_ids.drain(1..) {
@@ -530,9 +571,7 @@ async fn should_timeout_gossip_response() {
 
     // Advance time to trigger node 0's timeout causing it to gossip to the new nodes.
     let secs_to_advance = Config::default().gossip_request_timeout_secs();
-    time::pause();
-    time::advance(Duration::from_secs(secs_to_advance)).await;
-    time::resume();
+    clock.advance(Duration::from_secs(secs_to_advance));
     debug!("advanced time by {} secs", secs_to_advance);
 
     // Check every node has every deploy stored locally.
@@ -551,3 +590,69 @@ async fn should_timeout_gossip_response() {
 
     NetworkController::<NodeMessage>::remove_active();
 }
+
+#[tokio::test]
+async fn should_gossip_full_deploy_only_within_threshold() {
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    let mut rng = crate::new_rng();
+
+    for &(threshold, expect_full_item) in &[(u32::MAX, true), (1, false)] {
+        NetworkController::<NodeMessage>::create_active();
+        let mut network = Network::<Reactor>::new();
+
+        let config = Config::default().with_full_item_gossip_threshold_bytes(threshold);
+        let mut node_ids = vec![];
+        for _ in 0..2 {
+            let (node_id, _runner) = network
+                .add_node_with_config(config, &mut rng)
+                .await
+                .unwrap();
+            node_ids.push(node_id);
+        }
+
+        let deploy = Box::new(Deploy::random(&mut rng));
+        let deploy_id = *deploy.id();
+        network
+            .process_injected_effect_on(
+                &node_ids[0],
+                announce_deploy_received(deploy.clone(), None),
+            )
+            .await;
+
+        // Run node 0 until it has sent the gossip request, and check its form.
+        let made_expected_gossip_request = move |event: &Event| -> bool {
+            match event {
+                Event::NetworkRequest(NetworkRequest::Gossip { payload, .. }) => match **payload {
+                    NodeMessage::DeployGossiper(Message::GossipItem(_)) => expect_full_item,
+                    NodeMessage::DeployGossiper(Message::Gossip(_)) => !expect_full_item,
+                    _ => false,
+                },
+                _ => false,
+            }
+        };
+        network
+            .crank_until(
+                &node_ids[0],
+                &mut rng,
+                made_expected_gossip_request,
+                TIMEOUT,
+            )
+            .await;
+
+        // Check node 1 ends up with the deploy either way.
+        let deploy_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+            let runner = nodes.get(&node_ids[1]).unwrap();
+            runner
+                .reactor()
+                .inner()
+                .storage
+                .get_deploy_by_hash(deploy_id)
+                .map(|retrieved_deploy| retrieved_deploy == *deploy)
+                .unwrap_or_default()
+        };
+        network.settle_on(&mut rng, deploy_held, TIMEOUT).await;
+
+        NetworkController::<NodeMessage>::remove_active();
+    }
+}
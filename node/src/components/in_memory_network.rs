@@ -3,6 +3,11 @@
 //! The `InMemoryNetwork` represents a full virtual network with flawless connectivity and delivery
 //! by default.
 //!
+//! Adverse conditions can be simulated by setting `LinkConditions` for all links or for the link
+//! between two particular nodes, which add latency, drop messages or deliver them out of order.
+//! Whether a message is dropped and how long it is delayed is drawn from the sending reactor's
+//! random number generator, so a test seeding it gets the same decisions on every run.
+//!
 //! # Setup
 //!
 //! The network itself is managed by a `NetworkController` that can be used to create networking
@@ -286,11 +291,15 @@ use std::{
     convert::Infallible,
     fmt::{self, Display, Formatter},
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 use serde::Serialize;
-use tokio::sync::mpsc::{self, error::SendError};
+use tokio::{
+    sync::mpsc::{self, error::SendError},
+    time::{self, Instant},
+};
 use tracing::{debug, error, info, warn};
 
 use crate::{
@@ -309,6 +318,78 @@ use crate::{
 /// A network.
 type Network<P> = Arc<RwLock<HashMap<NodeId, mpsc::UnboundedSender<(NodeId, P)>>>>;
 
+/// The distribution of the time it takes a message to be delivered over a link.
+#[derive(Clone, Copy, Debug)]
+pub enum Latency {
+    /// Messages are delivered immediately.
+    None,
+    /// Every message is delayed by the same duration.
+    Fixed(Duration),
+    /// Every message is delayed by a duration drawn uniformly from the given range, inclusive.
+    Uniform {
+        /// The minimum delay.
+        min: Duration,
+        /// The maximum delay.
+        max: Duration,
+    },
+}
+
+impl Latency {
+    /// Draws the delay of a single message.
+    fn sample(&self, rng: &mut NodeRng) -> Duration {
+        match *self {
+            Latency::None => Duration::from_secs(0),
+            Latency::Fixed(delay) => delay,
+            Latency::Uniform { min, max } => {
+                let micros = rng.gen_range(min.as_micros() as u64, max.as_micros() as u64 + 1);
+                Duration::from_micros(micros)
+            }
+        }
+    }
+}
+
+/// The conditions of the link from one node to another.
+#[derive(Clone, Copy, Debug)]
+pub struct LinkConditions {
+    /// The time it takes a message to be delivered.
+    pub latency: Latency,
+    /// The probability of a message being dropped, between 0 and 1.
+    pub drop_probability: f64,
+    /// Whether messages are delivered as soon as their latency has passed, rather than in the
+    /// order they were sent.
+    pub reorder: bool,
+}
+
+impl Default for LinkConditions {
+    /// Flawless conditions: every message is delivered immediately and in order.
+    fn default() -> Self {
+        LinkConditions {
+            latency: Latency::None,
+            drop_probability: 0.0,
+            reorder: false,
+        }
+    }
+}
+
+/// The conditions of all links of a network.
+#[derive(Debug, Default)]
+struct Links {
+    /// The conditions of all links without conditions of their own.
+    default: LinkConditions,
+    /// The conditions of particular links, by source and destination.
+    overrides: HashMap<(NodeId, NodeId), LinkConditions>,
+}
+
+impl Links {
+    /// Returns the conditions of the link from `source` to `dest`.
+    fn get(&self, source: &NodeId, dest: &NodeId) -> LinkConditions {
+        self.overrides
+            .get(&(source.clone(), dest.clone()))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
 /// An in-memory network events.
 #[derive(Debug, Serialize)]
 pub struct Event<P>(NetworkRequest<NodeId, P>);
@@ -338,6 +419,8 @@ thread_local! {
 pub struct NetworkController<P> {
     /// Channels for network communication.
     nodes: Network<P>,
+    /// The conditions of the links between nodes.
+    links: Arc<RwLock<Links>>,
 }
 
 impl<P> NetworkController<P>
@@ -349,6 +432,7 @@ where
         let _ = logging::init();
         NetworkController {
             nodes: Default::default(),
+            links: Default::default(),
         }
     }
 
@@ -427,6 +511,46 @@ where
         })
     }
 
+    /// Sets the conditions of all links of the active network, except for those with conditions
+    /// set by `set_link_conditions_between`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned, there is no active network or the active
+    /// network is not of the correct message type.
+    pub fn set_link_conditions(conditions: LinkConditions) {
+        Self::with_active_links(|links| links.default = conditions)
+    }
+
+    /// Sets the conditions of the link from `source` to `dest` on the active network.
+    ///
+    /// The link in the opposite direction is unaffected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned, there is no active network or the active
+    /// network is not of the correct message type.
+    pub fn set_link_conditions_between(source: &NodeId, dest: &NodeId, conditions: LinkConditions) {
+        Self::with_active_links(|links| {
+            links
+                .overrides
+                .insert((source.clone(), dest.clone()), conditions);
+        })
+    }
+
+    /// Runs `f` on the link conditions of the active network.
+    fn with_active_links<F: FnOnce(&mut Links)>(f: F) {
+        ACTIVE_NETWORK.with(|active_network| {
+            let active_network = active_network.borrow();
+            let controller = active_network
+                .as_ref()
+                .expect("tried to set link conditions without active network set")
+                .downcast_ref::<Self>()
+                .expect("active network has wrong message type");
+            f(&mut controller.links.write().expect("poisoned lock"))
+        })
+    }
+
     /// Creates a new networking node with a random node ID.
     ///
     /// Returns the already connected new networking component for new node.
@@ -438,7 +562,12 @@ where
     where
         REv: From<NetworkAnnouncement<NodeId, P>> + Send,
     {
-        InMemoryNetwork::new_with_data(event_queue, NodeId::random(rng), self.nodes.clone())
+        InMemoryNetwork::new_with_data(
+            event_queue,
+            NodeId::random(rng),
+            self.nodes.clone(),
+            self.links.clone(),
+        )
     }
 }
 
@@ -450,6 +579,13 @@ pub struct InMemoryNetwork<P> {
 
     /// The nodes map, contains the incoming channel for each virtual node.
     nodes: Network<P>,
+
+    /// The conditions of the links between nodes.
+    links: Arc<RwLock<Links>>,
+
+    /// Queues delaying messages to each node over links which don't reorder them, in the order
+    /// they were sent.
+    in_order_queues: HashMap<NodeId, mpsc::UnboundedSender<(Instant, P)>>,
 }
 
 impl<P> InMemoryNetwork<P>
@@ -471,6 +607,7 @@ where
         event_queue: EventQueueHandle<REv>,
         node_id: NodeId,
         nodes: Network<P>,
+        links: Arc<RwLock<Links>>,
     ) -> Self
     where
         REv: From<NetworkAnnouncement<NodeId, P>> + Send,
@@ -486,7 +623,12 @@ where
 
         tokio::spawn(receiver_task(event_queue, receiver));
 
-        InMemoryNetwork { node_id, nodes }
+        InMemoryNetwork {
+            node_id,
+            nodes,
+            links,
+            in_order_queues: HashMap::new(),
+        }
     }
 
     /// Returns this node's ID.
@@ -498,35 +640,93 @@ where
 
 impl<P> InMemoryNetwork<P>
 where
-    P: Display,
+    P: 'static + Send + Display,
 {
     /// Internal helper, sends a payload to a node, ignoring but logging all errors.
     fn send(
-        &self,
+        &mut self,
         nodes: &HashMap<NodeId, mpsc::UnboundedSender<(NodeId, P)>>,
         dest: NodeId,
         payload: P,
+        rng: &mut NodeRng,
     ) {
         if dest == self.node_id {
             panic!("can't send message to self");
         }
 
-        match nodes.get(&dest) {
-            Some(sender) => {
-                if let Err(SendError((_, msg))) = sender.send((self.node_id.clone(), payload)) {
-                    warn!(%dest, %msg, "could not send message (send error)");
+        let sender = match nodes.get(&dest) {
+            Some(sender) => sender.clone(),
+            None => {
+                info!(%dest, %payload, "dropping message to non-existent recipient");
+                return;
+            }
+        };
+
+        let conditions = self
+            .links
+            .read()
+            .expect("network lock poisoned")
+            .get(&self.node_id, &dest);
+        if conditions.drop_probability > 0.0 && rng.gen_bool(conditions.drop_probability) {
+            debug!(%dest, %payload, "dropping message on lossy link");
+            return;
+        }
 
-                    // We do nothing else, the message is just dropped.
-                }
+        let source = self.node_id.clone();
+        match (conditions.latency, conditions.reorder) {
+            (Latency::None, false) => deliver(&sender, source, dest, payload),
+            (latency, true) => {
+                let delay = latency.sample(rng);
+                tokio::spawn(async move {
+                    time::delay_for(delay).await;
+                    deliver(&sender, source, dest, payload);
+                });
+            }
+            (latency, false) => {
+                let deliver_at = Instant::now() + latency.sample(rng);
+                let queue = self.in_order_queues.entry(dest.clone()).or_insert_with(|| {
+                    let (queue, receiver) = mpsc::unbounded_channel();
+                    tokio::spawn(in_order_delivery_task(source, dest, sender, receiver));
+                    queue
+                });
+                // The task only stops once we drop the queue.
+                let _ = queue.send((deliver_at, payload));
             }
-            None => info!(%dest, %payload, "dropping message to non-existent recipient"),
         }
     }
 }
 
+/// Hands a payload to the receiver task of `dest`, ignoring but logging all errors.
+fn deliver<P: Display>(
+    sender: &mpsc::UnboundedSender<(NodeId, P)>,
+    source: NodeId,
+    dest: NodeId,
+    payload: P,
+) {
+    if let Err(SendError((_, msg))) = sender.send((source, payload)) {
+        warn!(%dest, %msg, "could not send message (send error)");
+
+        // We do nothing else, the message is just dropped.
+    }
+}
+
+/// Delivers the messages from `source` to `dest` in the order they were sent, each not before the
+/// time it is due.
+async fn in_order_delivery_task<P: Display>(
+    source: NodeId,
+    dest: NodeId,
+    sender: mpsc::UnboundedSender<(NodeId, P)>,
+    mut queue: mpsc::UnboundedReceiver<(Instant, P)>,
+) {
+    while let Some((deliver_at, payload)) = queue.recv().await {
+        time::delay_until(deliver_at).await;
+        deliver(&sender, source.clone(), dest.clone(), payload);
+    }
+}
+
 impl<P, REv> Component<REv> for InMemoryNetwork<P>
 where
-    P: Display + Clone,
+    P: 'static + Send + Display + Clone,
 {
     type Event = Event<P>;
     type ConstructionError = Infallible;
@@ -547,8 +747,9 @@ where
                     panic!("can't send message to self");
                 }
 
-                if let Ok(guard) = self.nodes.read() {
-                    self.send(&guard, *dest, *payload);
+                let nodes = Arc::clone(&self.nodes);
+                if let Ok(guard) = nodes.read() {
+                    self.send(&guard, *dest, *payload, rng);
                 } else {
                     error!("network lock has been poisoned")
                 };
@@ -556,9 +757,15 @@ where
                 responder.respond(()).ignore()
             }
             NetworkRequest::Broadcast { payload, responder } => {
-                if let Ok(guard) = self.nodes.read() {
-                    for dest in guard.keys().filter(|&node_id| node_id != &self.node_id) {
-                        self.send(&guard, dest.clone(), *payload.clone());
+                let nodes = Arc::clone(&self.nodes);
+                if let Ok(guard) = nodes.read() {
+                    let dests: Vec<_> = guard
+                        .keys()
+                        .filter(|&node_id| node_id != &self.node_id)
+                        .cloned()
+                        .collect();
+                    for dest in dests {
+                        self.send(&guard, dest, *payload.clone(), rng);
                     }
                 } else {
                     error!("network lock has been poisoned")
@@ -572,7 +779,8 @@ where
                 exclude,
                 responder,
             } => {
-                if let Ok(guard) = self.nodes.read() {
+                let nodes = Arc::clone(&self.nodes);
+                if let Ok(guard) = nodes.read() {
                     let chosen: HashSet<_> = guard
                         .keys()
                         .filter(|&node_id| !exclude.contains(node_id) && node_id != &self.node_id)
@@ -582,7 +790,7 @@ where
                         .collect();
                     // Not terribly efficient, but will always get us the maximum amount of nodes.
                     for dest in chosen.iter() {
-                        self.send(&guard, dest.clone(), *payload.clone());
+                        self.send(&guard, dest.clone(), *payload.clone(), rng);
                     }
                     responder.respond(chosen).ignore()
                 } else {
@@ -1,40 +1,56 @@
+mod height_conflict;
+
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap},
     convert::Infallible,
     fmt::{self, Display, Formatter},
     marker::PhantomData,
+    mem,
 };
 
 use datasize::DataSize;
 use derive_more::From;
 use futures::FutureExt;
 use itertools::Itertools;
-use prometheus::{IntGauge, Registry};
+use num::rational::Ratio;
+use prometheus::{IntCounter, IntGauge, Registry};
+use serde_json::{json, Value};
 use tracing::{debug, error, info, warn};
 
-use casper_types::{ExecutionResult, ProtocolVersion, PublicKey, SemVer};
+use casper_types::{
+    system::auction::ValidatorWeights, AsymmetricType, ExecutionResult, ProtocolVersion, PublicKey,
+    SemVer, U512,
+};
 
 use super::{consensus::EraId, Component};
 use crate::{
+    crypto::{self, SignatureVerifier},
     effect::{
         announcements::LinearChainAnnouncement,
         requests::{
-            ConsensusRequest, ContractRuntimeRequest, LinearChainRequest, NetworkRequest,
-            StorageRequest,
+            ConsensusRequest, ContractRuntimeRequest, DumpStateRequest, LinearChainRequest,
+            NetworkRequest, StateStoreRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, EffectOptionExt, EffectResultExt, Effects, Responder,
     },
+    fatal,
     protocol::Message,
     types::{
-        Block, BlockByHeight, BlockHash, BlockSignatures, DeployHash, FinalitySignature, Timestamp,
+        Block, BlockByHeight, BlockExecutionResults, BlockFinalityProgress, BlockHash, BlockHeader,
+        BlockSignatures, DeployHash, ExitCode, FinalitySignature, Timestamp,
     },
     unregister_metric, NodeRng,
 };
+use height_conflict::{ConflictingBlock, HeightConflictEvidence};
 
 /// The maximum number of finality signatures from a single validator we keep in memory while
 /// waiting for their block.
 const MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR: usize = 1000;
 
+/// The number of most recent blocks for which we track the progress of collecting finality
+/// signatures.
+const MAX_FINALITY_PROGRESS_BLOCKS: usize = 20;
+
 impl<I> From<Box<FinalitySignature>> for Event<I> {
     fn from(fs: Box<FinalitySignature>) -> Self {
         Event::FinalitySignatureReceived(fs)
@@ -53,15 +69,33 @@ pub enum Event<I> {
         /// The deploys' execution results.
         execution_results: HashMap<DeployHash, ExecutionResult>,
     },
+    /// The result of checking whether a different block is already stored at the height of a new
+    /// linear chain block.
+    HeightChecked {
+        /// The new block.
+        block: Box<Block>,
+        /// The deploys' execution results.
+        execution_results: HashMap<DeployHash, ExecutionResult>,
+        /// The block already stored at the same height, if any.
+        stored_block: Option<Box<Block>>,
+    },
     /// A continuation for `GetBlock` scenario.
     GetBlockResult(BlockHash, Option<Box<Block>>, I),
     /// A continuation for `BlockAtHeight` scenario.
     GetBlockByHeightResult(u64, Option<Box<Block>>, I),
+    /// A continuation for `BlockHeaderRequest` scenario.
+    GetBlockHeaderResult(BlockHash, Option<Box<BlockHeader>>, I),
+    /// A continuation for `BlockExecutionResultsRequest` scenario.
+    GetBlockExecutionResultsResult(BlockHash, Option<Box<BlockExecutionResults>>, I),
     /// A continuation for `BlockAtHeightLocal` scenario.
     GetBlockByHeightResultLocal(u64, Option<Box<Block>>, Responder<Option<Block>>),
     /// Finality signature received.
     /// Not necessarily _new_ finality signature.
     FinalitySignatureReceived(Box<FinalitySignature>),
+    /// Time to verify the batch of received finality signatures.
+    VerifyFinalitySignatures,
+    /// The batch of received finality signatures was verified.
+    FinalitySignaturesVerified(Vec<(Box<FinalitySignature>, crypto::Result<()>)>),
     /// The result of putting a block to storage.
     PutBlockResult {
         /// The block.
@@ -76,6 +110,11 @@ pub enum Event<I> {
     IsBondedFutureEra(Option<Box<BlockSignatures>>, Box<FinalitySignature>),
     /// Result of testing if creator of the finality signature is bonded validator.
     IsBonded(Option<Box<BlockSignatures>>, Box<FinalitySignature>, bool),
+    /// The validator weights of an era, if consensus knows them.
+    GotValidatorWeights(EraId, Option<ValidatorWeights>),
+    /// A request to dump a snapshot of the internal state.
+    #[from]
+    DumpStateRequest(DumpStateRequest),
 }
 
 impl<I: Display> Display for Event<I> {
@@ -85,6 +124,19 @@ impl<I: Display> Display for Event<I> {
             Event::LinearChainBlock { block, .. } => {
                 write!(f, "linear chain new block: {}", block.hash())
             }
+            Event::HeightChecked {
+                block,
+                stored_block,
+                ..
+            } => write!(
+                f,
+                "linear chain checked height {} of {}, stored block: {:?}",
+                block.height(),
+                block.hash(),
+                stored_block
+                    .as_ref()
+                    .map(|stored_block| stored_block.hash())
+            ),
             Event::GetBlockResult(block_hash, maybe_block, peer) => write!(
                 f,
                 "linear chain get-block for {} from {} found: {}",
@@ -92,11 +144,33 @@ impl<I: Display> Display for Event<I> {
                 peer,
                 maybe_block.is_some()
             ),
+            Event::GetBlockHeaderResult(block_hash, maybe_header, peer) => write!(
+                f,
+                "linear chain get-block-header for {} from {} found: {}",
+                block_hash,
+                peer,
+                maybe_header.is_some()
+            ),
+            Event::GetBlockExecutionResultsResult(block_hash, maybe_results, peer) => write!(
+                f,
+                "linear chain get-block-execution-results for {} from {} found: {}",
+                block_hash,
+                peer,
+                maybe_results.is_some()
+            ),
             Event::FinalitySignatureReceived(fs) => write!(
                 f,
                 "linear-chain new finality signature for block: {}, from: {}",
                 fs.block_hash, fs.public_key,
             ),
+            Event::VerifyFinalitySignatures => {
+                write!(f, "linear-chain verify received finality signatures")
+            }
+            Event::FinalitySignaturesVerified(results) => write!(
+                f,
+                "linear-chain verified {} finality signatures",
+                results.len()
+            ),
             Event::PutBlockResult { .. } => write!(f, "linear-chain put-block result"),
             Event::GetBlockByHeightResult(height, result, peer) => write!(
                 f,
@@ -133,6 +207,13 @@ impl<I: Display> Display for Event<I> {
                     fs.era_id, fs.public_key
                 )
             }
+            Event::GotValidatorWeights(era_id, maybe_weights) => write!(
+                f,
+                "linear chain validator weights for era {} found: {}",
+                era_id,
+                maybe_weights.is_some()
+            ),
+            Event::DumpStateRequest(req) => write!(f, "linear chain {}", req),
         }
     }
 }
@@ -195,6 +276,111 @@ impl SignatureCache {
     }
 }
 
+/// A recent block whose finality signatures are being tracked.
+#[derive(DataSize, Debug)]
+struct RecentBlock {
+    block_hash: BlockHash,
+    era_id: EraId,
+    signers: BTreeSet<PublicKey>,
+}
+
+/// Tracks which validators have signed the most recent blocks, to measure how close they are to
+/// finality.
+#[derive(DataSize, Debug)]
+struct FinalityProgress {
+    /// The most recent blocks, by height.
+    blocks: BTreeMap<u64, RecentBlock>,
+    /// The validator weights of the eras of the tracked blocks, where known.
+    era_weights: BTreeMap<EraId, ValidatorWeights>,
+    /// The finality threshold fraction from the chainspec.
+    #[data_size(skip)]
+    finality_threshold_fraction: Ratio<u64>,
+}
+
+impl FinalityProgress {
+    fn new(finality_threshold_fraction: Ratio<u64>) -> Self {
+        FinalityProgress {
+            blocks: BTreeMap::new(),
+            era_weights: BTreeMap::new(),
+            finality_threshold_fraction,
+        }
+    }
+
+    /// Starts tracking a block, dropping the oldest one if the limit is exceeded.
+    fn add_block(&mut self, height: u64, block_signatures: &BlockSignatures) {
+        let recent_block = RecentBlock {
+            block_hash: block_signatures.block_hash,
+            era_id: block_signatures.era_id,
+            signers: block_signatures.proofs.keys().copied().collect(),
+        };
+        self.blocks.insert(height, recent_block);
+        while self.blocks.len() > MAX_FINALITY_PROGRESS_BLOCKS {
+            let oldest_height = *self.blocks.keys().next().expect("can't be empty");
+            self.blocks.remove(&oldest_height);
+        }
+        if let Some(oldest_era_id) = self.blocks.values().map(|block| block.era_id).min() {
+            self.era_weights = self.era_weights.split_off(&oldest_era_id);
+        }
+    }
+
+    /// Records a new signer of the given block, if it is tracked.
+    fn add_signer(&mut self, block_hash: &BlockHash, public_key: PublicKey) {
+        if let Some(block) = self
+            .blocks
+            .values_mut()
+            .rev()
+            .find(|block| block.block_hash == *block_hash)
+        {
+            block.signers.insert(public_key);
+        }
+    }
+
+    fn has_era_weights(&self, era_id: EraId) -> bool {
+        self.era_weights.contains_key(&era_id)
+    }
+
+    /// Sets the validator weights of an era, if any tracked block belongs to it.
+    fn set_era_weights(&mut self, era_id: EraId, validator_weights: ValidatorWeights) {
+        if self.blocks.values().any(|block| block.era_id == era_id) {
+            self.era_weights.insert(era_id, validator_weights);
+        }
+    }
+
+    /// Returns the progress of each tracked block whose era's validator weights are known, in
+    /// ascending order of height.
+    fn progress(&self) -> Vec<BlockFinalityProgress> {
+        self.blocks
+            .iter()
+            .filter_map(|(height, block)| {
+                let weights = self.era_weights.get(&block.era_id)?;
+                let signed_weight = block
+                    .signers
+                    .iter()
+                    .filter_map(|public_key| weights.get(public_key))
+                    .fold(U512::zero(), |sum, weight| sum + *weight);
+                let total_weight = weights
+                    .values()
+                    .fold(U512::zero(), |sum, weight| sum + *weight);
+                Some(BlockFinalityProgress {
+                    block_hash: block.block_hash,
+                    height: *height,
+                    era_id: block.era_id,
+                    signed_weight,
+                    total_weight,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the progress of each tracked block which doesn't have strict finality yet.
+    fn below_strict_finality(&self) -> Vec<BlockFinalityProgress> {
+        self.progress()
+            .into_iter()
+            .filter(|progress| !progress.is_strictly_final(self.finality_threshold_fraction))
+            .collect()
+    }
+}
+
 #[derive(DataSize, Debug)]
 pub(crate) struct LinearChain<I> {
     /// The most recently added block.
@@ -202,6 +388,14 @@ pub(crate) struct LinearChain<I> {
     /// Finality signatures to be inserted in a block once it is available.
     pending_finality_signatures: HashMap<PublicKey, HashMap<BlockHash, FinalitySignature>>,
     signature_cache: SignatureCache,
+    /// The progress of collecting finality signatures for the most recent blocks.
+    finality_progress: FinalityProgress,
+    /// Received finality signatures which are yet to be verified.
+    ///
+    /// These are verified as a batch on the signature verifier's worker threads.
+    unverified_finality_signatures: Vec<Box<FinalitySignature>>,
+    #[data_size(skip)]
+    signature_verifier: SignatureVerifier,
 
     #[data_size(skip)]
     metrics: LinearChainMetrics,
@@ -210,17 +404,48 @@ pub(crate) struct LinearChain<I> {
 }
 
 impl<I> LinearChain<I> {
-    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+    pub fn new(
+        registry: &Registry,
+        signature_verifier: SignatureVerifier,
+        finality_threshold_fraction: Ratio<u64>,
+    ) -> Result<Self, prometheus::Error> {
         let metrics = LinearChainMetrics::new(registry)?;
         Ok(LinearChain {
             latest_block: None,
             pending_finality_signatures: HashMap::new(),
             signature_cache: SignatureCache::new(),
+            finality_progress: FinalityProgress::new(finality_threshold_fraction),
+            unverified_finality_signatures: Vec::new(),
+            signature_verifier,
             metrics,
             _marker: PhantomData,
         })
     }
 
+    /// Returns the height of the most recently added block, if any.
+    pub(crate) fn latest_block_height(&self) -> Option<u64> {
+        self.latest_block.as_ref().map(Block::height)
+    }
+
+    /// Returns a snapshot of the internal state for debugging purposes.
+    ///
+    /// Pending finality signatures are summarized per signer; the signatures themselves are
+    /// omitted.
+    fn dump_state(&self) -> Value {
+        let pending_signatures: BTreeMap<String, usize> = self
+            .pending_finality_signatures
+            .iter()
+            .map(|(public_key, signatures)| (public_key.to_hex(), signatures.len()))
+            .collect();
+        json!({
+            "latest_block_hash": self.latest_block.as_ref().map(|block| block.hash().to_string()),
+            "latest_block_height": self.latest_block.as_ref().map(Block::height),
+            "pending_finality_signatures": pending_signatures,
+            "cached_signature_blocks": self.signature_cache.signatures.len(),
+            "unverified_finality_signatures": self.unverified_finality_signatures.len(),
+        })
+    }
+
     // Checks if we have already enqueued that finality signature.
     fn has_finality_signature(&self, fs: &FinalitySignature) -> bool {
         let creator = fs.public_key;
@@ -306,6 +531,148 @@ impl<I> LinearChain<I> {
         let _ = sigs.insert(block_hash, fs);
     }
 
+    /// Updates the finality progress metrics from the tracked blocks.
+    fn update_finality_progress_metrics(&self) {
+        let progress = self.finality_progress.progress();
+        let below_strict_finality = progress
+            .iter()
+            .filter(|progress| {
+                !progress.is_strictly_final(self.finality_progress.finality_threshold_fraction)
+            })
+            .count();
+        self.metrics
+            .blocks_below_strict_finality
+            .set(below_strict_finality as i64);
+        // The signed weight never exceeds the total, so the percentage always fits in a `u64`.
+        let signed_weight_percent = |progress: &BlockFinalityProgress| {
+            if progress.total_weight.is_zero() {
+                return 0;
+            }
+            (progress.signed_weight * U512::from(100) / progress.total_weight).as_u64() as i64
+        };
+        if let Some(latest) = progress.last() {
+            self.metrics
+                .latest_block_signed_weight_percent
+                .set(signed_weight_percent(latest));
+        }
+        if let Some(min_percent) = progress.iter().map(signed_weight_percent).min() {
+            self.metrics
+                .min_recent_block_signed_weight_percent
+                .set(min_percent);
+        }
+    }
+
+    /// Handles a finality signature which was successfully verified.
+    fn handle_verified_finality_signature<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        fs: Box<FinalitySignature>,
+    ) -> Effects<Event<I>>
+    where
+        REv: From<StorageRequest> + Send,
+        I: Send + 'static,
+    {
+        let FinalitySignature {
+            block_hash, era_id, ..
+        } = *fs;
+        if self.has_finality_signature(&fs) {
+            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
+                "finality signature already pending");
+            return Effects::new();
+        }
+        if self.signature_cache.known_signature(&fs) {
+            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
+                "finality signature is already known");
+            return Effects::new();
+        }
+        self.add_pending_finality_signature(*fs.clone());
+        match self.signature_cache.get(&block_hash, era_id) {
+            None => effect_builder
+                .get_signatures_from_storage(block_hash)
+                .event(move |maybe_signatures| {
+                    let maybe_box_signatures = maybe_signatures.map(Box::new);
+                    Event::GetStoredFinalitySignaturesResult(fs, maybe_box_signatures)
+                }),
+            Some(signatures) => effect_builder.immediately().event(move |_| {
+                Event::GetStoredFinalitySignaturesResult(fs, Some(Box::new(signatures)))
+            }),
+        }
+    }
+
+    /// Handles a new linear chain block at the same height as a different, already stored one.
+    ///
+    /// Gathers the finality signatures of both blocks and the validator weights of their eras,
+    /// persists them as evidence and then stops the node.
+    fn handle_height_conflict<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        stored_block: Box<Block>,
+        block: Box<Block>,
+    ) -> Effects<Event<I>>
+    where
+        REv: From<StorageRequest> + From<ConsensusRequest> + From<StateStoreRequest> + Send,
+        I: Send + 'static,
+    {
+        self.metrics.height_conflicts.inc();
+        let finality_threshold_fraction = self.finality_progress.finality_threshold_fraction;
+        error!(
+            height = block.height(),
+            stored_block_hash = %stored_block.hash(),
+            new_block_hash = %block.hash(),
+            "new linear chain block conflicts with stored block at the same height"
+        );
+
+        // The new block isn't stored, so its signatures are only known in memory.
+        let era_id = block.header().era_id();
+        let mut new_signatures = self
+            .signature_cache
+            .get_known_signatures(block.hash(), era_id);
+        for fs in self
+            .pending_finality_signatures
+            .values()
+            .filter_map(|sigs| sigs.get(block.hash()))
+            .filter(|fs| fs.era_id == era_id)
+        {
+            new_signatures.insert_proof(fs.public_key, fs.signature);
+        }
+
+        async move {
+            let stored_signatures = effect_builder
+                .get_signatures_from_storage(*stored_block.hash())
+                .await
+                .unwrap_or_else(|| {
+                    BlockSignatures::new(*stored_block.hash(), stored_block.header().era_id())
+                });
+            let stored_weights = effect_builder
+                .get_validator_weights_from_consensus(stored_block.header().era_id())
+                .await;
+            let new_weights = effect_builder
+                .get_validator_weights_from_consensus(era_id)
+                .await;
+            let evidence = HeightConflictEvidence::new(
+                block.height(),
+                ConflictingBlock::new(&stored_block, stored_signatures, stored_weights.as_ref()),
+                ConflictingBlock::new(&block, new_signatures, new_weights.as_ref()),
+            );
+            if !effect_builder
+                .save_state(evidence.state_key(), evidence.clone())
+                .await
+            {
+                error!("failed to persist height conflict evidence");
+            }
+            fatal!(
+                effect_builder,
+                ExitCode::FinalityViolation,
+                "{} (finality violated: {}), evidence: {:?}",
+                evidence,
+                evidence.both_have_quorum(finality_threshold_fraction),
+                evidence
+            )
+            .await
+        }
+        .ignore()
+    }
+
     /// Removes finality signature from the pending collection.
     fn remove_from_pending_fs(&mut self, fs: &FinalitySignature) {
         let FinalitySignature {
@@ -329,6 +696,7 @@ where
         + From<NetworkRequest<I, Message>>
         + From<LinearChainAnnouncement>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + Send,
     I: Display + Send + 'static,
 {
@@ -347,6 +715,24 @@ where
                 .event(move |maybe_block| {
                     Event::GetBlockResult(block_hash, maybe_block.map(Box::new), sender)
                 }),
+            Event::Request(LinearChainRequest::BlockHeaderRequest(block_hash, sender)) => {
+                effect_builder
+                    .get_block_header_from_storage(block_hash)
+                    .event(move |maybe_header| {
+                        Event::GetBlockHeaderResult(block_hash, maybe_header.map(Box::new), sender)
+                    })
+            }
+            Event::Request(LinearChainRequest::BlockExecutionResultsRequest(block_hash, sender)) => {
+                effect_builder
+                    .get_block_execution_results_from_storage(block_hash)
+                    .event(move |maybe_results| {
+                        Event::GetBlockExecutionResultsResult(
+                            block_hash,
+                            maybe_results.map(Box::new),
+                            sender,
+                        )
+                    })
+            }
             Event::Request(LinearChainRequest::BlockAtHeightLocal(height, responder)) => {
                 effect_builder
                     .get_block_at_height_from_storage(height)
@@ -359,6 +745,9 @@ where
                 .event(move |maybe_block| {
                     Event::GetBlockByHeightResult(height, maybe_block.map(Box::new), sender)
                 }),
+            Event::Request(LinearChainRequest::BlocksBelowStrictFinality(responder)) => responder
+                .respond(self.finality_progress.below_strict_finality())
+                .ignore(),
             Event::GetBlockByHeightResultLocal(_height, block, responder) => {
                 responder.respond(block.map(|boxed| *boxed)).ignore()
             }
@@ -391,9 +780,58 @@ where
                     }
                 },
             },
+            Event::GetBlockHeaderResult(block_hash, maybe_header, sender) => match maybe_header {
+                None => {
+                    debug!("failed to get header of {} for {}", block_hash, sender);
+                    Effects::new()
+                }
+                Some(header) => match Message::new_get_response(&*header) {
+                    Ok(message) => effect_builder.send_message(sender, message).ignore(),
+                    Err(error) => {
+                        error!("failed to create get-response {}", error);
+                        Effects::new()
+                    }
+                },
+            },
+            Event::GetBlockExecutionResultsResult(block_hash, maybe_results, sender) => {
+                match maybe_results {
+                    None => {
+                        debug!(
+                            "failed to get execution results of {} for {}",
+                            block_hash, sender
+                        );
+                        Effects::new()
+                    }
+                    Some(results) => match Message::new_get_response(&*results) {
+                        Ok(message) => effect_builder.send_message(sender, message).ignore(),
+                        Err(error) => {
+                            error!("failed to create get-response {}", error);
+                            Effects::new()
+                        }
+                    },
+                }
+            }
             Event::LinearChainBlock {
                 block,
                 execution_results,
+            } => effect_builder
+                .get_block_at_height_from_storage(block.height())
+                .event(move |maybe_stored_block| Event::HeightChecked {
+                    block,
+                    execution_results,
+                    stored_block: maybe_stored_block.map(Box::new),
+                }),
+            Event::HeightChecked {
+                block,
+                stored_block: Some(stored_block),
+                ..
+            } if stored_block.hash() != block.hash() => {
+                self.handle_height_conflict(effect_builder, stored_block, block)
+            }
+            Event::HeightChecked {
+                block,
+                execution_results,
+                ..
             } => {
                 let (signatures, mut effects) = self.collect_pending_finality_signatures(
                     block.hash(),
@@ -402,6 +840,20 @@ where
                 );
                 // Cache the signature as we expect more finality signatures to arrive soon.
                 self.signature_cache.insert(signatures.clone());
+                let era_id = block.header().era_id();
+                self.finality_progress
+                    .add_block(block.header().height(), &signatures);
+                if self.finality_progress.has_era_weights(era_id) {
+                    self.update_finality_progress_metrics();
+                } else {
+                    effects.extend(
+                        effect_builder
+                            .get_validator_weights_from_consensus(era_id)
+                            .event(move |maybe_weights| {
+                                Event::GotValidatorWeights(era_id, maybe_weights)
+                            }),
+                    );
+                }
                 effects.extend(effect_builder.put_signatures_to_storage(signatures).ignore());
                 effects.extend(effect_builder.put_block_to_storage(block.clone()).event(
                     move |_| Event::PutBlockResult {
@@ -441,38 +893,47 @@ where
                 effects
             }
             Event::FinalitySignatureReceived(fs) => {
-                let FinalitySignature {
-                    block_hash,
-                    public_key,
-                    era_id,
-                    ..
-                } = *fs;
-                if let Err(err) = fs.verify() {
-                    warn!(%block_hash, %public_key, %err, "received invalid finality signature");
-                    return Effects::new();
-                }
-                if self.has_finality_signature(&fs) {
-                    debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                        "finality signature already pending");
-                    return Effects::new();
+                // Collect the signatures received until the verification is handled, so that
+                // they can be verified in parallel.
+                self.unverified_finality_signatures.push(fs);
+                if self.unverified_finality_signatures.len() == 1 {
+                    effect_builder
+                        .immediately()
+                        .event(|_| Event::VerifyFinalitySignatures)
+                } else {
+                    Effects::new()
                 }
-                if self.signature_cache.known_signature(&fs) {
-                    debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                        "finality signature is already known");
-                    return Effects::new();
+            }
+            Event::VerifyFinalitySignatures => {
+                let batch = mem::take(&mut self.unverified_finality_signatures);
+                let signature_verifier = self.signature_verifier.clone();
+                async move {
+                    signature_verifier
+                        .verify_batch_async(batch, |fs| fs.verify())
+                        .await
                 }
-                self.add_pending_finality_signature(*fs.clone());
-                match self.signature_cache.get(&block_hash, era_id) {
-                    None => effect_builder
-                        .get_signatures_from_storage(block_hash)
-                        .event(move |maybe_signatures| {
-                            let maybe_box_signatures = maybe_signatures.map(Box::new);
-                            Event::GetStoredFinalitySignaturesResult(fs, maybe_box_signatures)
-                        }),
-                    Some(signatures) => effect_builder.immediately().event(move |_| {
-                        Event::GetStoredFinalitySignaturesResult(fs, Some(Box::new(signatures)))
-                    }),
+                .event(Event::FinalitySignaturesVerified)
+            }
+            Event::FinalitySignaturesVerified(results) => {
+                let mut effects = Effects::new();
+                for (fs, result) in results {
+                    match result {
+                        Ok(()) => effects.extend(self.handle_verified_finality_signature(
+                            effect_builder,
+                            fs,
+                        )),
+                        Err(err) => {
+                            let FinalitySignature {
+                                block_hash,
+                                public_key,
+                                ..
+                            } = *fs;
+                            warn!(%block_hash, %public_key, %err,
+                                "received invalid finality signature");
+                        }
+                    }
                 }
+                effects
             }
             Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures) => {
                 if let Some(signatures) = &maybe_signatures {
@@ -562,6 +1023,9 @@ where
                             .ignore(),
                     );
                     signatures.insert_proof(fs.public_key, fs.signature);
+                    self.finality_progress
+                        .add_signer(&signatures.block_hash, fs.public_key);
+                    self.update_finality_progress_metrics();
                     // Cache the results in case we receive the same finality signature before we
                     // manage to store it in the database.
                     self.signature_cache.insert(*signatures.clone());
@@ -575,6 +1039,20 @@ where
                     effects
                 }
             }
+            Event::GotValidatorWeights(era_id, maybe_weights) => {
+                match maybe_weights {
+                    Some(validator_weights) => {
+                        self.finality_progress
+                            .set_era_weights(era_id, validator_weights);
+                        self.update_finality_progress_metrics();
+                    }
+                    None => debug!(%era_id, "no validator weights for finality progress"),
+                }
+                Effects::new()
+            }
+            Event::DumpStateRequest(DumpStateRequest { responder, .. }) => {
+                responder.respond(Some(self.dump_state())).ignore()
+            }
             Event::IsBonded(None, _, true) => {
                 // Unknown block but validator is bonded.
                 // We should finalize the same block eventually. Either in this or in the
@@ -604,6 +1082,14 @@ where
 #[derive(Debug)]
 struct LinearChainMetrics {
     block_completion_duration: IntGauge,
+    /// Number of new blocks found to conflict with a stored block at the same height.
+    height_conflicts: IntCounter,
+    /// Number of recent blocks which don't have strict finality yet.
+    blocks_below_strict_finality: IntGauge,
+    /// Percentage of the total validator weight which has signed the latest block.
+    latest_block_signed_weight_percent: IntGauge,
+    /// Lowest percentage of the total validator weight which has signed any recent block.
+    min_recent_block_signed_weight_percent: IntGauge,
     /// Prometheus registry used to publish metrics.
     registry: Registry,
 }
@@ -614,9 +1100,33 @@ impl LinearChainMetrics {
             "block_completion_duration",
             "duration of time from consensus through execution for a block",
         )?;
+        let height_conflicts = IntCounter::new(
+            "linear_chain_height_conflicts",
+            "number of new blocks found to conflict with a stored block at the same height",
+        )?;
+        let blocks_below_strict_finality = IntGauge::new(
+            "blocks_below_strict_finality",
+            "number of recent blocks without strict finality",
+        )?;
+        let latest_block_signed_weight_percent = IntGauge::new(
+            "latest_block_signed_weight_percent",
+            "percentage of the total validator weight which has signed the latest block",
+        )?;
+        let min_recent_block_signed_weight_percent = IntGauge::new(
+            "min_recent_block_signed_weight_percent",
+            "lowest percentage of the total validator weight which has signed any recent block",
+        )?;
         registry.register(Box::new(block_completion_duration.clone()))?;
+        registry.register(Box::new(height_conflicts.clone()))?;
+        registry.register(Box::new(blocks_below_strict_finality.clone()))?;
+        registry.register(Box::new(latest_block_signed_weight_percent.clone()))?;
+        registry.register(Box::new(min_recent_block_signed_weight_percent.clone()))?;
         Ok(Self {
             block_completion_duration,
+            height_conflicts,
+            blocks_below_strict_finality,
+            latest_block_signed_weight_percent,
+            min_recent_block_signed_weight_percent,
             registry: registry.clone(),
         })
     }
@@ -625,5 +1135,50 @@ impl LinearChainMetrics {
 impl Drop for LinearChainMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.block_completion_duration);
+        unregister_metric!(self.registry, self.height_conflicts);
+        unregister_metric!(self.registry, self.blocks_below_strict_finality);
+        unregister_metric!(self.registry, self.latest_block_signed_weight_percent);
+        unregister_metric!(self.registry, self.min_recent_block_signed_weight_percent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::SecretKey;
+
+    use super::*;
+    use crate::{crypto::AsymmetricKeyExt, testing::TestRng};
+
+    #[test]
+    fn should_report_blocks_below_strict_finality() {
+        let mut rng = TestRng::new();
+        let validators: Vec<PublicKey> = (0..3)
+            .map(|_| PublicKey::from(&SecretKey::random(&mut rng)))
+            .collect();
+        let validator_weights: ValidatorWeights = validators
+            .iter()
+            .zip(&[1u64, 2, 3])
+            .map(|(public_key, weight)| (*public_key, U512::from(*weight)))
+            .collect();
+        let era_id = EraId(1);
+        let block_hash = BlockHash::random(&mut rng);
+
+        let mut finality_progress = FinalityProgress::new(Ratio::new(1, 3));
+        finality_progress.add_block(10, &BlockSignatures::new(block_hash, era_id));
+        // Nothing can be reported until the era's weights are known.
+        assert!(finality_progress.below_strict_finality().is_empty());
+
+        finality_progress.set_era_weights(era_id, validator_weights);
+        assert_eq!(finality_progress.below_strict_finality().len(), 1);
+
+        // A weight of 4 out of 6 is exactly two thirds, which isn't enough.
+        finality_progress.add_signer(&block_hash, validators[2]);
+        finality_progress.add_signer(&block_hash, validators[0]);
+        let below = finality_progress.below_strict_finality();
+        assert_eq!(below[0].signed_weight, U512::from(4));
+        assert_eq!(below[0].total_weight, U512::from(6));
+
+        finality_progress.add_signer(&block_hash, validators[1]);
+        assert!(finality_progress.below_strict_finality().is_empty());
     }
 }
@@ -0,0 +1,247 @@
+//! Evidence of two different blocks at the same height.
+//!
+//! Finality should make this impossible, so a conflict means either more than the finality
+//! threshold fraction of the validator weight equivocated, or there is a bug.  Either way the node
+//! must not continue, but the evidence is kept for later analysis first.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+
+use num::rational::Ratio;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{system::auction::ValidatorWeights, U512};
+
+use crate::types::{
+    Block, BlockFinalityProgress, BlockHeader, BlockSignatures, FinalityStatus, Timestamp,
+};
+
+/// Prefix of the state store keys under which evidence is persisted, followed by the height.
+const EVIDENCE_KEY_PREFIX: &str = "linear_chain_height_conflict_";
+
+/// One of two conflicting blocks, with the finality signatures known for it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(super) struct ConflictingBlock {
+    /// The block's header.
+    header: BlockHeader,
+    /// The finality signatures known for the block.
+    signatures: BlockSignatures,
+    /// The weight of the signatures, if the validator weights of the block's era are known.
+    finality: Option<BlockFinalityProgress>,
+}
+
+impl ConflictingBlock {
+    pub(super) fn new(
+        block: &Block,
+        signatures: BlockSignatures,
+        maybe_validator_weights: Option<&ValidatorWeights>,
+    ) -> Self {
+        let finality = maybe_validator_weights.map(|weights| {
+            let signed_weight = signatures
+                .proofs
+                .keys()
+                .filter_map(|public_key| weights.get(public_key))
+                .fold(U512::zero(), |sum, weight| sum + *weight);
+            let total_weight = weights
+                .values()
+                .fold(U512::zero(), |sum, weight| sum + *weight);
+            BlockFinalityProgress {
+                block_hash: *block.hash(),
+                height: block.height(),
+                era_id: block.header().era_id(),
+                signed_weight,
+                total_weight,
+            }
+        });
+        ConflictingBlock {
+            header: block.header().clone(),
+            signatures,
+            finality,
+        }
+    }
+
+    /// Returns the degree of finality the known signatures amount to, or `None` if the validator
+    /// weights of the block's era are unknown.
+    pub(super) fn finality_status(
+        &self,
+        finality_threshold_fraction: Ratio<u64>,
+    ) -> Option<FinalityStatus> {
+        self.finality
+            .as_ref()
+            .map(|finality| finality.finality_status(finality_threshold_fraction))
+    }
+}
+
+impl Display for ConflictingBlock {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "block {} in {} with {} signatures",
+            self.header.hash(),
+            self.header.era_id(),
+            self.signatures.proofs.len()
+        )?;
+        match &self.finality {
+            Some(finality) => write!(
+                formatter,
+                " of weight {}/{}",
+                finality.signed_weight, finality.total_weight
+            ),
+            None => write!(formatter, " of unknown weight"),
+        }
+    }
+}
+
+/// Evidence of two different blocks at the same height.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(super) struct HeightConflictEvidence {
+    /// The height both blocks claim.
+    height: u64,
+    /// The block which was already stored.
+    stored: ConflictingBlock,
+    /// The block which was newly added to the linear chain.
+    new: ConflictingBlock,
+    /// When the conflict was detected.
+    detected_at: Timestamp,
+}
+
+impl HeightConflictEvidence {
+    pub(super) fn new(height: u64, stored: ConflictingBlock, new: ConflictingBlock) -> Self {
+        HeightConflictEvidence {
+            height,
+            stored,
+            new,
+            detected_at: Timestamp::now(),
+        }
+    }
+
+    /// Returns whether both blocks are signed by validators with more than the finality threshold
+    /// fraction of their era's weight, i.e. whether finality was actually violated.
+    pub(super) fn both_have_quorum(&self, finality_threshold_fraction: Ratio<u64>) -> bool {
+        let has_quorum = |block: &ConflictingBlock| {
+            matches!(
+                block.finality_status(finality_threshold_fraction),
+                Some(FinalityStatus::Weak) | Some(FinalityStatus::Strict)
+            )
+        };
+        has_quorum(&self.stored) && has_quorum(&self.new)
+    }
+
+    /// Returns the state store key under which the evidence is persisted.
+    pub(super) fn state_key(&self) -> Cow<'static, [u8]> {
+        format!("{}{}", EVIDENCE_KEY_PREFIX, self.height)
+            .into_bytes()
+            .into()
+    }
+}
+
+impl Display for HeightConflictEvidence {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "conflicting blocks at height {}: stored {}, new {}",
+            self.height, self.stored, self.new
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{PublicKey, SecretKey};
+
+    use super::*;
+    use crate::{
+        components::consensus::EraId, crypto::AsymmetricKeyExt, testing::TestRng,
+        types::FinalitySignature,
+    };
+
+    /// Returns the signatures of the given validators for `block`.
+    fn signatures(block: &Block, secret_keys: &[SecretKey], rng: &mut TestRng) -> BlockSignatures {
+        let era_id = block.header().era_id();
+        let mut signatures = BlockSignatures::new(*block.hash(), era_id);
+        for secret_key in secret_keys {
+            let public_key = PublicKey::from(secret_key);
+            let fs = FinalitySignature::new(*block.hash(), era_id, secret_key, public_key, rng);
+            signatures.insert_proof(fs.public_key, fs.signature);
+        }
+        signatures
+    }
+
+    #[test]
+    fn should_require_quorum_for_both_blocks() {
+        let mut rng = TestRng::new();
+        let ftt = Ratio::new(1, 3);
+        let secret_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut rng)).collect();
+        let validator_weights: ValidatorWeights = secret_keys
+            .iter()
+            .map(|secret_key| (PublicKey::from(secret_key), U512::from(10)))
+            .collect();
+        let stored_block = Block::random_with_specifics(&mut rng, EraId(1), 5, false);
+        let new_block = Block::random_with_specifics(&mut rng, EraId(1), 5, false);
+
+        let stored = ConflictingBlock::new(
+            &stored_block,
+            signatures(&stored_block, &secret_keys, &mut rng),
+            Some(&validator_weights),
+        );
+        assert_eq!(stored.finality_status(ftt), Some(FinalityStatus::Strict));
+
+        // A single signer of three equally weighted validators isn't more than a third.
+        let new = ConflictingBlock::new(
+            &new_block,
+            signatures(&new_block, &secret_keys[..1], &mut rng),
+            Some(&validator_weights),
+        );
+        let evidence = HeightConflictEvidence::new(5, stored.clone(), new);
+        assert!(!evidence.both_have_quorum(ftt));
+
+        let new = ConflictingBlock::new(
+            &new_block,
+            signatures(&new_block, &secret_keys[1..], &mut rng),
+            Some(&validator_weights),
+        );
+        assert_eq!(new.finality_status(ftt), Some(FinalityStatus::Weak));
+        let evidence = HeightConflictEvidence::new(5, stored.clone(), new);
+        assert!(evidence.both_have_quorum(ftt));
+
+        // Without the validator weights, the quorum can't be established.
+        let new = ConflictingBlock::new(
+            &new_block,
+            signatures(&new_block, &secret_keys, &mut rng),
+            None,
+        );
+        let evidence = HeightConflictEvidence::new(5, stored, new);
+        assert!(!evidence.both_have_quorum(ftt));
+    }
+
+    #[test]
+    fn should_roundtrip_and_name_both_blocks() {
+        let mut rng = TestRng::new();
+        let stored_block = Block::random_with_specifics(&mut rng, EraId(2), 7, false);
+        let new_block = Block::random_with_specifics(&mut rng, EraId(2), 7, false);
+        let evidence = HeightConflictEvidence::new(
+            7,
+            ConflictingBlock::new(
+                &stored_block,
+                signatures(&stored_block, &[], &mut rng),
+                None,
+            ),
+            ConflictingBlock::new(&new_block, signatures(&new_block, &[], &mut rng), None),
+        );
+
+        let description = evidence.to_string();
+        assert!(description.contains(&stored_block.hash().to_string()));
+        assert!(description.contains(&new_block.hash().to_string()));
+        assert_eq!(
+            evidence.state_key().as_ref(),
+            b"linear_chain_height_conflict_7"
+        );
+
+        let serialized = bincode::serialize(&evidence).expect("should serialize");
+        let deserialized: HeightConflictEvidence =
+            bincode::deserialize(&serialized).expect("should deserialize");
+        assert_eq!(deserialized.to_string(), description);
+    }
+}
@@ -21,11 +21,12 @@ use super::{
     Component,
 };
 use crate::{
-    effect::{EffectBuilder, EffectExt, EffectOptionExt, Effects},
+    effect::{EffectBuilder, EffectExt, EffectResultExt, Effects},
     types::{
         ActivationPoint, Block, BlockByHeight, BlockHash, BlockHeader, Chainspec, FinalizedBlock,
     },
-    NodeRng,
+    utils::SyncProgress,
+    LinearChainSyncConfig, NodeRng,
 };
 use event::BlockByHeightResult;
 pub use event::Event;
@@ -46,12 +47,15 @@ pub(crate) struct LinearChainFastSync<I> {
 impl<I: Clone + PartialEq + 'static> LinearChainFastSync<I> {
     pub fn new<Err>(
         registry: &Registry,
+        _config: LinearChainSyncConfig,
         _chainspec: &Chainspec,
         _storage: &Storage,
         init_hash: Option<BlockHash>,
         _highest_block_header: Option<BlockHeader>,
         genesis_validator_weights: BTreeMap<PublicKey, U512>,
         _next_upgrade_activation_point: Option<ActivationPoint>,
+        _minimal_join: bool,
+        _progress: SyncProgress,
     ) -> Result<Self, Err>
     where
         Err: From<prometheus::Error> + From<storage::Error>,
@@ -539,8 +543,9 @@ where
     let block_timestamp = block.header().timestamp();
     effect_builder
         .validate_block(peer.clone(), block, block_timestamp)
-        .event(move |(found, block)| {
-            if found {
+        .event(move |result| {
+            let block = result.block;
+            if result.outcome.is_valid() {
                 Event::GetDeploysResult(DeploysResult::Found(Box::new(block)))
             } else {
                 Event::GetDeploysResult(DeploysResult::NotFound(Box::new(block), peer))
@@ -557,7 +562,7 @@ where
     REv: ReactorEventT<I>,
 {
     let cloned = peer.clone();
-    effect_builder.fetch_block(block_hash, peer).map_or_else(
+    effect_builder.fetch_block(block_hash, peer).result(
         move |fetch_result| match fetch_result {
             FetchResult::FromStorage(block) => {
                 Event::GetBlockHashResult(block_hash, BlockByHashResult::FromStorage(block))
@@ -566,7 +571,7 @@ where
                 Event::GetBlockHashResult(block_hash, BlockByHashResult::FromPeer(block, peer))
             }
         },
-        move || Event::GetBlockHashResult(block_hash, BlockByHashResult::Absent(cloned)),
+        move |_| Event::GetBlockHashResult(block_hash, BlockByHashResult::Absent(cloned)),
     )
 }
 
@@ -581,7 +586,7 @@ where
     let cloned = peer.clone();
     effect_builder
         .fetch_block_by_height(block_height, peer.clone())
-        .map_or_else(
+        .result(
             move |fetch_result| match fetch_result {
                 FetchResult::FromPeer(result, _) => match *result {
                     BlockByHeight::Absent(ret_height) => {
@@ -608,6 +613,6 @@ where
                     ),
                 },
             },
-            move || Event::GetBlockHeightResult(block_height, BlockByHeightResult::Absent(cloned)),
+            move |_| Event::GetBlockHeightResult(block_height, BlockByHeightResult::Absent(cloned)),
         )
 }
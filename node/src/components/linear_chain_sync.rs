@@ -22,20 +22,58 @@
 //! execution is interleaved. If we had downloaded the whole chain, and then deploys, and then
 //! execute (as we do in the first, SynchronizeTrustedHash, phase) it would have taken more time and
 //! we might miss more eras.
+//!
+//! In "minimal join" mode, step 1 starts by checking whether the global state of the trusted block
+//! is already present locally. If it is, only the headers of its ancestors are downloaded, down to
+//! the oldest switch block consensus needs, and of those only the switch blocks are downloaded in
+//! full. They are stored without being executed and we transition straight to
+//! `SyncingDescendants`. If the global state is missing, or the ancestors reach local storage,
+//! genesis or the last activation point first, we fall back to downloading the whole chain.
+//!
+//! Since the trusted block isn't executed either, its execution results are fetched from peers.
+//! They are verified against the checksum registry in the trusted block's global state, and
+//! fetched again from another peer on a mismatch. Blocks of eras before the registry's activation
+//! can't be verified this way, so their execution results aren't fetched.
+//!
+//! If several trusted hashes are configured, the blocks of all of them are fetched first and the
+//! highest one is used as the trusted hash.  The others must turn up among its ancestors in step
+//! 1, otherwise synchronization stops with a report of the conflicting hashes.
+//!
+//! The state is saved to storage when the node stops for an upgrade or is shut down while
+//! syncing. On restart, it is validated against the trusted hash and the highest block in storage,
+//! and synchronization resumes from where it left off, without downloading the blocks again.
 
+mod config;
 mod event;
 mod metrics;
 mod peers;
 mod state;
+mod tests;
 mod traits;
+mod trusted_hashes;
 
-use std::{collections::BTreeMap, convert::Infallible, fmt::Display, mem};
+use std::{
+    cmp,
+    collections::BTreeMap,
+    convert::{Infallible, TryFrom},
+    fmt::Display,
+    hash::Hash,
+    mem,
+    time::Duration,
+};
 
 use datasize::DataSize;
 use prometheus::Registry;
 use tracing::{error, info, trace, warn};
 
-use self::event::{BlockByHashResult, DeploysResult};
+use self::{
+    event::{
+        BlockByHashResult, BlockHeaderResult, DemotionReason, DeploysResult,
+        ExecutionResultsResult, PendingFetch,
+    },
+    trusted_hashes::{TrustedHashConflict, TrustedHashes},
+};
+use casper_execution_engine::core::engine_state::{QueryRequest, QueryResult};
 use casper_types::{PublicKey, U512};
 
 use super::{
@@ -45,13 +83,15 @@ use super::{
     Component,
 };
 use crate::{
-    effect::{EffectBuilder, EffectExt, EffectOptionExt, Effects},
+    effect::{EffectBuilder, EffectExt, EffectResultExt, Effects},
     types::{
-        ActivationPoint, Block, BlockByHeight, BlockHash, BlockHeader, Chainspec, FinalizedBlock,
-        TimeDiff,
+        ActivationPoint, Block, BlockByHeight, BlockExecutionResults, BlockHash, BlockHeader,
+        Chainspec, ChecksumRegistry, FinalizedBlock, TimeDiff, CHECKSUM_REGISTRY_KEY,
     },
+    utils::{SyncProgress, SyncSnapshot},
     NodeRng,
 };
+pub use config::{Config, PeerSelectionPolicy};
 use event::BlockByHeightResult;
 pub use event::Event;
 pub use metrics::LinearChainSyncMetrics;
@@ -59,6 +99,14 @@ pub use peers::PeersState;
 pub use state::State;
 pub use traits::ReactorEventT;
 
+/// The delay before asking the networking component for the current peers, after we ran out of
+/// reliable peers to fetch from.
+const PEER_REFRESH_DELAY: Duration = Duration::from_secs(5);
+
+/// The delay before checking again whether the block executor can take another block, after its
+/// queue was found to be full.
+const EXECUTION_QUEUE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
 #[derive(DataSize, Debug)]
 pub(crate) struct LinearChainSync<I> {
     peers: PeersState<I>,
@@ -77,29 +125,95 @@ pub(crate) struct LinearChainSync<I> {
     acceptable_drift: TimeDiff,
     /// Shortest era that is allowed with the given protocol configuration.
     shortest_era: TimeDiff,
+    /// Whether to skip executing the ancestors of the trusted block if its global state is
+    /// available locally.
+    minimal_join: bool,
+    /// The number of eras before the era of the trusted block whose switch blocks consensus needs
+    /// after joining in minimal mode.
+    minimal_join_eras: u64,
+    /// The era of the last activation point. Switch blocks of earlier eras are never used.
+    last_activation_era: EraId,
+    /// The first era whose blocks were committed with a checksum registry.
+    checksum_registry_activation_era: EraId,
+    /// The checksum registry of the trusted block, while fetching its execution results.
+    #[data_size(skip)]
+    trusted_checksum_registry: Option<ChecksumRegistry>,
+    /// Whether the latest block of a restored `SyncingDescendants` state was not yet executed, and
+    /// needs to be before fetching its child.
+    execute_latest_block: bool,
+    /// The configured trusted hashes, if there are several of them, while verifying that they lie
+    /// on the same chain.
+    trusted_hashes: Option<TrustedHashes>,
+    /// The latest progress, shared with the REST server.
+    #[data_size(skip)]
+    progress: SyncProgress,
 }
 
-impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
+impl<I: Display + Clone + Eq + Hash + 'static> LinearChainSync<I> {
     // TODO: fix this
     #[allow(clippy::too_many_arguments)]
     pub fn new<Err>(
         registry: &Registry,
+        config: Config,
         chainspec: &Chainspec,
         storage: &Storage,
-        init_hash: Option<BlockHash>,
+        trusted_hashes: Vec<BlockHash>,
         highest_block_header: Option<BlockHeader>,
         _genesis_validator_weights: BTreeMap<PublicKey, U512>,
         next_upgrade_activation_point: Option<ActivationPoint>,
+        minimal_join: bool,
+        progress: SyncProgress,
     ) -> Result<Self, Err>
     where
         Err: From<prometheus::Error> + From<storage::Error>,
     {
-        if let Some(state) = read_init_state(storage, chainspec)? {
+        let maybe_restored_state = read_init_state(storage, chainspec)?.and_then(|state| {
+            let restored_state = state.restore(&trusted_hashes, highest_block_header.as_ref());
+            if restored_state.is_none() {
+                info!("previous state doesn't match storage, starting synchronization afresh");
+            }
+            restored_state
+        });
+        // The trusted hashes are only verified against each other while downloading the chain, so
+        // if that was interrupted, it has to be done again.
+        let maybe_restored_state = maybe_restored_state.filter(|(state, _)| {
+            let still_downloading = matches!(
+                state,
+                State::SyncingTrustedHash { latest_block, .. } if latest_block.is_none()
+            );
+            if still_downloading && trusted_hashes.len() > 1 {
+                info!("restarting the download to verify the trusted hashes");
+                return false;
+            }
+            true
+        });
+        // Joining in minimal mode only keeps the switch blocks among the ancestors of the trusted
+        // block, which can't be executed if minimal joining has been disabled since.
+        let maybe_restored_state = maybe_restored_state.filter(|(state, _)| {
+            let skipped_blocks = matches!(
+                state,
+                State::SyncingTrustedHash { linear_chain, latest_block, .. }
+                    if latest_block.is_none()
+                        && linear_chain
+                            .windows(2)
+                            .any(|pair| pair[0].header().parent_hash() != pair[1].hash())
+            );
+            if skipped_blocks && !minimal_join {
+                info!("restarting the download, since minimal joining was disabled");
+                return false;
+            }
+            true
+        });
+        if let Some((state, execute_latest_block)) = maybe_restored_state {
             Ok(LinearChainSync::from_state(
                 registry,
+                config,
                 chainspec,
                 state,
+                execute_latest_block,
                 next_upgrade_activation_point,
+                minimal_join,
+                progress,
             )?)
         } else {
             let acceptable_drift = chainspec.highway_config.max_round_length();
@@ -109,12 +223,17 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
                     * chainspec.core_config.minimum_era_height,
                 chainspec.core_config.era_duration,
             );
-            let state = init_hash.map_or(State::None, |init_hash| {
-                State::sync_trusted_hash(init_hash, highest_block_header)
+            // With several trusted hashes, the first one is replaced by the highest once all of
+            // their blocks are fetched.
+            let state = trusted_hashes.first().map_or(State::None, |init_hash| {
+                State::sync_trusted_hash(*init_hash, highest_block_header)
             });
+            let trusted_hashes = Some(trusted_hashes)
+                .filter(|hashes| hashes.len() > 1)
+                .map(TrustedHashes::new);
             let state_key = create_state_key(&chainspec);
             Ok(LinearChainSync {
-                peers: PeersState::new(),
+                peers: PeersState::new(config),
                 state,
                 metrics: LinearChainSyncMetrics::new(registry)?,
                 next_upgrade_activation_point,
@@ -122,6 +241,16 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
                 state_key,
                 acceptable_drift,
                 shortest_era,
+                minimal_join,
+                minimal_join_eras: minimal_join_eras(chainspec),
+                last_activation_era: chainspec.protocol_config.activation_point.era_id(),
+                checksum_registry_activation_era: chainspec
+                    .core_config
+                    .checksum_registry_activation_era,
+                trusted_checksum_registry: None,
+                execute_latest_block: false,
+                trusted_hashes,
+                progress,
             })
         }
     }
@@ -129,9 +258,13 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
     /// Initialize `LinearChainSync` component from preloaded `State`.
     fn from_state(
         registry: &Registry,
+        config: Config,
         chainspec: &Chainspec,
         state: State,
+        execute_latest_block: bool,
         next_upgrade_activation_point: Option<ActivationPoint>,
+        minimal_join: bool,
+        progress: SyncProgress,
     ) -> Result<Self, prometheus::Error> {
         let state_key = create_state_key(chainspec);
         info!(?state, "reusing previous state");
@@ -142,7 +275,7 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
             chainspec.core_config.era_duration,
         );
         Ok(LinearChainSync {
-            peers: PeersState::new(),
+            peers: PeersState::new(config),
             state,
             metrics: LinearChainSyncMetrics::new(registry)?,
             next_upgrade_activation_point,
@@ -150,6 +283,16 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
             state_key,
             acceptable_drift,
             shortest_era,
+            minimal_join,
+            minimal_join_eras: minimal_join_eras(chainspec),
+            last_activation_era: chainspec.protocol_config.activation_point.era_id(),
+            checksum_registry_activation_era: chainspec
+                .core_config
+                .checksum_registry_activation_era,
+            trusted_checksum_registry: None,
+            execute_latest_block,
+            trusted_hashes: None,
+            progress,
         })
     }
 
@@ -182,15 +325,41 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         I: Send + 'static,
         REv: ReactorEventT<I>,
     {
-        self.peers.reset(rng);
+        self.peers.reset(rng, self.peer_policy());
+        if let State::SyncingTrustedHash { .. } = self.state {
+            if let Some(Err(conflict)) = self
+                .trusted_hashes
+                .as_mut()
+                .map(|trusted_hashes| trusted_hashes.check_ancestor(block))
+            {
+                self.fail_on_trusted_hash_conflict(conflict);
+            }
+        }
         self.state.block_downloaded(block);
         self.add_block(block.clone());
+        self.continue_after_download(rng, effect_builder, block)
+    }
+
+    /// Continues synchronization after `block`, the latest block added to the state, has been
+    /// downloaded.
+    fn continue_after_download<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        block: &Block,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let reached_minimal_join_depth = self.reached_minimal_join_depth(block);
         match &self.state {
             State::None | State::Done(_) => {
                 panic!("Downloaded block when in {} state.", self.state)
             }
             State::SyncingTrustedHash {
                 highest_block_header,
+                linear_chain,
                 ..
             } => {
                 let should_start_downloading_deploys = highest_block_header
@@ -198,13 +367,22 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
                     .map(|hdr| hdr.hash() == *block.header().parent_hash())
                     .unwrap_or(false)
                     || block.header().is_genesis_child();
+                let is_trusted_block = linear_chain.len() == 1;
                 if should_start_downloading_deploys {
+                    self.check_trusted_hashes_in_storage(block.height() - 1);
                     info!("linear chain downloaded. Start downloading deploys.");
                     effect_builder
                         .immediately()
                         .event(move |_| Event::StartDownloadingDeploys)
-                } else {
+                } else if !self.minimal_join {
                     self.fetch_next_block(effect_builder, rng, block)
+                } else if is_trusted_block {
+                    // Skipping the ancestors is only possible if we won't need to execute them.
+                    self.check_trusted_state(effect_builder)
+                } else if reached_minimal_join_depth {
+                    self.read_trusted_checksum_registry(rng, effect_builder)
+                } else {
+                    self.fetch_parent_header(rng, effect_builder, block.header())
                 }
             }
             State::SyncingDescendants { .. } => {
@@ -215,6 +393,469 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         }
     }
 
+    /// Returns `true` if we're joining in minimal mode and `block` is the oldest switch block
+    /// needed to initialize consensus in the era of the trusted block.
+    fn reached_minimal_join_depth(&self, block: &Block) -> bool {
+        if !self.minimal_join
+            || !block.header().is_switch_block()
+            || self
+                .trusted_hashes
+                .as_ref()
+                .map_or(false, TrustedHashes::has_pending)
+            || block.header().era_id() < self.last_activation_era
+        {
+            return false;
+        }
+        match &self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => {
+                linear_chain.first().map_or(false, |trusted_block| {
+                    block.header().era_id().0 + self.minimal_join_eras
+                        <= trusted_block.header().era_id().0
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks whether the global state of the trusted block is fully present in the local trie
+    /// store.
+    fn check_trusted_state<REv>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let state_root_hash = match &self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => match linear_chain.first() {
+                Some(trusted_block) => *trusted_block.state_root_hash(),
+                None => panic!("Checking trusted state without a trusted block."),
+            },
+            _ => panic!("Checking trusted state when in {} state.", self.state),
+        };
+        effect_builder
+            .missing_trie_keys(state_root_hash.into())
+            .event(|result| {
+                Event::TrustedStateChecked(matches!(result, Ok(missing) if missing.is_empty()))
+            })
+    }
+
+    /// Handles the result of checking whether the global state of the trusted block is present.
+    ///
+    /// If it is, we start downloading the headers of the ancestors of the trusted block. Otherwise
+    /// we fall back to downloading the whole chain.
+    fn trusted_state_checked<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        present: bool,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let trusted_header = match &self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => match linear_chain.first() {
+                Some(trusted_block) => trusted_block.header().clone(),
+                None => panic!("Trusted state checked without a trusted block."),
+            },
+            other => {
+                error!(state = %other, "trusted state checked when not syncing trusted hash");
+                return Effects::new();
+            }
+        };
+        if !present {
+            warn!("global state of the trusted block is not present. falling back to full synchronization");
+            return self.fall_back_to_full_sync(rng, effect_builder);
+        }
+        info!("global state of the trusted block is present. downloading the headers of its ancestors");
+        self.fetch_parent_header(rng, effect_builder, &trusted_header)
+    }
+
+    /// Fetches the header of the parent of `header`, while joining in minimal mode.
+    fn fetch_parent_header<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        header: &BlockHeader,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        self.peers.reset(rng, self.peer_policy());
+        let peer = self.peers.random_unsafe();
+        self.metrics.reset_start_time();
+        fetch_block_header(effect_builder, peer, *header.parent_hash())
+    }
+
+    /// Handles the downloaded header of an ancestor of the trusted block, while joining in minimal
+    /// mode.
+    ///
+    /// Consensus reads switch blocks from storage, so those are fetched in full and added to the
+    /// downloaded chain. Any other block is skipped, and we continue with its parent's header.
+    fn header_downloaded<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        header: &BlockHeader,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        if let Some(Err(conflict)) = self
+            .trusted_hashes
+            .as_mut()
+            .map(|trusted_hashes| trusted_hashes.check_ancestor(header))
+        {
+            self.fail_on_trusted_hash_conflict(conflict);
+        }
+        let connects_to_storage = match &self.state {
+            State::SyncingTrustedHash {
+                highest_block_header,
+                ..
+            } => highest_block_header
+                .as_ref()
+                .map_or(false, |hdr| hdr.hash() == *header.parent_hash()),
+            other => {
+                error!(state = %other, "downloaded block header when not syncing trusted hash");
+                return Effects::new();
+            }
+        };
+        if connects_to_storage
+            || header.is_genesis_child()
+            || header.era_id() < self.last_activation_era
+        {
+            info!(
+                height = header.height(),
+                era = header.era_id().0,
+                "ancestors of the trusted block reached storage, genesis or the last activation point. falling back to full synchronization"
+            );
+            return self.fall_back_to_full_sync(rng, effect_builder);
+        }
+        if !header.is_switch_block() {
+            return self.fetch_parent_header(rng, effect_builder, header);
+        }
+        self.peers.reset(rng, self.peer_policy());
+        let peer = self.peers.random_unsafe();
+        self.metrics.reset_start_time();
+        fetch_block_by_hash(effect_builder, peer, header.hash())
+    }
+
+    /// Reads the checksum registry from the global state of the trusted block, so that the trusted
+    /// block's execution results can be verified once fetched.
+    ///
+    /// If the trusted block precedes the registry's activation, joining in minimal mode finishes
+    /// without its execution results.
+    fn read_trusted_checksum_registry<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let (era_id, state_root_hash) = match &self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => match linear_chain.first() {
+                Some(trusted_block) => (
+                    trusted_block.header().era_id(),
+                    *trusted_block.state_root_hash(),
+                ),
+                None => panic!("Reading the checksum registry without a trusted block."),
+            },
+            other => panic!("Reading the checksum registry when in {} state.", other),
+        };
+        if era_id < self.checksum_registry_activation_era {
+            info!(
+                era = era_id.0,
+                "trusted block precedes the checksum registry. not fetching its execution results"
+            );
+            return self.finish_minimal_join(rng, effect_builder, None);
+        }
+        let query = QueryRequest::new(state_root_hash.into(), *CHECKSUM_REGISTRY_KEY, vec![]);
+        effect_builder
+            .query_global_state(query)
+            .event(|result| match result {
+                Ok(QueryResult::Success { value, .. }) => {
+                    Event::ChecksumRegistryRead(ChecksumRegistry::try_from(*value).ok())
+                }
+                Ok(_) | Err(_) => Event::ChecksumRegistryRead(None),
+            })
+    }
+
+    /// Handles the checksum registry read from the global state of the trusted block, and starts
+    /// fetching the trusted block's execution results.
+    ///
+    /// Without a registry, the execution results can't be verified, so we fall back to downloading
+    /// and executing the whole chain.
+    fn checksum_registry_read<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        maybe_registry: Option<ChecksumRegistry>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let trusted_block_hash = match &self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => match linear_chain.first() {
+                Some(trusted_block) => *trusted_block.hash(),
+                None => panic!("Checksum registry read without a trusted block."),
+            },
+            other => {
+                error!(state = %other, "checksum registry read when not syncing trusted hash");
+                return Effects::new();
+            }
+        };
+        let registry = match maybe_registry {
+            Some(registry) => registry,
+            None => {
+                warn!("checksum registry of the trusted block is missing or invalid. falling back to full synchronization");
+                return self.fall_back_to_full_sync(rng, effect_builder);
+            }
+        };
+        info!("downloading the execution results of the trusted block");
+        self.trusted_checksum_registry = Some(registry);
+        self.peers.reset(rng, self.peer_policy());
+        let peer = self.peers.random_unsafe();
+        self.metrics.reset_start_time();
+        fetch_execution_results(effect_builder, peer, trusted_block_hash)
+    }
+
+    /// Handles the execution results of the trusted block downloaded from `peer`.
+    ///
+    /// If they don't match the trusted block's checksum registry, the peer is banned and reported,
+    /// and they are fetched again from another peer.
+    fn execution_results_downloaded<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        block_hash: BlockHash,
+        execution_results: Box<BlockExecutionResults>,
+        peer: I,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let registry = match &self.trusted_checksum_registry {
+            Some(registry) => registry,
+            None => {
+                error!(%block_hash, "downloaded execution results without a checksum registry");
+                return Effects::new();
+            }
+        };
+        let valid = *execution_results.block_hash() == block_hash
+            && registry.verify_execution_results(
+                execution_results
+                    .execution_results()
+                    .iter()
+                    .map(|(deploy_hash, execution_result)| (deploy_hash, execution_result)),
+            );
+        if !valid {
+            warn!(%peer, %block_hash, "execution results don't match the checksum registry");
+            self.peers.ban(&peer);
+            let mut effects = effect_builder
+                .announce_invalid_execution_results_received(peer.clone(), block_hash)
+                .ignore();
+            effects.extend(self.handle_event(
+                effect_builder,
+                rng,
+                Event::GetExecutionResultsResult(
+                    block_hash,
+                    ExecutionResultsResult::Absent(peer, DemotionReason::InvalidData),
+                ),
+            ));
+            return effects;
+        }
+        self.peers.success(peer);
+        self.trusted_checksum_registry = None;
+        self.finish_minimal_join(rng, effect_builder, Some(*execution_results))
+    }
+
+    /// Stores the switch blocks downloaded while joining in minimal mode without executing them,
+    /// along with the trusted block and its verified execution results, if any, and starts syncing
+    /// the descendants of the trusted block.
+    fn finish_minimal_join<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        maybe_execution_results: Option<BlockExecutionResults>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let (trusted_hash, linear_chain) = match mem::replace(&mut self.state, State::None) {
+            State::SyncingTrustedHash {
+                trusted_hash,
+                linear_chain,
+                ..
+            } => (trusted_hash, linear_chain),
+            other => panic!("Finished minimal join when in {} state.", other),
+        };
+        let mut blocks = linear_chain.into_iter();
+        let trusted_block = match blocks.next() {
+            Some(trusted_block) => trusted_block,
+            None => panic!("Finished minimal join without a trusted block."),
+        };
+        let switch_blocks: Vec<Block> = blocks.collect();
+        let maybe_switch_block = switch_blocks
+            .iter()
+            .chain(std::iter::once(&trusted_block))
+            .filter(|block| block.header().is_switch_block())
+            .max_by_key(|block| block.height())
+            .cloned()
+            .map(Box::new);
+        info!(
+            trusted_height = trusted_block.height(),
+            switch_blocks = switch_blocks.len(),
+            "downloaded the switch blocks needed by consensus. skipping execution of the ancestors of the trusted block"
+        );
+        self.state =
+            State::sync_descendants(trusted_hash, trusted_block.clone(), maybe_switch_block);
+        self.peers.reset(rng, self.peer_policy());
+        let peer = self.peers.random_unsafe();
+        // Store the blocks oldest first, so that consensus can find the switch and booking blocks
+        // it needs, and only then start syncing the descendants.
+        async move {
+            for block in switch_blocks.into_iter().rev() {
+                effect_builder.put_block_to_storage(Box::new(block)).await;
+            }
+            let trusted_block_hash = *trusted_block.hash();
+            effect_builder
+                .put_block_to_storage(Box::new(trusted_block))
+                .await;
+            if let Some(execution_results) = maybe_execution_results {
+                effect_builder
+                    .put_execution_results_to_storage(
+                        trusted_block_hash,
+                        execution_results.into_map(),
+                    )
+                    .await;
+            }
+        }
+        .event(move |_| Event::Start(peer))
+    }
+
+    /// Stops joining in minimal mode and downloads the whole chain below the trusted block
+    /// instead.
+    fn fall_back_to_full_sync<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        self.minimal_join = false;
+        let trusted_block = match &mut self.state {
+            State::SyncingTrustedHash { linear_chain, .. } => {
+                // The switch blocks downloaded so far don't form a chain, so only the trusted block
+                // is kept.
+                linear_chain.truncate(1);
+                linear_chain
+                    .first()
+                    .cloned()
+                    .expect("should have downloaded at least the trusted block")
+            }
+            other => panic!(
+                "Falling back to full synchronization when in {} state.",
+                other
+            ),
+        };
+        self.fetch_next_block(effect_builder, rng, &trusted_block)
+    }
+
+    /// Requests the set of currently connected peers after a short delay, so that fetching
+    /// `pending` can be retried once we ran out of reliable peers.
+    fn refresh_peers<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        pending: PendingFetch,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        warn!(?pending, "no reliable peers left. refreshing peer set");
+        self.metrics.observe_peer_refresh();
+        async move {
+            effect_builder.set_timeout(PEER_REFRESH_DELAY).await;
+            effect_builder.network_peers::<I>().await
+        }
+        .event(move |peers| {
+            Event::PeersRefreshed(peers.into_iter().map(|(peer, _)| peer).collect(), pending)
+        })
+    }
+
+    /// Passes the block on for execution, unless the block executor's queue is full, in which case
+    /// the check is repeated after a delay.
+    fn execution_queue_checked<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        block: Box<Block>,
+        full: bool,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        if full {
+            trace!(block_hash = %block.hash(), "execution queue full, holding back block");
+            return async move {
+                effect_builder
+                    .set_timeout(EXECUTION_QUEUE_RETRY_DELAY)
+                    .await;
+                effect_builder.is_execution_queue_full().await
+            }
+            .event(move |full| Event::ExecutionQueueChecked { block, full });
+        }
+        let finalized_block: FinalizedBlock = (*block).into();
+        effect_builder.execute_block(finalized_block).ignore()
+    }
+
+    /// Retries fetching `pending` with the refreshed peer set.
+    fn peers_refreshed<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        peers: Vec<I>,
+        pending: PendingFetch,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        self.peers.refresh(peers);
+        self.peers.reset(rng, self.peer_policy());
+        match self.peers.random() {
+            None => self.refresh_peers(effect_builder, pending),
+            Some(peer) => {
+                self.metrics.reset_start_time();
+                match pending {
+                    PendingFetch::BlockByHash(block_hash) => {
+                        fetch_block_by_hash(effect_builder, peer, block_hash)
+                    }
+                    PendingFetch::TrustedBlock(block_hash) => {
+                        fetch_trusted_block(effect_builder, peer, block_hash)
+                    }
+                    PendingFetch::BlockHeader(block_hash) => {
+                        fetch_block_header(effect_builder, peer, block_hash)
+                    }
+                    PendingFetch::Deploys(block) => {
+                        fetch_block_deploys(effect_builder, peer, *block)
+                    }
+                    PendingFetch::ExecutionResults(block_hash) => {
+                        fetch_execution_results(effect_builder, peer, block_hash)
+                    }
+                }
+            }
+        }
+    }
+
     fn mark_done(&mut self) {
         let latest_block = self.latest_block().cloned().map(Box::new);
         self.state = State::Done(latest_block);
@@ -247,7 +888,7 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
                 .event(|_| Event::InitUpgradeShutdown);
         }
         // Reset peers before creating new requests.
-        self.peers.reset(rng);
+        self.peers.reset(rng, self.peer_policy());
         let block_height = block.height();
         let curr_state = mem::replace(&mut self.state, State::None);
         match curr_state {
@@ -374,6 +1015,18 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         )
     }
 
+    /// Returns the policy for choosing peers in the current state.
+    ///
+    /// Fetches of descendants of the trusted hash are time-critical, since they follow the tip of
+    /// the chain, whereas the chain below the trusted hash is downloaded in bulk.
+    fn peer_policy(&self) -> PeerSelectionPolicy {
+        let config = self.peers.config();
+        match self.state {
+            State::SyncingDescendants { .. } => config.descendants_peer_policy,
+            _ => config.trusted_hash_peer_policy,
+        }
+    }
+
     fn fetch_next_block<REv>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -384,7 +1037,7 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         I: Send + 'static,
         REv: ReactorEventT<I>,
     {
-        self.peers.reset(rng);
+        self.peers.reset(rng, self.peer_policy());
         let peer = self.peers.random_unsafe();
         match self.state {
             State::SyncingTrustedHash { .. } => {
@@ -423,6 +1076,19 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
             .event(|_| Event::Shutdown(true))
     }
 
+    /// Saves the state to storage if synchronization is in progress, so that it can be resumed
+    /// after a restart.
+    ///
+    /// Returns whether a state was saved.
+    pub(crate) fn checkpoint(&self, storage: &Storage) -> Result<bool, storage::Error> {
+        if self.state.is_none() || self.state.is_done() {
+            return Ok(false);
+        }
+        storage.write_state_store(&self.state_key, &serialize_state(&self.state))?;
+        info!(state = %self.state, "saved linear chain sync state");
+        Ok(true)
+    }
+
     pub(crate) fn latest_block(&self) -> Option<&Block> {
         match &self.state {
             State::SyncingTrustedHash { latest_block, .. } => Option::as_ref(&*latest_block),
@@ -432,6 +1098,39 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         }
     }
 
+    /// Returns a snapshot of the progress of synchronization.
+    fn progress_snapshot(&self) -> SyncSnapshot {
+        let mut snapshot = SyncSnapshot {
+            latest_block_height: self.latest_block().map(Block::height),
+            peers: self.peers.len(),
+            successful_peers: self.peers.successful_len(),
+            fetch_retries: self.peers.failures(),
+            ..Default::default()
+        };
+        match &self.state {
+            State::None => snapshot.state = "none",
+            State::SyncingTrustedHash {
+                highest_block_seen,
+                linear_chain,
+                ..
+            } => {
+                snapshot.state = "syncing_trusted_hash";
+                snapshot.highest_block_seen = *highest_block_seen;
+                snapshot.blocks_awaiting_execution = linear_chain.len();
+                // Blocks are downloaded from the trusted one down, and executed from the lowest.
+                snapshot.lowest_downloaded_block_height = linear_chain.last().map(Block::height);
+            }
+            State::SyncingDescendants {
+                highest_block_seen, ..
+            } => {
+                snapshot.state = "syncing_descendants";
+                snapshot.highest_block_seen = *highest_block_seen;
+            }
+            State::Done(_) => snapshot.state = "done",
+        }
+        snapshot
+    }
+
     fn should_upgrade(&self, era_id: EraId) -> bool {
         match self.next_upgrade_activation_point {
             None => false,
@@ -439,6 +1138,145 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
         }
     }
 
+    /// Returns the next of several trusted hashes whose block needs to be fetched before
+    /// synchronization can start, if any.
+    fn next_unresolved_trusted_hash(&self) -> Option<BlockHash> {
+        self.trusted_hashes
+            .as_ref()
+            .and_then(TrustedHashes::next_unresolved)
+    }
+
+    /// Handles the result of fetching the block of one of several trusted hashes.
+    ///
+    /// Once all of them are fetched, synchronization continues from the highest one as if it had
+    /// been the only trusted hash.
+    fn trusted_block_fetched<REv>(
+        &mut self,
+        rng: &mut NodeRng,
+        effect_builder: EffectBuilder<REv>,
+        block_hash: BlockHash,
+        fetch_result: BlockByHashResult<I>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + 'static,
+        REv: ReactorEventT<I>,
+    {
+        self.metrics.observe_get_block_by_hash();
+        let (block, stored_locally) = match fetch_result {
+            BlockByHashResult::Absent(peer, reason) => {
+                trace!(%block_hash, %peer, ?reason, "failed to download trusted block. Trying next peer");
+                self.metrics.observe_peer_demotion("trusted_block", reason);
+                self.peers.strike(&peer);
+                return match self.peers.random() {
+                    None => {
+                        error!(%block_hash, "could not download trusted block from any peer");
+                        self.refresh_peers(effect_builder, PendingFetch::TrustedBlock(block_hash))
+                    }
+                    Some(peer) => {
+                        self.metrics.reset_start_time();
+                        fetch_trusted_block(effect_builder, peer, block_hash)
+                    }
+                };
+            }
+            BlockByHashResult::FromStorage(block) => (block, true),
+            BlockByHashResult::FromPeer(block, peer) => {
+                if let Err(error) = block.verify() {
+                    warn!(%peer, %block_hash, %error, "received invalid trusted block");
+                    self.peers.ban(&peer);
+                    let mut effects = effect_builder
+                        .announce_invalid_block_received(peer.clone(), block_hash, error)
+                        .ignore();
+                    effects.extend(self.trusted_block_fetched(
+                        rng,
+                        effect_builder,
+                        block_hash,
+                        BlockByHashResult::Absent(peer, DemotionReason::InvalidData),
+                    ));
+                    return effects;
+                }
+                if block.header().hash() != block_hash || *block.hash() != block_hash {
+                    warn!(%peer, %block_hash, got = %block.hash(), "trusted block hash mismatch");
+                    self.peers.ban(&peer);
+                    return self.trusted_block_fetched(
+                        rng,
+                        effect_builder,
+                        block_hash,
+                        BlockByHashResult::Absent(peer, DemotionReason::WrongItem),
+                    );
+                }
+                self.peers.success(peer);
+                (block, false)
+            }
+        };
+        let resolved = match self.trusted_hashes.as_mut() {
+            Some(trusted_hashes) => trusted_hashes.resolve(&block, stored_locally),
+            None => {
+                error!(%block_hash, "fetched trusted block when not verifying trusted hashes");
+                return Effects::new();
+            }
+        };
+        self.peers.reset(rng, self.peer_policy());
+        match resolved {
+            Err(conflict) => self.fail_on_trusted_hash_conflict(conflict),
+            Ok(None) => {
+                let next_hash = self
+                    .next_unresolved_trusted_hash()
+                    .expect("should have unresolved trusted hashes");
+                let peer = self.peers.random_unsafe();
+                fetch_trusted_block(effect_builder, peer, next_hash)
+            }
+            Ok(Some(anchor_hash)) => {
+                info!(%anchor_hash, "trusted blocks fetched. synchronizing to the highest one");
+                let highest_block_header = match mem::replace(&mut self.state, State::None) {
+                    State::SyncingTrustedHash {
+                        highest_block_header,
+                        ..
+                    } => highest_block_header.map(|header| *header),
+                    other => panic!("Fetched trusted blocks when in {} state.", other),
+                };
+                self.state = State::sync_trusted_hash(anchor_hash, highest_block_header);
+                if anchor_hash != block_hash {
+                    // The highest trusted block was fetched earlier; fetch it again.
+                    let peer = self.peers.random_unsafe();
+                    return fetch_block_by_hash(effect_builder, peer, anchor_hash);
+                }
+                if stored_locally {
+                    self.check_trusted_hashes_in_storage(block.height());
+                    self.set_last_block_if_syncing_trusted_hash(&block);
+                    self.block_handled(rng, effect_builder, *block)
+                } else {
+                    self.block_downloaded(rng, effect_builder, &block)
+                }
+            }
+        }
+    }
+
+    /// Verifies that the trusted blocks not found among the downloaded blocks are in local
+    /// storage, given that the downloaded chain connects to the stored block at `height`.
+    fn check_trusted_hashes_in_storage(&mut self, height: u64) {
+        if let Some(Err(conflict)) = self
+            .trusted_hashes
+            .as_mut()
+            .map(|trusted_hashes| trusted_hashes.check_connected_to_storage(height))
+        {
+            self.fail_on_trusted_hash_conflict(conflict);
+        }
+    }
+
+    /// Stops the node, since the configured trusted hashes don't lie on the same chain.
+    fn fail_on_trusted_hash_conflict(&self, conflict: TrustedHashConflict) -> ! {
+        let report = self
+            .trusted_hashes
+            .as_ref()
+            .map(TrustedHashes::report)
+            .unwrap_or_default();
+        error!(%conflict, trusted_blocks = %report, "configured trusted hashes conflict");
+        panic!(
+            "configured trusted hashes conflict: {}. trusted blocks: {}",
+            conflict, report
+        )
+    }
+
     fn set_last_block_if_syncing_trusted_hash(&mut self, block: &Block) {
         if let State::SyncingTrustedHash {
             ref mut latest_block,
@@ -453,7 +1291,7 @@ impl<I: Clone + PartialEq + 'static> LinearChainSync<I> {
 
 impl<I, REv> Component<REv> for LinearChainSync<I>
 where
-    I: Display + Clone + Send + PartialEq + 'static,
+    I: Display + Clone + Send + Eq + Hash + 'static,
     REv: ReactorEventT<I>,
 {
     type Event = Event<I>;
@@ -465,6 +1303,25 @@ where
         rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
+        let effects = self.handle_sync_event(effect_builder, rng, event);
+        self.progress.publish(self.progress_snapshot());
+        effects
+    }
+}
+
+impl<I> LinearChainSync<I>
+where
+    I: Display + Clone + Send + Eq + Hash + 'static,
+{
+    fn handle_sync_event<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        event: Event<I>,
+    ) -> Effects<Event<I>>
+    where
+        REv: ReactorEventT<I>,
+    {
         match event {
             Event::Start(init_peer) => {
                 match &self.state {
@@ -478,25 +1335,60 @@ where
                         error!("should not have received `Start` event when in `Done` state.",);
                         Effects::new()
                     }
+                    State::SyncingDescendants { latest_block, .. } if self.execute_latest_block => {
+                        info!(
+                            block_height = latest_block.height(),
+                            "resume synchronization by executing latest block"
+                        );
+                        let latest_block = (**latest_block).clone();
+                        self.execute_latest_block = false;
+                        self.peers.reset(rng, self.peer_policy());
+                        self.continue_after_download(rng, effect_builder, &latest_block)
+                    }
                     State::SyncingDescendants { latest_block, .. } => {
                         let next_block_height = latest_block.height() + 1;
                         info!(?next_block_height, "start synchronization");
                         self.metrics.reset_start_time();
                         fetch_block_at_height(effect_builder, init_peer, next_block_height)
                     }
+                    State::SyncingTrustedHash { latest_block, .. } if latest_block.is_some() => {
+                        info!("resume synchronization by executing the downloaded blocks");
+                        self.peers.reset(rng, self.peer_policy());
+                        self.fetch_next_block_deploys(effect_builder)
+                    }
+                    State::SyncingTrustedHash { linear_chain, .. } if !linear_chain.is_empty() => {
+                        let oldest_block = linear_chain[linear_chain.len() - 1].clone();
+                        info!(
+                            downloaded = linear_chain.len(),
+                            "resume downloading the linear chain"
+                        );
+                        self.peers.reset(rng, self.peer_policy());
+                        self.continue_after_download(rng, effect_builder, &oldest_block)
+                    }
                     State::SyncingTrustedHash { trusted_hash, .. } => {
-                        trace!(?trusted_hash, "start synchronization");
-                        // Start synchronization.
                         self.metrics.reset_start_time();
-                        fetch_block_by_hash(effect_builder, init_peer, *trusted_hash)
+                        match self.next_unresolved_trusted_hash() {
+                            // Several trusted hashes: fetch all their blocks first.
+                            Some(next_hash) => {
+                                info!("start synchronization by fetching the trusted blocks");
+                                fetch_trusted_block(effect_builder, init_peer, next_hash)
+                            }
+                            None => {
+                                trace!(?trusted_hash, "start synchronization");
+                                // Start synchronization.
+                                fetch_block_by_hash(effect_builder, init_peer, *trusted_hash)
+                            }
+                        }
                     }
                 }
             }
             Event::GetBlockHeightResult(block_height, fetch_result) => {
                 match fetch_result {
-                    BlockByHeightResult::Absent(peer) => {
+                    BlockByHeightResult::Absent(peer, reason) => {
                         self.metrics.observe_get_block_by_height();
-                        trace!(%block_height, %peer, "failed to download block by height. Trying next peer");
+                        trace!(%block_height, %peer, ?reason, "failed to download block by height. Trying next peer");
+                        self.metrics
+                            .observe_peer_demotion("block_by_height", reason);
                         self.peers.failure(&peer);
                         match self.peers.random() {
                             None => {
@@ -525,6 +1417,22 @@ where
                     BlockByHeightResult::FromPeer(block, peer) => {
                         self.metrics.observe_get_block_by_height();
                         trace!(%block_height, %peer, "linear chain block downloaded from a peer");
+                        if let Err(error) = block.verify() {
+                            warn!(%peer, %block_height, %error, "received invalid block");
+                            self.peers.ban(&peer);
+                            let mut effects = effect_builder
+                                .announce_invalid_block_received(peer.clone(), *block.hash(), error)
+                                .ignore();
+                            effects.extend(self.handle_event(
+                                effect_builder,
+                                rng,
+                                Event::GetBlockHeightResult(
+                                    block_height,
+                                    BlockByHeightResult::Absent(peer, DemotionReason::InvalidData),
+                                ),
+                            ));
+                            return effects;
+                        }
                         if block.height() != block_height
                             || *block.header().parent_hash() != *self.latest_block().unwrap().hash()
                         {
@@ -543,7 +1451,7 @@ where
                                 rng,
                                 Event::GetBlockHeightResult(
                                     block_height,
-                                    BlockByHeightResult::Absent(peer),
+                                    BlockByHeightResult::Absent(peer, DemotionReason::WrongItem),
                                 ),
                             );
                         }
@@ -554,14 +1462,18 @@ where
             }
             Event::GetBlockHashResult(block_hash, fetch_result) => {
                 match fetch_result {
-                    BlockByHashResult::Absent(peer) => {
+                    BlockByHashResult::Absent(peer, reason) => {
                         self.metrics.observe_get_block_by_hash();
-                        trace!(%block_hash, %peer, "failed to download block by hash. Trying next peer");
-                        self.peers.failure(&peer);
+                        trace!(%block_hash, %peer, ?reason, "failed to download block by hash. Trying next peer");
+                        self.metrics.observe_peer_demotion("block_by_hash", reason);
+                        self.peers.strike(&peer);
                         match self.peers.random() {
                             None => {
                                 error!(%block_hash, "Could not download linear block from any of the peers.");
-                                panic!("Failed to download linear chain.")
+                                self.refresh_peers(
+                                    effect_builder,
+                                    PendingFetch::BlockByHash(block_hash),
+                                )
                             }
                             Some(peer) => {
                                 self.metrics.reset_start_time();
@@ -579,12 +1491,29 @@ where
                         // chain forwards and downloading the deploys.
                         // We don't want to download and execute a block we already have, so
                         // instead of calling self.block_downloaded(), we take a shortcut:
+                        self.check_trusted_hashes_in_storage(block.height());
                         self.set_last_block_if_syncing_trusted_hash(&block);
                         self.block_handled(rng, effect_builder, *block)
                     }
                     BlockByHashResult::FromPeer(block, peer) => {
                         self.metrics.observe_get_block_by_hash();
                         trace!(%block_hash, %peer, "linear chain block downloaded from a peer");
+                        if let Err(error) = block.verify() {
+                            warn!(%peer, %block_hash, %error, "received invalid block");
+                            self.peers.ban(&peer);
+                            let mut effects = effect_builder
+                                .announce_invalid_block_received(peer.clone(), block_hash, error)
+                                .ignore();
+                            effects.extend(self.handle_event(
+                                effect_builder,
+                                rng,
+                                Event::GetBlockHashResult(
+                                    block_hash,
+                                    BlockByHashResult::Absent(peer, DemotionReason::InvalidData),
+                                ),
+                            ));
+                            return effects;
+                        }
                         let header_hash = block.header().hash();
                         if header_hash != block_hash || header_hash != *block.hash() {
                             warn!(
@@ -602,7 +1531,7 @@ where
                                 rng,
                                 Event::GetBlockHashResult(
                                     block_hash,
-                                    BlockByHashResult::Absent(peer),
+                                    BlockByHashResult::Absent(peer, DemotionReason::WrongItem),
                                 ),
                             );
                         }
@@ -611,6 +1540,103 @@ where
                     }
                 }
             }
+            Event::GetBlockHeaderResult(block_hash, fetch_result) => {
+                match fetch_result {
+                    BlockHeaderResult::Absent(peer, reason) => {
+                        self.metrics.observe_get_block_header();
+                        trace!(%block_hash, %peer, ?reason, "failed to download block header. Trying next peer");
+                        self.metrics.observe_peer_demotion("block_header", reason);
+                        self.peers.strike(&peer);
+                        match self.peers.random() {
+                            None => {
+                                error!(%block_hash, "could not download block header from any peer");
+                                self.refresh_peers(
+                                    effect_builder,
+                                    PendingFetch::BlockHeader(block_hash),
+                                )
+                            }
+                            Some(peer) => {
+                                self.metrics.reset_start_time();
+                                fetch_block_header(effect_builder, peer, block_hash)
+                            }
+                        }
+                    }
+                    BlockHeaderResult::FromStorage(header) => {
+                        // We shouldn't get invalid data from the storage.
+                        // If we do, it's a bug.
+                        assert_eq!(header.hash(), block_hash, "Block header hash mismatch.");
+                        // The ancestors are stored down from here, so there is nothing to skip.
+                        info!(%block_hash, "ancestor of the trusted block found in the local storage. falling back to full synchronization");
+                        self.fall_back_to_full_sync(rng, effect_builder)
+                    }
+                    BlockHeaderResult::FromPeer(header, peer) => {
+                        self.metrics.observe_get_block_header();
+                        trace!(%block_hash, %peer, "block header downloaded from a peer");
+                        let header_hash = header.hash();
+                        if header_hash != block_hash {
+                            warn!(%peer, %block_hash, got = %header_hash, "block header hash mismatch");
+                            self.peers.ban(&peer);
+                            return self.handle_event(
+                                effect_builder,
+                                rng,
+                                Event::GetBlockHeaderResult(
+                                    block_hash,
+                                    BlockHeaderResult::Absent(peer, DemotionReason::WrongItem),
+                                ),
+                            );
+                        }
+                        self.peers.success(peer);
+                        self.header_downloaded(rng, effect_builder, &header)
+                    }
+                }
+            }
+            Event::ChecksumRegistryRead(maybe_registry) => {
+                self.checksum_registry_read(rng, effect_builder, maybe_registry)
+            }
+            Event::GetExecutionResultsResult(block_hash, fetch_result) => match fetch_result {
+                ExecutionResultsResult::Absent(peer, reason) => {
+                    trace!(%block_hash, %peer, ?reason, "failed to download execution results. Trying next peer");
+                    self.metrics
+                        .observe_peer_demotion("execution_results", reason);
+                    self.peers.strike(&peer);
+                    match self.peers.random() {
+                        None => {
+                            error!(%block_hash, "could not download execution results from any peer");
+                            self.refresh_peers(
+                                effect_builder,
+                                PendingFetch::ExecutionResults(block_hash),
+                            )
+                        }
+                        Some(peer) => {
+                            self.metrics.reset_start_time();
+                            fetch_execution_results(effect_builder, peer, block_hash)
+                        }
+                    }
+                }
+                ExecutionResultsResult::FromStorage(execution_results) => {
+                    // We shouldn't get invalid data from the storage.
+                    // If we do, it's a bug.
+                    assert_eq!(
+                        *execution_results.block_hash(),
+                        block_hash,
+                        "Execution results block hash mismatch."
+                    );
+                    // The execution results are stored already.
+                    self.trusted_checksum_registry = None;
+                    self.finish_minimal_join(rng, effect_builder, None)
+                }
+                ExecutionResultsResult::FromPeer(execution_results, peer) => self
+                    .execution_results_downloaded(
+                        rng,
+                        effect_builder,
+                        block_hash,
+                        execution_results,
+                        peer,
+                    ),
+            },
+            Event::GetTrustedBlockResult(block_hash, fetch_result) => {
+                self.trusted_block_fetched(rng, effect_builder, block_hash, fetch_result)
+            }
             Event::GetDeploysResult(fetch_result) => {
                 self.metrics.observe_get_deploys();
                 match fetch_result {
@@ -618,20 +1644,23 @@ where
                         let block_hash = block.hash();
                         trace!(%block_hash, "deploys for linear chain block found");
                         // Reset used peers so we can download next block with the full set.
-                        self.peers.reset(rng);
-                        // Execute block
-                        let finalized_block: FinalizedBlock = (*block).into();
-                        effect_builder.execute_block(finalized_block).ignore()
+                        self.peers.reset(rng, self.peer_policy());
+                        // Execute the block once the block executor can take it.
+                        effect_builder
+                            .is_execution_queue_full()
+                            .event(move |full| Event::ExecutionQueueChecked { block, full })
                     }
                     event::DeploysResult::NotFound(block, peer) => {
                         let block_hash = block.hash();
                         trace!(%block_hash, %peer, "deploy for linear chain block not found. Trying next peer");
-                        self.peers.failure(&peer);
+                        self.metrics
+                            .observe_peer_demotion("deploys", DemotionReason::Absent);
+                        self.peers.strike(&peer);
                         match self.peers.random() {
                             None => {
                                 error!(%block_hash,
                                 "could not download deploys from linear chain block.");
-                                panic!("Failed to download linear chain deploys.")
+                                self.refresh_peers(effect_builder, PendingFetch::Deploys(block))
                             }
                             Some(peer) => {
                                 self.metrics.reset_start_time();
@@ -643,7 +1672,7 @@ where
             }
             Event::StartDownloadingDeploys => {
                 // Start downloading deploys from the first block of the linear chain.
-                self.peers.reset(rng);
+                self.peers.reset(rng, self.peer_policy());
                 self.fetch_next_block_deploys(effect_builder)
             }
             Event::NewPeerConnected(peer_id) => {
@@ -684,6 +1713,15 @@ where
                 self.stop_for_upgrade = upgrade;
                 Effects::new()
             }
+            Event::PeersRefreshed(peers, pending) => {
+                self.peers_refreshed(rng, effect_builder, peers, pending)
+            }
+            Event::TrustedStateChecked(present) => {
+                self.trusted_state_checked(rng, effect_builder, present)
+            }
+            Event::ExecutionQueueChecked { block, full } => {
+                self.execution_queue_checked(effect_builder, block, full)
+            }
         }
     }
 }
@@ -699,8 +1737,9 @@ where
     let block_timestamp = block.header().timestamp();
     effect_builder
         .validate_block(peer.clone(), block, block_timestamp)
-        .event(move |(found, block)| {
-            if found {
+        .event(move |result| {
+            let block = result.block;
+            if result.outcome.is_valid() {
                 Event::GetDeploysResult(DeploysResult::Found(Box::new(block)))
             } else {
                 Event::GetDeploysResult(DeploysResult::NotFound(Box::new(block), peer))
@@ -717,7 +1756,7 @@ where
     REv: ReactorEventT<I>,
 {
     let cloned = peer.clone();
-    effect_builder.fetch_block(block_hash, peer).map_or_else(
+    effect_builder.fetch_block(block_hash, peer).result(
         move |fetch_result| match fetch_result {
             FetchResult::FromStorage(block) => {
                 Event::GetBlockHashResult(block_hash, BlockByHashResult::FromStorage(block))
@@ -726,10 +1765,100 @@ where
                 Event::GetBlockHashResult(block_hash, BlockByHashResult::FromPeer(block, peer))
             }
         },
-        move || Event::GetBlockHashResult(block_hash, BlockByHashResult::Absent(cloned)),
+        move |failure| {
+            Event::GetBlockHashResult(
+                block_hash,
+                BlockByHashResult::Absent(cloned, failure.into()),
+            )
+        },
     )
 }
 
+fn fetch_trusted_block<I: Clone + Send + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    block_hash: BlockHash,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    let cloned = peer.clone();
+    effect_builder.fetch_block(block_hash, peer).result(
+        move |fetch_result| match fetch_result {
+            FetchResult::FromStorage(block) => {
+                Event::GetTrustedBlockResult(block_hash, BlockByHashResult::FromStorage(block))
+            }
+            FetchResult::FromPeer(block, peer) => {
+                Event::GetTrustedBlockResult(block_hash, BlockByHashResult::FromPeer(block, peer))
+            }
+        },
+        move |failure| {
+            Event::GetTrustedBlockResult(
+                block_hash,
+                BlockByHashResult::Absent(cloned, failure.into()),
+            )
+        },
+    )
+}
+
+fn fetch_block_header<I: Clone + Send + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    block_hash: BlockHash,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    let cloned = peer.clone();
+    effect_builder.fetch_block_header(block_hash, peer).result(
+        move |fetch_result| match fetch_result {
+            FetchResult::FromStorage(header) => {
+                Event::GetBlockHeaderResult(block_hash, BlockHeaderResult::FromStorage(header))
+            }
+            FetchResult::FromPeer(header, peer) => {
+                Event::GetBlockHeaderResult(block_hash, BlockHeaderResult::FromPeer(header, peer))
+            }
+        },
+        move |failure| {
+            Event::GetBlockHeaderResult(
+                block_hash,
+                BlockHeaderResult::Absent(cloned, failure.into()),
+            )
+        },
+    )
+}
+
+fn fetch_execution_results<I: Clone + Send + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    block_hash: BlockHash,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    let cloned = peer.clone();
+    effect_builder
+        .fetch_block_execution_results(block_hash, peer)
+        .result(
+            move |fetch_result| match fetch_result {
+                FetchResult::FromStorage(execution_results) => Event::GetExecutionResultsResult(
+                    block_hash,
+                    ExecutionResultsResult::FromStorage(execution_results),
+                ),
+                FetchResult::FromPeer(execution_results, peer) => Event::GetExecutionResultsResult(
+                    block_hash,
+                    ExecutionResultsResult::FromPeer(execution_results, peer),
+                ),
+            },
+            move |failure| {
+                Event::GetExecutionResultsResult(
+                    block_hash,
+                    ExecutionResultsResult::Absent(cloned, failure.into()),
+                )
+            },
+        )
+}
+
 fn fetch_block_at_height<I: Send + Clone + 'static, REv>(
     effect_builder: EffectBuilder<REv>,
     peer: I,
@@ -741,7 +1870,7 @@ where
     let cloned = peer.clone();
     effect_builder
         .fetch_block_by_height(block_height, peer.clone())
-        .map_or_else(
+        .result(
             move |fetch_result| match fetch_result {
                 FetchResult::FromPeer(result, _) => match *result {
                     BlockByHeight::Absent(ret_height) => {
@@ -749,7 +1878,10 @@ where
                             "Fetcher returned result for invalid height. Expected {}, got {}",
                             block_height, ret_height
                         );
-                        Event::GetBlockHeightResult(block_height, BlockByHeightResult::Absent(peer))
+                        Event::GetBlockHeightResult(
+                            block_height,
+                            BlockByHeightResult::Absent(peer, DemotionReason::Absent),
+                        )
                     }
                     BlockByHeight::Block(block) => Event::GetBlockHeightResult(
                         block_height,
@@ -768,10 +1900,33 @@ where
                     ),
                 },
             },
-            move || Event::GetBlockHeightResult(block_height, BlockByHeightResult::Absent(cloned)),
+            move |failure| {
+                Event::GetBlockHeightResult(
+                    block_height,
+                    BlockByHeightResult::Absent(cloned, failure.into()),
+                )
+            },
         )
 }
 
+/// Returns the number of eras before the era of the trusted block whose switch blocks consensus
+/// needs after joining in minimal mode.
+///
+/// Consensus is initialized with the last `3 * bonded_eras` eras before the current one, which is
+/// at most the era of the trusted block.  Each era is initialized from its key block, the switch
+/// block of the era before, so the oldest key block is `3 * bonded_eras + 1` eras before the
+/// trusted block's.  Once it has joined, consensus also reads the booking block of each new era:
+/// the switch block `auction_delay` eras before the new era's predecessor.  The first new era
+/// follows the trusted block's, so its booking block is `auction_delay` eras before that.
+fn minimal_join_eras(chainspec: &Chainspec) -> u64 {
+    let auction_delay = chainspec.core_config.auction_delay;
+    let bonded_eras = chainspec
+        .core_config
+        .unbonding_delay
+        .saturating_sub(auction_delay);
+    cmp::max(3 * bonded_eras + 1, auction_delay)
+}
+
 /// Returns key in the database, under which the LinearChainSync's state is stored.
 fn create_state_key(chainspec: &Chainspec) -> Vec<u8> {
     format!(
@@ -792,6 +1947,13 @@ fn deserialize_state(serialized_state: &[u8]) -> Option<State> {
     })
 }
 
+/// Serializes `LinearChainSync::State` in the format read by `deserialize_state`.
+/// Panics on serialization errors.
+fn serialize_state(state: &State) -> Vec<u8> {
+    bincode::serialize(&Some(state))
+        .unwrap_or_else(|error| panic!("could not serialize state, error {:?}", error))
+}
+
 /// Reads the `LinearChainSync's` state from storage, if any.
 /// Panics on deserialization errors.
 pub(crate) fn read_init_state(
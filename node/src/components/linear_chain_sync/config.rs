@@ -0,0 +1,83 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::{types::TimeDiff, utils::ConfigSection};
+
+const DEFAULT_SUCCESSFUL_PEER_WEIGHT: u32 = 1;
+const DEFAULT_LONG_LIVED_PEER_WEIGHT: u32 = 1;
+const DEFAULT_OTHER_PEER_WEIGHT: u32 = 1;
+const DEFAULT_LONG_LIVED_PEER_THRESHOLD: &str = "10min";
+const DEFAULT_RECENT_SUCCESS_WINDOW: &str = "1min";
+
+/// How the peers to fetch data from are chosen.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerSelectionPolicy {
+    /// Peers are drawn from groups picked at random in proportion to the `*_peer_weight` options.
+    Stratified,
+    /// Peers which provided data successfully within `recent_success_window` are asked first,
+    /// those with the lowest measured response time first.  All other peers follow, chosen as by
+    /// `Stratified`.
+    LowLatency,
+}
+
+/// Configuration options for choosing the peers blocks and deploys are fetched from while
+/// synchronizing the linear chain.
+///
+/// Known peers are divided into three groups: those which recently provided data successfully,
+/// those known for at least `long_lived_peer_threshold`, and all others.  Each time a peer is
+/// chosen, the group is picked at random in proportion to the groups' weights, so that no single
+/// group, and hence no adversary dominating it, serves all requests for a block.
+///
+/// Near the tip, when following the descendants of the trusted hash, fetches are time-critical, so
+/// fast peers can be preferred there, while the download of the chain back to the trusted hash
+/// can be spread over all peers.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Relative weight of peers which recently provided data successfully.
+    pub successful_peer_weight: u32,
+    /// Relative weight of peers known for at least `long_lived_peer_threshold`.
+    pub long_lived_peer_weight: u32,
+    /// Relative weight of all other peers.
+    pub other_peer_weight: u32,
+    /// How long a peer must have been known for to be considered long-lived.
+    pub long_lived_peer_threshold: TimeDiff,
+    /// The policy for choosing peers while downloading the chain back to the trusted hash.
+    pub trusted_hash_peer_policy: PeerSelectionPolicy,
+    /// The policy for choosing peers while following the descendants of the trusted hash.
+    pub descendants_peer_policy: PeerSelectionPolicy,
+    /// How long after its last successful response a peer is preferred by the `low_latency`
+    /// policy.
+    pub recent_success_window: TimeDiff,
+}
+
+impl Config {
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        let total_weight = u64::from(self.successful_peer_weight)
+            + u64::from(self.long_lived_peer_weight)
+            + u64::from(self.other_peer_weight);
+        section.check(
+            "*_peer_weight",
+            total_weight > 0,
+            "all weights 0",
+            "at least one non-zero weight",
+        );
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            successful_peer_weight: DEFAULT_SUCCESSFUL_PEER_WEIGHT,
+            long_lived_peer_weight: DEFAULT_LONG_LIVED_PEER_WEIGHT,
+            other_peer_weight: DEFAULT_OTHER_PEER_WEIGHT,
+            long_lived_peer_threshold: DEFAULT_LONG_LIVED_PEER_THRESHOLD.parse().unwrap(),
+            trusted_hash_peer_policy: PeerSelectionPolicy::Stratified,
+            descendants_peer_policy: PeerSelectionPolicy::LowLatency,
+            recent_success_window: DEFAULT_RECENT_SUCCESS_WINDOW.parse().unwrap(),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use crate::types::{ActivationPoint, Block, BlockHash};
+use crate::{
+    components::fetcher::FetchFailure,
+    types::{
+        ActivationPoint, Block, BlockExecutionResults, BlockHash, BlockHeader, ChecksumRegistry,
+    },
+};
 
 use std::fmt::{Debug, Display};
 
@@ -6,14 +11,86 @@ use std::fmt::{Debug, Display};
 pub enum Event<I> {
     Start(I),
     GetBlockHashResult(BlockHash, BlockByHashResult<I>),
+    /// The result of fetching the block of one of several trusted hashes.
+    GetTrustedBlockResult(BlockHash, BlockByHashResult<I>),
     GetBlockHeightResult(u64, BlockByHeightResult<I>),
+    /// The result of fetching the header of an ancestor of the trusted block, when joining in
+    /// minimal mode.
+    GetBlockHeaderResult(BlockHash, BlockHeaderResult<I>),
     GetDeploysResult(DeploysResult<I>),
+    /// The checksum registry in the global state of the trusted block, or `None` if it couldn't be
+    /// read.
+    ChecksumRegistryRead(Option<ChecksumRegistry>),
+    /// The result of fetching the execution results of the trusted block, when joining in minimal
+    /// mode.
+    GetExecutionResultsResult(BlockHash, ExecutionResultsResult<I>),
     StartDownloadingDeploys,
     NewPeerConnected(I),
     BlockHandled(Box<Block>),
     GotUpgradeActivationPoint(ActivationPoint),
     InitUpgradeShutdown,
     Shutdown(bool),
+    /// The result of checking whether the global state of the trusted block is fully present in
+    /// the local trie store.
+    TrustedStateChecked(bool),
+    /// The currently connected peers, requested after we ran out of reliable peers to fetch the
+    /// pending item from.
+    PeersRefreshed(Vec<I>, PendingFetch),
+    /// The result of checking whether the block executor's queue is full, before passing it the
+    /// block whose deploys have been downloaded.
+    ExecutionQueueChecked {
+        block: Box<Block>,
+        full: bool,
+    },
+}
+
+/// An item we failed to fetch from any of our peers, to be retried once the peer set is refreshed.
+#[derive(Debug)]
+pub enum PendingFetch {
+    /// A block by its hash.
+    BlockByHash(BlockHash),
+    /// The block of one of several trusted hashes.
+    TrustedBlock(BlockHash),
+    /// A block header by its block's hash.
+    BlockHeader(BlockHash),
+    /// The deploys of a block.
+    Deploys(Box<Block>),
+    /// The execution results of a block by its hash.
+    ExecutionResults(BlockHash),
+}
+
+/// The reason a peer failed to provide an item, for which it gets demoted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DemotionReason {
+    /// The peer didn't respond in time.
+    Timeout,
+    /// The peer doesn't have the item.
+    Absent,
+    /// The peer sent an item which failed validation.
+    InvalidData,
+    /// The peer sent a valid item other than the one requested.
+    WrongItem,
+}
+
+impl DemotionReason {
+    /// Returns the label of the reason used in metrics.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            DemotionReason::Timeout => "timeout",
+            DemotionReason::Absent => "absent",
+            DemotionReason::InvalidData => "invalid_data",
+            DemotionReason::WrongItem => "wrong_item",
+        }
+    }
+}
+
+impl From<FetchFailure> for DemotionReason {
+    fn from(failure: FetchFailure) -> Self {
+        match failure {
+            FetchFailure::Absent => DemotionReason::Absent,
+            FetchFailure::TimedOut => DemotionReason::Timeout,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -24,14 +101,28 @@ pub enum DeploysResult<I> {
 
 #[derive(Debug)]
 pub enum BlockByHashResult<I> {
-    Absent(I),
+    Absent(I, DemotionReason),
     FromStorage(Box<Block>),
     FromPeer(Box<Block>, I),
 }
 
+#[derive(Debug)]
+pub enum BlockHeaderResult<I> {
+    Absent(I, DemotionReason),
+    FromStorage(Box<BlockHeader>),
+    FromPeer(Box<BlockHeader>, I),
+}
+
+#[derive(Debug)]
+pub enum ExecutionResultsResult<I> {
+    Absent(I, DemotionReason),
+    FromStorage(Box<BlockExecutionResults>),
+    FromPeer(Box<BlockExecutionResults>, I),
+}
+
 #[derive(Debug)]
 pub enum BlockByHeightResult<I> {
-    Absent(I),
+    Absent(I, DemotionReason),
     FromStorage(Box<Block>),
     FromPeer(Box<Block>, I),
 }
@@ -46,9 +137,20 @@ where
             Event::GetBlockHashResult(block_hash, r) => {
                 write!(f, "Get block result for {}: {:?}", block_hash, r)
             }
+            Event::GetTrustedBlockResult(block_hash, r) => {
+                write!(f, "Get trusted block result for {}: {:?}", block_hash, r)
+            }
             Event::GetDeploysResult(result) => {
                 write!(f, "Get deploys for block result {:?}", result)
             }
+            Event::ChecksumRegistryRead(maybe_registry) => write!(
+                f,
+                "checksum registry of the trusted block read: {}",
+                maybe_registry.is_some()
+            ),
+            Event::GetExecutionResultsResult(block_hash, r) => {
+                write!(f, "Get execution results for {}: {:?}", block_hash, r)
+            }
             Event::StartDownloadingDeploys => write!(f, "Start downloading deploys event."),
             Event::NewPeerConnected(peer_id) => write!(f, "A new peer connected: {}", peer_id),
             Event::BlockHandled(block) => {
@@ -63,6 +165,9 @@ where
             Event::GetBlockHeightResult(height, res) => {
                 write!(f, "Get block result for height {}: {:?}", height, res)
             }
+            Event::GetBlockHeaderResult(block_hash, r) => {
+                write!(f, "Get block header result for {}: {:?}", block_hash, r)
+            }
             Event::GotUpgradeActivationPoint(activation_point) => {
                 write!(f, "new upgrade activation point: {:?}", activation_point)
             }
@@ -72,6 +177,23 @@ where
                 "linear chain sync is ready for shutdown. upgrade: {}",
                 upgrade
             ),
+            Event::PeersRefreshed(peers, pending) => write!(
+                f,
+                "refreshed peers ({} connected), retrying {:?}",
+                peers.len(),
+                pending
+            ),
+            Event::TrustedStateChecked(present) => write!(
+                f,
+                "global state of the trusted block present locally: {}",
+                present
+            ),
+            Event::ExecutionQueueChecked { block, full } => write!(
+                f,
+                "execution queue full: {}, for block {}",
+                full,
+                block.hash()
+            ),
         }
     }
 }
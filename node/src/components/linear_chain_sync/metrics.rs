@@ -1,12 +1,17 @@
 use std::time::Instant;
 
-use prometheus::{Histogram, HistogramOpts, Registry};
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry};
+
+use super::event::DemotionReason;
 
 #[derive(Debug)]
 pub struct LinearChainSyncMetrics {
     get_block_by_hash: Histogram,
     get_block_by_height: Histogram,
+    get_block_header: Histogram,
     get_deploys: Histogram,
+    peer_refreshes: IntCounter,
+    peer_demotions: IntCounterVec,
     request_start: Instant,
 }
 
@@ -14,8 +19,16 @@ const GET_BLOCK_BY_HASH: &str = "linear_chain_sync_get_block_by_hash";
 const GET_BLOCK_BY_HASH_HELP: &str = "histogram of linear_chain_sync get_block_by_hash request";
 const GET_BLOCK_BY_HEIGHT: &str = "linear_chain_sync_get_block_by_height";
 const GET_BLOCK_BY_HEIGHT_HELP: &str = "histogram of linear_chain_sync get_block_by_height request";
+const GET_BLOCK_HEADER: &str = "linear_chain_sync_get_block_header";
+const GET_BLOCK_HEADER_HELP: &str = "histogram of linear_chain_sync get_block_header request";
 const GET_DEPLOYS: &str = "linear_chain_sync_get_deploys";
 const GET_DEPLOYS_HELP: &str = "histogram of linear_chain_sync get_deploys request";
+const PEER_REFRESHES: &str = "linear_chain_sync_peer_refreshes";
+const PEER_REFRESHES_HELP: &str =
+    "number of times linear_chain_sync ran out of reliable peers and refreshed its peer set";
+const PEER_DEMOTIONS: &str = "linear_chain_sync_peer_demotions";
+const PEER_DEMOTIONS_HELP: &str =
+    "number of times linear_chain_sync demoted a peer, by fetched item and reason";
 
 /// Value of upper bound of histogram.
 const EXPONENTIAL_BUCKET_START: f64 = 0.01;
@@ -43,6 +56,13 @@ fn register_histogram_metric(
 
 impl LinearChainSyncMetrics {
     pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let peer_refreshes = IntCounter::new(PEER_REFRESHES, PEER_REFRESHES_HELP)?;
+        registry.register(Box::new(peer_refreshes.clone()))?;
+        let peer_demotions = IntCounterVec::new(
+            Opts::new(PEER_DEMOTIONS, PEER_DEMOTIONS_HELP),
+            &["item", "reason"],
+        )?;
+        registry.register(Box::new(peer_demotions.clone()))?;
         Ok(LinearChainSyncMetrics {
             get_block_by_hash: register_histogram_metric(
                 registry,
@@ -54,7 +74,14 @@ impl LinearChainSyncMetrics {
                 GET_BLOCK_BY_HEIGHT,
                 GET_BLOCK_BY_HEIGHT_HELP,
             )?,
+            get_block_header: register_histogram_metric(
+                registry,
+                GET_BLOCK_HEADER,
+                GET_BLOCK_HEADER_HELP,
+            )?,
             get_deploys: register_histogram_metric(registry, GET_DEPLOYS, GET_DEPLOYS_HELP)?,
+            peer_refreshes,
+            peer_demotions,
             request_start: Instant::now(),
         })
     }
@@ -73,8 +100,24 @@ impl LinearChainSyncMetrics {
             .observe(self.request_start.elapsed().as_secs_f64());
     }
 
+    pub fn observe_get_block_header(&mut self) {
+        self.get_block_header
+            .observe(self.request_start.elapsed().as_secs_f64());
+    }
+
     pub fn observe_get_deploys(&mut self) {
         self.get_deploys
             .observe(self.request_start.elapsed().as_secs_f64());
     }
+
+    pub fn observe_peer_refresh(&self) {
+        self.peer_refreshes.inc();
+    }
+
+    /// Counts a peer demotion for failing to provide `item` for the given reason.
+    pub fn observe_peer_demotion(&self, item: &str, reason: DemotionReason) {
+        self.peer_demotions
+            .with_label_values(&[item, reason.label()])
+            .inc();
+    }
 }
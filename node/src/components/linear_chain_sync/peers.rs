@@ -1,49 +1,184 @@
-use std::collections::VecDeque;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
 
 use datasize::DataSize;
 use rand::{seq::SliceRandom, Rng};
 
+use super::{config::PeerSelectionPolicy, Config};
+use crate::types::Timestamp;
+
+/// The number of strikes after which a peer is considered unreliable and is no longer returned by
+/// `random`, until its strikes decay again.
+const MAX_STRIKES: u8 = 3;
+
+/// The weight of the previous estimate when averaging a peer's response time with a new sample,
+/// out of `LATENCY_WEIGHT_TOTAL`.
+const LATENCY_PREVIOUS_WEIGHT: u64 = 3;
+const LATENCY_WEIGHT_TOTAL: u64 = 4;
+
+/// The groups known peers are divided into when scheduling which peers to ask for data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stratum {
+    /// Peers which recently provided data successfully.
+    Successful = 0,
+    /// Peers known for at least the configured threshold.
+    LongLived = 1,
+    /// All other peers.
+    Other = 2,
+}
+
 #[derive(DataSize, Debug)]
 pub struct PeersState<I> {
+    config: Config,
     // Set of peers that we can request blocks from.
     peers: Vec<I>,
-    // Peers we have not yet requested current block from.
-    // NOTE: Maybe use a bitmask to decide which peers were tried?
+    // The time at which each of `peers` was first added.
+    first_seen: HashMap<I, Timestamp>,
+    // Peers we have not yet requested current block from, in reverse order of when they will be
+    // asked.
     peers_to_try: Vec<I>,
     // Peers we successfuly downloaded data from previously.
     // Have higher chance of having the next data.
     succ_peers: VecDeque<I>,
-    succ_attempts: u8,
-    succ_attempts_max: u8,
+    // Number of times a peer failed to provide data it should have had, since it last succeeded.
+    strikes: HashMap<I, u8>,
+    // Peers which sent us invalid data. They are never added back to `peers`.
+    banned: HashSet<I>,
+    // Number of times a peer failed to provide data or was banned, so that it had to be fetched
+    // from another one.
+    failures: u64,
+    // The time each peer was last returned by `random`, until it responds.
+    asked_at: HashMap<I, Timestamp>,
+    // The moving average of each peer's response time, in milliseconds.
+    latencies: HashMap<I, u64>,
+    // The time of each peer's last successful response.
+    last_success: HashMap<I, Timestamp>,
 }
 
-impl<I: Clone + PartialEq + 'static> PeersState<I> {
-    pub fn new() -> Self {
+impl<I: Clone + Eq + Hash + 'static> PeersState<I> {
+    pub fn new(config: Config) -> Self {
         PeersState {
+            config,
             peers: Default::default(),
+            first_seen: Default::default(),
             peers_to_try: Default::default(),
             succ_peers: Default::default(),
-            succ_attempts: 0,
-            succ_attempts_max: 5,
+            strikes: Default::default(),
+            banned: Default::default(),
+            failures: 0,
+            asked_at: Default::default(),
+            latencies: Default::default(),
+            last_success: Default::default(),
         }
     }
 
-    /// Resets `peers_to_try` back to all `peers` we know of.
-    pub(crate) fn reset<R: Rng + ?Sized>(&mut self, rng: &mut R) {
-        self.peers_to_try = self.peers.clone();
-        self.peers_to_try.as_mut_slice().shuffle(rng);
+    /// Returns the configuration.
+    pub(crate) fn config(&self) -> &Config {
+        &self.config
     }
 
-    /// Returns a random peer.
-    pub(crate) fn random(&mut self) -> Option<I> {
-        if self.succ_attempts < self.succ_attempts_max {
-            self.next_succ().or_else(|| self.peers_to_try.pop())
+    /// Resets `peers_to_try` back to all `peers` we know of, ordered according to `policy`.
+    pub(crate) fn reset<R: Rng + ?Sized>(&mut self, rng: &mut R, policy: PeerSelectionPolicy) {
+        self.reset_at(rng, policy, Timestamp::now())
+    }
+
+    /// Resets `peers_to_try` back to all `peers` we know of, ordered according to `policy` as of
+    /// `now`.
+    fn reset_at<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        policy: PeerSelectionPolicy,
+        now: Timestamp,
+    ) {
+        let mut schedule = match policy {
+            PeerSelectionPolicy::Stratified => self.stratified_schedule(rng, &self.peers, now),
+            PeerSelectionPolicy::LowLatency => self.low_latency_schedule(rng, now),
+        };
+        // `next_to_try` pops from the back.
+        schedule.reverse();
+        self.peers_to_try = schedule;
+    }
+
+    /// Returns the recently successful peers, fastest first, followed by all others in stratified
+    /// order.
+    ///
+    /// Peers without a measured response time come after those with one.  Peers with equal
+    /// response times are shuffled.
+    fn low_latency_schedule<R: Rng + ?Sized>(&self, rng: &mut R, now: Timestamp) -> Vec<I> {
+        let window = self.config.recent_success_window.millis();
+        let (mut recent, others): (Vec<I>, Vec<I>) = self.peers.iter().cloned().partition(|peer| {
+            self.last_success.get(peer).map_or(false, |last_success| {
+                now.saturating_diff(*last_success).millis() <= window
+            })
+        });
+        recent.as_mut_slice().shuffle(rng);
+        recent.sort_by_key(|peer| self.latencies.get(peer).copied().unwrap_or(u64::MAX));
+        recent.extend(self.stratified_schedule(rng, &others, now));
+        recent
+    }
+
+    /// Returns `peers` in stratified order.
+    ///
+    /// Rather than shuffling all peers uniformly, each position in the schedule is filled by first
+    /// picking one of the strata at random in proportion to its configured weight, then a random
+    /// peer from it.  A stratum with zero weight is only drawn from once all others are exhausted.
+    fn stratified_schedule<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        peers: &[I],
+        now: Timestamp,
+    ) -> Vec<I> {
+        let mut strata: [(u32, Vec<I>); 3] = [
+            (self.config.successful_peer_weight, Vec::new()),
+            (self.config.long_lived_peer_weight, Vec::new()),
+            (self.config.other_peer_weight, Vec::new()),
+        ];
+        for peer in peers {
+            strata[self.stratum(peer, now) as usize]
+                .1
+                .push(peer.clone());
+        }
+        for (_, peers) in strata.iter_mut() {
+            peers.as_mut_slice().shuffle(rng);
+        }
+
+        let mut schedule = Vec::with_capacity(peers.len());
+        while let Some(index) = pick_stratum(&strata, rng) {
+            if let Some(peer) = strata[index].1.pop() {
+                schedule.push(peer);
+            }
+        }
+        schedule
+    }
+
+    /// Returns the stratum `peer` belongs to as of `now`.
+    fn stratum(&self, peer: &I, now: Timestamp) -> Stratum {
+        if self.succ_peers.contains(peer) {
+            return Stratum::Successful;
+        }
+        let is_long_lived = self.first_seen.get(peer).map_or(false, |first_seen| {
+            now.saturating_diff(*first_seen).millis()
+                >= self.config.long_lived_peer_threshold.millis()
+        });
+        if is_long_lived {
+            Stratum::LongLived
         } else {
-            self.succ_attempts = 0;
-            self.peers_to_try.pop().or_else(|| self.next_succ())
+            Stratum::Other
         }
     }
 
+    /// Returns a random peer.
+    ///
+    /// Follows the schedule set up by the last `reset`, then falls back to peers we downloaded
+    /// data from successfully.
+    pub(crate) fn random(&mut self) -> Option<I> {
+        let peer = self.next_to_try().or_else(|| self.next_succ())?;
+        self.asked_at.insert(peer.clone(), Timestamp::now());
+        Some(peer)
+    }
+
     /// Unsafe version of `random_peer`.
     /// Panics if no peer is available for querying.
     pub(crate) fn random_unsafe(&mut self) -> I {
@@ -54,7 +189,15 @@ impl<I: Clone + PartialEq + 'static> PeersState<I> {
     /// Remove it from the set of nodes we request data from.
     pub(crate) fn ban(&mut self, peer: &I) {
         self.peers.retain(|p| p != peer);
+        self.peers_to_try.retain(|p| p != peer);
         self.succ_peers.retain(|p| p != peer);
+        self.strikes.remove(peer);
+        self.first_seen.remove(peer);
+        self.asked_at.remove(peer);
+        self.latencies.remove(peer);
+        self.last_success.remove(peer);
+        self.banned.insert(peer.clone());
+        self.failures += 1;
     }
 
     /// Returns whether known peer set is empty.
@@ -62,11 +205,46 @@ impl<I: Clone + PartialEq + 'static> PeersState<I> {
         self.peers.is_empty()
     }
 
+    /// Returns the number of known peers.
+    pub(crate) fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns the number of peers we downloaded data from successfully.
+    pub(crate) fn successful_len(&self) -> usize {
+        self.succ_peers.len()
+    }
+
+    /// Returns the number of times data had to be fetched from another peer after a failure.
+    pub(crate) fn failures(&self) -> u64 {
+        self.failures
+    }
+
     /// Adds a new peer.
     pub(crate) fn push(&mut self, peer: I) {
+        self.first_seen
+            .entry(peer.clone())
+            .or_insert_with(Timestamp::now);
         self.peers.push(peer)
     }
 
+    /// Adds any of `peers` we don't know about yet, unless they were banned, and decays the strikes
+    /// of all known peers, giving unreliable peers another chance.
+    pub(crate) fn refresh<T: IntoIterator<Item = I>>(&mut self, peers: T) {
+        for peer in peers {
+            if !self.banned.contains(&peer) && !self.peers.contains(&peer) {
+                self.first_seen
+                    .entry(peer.clone())
+                    .or_insert_with(Timestamp::now);
+                self.peers.push(peer);
+            }
+        }
+        self.strikes.retain(|_, strikes| {
+            *strikes /= 2;
+            *strikes > 0
+        });
+    }
+
     /// Returns the next peer, if any, that we downloaded data the previous time.
     /// Keeps the peer in the set of `succ_peers`.
     fn next_succ(&mut self) -> Option<I> {
@@ -75,14 +253,233 @@ impl<I: Clone + PartialEq + 'static> PeersState<I> {
         Some(peer)
     }
 
+    /// Returns the next peer we haven't tried yet, skipping unreliable ones.
+    fn next_to_try(&mut self) -> Option<I> {
+        while let Some(peer) = self.peers_to_try.pop() {
+            if !self.is_unreliable(&peer) {
+                return Some(peer);
+            }
+        }
+        None
+    }
+
+    /// Returns whether the peer has accumulated too many strikes to be asked for data.
+    fn is_unreliable(&self, peer: &I) -> bool {
+        self.strikes
+            .get(peer)
+            .map_or(false, |strikes| *strikes >= MAX_STRIKES)
+    }
+
     /// Peer didn't respond or didn't have the data we asked for.
     pub(crate) fn failure(&mut self, peer: &I) {
         self.succ_peers.retain(|id| id != peer);
+        self.asked_at.remove(peer);
+        self.failures += 1;
+    }
+
+    /// Peer didn't have data that it should have had, e.g. an ancestor of a block it gave us.
+    /// Counts towards the peer being considered unreliable.
+    pub(crate) fn strike(&mut self, peer: &I) {
+        self.failure(peer);
+        let strikes = self.strikes.entry(peer.clone()).or_insert(0);
+        *strikes = strikes.saturating_add(1);
     }
 
     /// Peer had the data we asked for.
     pub(crate) fn success(&mut self, peer: I) {
-        self.succ_attempts += 1;
-        self.succ_peers.push_back(peer);
+        self.success_at(peer, Timestamp::now())
+    }
+
+    /// Peer had the data we asked for, responding at `now`.
+    ///
+    /// The time since it was asked is averaged into its response time.
+    fn success_at(&mut self, peer: I, now: Timestamp) {
+        self.strikes.remove(&peer);
+        if let Some(asked_at) = self.asked_at.remove(&peer) {
+            let sample = now.saturating_diff(asked_at).millis();
+            let latency = match self.latencies.get(&peer) {
+                Some(previous) => {
+                    previous
+                        .saturating_mul(LATENCY_PREVIOUS_WEIGHT)
+                        .saturating_add(sample)
+                        / LATENCY_WEIGHT_TOTAL
+                }
+                None => sample,
+            };
+            self.latencies.insert(peer.clone(), latency);
+        }
+        self.last_success.insert(peer.clone(), now);
+        if !self.succ_peers.contains(&peer) {
+            self.succ_peers.push_back(peer);
+        }
+    }
+}
+
+/// Picks the index of a non-empty stratum at random, in proportion to the strata's weights.
+///
+/// If all non-empty strata have zero weight, the first of them is picked.  Returns `None` if all
+/// strata are empty.
+fn pick_stratum<I, R: Rng + ?Sized>(strata: &[(u32, Vec<I>)], rng: &mut R) -> Option<usize> {
+    let total_weight: u64 = strata
+        .iter()
+        .filter(|(_, peers)| !peers.is_empty())
+        .map(|(weight, _)| u64::from(*weight))
+        .sum();
+    if total_weight == 0 {
+        return strata.iter().position(|(_, peers)| !peers.is_empty());
+    }
+    let mut pick = rng.gen_range(0, total_weight);
+    strata.iter().position(|(weight, peers)| {
+        if peers.is_empty() {
+            return false;
+        }
+        let weight = u64::from(*weight);
+        if pick < weight {
+            true
+        } else {
+            pick -= weight;
+            false
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{testing::TestRng, types::TimeDiff};
+
+    fn config(successful: u32, long_lived: u32, other: u32, threshold: TimeDiff) -> Config {
+        Config {
+            successful_peer_weight: successful,
+            long_lived_peer_weight: long_lived,
+            other_peer_weight: other,
+            long_lived_peer_threshold: threshold,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn should_skip_unreliable_peers() {
+        let mut rng = TestRng::new();
+        let mut peers = PeersState::new(Config::default());
+        peers.push(1);
+        peers.push(2);
+
+        for _ in 0..MAX_STRIKES {
+            peers.strike(&1);
+        }
+        peers.reset(&mut rng, PeerSelectionPolicy::Stratified);
+        assert_eq!(peers.random(), Some(2));
+        assert_eq!(peers.random(), None);
+    }
+
+    #[test]
+    fn should_decay_strikes_and_add_new_peers_on_refresh() {
+        let mut rng = TestRng::new();
+        let mut peers = PeersState::new(Config::default());
+        peers.push(1);
+        peers.push(2);
+        for _ in 0..MAX_STRIKES {
+            peers.strike(&1);
+        }
+        peers.ban(&2);
+
+        peers.refresh(vec![1, 2, 3]);
+        peers.reset(&mut rng, PeerSelectionPolicy::Stratified);
+        let mut available = vec![peers.random().unwrap(), peers.random().unwrap()];
+        available.sort();
+        assert_eq!(available, vec![1, 3]);
+        assert_eq!(peers.random(), None);
+    }
+
+    #[test]
+    fn should_schedule_zero_weight_strata_last() {
+        let mut rng = TestRng::new();
+        let threshold = TimeDiff::from(Duration::from_secs(60));
+        let mut peers = PeersState::new(config(1, 0, 0, threshold));
+        for peer in 0..10 {
+            peers.push(peer);
+        }
+        for peer in 0..3 {
+            peers.success(peer);
+        }
+
+        peers.reset(&mut rng, PeerSelectionPolicy::Stratified);
+        let mut first: Vec<_> = (0..3).map(|_| peers.random().unwrap()).collect();
+        first.sort();
+        assert_eq!(first, vec![0, 1, 2]);
+        let mut rest: Vec<_> = (0..7).map(|_| peers.random().unwrap()).collect();
+        rest.sort();
+        assert_eq!(rest, (3..10).collect::<Vec<_>>());
+        // Once the schedule is exhausted, successful peers are cycled through.
+        assert!(peers.random().unwrap() < 3);
+    }
+
+    #[test]
+    fn should_draw_from_strata_in_proportion_to_weights() {
+        let mut rng = TestRng::new();
+        let threshold = TimeDiff::from(Duration::from_secs(60));
+        let mut peers = PeersState::new(config(0, 3, 1, threshold));
+        for peer in 0..20 {
+            peers.push(peer);
+        }
+        // Peers 0 to 9 have been known for longer than the threshold.
+        let now = Timestamp::now() + TimeDiff::from(Duration::from_secs(120));
+        for peer in 10..20 {
+            peers.first_seen.insert(peer, now);
+        }
+
+        const RUNS: usize = 1000;
+        let mut long_lived_first = 0;
+        for _ in 0..RUNS {
+            peers.reset_at(&mut rng, PeerSelectionPolicy::Stratified, now);
+            if peers.random().unwrap() < 10 {
+                long_lived_first += 1;
+            }
+        }
+        // Expected to be 750, with a standard deviation of about 14.
+        assert!(
+            (650..=850).contains(&long_lived_first),
+            "long-lived peer scheduled first {} times out of {}",
+            long_lived_first,
+            RUNS
+        );
+    }
+
+    #[test]
+    fn should_prefer_recently_successful_fast_peers() {
+        let mut rng = TestRng::new();
+        let mut peers = PeersState::new(Config::default());
+        for peer in 0..6 {
+            peers.push(peer);
+        }
+        let now = Timestamp::now();
+        let window = Config::default().recent_success_window;
+        // Peers 0 to 2 responded recently, in 300, 100 and 200 ms respectively.  Peer 3 responded
+        // quickly, but too long ago.
+        for (peer, latency) in [(0, 300), (1, 100), (2, 200)].iter() {
+            let asked_at = now - TimeDiff::from(*latency);
+            peers.asked_at.insert(*peer, asked_at);
+            peers.success_at(*peer, now);
+        }
+        let long_ago = now - window - TimeDiff::from(1000);
+        peers.asked_at.insert(3, long_ago - TimeDiff::from(10));
+        peers.success_at(3, long_ago);
+
+        peers.reset_at(&mut rng, PeerSelectionPolicy::LowLatency, now);
+        let first: Vec<_> = (0..3).map(|_| peers.random().unwrap()).collect();
+        assert_eq!(first, vec![1, 2, 0]);
+        let mut rest: Vec<_> = (0..3).map(|_| peers.random().unwrap()).collect();
+        rest.sort();
+        assert_eq!(rest, vec![3, 4, 5]);
+
+        // A slow response raises the peer's average response time.
+        peers.asked_at.insert(1, now - TimeDiff::from(900));
+        peers.success_at(1, now);
+        assert_eq!(peers.latencies[&1], 300);
+        peers.reset_at(&mut rng, PeerSelectionPolicy::LowLatency, now);
+        assert_eq!(peers.random(), Some(2));
     }
 }
@@ -130,6 +130,83 @@ impl State {
         matches!(self, State::None)
     }
 
+    /// Validates a state restored from storage against the trusted hashes we're configured with and
+    /// the highest block in storage.
+    ///
+    /// Returns the state to resume synchronizing from, along with whether the latest block of
+    /// `SyncingDescendants` still needs to be executed, or `None` if the restored state doesn't
+    /// match the contents of storage and synchronization should start from scratch.
+    pub(crate) fn restore(
+        self,
+        trusted_hashes: &[BlockHash],
+        highest_block_header: Option<&BlockHeader>,
+    ) -> Option<(State, bool)> {
+        let highest_block_hash = highest_block_header.map(BlockHeader::hash);
+        // Whether `block` is the next block to be executed on top of storage.
+        let is_next_block = |block: &Block| match highest_block_hash {
+            Some(hash) => hash == *block.header().parent_hash(),
+            None => block.header().is_genesis_child(),
+        };
+
+        match self {
+            State::None | State::Done(_) => None,
+            State::SyncingTrustedHash {
+                trusted_hash: restored_hash,
+                ..
+            }
+            | State::SyncingDescendants {
+                trusted_hash: restored_hash,
+                ..
+            } if !trusted_hashes.is_empty() && !trusted_hashes.contains(&restored_hash) => None,
+            State::SyncingTrustedHash {
+                trusted_hash,
+                highest_block_header: restored_highest_block_header,
+                highest_block_seen,
+                mut linear_chain,
+                latest_block,
+                maybe_switch_block,
+            } => {
+                match &*latest_block {
+                    // Still downloading: storage must not have changed since.
+                    None => {
+                        let restored_highest_block_hash = restored_highest_block_header
+                            .as_ref()
+                            .map(|header| header.hash());
+                        if linear_chain.is_empty()
+                            || restored_highest_block_hash != highest_block_hash
+                        {
+                            return None;
+                        }
+                    }
+                    // Executing: the latest block must have been executed, or be next in line.
+                    Some(block) if highest_block_hash == Some(*block.hash()) => (),
+                    Some(block) if is_next_block(block) => linear_chain.push(block.clone()),
+                    Some(_) => return None,
+                }
+                let state = State::SyncingTrustedHash {
+                    trusted_hash,
+                    highest_block_header: restored_highest_block_header,
+                    highest_block_seen,
+                    linear_chain,
+                    latest_block,
+                    maybe_switch_block,
+                };
+                Some((state, false))
+            }
+            State::SyncingDescendants {
+                ref latest_block, ..
+            } => {
+                let executed = highest_block_hash == Some(*latest_block.hash());
+                let execute_latest_block = !executed && is_next_block(latest_block);
+                if executed || execute_latest_block {
+                    Some((self, execute_latest_block))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
     /// Updates the state with a new switch block.
     pub(crate) fn new_switch_block(&mut self, block: &Block) {
         match self {
@@ -143,3 +220,96 @@ impl State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ProtocolVersion;
+
+    use super::*;
+    use crate::{
+        components::consensus::EraId, crypto::hash::Digest, testing::TestRng, types::FinalizedBlock,
+    };
+
+    /// Returns a random block with `parent` as its parent.
+    fn random_child(rng: &mut TestRng, parent: &Block) -> Block {
+        let era_id = parent.header().era_id();
+        let finalized_block =
+            FinalizedBlock::random_with_specifics(rng, era_id, parent.height() + 1, false);
+        Block::new(
+            *parent.hash(),
+            Digest::random(rng),
+            Digest::random(rng),
+            finalized_block,
+            None,
+            ProtocolVersion::V1_0_0,
+        )
+    }
+
+    #[test]
+    fn should_restore_syncing_descendants() {
+        let mut rng = TestRng::new();
+        let parent = Block::random_with_specifics(&mut rng, EraId(1), 10, false);
+        let latest_block = random_child(&mut rng, &parent);
+        let trusted_hash = *parent.hash();
+        let state = State::sync_descendants(trusted_hash, latest_block.clone(), None);
+
+        // The latest block has been executed: continue with its child.
+        let (_, execute_latest_block) = state
+            .clone()
+            .restore(&[trusted_hash], Some(latest_block.header()))
+            .expect("should restore");
+        assert!(!execute_latest_block);
+
+        // Only the parent has been executed: execute the latest block first.
+        let (_, execute_latest_block) = state
+            .clone()
+            .restore(&[], Some(parent.header()))
+            .expect("should restore");
+        assert!(execute_latest_block);
+
+        // Storage doesn't match, or we're configured with a different trusted hash.
+        let unrelated = Block::random(&mut rng);
+        assert!(state
+            .clone()
+            .restore(&[], Some(unrelated.header()))
+            .is_none());
+        assert!(state
+            .restore(&[*unrelated.hash()], Some(latest_block.header()))
+            .is_none());
+    }
+
+    #[test]
+    fn should_requeue_unexecuted_block_when_restoring_syncing_trusted_hash() {
+        let mut rng = TestRng::new();
+        let highest_block = Block::random_with_specifics(&mut rng, EraId(1), 10, false);
+        let latest_block = random_child(&mut rng, &highest_block);
+        let next_block = random_child(&mut rng, &latest_block);
+        let trusted_hash = *next_block.hash();
+        let state = State::SyncingTrustedHash {
+            trusted_hash,
+            highest_block_header: None,
+            highest_block_seen: next_block.height(),
+            linear_chain: vec![next_block.clone()],
+            latest_block: Box::new(Some(latest_block.clone())),
+            maybe_switch_block: None,
+        };
+
+        // The latest block was being executed when we shut down, so it has to be executed again.
+        match state.clone().restore(&[], Some(highest_block.header())) {
+            Some((State::SyncingTrustedHash { linear_chain, .. }, _)) => {
+                assert_eq!(linear_chain, vec![next_block.clone(), latest_block.clone()])
+            }
+            other => panic!("unexpected restored state {:?}", other),
+        }
+
+        // The latest block was executed, so only the remaining ones need to be.
+        match state.clone().restore(&[], Some(latest_block.header())) {
+            Some((State::SyncingTrustedHash { linear_chain, .. }, _)) => {
+                assert_eq!(linear_chain, vec![next_block])
+            }
+            other => panic!("unexpected restored state {:?}", other),
+        }
+
+        assert!(state.restore(&[], None).is_none());
+    }
+}
@@ -0,0 +1,446 @@
+#![cfg(test)]
+
+use std::collections::BTreeMap;
+
+use derive_more::From;
+use rand::Rng;
+
+use casper_execution_engine::shared::newtypes::Blake2bHash;
+use casper_types::{ExecutionResult, ProtocolVersion};
+
+use super::*;
+use crate::{
+    crypto::hash::Digest,
+    effect::{
+        announcements::LinearChainSyncAnnouncement,
+        requests::{
+            BlockExecutorRequest, BlockValidationRequest, ContractRuntimeRequest, FetcherRequest,
+            NetworkInfoRequest, StateStoreRequest, StorageRequest,
+        },
+    },
+    testing::{ComponentHarness, TestRng},
+    types::{BlockLike, DeployHash, NodeId},
+};
+
+/// The requests and announcements issued by the linear chain synchronizer.
+#[derive(Debug, From)]
+enum ReactorEvent {
+    #[from]
+    Storage(StorageRequest),
+    #[from]
+    BlockFetcher(FetcherRequest<NodeId, Block>),
+    #[from]
+    BlockByHeightFetcher(FetcherRequest<NodeId, BlockByHeight>),
+    #[from]
+    BlockHeaderFetcher(FetcherRequest<NodeId, BlockHeader>),
+    #[from]
+    BlockExecutionResultsFetcher(FetcherRequest<NodeId, BlockExecutionResults>),
+    #[from]
+    BlockValidator(BlockValidationRequest<Block, NodeId>),
+    #[from]
+    BlockExecutor(BlockExecutorRequest),
+    #[from]
+    StateStore(StateStoreRequest),
+    #[from]
+    ContractRuntime(ContractRuntimeRequest),
+    #[from]
+    NetworkInfo(NetworkInfoRequest<NodeId>),
+    #[from]
+    LinearChainSyncAnnouncement(LinearChainSyncAnnouncement<NodeId>),
+}
+
+/// Returns a chainspec with a single bonded era, so that consensus needs the switch blocks of the
+/// four eras before the trusted block's.
+fn chainspec() -> Chainspec {
+    let mut chainspec = Chainspec::from_resources("local");
+    chainspec.core_config.auction_delay = 1;
+    chainspec.core_config.unbonding_delay = 2;
+    chainspec
+}
+
+/// Returns a chain of two blocks per era, oldest first, in the eras up to `last_era`. The second
+/// block of each era is its switch block.
+fn chain(rng: &mut TestRng, last_era: u64) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for era_id in 0..=last_era {
+        for &is_switch in &[false, true] {
+            let height = 10 + blocks.len() as u64;
+            let parent_hash = match blocks.last() {
+                Some(parent) => *parent.hash(),
+                None => BlockHash::new(Digest::random(rng)),
+            };
+            let finalized_block =
+                FinalizedBlock::random_with_specifics(rng, EraId(era_id), height, is_switch);
+            let next_era_validator_weights = if is_switch {
+                Some(BTreeMap::new())
+            } else {
+                None
+            };
+            blocks.push(Block::new(
+                parent_hash,
+                Digest::random(rng),
+                Digest::random(rng),
+                finalized_block,
+                next_era_validator_weights,
+                ProtocolVersion::V1_0_0,
+            ));
+        }
+    }
+    blocks
+}
+
+/// Returns a synchronizer joining in minimal mode from `trusted_block`, with `peer` to fetch from.
+fn minimal_join_sync(
+    chainspec: &Chainspec,
+    trusted_block: &Block,
+    peer: NodeId,
+) -> LinearChainSync<NodeId> {
+    let state = State::sync_trusted_hash(*trusted_block.hash(), None);
+    let mut sync = LinearChainSync::from_state(
+        &Registry::new(),
+        Config::default(),
+        chainspec,
+        state,
+        false,
+        None,
+        true,
+        SyncProgress::default(),
+    )
+    .expect("should create synchronizer");
+    sync.peers.push(peer);
+    sync
+}
+
+/// Hands `event` to the synchronizer, and returns the first request issued by the resulting
+/// effects.
+fn next_request(
+    harness: &mut ComponentHarness<ReactorEvent>,
+    sync: &mut LinearChainSync<NodeId>,
+    event: Event<NodeId>,
+) -> ReactorEvent {
+    for effect in harness.send_event(sync, event) {
+        harness.runtime.spawn(effect);
+    }
+    let (queued_event, _) = harness.runtime.block_on(harness.scheduler.pop());
+    queued_event.event
+}
+
+/// Returns the ID of the block fetched by `request`, or panics if it isn't a fetch of a full block.
+fn fetched_block(request: ReactorEvent) -> BlockHash {
+    match request {
+        ReactorEvent::BlockFetcher(FetcherRequest::Fetch { id, .. }) => id,
+        other => panic!("expected a block fetch, got {:?}", other),
+    }
+}
+
+/// Returns the ID of the block whose header is fetched by `request`, or panics if it isn't a fetch
+/// of a header.
+fn fetched_header(request: ReactorEvent) -> BlockHash {
+    match request {
+        ReactorEvent::BlockHeaderFetcher(FetcherRequest::Fetch { id, .. }) => id,
+        other => panic!("expected a block header fetch, got {:?}", other),
+    }
+}
+
+/// Downloads the trusted block, and checks that its global state is looked up in the trie store.
+fn download_trusted_block(
+    harness: &mut ComponentHarness<ReactorEvent>,
+    sync: &mut LinearChainSync<NodeId>,
+    trusted_block: &Block,
+    peer: &NodeId,
+) {
+    let event = Event::GetBlockHashResult(
+        *trusted_block.hash(),
+        BlockByHashResult::FromPeer(Box::new(trusted_block.clone()), peer.clone()),
+    );
+    match next_request(harness, sync, event) {
+        ReactorEvent::ContractRuntime(ContractRuntimeRequest::MissingTrieKeys {
+            trie_key, ..
+        }) => assert_eq!(
+            trie_key,
+            Blake2bHash::from(*trusted_block.state_root_hash())
+        ),
+        other => panic!("expected a trie store lookup, got {:?}", other),
+    }
+}
+
+/// Hands the downloaded header of `block` to the synchronizer.
+fn header_downloaded(block: &Block, peer: &NodeId) -> Event<NodeId> {
+    Event::GetBlockHeaderResult(
+        *block.hash(),
+        BlockHeaderResult::FromPeer(Box::new(block.header().clone()), peer.clone()),
+    )
+}
+
+/// Hands the downloaded `block` to the synchronizer.
+fn block_downloaded(block: &Block, peer: &NodeId) -> Event<NodeId> {
+    Event::GetBlockHashResult(
+        *block.hash(),
+        BlockByHashResult::FromPeer(Box::new(block.clone()), peer.clone()),
+    )
+}
+
+/// Confirms the trusted state and downloads `ancestors`, oldest first, down from the trusted block.
+/// Returns the request issued after the oldest one was downloaded.
+fn download_ancestors(
+    harness: &mut ComponentHarness<ReactorEvent>,
+    sync: &mut LinearChainSync<NodeId>,
+    ancestors: &[Block],
+    peer: &NodeId,
+) -> ReactorEvent {
+    let mut request = next_request(harness, sync, Event::TrustedStateChecked(true));
+    for block in ancestors.iter().rev() {
+        assert_eq!(fetched_header(request), *block.hash());
+        request = next_request(harness, sync, header_downloaded(block, peer));
+        if block.header().is_switch_block() {
+            assert_eq!(fetched_block(request), *block.hash());
+            request = next_request(harness, sync, block_downloaded(block, peer));
+        }
+    }
+    request
+}
+
+/// Returns random execution results of the deploys of `block`, in block order.
+fn random_execution_results(rng: &mut TestRng, block: &Block) -> BlockExecutionResults {
+    let execution_results = block
+        .deploys()
+        .into_iter()
+        .map(|deploy_hash| (*deploy_hash, rng.gen()))
+        .collect();
+    BlockExecutionResults::new(*block.hash(), execution_results)
+}
+
+/// Returns the checksum registry of `execution_results`.
+fn checksum_registry(execution_results: &BlockExecutionResults) -> ChecksumRegistry {
+    ChecksumRegistry::new(
+        execution_results
+            .execution_results()
+            .iter()
+            .map(|(deploy_hash, execution_result)| (deploy_hash, execution_result)),
+    )
+}
+
+/// Checks that `request` reads the checksum registry from the global state of `trusted_block`.
+fn assert_reads_checksum_registry(request: ReactorEvent, trusted_block: &Block) {
+    match request {
+        ReactorEvent::ContractRuntime(ContractRuntimeRequest::Query { query_request, .. }) => {
+            assert_eq!(
+                query_request.state_hash(),
+                Blake2bHash::from(*trusted_block.state_root_hash())
+            );
+            assert_eq!(query_request.key(), *CHECKSUM_REGISTRY_KEY);
+        }
+        other => panic!("expected a checksum registry query, got {:?}", other),
+    }
+}
+
+/// Returns the ID of the block whose execution results are fetched by `request`, and the peer
+/// they are fetched from, or panics if it isn't a fetch of execution results.
+fn fetched_execution_results(request: ReactorEvent) -> (BlockHash, NodeId) {
+    match request {
+        ReactorEvent::BlockExecutionResultsFetcher(FetcherRequest::Fetch { id, peer, .. }) => {
+            (id, peer)
+        }
+        other => panic!("expected an execution results fetch, got {:?}", other),
+    }
+}
+
+/// Hands the downloaded `execution_results` to the synchronizer.
+fn execution_results_downloaded(
+    execution_results: &BlockExecutionResults,
+    peer: &NodeId,
+) -> Event<NodeId> {
+    Event::GetExecutionResultsResult(
+        *execution_results.block_hash(),
+        ExecutionResultsResult::FromPeer(Box::new(execution_results.clone()), peer.clone()),
+    )
+}
+
+#[test]
+fn should_only_download_switch_blocks_in_full_when_joining_in_minimal_mode() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let chainspec = chainspec();
+    assert_eq!(minimal_join_eras(&chainspec), 4);
+    let blocks = chain(&mut harness.rng, 6);
+    let peer = NodeId::random(&mut harness.rng);
+    // The first block of era 6 is trusted, so the oldest switch block needed is that of era 2.
+    let trusted_block = &blocks[12];
+    let oldest_switch_block = &blocks[5];
+    let mut sync = minimal_join_sync(&chainspec, trusted_block, peer.clone());
+
+    download_trusted_block(&mut harness, &mut sync, trusted_block, &peer);
+    let request = download_ancestors(&mut harness, &mut sync, &blocks[5..12], &peer);
+
+    // The execution results of the trusted block are verified against its checksum registry.
+    assert_reads_checksum_registry(request, trusted_block);
+    let execution_results = random_execution_results(&mut harness.rng, trusted_block);
+    let registry = checksum_registry(&execution_results);
+    let request = next_request(
+        &mut harness,
+        &mut sync,
+        Event::ChecksumRegistryRead(Some(registry)),
+    );
+    assert_eq!(fetched_execution_results(request).0, *trusted_block.hash());
+    let request = next_request(
+        &mut harness,
+        &mut sync,
+        execution_results_downloaded(&execution_results, &peer),
+    );
+
+    // The oldest switch block is stored first, and the trusted block's descendants are next.
+    match request {
+        ReactorEvent::Storage(StorageRequest::PutBlock { block, .. }) => {
+            assert_eq!(*block, *oldest_switch_block)
+        }
+        other => panic!(
+            "expected the oldest switch block to be stored, got {:?}",
+            other
+        ),
+    }
+    match &sync.state {
+        State::SyncingDescendants {
+            latest_block,
+            maybe_switch_block,
+            ..
+        } => {
+            assert_eq!(**latest_block, *trusted_block);
+            assert_eq!(
+                maybe_switch_block.as_deref(),
+                Some(&blocks[11]),
+                "should know the switch block of the era before the trusted block's"
+            );
+        }
+        other => panic!("expected to sync descendants, got {}", other),
+    }
+}
+
+#[test]
+fn should_fall_back_to_full_sync_if_trusted_state_is_missing() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let chainspec = chainspec();
+    let blocks = chain(&mut harness.rng, 6);
+    let peer = NodeId::random(&mut harness.rng);
+    let trusted_block = &blocks[12];
+    let mut sync = minimal_join_sync(&chainspec, trusted_block, peer.clone());
+
+    download_trusted_block(&mut harness, &mut sync, trusted_block, &peer);
+    let request = next_request(&mut harness, &mut sync, Event::TrustedStateChecked(false));
+    assert_eq!(fetched_block(request), *blocks[11].hash());
+    assert!(!sync.minimal_join);
+}
+
+#[test]
+fn should_fall_back_to_full_sync_if_ancestors_reach_activation_point() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let mut chainspec = chainspec();
+    chainspec.protocol_config.activation_point = ActivationPoint::EraId(EraId(5));
+    let blocks = chain(&mut harness.rng, 6);
+    let peer = NodeId::random(&mut harness.rng);
+    let trusted_block = &blocks[12];
+    let mut sync = minimal_join_sync(&chainspec, trusted_block, peer.clone());
+
+    download_trusted_block(&mut harness, &mut sync, trusted_block, &peer);
+    let mut request = next_request(&mut harness, &mut sync, Event::TrustedStateChecked(true));
+    for block in blocks[10..12].iter().rev() {
+        assert_eq!(fetched_header(request), *block.hash());
+        request = next_request(&mut harness, &mut sync, header_downloaded(block, &peer));
+        if block.header().is_switch_block() {
+            assert_eq!(fetched_block(request), *block.hash());
+            request = next_request(&mut harness, &mut sync, block_downloaded(block, &peer));
+        }
+    }
+
+    // The switch block of era 4 precedes the activation point, so the switch block of era 5 is
+    // discarded and the whole chain is downloaded again, from the trusted block's parent down.
+    assert_eq!(fetched_header(request), *blocks[9].hash());
+    let request = next_request(
+        &mut harness,
+        &mut sync,
+        header_downloaded(&blocks[9], &peer),
+    );
+    assert_eq!(fetched_block(request), *blocks[11].hash());
+    assert!(!sync.minimal_join);
+    match &sync.state {
+        State::SyncingTrustedHash { linear_chain, .. } => {
+            assert_eq!(linear_chain.as_slice(), &[trusted_block.clone()])
+        }
+        other => panic!("expected to sync the trusted hash, got {}", other),
+    }
+}
+
+#[test]
+fn should_refetch_execution_results_not_matching_checksum_registry() {
+    let mut harness = ComponentHarness::<ReactorEvent>::default();
+    let chainspec = chainspec();
+    let blocks = chain(&mut harness.rng, 6);
+    let bad_peer = NodeId::random(&mut harness.rng);
+    let good_peer = NodeId::random(&mut harness.rng);
+    let trusted_block = &blocks[12];
+    let mut sync = minimal_join_sync(&chainspec, trusted_block, bad_peer.clone());
+
+    download_trusted_block(&mut harness, &mut sync, trusted_block, &bad_peer);
+    let request = download_ancestors(&mut harness, &mut sync, &blocks[5..12], &bad_peer);
+    assert_reads_checksum_registry(request, trusted_block);
+    let execution_results = random_execution_results(&mut harness.rng, trusted_block);
+    let registry = checksum_registry(&execution_results);
+    sync.peers.push(good_peer.clone());
+    let request = next_request(
+        &mut harness,
+        &mut sync,
+        Event::ChecksumRegistryRead(Some(registry)),
+    );
+    let (block_hash, peer) = fetched_execution_results(request);
+    assert_eq!(block_hash, *trusted_block.hash());
+    let (bad_peer, good_peer) = if peer == bad_peer {
+        (bad_peer, good_peer)
+    } else {
+        (good_peer, bad_peer)
+    };
+
+    // The results of an extra deploy don't match the registry.
+    let mut forged_results = execution_results.execution_results().to_vec();
+    forged_results.push((
+        DeployHash::new(Digest::random(&mut harness.rng)),
+        harness.rng.gen::<ExecutionResult>(),
+    ));
+    let forged_results = BlockExecutionResults::new(*trusted_block.hash(), forged_results);
+    for effect in harness.send_event(
+        &mut sync,
+        execution_results_downloaded(&forged_results, &bad_peer),
+    ) {
+        harness.runtime.spawn(effect);
+    }
+    let mut reported = false;
+    let mut refetched = false;
+    for _ in 0..2 {
+        let (queued_event, _) = harness.runtime.block_on(harness.scheduler.pop());
+        match queued_event.event {
+            ReactorEvent::LinearChainSyncAnnouncement(
+                LinearChainSyncAnnouncement::InvalidExecutionResultsReceived { peer, block_hash },
+            ) => {
+                assert_eq!(peer, bad_peer);
+                assert_eq!(block_hash, *trusted_block.hash());
+                reported = true;
+            }
+            other => {
+                assert_eq!(
+                    fetched_execution_results(other),
+                    (*trusted_block.hash(), good_peer.clone())
+                );
+                refetched = true;
+            }
+        }
+    }
+    assert!(reported && refetched);
+
+    // The genuine results from the other peer are accepted.
+    match next_request(
+        &mut harness,
+        &mut sync,
+        execution_results_downloaded(&execution_results, &good_peer),
+    ) {
+        ReactorEvent::Storage(StorageRequest::PutBlock { .. }) => {}
+        other => panic!("expected the switch blocks to be stored, got {:?}", other),
+    }
+    assert!(matches!(sync.state, State::SyncingDescendants { .. }));
+}
@@ -1,17 +1,25 @@
 use crate::{
-    effect::requests::{
-        BlockExecutorRequest, BlockValidationRequest, FetcherRequest, StateStoreRequest,
-        StorageRequest,
+    effect::{
+        announcements::LinearChainSyncAnnouncement,
+        requests::{
+            BlockExecutorRequest, BlockValidationRequest, ContractRuntimeRequest, FetcherRequest,
+            NetworkInfoRequest, StateStoreRequest, StorageRequest,
+        },
     },
-    types::{Block, BlockByHeight},
+    types::{Block, BlockByHeight, BlockExecutionResults, BlockHeader},
 };
 pub trait ReactorEventT<I>:
     From<StorageRequest>
     + From<FetcherRequest<I, Block>>
     + From<FetcherRequest<I, BlockByHeight>>
+    + From<FetcherRequest<I, BlockHeader>>
+    + From<FetcherRequest<I, BlockExecutionResults>>
     + From<BlockValidationRequest<Block, I>>
     + From<BlockExecutorRequest>
     + From<StateStoreRequest>
+    + From<ContractRuntimeRequest>
+    + From<NetworkInfoRequest<I>>
+    + From<LinearChainSyncAnnouncement<I>>
     + Send
 {
 }
@@ -20,9 +28,15 @@ impl<I, REv> ReactorEventT<I> for REv where
     REv: From<StorageRequest>
         + From<FetcherRequest<I, Block>>
         + From<FetcherRequest<I, BlockByHeight>>
+        + From<FetcherRequest<I, BlockHeader>>
+        + From<FetcherRequest<I, BlockExecutionResults>>
+        + From<FetcherRequest<I, BlockExecutionResults>>
         + From<BlockValidationRequest<Block, I>>
         + From<BlockExecutorRequest>
         + From<StateStoreRequest>
+        + From<ContractRuntimeRequest>
+        + From<NetworkInfoRequest<I>>
+        + From<LinearChainSyncAnnouncement<I>>
         + Send
 {
 }
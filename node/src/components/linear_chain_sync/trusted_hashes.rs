@@ -0,0 +1,303 @@
+//! Verification of several trusted hashes against each other.
+//!
+//! If more than one trusted hash is configured, the blocks of all of them are fetched before
+//! synchronization starts, and the highest one becomes the trust anchor.  The remaining ones must
+//! then show up at their heights while the ancestors of the anchor are downloaded, or already be
+//! in local storage below the block the downloaded chain connects to.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::types::{Block, BlockHash, BlockHeader};
+
+/// A trusted hash whose block has been fetched.
+#[derive(Clone, DataSize, Debug)]
+struct TrustedBlock {
+    hash: BlockHash,
+    height: u64,
+    /// Whether the block was found in local storage rather than downloaded from a peer.
+    stored_locally: bool,
+}
+
+impl Display for TrustedBlock {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} at height {}", self.hash, self.height)
+    }
+}
+
+/// A conflict between the configured trusted hashes.
+#[derive(Clone, Debug, Error)]
+pub(crate) enum TrustedHashConflict {
+    /// Two trusted blocks have the same height.
+    #[error("trusted blocks {first} and {second} both have height {height}")]
+    SameHeight {
+        height: u64,
+        first: BlockHash,
+        second: BlockHash,
+    },
+    /// The ancestor of the anchor at the height of a trusted block is a different block.
+    #[error(
+        "trusted block {trusted} is not an ancestor of trusted block {anchor}, whose ancestor at \
+         that height is {ancestor}"
+    )]
+    DifferentAncestor {
+        anchor: BlockHash,
+        trusted: BlockHash,
+        height: u64,
+        ancestor: BlockHash,
+    },
+    /// A trusted block was neither among the downloaded ancestors of the anchor nor in storage.
+    #[error(
+        "trusted block {trusted} at height {height} is not an ancestor of trusted block {anchor}"
+    )]
+    NotAnAncestor {
+        anchor: BlockHash,
+        trusted: BlockHash,
+        height: u64,
+    },
+}
+
+/// The configured trusted hashes, while fetching their blocks and verifying that they lie on the
+/// same chain.
+#[derive(DataSize, Debug)]
+pub(crate) struct TrustedHashes {
+    /// The trusted hashes whose blocks haven't been fetched yet.
+    unresolved: Vec<BlockHash>,
+    /// The trusted blocks fetched so far.
+    resolved: Vec<TrustedBlock>,
+    /// The highest trusted block, once all of them have been fetched.
+    anchor: Option<TrustedBlock>,
+    /// The trusted blocks below the anchor which haven't been found among its ancestors yet, by
+    /// height.
+    pending: BTreeMap<u64, TrustedBlock>,
+}
+
+impl TrustedHashes {
+    pub(crate) fn new(trusted_hashes: Vec<BlockHash>) -> Self {
+        TrustedHashes {
+            unresolved: trusted_hashes,
+            resolved: Vec::new(),
+            anchor: None,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the next trusted hash whose block needs to be fetched, if any.
+    pub(crate) fn next_unresolved(&self) -> Option<BlockHash> {
+        self.unresolved.first().copied()
+    }
+
+    /// Records the fetched block of a trusted hash.
+    ///
+    /// Once all of them are fetched, returns the hash of the highest one, which is the block to
+    /// synchronize to.
+    pub(crate) fn resolve(
+        &mut self,
+        block: &Block,
+        stored_locally: bool,
+    ) -> Result<Option<BlockHash>, TrustedHashConflict> {
+        let trusted_block = TrustedBlock {
+            hash: *block.hash(),
+            height: block.height(),
+            stored_locally,
+        };
+        if let Some(other) = self
+            .resolved
+            .iter()
+            .find(|other| other.height == trusted_block.height)
+        {
+            return Err(TrustedHashConflict::SameHeight {
+                height: trusted_block.height,
+                first: other.hash,
+                second: trusted_block.hash,
+            });
+        }
+        self.unresolved.retain(|hash| *hash != trusted_block.hash);
+        self.resolved.push(trusted_block);
+        if !self.unresolved.is_empty() {
+            return Ok(None);
+        }
+
+        let mut resolved = std::mem::take(&mut self.resolved);
+        resolved.sort_by_key(|trusted_block| trusted_block.height);
+        let anchor = resolved
+            .pop()
+            .expect("should have resolved at least one block");
+        let anchor_hash = anchor.hash;
+        self.pending = resolved
+            .into_iter()
+            .map(|trusted_block| (trusted_block.height, trusted_block))
+            .collect();
+        self.anchor = Some(anchor);
+        Ok(Some(anchor_hash))
+    }
+
+    /// Returns whether some trusted blocks still have to be found among the anchor's ancestors.
+    pub(crate) fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Checks the header of a downloaded ancestor of the anchor against the trusted block at its
+    /// height, if any.
+    pub(crate) fn check_ancestor(
+        &mut self,
+        header: &BlockHeader,
+    ) -> Result<(), TrustedHashConflict> {
+        let trusted_block = match self.pending.remove(&header.height()) {
+            Some(trusted_block) => trusted_block,
+            None => return Ok(()),
+        };
+        let ancestor = header.hash();
+        if trusted_block.hash == ancestor {
+            return Ok(());
+        }
+        Err(TrustedHashConflict::DifferentAncestor {
+            anchor: self.anchor_hash(),
+            trusted: trusted_block.hash,
+            height: trusted_block.height,
+            ancestor,
+        })
+    }
+
+    /// Checks the remaining trusted blocks once the downloaded chain connects to the block at
+    /// `height` in local storage: they must all be stored locally, at or below that height.
+    pub(crate) fn check_connected_to_storage(
+        &mut self,
+        height: u64,
+    ) -> Result<(), TrustedHashConflict> {
+        let anchor = self.anchor_hash();
+        let pending = std::mem::take(&mut self.pending);
+        match pending
+            .into_iter()
+            .map(|(_, trusted_block)| trusted_block)
+            .find(|trusted_block| !trusted_block.stored_locally || trusted_block.height > height)
+        {
+            Some(trusted_block) => Err(TrustedHashConflict::NotAnAncestor {
+                anchor,
+                trusted: trusted_block.hash,
+                height: trusted_block.height,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a description of all trusted blocks fetched so far, for reporting a conflict.
+    pub(crate) fn report(&self) -> String {
+        self.anchor
+            .iter()
+            .chain(self.pending.values())
+            .chain(self.resolved.iter())
+            .map(ToString::to_string)
+            .chain(
+                self.unresolved
+                    .iter()
+                    .map(|hash| format!("{} not fetched", hash)),
+            )
+            .join(", ")
+    }
+
+    fn anchor_hash(&self) -> BlockHash {
+        self.anchor
+            .as_ref()
+            .map(|anchor| anchor.hash)
+            .expect("should have chosen the anchor before checking its ancestors")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ProtocolVersion;
+
+    use super::*;
+    use crate::{
+        components::consensus::EraId, crypto::hash::Digest, testing::TestRng, types::FinalizedBlock,
+    };
+
+    /// Returns a random block with `parent` as its parent.
+    fn random_child(rng: &mut TestRng, parent: &Block) -> Block {
+        let era_id = parent.header().era_id();
+        let finalized_block =
+            FinalizedBlock::random_with_specifics(rng, era_id, parent.height() + 1, false);
+        Block::new(
+            *parent.hash(),
+            Digest::random(rng),
+            Digest::random(rng),
+            finalized_block,
+            None,
+            ProtocolVersion::V1_0_0,
+        )
+    }
+
+    #[test]
+    fn should_choose_highest_and_verify_ancestors() {
+        let mut rng = TestRng::new();
+        let lower = Block::random_with_specifics(&mut rng, EraId(1), 10, false);
+        let middle = random_child(&mut rng, &lower);
+        let higher = random_child(&mut rng, &middle);
+
+        let mut trusted_hashes = TrustedHashes::new(vec![*lower.hash(), *higher.hash()]);
+        assert_eq!(trusted_hashes.next_unresolved(), Some(*lower.hash()));
+        assert_eq!(trusted_hashes.resolve(&lower, false).unwrap(), None);
+        assert_eq!(trusted_hashes.next_unresolved(), Some(*higher.hash()));
+        assert_eq!(
+            trusted_hashes.resolve(&higher, false).unwrap(),
+            Some(*higher.hash())
+        );
+        assert_eq!(trusted_hashes.next_unresolved(), None);
+        assert!(trusted_hashes.has_pending());
+
+        trusted_hashes.check_ancestor(middle.header()).unwrap();
+        assert!(trusted_hashes.has_pending());
+        trusted_hashes.check_ancestor(lower.header()).unwrap();
+        assert!(!trusted_hashes.has_pending());
+        trusted_hashes.check_connected_to_storage(0).unwrap();
+    }
+
+    #[test]
+    fn should_report_conflicting_trusted_hashes() {
+        let mut rng = TestRng::new();
+        let lower = Block::random_with_specifics(&mut rng, EraId(1), 10, false);
+        let fork = Block::random_with_specifics(&mut rng, EraId(1), 10, false);
+        let higher = random_child(&mut rng, &lower);
+
+        // Two trusted blocks at the same height can't both be on the chain.
+        let mut trusted_hashes = TrustedHashes::new(vec![*lower.hash(), *fork.hash()]);
+        trusted_hashes.resolve(&lower, false).unwrap();
+        assert!(matches!(
+            trusted_hashes.resolve(&fork, false),
+            Err(TrustedHashConflict::SameHeight { height: 10, .. })
+        ));
+
+        // The anchor's ancestor at the trusted block's height is a different block.
+        let mut trusted_hashes = TrustedHashes::new(vec![*fork.hash(), *higher.hash()]);
+        trusted_hashes.resolve(&fork, false).unwrap();
+        trusted_hashes.resolve(&higher, false).unwrap();
+        let conflict = trusted_hashes.check_ancestor(lower.header()).unwrap_err();
+        let description = conflict.to_string();
+        assert!(description.contains(&fork.hash().to_string()));
+        assert!(description.contains(&lower.hash().to_string()));
+        assert!(description.contains(&higher.hash().to_string()));
+
+        // A trusted block which was downloaded from a peer can't be an ancestor found in storage.
+        let mut trusted_hashes = TrustedHashes::new(vec![*fork.hash(), *higher.hash()]);
+        trusted_hashes.resolve(&fork, false).unwrap();
+        trusted_hashes.resolve(&higher, false).unwrap();
+        assert!(matches!(
+            trusted_hashes.check_connected_to_storage(10),
+            Err(TrustedHashConflict::NotAnAncestor { height: 10, .. })
+        ));
+
+        // It's fine if it was in storage already, below the block the chain connects to.
+        let mut trusted_hashes = TrustedHashes::new(vec![*fork.hash(), *higher.hash()]);
+        trusted_hashes.resolve(&fork, true).unwrap();
+        trusted_hashes.resolve(&higher, false).unwrap();
+        assert!(trusted_hashes.report().contains(&fork.hash().to_string()));
+        trusted_hashes.check_connected_to_storage(10).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
 //! Metrics component.
 //!
-//! The metrics component renders metrics upon request.
+//! The metrics component renders metrics upon request.  It also periodically samples statistics of
+//! the storage component's LMDB databases, so that disk growth and exhaustion of the memory map can
+//! be forecast.
 //!
 //! # Adding metrics to a component
 //!
@@ -23,17 +25,56 @@
 //!    prevent any actual logic depending on them. If a counter is being increment as a metric and
 //!    also required for busines logic, a second counter should be kept in the component's state.
 
-use std::convert::Infallible;
+mod storage_metrics;
+
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
 
 use datasize::DataSize;
+use derive_more::From;
 use prometheus::{Encoder, Registry, TextEncoder};
-use tracing::error;
+use tracing::{debug, error};
 
 use crate::{
-    components::Component,
-    effect::{requests::MetricsRequest, EffectBuilder, EffectExt, Effects},
+    components::{storage::StorageStats, Component},
+    effect::{
+        requests::{MetricsRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects,
+    },
     NodeRng,
 };
+use storage_metrics::StorageMetrics;
+
+/// Interval between two samples of the storage statistics.
+const STORAGE_SAMPLING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Metrics component events.
+#[derive(Debug, From)]
+pub enum Event {
+    /// An incoming metrics request.
+    #[from]
+    Request(MetricsRequest),
+    /// Time to sample the storage statistics.
+    SampleStorage,
+    /// The storage statistics were read, or `None` if reading them failed.
+    StorageSampled(Option<Box<StorageStats>>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Request(req) => write!(formatter, "{}", req),
+            Event::SampleStorage => write!(formatter, "sample storage statistics"),
+            Event::StorageSampled(Some(_)) => write!(formatter, "sampled storage statistics"),
+            Event::StorageSampled(None) => {
+                write!(formatter, "failed to sample storage statistics")
+            }
+        }
+    }
+}
 
 /// The metrics component.
 #[derive(DataSize, Debug)]
@@ -41,20 +82,38 @@ pub(crate) struct Metrics {
     /// Metrics registry used to answer metrics queries.
     #[data_size(skip)] // Actual implementation is just a wrapper around an `Arc`.
     registry: Registry,
+    /// Metrics sampled from storage.
+    #[data_size(skip)]
+    storage_metrics: StorageMetrics,
 }
 
-impl<REv> Component<REv> for Metrics {
-    type Event = MetricsRequest;
+impl<REv> Component<REv> for Metrics
+where
+    REv: From<StorageRequest> + Send,
+{
+    type Event = Event;
     type ConstructionError = Infallible;
 
     fn handle_event(
         &mut self,
-        _effect_builder: EffectBuilder<REv>,
+        effect_builder: EffectBuilder<REv>,
         _rng: &mut NodeRng,
-        req: Self::Event,
+        event: Self::Event,
     ) -> Effects<Self::Event> {
-        match req {
-            MetricsRequest::RenderNodeMetricsText { responder } => {
+        match event {
+            Event::SampleStorage => effect_builder
+                .get_storage_stats()
+                .event(|maybe_stats| Event::StorageSampled(maybe_stats.map(Box::new))),
+            Event::StorageSampled(maybe_stats) => {
+                match maybe_stats {
+                    Some(stats) => self.storage_metrics.update(&stats),
+                    None => debug!("no storage statistics available"),
+                }
+                effect_builder
+                    .set_timeout(STORAGE_SAMPLING_INTERVAL)
+                    .event(|_| Event::SampleStorage)
+            }
+            Event::Request(MetricsRequest::RenderNodeMetricsText { responder }) => {
                 let mut buf: Vec<u8> = Vec::<u8>::new();
 
                 if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buf) {
@@ -75,8 +134,18 @@ impl<REv> Component<REv> for Metrics {
 }
 
 impl Metrics {
-    /// Create and initialize a new metrics component.
-    pub(crate) fn new(registry: Registry) -> Self {
-        Metrics { registry }
+    /// Create and initialize a new metrics component, scheduling the first sample of the storage
+    /// statistics.
+    pub(crate) fn new<REv: Send>(
+        registry: Registry,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        let storage_metrics = StorageMetrics::new(&registry)?;
+        let metrics = Metrics {
+            registry,
+            storage_metrics,
+        };
+        let effects = effect_builder.immediately().event(|_| Event::SampleStorage);
+        Ok((metrics, effects))
     }
 }
@@ -0,0 +1,157 @@
+use prometheus::{Gauge, IntGauge, IntGaugeVec, Opts, Registry};
+
+use crate::{components::storage::StorageStats, unregister_metric};
+
+/// Label distinguishing the individual LMDB databases.
+const DATABASE_LABEL: &str = "database";
+
+/// Metrics sampled from the LMDB environment of the storage component.
+#[derive(Debug)]
+pub(super) struct StorageMetrics {
+    /// Number of entries per database.
+    entries: IntGaugeVec,
+    /// Number of branch pages per database.
+    branch_pages: IntGaugeVec,
+    /// Number of leaf pages per database.
+    leaf_pages: IntGaugeVec,
+    /// Number of overflow pages per database.
+    overflow_pages: IntGaugeVec,
+    /// Size of the memory map, in bytes.
+    map_size: IntGauge,
+    /// Size of the database file used so far, including free pages, in bytes.
+    used_size: IntGauge,
+    /// Number of pages freed by past transactions and available for reuse.
+    free_pages: IntGauge,
+    /// Fraction of the memory map occupied by pages which are not free.
+    map_utilization: Gauge,
+    /// Number of bytes copied by the compaction in progress, zero if none is running.
+    compaction_copied_bytes: IntGauge,
+    /// Estimated size of the copy made by the compaction in progress, zero if none is running.
+    compaction_estimated_bytes: IntGauge,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl StorageMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let entries = IntGaugeVec::new(
+            Opts::new(
+                "storage_db_entries",
+                "number of entries per storage database",
+            ),
+            &[DATABASE_LABEL],
+        )?;
+        let branch_pages = IntGaugeVec::new(
+            Opts::new(
+                "storage_db_branch_pages",
+                "number of branch pages per storage database",
+            ),
+            &[DATABASE_LABEL],
+        )?;
+        let leaf_pages = IntGaugeVec::new(
+            Opts::new(
+                "storage_db_leaf_pages",
+                "number of leaf pages per storage database",
+            ),
+            &[DATABASE_LABEL],
+        )?;
+        let overflow_pages = IntGaugeVec::new(
+            Opts::new(
+                "storage_db_overflow_pages",
+                "number of overflow pages per storage database",
+            ),
+            &[DATABASE_LABEL],
+        )?;
+        let map_size = IntGauge::new(
+            "storage_map_size_bytes",
+            "maximum size the storage database file can grow to, in bytes",
+        )?;
+        let used_size = IntGauge::new(
+            "storage_used_size_bytes",
+            "size of the storage database file used so far including free pages, in bytes",
+        )?;
+        let free_pages = IntGauge::new(
+            "storage_free_pages",
+            "number of pages in the storage database file available for reuse",
+        )?;
+        let map_utilization = Gauge::new(
+            "storage_map_utilization",
+            "fraction of the storage memory map occupied by pages which are not free",
+        )?;
+        let compaction_copied_bytes = IntGauge::new(
+            "storage_compaction_copied_bytes",
+            "number of bytes copied by the storage compaction in progress",
+        )?;
+        let compaction_estimated_bytes = IntGauge::new(
+            "storage_compaction_estimated_bytes",
+            "estimated size of the copy made by the storage compaction in progress, in bytes",
+        )?;
+        registry.register(Box::new(entries.clone()))?;
+        registry.register(Box::new(branch_pages.clone()))?;
+        registry.register(Box::new(leaf_pages.clone()))?;
+        registry.register(Box::new(overflow_pages.clone()))?;
+        registry.register(Box::new(map_size.clone()))?;
+        registry.register(Box::new(used_size.clone()))?;
+        registry.register(Box::new(free_pages.clone()))?;
+        registry.register(Box::new(map_utilization.clone()))?;
+        registry.register(Box::new(compaction_copied_bytes.clone()))?;
+        registry.register(Box::new(compaction_estimated_bytes.clone()))?;
+
+        Ok(StorageMetrics {
+            entries,
+            branch_pages,
+            leaf_pages,
+            overflow_pages,
+            map_size,
+            used_size,
+            free_pages,
+            map_utilization,
+            compaction_copied_bytes,
+            compaction_estimated_bytes,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Updates all metrics from the given statistics.
+    pub(super) fn update(&self, stats: &StorageStats) {
+        for db in &stats.databases {
+            let labels = [db.name];
+            self.entries
+                .with_label_values(&labels)
+                .set(db.entries as i64);
+            self.branch_pages
+                .with_label_values(&labels)
+                .set(db.branch_pages as i64);
+            self.leaf_pages
+                .with_label_values(&labels)
+                .set(db.leaf_pages as i64);
+            self.overflow_pages
+                .with_label_values(&labels)
+                .set(db.overflow_pages as i64);
+        }
+        self.map_size.set(stats.map_size as i64);
+        self.used_size.set(stats.used_size() as i64);
+        self.free_pages.set(stats.free_pages as i64);
+        self.map_utilization.set(stats.map_utilization());
+        let compaction = stats.compaction.unwrap_or_default();
+        self.compaction_copied_bytes
+            .set(compaction.copied_bytes as i64);
+        self.compaction_estimated_bytes
+            .set(compaction.estimated_bytes as i64);
+    }
+}
+
+impl Drop for StorageMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.entries);
+        unregister_metric!(self.registry, self.branch_pages);
+        unregister_metric!(self.registry, self.leaf_pages);
+        unregister_metric!(self.registry, self.overflow_pages);
+        unregister_metric!(self.registry, self.map_size);
+        unregister_metric!(self.registry, self.used_size);
+        unregister_metric!(self.registry, self.free_pages);
+        unregister_metric!(self.registry, self.map_utilization);
+        unregister_metric!(self.registry, self.compaction_copied_bytes);
+        unregister_metric!(self.registry, self.compaction_estimated_bytes);
+    }
+}
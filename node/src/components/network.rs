@@ -51,7 +51,9 @@ use self::{
 };
 pub use self::{config::Config, error::Error};
 use crate::{
-    components::{networking_metrics::NetworkingMetrics, Component},
+    components::{
+        networking_metrics::NetworkingMetrics, small_network::NetworkTopology, Component,
+    },
     effect::{
         announcements::NetworkAnnouncement,
         requests::{NetworkInfoRequest, NetworkRequest},
@@ -59,7 +61,7 @@ use crate::{
     },
     fatal,
     reactor::{EventQueueHandle, Finalize, QueueKind},
-    types::{Chainspec, NodeId},
+    types::{Chainspec, ExitCode, NodeId},
     utils::{self, ds, CountingReceiver, CountingSender, DisplayIter},
     NodeRng,
 };
@@ -316,6 +318,7 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
                 Err(err) => {
                     return fatal!(
                         effect_builder,
+                        ExitCode::NetworkFailure,
                         "Could not acquire `known_addresses_mut` mutex: {:?}",
                         err
                     )
@@ -917,7 +920,12 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Component<REv> for Network<REv, P> {
             Event::ExpiredListenAddress(address) => {
                 self.listening_addresses.retain(|addr| *addr != address);
                 if self.listening_addresses.is_empty() {
-                    return fatal!(effect_builder, "no remaining listening addresses").ignore();
+                    return fatal!(
+                        effect_builder,
+                        ExitCode::NetworkFailure,
+                        "no remaining listening addresses"
+                    )
+                    .ignore();
                 }
                 debug!(%address, "{}: listening address expired", self.our_id);
                 Effects::new()
@@ -926,7 +934,13 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Component<REv> for Network<REv, P> {
                 // If the listener closed without an error, we're already shutting down the server.
                 // Otherwise, we need to kill the node as it cannot function without a listener.
                 match reason {
-                    Err(error) => fatal!(effect_builder, "listener closed: {}", error).ignore(),
+                    Err(error) => fatal!(
+                        effect_builder,
+                        ExitCode::NetworkFailure,
+                        "listener closed: {}",
+                        error
+                    )
+                    .ignore(),
                     Ok(()) => {
                         debug!("{}: listener closed", self.our_id);
                         Effects::new()
@@ -1006,6 +1020,28 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Component<REv> for Network<REv, P> {
                         .collect();
                     responder.respond(peers).ignore()
                 }
+                // The details of connections are not tracked by this implementation.
+                NetworkInfoRequest::GetTopology { responder } => responder
+                    .respond(NetworkTopology {
+                        our_id: self.our_id.clone(),
+                        connections: Vec::new(),
+                    })
+                    .ignore(),
+                // Misbehaving peers are not blocklisted by this implementation.
+                NetworkInfoRequest::GetBlocklist { responder } => {
+                    responder.respond(Vec::new()).ignore()
+                }
+                NetworkInfoRequest::ClearBlocklist { responder, .. } => {
+                    responder.respond(0).ignore()
+                }
+                NetworkInfoRequest::BlockPeer { peer, responder } => {
+                    debug!(%peer, "not blocking misbehaving peer");
+                    responder.respond(()).ignore()
+                }
+                // The reachability of our address is not checked by this implementation.
+                NetworkInfoRequest::GetPublicEndpoint { responder } => {
+                    responder.respond(None).ignore()
+                }
             },
         }
     }
@@ -1,7 +1,14 @@
-use prometheus::{IntCounter, IntGauge, Registry};
+use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntGauge, Registry};
 
 use crate::unregister_metric;
 
+/// Upper bound of the first bucket of the ping round-trip time histograms, in seconds.
+const PING_RTT_BUCKET_START: f64 = 0.001;
+/// Multiplier of previous upper bound for next bound.
+const PING_RTT_BUCKET_FACTOR: f64 = 2.0;
+/// Bucket count, with last going to +Inf.
+const PING_RTT_BUCKET_COUNT: usize = 14;
+
 /// Network-type agnostic networking metrics.
 pub(super) struct NetworkingMetrics {
     /// How often a request was made by a component to broadcast.
@@ -12,8 +19,18 @@ pub(super) struct NetworkingMetrics {
     pub(super) open_connections: IntGauge,
     /// Number of messages still waiting to be sent out (broadcast and direct).
     pub(super) queued_messages: IntGauge,
+    /// Number of outgoing messages dropped because their queue was full.
+    pub(super) dropped_outgoing_messages: IntCounter,
     /// Number of connected peers.
     pub(super) peers: IntGauge,
+    /// Round-trip times of answered pings, labeled by peer.
+    pub(super) peer_ping_rtt: HistogramVec,
+    /// Number of pings which were not answered in time.
+    pub(super) failed_pings: IntCounter,
+    /// Number of peers currently banned.
+    pub(super) blocklisted_peers: IntGauge,
+    /// Number of offenses for which a peer was banned.
+    pub(super) blocklist_offenses: IntCounter,
 
     // Potentially temporary metrics, not supported by all networking components:
     /// Number of do-nothing futures that have not finished executing for read requests.
@@ -44,7 +61,33 @@ impl NetworkingMetrics {
             "net_queued_direct_messages",
             "number of messages waiting to be sent out",
         )?;
+        let dropped_outgoing_messages = IntCounter::new(
+            "net_dropped_outgoing_messages",
+            "number of outgoing gossip or item messages dropped because their queue was full",
+        )?;
         let peers = IntGauge::new("peers", "Number of connected peers.")?;
+        let peer_ping_rtt = HistogramVec::new(
+            HistogramOpts::new(
+                "net_peer_ping_rtt_seconds",
+                "round-trip time of pings to connected peers, in seconds",
+            )
+            .buckets(prometheus::exponential_buckets(
+                PING_RTT_BUCKET_START,
+                PING_RTT_BUCKET_FACTOR,
+                PING_RTT_BUCKET_COUNT,
+            )?),
+            &["peer"],
+        )?;
+        let failed_pings = IntCounter::new(
+            "net_failed_pings",
+            "number of pings to peers which were not answered in time",
+        )?;
+        let blocklisted_peers =
+            IntGauge::new("net_blocklisted_peers", "number of peers currently banned")?;
+        let blocklist_offenses = IntCounter::new(
+            "net_blocklist_offenses",
+            "number of offenses for which a peer was banned",
+        )?;
 
         let read_futures_in_flight = prometheus::Gauge::new(
             "owm_read_futures_in_flight",
@@ -67,7 +110,12 @@ impl NetworkingMetrics {
         registry.register(Box::new(direct_message_requests.clone()))?;
         registry.register(Box::new(open_connections.clone()))?;
         registry.register(Box::new(queued_messages.clone()))?;
+        registry.register(Box::new(dropped_outgoing_messages.clone()))?;
         registry.register(Box::new(peers.clone()))?;
+        registry.register(Box::new(peer_ping_rtt.clone()))?;
+        registry.register(Box::new(failed_pings.clone()))?;
+        registry.register(Box::new(blocklisted_peers.clone()))?;
+        registry.register(Box::new(blocklist_offenses.clone()))?;
 
         registry.register(Box::new(read_futures_in_flight.clone()))?;
         registry.register(Box::new(read_futures_total.clone()))?;
@@ -79,7 +127,12 @@ impl NetworkingMetrics {
             direct_message_requests,
             open_connections,
             queued_messages,
+            dropped_outgoing_messages,
             peers,
+            peer_ping_rtt,
+            failed_pings,
+            blocklisted_peers,
+            blocklist_offenses,
             read_futures_in_flight,
             read_futures_total,
             write_futures_in_flight,
@@ -95,7 +148,12 @@ impl Drop for NetworkingMetrics {
         unregister_metric!(self.registry, self.direct_message_requests);
         unregister_metric!(self.registry, self.open_connections);
         unregister_metric!(self.registry, self.queued_messages);
+        unregister_metric!(self.registry, self.dropped_outgoing_messages);
         unregister_metric!(self.registry, self.peers);
+        unregister_metric!(self.registry, self.peer_ping_rtt);
+        unregister_metric!(self.registry, self.failed_pings);
+        unregister_metric!(self.registry, self.blocklisted_peers);
+        unregister_metric!(self.registry, self.blocklist_offenses);
         unregister_metric!(self.registry, self.read_futures_in_flight);
         unregister_metric!(self.registry, self.read_futures_total);
         unregister_metric!(self.registry, self.write_futures_in_flight);
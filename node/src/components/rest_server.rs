@@ -12,43 +12,55 @@
 //! reactor, and an external facing http server that exposes various uri routes and converts
 //! HTTP requests into the appropriate component events.
 //!
-//! Currently this component supports two endpoints, each of which takes no arguments:
+//! Currently this component supports three endpoints, each of which takes no arguments:
 //! /status : a human readable JSON equivalent of the info-get-status rpc method.
 //!     example: curl -X GET 'http://<ip>:8888/status'
 //! /metrics : time series data collected from the internals of the node being queried.
 //!     example: curl -X GET 'http://<ip>:8888/metrics'
+//! /metrics/sync : the progress of the linear chain sync in the same format, served without going
+//!     through the reactor, for frequent scraping while a node is joining.
+//!     example: curl -X GET 'http://<ip>:8888/metrics/sync'
+//! /topology : the node's connections as a JSON graph with salted, hashed node IDs.  Only served if
+//!     enabled in the config.
+//!     example: curl -X GET 'http://<ip>:8888/topology'
 
 mod config;
 mod event;
 mod filters;
 mod http_server;
+mod topology;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::{convert::Infallible, fmt::Debug, sync::Arc};
 
 use datasize::DataSize;
 use futures::{future::BoxFuture, join, FutureExt};
+use prometheus::Registry;
+use rand::Rng;
 use semver::Version;
 use tokio::{sync::oneshot, task::JoinHandle};
 use tracing::{debug, error, warn};
 
 use super::Component;
 use crate::{
+    components::event_stream_server::SseEventType,
+    crypto::hash,
     effect::{
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, MetricsRequest, NetworkInfoRequest,
-            StorageRequest,
+            ChainspecLoaderRequest, ConsensusRequest, LinearChainRequest, MetricsRequest,
+            NetworkInfoRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
     reactor::Finalize,
-    types::{NodeId, StatusFeed},
-    utils::{self, ListeningError},
+    types::{NodeId, StatusFeed, Timestamp},
+    utils::{self, rate_limiter::RateLimiter, ListeningError, Scheduler, SyncProgress},
     NodeRng,
 };
 
 use crate::effect::requests::RestRequest;
 pub use config::Config;
 pub(crate) use event::Event;
+pub(crate) use topology::TopologyGraph;
 
 /// A helper trait capturing all of this components Request type dependencies.
 pub trait ReactorEventT:
@@ -58,6 +70,7 @@ pub trait ReactorEventT:
     + From<StorageRequest>
     + From<ChainspecLoaderRequest>
     + From<ConsensusRequest>
+    + From<LinearChainRequest<NodeId>>
     + From<MetricsRequest>
     + Send
 {
@@ -70,6 +83,7 @@ impl<REv> ReactorEventT for REv where
         + From<StorageRequest>
         + From<ChainspecLoaderRequest>
         + From<ConsensusRequest>
+        + From<LinearChainRequest<NodeId>>
         + From<MetricsRequest>
         + Send
         + 'static
@@ -82,6 +96,13 @@ pub(crate) struct RestServer {
     shutdown_sender: oneshot::Sender<()>,
     /// The task handle which will only join once the server loop has exited.
     server_join_handle: Option<JoinHandle<()>>,
+    /// The scheduler of the components' periodic jobs, listed in the status.
+    #[data_size(skip)]
+    scheduler: Scheduler,
+    /// The types of events sent on the event stream, listed in the status.
+    event_stream_events: Vec<SseEventType>,
+    /// The salt mixed into the node IDs of the topology graph.
+    topology_salt: [u8; topology::SALT_LENGTH],
 }
 
 impl RestServer {
@@ -89,12 +110,23 @@ impl RestServer {
         config: Config,
         effect_builder: EffectBuilder<REv>,
         api_version: Version,
+        registry: &Registry,
+        scheduler: Scheduler,
+        event_stream_events: Vec<SseEventType>,
+        sync_progress: SyncProgress,
+        rng: &mut NodeRng,
     ) -> Result<Self, ListeningError>
     where
         REv: ReactorEventT,
     {
         let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
 
+        let topology_salt = match &config.topology_salt {
+            Some(salt) => hash::hash(salt).to_array(),
+            None => rng.gen(),
+        };
+
+        let rate_limiter = Arc::new(RateLimiter::new("rest", config.rate_limit, registry)?);
         let builder = utils::start_listening(&config.address)?;
         let server_join_handle = tokio::spawn(http_server::run(
             builder,
@@ -102,11 +134,17 @@ impl RestServer {
             api_version,
             shutdown_receiver,
             config.qps_limit,
+            rate_limiter,
+            sync_progress,
+            config.enable_topology,
         ));
 
         Ok(RestServer {
             shutdown_sender,
             server_join_handle: Some(server_join_handle),
+            scheduler,
+            event_stream_events,
+            topology_salt,
         })
     }
 }
@@ -125,18 +163,38 @@ where
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::RestRequest(RestRequest::GetStatus { responder }) => async move {
-                let (last_added_block, peers, chainspec_info, consensus_status) = join!(
-                    effect_builder.get_highest_block_from_storage(),
-                    effect_builder.network_peers(),
-                    effect_builder.get_chainspec_info(),
-                    effect_builder.consensus_status()
-                );
-                let status_feed =
-                    StatusFeed::new(last_added_block, peers, chainspec_info, consensus_status);
-                responder.respond(status_feed).await;
+            Event::RestRequest(RestRequest::GetStatus { responder }) => {
+                let scheduled_jobs = self.scheduler.jobs();
+                let event_stream_events = self.event_stream_events.clone();
+                async move {
+                    let (
+                        last_added_block,
+                        peers,
+                        public_endpoint,
+                        chainspec_info,
+                        consensus_status,
+                        blocks_below_strict_finality,
+                    ) = join!(
+                        effect_builder.get_highest_block_from_storage(),
+                        effect_builder.network_peers(),
+                        effect_builder.network_public_endpoint::<NodeId>(),
+                        effect_builder.get_chainspec_info(),
+                        effect_builder.consensus_status(),
+                        effect_builder.get_blocks_below_strict_finality::<NodeId>()
+                    );
+                    let status_feed = StatusFeed::new(
+                        last_added_block,
+                        (peers, public_endpoint),
+                        chainspec_info,
+                        consensus_status,
+                        blocks_below_strict_finality,
+                        scheduled_jobs,
+                        event_stream_events,
+                    );
+                    responder.respond(status_feed).await;
+                }
+                .ignore()
             }
-            .ignore(),
             Event::RestRequest(RestRequest::GetMetrics { responder }) => effect_builder
                 .get_metrics()
                 .event(move |text| Event::GetMetricsResult {
@@ -147,6 +205,15 @@ where
                 text,
                 main_responder,
             } => main_responder.respond(text).ignore(),
+            Event::RestRequest(RestRequest::GetTopology { responder }) => {
+                let salt = self.topology_salt;
+                async move {
+                    let topology = effect_builder.network_topology::<NodeId>().await;
+                    let graph = TopologyGraph::new(&salt, topology, Timestamp::now());
+                    responder.respond(graph).await;
+                }
+                .ignore()
+            }
         }
     }
 }
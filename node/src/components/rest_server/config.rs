@@ -1,12 +1,16 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::RateLimitConfig;
+
 /// Default binding address for the REST HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
 const DEFAULT_ADDRESS: &str = "0.0.0.0:0";
 /// Default rate limit in qps.
 const DEFAULT_QPS_LIMIT: u64 = 100;
+/// Default rate limits applied to all endpoints.
+const DEFAULT_RATE_LIMIT: RateLimitConfig = RateLimitConfig::new(100, 10);
 
 /// REST HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -18,6 +22,26 @@ pub struct Config {
 
     /// Max rate limit in qps.
     pub qps_limit: u64,
+
+    /// Rate limits applied to all endpoints, rejecting excess requests with "429 Too Many
+    /// Requests".
+    #[serde(default = "default_rate_limit")]
+    pub rate_limit: RateLimitConfig,
+
+    /// Whether to serve the node's view of the network topology on `/topology`.
+    #[serde(default)]
+    pub enable_topology: bool,
+
+    /// The salt mixed into the hashed node IDs served on `/topology`.
+    ///
+    /// Nodes sharing a salt export the same hash for the same peer, so their graphs can be joined.
+    /// If unset, a random salt is drawn whenever the server starts.
+    #[serde(default)]
+    pub topology_salt: Option<String>,
+}
+
+fn default_rate_limit() -> RateLimitConfig {
+    DEFAULT_RATE_LIMIT
 }
 
 impl Config {
@@ -26,6 +50,9 @@ impl Config {
         Config {
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            enable_topology: false,
+            topology_salt: None,
         }
     }
 }
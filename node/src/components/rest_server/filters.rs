@@ -16,6 +16,7 @@ use crate::{
     effect::{requests::RestRequest, EffectBuilder},
     reactor::QueueKind,
     types::GetStatusResult,
+    utils::SyncProgress,
 };
 
 /// The status URL path.
@@ -24,6 +25,12 @@ pub const STATUS_API_PATH: &str = "status";
 /// The metrics URL path.
 pub const METRICS_API_PATH: &str = "metrics";
 
+/// The URL path of the linear chain sync progress, below the metrics path.
+pub const SYNC_METRICS_API_PATH: &str = "sync";
+
+/// The network topology URL path.
+pub const TOPOLOGY_API_PATH: &str = "topology";
+
 pub(super) fn create_status_filter<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
     api_version: Version,
@@ -45,6 +52,52 @@ pub(super) fn create_status_filter<REv: ReactorEventT>(
         .boxed()
 }
 
+/// Serves the latest progress of the linear chain sync, straight from the shared snapshot rather
+/// than through the reactor.
+pub(super) fn create_sync_metrics_filter(
+    sync_progress: SyncProgress,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(METRICS_API_PATH))
+        .and(warp::path(SYNC_METRICS_API_PATH))
+        .and(warp::path::end())
+        .map(move || match sync_progress.render() {
+            Ok(text) => reply::with_status(text, StatusCode::OK).into_response(),
+            Err(error) => {
+                warn!(%error, "could not render sync progress");
+                reply::with_status(
+                    "sync progress not available",
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+                .into_response()
+            }
+        })
+        .boxed()
+}
+
+/// Serves the node's view of the network topology, or rejects the request if not `enabled`.
+pub(super) fn create_topology_filter<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    enabled: bool,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(TOPOLOGY_API_PATH))
+        .and(warp::path::end())
+        .and_then(move || async move {
+            if !enabled {
+                return Err(warp::reject::not_found());
+            }
+            let graph = effect_builder
+                .make_request(
+                    |responder| RestRequest::GetTopology { responder },
+                    QueueKind::Api,
+                )
+                .await;
+            Ok::<_, Rejection>(reply::json(&graph).into_response())
+        })
+        .boxed()
+}
+
 pub(super) fn create_metrics_filter<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
 ) -> BoxedFilter<(Response<Body>,)> {
@@ -1,7 +1,15 @@
-use std::{convert::Infallible, time::Duration};
+use std::{convert::Infallible, sync::Arc, time::Duration};
 
 use futures::{future, TryFutureExt};
-use hyper::server::{conn::AddrIncoming, Builder};
+use http::{Request, StatusCode};
+use hyper::{
+    server::{
+        conn::{AddrIncoming, AddrStream},
+        Builder,
+    },
+    service::Service,
+    Body,
+};
 use semver::Version;
 use tokio::sync::oneshot;
 use tower::builder::ServiceBuilder;
@@ -9,7 +17,13 @@ use tracing::{info, warn};
 use warp::Filter;
 
 use super::{filters, ReactorEventT};
-use crate::effect::EffectBuilder;
+use crate::{
+    effect::EffectBuilder,
+    utils::{
+        rate_limiter::{self, ClientIp, RateLimiter},
+        SyncProgress,
+    },
+};
 
 /// Run the REST HTTP server.
 ///
@@ -20,16 +34,41 @@ pub(super) async fn run<REv: ReactorEventT>(
     api_version: Version,
     shutdown_receiver: oneshot::Receiver<()>,
     qps_limit: u64,
+    rate_limiter: Arc<RateLimiter>,
+    sync_progress: SyncProgress,
+    enable_topology: bool,
 ) {
     // REST filters.
     let rest_status = filters::create_status_filter(effect_builder, api_version);
     let rest_metrics = filters::create_metrics_filter(effect_builder);
+    let rest_sync_metrics = filters::create_sync_metrics_filter(sync_progress);
+    let rest_topology = filters::create_topology_filter(effect_builder, enable_topology);
 
-    let service = warp_json_rpc::service(rest_status.or(rest_metrics));
+    // Catch requests exceeding the rate limits.
+    let rate_limited = rate_limiter::exceeded(rate_limiter)
+        .map(|| warp::reply::with_status("rate limit exceeded", StatusCode::TOO_MANY_REQUESTS));
+
+    // The sync metrics are nested below the general metrics, so have to be matched first.
+    let service = warp_json_rpc::service(
+        rate_limited
+            .or(rest_status)
+            .or(rest_sync_metrics)
+            .or(rest_metrics)
+            .or(rest_topology),
+    );
 
     // Start the server, passing a oneshot receiver to allow the server to be shut down gracefully.
-    let make_svc =
-        hyper::service::make_service_fn(move |_| future::ok::<_, Infallible>(service.clone()));
+    // Each request is tagged with the client's IP address, for use by the rate limiter.
+    let make_svc = hyper::service::make_service_fn(move |connection: &AddrStream| {
+        let client_ip = ClientIp(connection.remote_addr().ip());
+        let mut service = service.clone();
+        future::ok::<_, Infallible>(hyper::service::service_fn(
+            move |mut request: Request<Body>| {
+                request.extensions_mut().insert(client_ip);
+                service.call(request)
+            },
+        ))
+    });
 
     let make_svc = ServiceBuilder::new()
         .rate_limit(qps_limit, Duration::from_secs(1))
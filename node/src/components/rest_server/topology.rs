@@ -0,0 +1,157 @@
+//! The node's view of the network overlay, as a graph for topology dashboards.
+//!
+//! Peer IDs are never exported as they are: each node ID is replaced by a hash of the ID and a
+//! salt.  Operators who share a salt across their nodes can join the graphs of these nodes to
+//! detect partitions, while the graphs reveal nothing about the peers to anyone without the salt.
+
+use serde::Serialize;
+
+use crate::{
+    components::small_network::{ConnectionDirection, NetworkTopology},
+    crypto::hash,
+    types::{NodeId, Timestamp},
+};
+
+/// The length in bytes of the salt mixed into hashed node IDs.
+pub(super) const SALT_LENGTH: usize = 32;
+
+/// A node of the graph.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct TopologyNode {
+    /// The salted hash of the node ID.
+    pub(crate) id: String,
+    /// Whether this is the node serving the graph.
+    pub(crate) is_self: bool,
+}
+
+/// A single connection, directed from the node which established it to the one accepting it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct TopologyEdge {
+    /// The salted hash of the ID of the node which established the connection.
+    pub(crate) source: String,
+    /// The salted hash of the ID of the node which accepted the connection.
+    pub(crate) target: String,
+    /// The direction of the connection as seen from the node serving the graph.
+    pub(crate) direction: ConnectionDirection,
+    /// The number of seconds since the connection was established.
+    pub(crate) age_secs: u64,
+    /// The time the last message was received over the connection, for incoming connections.
+    pub(crate) last_seen: Option<Timestamp>,
+    /// The kinds of items served to the peer over the connection, for outgoing connections.
+    pub(crate) served_tags: Vec<String>,
+}
+
+/// The node's current view of the overlay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct TopologyGraph {
+    /// The salted hash of the ID of the node serving the graph.
+    pub(crate) node: String,
+    /// The node serving the graph and all of its peers.
+    pub(crate) nodes: Vec<TopologyNode>,
+    /// The connections of the node serving the graph.
+    pub(crate) edges: Vec<TopologyEdge>,
+}
+
+impl TopologyGraph {
+    /// Builds the graph of the given connections, with all node IDs salted and hashed.
+    pub(crate) fn new(salt: &[u8; SALT_LENGTH], topology: NetworkTopology, now: Timestamp) -> Self {
+        let node = salted_id(salt, &topology.our_id);
+        let mut nodes = vec![TopologyNode {
+            id: node.clone(),
+            is_self: true,
+        }];
+        let mut edges = Vec::with_capacity(topology.connections.len());
+        for connection in topology.connections {
+            let peer = salted_id(salt, &connection.peer_id);
+            if !nodes.iter().any(|existing| existing.id == peer) {
+                nodes.push(TopologyNode {
+                    id: peer.clone(),
+                    is_self: false,
+                });
+            }
+            let (source, target) = match connection.direction {
+                ConnectionDirection::Outgoing => (node.clone(), peer),
+                ConnectionDirection::Incoming => (peer, node.clone()),
+            };
+            edges.push(TopologyEdge {
+                source,
+                target,
+                direction: connection.direction,
+                age_secs: now.saturating_diff(connection.connected_since).millis() / 1_000,
+                last_seen: connection.last_seen,
+                served_tags: connection
+                    .served_tags
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            });
+        }
+        TopologyGraph { node, nodes, edges }
+    }
+}
+
+/// Returns the hex-encoded hash of the salt and the node ID.
+fn salted_id(salt: &[u8; SALT_LENGTH], node_id: &NodeId) -> String {
+    let mut data = salt.to_vec();
+    // Serializing a node ID can't fail.
+    data.extend(bincode::serialize(node_id).unwrap_or_default());
+    hex::encode(hash::hash(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::small_network::ConnectionInfo,
+        testing::TestRng,
+        types::{Tag, TimeDiff},
+    };
+
+    #[test]
+    fn should_build_graph_with_salted_ids() {
+        let mut rng = TestRng::new();
+        let our_id = NodeId::random_tls(&mut rng);
+        let peer_id = NodeId::random_tls(&mut rng);
+        let now = Timestamp::from(100_000);
+        let connected_since = now - TimeDiff::from(30_000);
+        let topology = NetworkTopology {
+            our_id: our_id.clone(),
+            connections: vec![
+                ConnectionInfo {
+                    peer_id: peer_id.clone(),
+                    direction: ConnectionDirection::Outgoing,
+                    connected_since,
+                    last_seen: None,
+                    served_tags: vec![Tag::Deploy],
+                },
+                ConnectionInfo {
+                    peer_id: peer_id.clone(),
+                    direction: ConnectionDirection::Incoming,
+                    connected_since,
+                    last_seen: Some(now),
+                    served_tags: vec![],
+                },
+            ],
+        };
+
+        let salt = [1; SALT_LENGTH];
+        let graph = TopologyGraph::new(&salt, topology.clone(), now);
+        let peer = salted_id(&salt, &peer_id);
+        assert_eq!(graph.node, salted_id(&salt, &our_id));
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.nodes[0].is_self);
+        assert_eq!(graph.nodes[1].id, peer);
+        assert_eq!(graph.edges[0].source, graph.node);
+        assert_eq!(graph.edges[0].target, peer);
+        assert_eq!(graph.edges[0].age_secs, 30);
+        assert_eq!(graph.edges[0].served_tags, vec!["Deploy".to_string()]);
+        assert_eq!(graph.edges[1].source, peer);
+        assert_eq!(graph.edges[1].last_seen, Some(now));
+
+        // The exported IDs reveal neither the node IDs nor their relation across salts.
+        let serialized = serde_json::to_string(&graph).unwrap();
+        assert!(!serialized.contains(&hex::encode(bincode::serialize(&peer_id).unwrap())));
+        let other_graph = TopologyGraph::new(&[2; SALT_LENGTH], topology, now);
+        assert_ne!(other_graph.node, graph.node);
+    }
+}
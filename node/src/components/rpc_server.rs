@@ -13,47 +13,59 @@
 //!
 //! For the list of supported RPC methods, see:
 //! <https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs>
+//!
+//! Operator RPCs are not part of the public API and are only served on the mutually authenticated
+//! admin API, if enabled.
+//!
+//! A subset of the queries is also available over gRPC, if enabled.
 
+mod admin_server;
 mod config;
 mod event;
+mod grpc_server;
 mod http_server;
+mod metrics;
 pub mod rpcs;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::{convert::Infallible, fmt::Debug, sync::Arc};
 
 use datasize::DataSize;
 use futures::join;
+use prometheus::Registry;
 use semver::Version;
+use tracing::{info, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
-        self, BalanceRequest, BalanceResult, GetBidsRequest, GetEraValidatorsError, QueryRequest,
+        self, BalanceBatchRequest, BalanceBatchResult, BalanceRequest, BalanceResult,
+        GetBidsResult, GetEraValidatorsError, QueryBatchRequest, QueryBatchResult, QueryRequest,
         QueryResult,
     },
     storage::protocol_data::ProtocolData,
 };
 use casper_types::{system::auction::EraValidators, Key, ProtocolVersion, URef};
 
-use self::rpcs::chain::BlockIdentifier;
+use self::{http_server::RateLimiters, metrics::RpcMetrics, rpcs::chain::BlockIdentifier};
 
 use super::Component;
 use crate::{
-    components::contract_runtime::EraValidatorsRequest,
+    components::{contract_runtime::EraValidatorsRequest, event_stream_server::SseEventType},
     crypto::hash::Digest,
     effect::{
         announcements::RpcServerAnnouncement,
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, LinearChainRequest,
-            MetricsRequest, NetworkInfoRequest, RpcRequest, StorageRequest,
+            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, DumpStateRequest,
+            LinearChainRequest, MetricsRequest, NetworkInfoRequest, RpcRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects, Responder,
     },
     types::{NodeId, StatusFeed},
-    utils::{self, ListeningError},
+    utils::{self, ListeningError, Scheduler, WithDir},
     NodeRng,
 };
 
-pub use config::Config;
+pub use config::{AdminConfig, Config, GrpcConfig, QueryBatchConfig};
+
 pub(crate) use event::Event;
 
 /// A helper trait capturing all of this components Request type dependencies.
@@ -64,6 +76,7 @@ pub trait ReactorEventT:
     + From<ChainspecLoaderRequest>
     + From<ContractRuntimeRequest>
     + From<ConsensusRequest>
+    + From<DumpStateRequest>
     + From<LinearChainRequest<NodeId>>
     + From<MetricsRequest>
     + From<NetworkInfoRequest<NodeId>>
@@ -79,6 +92,7 @@ impl<REv> ReactorEventT for REv where
         + From<ChainspecLoaderRequest>
         + From<ContractRuntimeRequest>
         + From<ConsensusRequest>
+        + From<DumpStateRequest>
         + From<LinearChainRequest<NodeId>>
         + From<MetricsRequest>
         + From<NetworkInfoRequest<NodeId>>
@@ -89,30 +103,99 @@ impl<REv> ReactorEventT for REv where
 }
 
 #[derive(DataSize, Debug)]
-pub(crate) struct RpcServer {}
+pub(crate) struct RpcServer {
+    /// The configuration currently in effect.
+    config: Config,
+    /// The rate limiters shared with the running HTTP server.
+    #[data_size(skip)]
+    rate_limiters: RateLimiters,
+    /// The scheduler of the components' periodic jobs, listed in the status.
+    #[data_size(skip)]
+    scheduler: Scheduler,
+    /// The types of events sent on the event stream, listed in the status.
+    event_stream_events: Vec<SseEventType>,
+}
 
 impl RpcServer {
     pub(crate) fn new<REv>(
-        config: Config,
+        config: WithDir<Config>,
         effect_builder: EffectBuilder<REv>,
         api_version: Version,
+        registry: &Registry,
+        scheduler: Scheduler,
+        event_stream_events: Vec<SseEventType>,
     ) -> Result<Self, ListeningError>
     where
         REv: ReactorEventT,
     {
+        if config.value().admin.enable_server {
+            let admin_config = config.map_ref(|config| config.admin.clone());
+            if let Some((listener, acceptor)) = admin_server::setup(&admin_config) {
+                tokio::spawn(admin_server::run(
+                    listener,
+                    acceptor,
+                    effect_builder,
+                    api_version.clone(),
+                ));
+            }
+        }
+
+        if config.value().grpc.enable_server {
+            if let Some(listener) = grpc_server::setup(&config.value().grpc) {
+                tokio::spawn(grpc_server::run(
+                    listener,
+                    effect_builder,
+                    api_version.clone(),
+                ));
+            }
+        }
+
+        let config = config.value().clone();
+        let rate_limiters = RateLimiters::new(&config.rate_limits, registry)?;
+        let metrics = Arc::new(RpcMetrics::new(registry)?);
         let builder = utils::start_listening(&config.address)?;
         tokio::spawn(http_server::run(
             builder,
             effect_builder,
             api_version,
             config.qps_limit,
+            rate_limiters.clone(),
+            config.concurrency_limits.clone(),
+            metrics,
         ));
 
-        Ok(RpcServer {})
+        Ok(RpcServer {
+            config,
+            rate_limiters,
+            scheduler,
+            event_stream_events,
+        })
     }
 }
 
 impl RpcServer {
+    /// Applies the rate limits and query batch limits of a reloaded configuration.
+    ///
+    /// The update is rejected as a whole if any other setting was changed, since the server would
+    /// have to be restarted for those to take effect.
+    fn handle_config_updated(&mut self, config: Config) -> Effects<Event> {
+        if self.config.requires_restart_to_become(&config) {
+            warn!(
+                "rejecting reloaded RPC server config: only the rate limits and query batch limits \
+                can be changed without restarting the node"
+            );
+            return Effects::new();
+        }
+        self.rate_limiters.update(&config.rate_limits);
+        info!(
+            rate_limits = ?config.rate_limits,
+            query_batch = ?config.query_batch,
+            "updated RPC server limits"
+        );
+        self.config = config;
+        Effects::new()
+    }
+
     fn handle_protocol_data<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -144,6 +227,27 @@ impl RpcServer {
             })
     }
 
+    fn handle_query_batch<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+        queries: Vec<(Key, Vec<String>)>,
+        responder: Responder<Result<QueryBatchResult, engine_state::Error>>,
+    ) -> Effects<Event> {
+        let request = QueryBatchRequest::new(
+            state_root_hash.into(),
+            queries,
+            self.config.query_batch.max_keys,
+            self.config.query_batch.max_response_size,
+        );
+        effect_builder
+            .query_global_state_batch(request)
+            .event(move |result| Event::QueryGlobalStateBatchResult {
+                result,
+                main_responder: responder,
+            })
+    }
+
     fn handle_era_validators<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -160,6 +264,20 @@ impl RpcServer {
             })
     }
 
+    fn handle_get_bids<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+        responder: Responder<Result<GetBidsResult, engine_state::Error>>,
+    ) -> Effects<Event> {
+        effect_builder
+            .get_bids(state_root_hash.into())
+            .event(move |result| Event::GetBidsResult {
+                result,
+                main_responder: responder,
+            })
+    }
+
     fn handle_get_balance<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -175,6 +293,22 @@ impl RpcServer {
                 main_responder: responder,
             })
     }
+
+    fn handle_get_balance_batch<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+        keys: Vec<Key>,
+        responder: Responder<Result<BalanceBatchResult, engine_state::Error>>,
+    ) -> Effects<Event> {
+        let request = BalanceBatchRequest::new(state_root_hash.into(), keys);
+        effect_builder
+            .get_balance_batch(request)
+            .event(move |result| Event::GetBalanceBatchResult {
+                result,
+                main_responder: responder,
+            })
+    }
 }
 
 impl<REv> Component<REv> for RpcServer
@@ -194,6 +328,13 @@ where
             Event::RpcRequest(RpcRequest::SubmitDeploy { deploy, responder }) => effect_builder
                 .announce_deploy_received(deploy, Some(responder))
                 .ignore(),
+            Event::RpcRequest(RpcRequest::AddDeployApprovals {
+                deploy_hash,
+                approvals,
+                responder,
+            }) => effect_builder
+                .announce_deploy_approvals_received(deploy_hash, approvals, responder)
+                .ignore(),
             Event::RpcRequest(RpcRequest::GetBlock {
                 maybe_id: Some(BlockIdentifier::Hash(hash)),
                 responder,
@@ -224,6 +365,28 @@ where
                     result: Box::new(result),
                     main_responder: responder,
                 }),
+            Event::RpcRequest(RpcRequest::GetBlockWithSignatures {
+                maybe_id,
+                responder,
+            }) => effect_builder
+                .get_block_with_signatures_from_storage(maybe_id)
+                .event(move |result| Event::GetBlockWithSignaturesResult {
+                    maybe_id,
+                    result: Box::new(result),
+                    main_responder: responder,
+                }),
+            Event::RpcRequest(RpcRequest::GetBlockRange {
+                start_height,
+                count,
+                with_deploys,
+                responder,
+            }) => async move {
+                let result = effect_builder
+                    .get_block_range_from_storage(start_height, count, with_deploys)
+                    .await;
+                responder.respond(result).await;
+            }
+            .ignore(),
             Event::RpcRequest(RpcRequest::GetBlockTransfers {
                 block_hash,
                 responder,
@@ -238,12 +401,45 @@ where
                 protocol_version,
                 responder,
             }) => self.handle_protocol_data(effect_builder, protocol_version, responder),
+            Event::RpcRequest(RpcRequest::GetCostTable {
+                protocol_version,
+                responder,
+            }) => async move {
+                let result = effect_builder.get_cost_table(protocol_version).await;
+                responder.respond(result).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::GetChainspecRawBytes {
+                protocol_version,
+                responder,
+            }) => async move {
+                let result = effect_builder
+                    .get_chainspec_raw_bytes(protocol_version)
+                    .await;
+                responder.respond(result).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::EstimatePurge {
+                state_root_hash,
+                keys,
+                responder,
+            }) => effect_builder
+                .estimate_purge(state_root_hash.into(), keys)
+                .event(move |result| Event::EstimatePurgeResult {
+                    result,
+                    main_responder: responder,
+                }),
             Event::RpcRequest(RpcRequest::QueryGlobalState {
                 state_root_hash,
                 base_key,
                 path,
                 responder,
             }) => self.handle_query(effect_builder, state_root_hash, base_key, path, responder),
+            Event::RpcRequest(RpcRequest::QueryGlobalStateBatch {
+                state_root_hash,
+                queries,
+                responder,
+            }) => self.handle_query_batch(effect_builder, state_root_hash, queries, responder),
             Event::RpcRequest(RpcRequest::QueryEraValidators {
                 state_root_hash,
                 protocol_version,
@@ -257,20 +453,17 @@ where
             Event::RpcRequest(RpcRequest::GetBids {
                 state_root_hash,
                 responder,
-            }) => {
-                let get_bids_request = GetBidsRequest::new(state_root_hash.into());
-                effect_builder
-                    .get_bids(get_bids_request)
-                    .event(move |result| Event::GetBidsResult {
-                        result,
-                        main_responder: responder,
-                    })
-            }
+            }) => self.handle_get_bids(effect_builder, state_root_hash, responder),
             Event::RpcRequest(RpcRequest::GetBalance {
                 state_root_hash,
                 purse_uref,
                 responder,
             }) => self.handle_get_balance(effect_builder, state_root_hash, purse_uref, responder),
+            Event::RpcRequest(RpcRequest::GetBalanceBatch {
+                state_root_hash,
+                keys,
+                responder,
+            }) => self.handle_get_balance_batch(effect_builder, state_root_hash, keys, responder),
             Event::RpcRequest(RpcRequest::GetDeploy { hash, responder }) => effect_builder
                 .get_deploy_and_metadata_from_storage(hash)
                 .event(move |result| Event::GetDeployResult {
@@ -278,35 +471,115 @@ where
                     result: Box::new(result),
                     main_responder: responder,
                 }),
+            Event::RpcRequest(RpcRequest::GetDeployHashesByAccount {
+                account,
+                range,
+                responder,
+            }) => effect_builder
+                .get_deploy_hashes_by_account_from_storage(account, range.clone())
+                .event(move |result| Event::GetDeployHashesByAccountResult {
+                    account,
+                    range,
+                    result,
+                    main_responder: responder,
+                }),
             Event::RpcRequest(RpcRequest::GetPeers { responder }) => effect_builder
                 .network_peers()
                 .event(move |peers| Event::GetPeersResult {
                     peers,
                     main_responder: responder,
                 }),
-            Event::RpcRequest(RpcRequest::GetStatus { responder }) => async move {
-                let (last_added_block, peers, chainspec_info, consensus_status) = join!(
-                    effect_builder.get_highest_block_from_storage(),
-                    effect_builder.network_peers(),
-                    effect_builder.get_chainspec_info(),
-                    effect_builder.consensus_status()
-                );
-                let status_feed =
-                    StatusFeed::new(last_added_block, peers, chainspec_info, consensus_status);
-                responder.respond(status_feed).await;
+            Event::RpcRequest(RpcRequest::GetBlocklist { responder }) => async move {
+                let entries = effect_builder.network_blocklist::<NodeId>().await;
+                responder.respond(entries).await;
             }
             .ignore(),
+            Event::RpcRequest(RpcRequest::ClearBlocklist { address, responder }) => async move {
+                let cleared = effect_builder
+                    .clear_network_blocklist::<NodeId>(address)
+                    .await;
+                responder.respond(cleared).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::GetStatus { responder }) => {
+                let scheduled_jobs = self.scheduler.jobs();
+                let event_stream_events = self.event_stream_events.clone();
+                async move {
+                    let (
+                        last_added_block,
+                        peers,
+                        public_endpoint,
+                        chainspec_info,
+                        consensus_status,
+                        blocks_below_strict_finality,
+                    ) = join!(
+                        effect_builder.get_highest_block_from_storage(),
+                        effect_builder.network_peers(),
+                        effect_builder.network_public_endpoint::<NodeId>(),
+                        effect_builder.get_chainspec_info(),
+                        effect_builder.consensus_status(),
+                        effect_builder.get_blocks_below_strict_finality::<NodeId>()
+                    );
+                    let status_feed = StatusFeed::new(
+                        last_added_block,
+                        (peers, public_endpoint),
+                        chainspec_info,
+                        consensus_status,
+                        blocks_below_strict_finality,
+                        scheduled_jobs,
+                        event_stream_events,
+                    );
+                    responder.respond(status_feed).await;
+                }
+                .ignore()
+            }
             Event::RpcRequest(RpcRequest::GetMetrics { responder }) => effect_builder
                 .get_metrics()
                 .event(move |text| Event::GetMetricsResult {
                     text,
                     main_responder: responder,
                 }),
+            Event::RpcRequest(RpcRequest::DumpComponentState {
+                component,
+                responder,
+            }) => effect_builder
+                .dump_component_state(component)
+                .event(move |state| Event::DumpComponentStateResult {
+                    component,
+                    state,
+                    main_responder: responder,
+                }),
+            Event::RpcRequest(RpcRequest::StageSigningKey {
+                secret_key_path,
+                activation_era,
+                responder,
+            }) => async move {
+                let result = effect_builder
+                    .stage_signing_key(secret_key_path, activation_era)
+                    .await;
+                responder.respond(result).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::CompactStorage { responder }) => async move {
+                let result = effect_builder.compact_storage().await;
+                responder.respond(result).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::GetEraStorageStats { eras, responder }) => async move {
+                let stats = effect_builder.get_era_storage_stats(eras).await;
+                responder.respond(stats).await;
+            }
+            .ignore(),
             Event::GetBlockResult {
                 maybe_id: _,
                 result,
                 main_responder,
             } => main_responder.respond(*result).ignore(),
+            Event::GetBlockWithSignaturesResult {
+                result,
+                main_responder,
+                ..
+            } => main_responder.respond(*result).ignore(),
             Event::GetBlockTransfersResult {
                 result,
                 main_responder,
@@ -316,6 +589,10 @@ where
                 result,
                 main_responder,
             } => main_responder.respond(result).ignore(),
+            Event::EstimatePurgeResult {
+                result,
+                main_responder,
+            } => main_responder.respond(result).ignore(),
             Event::QueryGlobalStateResult {
                 result,
                 main_responder,
@@ -332,11 +609,24 @@ where
                 result,
                 main_responder,
             } => main_responder.respond(result).ignore(),
+            Event::QueryGlobalStateBatchResult {
+                result,
+                main_responder,
+            } => main_responder.respond(result).ignore(),
+            Event::GetBalanceBatchResult {
+                result,
+                main_responder,
+            } => main_responder.respond(result).ignore(),
             Event::GetDeployResult {
                 hash: _,
                 result,
                 main_responder,
             } => main_responder.respond(*result).ignore(),
+            Event::GetDeployHashesByAccountResult {
+                result,
+                main_responder,
+                ..
+            } => main_responder.respond(result).ignore(),
             Event::GetPeersResult {
                 peers,
                 main_responder,
@@ -345,6 +635,12 @@ where
                 text,
                 main_responder,
             } => main_responder.respond(text).ignore(),
+            Event::DumpComponentStateResult {
+                state,
+                main_responder,
+                ..
+            } => main_responder.respond(state).ignore(),
+            Event::ConfigUpdated(config) => self.handle_config_updated(*config),
         }
     }
 }
@@ -0,0 +1,174 @@
+//! Mutually authenticated admin API.
+//!
+//! Operator endpoints, i.e. the RPCs in the [`debug`](../rpcs/debug/index.html) module, are not
+//! served by the public JSON-RPC server. Instead they are served on a separate listener which only
+//! completes TLS handshakes with clients presenting a certificate issued by the configured CA.
+
+use std::{convert::Infallible, net::TcpListener, sync::Arc, time::Duration};
+
+use futures::{channel::mpsc, future};
+use openssl::ssl::SslAcceptor;
+use semver::Version;
+use tracing::{info, trace, warn};
+use warp::{Filter, Rejection};
+
+use super::{
+    config::AdminConfig,
+    http_server::new_error_response,
+    rpcs::{self, RpcWithParamsExt, RpcWithoutParamsExt, RPC_API_PATH},
+    ReactorEventT,
+};
+use crate::{
+    effect::EffectBuilder,
+    tls,
+    utils::{resolve_address, WithDir},
+};
+
+/// The delay before accepting connections again after accepting one failed.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Binds the admin API listener and loads its TLS material.
+///
+/// Returns `None` and logs the reason if the admin API cannot be started.
+pub(super) fn setup(config: &WithDir<AdminConfig>) -> Option<(TcpListener, SslAcceptor)> {
+    let admin_config = config.value();
+    let acceptor = match load_acceptor(config) {
+        Ok(acceptor) => acceptor,
+        Err(error) => {
+            warn!(%error, "failed to load TLS material, admin API will not run");
+            return None;
+        }
+    };
+
+    let address = match resolve_address(&admin_config.address) {
+        Ok(address) => address,
+        Err(error) => {
+            warn!(%error, address = %admin_config.address, "failed to parse admin API address");
+            return None;
+        }
+    };
+    match TcpListener::bind(address) {
+        Ok(listener) => Some((listener, acceptor)),
+        Err(error) => {
+            warn!(%error, %address, "failed to bind admin API listener");
+            None
+        }
+    }
+}
+
+/// Creates a TLS acceptor requiring client certificates issued by the configured CA.
+fn load_acceptor(config: &WithDir<AdminConfig>) -> anyhow::Result<SslAcceptor> {
+    let admin_config = config.value();
+    let ca_cert = tls::load_cert(config.with_dir(admin_config.ca_certificate.clone()))?;
+    let cert = tls::load_cert(config.with_dir(admin_config.certificate.clone()))?;
+    let secret_key = tls::load_private_key(config.with_dir(admin_config.secret_key.clone()))?;
+    Ok(tls::create_mutual_tls_acceptor(
+        &cert,
+        &secret_key,
+        ca_cert,
+    )?)
+}
+
+/// Run the admin API server.
+pub(super) async fn run<REv: ReactorEventT>(
+    listener: TcpListener,
+    acceptor: SslAcceptor,
+    effect_builder: EffectBuilder<REv>,
+    api_version: Version,
+) {
+    let mut listener = match tokio::net::TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(error) => {
+            warn!(%error, "failed to convert admin API listener");
+            return;
+        }
+    };
+    if let Ok(address) = listener.local_addr() {
+        info!(%address, "started admin API server");
+    }
+
+    // Handshakes are performed on separate tasks, so that a client stalling its handshake cannot
+    // block other connections. Only authenticated connections are passed on to the server.
+    let acceptor = Arc::new(acceptor);
+    let (sender, receiver) = mpsc::unbounded();
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer_address) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    // Errors like running out of file descriptors persist for a while, so retrying
+                    // immediately would only spin.
+                    warn!(%error, "failed to accept admin API connection");
+                    tokio::time::delay_for(ACCEPT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let acceptor = Arc::clone(&acceptor);
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                match tokio_openssl::accept(&acceptor, stream).await {
+                    Ok(tls_stream) if tls_stream.ssl().peer_certificate().is_some() => {
+                        let _ = sender.unbounded_send(Ok::<_, Infallible>(tls_stream));
+                    }
+                    Ok(_) => warn!(%peer_address, "admin API client sent no certificate"),
+                    Err(error) => {
+                        warn!(%peer_address, ?error, "rejected admin API connection")
+                    }
+                }
+            });
+        }
+    });
+
+    // RPC filters.
+    let rpc_dump_component_state =
+        rpcs::debug::DumpComponentState::create_filter(effect_builder, api_version.clone());
+    let rpc_estimate_purge =
+        rpcs::debug::EstimatePurge::create_filter(effect_builder, api_version.clone());
+    let rpc_reload_config =
+        rpcs::debug::ReloadConfig::create_filter(effect_builder, api_version.clone());
+    let rpc_stage_signing_key =
+        rpcs::debug::StageSigningKey::create_filter(effect_builder, api_version.clone());
+    let rpc_compact_storage =
+        rpcs::debug::CompactStorage::create_filter(effect_builder, api_version.clone());
+    let rpc_get_blocklist =
+        rpcs::debug::GetBlocklist::create_filter(effect_builder, api_version.clone());
+    let rpc_clear_blocklist =
+        rpcs::debug::ClearBlocklist::create_filter(effect_builder, api_version);
+
+    // Catch requests where the method is not one we handle.
+    let unknown_method = warp::path(RPC_API_PATH)
+        .and(warp_json_rpc::filters::json_rpc())
+        .and_then(move |response_builder: warp_json_rpc::Builder| async move {
+            response_builder
+                .error(warp_json_rpc::Error::METHOD_NOT_FOUND)
+                .map_err(|_| warp::reject())
+        });
+
+    // Catch requests which don't parse as JSON.
+    let parse_failure = warp::path(RPC_API_PATH).and_then(move || async move {
+        let error_response = new_error_response(warp_json_rpc::Error::PARSE_ERROR);
+        Ok::<_, Rejection>(error_response)
+    });
+
+    let service = warp_json_rpc::service(
+        rpc_dump_component_state
+            .or(rpc_estimate_purge)
+            .or(rpc_reload_config)
+            .or(rpc_stage_signing_key)
+            .or(rpc_compact_storage)
+            .or(rpc_get_blocklist)
+            .or(rpc_clear_blocklist)
+            .or(unknown_method)
+            .or(parse_failure),
+    );
+    let make_svc =
+        hyper::service::make_service_fn(move |_| future::ok::<_, Infallible>(service.clone()));
+
+    let server =
+        hyper::Server::builder(hyper::server::accept::from_stream(receiver)).serve(make_svc);
+    if let Err(error) = server.await {
+        warn!(%error, "admin API server failed");
+    }
+
+    trace!("admin API server stopped");
+}
@@ -1,12 +1,42 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::{ConfigSection, RateLimitConfig};
+
 /// Default binding address for the JSON-RPC HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
 const DEFAULT_ADDRESS: &str = "0.0.0.0:0";
 /// Default rate limit in qps.
 const DEFAULT_QPS_LIMIT: u64 = 100;
+/// Default rate limits for RPCs querying the chain or global state.
+const DEFAULT_CHAIN_QUERIES_RATE_LIMIT: RateLimitConfig = RateLimitConfig::new(100, 20);
+/// Default rate limits for RPCs submitting deploys.
+const DEFAULT_ACCOUNT_SUBMISSION_RATE_LIMIT: RateLimitConfig = RateLimitConfig::new(50, 5);
+/// Default rate limits for RPCs reporting on the node itself.
+const DEFAULT_STATUS_RATE_LIMIT: RateLimitConfig = RateLimitConfig::new(100, 10);
+/// Default maximum number of keys queried by a single global state batch query.
+const DEFAULT_QUERY_BATCH_MAX_KEYS: usize = 100;
+/// Default maximum size in bytes of the values and proofs returned by a global state batch query.
+const DEFAULT_QUERY_BATCH_MAX_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+/// Default limits on the number of requests served at a time, for the RPCs reading the most data.
+const DEFAULT_CONCURRENCY_LIMITS: [(&str, usize); 5] = [
+    ("chain_get_blocks", 4),
+    ("info_get_account_deploy_hashes", 8),
+    ("query_global_state_batch", 4),
+    ("state_get_auction_info", 2),
+    ("state_get_balance_batch", 4),
+];
+/// Default binding address for the admin API.
+///
+/// Uses a random port, and only binds on the loopback interface.
+const DEFAULT_ADMIN_ADDRESS: &str = "127.0.0.1:0";
+/// Default binding address for the gRPC server.
+///
+/// Uses a random port, but binds on any interface.
+const DEFAULT_GRPC_ADDRESS: &str = "0.0.0.0:0";
 
 /// JSON-RPC HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -18,6 +48,28 @@ pub struct Config {
 
     /// Max rate limit in qps.
     pub qps_limit: u64,
+
+    /// Rate limits applied to groups of RPCs, rejecting excess requests with "429 Too Many
+    /// Requests".
+    #[serde(default)]
+    pub rate_limits: RateLimits,
+
+    /// Limits of global state batch queries.
+    #[serde(default)]
+    pub query_batch: QueryBatchConfig,
+
+    /// Maximum number of requests served at a time, by RPC method name.  Further requests wait for
+    /// their turn.  Methods not listed, or with a limit of 0, are not limited.
+    #[serde(default = "default_concurrency_limits")]
+    pub concurrency_limits: BTreeMap<String, usize>,
+
+    /// Mutually authenticated admin API configuration.
+    #[serde(default)]
+    pub admin: AdminConfig,
+
+    /// gRPC server configuration.
+    #[serde(default)]
+    pub grpc: GrpcConfig,
 }
 
 impl Config {
@@ -26,8 +78,33 @@ impl Config {
         Config {
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
+            rate_limits: RateLimits::default(),
+            query_batch: QueryBatchConfig::default(),
+            concurrency_limits: default_concurrency_limits(),
+            admin: AdminConfig::default(),
+            grpc: GrpcConfig::default(),
         }
     }
+
+    /// Returns whether `other` differs from `self` in any setting which can't be changed without
+    /// restarting the server, i.e. anything other than the rate limits and query batch limits.
+    pub(crate) fn requires_restart_to_become(&self, other: &Config) -> bool {
+        self.address != other.address
+            || self.qps_limit != other.qps_limit
+            || self.concurrency_limits != other.concurrency_limits
+            || self.admin != other.admin
+            || self.grpc != other.grpc
+    }
+
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("query_batch.max_keys", self.query_batch.max_keys, 1);
+        section.at_least(
+            "query_batch.max_response_size",
+            self.query_batch.max_response_size,
+            1,
+        );
+    }
 }
 
 impl Default for Config {
@@ -35,3 +112,110 @@ impl Default for Config {
         Config::new()
     }
 }
+
+fn default_concurrency_limits() -> BTreeMap<String, usize> {
+    DEFAULT_CONCURRENCY_LIMITS
+        .iter()
+        .map(|&(method, limit)| (method.to_string(), limit))
+        .collect()
+}
+
+/// Rate limits for the groups of RPCs served by the JSON-RPC HTTP server.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct RateLimits {
+    /// Limits for RPCs querying blocks, deploys and global state.
+    pub chain_queries: RateLimitConfig,
+
+    /// Limits for RPCs submitting deploys.
+    pub account_submission: RateLimitConfig,
+
+    /// Limits for RPCs reporting on the node, its peers and the RPC API itself.
+    pub status: RateLimitConfig,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        RateLimits {
+            chain_queries: DEFAULT_CHAIN_QUERIES_RATE_LIMIT,
+            account_submission: DEFAULT_ACCOUNT_SUBMISSION_RATE_LIMIT,
+            status: DEFAULT_STATUS_RATE_LIMIT,
+        }
+    }
+}
+
+/// Limits of the "query_global_state_batch" RPC.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct QueryBatchConfig {
+    /// Maximum number of keys in a single request.
+    pub max_keys: usize,
+
+    /// Maximum total size in bytes of the serialized values and proofs in a single response.
+    pub max_response_size: usize,
+}
+
+impl Default for QueryBatchConfig {
+    fn default() -> Self {
+        QueryBatchConfig {
+            max_keys: DEFAULT_QUERY_BATCH_MAX_KEYS,
+            max_response_size: DEFAULT_QUERY_BATCH_MAX_RESPONSE_SIZE,
+        }
+    }
+}
+
+/// Admin API configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Whether to run the admin API.
+    pub enable_server: bool,
+
+    /// Address to bind the admin API to.
+    pub address: String,
+
+    /// Path to the certificate of the CA issuing the admin API's and the operators' certificates.
+    pub ca_certificate: PathBuf,
+
+    /// Path to the admin API's certificate.
+    pub certificate: PathBuf,
+
+    /// Path to the admin API's secret key.
+    pub secret_key: PathBuf,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        AdminConfig {
+            enable_server: false,
+            address: DEFAULT_ADMIN_ADDRESS.to_string(),
+            ca_certificate: PathBuf::from("admin/ca_cert.pem"),
+            certificate: PathBuf::from("admin/cert.pem"),
+            secret_key: PathBuf::from("admin/secret_key.pem"),
+        }
+    }
+}
+
+/// gRPC server configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct GrpcConfig {
+    /// Whether to run the gRPC server.
+    pub enable_server: bool,
+
+    /// Address to bind the gRPC server to.
+    pub address: String,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        GrpcConfig {
+            enable_server: false,
+            address: DEFAULT_GRPC_ADDRESS.to_string(),
+        }
+    }
+}
@@ -1,20 +1,29 @@
 use std::{
     collections::BTreeMap,
     fmt::{self, Display, Formatter},
+    ops::Range,
 };
 
 use derive_more::From;
 
 use casper_execution_engine::{
-    core::engine_state::{self, BalanceResult, GetBidsResult, GetEraValidatorsError, QueryResult},
+    core::engine_state::{
+        self, BalanceBatchResult, BalanceResult, GetBidsResult, GetEraValidatorsError,
+        QueryBatchResult, QueryResult,
+    },
     storage::protocol_data::ProtocolData,
 };
-use casper_types::{system::auction::EraValidators, Transfer};
+use casper_types::{account::AccountHash, system::auction::EraValidators, Transfer};
 
+use super::Config;
 use crate::{
+    components::contract_runtime::PurgeEstimate,
     effect::{requests::RpcRequest, Responder},
-    rpcs::chain::BlockIdentifier,
-    types::{Block, BlockHash, BlockSignatures, Deploy, DeployHash, DeployMetadata, NodeId},
+    rpcs::{chain::BlockIdentifier, debug::DumpableComponent},
+    types::{
+        Block, BlockHash, BlockSignatures, BlockWithSignatures, Deploy, DeployHash, DeployMetadata,
+        NodeId,
+    },
 };
 
 #[derive(Debug, From)]
@@ -26,6 +35,11 @@ pub enum Event {
         result: Box<Option<(Block, BlockSignatures)>>,
         main_responder: Responder<Option<(Block, BlockSignatures)>>,
     },
+    GetBlockWithSignaturesResult {
+        maybe_id: Option<BlockIdentifier>,
+        result: Box<Option<BlockWithSignatures>>,
+        main_responder: Responder<Option<BlockWithSignatures>>,
+    },
     GetBlockTransfersResult {
         block_hash: BlockHash,
         result: Box<Option<Vec<Transfer>>>,
@@ -35,6 +49,10 @@ pub enum Event {
         result: Result<Option<Box<ProtocolData>>, engine_state::Error>,
         main_responder: Responder<Result<Option<Box<ProtocolData>>, engine_state::Error>>,
     },
+    EstimatePurgeResult {
+        result: Result<Option<PurgeEstimate>, engine_state::Error>,
+        main_responder: Responder<Result<Option<PurgeEstimate>, engine_state::Error>>,
+    },
     QueryGlobalStateResult {
         result: Result<QueryResult, engine_state::Error>,
         main_responder: Responder<Result<QueryResult, engine_state::Error>>,
@@ -52,6 +70,12 @@ pub enum Event {
         result: Box<Option<(Deploy, DeployMetadata)>>,
         main_responder: Responder<Option<(Deploy, DeployMetadata)>>,
     },
+    GetDeployHashesByAccountResult {
+        account: AccountHash,
+        range: Range<u64>,
+        result: Vec<DeployHash>,
+        main_responder: Responder<Vec<DeployHash>>,
+    },
     GetPeersResult {
         peers: BTreeMap<NodeId, String>,
         main_responder: Responder<BTreeMap<NodeId, String>>,
@@ -60,10 +84,25 @@ pub enum Event {
         text: Option<String>,
         main_responder: Responder<Option<String>>,
     },
+    QueryGlobalStateBatchResult {
+        result: Result<QueryBatchResult, engine_state::Error>,
+        main_responder: Responder<Result<QueryBatchResult, engine_state::Error>>,
+    },
     GetBalanceResult {
         result: Result<BalanceResult, engine_state::Error>,
         main_responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    GetBalanceBatchResult {
+        result: Result<BalanceBatchResult, engine_state::Error>,
+        main_responder: Responder<Result<BalanceBatchResult, engine_state::Error>>,
+    },
+    DumpComponentStateResult {
+        component: DumpableComponent,
+        state: Option<serde_json::Value>,
+        main_responder: Responder<Option<serde_json::Value>>,
+    },
+    /// The node's configuration was reloaded.
+    ConfigUpdated(Box<Config>),
 }
 
 impl Display for Event {
@@ -85,6 +124,13 @@ impl Display for Event {
                 result,
                 ..
             } => write!(formatter, "get latest block result: {:?}", result),
+            Event::GetBlockWithSignaturesResult {
+                maybe_id, result, ..
+            } => write!(
+                formatter,
+                "get block with signatures result for {:?}: {:?}",
+                maybe_id, result
+            ),
             Event::GetBlockTransfersResult {
                 block_hash, result, ..
             } => write!(
@@ -95,9 +141,15 @@ impl Display for Event {
             Event::QueryProtocolDataResult { result, .. } => {
                 write!(formatter, "query protocol data result: {:?}", result)
             }
+            Event::EstimatePurgeResult { result, .. } => {
+                write!(formatter, "estimate purge result: {:?}", result)
+            }
             Event::QueryGlobalStateResult { result, .. } => {
                 write!(formatter, "query result: {:?}", result)
             }
+            Event::QueryGlobalStateBatchResult { result, .. } => {
+                write!(formatter, "query batch result: {:?}", result)
+            }
             Event::QueryEraValidatorsResult { result, .. } => {
                 write!(formatter, "query era validators result: {:?}", result)
             }
@@ -107,14 +159,36 @@ impl Display for Event {
             Event::GetBalanceResult { result, .. } => {
                 write!(formatter, "balance result: {:?}", result)
             }
+            Event::GetBalanceBatchResult { result, .. } => {
+                write!(formatter, "balance batch result: {:?}", result)
+            }
             Event::GetDeployResult { hash, result, .. } => {
                 write!(formatter, "get deploy result for {}: {:?}", hash, result)
             }
+            Event::GetDeployHashesByAccountResult {
+                account,
+                range,
+                result,
+                ..
+            } => write!(
+                formatter,
+                "get deploy hashes {:?} for account {} result: {} deploys",
+                range,
+                account,
+                result.len()
+            ),
             Event::GetPeersResult { peers, .. } => write!(formatter, "get peers: {}", peers.len()),
             Event::GetMetricsResult { text, .. } => match text {
                 Some(txt) => write!(formatter, "get metrics ({} bytes)", txt.len()),
                 None => write!(formatter, "get metrics (failed)"),
             },
+            Event::DumpComponentStateResult {
+                component, state, ..
+            } => match state {
+                Some(_) => write!(formatter, "dumped state of {}", component),
+                None => write!(formatter, "dump state of {} (not running)", component),
+            },
+            Event::ConfigUpdated(_) => write!(formatter, "config updated"),
         }
     }
 }
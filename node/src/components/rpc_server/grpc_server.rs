@@ -0,0 +1,275 @@
+//! gRPC server.
+//!
+//! Serves a curated subset of the JSON-RPC queries over gRPC, as defined by the `NodeQuery` service
+//! in `proto/node_query.proto`.  Requests are translated into the same `RpcRequest`s as used by the
+//! JSON-RPC server.  Values without a natural protobuf representation, such as blocks or stored
+//! values, are returned in the same JSON encoding as the corresponding JSON-RPC responses.
+
+use std::{convert::TryFrom, net::TcpListener};
+
+use semver::Version;
+use serde::Serialize;
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{info, warn};
+
+use casper_types::Key;
+
+use super::{
+    config::GrpcConfig,
+    rpcs::{chain::BlockIdentifier, common, info::JsonExecutionResult},
+    ReactorEventT,
+};
+use crate::{
+    crypto::hash::Digest,
+    effect::{requests::RpcRequest, EffectBuilder},
+    reactor::QueueKind,
+    types::{BlockHash, DeployHash, GetStatusResult, JsonBlock},
+    utils::resolve_address,
+};
+
+/// Types generated from `proto/node_query.proto`.
+#[allow(missing_docs, clippy::all, unused_qualifications)]
+mod proto {
+    tonic::include_proto!("casper.node");
+}
+
+use proto::{
+    node_query_server::{NodeQuery, NodeQueryServer},
+    BlockHashRequest, BlockHeightRequest, BlockReply, DeployHashRequest, DeployReply,
+    QueryStateReply, QueryStateRequest, StatusReply, StatusRequest,
+};
+
+/// Binds the gRPC server's listener.
+///
+/// Returns `None` and logs the reason if the gRPC server cannot be started.
+pub(super) fn setup(config: &GrpcConfig) -> Option<TcpListener> {
+    let address = match resolve_address(&config.address) {
+        Ok(address) => address,
+        Err(error) => {
+            warn!(%error, address = %config.address, "failed to parse gRPC server address");
+            return None;
+        }
+    };
+    match TcpListener::bind(address) {
+        Ok(listener) => Some(listener),
+        Err(error) => {
+            warn!(%error, %address, "failed to bind gRPC server listener");
+            None
+        }
+    }
+}
+
+/// Run the gRPC server.
+pub(super) async fn run<REv: ReactorEventT>(
+    listener: TcpListener,
+    effect_builder: EffectBuilder<REv>,
+    api_version: Version,
+) {
+    let mut listener = match tokio::net::TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(error) => {
+            warn!(%error, "failed to convert gRPC server listener");
+            return;
+        }
+    };
+    if let Ok(address) = listener.local_addr() {
+        info!(%address, "started gRPC server");
+    }
+
+    let service = NodeQueryService {
+        effect_builder,
+        api_version,
+    };
+    if let Err(error) = Server::builder()
+        .add_service(NodeQueryServer::new(service))
+        .serve_with_incoming(listener.incoming())
+        .await
+    {
+        warn!(%error, "error running gRPC server");
+    }
+}
+
+/// The implementation of the `NodeQuery` service.
+struct NodeQueryService<REv: 'static> {
+    effect_builder: EffectBuilder<REv>,
+    api_version: Version,
+}
+
+impl<REv: ReactorEventT> NodeQueryService<REv> {
+    async fn get_block(&self, block_id: BlockIdentifier) -> Result<BlockReply, Status> {
+        let (block, signatures) = self
+            .effect_builder
+            .make_request(
+                |responder| RpcRequest::GetBlock {
+                    maybe_id: Some(block_id),
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await
+            .ok_or_else(|| Status::not_found("block not known"))?;
+
+        let header = block.header();
+        Ok(BlockReply {
+            block_hash: block.hash().inner().as_ref().to_vec(),
+            height: header.height(),
+            era_id: header.era_id().0,
+            timestamp_ms: header.timestamp().millis(),
+            parent_hash: header.parent_hash().inner().as_ref().to_vec(),
+            state_root_hash: header.state_root_hash().as_ref().to_vec(),
+            block_json: to_json(&JsonBlock::new(block, signatures))?,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl<REv: ReactorEventT> NodeQuery for NodeQueryService<REv> {
+    async fn get_block_by_hash(
+        &self,
+        request: Request<BlockHashRequest>,
+    ) -> Result<Response<BlockReply>, Status> {
+        let block_hash = BlockHash::new(parse_digest(&request.into_inner().block_hash)?);
+        let reply = self.get_block(BlockIdentifier::Hash(block_hash)).await?;
+        Ok(Response::new(reply))
+    }
+
+    async fn get_block_by_height(
+        &self,
+        request: Request<BlockHeightRequest>,
+    ) -> Result<Response<BlockReply>, Status> {
+        let height = request.into_inner().height;
+        let reply = self.get_block(BlockIdentifier::Height(height)).await?;
+        Ok(Response::new(reply))
+    }
+
+    async fn get_deploy(
+        &self,
+        request: Request<DeployHashRequest>,
+    ) -> Result<Response<DeployReply>, Status> {
+        let hash = DeployHash::new(parse_digest(&request.into_inner().deploy_hash)?);
+        let (deploy, metadata) = self
+            .effect_builder
+            .make_request(
+                |responder| RpcRequest::GetDeploy { hash, responder },
+                QueueKind::Api,
+            )
+            .await
+            .ok_or_else(|| Status::not_found("deploy not known"))?;
+
+        let execution_results: Vec<_> = metadata
+            .execution_results
+            .into_iter()
+            .map(|(block_hash, result)| JsonExecutionResult { block_hash, result })
+            .collect();
+        let block_hashes = execution_results
+            .iter()
+            .map(|execution_result| execution_result.block_hash.inner().as_ref().to_vec())
+            .collect();
+
+        Ok(Response::new(DeployReply {
+            deploy_hash: hash.inner().as_ref().to_vec(),
+            block_hashes,
+            deploy_json: to_json(&deploy)?,
+            execution_results_json: to_json(&execution_results)?,
+        }))
+    }
+
+    async fn get_status(
+        &self,
+        _request: Request<StatusRequest>,
+    ) -> Result<Response<StatusReply>, Status> {
+        let status_feed = self
+            .effect_builder
+            .make_request(
+                |responder| RpcRequest::GetStatus { responder },
+                QueueKind::Api,
+            )
+            .await;
+
+        let (last_added_block_hash, last_added_block_height) = match &status_feed.last_added_block {
+            Some(block) => (block.hash().inner().as_ref().to_vec(), block.height()),
+            None => (Vec::new(), 0),
+        };
+        let peer_count = u32::try_from(status_feed.peers.len()).unwrap_or(u32::MAX);
+        let status = GetStatusResult::new(status_feed, self.api_version.clone());
+
+        Ok(Response::new(StatusReply {
+            api_version: self.api_version.to_string(),
+            chainspec_name: status.chainspec_name.clone(),
+            last_added_block_hash,
+            last_added_block_height,
+            peer_count,
+            status_json: to_json(&status)?,
+        }))
+    }
+
+    async fn query_state(
+        &self,
+        request: Request<QueryStateRequest>,
+    ) -> Result<Response<QueryStateReply>, Status> {
+        let QueryStateRequest {
+            state_root_hash,
+            key,
+            path,
+        } = request.into_inner();
+        let state_root_hash = parse_digest(&state_root_hash)?;
+        let base_key = Key::from_formatted_str(&key).map_err(|error| {
+            Status::invalid_argument(format!("failed to parse key: {:?}", error))
+        })?;
+
+        let query_result = self
+            .effect_builder
+            .make_request(
+                |responder| RpcRequest::QueryGlobalState {
+                    state_root_hash,
+                    base_key,
+                    path,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        let (stored_value, merkle_proof) =
+            common::extract_query_result(query_result).map_err(|(_, error_msg)| {
+                info!("{}", error_msg);
+                Status::failed_precondition(error_msg)
+            })?;
+
+        Ok(Response::new(QueryStateReply {
+            stored_value_json: to_json(&stored_value)?,
+            merkle_proof,
+        }))
+    }
+}
+
+/// Parses a 32 byte hash from a request field.
+fn parse_digest(bytes: &[u8]) -> Result<Digest, Status> {
+    Digest::try_from(bytes).map_err(|_| {
+        Status::invalid_argument(format!(
+            "expected a {} byte hash, got {} bytes",
+            Digest::LENGTH,
+            bytes.len()
+        ))
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, Status> {
+    serde_json::to_string(value)
+        .map_err(|error| Status::internal(format!("failed to encode reply: {}", error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_hashes_of_wrong_length() {
+        assert!(parse_digest(&[1; Digest::LENGTH]).is_ok());
+
+        let status = parse_digest(&[1; Digest::LENGTH - 1]).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let status = parse_digest(&[]).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
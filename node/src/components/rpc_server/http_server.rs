@@ -1,27 +1,74 @@
-use std::{convert::Infallible, time::Duration};
+use std::{collections::BTreeMap, convert::Infallible, sync::Arc, time::Duration};
 
 use futures::future;
-use http::{Response, StatusCode};
+use http::{Request, Response, StatusCode};
 use hyper::{
-    server::{conn::AddrIncoming, Builder},
+    server::{
+        conn::{AddrIncoming, AddrStream},
+        Builder,
+    },
+    service::Service,
     Body,
 };
+use prometheus::Registry;
 use semver::Version;
 use serde::Serialize;
 use tokio::sync::oneshot;
 use tower::builder::ServiceBuilder;
-use tracing::{info, trace};
-use warp::{Filter, Rejection};
+use tracing::{info, trace, warn};
+use warp::{filters::BoxedFilter, Filter, Rejection};
 
 use super::{
-    rpcs::{self, RpcWithOptionalParamsExt, RpcWithParamsExt, RpcWithoutParamsExt, RPC_API_PATH},
+    config::RateLimits,
+    metrics::{self, RpcMetrics},
+    rpcs::{
+        self, RpcWithOptionalParams, RpcWithOptionalParamsExt, RpcWithParams, RpcWithParamsExt,
+        RpcWithoutParams, RpcWithoutParamsExt, RPC_API_PATH,
+    },
     ReactorEventT,
 };
-use crate::effect::EffectBuilder;
+use crate::{
+    effect::EffectBuilder,
+    utils::rate_limiter::{ClientIp, RateLimiter},
+};
+
+/// Rate limiters for the groups of RPCs.
+#[derive(Clone, Debug)]
+pub(super) struct RateLimiters {
+    chain_queries: Arc<RateLimiter>,
+    account_submission: Arc<RateLimiter>,
+    status: Arc<RateLimiter>,
+}
+
+impl RateLimiters {
+    pub(super) fn new(config: &RateLimits, registry: &Registry) -> Result<Self, prometheus::Error> {
+        Ok(RateLimiters {
+            chain_queries: Arc::new(RateLimiter::new(
+                "rpc_chain_queries",
+                config.chain_queries,
+                registry,
+            )?),
+            account_submission: Arc::new(RateLimiter::new(
+                "rpc_account_submission",
+                config.account_submission,
+                registry,
+            )?),
+            status: Arc::new(RateLimiter::new("rpc_status", config.status, registry)?),
+        })
+    }
+
+    /// Applies new limits to the running server.
+    pub(super) fn update(&self, config: &RateLimits) {
+        self.chain_queries.update_config(config.chain_queries);
+        self.account_submission
+            .update_config(config.account_submission);
+        self.status.update_config(config.status);
+    }
+}
 
 // This is a workaround for not being able to create a `warp_json_rpc::Response` without a
 // `warp_json_rpc::Builder`.
-fn new_error_response(error: warp_json_rpc::Error) -> Response<Body> {
+pub(super) fn new_error_response(error: warp_json_rpc::Error) -> Response<Body> {
     #[derive(Serialize)]
     struct JsonRpcErrorResponse {
         jsonrpc: String,
@@ -43,32 +90,221 @@ fn new_error_response(error: warp_json_rpc::Error) -> Response<Body> {
         .unwrap()
 }
 
+/// Applies the rate limits of `limiter` and the concurrency limit configured in
+/// `concurrency_limits` to the RPC named `method` and records its metrics.
+///
+/// The method's entry is removed from `concurrency_limits`, so that the remaining ones can be
+/// reported as unknown.
+fn serve(
+    method: &'static str,
+    limiter: &Arc<RateLimiter>,
+    concurrency_limits: &mut BTreeMap<String, usize>,
+    metrics: &Arc<RpcMetrics>,
+    filter: BoxedFilter<(Response<Body>,)>,
+) -> BoxedFilter<(Response<Body>,)> {
+    let filter = match concurrency_limits.remove(method) {
+        Some(limit) if limit > 0 => rpcs::concurrency_limited(method, limit, filter),
+        _ => filter,
+    };
+    metrics::metered(method, metrics, rpcs::rate_limited(method, limiter, filter))
+}
+
 /// Run the JSON-RPC server.
 pub(super) async fn run<REv: ReactorEventT>(
     builder: Builder<AddrIncoming>,
     effect_builder: EffectBuilder<REv>,
     api_version: Version,
     qps_limit: u64,
+    rate_limiters: RateLimiters,
+    mut concurrency_limits: BTreeMap<String, usize>,
+    metrics: Arc<RpcMetrics>,
 ) {
+    let RateLimiters {
+        chain_queries,
+        account_submission,
+        status,
+    } = rate_limiters;
+
     // RPC filters.
-    let rpc_put_deploy =
-        rpcs::account::PutDeploy::create_filter(effect_builder, api_version.clone());
-    let rpc_get_block = rpcs::chain::GetBlock::create_filter(effect_builder, api_version.clone());
-    let rpc_get_block_transfers =
-        rpcs::chain::GetBlockTransfers::create_filter(effect_builder, api_version.clone());
-    let rpc_get_state_root_hash =
-        rpcs::chain::GetStateRootHash::create_filter(effect_builder, api_version.clone());
-    let rpc_get_item = rpcs::state::GetItem::create_filter(effect_builder, api_version.clone());
-    let rpc_get_balance =
-        rpcs::state::GetBalance::create_filter(effect_builder, api_version.clone());
-    let rpc_get_deploy = rpcs::info::GetDeploy::create_filter(effect_builder, api_version.clone());
-    let rpc_get_peers = rpcs::info::GetPeers::create_filter(effect_builder, api_version.clone());
-    let rpc_get_status = rpcs::info::GetStatus::create_filter(effect_builder, api_version.clone());
-    let rpc_get_era_info =
-        rpcs::chain::GetEraInfoBySwitchBlock::create_filter(effect_builder, api_version.clone());
-    let rpc_get_auction_info =
-        rpcs::state::GetAuctionInfo::create_filter(effect_builder, api_version.clone());
-    let rpc_get_rpcs = rpcs::docs::ListRpcs::create_filter(effect_builder, api_version);
+    let rpc_put_deploy = serve(
+        rpcs::account::PutDeploy::METHOD,
+        &account_submission,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::account::PutDeploy::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_put_deploy_approvals = serve(
+        rpcs::account::PutDeployApprovals::METHOD,
+        &account_submission,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::account::PutDeployApprovals::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_block = serve(
+        rpcs::chain::GetBlock::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetBlock::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_block_with_signatures = serve(
+        rpcs::chain::GetBlockWithSignatures::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetBlockWithSignatures::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_blocks = serve(
+        rpcs::chain::GetBlocks::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetBlocks::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_block_transfers = serve(
+        rpcs::chain::GetBlockTransfers::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetBlockTransfers::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_block_transfers_v2 = serve(
+        rpcs::chain::GetBlockTransfersV2::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetBlockTransfersV2::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_deploy_inclusion_proof = serve(
+        rpcs::chain::GetDeployInclusionProof::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetDeployInclusionProof::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_state_root_hash = serve(
+        rpcs::chain::GetStateRootHash::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetStateRootHash::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_item = serve(
+        rpcs::state::GetItem::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::state::GetItem::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_balance = serve(
+        rpcs::state::GetBalance::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::state::GetBalance::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_balance_batch = serve(
+        rpcs::state::GetBalanceBatch::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::state::GetBalanceBatch::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_query_global_state_batch = serve(
+        rpcs::state::QueryGlobalStateBatch::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::state::QueryGlobalStateBatch::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_deploy = serve(
+        rpcs::info::GetDeploy::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetDeploy::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_deploy_effects = serve(
+        rpcs::info::GetDeployEffects::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetDeployEffects::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_deploy_approvals = serve(
+        rpcs::info::GetDeployApprovals::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetDeployApprovals::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_account_deploy_hashes = serve(
+        rpcs::info::GetAccountDeployHashes::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetAccountDeployHashes::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_peers = serve(
+        rpcs::info::GetPeers::METHOD,
+        &status,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetPeers::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_status = serve(
+        rpcs::info::GetStatus::METHOD,
+        &status,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetStatus::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_era_info = serve(
+        rpcs::chain::GetEraInfoBySwitchBlock::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::chain::GetEraInfoBySwitchBlock::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_auction_info = serve(
+        rpcs::state::GetAuctionInfo::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::state::GetAuctionInfo::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_cost_table = serve(
+        rpcs::info::GetCostTable::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetCostTable::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_chainspec = serve(
+        rpcs::info::GetChainspec::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetChainspec::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_era_storage_stats = serve(
+        rpcs::info::GetEraStorageStats::METHOD,
+        &chain_queries,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::info::GetEraStorageStats::create_filter(effect_builder, api_version.clone()),
+    );
+    let rpc_get_rpcs = serve(
+        rpcs::docs::ListRpcs::METHOD,
+        &status,
+        &mut concurrency_limits,
+        &metrics,
+        rpcs::docs::ListRpcs::create_filter(effect_builder, api_version),
+    );
+
+    for method in concurrency_limits.keys() {
+        warn!(%method, "ignoring concurrency limit of unknown RPC");
+    }
 
     // Catch requests where the method is not one we handle.
     let unknown_method = warp::path(RPC_API_PATH)
@@ -90,24 +326,46 @@ pub(super) async fn run<REv: ReactorEventT>(
     //        update to or move away from warp_json_rpc.
     let service = warp_json_rpc::service(
         rpc_put_deploy
+            .or(rpc_put_deploy_approvals)
             .or(rpc_get_block)
+            .or(rpc_get_block_with_signatures)
+            .or(rpc_get_blocks)
             .or(rpc_get_block_transfers)
+            .or(rpc_get_block_transfers_v2)
+            .or(rpc_get_deploy_inclusion_proof)
             .or(rpc_get_state_root_hash)
             .or(rpc_get_item)
             .or(rpc_get_balance)
+            .or(rpc_get_balance_batch)
+            .or(rpc_query_global_state_batch)
             .or(rpc_get_deploy)
+            .or(rpc_get_deploy_effects)
+            .or(rpc_get_deploy_approvals)
+            .or(rpc_get_account_deploy_hashes)
             .or(rpc_get_peers)
             .or(rpc_get_status)
             .or(rpc_get_era_info)
             .or(rpc_get_auction_info)
+            .or(rpc_get_cost_table)
+            .or(rpc_get_chainspec)
+            .or(rpc_get_era_storage_stats)
             .or(rpc_get_rpcs)
             .or(unknown_method)
             .or(parse_failure),
     );
 
     // Start the server, passing a oneshot receiver to allow the server to be shut down gracefully.
-    let make_svc =
-        hyper::service::make_service_fn(move |_| future::ok::<_, Infallible>(service.clone()));
+    // Each request is tagged with the client's IP address, for use by the rate limiters.
+    let make_svc = hyper::service::make_service_fn(move |connection: &AddrStream| {
+        let client_ip = ClientIp(connection.remote_addr().ip());
+        let mut service = service.clone();
+        future::ok::<_, Infallible>(hyper::service::service_fn(
+            move |mut request: Request<Body>| {
+                request.extensions_mut().insert(client_ip);
+                service.call(request)
+            },
+        ))
+    });
 
     let make_svc = ServiceBuilder::new()
         .rate_limit(qps_limit, Duration::from_secs(1))
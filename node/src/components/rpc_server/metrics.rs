@@ -0,0 +1,259 @@
+//! Metrics of the JSON-RPC server.
+//!
+//! Every RPC served is counted by method name and outcome, and its latency is recorded by method
+//! name, allowing the error rate and response times of the public API to be monitored.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future;
+use http::{Response, StatusCode};
+use hyper::Body;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use serde_json::Value;
+use warp::{filters::BoxedFilter, Filter, Rejection};
+
+use super::rpcs::{self, ErrorCode};
+use crate::unregister_metric;
+
+/// The JSON-RPC "Internal error" code.
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Error codes which indicate a failure of the node rather than a bad request.
+const SERVER_ERROR_CODES: [i64; 4] = [
+    INTERNAL_ERROR_CODE,
+    ErrorCode::QueryFailedToExecute as i64,
+    ErrorCode::GetBalanceFailedToExecute as i64,
+    ErrorCode::ComponentNotRunning as i64,
+];
+
+/// Upper bound of the first latency bucket, in seconds.
+const LATENCY_BUCKET_START: f64 = 0.001;
+/// Multiplier of previous upper bound for next bound.
+const LATENCY_BUCKET_FACTOR: f64 = 4.0;
+/// Bucket count, with last going to +Inf.
+const LATENCY_BUCKET_COUNT: usize = 8;
+
+/// The outcome of serving an RPC.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Outcome {
+    /// The RPC returned a result.
+    Success,
+    /// The request was invalid, referred to unknown data or was rate limited.
+    ClientError,
+    /// The node failed to process a valid request.
+    ServerError,
+}
+
+impl Outcome {
+    fn from_error_code(code: i64) -> Self {
+        if SERVER_ERROR_CODES.contains(&code) {
+            Outcome::ServerError
+        } else {
+            Outcome::ClientError
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Success => write!(formatter, "success"),
+            Outcome::ClientError => write!(formatter, "client_error"),
+            Outcome::ServerError => write!(formatter, "server_error"),
+        }
+    }
+}
+
+/// Metrics of the JSON-RPC server.
+#[derive(Debug)]
+pub(super) struct RpcMetrics {
+    /// Number of RPCs served, by method and outcome.
+    requests: IntCounterVec,
+    /// Time taken to serve RPCs, by method.
+    latency: HistogramVec,
+    /// Instance of registry to unregister from when being dropped.
+    registry: Registry,
+}
+
+impl RpcMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "rpc_requests",
+                "number of JSON-RPC requests served, by method and outcome",
+            ),
+            &["method", "outcome"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "rpc_request_duration_seconds",
+                "time in seconds to serve JSON-RPC requests, by method",
+            )
+            .buckets(prometheus::exponential_buckets(
+                LATENCY_BUCKET_START,
+                LATENCY_BUCKET_FACTOR,
+                LATENCY_BUCKET_COUNT,
+            )?),
+            &["method"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        Ok(RpcMetrics {
+            requests,
+            latency,
+            registry: registry.clone(),
+        })
+    }
+
+    fn record(&self, method: &str, outcome: Outcome) {
+        self.requests
+            .with_label_values(&[method, &outcome.to_string()])
+            .inc();
+    }
+
+    fn observe(&self, method: &str, outcome: Outcome, elapsed: Duration) {
+        self.record(method, outcome);
+        self.latency
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+    }
+}
+
+impl Drop for RpcMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.requests);
+        unregister_metric!(self.registry, self.latency);
+    }
+}
+
+/// Wraps the filter of the RPC named `method` so that its outcome and latency are recorded in
+/// `metrics`.
+///
+/// Requests for other methods pass through unrecorded.
+pub(super) fn metered(
+    method: &'static str,
+    metrics: &Arc<RpcMetrics>,
+    filter: BoxedFilter<(Response<Body>,)>,
+) -> BoxedFilter<(Response<Body>,)> {
+    let on_response = Arc::clone(metrics);
+    let on_rejection = Arc::clone(metrics);
+    warp::any()
+        .map(Instant::now)
+        .and(filter)
+        .and_then(move |start: Instant, response: Response<Body>| {
+            let metrics = Arc::clone(&on_response);
+            async move {
+                let (response, outcome) = classify(response).await;
+                metrics.observe(method, outcome, start.elapsed());
+                Ok::<_, Rejection>(response)
+            }
+        })
+        .or_else(move |rejection: Rejection| {
+            // Handlers reject with an `rpcs::Error` if they fail to build a response at all.
+            if rejection.find::<rpcs::Error>().is_some() {
+                on_rejection.record(method, Outcome::ServerError);
+            }
+            future::err::<(Response<Body>,), _>(rejection)
+        })
+        .boxed()
+}
+
+/// Determines the outcome of an RPC from its response.
+///
+/// JSON-RPC errors are returned with a status of "200 OK", so the body is buffered to inspect its
+/// error code, if any, and the response is rebuilt from it.
+async fn classify(response: Response<Body>) -> (Response<Body>, Outcome) {
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return (response, Outcome::ClientError);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return (
+                Response::from_parts(parts, Body::empty()),
+                Outcome::ServerError,
+            )
+        }
+    };
+    let outcome = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|value| value.get("error")?.get("code")?.as_i64())
+        .map_or(Outcome::Success, Outcome::from_error_code);
+    (Response::from_parts(parts, Body::from(bytes)), outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_response(body: &str) -> Response<Body> {
+        Response::new(Body::from(body.to_string()))
+    }
+
+    #[tokio::test]
+    async fn should_classify_responses() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"api_version":"1.0.0"}}"#;
+        let (response, outcome) = classify(json_response(body)).await;
+        assert_eq!(outcome, Outcome::Success);
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(bytes, body.as_bytes());
+
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"error":{{"code":{},"message":"no such block"}}}}"#,
+            ErrorCode::NoSuchBlock as i64
+        );
+        let (_, outcome) = classify(json_response(&body)).await;
+        assert_eq!(outcome, Outcome::ClientError);
+
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"error":{{"code":{},"message":"not running"}}}}"#,
+            ErrorCode::ComponentNotRunning as i64
+        );
+        let (_, outcome) = classify(json_response(&body)).await;
+        assert_eq!(outcome, Outcome::ServerError);
+
+        let mut rate_limited = json_response("");
+        *rate_limited.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+        let (_, outcome) = classify(rate_limited).await;
+        assert_eq!(outcome, Outcome::ClientError);
+    }
+
+    #[test]
+    fn should_label_by_method_and_outcome() {
+        let metrics = RpcMetrics::new(&Registry::new()).unwrap();
+        metrics.observe(
+            "info_get_status",
+            Outcome::Success,
+            Duration::from_millis(3),
+        );
+        metrics.observe(
+            "info_get_status",
+            Outcome::Success,
+            Duration::from_millis(5),
+        );
+        metrics.record("chain_get_block", Outcome::ServerError);
+
+        let count = |method: &str, outcome: Outcome| {
+            metrics
+                .requests
+                .with_label_values(&[method, &outcome.to_string()])
+                .get()
+        };
+        assert_eq!(count("info_get_status", Outcome::Success), 2);
+        assert_eq!(count("info_get_status", Outcome::ClientError), 0);
+        assert_eq!(count("chain_get_block", Outcome::ServerError), 1);
+        assert_eq!(
+            metrics
+                .latency
+                .with_label_values(&["info_get_status"])
+                .get_sample_count(),
+            2
+        );
+    }
+}
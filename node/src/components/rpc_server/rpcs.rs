@@ -4,28 +4,33 @@
 
 pub mod account;
 pub mod chain;
+pub mod debug;
 pub mod docs;
 pub mod info;
 pub mod state;
 
-use std::str;
+use std::{str, sync::Arc};
 
 use futures::{future::BoxFuture, TryFutureExt};
-use http::Response;
+use http::{Response, StatusCode};
 use hyper::Body;
 use schemars::JsonSchema;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use warp::{
     filters::BoxedFilter,
     reject::{self, Reject},
-    Filter,
+    Filter, Rejection,
 };
 use warp_json_rpc::{filters, Builder};
 
 use super::{ReactorEventT, RpcRequest};
-use crate::effect::EffectBuilder;
+use crate::{
+    effect::EffectBuilder,
+    utils::rate_limiter::{self, RateLimiter},
+};
 use docs::DocExample;
 
 /// The URL path.
@@ -35,7 +40,7 @@ pub const RPC_API_PATH: &str = "rpc";
 ///
 /// See <https://www.jsonrpc.org/specification#error_object> for details.
 #[repr(i64)]
-enum ErrorCode {
+pub(crate) enum ErrorCode {
     NoSuchDeploy = -32000,
     NoSuchBlock = -32001,
     ParseQueryKey = -32002,
@@ -45,6 +50,17 @@ enum ErrorCode {
     GetBalanceFailed = -32006,
     GetBalanceFailedToExecute = -32007,
     InvalidDeploy = -32008,
+    ComponentNotRunning = -32009,
+    RateLimited = -32010,
+    BalanceBatchTooLarge = -32011,
+    StageSigningKeyFailed = -32012,
+    CompactStorageFailed = -32013,
+    NoSuchProtocolVersion = -32014,
+    DeployNotIncluded = -32015,
+    QueryBatchTooManyKeys = -32016,
+    QueryBatchResponseTooLarge = -32017,
+    NoSuchDeployEffectsPage = -32018,
+    PurgeEstimateTooManyKeys = -32019,
 }
 
 #[derive(Debug)]
@@ -58,6 +74,50 @@ impl From<anyhow::Error> for Error {
     }
 }
 
+/// Wraps the filter of the RPC named `method` so that requests exceeding the rate limits of
+/// `limiter` are answered with "429 Too Many Requests" instead.
+pub(super) fn rate_limited(
+    method: &'static str,
+    limiter: &Arc<RateLimiter>,
+    filter: BoxedFilter<(Response<Body>,)>,
+) -> BoxedFilter<(Response<Body>,)> {
+    let limit_exceeded = warp::path(RPC_API_PATH)
+        .and(filters::json_rpc())
+        .and(filters::method(method))
+        .and(rate_limiter::exceeded(Arc::clone(limiter)))
+        .and_then(move |response_builder: Builder| async move {
+            let mut response = response_builder
+                .error(warp_json_rpc::Error::custom(
+                    ErrorCode::RateLimited as i64,
+                    "rate limit exceeded",
+                ))
+                .map_err(|_| reject::reject())?;
+            *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            Ok::<_, Rejection>(response)
+        });
+    limit_exceeded.or(filter).unify().boxed()
+}
+
+/// Wraps the filter of the RPC named `method` so that at most `limit` requests to it are served at
+/// a time, with further requests waiting until one of them has completed.
+pub(super) fn concurrency_limited(
+    method: &'static str,
+    limit: usize,
+    filter: BoxedFilter<(Response<Body>,)>,
+) -> BoxedFilter<(Response<Body>,)> {
+    let semaphore = Arc::new(Semaphore::new(limit));
+    // The path is left to `filter`, which would not match once it has been consumed.
+    filters::json_rpc()
+        .and(filters::method(method))
+        .and_then(move |_response_builder: Builder| {
+            let semaphore = Arc::clone(&semaphore);
+            async move { Ok::<_, Rejection>(semaphore.acquire_owned().await) }
+        })
+        .and(filter)
+        .map(|_permit: OwnedSemaphorePermit, response: Response<Body>| response)
+        .boxed()
+}
+
 /// A JSON-RPC requiring the "params" field to be present.
 pub trait RpcWithParams {
     /// The JSON-RPC "method" name.
@@ -275,7 +335,7 @@ pub(super) trait RpcWithOptionalParamsExt: RpcWithOptionalParams {
     ) -> BoxFuture<'static, Result<Response<Body>, Error>>;
 }
 
-mod common {
+pub(crate) mod common {
     use std::convert::TryFrom;
 
     use once_cell::sync::Lazy;
@@ -302,7 +362,7 @@ mod common {
     });
 
     // Extract the EE `(StoredValue, Vec<TrieMerkleProof<Key, StoredValue>>)` from the result.
-    pub(super) fn extract_query_result(
+    pub(crate) fn extract_query_result(
         query_result: Result<QueryResult, engine_state::Error>,
     ) -> Result<(StoredValue, Vec<u8>), (ErrorCode, String)> {
         let (value, proof) = match query_result {
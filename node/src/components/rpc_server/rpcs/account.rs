@@ -12,6 +12,7 @@ use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::info;
 use warp_json_rpc::Builder;
 
@@ -20,10 +21,13 @@ use super::{
     Error, ReactorEventT, RpcRequest, RpcWithParams, RpcWithParamsExt,
 };
 use crate::{
-    components::rpc_server::rpcs::ErrorCode,
+    components::{
+        deploy_acceptor::{self, Acceptance},
+        rpc_server::rpcs::ErrorCode,
+    },
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{Deploy, DeployHash},
+    types::{Approval, Deploy, DeployHash},
 };
 
 static PUT_DEPLOY_PARAMS: Lazy<PutDeployParams> = Lazy::new(|| PutDeployParams {
@@ -32,7 +36,19 @@ static PUT_DEPLOY_PARAMS: Lazy<PutDeployParams> = Lazy::new(|| PutDeployParams {
 static PUT_DEPLOY_RESULT: Lazy<PutDeployResult> = Lazy::new(|| PutDeployResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
     deploy_hash: *Deploy::doc_example().id(),
+    awaiting_approvals: false,
 });
+static PUT_DEPLOY_APPROVALS_PARAMS: Lazy<PutDeployApprovalsParams> =
+    Lazy::new(|| PutDeployApprovalsParams {
+        deploy_hash: *Deploy::doc_example().id(),
+        approvals: Deploy::doc_example().approvals().to_vec(),
+    });
+static PUT_DEPLOY_APPROVALS_RESULT: Lazy<PutDeployApprovalsResult> =
+    Lazy::new(|| PutDeployApprovalsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        deploy_hash: *Deploy::doc_example().id(),
+        awaiting_approvals: false,
+    });
 
 /// Params for "account_put_deploy" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -57,6 +73,9 @@ pub struct PutDeployResult {
     pub api_version: Version,
     /// The deploy hash.
     pub deploy_hash: DeployHash,
+    /// Whether the deploy's approvals don't meet its account's deployment threshold yet, in which
+    /// case it is held by the node until further approvals are added.
+    pub awaiting_approvals: bool,
 }
 
 impl DocExample for PutDeployResult {
@@ -96,13 +115,17 @@ impl RpcWithParamsExt for PutDeploy {
                 .await;
 
             match put_deploy_result {
-                Ok(_) => {
-                    info!(%deploy_hash,
-                    "deploy was stored"
-                    );
+                Ok(acceptance) => {
+                    let awaiting_approvals = acceptance == Acceptance::AwaitingApprovals;
+                    if awaiting_approvals {
+                        info!(%deploy_hash, "deploy is awaiting approvals");
+                    } else {
+                        info!(%deploy_hash, "deploy was stored");
+                    }
                     let result = Self::ResponseResult {
                         api_version,
                         deploy_hash,
+                        awaiting_approvals,
                     };
                     Ok(response_builder.success(result)?)
                 }
@@ -112,13 +135,107 @@ impl RpcWithParamsExt for PutDeploy {
                         %error,
                         "the deploy submitted by the client was invalid",
                     );
-                    Ok(response_builder.error(warp_json_rpc::Error::custom(
-                        ErrorCode::InvalidDeploy as i64,
-                        error.to_string(),
-                    ))?)
+                    Ok(response_builder.error(rejection_error(&error))?)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Params for "account_put_deploy_approvals" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PutDeployApprovalsParams {
+    /// The hash of a deploy awaiting approvals.
+    pub deploy_hash: DeployHash,
+    /// The approvals to add to the deploy.
+    pub approvals: Vec<Approval>,
+}
+
+impl DocExample for PutDeployApprovalsParams {
+    fn doc_example() -> &'static Self {
+        &*PUT_DEPLOY_APPROVALS_PARAMS
+    }
+}
+
+/// Result for "account_put_deploy_approvals" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PutDeployApprovalsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// Whether the deploy's approvals still don't meet its account's deployment threshold.
+    pub awaiting_approvals: bool,
+}
+
+impl DocExample for PutDeployApprovalsResult {
+    fn doc_example() -> &'static Self {
+        &*PUT_DEPLOY_APPROVALS_RESULT
+    }
+}
+
+/// "account_put_deploy_approvals" RPC
+pub struct PutDeployApprovals {}
+
+impl RpcWithParams for PutDeployApprovals {
+    const METHOD: &'static str = "account_put_deploy_approvals";
+    type RequestParams = PutDeployApprovalsParams;
+    type ResponseResult = PutDeployApprovalsResult;
+}
+
+impl RpcWithParamsExt for PutDeployApprovals {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let deploy_hash = params.deploy_hash;
+
+            let add_approvals_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::AddDeployApprovals {
+                        deploy_hash,
+                        approvals: params.approvals,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            match add_approvals_result {
+                Ok(acceptance) => {
+                    let awaiting_approvals = acceptance == Acceptance::AwaitingApprovals;
+                    info!(%deploy_hash, %awaiting_approvals, "added approvals to deploy");
+                    let result = Self::ResponseResult {
+                        api_version,
+                        deploy_hash,
+                        awaiting_approvals,
+                    };
+                    Ok(response_builder.success(result)?)
+                }
+                Err(error) => {
+                    info!(
+                        %deploy_hash,
+                        %error,
+                        "the approvals submitted by the client were rejected",
+                    );
+                    Ok(response_builder.error(rejection_error(&error))?)
                 }
             }
         }
         .boxed()
     }
 }
+
+/// Returns the JSON-RPC error for a rejected deploy or approvals, with the acceptance error code
+/// as its data so that clients needn't parse the message.
+fn rejection_error(error: &deploy_acceptor::Error) -> warp_json_rpc::Error {
+    warp_json_rpc::Error::custom(ErrorCode::InvalidDeploy as i64, error.to_string())
+        .with_data(json!({ "code": error.code() as u16 }))
+}
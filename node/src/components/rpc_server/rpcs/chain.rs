@@ -17,18 +17,22 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use casper_types::{Key, Transfer};
+use casper_types::{account::AccountHash, Key, PublicKey, Transfer, URef, U512};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithOptionalParams, RpcWithOptionalParamsExt,
+    RpcWithParams, RpcWithParamsExt,
 };
 use crate::{
     crypto::hash::Digest,
     effect::EffectBuilder,
     reactor::QueueKind,
     rpcs::common::{self},
-    types::{Block, BlockHash, BlockSignatures, Item, JsonBlock},
+    types::{
+        Block, BlockHash, BlockSignatures, BlockWithDeploys, BlockWithSignatures, Deploy,
+        DeployHash, DeployInclusionProof, FinalityStatus, Item, JsonBlock, JsonBlockHeader,
+    },
 };
 pub use era_summary::EraSummary;
 use era_summary::ERA_SUMMARY;
@@ -40,6 +44,40 @@ static GET_BLOCK_RESULT: Lazy<GetBlockResult> = Lazy::new(|| GetBlockResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
     block: Some(JsonBlock::doc_example().clone()),
 });
+static GET_BLOCK_WITH_SIGNATURES_PARAMS: Lazy<GetBlockWithSignaturesParams> =
+    Lazy::new(|| GetBlockWithSignaturesParams {
+        block_identifier: BlockIdentifier::Hash(Block::doc_example().id()),
+    });
+static GET_BLOCK_WITH_SIGNATURES_RESULT: Lazy<GetBlockWithSignaturesResult> =
+    Lazy::new(|| GetBlockWithSignaturesResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        block: Some(JsonBlock::doc_example().clone()),
+        era_validator_weights: Block::doc_example()
+            .header()
+            .next_era_validator_weights()
+            .map(|weights| {
+                weights
+                    .iter()
+                    .map(|(public_key, weight)| EraValidatorWeight {
+                        public_key: public_key.clone(),
+                        weight: *weight,
+                    })
+                    .collect()
+            }),
+        finality_status: Some(FinalityStatus::Strict),
+    });
+static GET_BLOCKS_PARAMS: Lazy<GetBlocksParams> = Lazy::new(|| GetBlocksParams {
+    start_height: Block::doc_example().header().height(),
+    count: 10,
+    with_deploys: true,
+});
+static GET_BLOCKS_RESULT: Lazy<GetBlocksResult> = Lazy::new(|| GetBlocksResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    blocks: vec![JsonBlockWithDeploys {
+        block: JsonBlock::doc_example().clone(),
+        deploys: Some(vec![Deploy::doc_example().clone()]),
+    }],
+});
 static GET_BLOCK_TRANSFERS_PARAMS: Lazy<GetBlockTransfersParams> =
     Lazy::new(|| GetBlockTransfersParams {
         block_identifier: BlockIdentifier::Hash(Block::doc_example().id()),
@@ -50,6 +88,24 @@ static GET_BLOCK_TRANSFERS_RESULT: Lazy<GetBlockTransfersResult> =
         block_hash: Some(Block::doc_example().id()),
         transfers: Some(vec![Transfer::default()]),
     });
+static GET_BLOCK_TRANSFERS_V2_RESULT: Lazy<GetBlockTransfersV2Result> =
+    Lazy::new(|| GetBlockTransfersV2Result {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        block_hash: Some(Block::doc_example().id()),
+        transfers: Some(vec![BlockTransfer::from(Transfer::default())]),
+    });
+static GET_DEPLOY_INCLUSION_PROOF_PARAMS: Lazy<GetDeployInclusionProofParams> =
+    Lazy::new(|| GetDeployInclusionProofParams {
+        deploy_hash: *Deploy::doc_example().id(),
+    });
+static GET_DEPLOY_INCLUSION_PROOF_RESULT: Lazy<GetDeployInclusionProofResult> =
+    Lazy::new(|| GetDeployInclusionProofResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        block_hash: Block::doc_example().id(),
+        block_header: JsonBlockHeader::from(Block::doc_example().header().clone()),
+        proof: DeployInclusionProof::new(Block::doc_example(), Deploy::doc_example().id())
+            .expect("example block should include example deploy"),
+    });
 static GET_STATE_ROOT_HASH_PARAMS: Lazy<GetStateRootHashParams> =
     Lazy::new(|| GetStateRootHashParams {
         block_identifier: BlockIdentifier::Height(Block::doc_example().header().height()),
@@ -153,6 +209,231 @@ impl RpcWithOptionalParamsExt for GetBlock {
     }
 }
 
+/// Params for "chain_get_block_with_signatures" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockWithSignaturesParams {
+    /// The block hash or height.
+    pub block_identifier: BlockIdentifier,
+}
+
+impl DocExample for GetBlockWithSignaturesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_WITH_SIGNATURES_PARAMS
+    }
+}
+
+/// The weight of a validator in the era of a block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraValidatorWeight {
+    /// The validator's public key.
+    pub public_key: PublicKey,
+    /// The validator's weight.
+    pub weight: U512,
+}
+
+/// Result for "chain_get_block_with_signatures" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockWithSignaturesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The block with all its known finality signatures, if found.
+    pub block: Option<JsonBlock>,
+    /// The validator weights of the block's era, if known.
+    pub era_validator_weights: Option<Vec<EraValidatorWeight>>,
+    /// The finality status the block's signatures amount to, if the era's validators are known.
+    pub finality_status: Option<FinalityStatus>,
+}
+
+impl DocExample for GetBlockWithSignaturesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_WITH_SIGNATURES_RESULT
+    }
+}
+
+/// "chain_get_block_with_signatures" RPC.
+pub struct GetBlockWithSignatures {}
+
+impl RpcWithOptionalParams for GetBlockWithSignatures {
+    const METHOD: &'static str = "chain_get_block_with_signatures";
+    type OptionalRequestParams = GetBlockWithSignaturesParams;
+    type ResponseResult = GetBlockWithSignaturesResult;
+}
+
+impl RpcWithOptionalParamsExt for GetBlockWithSignatures {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Get the block, its signatures and its era's validator weights.
+            let maybe_id = maybe_params.map(|params| params.block_identifier);
+            let maybe_result: Option<BlockWithSignatures> = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBlockWithSignatures {
+                        maybe_id,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let block_with_signatures = match maybe_result {
+                Some(block_with_signatures) => block_with_signatures,
+                None => {
+                    info!("failed to get {:?} with signatures from storage", maybe_id);
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchBlock as i64,
+                        "block not known",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+            };
+
+            let finality_threshold_fraction = effect_builder
+                .get_chainspec()
+                .await
+                .core_config
+                .finality_threshold_fraction;
+            let finality_status = block_with_signatures
+                .finality_progress()
+                .map(|progress| progress.finality_status(finality_threshold_fraction));
+            let BlockWithSignatures {
+                block,
+                signatures,
+                era_validator_weights,
+            } = block_with_signatures;
+            let era_validator_weights = era_validator_weights.map(|weights| {
+                weights
+                    .into_iter()
+                    .map(|(public_key, weight)| EraValidatorWeight { public_key, weight })
+                    .collect()
+            });
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                block: Some(JsonBlock::new(block, signatures)),
+                era_validator_weights,
+                finality_status,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// The maximum number of blocks returned by a single "chain_get_blocks" request.
+pub const MAX_BLOCKS_PER_REQUEST: u64 = 100;
+
+/// Params for "chain_get_blocks" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlocksParams {
+    /// The height of the first block to return.
+    pub start_height: u64,
+    /// The maximum number of blocks to return, capped at 100.
+    pub count: u64,
+    /// Whether to include the deploys of each block.
+    #[serde(default)]
+    pub with_deploys: bool,
+}
+
+impl DocExample for GetBlocksParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCKS_PARAMS
+    }
+}
+
+/// A block in a "chain_get_blocks" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonBlockWithDeploys {
+    /// The block with its known finality signatures.
+    pub block: JsonBlock,
+    /// The block's deploys followed by its transfers, if requested.
+    pub deploys: Option<Vec<Deploy>>,
+}
+
+impl From<BlockWithDeploys> for JsonBlockWithDeploys {
+    fn from(block_with_deploys: BlockWithDeploys) -> Self {
+        let BlockWithDeploys {
+            block,
+            signatures,
+            deploys,
+        } = block_with_deploys;
+        JsonBlockWithDeploys {
+            block: JsonBlock::new(block, signatures),
+            deploys,
+        }
+    }
+}
+
+/// Result for "chain_get_blocks" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlocksResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// Consecutive blocks in ascending order of height, starting at the requested height.
+    ///
+    /// This holds fewer blocks than requested if the node doesn't have all of them, or if the
+    /// response would be too large; the next page then starts after the last block returned.
+    pub blocks: Vec<JsonBlockWithDeploys>,
+}
+
+impl DocExample for GetBlocksResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCKS_RESULT
+    }
+}
+
+/// "chain_get_blocks" RPC.
+pub struct GetBlocks {}
+
+impl RpcWithParams for GetBlocks {
+    const METHOD: &'static str = "chain_get_blocks";
+    type RequestParams = GetBlocksParams;
+    type ResponseResult = GetBlocksResult;
+}
+
+impl RpcWithParamsExt for GetBlocks {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let count = params.count.min(MAX_BLOCKS_PER_REQUEST);
+            let blocks = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBlockRange {
+                        start_height: params.start_height,
+                        count,
+                        with_deploys: params.with_deploys,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                blocks: blocks.into_iter().map(JsonBlockWithDeploys::from).collect(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 /// Params for "chain_get_block_transfers" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -250,6 +531,236 @@ impl RpcWithOptionalParamsExt for GetBlockTransfers {
     }
 }
 
+/// A transfer made by the mint while executing a deploy in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockTransfer {
+    /// The deploy which made the transfer.
+    pub deploy_hash: DeployHash,
+    /// The account from which the transfer was executed.
+    pub from: AccountHash,
+    /// The account to which the funds were transferred, if known.
+    pub to: Option<AccountHash>,
+    /// The source purse.
+    pub source: URef,
+    /// The target purse.
+    pub target: URef,
+    /// The amount transferred.
+    pub amount: U512,
+    /// The user-defined id.
+    pub id: Option<u64>,
+}
+
+impl From<Transfer> for BlockTransfer {
+    fn from(transfer: Transfer) -> Self {
+        BlockTransfer {
+            deploy_hash: transfer.deploy_hash.into(),
+            from: transfer.from,
+            to: transfer.to,
+            source: transfer.source,
+            target: transfer.target,
+            amount: transfer.amount,
+            id: transfer.id,
+        }
+    }
+}
+
+/// Result for "chain_get_block_transfers_v2" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockTransfersV2Result {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The block hash, if found.
+    pub block_hash: Option<BlockHash>,
+    /// The transfers made by the block's deploys, if found.
+    pub transfers: Option<Vec<BlockTransfer>>,
+}
+
+impl DocExample for GetBlockTransfersV2Result {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_TRANSFERS_V2_RESULT
+    }
+}
+
+/// "chain_get_block_transfers_v2" RPC.
+pub struct GetBlockTransfersV2 {}
+
+impl RpcWithOptionalParams for GetBlockTransfersV2 {
+    const METHOD: &'static str = "chain_get_block_transfers_v2";
+    type OptionalRequestParams = GetBlockTransfersParams;
+    type ResponseResult = GetBlockTransfersV2Result;
+}
+
+impl RpcWithOptionalParamsExt for GetBlockTransfersV2 {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Get the block.
+            let maybe_block_id = maybe_params.map(|params| params.block_identifier);
+            let block_hash = match get_block(maybe_block_id, effect_builder).await {
+                Ok(Some(block)) => *block.hash(),
+                Ok(None) => {
+                    let result = Self::ResponseResult {
+                        api_version,
+                        block_hash: None,
+                        transfers: None,
+                    };
+                    return Ok(response_builder.success(result)?);
+                }
+                Err(error) => return Ok(response_builder.error(error)?),
+            };
+
+            let maybe_transfers = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBlockTransfers {
+                        block_hash,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                block_hash: Some(block_hash),
+                transfers: maybe_transfers
+                    .map(|transfers| transfers.into_iter().map(BlockTransfer::from).collect()),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "chain_get_deploy_inclusion_proof" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployInclusionProofParams {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+}
+
+impl DocExample for GetDeployInclusionProofParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_INCLUSION_PROOF_PARAMS
+    }
+}
+
+/// Result for "chain_get_deploy_inclusion_proof" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployInclusionProofResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The hash of the block including the deploy.
+    pub block_hash: BlockHash,
+    /// The header of the block including the deploy.
+    pub block_header: JsonBlockHeader,
+    /// The proof linking the deploy hash to the body hash of the block header.
+    pub proof: DeployInclusionProof,
+}
+
+impl DocExample for GetDeployInclusionProofResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_INCLUSION_PROOF_RESULT
+    }
+}
+
+/// "chain_get_deploy_inclusion_proof" RPC.
+pub struct GetDeployInclusionProof {}
+
+impl RpcWithParams for GetDeployInclusionProof {
+    const METHOD: &'static str = "chain_get_deploy_inclusion_proof";
+    type RequestParams = GetDeployInclusionProofParams;
+    type ResponseResult = GetDeployInclusionProofResult;
+}
+
+impl RpcWithParamsExt for GetDeployInclusionProof {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Find the block including the deploy via the deploy's execution results.
+            let maybe_deploy_and_metadata = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetDeploy {
+                        hash: params.deploy_hash,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+            let metadata = match maybe_deploy_and_metadata {
+                Some((_, metadata)) => metadata,
+                None => {
+                    info!("failed to get {} from storage", params.deploy_hash);
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchDeploy as i64,
+                        "deploy not known",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+            };
+            let block_hash = match metadata.execution_results.keys().next() {
+                Some(block_hash) => *block_hash,
+                None => {
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::DeployNotIncluded as i64,
+                        "deploy not included in a block",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+            };
+
+            // Get the block and prove the deploy's inclusion in it.
+            let maybe_block_id = Some(BlockIdentifier::Hash(block_hash));
+            let block = match get_block(maybe_block_id, effect_builder).await {
+                Ok(Some(block)) => block,
+                Ok(None) => {
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchBlock as i64,
+                        "block not known",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+                Err(error) => return Ok(response_builder.error(error)?),
+            };
+            let proof = match DeployInclusionProof::new(&block, &params.deploy_hash) {
+                Some(proof) => proof,
+                None => {
+                    info!("{} not included in {}", params.deploy_hash, block_hash);
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::DeployNotIncluded as i64,
+                        "deploy not included in a block",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+            };
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                block_hash,
+                block_header: JsonBlockHeader::from(block.take_header()),
+                proof,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 /// Params for "chain_get_state_root_hash" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -0,0 +1,667 @@
+//! RPCs intended for node operators debugging or maintaining a running node.
+//!
+//! These are only served on the mutually authenticated admin API, never on the public JSON-RPC
+//! server.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::atomic::Ordering,
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use http::Response;
+use hyper::Body;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::info;
+use warp_json_rpc::Builder;
+
+use casper_types::{Key, PublicKey};
+
+use super::{
+    docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
+    Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithParams, RpcWithParamsExt, RpcWithoutParams,
+    RpcWithoutParamsExt,
+};
+use crate::{
+    components::{
+        consensus::EraId, contract_runtime::PurgeEstimate, small_network::BlocklistEntry,
+        storage::CompactionProgress,
+    },
+    crypto::{hash::Digest, AsymmetricKeyExt},
+    effect::EffectBuilder,
+    reactor::QueueKind,
+    types::{Block, Timestamp},
+    CONFIG_RELOAD_REQUESTED,
+};
+
+static DUMP_COMPONENT_STATE_PARAMS: Lazy<DumpComponentStateParams> =
+    Lazy::new(|| DumpComponentStateParams {
+        component: DumpableComponent::BlockProposer,
+    });
+static DUMP_COMPONENT_STATE_RESULT: Lazy<DumpComponentStateResult> =
+    Lazy::new(|| DumpComponentStateResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        component: DumpableComponent::BlockProposer,
+        state: json!({
+            "state": "ready",
+            "pending_deploys": 1,
+            "finalized_deploys": 10,
+            "next_finalized": 3,
+        }),
+    });
+static ESTIMATE_PURGE_PARAMS: Lazy<EstimatePurgeParams> = Lazy::new(|| EstimatePurgeParams {
+    state_root_hash: *Block::doc_example().header().state_root_hash(),
+    keys: vec!["era-41".to_string(), "era-42".to_string()],
+});
+static ESTIMATE_PURGE_RESULT: Lazy<EstimatePurgeResult> = Lazy::new(|| EstimatePurgeResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    estimate: PurgeEstimate {
+        present_keys: 2,
+        absent_keys: 0,
+        total_bytes: 5178,
+    },
+});
+static RELOAD_CONFIG_RESULT: Lazy<ReloadConfigResult> = Lazy::new(|| ReloadConfigResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+});
+static STAGE_SIGNING_KEY_PARAMS: Lazy<StageSigningKeyParams> =
+    Lazy::new(|| StageSigningKeyParams {
+        secret_key_path: PathBuf::from("/etc/casper/validator_keys/next_secret_key.pem"),
+        activation_era: EraId(42),
+    });
+static STAGE_SIGNING_KEY_RESULT: Lazy<StageSigningKeyResult> =
+    Lazy::new(|| StageSigningKeyResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        public_key: *PublicKey::doc_example(),
+        activation_era: EraId(42),
+    });
+static COMPACT_STORAGE_RESULT: Lazy<CompactStorageResult> = Lazy::new(|| CompactStorageResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    progress: CompactionProgress {
+        copied_bytes: 0,
+        estimated_bytes: 1_073_741_824,
+    },
+});
+static GET_BLOCKLIST_RESULT: Lazy<GetBlocklistResult> = Lazy::new(|| GetBlocklistResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    entries: vec![BlocklistEntry {
+        node_id: "NodeId::Tls(4b6f..2c1a)".to_string(),
+        address: ([127, 0, 0, 1], 34553).into(),
+        offenses: 2,
+        blocked_until: Timestamp::from(1_605_573_564_072),
+    }],
+});
+static CLEAR_BLOCKLIST_PARAMS: Lazy<ClearBlocklistParams> = Lazy::new(|| ClearBlocklistParams {
+    address: Some(([127, 0, 0, 1], 34553).into()),
+});
+static CLEAR_BLOCKLIST_RESULT: Lazy<ClearBlocklistResult> = Lazy::new(|| ClearBlocklistResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    cleared: 1,
+});
+
+/// A component whose internal state can be dumped for debugging.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DumpableComponent {
+    /// The block proposer.
+    BlockProposer,
+    /// The linear chain, which accumulates finality signatures for added blocks.
+    LinearChain,
+    /// The networking component.
+    Network,
+}
+
+impl Display for DumpableComponent {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            DumpableComponent::BlockProposer => write!(formatter, "block proposer"),
+            DumpableComponent::LinearChain => write!(formatter, "linear chain"),
+            DumpableComponent::Network => write!(formatter, "network"),
+        }
+    }
+}
+
+/// Params for "debug_dump_component_state" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DumpComponentStateParams {
+    /// The component whose state should be dumped.
+    pub component: DumpableComponent,
+}
+
+impl DocExample for DumpComponentStateParams {
+    fn doc_example() -> &'static Self {
+        &*DUMP_COMPONENT_STATE_PARAMS
+    }
+}
+
+/// Result for "debug_dump_component_state" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DumpComponentStateResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The component whose state was dumped.
+    pub component: DumpableComponent,
+    /// A redacted snapshot of the component's internal state.
+    pub state: Value,
+}
+
+impl DocExample for DumpComponentStateResult {
+    fn doc_example() -> &'static Self {
+        &*DUMP_COMPONENT_STATE_RESULT
+    }
+}
+
+/// "debug_dump_component_state" RPC.
+pub struct DumpComponentState {}
+
+impl RpcWithParams for DumpComponentState {
+    const METHOD: &'static str = "debug_dump_component_state";
+    type RequestParams = DumpComponentStateParams;
+    type ResponseResult = DumpComponentStateResult;
+}
+
+impl RpcWithParamsExt for DumpComponentState {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let component = params.component;
+            let maybe_state = effect_builder
+                .make_request(
+                    |responder| RpcRequest::DumpComponentState {
+                        component,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let state = match maybe_state {
+                Some(state) => state,
+                None => {
+                    info!(%component, "cannot dump state of component which is not running");
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::ComponentNotRunning as i64,
+                        format!("{} is not running", component),
+                    ))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                component,
+                state,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// The maximum number of keys accepted by a single "debug_estimate_purge" request.
+pub const MAX_PURGE_ESTIMATE_KEYS: usize = 500;
+
+/// Params for "debug_estimate_purge" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EstimatePurgeParams {
+    /// Hash of the state root.
+    pub state_root_hash: Digest,
+    /// The `casper_types::Key`s which would be purged, as formatted strings.  At most
+    /// `MAX_PURGE_ESTIMATE_KEYS` are allowed.
+    pub keys: Vec<String>,
+}
+
+impl DocExample for EstimatePurgeParams {
+    fn doc_example() -> &'static Self {
+        &*ESTIMATE_PURGE_PARAMS
+    }
+}
+
+/// Result for "debug_estimate_purge" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EstimatePurgeResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The global state data which would be removed by purging the keys.  Only the serialized
+    /// keys and values are counted, not the trie nodes leading to them.
+    pub estimate: PurgeEstimate,
+}
+
+impl DocExample for EstimatePurgeResult {
+    fn doc_example() -> &'static Self {
+        &*ESTIMATE_PURGE_RESULT
+    }
+}
+
+/// "debug_estimate_purge" RPC.
+///
+/// The estimate is an approximation: it sums up the serialized sizes of the keys and their values,
+/// but ignores the trie nodes storing them.  Purging the keys also removes or rewrites the nodes
+/// along their paths, so the space actually freed on disk differs from the estimate.
+pub struct EstimatePurge {}
+
+impl RpcWithParams for EstimatePurge {
+    const METHOD: &'static str = "debug_estimate_purge";
+    type RequestParams = EstimatePurgeParams;
+    type ResponseResult = EstimatePurgeResult;
+}
+
+impl RpcWithParamsExt for EstimatePurge {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            if params.keys.len() > MAX_PURGE_ESTIMATE_KEYS {
+                let error_msg = format!(
+                    "estimate-purge requested {} keys, at most {} are allowed",
+                    params.keys.len(),
+                    MAX_PURGE_ESTIMATE_KEYS
+                );
+                info!("{}", error_msg);
+                return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                    ErrorCode::PurgeEstimateTooManyKeys as i64,
+                    error_msg,
+                ))?);
+            }
+
+            // Try to parse the `casper_types::Key`s from the params.
+            let mut keys = Vec::with_capacity(params.keys.len());
+            for key in &params.keys {
+                match Key::from_formatted_str(key) {
+                    Ok(key) => keys.push(key),
+                    Err(error) => {
+                        let error_msg = format!("failed to parse key {}: {:?}", key, error);
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParseQueryKey as i64,
+                            error_msg,
+                        ))?);
+                    }
+                }
+            }
+
+            let estimate_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::EstimatePurge {
+                        state_root_hash: params.state_root_hash,
+                        keys,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let (error_code, error_msg) = match estimate_result {
+                Ok(Some(estimate)) => {
+                    let result = Self::ResponseResult {
+                        api_version,
+                        estimate,
+                    };
+                    return Ok(response_builder.success(result)?);
+                }
+                Ok(None) => (
+                    ErrorCode::QueryFailed,
+                    format!("state root {} not found", params.state_root_hash),
+                ),
+                Err(error) => (
+                    ErrorCode::QueryFailedToExecute,
+                    format!("purge estimate failed to execute: {:?}", error),
+                ),
+            };
+            info!("{}", error_msg);
+            Ok(response_builder
+                .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?)
+        }
+        .boxed()
+    }
+}
+
+/// Result for "debug_reload_config" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReloadConfigResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+}
+
+impl DocExample for ReloadConfigResult {
+    fn doc_example() -> &'static Self {
+        &*RELOAD_CONFIG_RESULT
+    }
+}
+
+/// "debug_reload_config" RPC.
+///
+/// Has the same effect as sending `SIGHUP` to the node: the config file is re-read and the
+/// settings which can be changed without a restart are applied.  The reload happens after the
+/// response is sent, and its outcome is logged.
+pub struct ReloadConfig {}
+
+impl RpcWithoutParams for ReloadConfig {
+    const METHOD: &'static str = "debug_reload_config";
+    type ResponseResult = ReloadConfigResult;
+}
+
+impl RpcWithoutParamsExt for ReloadConfig {
+    fn handle_request<REv: ReactorEventT>(
+        _effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            info!("config reload requested via admin API");
+            CONFIG_RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+            let result = Self::ResponseResult { api_version };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "debug_stage_signing_key" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StageSigningKeyParams {
+    /// The path to the new secret key file on the node's file system.
+    #[schemars(with = "String")]
+    pub secret_key_path: PathBuf,
+    /// The first era in which the new key is used.  Must be after the current era.
+    pub activation_era: EraId,
+}
+
+impl DocExample for StageSigningKeyParams {
+    fn doc_example() -> &'static Self {
+        &*STAGE_SIGNING_KEY_PARAMS
+    }
+}
+
+/// Result for "debug_stage_signing_key" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StageSigningKeyResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The public key of the staged signing key.
+    pub public_key: PublicKey,
+    /// The first era in which the new key is used.
+    pub activation_era: EraId,
+}
+
+impl DocExample for StageSigningKeyResult {
+    fn doc_example() -> &'static Self {
+        &*STAGE_SIGNING_KEY_RESULT
+    }
+}
+
+/// "debug_stage_signing_key" RPC.
+///
+/// Stages a new consensus signing key, e.g. to rotate the validator key.  The key is read from a
+/// file on the node, so that it never has to be sent over the network.  From the activation era on
+/// it replaces the current key.  A key staged this way is not persisted; to keep it across
+/// restarts, it also has to be set in the `consensus.key_rotation` section of the config.
+pub struct StageSigningKey {}
+
+impl RpcWithParams for StageSigningKey {
+    const METHOD: &'static str = "debug_stage_signing_key";
+    type RequestParams = StageSigningKeyParams;
+    type ResponseResult = StageSigningKeyResult;
+}
+
+impl RpcWithParamsExt for StageSigningKey {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let activation_era = params.activation_era;
+            let stage_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::StageSigningKey {
+                        secret_key_path: params.secret_key_path,
+                        activation_era,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            match stage_result {
+                Ok(public_key) => {
+                    let result = Self::ResponseResult {
+                        api_version,
+                        public_key,
+                        activation_era,
+                    };
+                    Ok(response_builder.success(result)?)
+                }
+                Err(error_msg) => {
+                    info!("{}", error_msg);
+                    Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::StageSigningKeyFailed as i64,
+                        error_msg,
+                    ))?)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Result for "debug_compact_storage" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CompactStorageResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The progress of the compaction when it was started.
+    pub progress: CompactionProgress,
+}
+
+impl DocExample for CompactStorageResult {
+    fn doc_example() -> &'static Self {
+        &*COMPACT_STORAGE_RESULT
+    }
+}
+
+/// "debug_compact_storage" RPC.
+///
+/// Starts compacting the storage database file, which otherwise never shrinks.  The live data is
+/// copied into a new file in the background, at the rate limited by `storage.compaction_rate_limit`
+/// in the config, and the new file replaces the old one once complete.  The progress is reported in
+/// the `storage_compaction_*` metrics.  Refused while the node is far behind the tip of the chain,
+/// or while another compaction is running.
+pub struct CompactStorage {}
+
+impl RpcWithoutParams for CompactStorage {
+    const METHOD: &'static str = "debug_compact_storage";
+    type ResponseResult = CompactStorageResult;
+}
+
+impl RpcWithoutParamsExt for CompactStorage {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let compact_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::CompactStorage { responder },
+                    QueueKind::Api,
+                )
+                .await;
+
+            match compact_result {
+                Ok(progress) => {
+                    let result = Self::ResponseResult {
+                        api_version,
+                        progress,
+                    };
+                    Ok(response_builder.success(result)?)
+                }
+                Err(error_msg) => {
+                    info!("{}", error_msg);
+                    Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::CompactStorageFailed as i64,
+                        error_msg,
+                    ))?)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Result for "debug_get_blocklist" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlocklistResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The blocklisted peers, including those whose ban expired but whose offenses are still
+    /// remembered.
+    pub entries: Vec<BlocklistEntry>,
+}
+
+impl DocExample for GetBlocklistResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCKLIST_RESULT
+    }
+}
+
+/// "debug_get_blocklist" RPC.
+///
+/// Returns the peers the node has blocklisted for misbehaving.  Each entry's offense score
+/// determines the length of the peer's next ban; it decays over time as configured in the
+/// `network` section of the config.
+pub struct GetBlocklist {}
+
+impl RpcWithoutParams for GetBlocklist {
+    const METHOD: &'static str = "debug_get_blocklist";
+    type ResponseResult = GetBlocklistResult;
+}
+
+impl RpcWithoutParamsExt for GetBlocklist {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let entries = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBlocklist { responder },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                entries,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "debug_clear_blocklist" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClearBlocklistParams {
+    /// The address of the peer to unblock.  If omitted, the whole blocklist is cleared.
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub address: Option<SocketAddr>,
+}
+
+impl DocExample for ClearBlocklistParams {
+    fn doc_example() -> &'static Self {
+        &*CLEAR_BLOCKLIST_PARAMS
+    }
+}
+
+/// Result for "debug_clear_blocklist" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClearBlocklistResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The number of removed entries.
+    pub cleared: usize,
+}
+
+impl DocExample for ClearBlocklistResult {
+    fn doc_example() -> &'static Self {
+        &*CLEAR_BLOCKLIST_RESULT
+    }
+}
+
+/// "debug_clear_blocklist" RPC.
+///
+/// Removes a peer, or all peers, from the blocklist, so that the node connects to them again and
+/// their offense scores start over.
+pub struct ClearBlocklist {}
+
+impl RpcWithParams for ClearBlocklist {
+    const METHOD: &'static str = "debug_clear_blocklist";
+    type RequestParams = ClearBlocklistParams;
+    type ResponseResult = ClearBlocklistResult;
+}
+
+impl RpcWithParamsExt for ClearBlocklist {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            info!(address = ?params.address, "blocklist clear requested via admin API");
+            let cleared = effect_builder
+                .make_request(
+                    |responder| RpcRequest::ClearBlocklist {
+                        address: params.address,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                cleared,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
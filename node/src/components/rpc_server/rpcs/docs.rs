@@ -18,10 +18,16 @@ use serde_json::{json, Value};
 use warp_json_rpc::Builder;
 
 use super::{
-    account::PutDeploy,
-    chain::{GetBlock, GetBlockTransfers, GetStateRootHash},
-    info::{GetDeploy, GetPeers, GetStatus},
-    state::{GetAuctionInfo, GetBalance, GetItem},
+    account::{PutDeploy, PutDeployApprovals},
+    chain::{
+        GetBlock, GetBlockTransfers, GetBlockTransfersV2, GetBlockWithSignatures, GetBlocks,
+        GetDeployInclusionProof, GetStateRootHash,
+    },
+    info::{
+        GetAccountDeployHashes, GetChainspec, GetCostTable, GetDeploy, GetDeployApprovals,
+        GetDeployEffects, GetEraStorageStats, GetPeers, GetStatus,
+    },
+    state::{GetAuctionInfo, GetBalance, GetBalanceBatch, GetItem, QueryGlobalStateBatch},
     Error, ReactorEventT, RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
     RpcWithoutParamsExt,
 };
@@ -67,24 +73,64 @@ static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
     };
 
     schema.push_with_params::<PutDeploy>("receives a Deploy to be executed by the network");
+    schema.push_with_params::<PutDeployApprovals>(
+        "adds approvals to a Deploy awaiting approvals from further keys of its account",
+    );
     schema.push_with_params::<GetDeploy>("returns a Deploy from the network");
+    schema.push_with_params::<GetDeployEffects>(
+        "returns a page of the operations and transforms of a Deploy's execution result",
+    );
+    schema.push_with_params::<GetDeployApprovals>(
+        "returns all approvals of a Deploy, including ones received after it was stored",
+    );
+    schema.push_with_params::<GetAccountDeployHashes>(
+        "returns the hashes of the Deploys created by an account, oldest first",
+    );
     schema.push_without_params::<GetPeers>("returns a list of peers connected to the node");
     schema.push_without_params::<GetStatus>("returns the current status of the node");
     schema.push_with_optional_params::<GetBlock>("returns a Block from the network");
+    schema.push_with_optional_params::<GetBlockWithSignatures>(
+        "returns a Block with all its known finality signatures and its finality status",
+    );
+    schema.push_with_params::<GetBlocks>(
+        "returns a page of consecutive Blocks, optionally with their Deploys",
+    );
     schema.push_with_optional_params::<GetBlockTransfers>(
         "returns all transfers for a Block from the network",
     );
+    schema.push_with_optional_params::<GetBlockTransfersV2>(
+        "returns the transfers made by the deploys of a Block, with their deploy hashes",
+    );
+    schema.push_with_params::<GetDeployInclusionProof>(
+        "returns the data proving that a Deploy is included in a Block, for light clients",
+    );
     schema.push_with_optional_params::<GetStateRootHash>(
         "returns a state root hash at a given Block",
     );
     schema.push_with_params::<GetItem>("returns a stored value from the network");
     schema.push_with_params::<GetBalance>("returns a purse's balance from the network");
+    schema.push_with_params::<GetBalanceBatch>(
+        "returns the balances of several purses at a single state root hash",
+    );
+    schema.push_with_params::<QueryGlobalStateBatch>(
+        "returns several stored values and their merkle proofs at a single state root hash",
+    );
     schema.push_with_optional_params::<GetEraInfoBySwitchBlock>(
         "returns an EraInfo from the network",
     );
     schema.push_without_params::<GetAuctionInfo>(
         "returns the bids and validators as of the most recently added Block",
     );
+    schema.push_with_optional_params::<GetCostTable>(
+        "returns the gas costs of wasm opcodes, storage and host functions for a protocol version",
+    );
+    schema.push_with_optional_params::<GetChainspec>(
+        "returns the chainspec file of a protocol version which the node has run",
+    );
+    schema.push_with_optional_params::<GetEraStorageStats>(
+        "returns the number and size of the blocks, deploys, execution results and signatures \
+        stored per era",
+    );
 
     schema
 });
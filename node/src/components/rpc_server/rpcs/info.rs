@@ -15,21 +15,29 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use casper_types::ExecutionResult;
+use casper_execution_engine::{core::engine_state::CostTable, shared::wasm_config::WasmConfig};
+use casper_types::{
+    ExecutionEffect, ExecutionResult, Operation, ProtocolVersion, PublicKey, TransformEntry,
+};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
-    Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithParams, RpcWithParamsExt, RpcWithoutParams,
-    RpcWithoutParamsExt,
+    Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithOptionalParams, RpcWithOptionalParamsExt,
+    RpcWithParams, RpcWithParamsExt, RpcWithoutParams, RpcWithoutParamsExt,
 };
 use crate::{
+    components::{
+        consensus::EraId,
+        storage::{EraStorageStats, ItemStats},
+    },
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{Block, BlockHash, Deploy, DeployHash, GetStatusResult, Item, PeersMap},
+    types::{Approval, Block, BlockHash, Deploy, DeployHash, GetStatusResult, Item, PeersMap},
 };
 
 static GET_DEPLOY_PARAMS: Lazy<GetDeployParams> = Lazy::new(|| GetDeployParams {
     deploy_hash: *Deploy::doc_example().id(),
+    omit_effects: false,
 });
 static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
@@ -39,10 +47,93 @@ static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
         result: ExecutionResult::example().clone(),
     }],
 });
+static GET_DEPLOY_EFFECTS_PARAMS: Lazy<GetDeployEffectsParams> =
+    Lazy::new(|| GetDeployEffectsParams {
+        deploy_hash: *Deploy::doc_example().id(),
+        block_hash: Some(Block::doc_example().id()),
+        page: 0,
+    });
+static GET_DEPLOY_EFFECTS_RESULT: Lazy<GetDeployEffectsResult> = Lazy::new(|| {
+    let effect = execution_effect(ExecutionResult::example());
+    GetDeployEffectsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        deploy_hash: *Deploy::doc_example().id(),
+        block_hash: Block::doc_example().id(),
+        page: 0,
+        page_count: 1,
+        operations: effect.operations.clone(),
+        transforms: effect.transforms.clone(),
+    }
+});
+static GET_DEPLOY_APPROVALS_PARAMS: Lazy<GetDeployApprovalsParams> =
+    Lazy::new(|| GetDeployApprovalsParams {
+        deploy_hash: *Deploy::doc_example().id(),
+    });
+static GET_DEPLOY_APPROVALS_RESULT: Lazy<GetDeployApprovalsResult> =
+    Lazy::new(|| GetDeployApprovalsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        deploy_hash: *Deploy::doc_example().id(),
+        approvals: Deploy::doc_example().approvals().to_vec(),
+    });
+static GET_ACCOUNT_DEPLOY_HASHES_PARAMS: Lazy<GetAccountDeployHashesParams> =
+    Lazy::new(|| GetAccountDeployHashesParams {
+        public_key: Deploy::doc_example().header().account().clone(),
+        offset: 0,
+        limit: 10,
+    });
+static GET_ACCOUNT_DEPLOY_HASHES_RESULT: Lazy<GetAccountDeployHashesResult> =
+    Lazy::new(|| GetAccountDeployHashesResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        deploy_hashes: vec![*Deploy::doc_example().id()],
+    });
 static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
     peers: GetStatusResult::doc_example().peers.clone(),
 });
+static GET_COST_TABLE_PARAMS: Lazy<GetCostTableParams> = Lazy::new(|| GetCostTableParams {
+    protocol_version: ProtocolVersion::V1_0_0,
+});
+static GET_CHAINSPEC_PARAMS: Lazy<GetChainspecParams> = Lazy::new(|| GetChainspecParams {
+    protocol_version: ProtocolVersion::V1_0_0,
+});
+static GET_CHAINSPEC_RESULT: Lazy<GetChainspecResult> = Lazy::new(|| GetChainspecResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    chainspec_bytes: hex::encode("[protocol]\nversion = '1.0.0'\n"),
+});
+static GET_ERA_STORAGE_STATS_PARAMS: Lazy<GetEraStorageStatsParams> =
+    Lazy::new(|| GetEraStorageStatsParams {
+        start_era_id: EraId(0),
+        end_era_id: EraId(1),
+    });
+static GET_ERA_STORAGE_STATS_RESULT: Lazy<GetEraStorageStatsResult> =
+    Lazy::new(|| GetEraStorageStatsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        eras: vec![JsonEraStorageStats {
+            era_id: EraId(1),
+            stats: EraStorageStats {
+                blocks: ItemStats {
+                    count: 2,
+                    bytes: 1_024,
+                },
+                deploys: ItemStats {
+                    count: 3,
+                    bytes: 3_584,
+                },
+                execution_results: ItemStats {
+                    count: 3,
+                    bytes: 6_144,
+                },
+                signatures: ItemStats {
+                    count: 10,
+                    bytes: 1_340,
+                },
+            },
+        }],
+    });
+static GET_COST_TABLE_RESULT: Lazy<GetCostTableResult> = Lazy::new(|| GetCostTableResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+    cost_table: CostTable::new(ProtocolVersion::V1_0_0, &WasmConfig::default()),
+});
 
 /// Params for "info_get_deploy" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -50,6 +141,10 @@ static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
 pub struct GetDeployParams {
     /// The deploy hash.
     pub deploy_hash: DeployHash,
+    /// Whether to leave out the operations and transforms of the execution results.  They can be
+    /// fetched page by page with "info_get_deploy_effects" instead.
+    #[serde(default)]
+    pub omit_effects: bool,
 }
 
 impl DocExample for GetDeployParams {
@@ -133,7 +228,12 @@ impl RpcWithParamsExt for GetDeploy {
             let execution_results = metadata
                 .execution_results
                 .into_iter()
-                .map(|(block_hash, result)| JsonExecutionResult { block_hash, result })
+                .map(|(block_hash, mut result)| {
+                    if params.omit_effects {
+                        *execution_effect_mut(&mut result) = ExecutionEffect::default();
+                    }
+                    JsonExecutionResult { block_hash, result }
+                })
                 .collect();
 
             let result = Self::ResponseResult {
@@ -147,6 +247,320 @@ impl RpcWithParamsExt for GetDeploy {
     }
 }
 
+/// The number of operations and transforms returned per page by "info_get_deploy_effects".
+pub const DEPLOY_EFFECTS_PAGE_SIZE: usize = 1_000;
+
+/// Params for "info_get_deploy_effects" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployEffectsParams {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// The hash of the block in which the deploy was executed.  If unset, the first block listed
+    /// by "info_get_deploy" is used.
+    #[serde(default)]
+    pub block_hash: Option<BlockHash>,
+    /// The index of the page to return, starting at 0.
+    pub page: u64,
+}
+
+impl DocExample for GetDeployEffectsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_EFFECTS_PARAMS
+    }
+}
+
+/// Result for "info_get_deploy_effects" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployEffectsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// The hash of the block in which the deploy was executed.
+    pub block_hash: BlockHash,
+    /// The index of the returned page.
+    pub page: u64,
+    /// The number of pages, at least 1.
+    pub page_count: u64,
+    /// The page's part of the operations performed while executing the deploy.
+    pub operations: Vec<Operation>,
+    /// The page's part of the transforms resulting from executing the deploy.
+    pub transforms: Vec<TransformEntry>,
+}
+
+impl DocExample for GetDeployEffectsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_EFFECTS_RESULT
+    }
+}
+
+/// "info_get_deploy_effects" RPC.
+pub struct GetDeployEffects {}
+
+impl RpcWithParams for GetDeployEffects {
+    const METHOD: &'static str = "info_get_deploy_effects";
+    type RequestParams = GetDeployEffectsParams;
+    type ResponseResult = GetDeployEffectsResult;
+}
+
+impl RpcWithParamsExt for GetDeployEffects {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let maybe_deploy_and_metadata = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetDeploy {
+                        hash: params.deploy_hash,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let mut metadata = match maybe_deploy_and_metadata {
+                Some((_, metadata)) => metadata,
+                None => {
+                    info!("failed to get {} from storage", params.deploy_hash);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchDeploy as i64,
+                        "deploy not known",
+                    ))?);
+                }
+            };
+
+            let maybe_block_hash = params
+                .block_hash
+                .or_else(|| metadata.execution_results.keys().next().copied());
+            let (block_hash, result) = match maybe_block_hash.and_then(|block_hash| {
+                let result = metadata.execution_results.remove(&block_hash)?;
+                Some((block_hash, result))
+            }) {
+                Some(block_hash_and_result) => block_hash_and_result,
+                None => {
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::DeployNotIncluded as i64,
+                        "deploy not executed in the given block",
+                    ))?);
+                }
+            };
+
+            let effect = execution_effect(&result);
+            let entry_count = effect.operations.len().max(effect.transforms.len());
+            let page_count =
+                ((entry_count + DEPLOY_EFFECTS_PAGE_SIZE - 1) / DEPLOY_EFFECTS_PAGE_SIZE).max(1);
+            if params.page >= page_count as u64 {
+                let error_msg = format!(
+                    "page {} requested, but the effects only have {} pages",
+                    params.page, page_count
+                );
+                return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                    ErrorCode::NoSuchDeployEffectsPage as i64,
+                    error_msg,
+                ))?);
+            }
+
+            let start = params.page as usize * DEPLOY_EFFECTS_PAGE_SIZE;
+            let page_of = |len: usize| start.min(len)..(start + DEPLOY_EFFECTS_PAGE_SIZE).min(len);
+            let result = Self::ResponseResult {
+                api_version,
+                deploy_hash: params.deploy_hash,
+                block_hash,
+                page: params.page,
+                page_count: page_count as u64,
+                operations: effect.operations[page_of(effect.operations.len())].to_vec(),
+                transforms: effect.transforms[page_of(effect.transforms.len())].to_vec(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Returns the effect of executing a deploy.
+fn execution_effect(result: &ExecutionResult) -> &ExecutionEffect {
+    match result {
+        ExecutionResult::Failure { effect, .. } | ExecutionResult::Success { effect, .. } => effect,
+    }
+}
+
+/// Returns the effect of executing a deploy, mutably.
+fn execution_effect_mut(result: &mut ExecutionResult) -> &mut ExecutionEffect {
+    match result {
+        ExecutionResult::Failure { effect, .. } | ExecutionResult::Success { effect, .. } => effect,
+    }
+}
+
+/// Params for "info_get_deploy_approvals" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployApprovalsParams {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+}
+
+impl DocExample for GetDeployApprovalsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_APPROVALS_PARAMS
+    }
+}
+
+/// Result for "info_get_deploy_approvals" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployApprovalsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// All approvals of the deploy held by this node, including ones received after it was
+    /// stored.
+    pub approvals: Vec<Approval>,
+}
+
+impl DocExample for GetDeployApprovalsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_APPROVALS_RESULT
+    }
+}
+
+/// "info_get_deploy_approvals" RPC.
+pub struct GetDeployApprovals {}
+
+impl RpcWithParams for GetDeployApprovals {
+    const METHOD: &'static str = "info_get_deploy_approvals";
+    type RequestParams = GetDeployApprovalsParams;
+    type ResponseResult = GetDeployApprovalsResult;
+}
+
+impl RpcWithParamsExt for GetDeployApprovals {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let maybe_deploy_and_metadata = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetDeploy {
+                        hash: params.deploy_hash,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let deploy = match maybe_deploy_and_metadata {
+                Some((deploy, _)) => deploy,
+                None => {
+                    info!("failed to get {} from storage", params.deploy_hash);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchDeploy as i64,
+                        "deploy not known",
+                    ))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                deploy_hash: params.deploy_hash,
+                approvals: deploy.approvals().to_vec(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// The maximum number of deploy hashes returned by a single "info_get_account_deploy_hashes"
+/// request.
+pub const MAX_ACCOUNT_DEPLOY_HASHES_PER_REQUEST: u64 = 100;
+
+/// Params for "info_get_account_deploy_hashes" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAccountDeployHashesParams {
+    /// The public key of the account which created the deploys.
+    pub public_key: PublicKey,
+    /// The number of the account's oldest deploys to skip.
+    pub offset: u64,
+    /// The maximum number of deploy hashes to return, capped at 100.
+    pub limit: u64,
+}
+
+impl DocExample for GetAccountDeployHashesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ACCOUNT_DEPLOY_HASHES_PARAMS
+    }
+}
+
+/// Result for "info_get_account_deploy_hashes" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAccountDeployHashesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The hashes of the deploys created by the account, oldest first.
+    pub deploy_hashes: Vec<DeployHash>,
+}
+
+impl DocExample for GetAccountDeployHashesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ACCOUNT_DEPLOY_HASHES_RESULT
+    }
+}
+
+/// "info_get_account_deploy_hashes" RPC.
+pub struct GetAccountDeployHashes {}
+
+impl RpcWithParams for GetAccountDeployHashes {
+    const METHOD: &'static str = "info_get_account_deploy_hashes";
+    type RequestParams = GetAccountDeployHashesParams;
+    type ResponseResult = GetAccountDeployHashesResult;
+}
+
+impl RpcWithParamsExt for GetAccountDeployHashes {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let account = params.public_key.to_account_hash();
+            let limit = params.limit.min(MAX_ACCOUNT_DEPLOY_HASHES_PER_REQUEST);
+            let range = params.offset..params.offset.saturating_add(limit);
+
+            let deploy_hashes = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetDeployHashesByAccount {
+                        account,
+                        range,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                deploy_hashes,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 /// Result for "info_get_peers" RPC response.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -226,3 +640,289 @@ impl RpcWithoutParamsExt for GetStatus {
         .boxed()
     }
 }
+
+/// Params for "info_get_cost_table" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetCostTableParams {
+    /// The protocol version whose costs are requested.
+    pub protocol_version: ProtocolVersion,
+}
+
+impl DocExample for GetCostTableParams {
+    fn doc_example() -> &'static Self {
+        &*GET_COST_TABLE_PARAMS
+    }
+}
+
+/// Result for "info_get_cost_table" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetCostTableResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The gas costs charged under the requested protocol version.
+    pub cost_table: CostTable,
+}
+
+impl DocExample for GetCostTableResult {
+    fn doc_example() -> &'static Self {
+        &*GET_COST_TABLE_RESULT
+    }
+}
+
+/// "info_get_cost_table" RPC.
+///
+/// Returns the costs of wasm opcodes, storage and host functions which the execution engine charges
+/// under the given protocol version, or under the node's own protocol version if none is given.
+pub struct GetCostTable {}
+
+impl RpcWithOptionalParams for GetCostTable {
+    const METHOD: &'static str = "info_get_cost_table";
+    type OptionalRequestParams = GetCostTableParams;
+    type ResponseResult = GetCostTableResult;
+}
+
+impl RpcWithOptionalParamsExt for GetCostTable {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let protocol_version = match maybe_params {
+                Some(params) => params.protocol_version,
+                None => ProtocolVersion::from_parts(
+                    api_version.major as u32,
+                    api_version.minor as u32,
+                    api_version.patch as u32,
+                ),
+            };
+
+            let cost_table_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetCostTable {
+                        protocol_version,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let cost_table = match cost_table_result {
+                Ok(Some(cost_table)) => cost_table,
+                Ok(None) => {
+                    let error_msg = format!("no protocol data for {}", protocol_version);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchProtocolVersion as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error) => {
+                    let error_msg = format!("failed to get cost table: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::QueryFailedToExecute as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                cost_table,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "info_get_chainspec" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetChainspecParams {
+    /// The protocol version whose chainspec is requested.
+    pub protocol_version: ProtocolVersion,
+}
+
+impl DocExample for GetChainspecParams {
+    fn doc_example() -> &'static Self {
+        &*GET_CHAINSPEC_PARAMS
+    }
+}
+
+/// Result for "info_get_chainspec" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetChainspecResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// Hex-encoded contents of the chainspec file.
+    pub chainspec_bytes: String,
+}
+
+impl DocExample for GetChainspecResult {
+    fn doc_example() -> &'static Self {
+        &*GET_CHAINSPEC_RESULT
+    }
+}
+
+/// "info_get_chainspec" RPC.
+///
+/// Returns the chainspec file of the given protocol version, or of the node's own protocol version
+/// if none is given.  Chainspecs of all versions the node has run are kept, so this can be used to
+/// look up the rules which applied to historical blocks.
+pub struct GetChainspec {}
+
+impl RpcWithOptionalParams for GetChainspec {
+    const METHOD: &'static str = "info_get_chainspec";
+    type OptionalRequestParams = GetChainspecParams;
+    type ResponseResult = GetChainspecResult;
+}
+
+impl RpcWithOptionalParamsExt for GetChainspec {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let protocol_version = match maybe_params {
+                Some(params) => {
+                    let semver = params.protocol_version.value();
+                    Version::new(
+                        semver.major.into(),
+                        semver.minor.into(),
+                        semver.patch.into(),
+                    )
+                }
+                None => api_version.clone(),
+            };
+
+            let maybe_raw_bytes = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetChainspecRawBytes {
+                        protocol_version: protocol_version.clone(),
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let raw_bytes = match maybe_raw_bytes {
+                Some(raw_bytes) => raw_bytes,
+                None => {
+                    let error_msg = format!("no chainspec for {}", protocol_version);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchProtocolVersion as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                chainspec_bytes: hex::encode(raw_bytes),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "info_get_era_storage_stats" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraStorageStatsParams {
+    /// The first era whose statistics are requested.
+    pub start_era_id: EraId,
+    /// The last era whose statistics are requested.
+    pub end_era_id: EraId,
+}
+
+impl DocExample for GetEraStorageStatsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_STORAGE_STATS_PARAMS
+    }
+}
+
+/// The items stored for a single era.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonEraStorageStats {
+    /// The era ID.
+    pub era_id: EraId,
+    /// The counts and sizes of the era's stored items.
+    pub stats: EraStorageStats,
+}
+
+/// Result for "info_get_era_storage_stats" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraStorageStatsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The requested eras with any items stored, in ascending order.
+    pub eras: Vec<JsonEraStorageStats>,
+}
+
+impl DocExample for GetEraStorageStatsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_STORAGE_STATS_RESULT
+    }
+}
+
+/// "info_get_era_storage_stats" RPC.
+///
+/// Returns the number and total size of the blocks, deploys, execution results and finality
+/// signatures stored for each era in the given range, or for all eras if none is given.  The
+/// figures are maintained as items are stored, so they are cheap to query, and can be used to
+/// estimate the space a retention policy would free or require.
+pub struct GetEraStorageStats {}
+
+impl RpcWithOptionalParams for GetEraStorageStats {
+    const METHOD: &'static str = "info_get_era_storage_stats";
+    type OptionalRequestParams = GetEraStorageStatsParams;
+    type ResponseResult = GetEraStorageStatsResult;
+}
+
+impl RpcWithOptionalParamsExt for GetEraStorageStats {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let eras = match maybe_params {
+                Some(params) => params.start_era_id..=params.end_era_id,
+                None => EraId(0)..=EraId(u64::MAX),
+            };
+
+            let stats = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetEraStorageStats { eras, responder },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                eras: stats
+                    .into_iter()
+                    .map(|(era_id, stats)| JsonEraStorageStats { era_id, stats })
+                    .collect(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
@@ -3,7 +3,7 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use std::str;
+use std::{convert::TryFrom, str};
 
 use futures::{future::BoxFuture, FutureExt};
 use http::Response;
@@ -15,7 +15,9 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use casper_execution_engine::core::engine_state::{BalanceResult, GetBidsResult};
+use casper_execution_engine::core::engine_state::{
+    BalanceBatchResult, BalanceResult, GetBidsResult, QueryBatchResult, QueryResult,
+};
 use casper_types::{bytesrepr::ToBytes, CLValue, Key, ProtocolVersion, URef, U512};
 
 use super::{
@@ -40,10 +42,11 @@ static GET_ITEM_PARAMS: Lazy<GetItemParams> = Lazy::new(|| GetItemParams {
     state_root_hash: *Block::doc_example().header().state_root_hash(),
     key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
     path: vec!["inner".to_string()],
+    proof_only: false,
 });
 static GET_ITEM_RESULT: Lazy<GetItemResult> = Lazy::new(|| GetItemResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
-    stored_value: StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
+    stored_value: Some(StoredValue::CLValue(CLValue::from_t(1u64).unwrap())),
     merkle_proof: MERKLE_PROOF.clone(),
 });
 static GET_BALANCE_PARAMS: Lazy<GetBalanceParams> = Lazy::new(|| GetBalanceParams {
@@ -56,6 +59,53 @@ static GET_BALANCE_RESULT: Lazy<GetBalanceResult> = Lazy::new(|| GetBalanceResul
     balance_value: U512::from(123_456),
     merkle_proof: MERKLE_PROOF.clone(),
 });
+static GET_BALANCE_BATCH_PARAMS: Lazy<GetBalanceBatchParams> =
+    Lazy::new(|| GetBalanceBatchParams {
+        state_root_hash: *Block::doc_example().header().state_root_hash(),
+        keys: vec![
+            "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007".to_string(),
+            "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb"
+                .to_string(),
+        ],
+    });
+static GET_BALANCE_BATCH_RESULT: Lazy<GetBalanceBatchResult> =
+    Lazy::new(|| GetBalanceBatchResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        balances: vec![
+            PurseBalance {
+                key: "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007"
+                    .to_string(),
+                balance_value: Some(U512::from(123_456)),
+                error: None,
+            },
+            PurseBalance {
+                key:
+                    "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb"
+                        .to_string(),
+                balance_value: None,
+                error: Some("Value not found".to_string()),
+            },
+        ],
+    });
+static QUERY_GLOBAL_STATE_BATCH_PARAMS: Lazy<QueryGlobalStateBatchParams> =
+    Lazy::new(|| QueryGlobalStateBatchParams {
+        state_root_hash: *Block::doc_example().header().state_root_hash(),
+        queries: vec![GlobalStateQuery {
+            key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1"
+                .to_string(),
+            path: vec!["inner".to_string()],
+        }],
+    });
+static QUERY_GLOBAL_STATE_BATCH_RESULT: Lazy<QueryGlobalStateBatchResult> =
+    Lazy::new(|| QueryGlobalStateBatchResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
+        values: vec![QueriedValue {
+            stored_value: Some(StoredValue::CLValue(CLValue::from_t(1u64).unwrap())),
+            error: None,
+        }],
+        // A list holding the single list of proofs of the only query.
+        merkle_proofs: format!("01000000{}", *MERKLE_PROOF),
+    });
 static GET_AUCTION_INFO_RESULT: Lazy<GetAuctionInfoResult> = Lazy::new(|| GetAuctionInfoResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION.clone(),
     auction_state: AuctionState::doc_example().clone(),
@@ -72,6 +122,11 @@ pub struct GetItemParams {
     /// The path components starting from the key as base.
     #[serde(default)]
     pub path: Vec<String>,
+    /// Whether to omit the stored value from the response, returning only the merkle proof.
+    ///
+    /// The proof contains the value, so this is useful to callers which already have it.
+    #[serde(default)]
+    pub proof_only: bool,
 }
 
 impl DocExample for GetItemParams {
@@ -87,8 +142,9 @@ pub struct GetItemResult {
     /// The RPC API version.
     #[schemars(with = "String")]
     pub api_version: Version,
-    /// The stored value.
-    pub stored_value: StoredValue,
+    /// The stored value, omitted if only the proof was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_value: Option<StoredValue>,
     /// The merkle proof.
     pub merkle_proof: String,
 }
@@ -154,7 +210,11 @@ impl RpcWithParamsExt for GetItem {
 
             let result = Self::ResponseResult {
                 api_version,
-                stored_value,
+                stored_value: if params.proof_only {
+                    None
+                } else {
+                    Some(stored_value)
+                },
                 merkle_proof: hex::encode(proof_bytes),
             };
 
@@ -284,6 +344,352 @@ impl RpcWithParamsExt for GetBalance {
     }
 }
 
+/// The maximum number of keys accepted by a single "state_get_balance_batch" request.
+pub const MAX_BALANCE_BATCH_KEYS: usize = 500;
+
+/// Params for "state_get_balance_batch" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBalanceBatchParams {
+    /// The hash of state root.
+    pub state_root_hash: Digest,
+    /// Formatted purse URefs or account hash keys.  For an account, its main purse is read.
+    pub keys: Vec<String>,
+}
+
+impl DocExample for GetBalanceBatchParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BALANCE_BATCH_PARAMS
+    }
+}
+
+/// The balance read for a single key of a "state_get_balance_batch" request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PurseBalance {
+    /// The key as given in the request.
+    pub key: String,
+    /// The balance value, if it could be read.
+    pub balance_value: Option<U512>,
+    /// The reason the balance could not be read.
+    pub error: Option<String>,
+}
+
+/// Result for "state_get_balance_batch" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBalanceBatchResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The balances, in the order of the requested keys.
+    pub balances: Vec<PurseBalance>,
+}
+
+impl DocExample for GetBalanceBatchResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BALANCE_BATCH_RESULT
+    }
+}
+
+/// "state_get_balance_batch" RPC.
+pub struct GetBalanceBatch {}
+
+impl RpcWithParams for GetBalanceBatch {
+    const METHOD: &'static str = "state_get_balance_batch";
+    type RequestParams = GetBalanceBatchParams;
+    type ResponseResult = GetBalanceBatchResult;
+}
+
+impl RpcWithParamsExt for GetBalanceBatch {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            if params.keys.len() > MAX_BALANCE_BATCH_KEYS {
+                let error_msg = format!(
+                    "get-balance-batch requested {} keys, at most {} are allowed",
+                    params.keys.len(),
+                    MAX_BALANCE_BATCH_KEYS
+                );
+                info!("{}", error_msg);
+                return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                    ErrorCode::BalanceBatchTooLarge as i64,
+                    error_msg,
+                ))?);
+            }
+
+            // Try to parse all the keys from the params.
+            let mut keys = Vec::with_capacity(params.keys.len());
+            for formatted_key in &params.keys {
+                match Key::from_formatted_str(formatted_key) {
+                    Ok(key) => keys.push(key),
+                    Err(error) => {
+                        let error_msg =
+                            format!("failed to parse key {}: {:?}", formatted_key, error);
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParseQueryKey as i64,
+                            error_msg,
+                        ))?);
+                    }
+                }
+            }
+
+            // Get the balances.
+            let balance_batch_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBalanceBatch {
+                        state_root_hash: params.state_root_hash,
+                        keys,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let balances = match balance_batch_result {
+                Ok(BalanceBatchResult::Success(balances)) => balances,
+                Ok(balance_batch_result) => {
+                    let error_msg = format!("get-balance-batch failed: {:?}", balance_batch_result);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailed as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error) => {
+                    let error_msg = format!("get-balance-batch failed to execute: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailedToExecute as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let balances = params
+                .keys
+                .into_iter()
+                .zip(balances)
+                .map(|(key, (_, balance))| match balance {
+                    Ok(balance_value) => PurseBalance {
+                        key,
+                        balance_value: Some(balance_value),
+                        error: None,
+                    },
+                    Err(error) => PurseBalance {
+                        key,
+                        balance_value: None,
+                        error: Some(error.to_string()),
+                    },
+                })
+                .collect();
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                balances,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// A single query of a "query_global_state_batch" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GlobalStateQuery {
+    /// `casper_types::Key` as formatted string.
+    pub key: String,
+    /// The path components starting from the key as base.
+    #[serde(default)]
+    pub path: Vec<String>,
+}
+
+/// Params for "query_global_state_batch" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryGlobalStateBatchParams {
+    /// Hash of the state root.
+    pub state_root_hash: Digest,
+    /// The values to query.
+    pub queries: Vec<GlobalStateQuery>,
+}
+
+impl DocExample for QueryGlobalStateBatchParams {
+    fn doc_example() -> &'static Self {
+        &*QUERY_GLOBAL_STATE_BATCH_PARAMS
+    }
+}
+
+/// The value found for a single query of a "query_global_state_batch" request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueriedValue {
+    /// The stored value, if it was found.
+    pub stored_value: Option<StoredValue>,
+    /// The reason the value was not found.
+    pub error: Option<String>,
+}
+
+/// Result for "query_global_state_batch" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryGlobalStateBatchResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: Version,
+    /// The values, in the order of the requested queries.
+    pub values: Vec<QueriedValue>,
+    /// The merkle proofs of all the values, as a single serialized list holding the proofs of
+    /// each query in request order.  The list is empty for queries which found no value.
+    pub merkle_proofs: String,
+}
+
+impl DocExample for QueryGlobalStateBatchResult {
+    fn doc_example() -> &'static Self {
+        &*QUERY_GLOBAL_STATE_BATCH_RESULT
+    }
+}
+
+/// "query_global_state_batch" RPC.
+pub struct QueryGlobalStateBatch {}
+
+impl RpcWithParams for QueryGlobalStateBatch {
+    const METHOD: &'static str = "query_global_state_batch";
+    type RequestParams = QueryGlobalStateBatchParams;
+    type ResponseResult = QueryGlobalStateBatchResult;
+}
+
+impl RpcWithParamsExt for QueryGlobalStateBatch {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: Version,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Try to parse all the keys from the params.
+            let mut queries = Vec::with_capacity(params.queries.len());
+            for query in params.queries {
+                match Key::from_formatted_str(&query.key) {
+                    Ok(key) => queries.push((key, query.path)),
+                    Err(error) => {
+                        let error_msg = format!("failed to parse key {}: {:?}", query.key, error);
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParseQueryKey as i64,
+                            error_msg,
+                        ))?);
+                    }
+                }
+            }
+
+            // Run the queries.
+            let query_batch_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::QueryGlobalStateBatch {
+                        state_root_hash: params.state_root_hash,
+                        queries,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let (error_code, error_msg) = match query_batch_result {
+                Ok(QueryBatchResult::Success(results)) => {
+                    return match collect_query_batch_results(results) {
+                        Ok((values, merkle_proofs)) => {
+                            let result = Self::ResponseResult {
+                                api_version,
+                                values,
+                                merkle_proofs,
+                            };
+                            Ok(response_builder.success(result)?)
+                        }
+                        Err(error_msg) => {
+                            info!("{}", error_msg);
+                            Ok(response_builder.error(warp_json_rpc::Error::custom(
+                                ErrorCode::QueryFailed as i64,
+                                error_msg,
+                            ))?)
+                        }
+                    };
+                }
+                Ok(QueryBatchResult::TooManyQueries { max_queries }) => (
+                    ErrorCode::QueryBatchTooManyKeys,
+                    format!(
+                        "query-global-state-batch requested too many keys, at most {} are allowed",
+                        max_queries
+                    ),
+                ),
+                Ok(QueryBatchResult::ResponseTooLarge { max_response_size }) => (
+                    ErrorCode::QueryBatchResponseTooLarge,
+                    format!(
+                        "query-global-state-batch response would exceed {} bytes",
+                        max_response_size
+                    ),
+                ),
+                Ok(QueryBatchResult::RootNotFound) => (
+                    ErrorCode::QueryFailed,
+                    "query-global-state-batch failed: root not found".to_string(),
+                ),
+                Err(error) => (
+                    ErrorCode::QueryFailedToExecute,
+                    format!("query-global-state-batch failed to execute: {}", error),
+                ),
+            };
+            info!("{}", error_msg);
+            Ok(response_builder
+                .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?)
+        }
+        .boxed()
+    }
+}
+
+/// Converts the results of a query batch into the values found, and the merkle proofs of all of
+/// them serialized as a single hex-encoded list.
+fn collect_query_batch_results(
+    results: Vec<QueryResult>,
+) -> Result<(Vec<QueriedValue>, String), String> {
+    let mut values = Vec::with_capacity(results.len());
+    let mut proofs = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            QueryResult::Success {
+                value,
+                proofs: value_proofs,
+            } => {
+                let stored_value = StoredValue::try_from(&*value)
+                    .map_err(|error| format!("failed to encode stored value: {:?}", error))?;
+                values.push(QueriedValue {
+                    stored_value: Some(stored_value),
+                    error: None,
+                });
+                proofs.push(value_proofs);
+            }
+            QueryResult::RootNotFound => return Err("root not found".to_string()),
+            QueryResult::ValueNotFound(error_msg) | QueryResult::CircularReference(error_msg) => {
+                values.push(QueriedValue {
+                    stored_value: None,
+                    error: Some(error_msg),
+                });
+                proofs.push(vec![]);
+            }
+        }
+    }
+    let proof_bytes = proofs
+        .to_bytes()
+        .map_err(|error| format!("failed to encode merkle proofs: {:?}", error))?;
+    Ok((values, hex::encode(proof_bytes)))
+}
+
 /// Result for "state_get_auction_info" RPC response.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -26,95 +26,196 @@
 //! for sending messages.
 //!
 //! Nodes gossip their public listening addresses periodically, and on learning of a new address,
-//! a node will try to establish an outgoing connection.
+//! a node will try to establish an outgoing connection.  Nodes also periodically ask a random peer
+//! for a bounded sample of the addresses it knows about (see the `peer_exchange` module).
 //!
 //! On losing an incoming or outgoing connection for a given peer, the other connection is closed.
 //! No explicit reconnect is attempted. Instead, if the peer is still online, the normal gossiping
 //! process will cause both peers to connect again.
+//!
+//! # Public address
+//!
+//! A node behind NAT can have its listening port mapped by a NAT-PMP gateway, and periodically asks
+//! a random peer to check that its public address is reachable (see the `reachability` module).
 
+mod blocklist;
+mod codec;
 mod config;
 mod error;
 mod event;
 mod gossiped_address;
+mod health;
 mod message;
+mod nat_pmp;
+mod outgoing_queue;
+mod peer_exchange;
+mod reachability;
 #[cfg(test)]
 mod tests;
+mod topology;
 
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
-    convert::Infallible,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    convert::{Infallible, TryFrom},
     env,
     fmt::{self, Debug, Display, Formatter},
     io,
-    net::{SocketAddr, TcpListener},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener},
     result,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
+use bytes::Bytes;
 use datasize::DataSize;
 use futures::{
     future::{select, BoxFuture, Either},
     stream::{SplitSink, SplitStream},
     FutureExt, SinkExt, StreamExt,
 };
-use once_cell::sync::Lazy;
 use openssl::{error::ErrorStack as OpenSslErrorStack, pkey};
 use pkey::{PKey, Private};
 use prometheus::{IntGauge, Registry};
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
 use thiserror::Error;
 use tokio::{
     net::TcpStream,
-    sync::{
-        mpsc::{self, UnboundedReceiver, UnboundedSender},
-        watch,
-    },
-    task::JoinHandle,
+    sync::watch,
+    task::{self, JoinHandle},
 };
 use tokio_openssl::SslStream;
-use tokio_serde::{formats::SymmetricalMessagePack, SymmetricallyFramed};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 use tracing::{debug, error, info, trace, warn};
 
-use self::error::Result;
-pub(crate) use self::{event::Event, gossiped_address::GossipedAddress, message::Message};
+use self::{
+    blocklist::Blocklist,
+    error::Result,
+    health::ConnectionHealth,
+    outgoing_queue::{QueueLimits, SendError},
+    peer_exchange::{AddressBook, MAX_SHARED_ADDRESSES},
+    reachability::Reachability,
+};
+pub(crate) use self::{event::Event, gossiped_address::GossipedAddress};
 use crate::{
     components::{
         network::ENABLE_LIBP2P_NET_ENV_VAR, networking_metrics::NetworkingMetrics, Component,
     },
     effect::{
         announcements::NetworkAnnouncement,
-        requests::{NetworkInfoRequest, NetworkRequest},
+        requests::{DumpStateRequest, NetworkInfoRequest, NetworkRequest},
         EffectBuilder, EffectExt, EffectResultExt, Effects,
     },
     fatal,
-    reactor::{EventQueueHandle, Finalize, QueueKind},
+    reactor::{correlation_id::CorrelationId, EventQueueHandle, Finalize, QueueKind},
     tls::{self, TlsCert, ValidationError},
-    types::{NodeId, TimeDiff, Timestamp},
+    types::{ExitCode, NodeId, Tag, Timestamp},
     utils, NodeRng,
 };
+pub use blocklist::BlocklistEntry;
+pub use codec::{decode_message, encode_message};
 pub use config::Config;
 pub use error::Error;
+pub use message::Message;
+pub use peer_exchange::SharedAddress;
+pub use reachability::{PublicEndpoint, ReachabilityCheck};
+pub use topology::{ConnectionDirection, ConnectionInfo, NetworkTopology};
 
 const MAX_ASYMMETRIC_CONNECTION_SEEN: u16 = 3;
-static BLOCKLIST_RETAIN_DURATION: Lazy<TimeDiff> =
-    Lazy::new(|| Duration::from_secs(60 * 10).into());
 
+/// The maximum number of connections opened to addresses learned from a single peer exchange
+/// response.
+const MAX_CONNECTIONS_PER_PEER_EXCHANGE: usize = 4;
+
+/// The delay before retrying a failed port mapping request.
+const PORT_MAPPING_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The maximum length in bytes of a single message frame sent or received over a connection.
+pub(crate) const MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// A payload which may be dropped on arrival when the node is overloaded.
+pub(crate) trait SheddablePayload {
+    /// Returns whether the payload is low-priority gossip which may be dropped while the received
+    /// messages waiting in the event queue exceed their memory budget.
+    fn is_sheddable(&self) -> bool;
+}
+
+/// A payload which may serve an item to the peer it is sent to.
+pub(crate) trait ServingPayload {
+    /// Returns the tag of the item if the payload is a response serving it to a peer's request.
+    fn served_tag(&self) -> Option<Tag>;
+}
+
+/// The class of an outgoing message, determining its queue on the connection.
+///
+/// Queued messages are sent in strict order of class, highest priority first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum MessageClass {
+    /// Consensus traffic, finality signatures and connection upkeep.  Never dropped.
+    Priority,
+    /// Gossip, of which the oldest is dropped when the queue is full.
+    Gossip,
+    /// Requests and responses for items, rejected when the queue is full.
+    Bulk,
+}
+
+/// A payload which is queued for sending according to its class.
+pub(crate) trait PrioritizedPayload {
+    /// Returns the class of the payload.
+    fn message_class(&self) -> MessageClass;
+}
+
+impl<P: SheddablePayload> SheddablePayload for Message<P> {
+    fn is_sheddable(&self) -> bool {
+        match self {
+            Message::Payload(payload) => payload.is_sheddable(),
+            Message::Handshake { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::PeerExchangeRequest
+            | Message::PeerExchangeResponse { .. }
+            | Message::ReachabilityRequest { .. }
+            | Message::ReachabilityResponse { .. } => false,
+        }
+    }
+}
+
+impl<P: PrioritizedPayload> PrioritizedPayload for Message<P> {
+    fn message_class(&self) -> MessageClass {
+        match self {
+            Message::Payload(payload) => payload.message_class(),
+            Message::Handshake { .. } | Message::Ping { .. } | Message::Pong { .. } => {
+                MessageClass::Priority
+            }
+            Message::PeerExchangeRequest
+            | Message::PeerExchangeResponse { .. }
+            | Message::ReachabilityRequest { .. }
+            | Message::ReachabilityResponse { .. } => MessageClass::Gossip,
+        }
+    }
+}
 #[derive(DataSize, Debug)]
 pub(crate) struct OutgoingConnection<P> {
-    #[data_size(skip)] // Unfortunately, there is no way to inspect an `UnboundedSender`.
-    sender: UnboundedSender<Message<P>>,
+    #[data_size(skip)] // The queued messages are owned by the sender task's future as well.
+    sender: outgoing_queue::Sender<Message<P>>,
     peer_address: SocketAddr,
 
     // for keeping track of connection asymmetry, tracking the number of times we've seen this
     // connection be asymmetric.
     times_seen_asymmetric: u16,
+
+    /// Health of the connection, as determined by pings sent over it.
+    health: ConnectionHealth,
+
+    /// The time the connection was established.
+    connected_since: Timestamp,
+    /// The kinds of items served to the peer over this connection.
+    served_tags: BTreeSet<Tag>,
 }
 
 #[derive(DataSize, Debug)]
@@ -124,6 +225,11 @@ pub(crate) struct IncomingConnection {
     // for keeping track of connection asymmetry, tracking the number of times we've seen this
     // connection be asymmetric.
     times_seen_asymmetric: u16,
+
+    /// The time the connection was established.
+    connected_since: Timestamp,
+    /// The time the last message was received over this connection.
+    last_seen: Timestamp,
 }
 
 #[derive(DataSize)]
@@ -137,6 +243,15 @@ where
     secret_key: Arc<PKey<Private>>,
     /// Our public listening address.
     public_address: SocketAddr,
+    /// The port we are listening on.
+    listening_port: u16,
+    /// The NAT-PMP gateway to ask for a mapping of our listening port, if any.
+    nat_pmp_gateway: Option<Ipv4Addr>,
+    /// Our public endpoint and the state of the reachability checks.
+    reachability: Reachability,
+    /// The interval between each request to a random peer to check our reachability, or zero if
+    /// disabled.
+    reachability_check_interval: Duration,
     /// Our node ID,
     our_id: NodeId,
     /// If we connect to ourself, this flag is set to true.
@@ -149,13 +264,23 @@ where
     /// Outgoing network connections' messages.
     outgoing: HashMap<NodeId, OutgoingConnection<P>>,
 
-    /// List of addresses which this node will avoid connecting to and the time they were added.
-    blocklist: HashMap<SocketAddr, Timestamp>,
+    /// Peers which misbehaved recently, and whose addresses this node avoids connecting to.
+    blocklist: Blocklist,
 
     /// Pending outgoing connections: ones for which we are currently trying to make a connection.
     pending: HashSet<SocketAddr>,
     /// The interval between each fresh round of gossiping the node's public listening address.
     gossip_interval: Duration,
+    /// The interval between each round of pinging connected peers.
+    ping_interval: Duration,
+    /// The number of consecutive failed pings after which a peer is disconnected.
+    max_failed_pings: u32,
+    /// The interval between each request to a random peer for the addresses it knows about.
+    peer_exchange_interval: Duration,
+    /// The bounds of the gossip and bulk queues of each outgoing connection.
+    outgoing_queue_limits: QueueLimits,
+    /// Addresses learned through peer exchange or our own outgoing connections.
+    address_book: AddressBook,
     /// Name of the network we participate in. We only remain connected to peers with the same
     /// network name as us.
     network_name: String,
@@ -183,7 +308,16 @@ where
 
 impl<REv, P> SmallNetwork<REv, P>
 where
-    P: Serialize + DeserializeOwned + Clone + Debug + Display + Send + 'static,
+    P: Serialize
+        + DeserializeOwned
+        + Clone
+        + Debug
+        + Display
+        + SheddablePayload
+        + ServingPayload
+        + PrioritizedPayload
+        + Send
+        + 'static,
     REv: Send + From<Event<P>> + From<NetworkAnnouncement<NodeId, P>>,
 {
     /// Creates a new small network component instance.
@@ -205,10 +339,15 @@ where
             return Err(Error::InvalidConfig);
         }
 
-        let mut public_address =
+        let configured_public_address =
             utils::resolve_address(&cfg.public_address).map_err(Error::ResolveAddr)?;
+        let mut public_address = configured_public_address;
 
         let our_id = NodeId::from(&small_network_identity);
+        let outgoing_queue_limits = QueueLimits {
+            gossip: cfg.max_queued_outgoing_gossip as usize,
+            bulk: cfg.max_queued_outgoing_bulk as usize,
+        };
         let secret_key = small_network_identity.secret_key;
         let certificate = small_network_identity.tls_certificate;
 
@@ -220,14 +359,27 @@ where
                 certificate,
                 secret_key,
                 public_address,
+                listening_port: public_address.port(),
+                nat_pmp_gateway: None,
+                reachability: Reachability::new(configured_public_address, public_address),
+                reachability_check_interval: cfg.reachability_check_interval,
                 our_id,
                 is_bootstrap_node: false,
                 event_queue,
                 incoming: HashMap::new(),
                 outgoing: HashMap::new(),
                 pending: HashSet::new(),
-                blocklist: HashMap::new(),
+                blocklist: Blocklist::new(
+                    cfg.blocklist_duration.into(),
+                    cfg.max_blocklist_duration.into(),
+                    cfg.blocklist_offense_decay.into(),
+                ),
                 gossip_interval: cfg.gossip_interval,
+                ping_interval: cfg.ping_interval,
+                max_failed_pings: cfg.max_failed_pings,
+                peer_exchange_interval: cfg.peer_exchange_interval,
+                outgoing_queue_limits,
+                address_book: AddressBook::default(),
                 network_name,
                 shutdown_sender: None,
                 shutdown_receiver: watch::channel(()).1,
@@ -239,6 +391,9 @@ where
         }
 
         let net_metrics = NetworkingMetrics::new(&registry)?;
+        event_queue.set_memory_budget(
+            usize::try_from(cfg.max_queued_incoming_bytes).unwrap_or(usize::MAX),
+        );
 
         // We can now create a listener.
         let bind_address = utils::resolve_address(&cfg.bind_address).map_err(Error::ResolveAddr)?;
@@ -284,14 +439,27 @@ where
             certificate,
             secret_key,
             public_address,
+            listening_port: local_address.port(),
+            nat_pmp_gateway: cfg.nat_pmp_gateway(),
+            reachability: Reachability::new(configured_public_address, public_address),
+            reachability_check_interval: cfg.reachability_check_interval,
             our_id,
             is_bootstrap_node: false,
             event_queue,
             incoming: HashMap::new(),
             outgoing: HashMap::new(),
             pending: HashSet::new(),
-            blocklist: HashMap::new(),
+            blocklist: Blocklist::new(
+                cfg.blocklist_duration.into(),
+                cfg.max_blocklist_duration.into(),
+                cfg.blocklist_offense_decay.into(),
+            ),
             gossip_interval: cfg.gossip_interval,
+            ping_interval: cfg.ping_interval,
+            max_failed_pings: cfg.max_failed_pings,
+            peer_exchange_interval: cfg.peer_exchange_interval,
+            outgoing_queue_limits,
+            address_book: AddressBook::default(),
             network_name,
             shutdown_sender: Some(server_shutdown_sender),
             shutdown_receiver,
@@ -341,6 +509,7 @@ where
             effects.extend(
                 fatal!(
                     effect_builder,
+                    ExitCode::NetworkFailure,
                     "was given known addresses, but failed to resolve any of them"
                 )
                 .ignore(),
@@ -348,6 +517,24 @@ where
         } else {
             // Start broadcasting our public listening address.
             effects.extend(model.gossip_our_address(effect_builder));
+            effects.extend(
+                effect_builder
+                    .set_timeout(model.ping_interval)
+                    .event(|_| Event::SendPings),
+            );
+            effects.extend(
+                effect_builder
+                    .set_timeout(model.peer_exchange_interval)
+                    .event(|_| Event::ExchangePeers),
+            );
+            effects.extend(model.map_port());
+            if model.reachability_check_interval > Duration::from_millis(0) {
+                effects.extend(
+                    effect_builder
+                        .set_timeout(reachability::FIRST_CHECK_DELAY)
+                        .event(|_| Event::CheckReachability),
+                );
+            }
         }
 
         Ok((model, effects))
@@ -397,11 +584,20 @@ where
     fn send_message(&self, dest: NodeId, msg: Message<P>) {
         // Try to send the message.
         if let Some(connection) = self.outgoing.get(&dest) {
-            if let Err(msg) = connection.sender.send(msg) {
-                // We lost the connection, but that fact has not reached us yet.
-                warn!(our_id=%self.our_id, %dest, ?msg, "dropped outgoing message, lost connection");
-            } else {
-                self.net_metrics.queued_messages.inc();
+            match connection.sender.send(msg) {
+                Ok(None) => self.net_metrics.queued_messages.inc(),
+                Ok(Some(dropped)) => {
+                    self.net_metrics.dropped_outgoing_messages.inc();
+                    debug!(our_id=%self.our_id, %dest, ?dropped, "dropped oldest outgoing gossip, queue full");
+                }
+                Err(SendError::Full(msg)) => {
+                    self.net_metrics.dropped_outgoing_messages.inc();
+                    debug!(our_id=%self.our_id, %dest, ?msg, "dropped outgoing message, queue full");
+                }
+                Err(SendError::Closed(msg)) => {
+                    // We lost the connection, but that fact has not reached us yet.
+                    warn!(our_id=%self.our_id, %dest, ?msg, "dropped outgoing message, lost connection");
+                }
             }
         } else {
             // We are not connected, so the reconnection is likely already in progress.
@@ -443,20 +639,25 @@ where
 
                 debug!(our_id=%self.our_id, %peer_id, %peer_address, "established incoming connection");
                 // The sink is only used to send a single handshake message, then dropped.
-                let (mut sink, stream) = framed::<P>(transport).split();
-                let handshake = Message::Handshake {
+                let (mut sink, stream) = framed(transport).split();
+                let handshake = Message::<P>::Handshake {
                     network_name: self.network_name.clone(),
                 };
                 let mut effects = async move {
-                    let _ = sink.send(handshake).await;
+                    if let Ok(frame) = codec::encode_message(&handshake) {
+                        let _ = sink.send(frame).await;
+                    }
                 }
                 .ignore::<Event<P>>();
 
+                let now = Timestamp::now();
                 let _ = self.incoming.insert(
                     peer_id.clone(),
                     IncomingConnection {
                         peer_address,
                         times_seen_asymmetric: 0,
+                        connected_since: now,
+                        last_seen: now,
                     },
                 );
                 self.net_metrics
@@ -532,14 +733,20 @@ where
         }
 
         // The stream is only used to receive a single handshake message and then dropped.
-        let (sink, stream) = framed::<P>(transport).split();
+        let (sink, stream) = framed(transport).split();
         debug!(our_id=%self.our_id, %peer_id, %peer_address, "established outgoing connection");
 
-        let (sender, receiver) = mpsc::unbounded_channel();
+        self.address_book
+            .record_reachable(peer_address, Timestamp::now());
+
+        let (sender, receiver) = outgoing_queue::channel(self.outgoing_queue_limits);
         let connection = OutgoingConnection {
             peer_address,
             sender,
             times_seen_asymmetric: 0,
+            health: ConnectionHealth::default(),
+            connected_since: Timestamp::now(),
+            served_tags: BTreeSet::new(),
         };
         if self.outgoing.insert(peer_id.clone(), connection).is_some() {
             // We assume that for a reconnect to have happened, the outgoing entry must have
@@ -603,6 +810,7 @@ where
         // outgoing connection, hence no need to call `self.remove()`.
         if let Some(err) = error {
             warn!(our_id=%self.our_id, %peer_address, %err, "outgoing connection failed");
+            self.address_book.record_unreachable(peer_address);
         } else {
             warn!(our_id=%self.our_id, %peer_address, "outgoing connection closed");
         }
@@ -626,11 +834,19 @@ where
         }
         if let Some(outgoing) = self.outgoing.remove(&peer_id) {
             trace!(our_id=%self.our_id, %peer_id, "removing peer from the outgoing connections");
+            let _ = self
+                .net_metrics
+                .peer_ping_rtt
+                .remove_label_values(&[&peer_id.to_string()]);
             let peer_ip = format!("{}", outgoing.peer_address.ip());
             if add_to_blocklist && !self.known_addresses.contains(&peer_ip) {
-                info!(our_id=%self.our_id, %peer_id, "blocklisting peer");
-                self.blocklist
-                    .insert(outgoing.peer_address, Timestamp::now());
+                let now = Timestamp::now();
+                let duration =
+                    self.blocklist
+                        .record_offense(peer_id.clone(), outgoing.peer_address, now);
+                info!(our_id=%self.our_id, %peer_id, %duration, "blocklisting peer");
+                self.net_metrics.blocklist_offenses.inc();
+                self.update_blocklist_metric(now);
             }
         }
         self.terminate_if_isolated(effect_builder)
@@ -650,6 +866,308 @@ where
         effects
     }
 
+    /// Pings all peers we have an outgoing connection to, disconnecting those which failed to
+    /// answer too many pings in a row, and schedules the next round of pings.
+    fn send_pings(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+    ) -> Effects<Event<P>> {
+        let now = Instant::now();
+        let mut unresponsive = Vec::new();
+        for (peer_id, connection) in self.outgoing.iter_mut() {
+            let nonce = rng.gen();
+            if connection.health.ping_sent(nonce, now) {
+                self.net_metrics.failed_pings.inc();
+                let failures = connection.health.consecutive_failures();
+                if failures >= self.max_failed_pings {
+                    warn!(
+                        our_id=%self.our_id,
+                        %peer_id,
+                        %failures,
+                        "peer failed to answer pings, disconnecting"
+                    );
+                    unresponsive.push(peer_id.clone());
+                    continue;
+                }
+                debug!(our_id=%self.our_id, %peer_id, %failures, "peer failed to answer ping");
+            }
+            if connection.sender.send(Message::Ping { nonce }).is_err() {
+                // We lost the connection, but that fact has not reached us yet.
+                debug!(our_id=%self.our_id, %peer_id, "dropped ping, lost connection");
+            } else {
+                self.net_metrics.queued_messages.inc();
+            }
+        }
+
+        let mut effects = Effects::new();
+        for peer_id in unresponsive {
+            effects.extend(self.remove(effect_builder, &peer_id, false));
+        }
+        self.update_peers_metric();
+        effects.extend(
+            effect_builder
+                .set_timeout(self.ping_interval)
+                .event(|_| Event::SendPings),
+        );
+        effects
+    }
+
+    /// Records a pong received from the given peer.
+    fn handle_pong(&mut self, peer_id: &NodeId, nonce: u64) {
+        let connection = match self.outgoing.get_mut(peer_id) {
+            Some(connection) => connection,
+            None => {
+                debug!(our_id=%self.our_id, %peer_id, "received pong from peer without outgoing connection");
+                return;
+            }
+        };
+        match connection.health.pong_received(nonce, Instant::now()) {
+            Some(rtt) => {
+                trace!(our_id=%self.our_id, %peer_id, ?rtt, "received pong");
+                self.net_metrics
+                    .peer_ping_rtt
+                    .with_label_values(&[&peer_id.to_string()])
+                    .observe(rtt.as_secs_f64());
+            }
+            None => debug!(our_id=%self.our_id, %peer_id, %nonce, "received unexpected pong"),
+        }
+    }
+
+    /// Asks a random peer for the addresses it knows about, and schedules the next such request.
+    fn exchange_peers(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+    ) -> Effects<Event<P>> {
+        let now = Timestamp::now();
+        // Any request still unanswered from the previous round is forgotten, so a late response
+        // to it is ignored.
+        self.address_book.prune(now);
+        for connection in self.outgoing.values() {
+            self.address_book
+                .record_reachable(connection.peer_address, now);
+        }
+        if let Some(peer_id) = self.outgoing.keys().choose(rng).cloned() {
+            trace!(our_id=%self.our_id, %peer_id, "requesting peer exchange");
+            self.address_book.request_sent(peer_id.clone());
+            self.send_message(peer_id, Message::PeerExchangeRequest);
+        }
+        effect_builder
+            .set_timeout(self.peer_exchange_interval)
+            .event(|_| Event::ExchangePeers)
+    }
+
+    /// Answers a peer exchange request, unless the peer has asked too recently.
+    fn handle_peer_exchange_request(&mut self, rng: &mut NodeRng, peer_id: NodeId) {
+        if !self.address_book.should_answer(&peer_id, Timestamp::now()) {
+            debug!(our_id=%self.our_id, %peer_id, "ignoring too frequent peer exchange request");
+            return;
+        }
+        let addresses = self.address_book.sample(rng, Timestamp::now());
+        self.send_message(peer_id, Message::PeerExchangeResponse { addresses });
+    }
+
+    /// Adds the addresses from a solicited peer exchange response to the address book, and
+    /// connects to a few of the newly learned ones the peer could reach.
+    fn handle_peer_exchange_response(
+        &mut self,
+        peer_id: NodeId,
+        addresses: Vec<SharedAddress>,
+    ) -> Effects<Event<P>> {
+        if !self.address_book.response_received(&peer_id) {
+            debug!(our_id=%self.our_id, %peer_id, "ignoring unsolicited peer exchange response");
+            return Effects::new();
+        }
+        if addresses.len() > MAX_SHARED_ADDRESSES {
+            warn!(
+                our_id=%self.our_id,
+                %peer_id,
+                count=addresses.len(),
+                "ignoring oversized peer exchange response"
+            );
+            return Effects::new();
+        }
+        let now = Timestamp::now();
+        let public_address = self.public_address;
+        let address_book = &mut self.address_book;
+        let to_connect: Vec<_> = addresses
+            .into_iter()
+            .filter(|shared| shared.address != public_address)
+            .filter(|shared| address_book.insert_learned(*shared, now) && shared.reachable)
+            .map(|shared| shared.address)
+            .collect();
+        debug!(
+            our_id=%self.our_id,
+            %peer_id,
+            learned=to_connect.len(),
+            known=self.address_book.len(),
+            "handled peer exchange response"
+        );
+        let mut effects = Effects::new();
+        for address in to_connect
+            .into_iter()
+            .take(MAX_CONNECTIONS_PER_PEER_EXCHANGE)
+        {
+            effects.extend(self.connect_to_peer_if_required(address));
+        }
+        effects
+    }
+
+    /// Asks the NAT-PMP gateway, if any, to map our listening port or renew the mapping.
+    fn map_port(&self) -> Effects<Event<P>> {
+        let gateway = match self.nat_pmp_gateway {
+            Some(gateway) => gateway,
+            None => return Effects::new(),
+        };
+        let port = self.listening_port;
+        async move {
+            task::spawn_blocking(move || nat_pmp::map_tcp_port(gateway, port))
+                .await
+                .unwrap_or_else(|error| {
+                    Err(io::Error::new(io::ErrorKind::Other, error.to_string()).into())
+                })
+        }
+        .event(|result| Event::PortMapped {
+            result: Box::new(result),
+        })
+    }
+
+    /// Advertises the address mapped by the NAT-PMP gateway, and schedules the renewal of the
+    /// mapping, or a retry if it failed.
+    fn handle_port_mapped(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        result: result::Result<nat_pmp::PortMapping, nat_pmp::Error>,
+    ) -> Effects<Event<P>> {
+        let mut effects = Effects::new();
+        let delay = match result {
+            Ok(mapping) => {
+                let address = self.reachability.set_port_mapping(mapping.external_address);
+                if address != self.public_address {
+                    info!(
+                        our_id=%self.our_id,
+                        old_address=%self.public_address,
+                        new_address=%address,
+                        external_address=%mapping.external_address,
+                        "port mapped by NAT-PMP gateway, advertising new public address"
+                    );
+                    self.public_address = address;
+                    effects.extend(
+                        effect_builder
+                            .announce_gossip_our_address(GossipedAddress::new(address))
+                            .ignore(),
+                    );
+                } else {
+                    debug!(
+                        our_id=%self.our_id,
+                        external_address=%mapping.external_address,
+                        lifetime=?mapping.lifetime,
+                        "port mapping renewed"
+                    );
+                }
+                mapping.lifetime / 2
+            }
+            Err(error) => {
+                warn!(our_id=%self.our_id, %error, "failed to map port on NAT-PMP gateway");
+                PORT_MAPPING_RETRY_INTERVAL
+            }
+        };
+        effects.extend(effect_builder.set_timeout(delay).event(|_| Event::MapPort));
+        effects
+    }
+
+    /// Asks a random peer whether it can connect to our public address, and schedules the next
+    /// such check.
+    fn check_reachability(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+    ) -> Effects<Event<P>> {
+        if let Some(peer_id) = self.outgoing.keys().choose(rng).cloned() {
+            trace!(our_id=%self.our_id, %peer_id, "requesting reachability check");
+            self.reachability.request_sent(peer_id.clone());
+            let address = self.public_address;
+            self.send_message(peer_id, Message::ReachabilityRequest { address });
+        }
+        effect_builder
+            .set_timeout(self.reachability_check_interval)
+            .event(|_| Event::CheckReachability)
+    }
+
+    /// Probes the address in a peer's reachability request, unless the peer has asked too
+    /// recently.
+    ///
+    /// Only addresses at the IP the peer's connection comes from are probed.
+    fn handle_reachability_request(
+        &mut self,
+        peer_id: NodeId,
+        address: SocketAddr,
+    ) -> Effects<Event<P>> {
+        let observed_ip = match self.incoming.get(&peer_id) {
+            Some(connection) => connection.peer_address.ip(),
+            None => {
+                debug!(our_id=%self.our_id, %peer_id, "received reachability request from peer without incoming connection");
+                return Effects::new();
+            }
+        };
+        if !self.reachability.should_answer(&peer_id, Timestamp::now()) {
+            debug!(our_id=%self.our_id, %peer_id, "ignoring too frequent reachability request");
+            return Effects::new();
+        }
+        if address.ip() != observed_ip {
+            self.send_message(
+                peer_id,
+                Message::ReachabilityResponse {
+                    observed_ip,
+                    reachable: false,
+                },
+            );
+            return Effects::new();
+        }
+        reachability::probe(address).event(move |reachable| Event::ReachabilityProbed {
+            peer_id: Box::new(peer_id),
+            observed_ip,
+            reachable,
+        })
+    }
+
+    /// Records the outcome of a solicited reachability check, warning if we seem unreachable.
+    fn handle_reachability_response(
+        &mut self,
+        peer_id: NodeId,
+        observed_ip: IpAddr,
+        reachable: bool,
+    ) {
+        if !self
+            .reachability
+            .response_received(&peer_id, observed_ip, reachable, Timestamp::now())
+        {
+            debug!(our_id=%self.our_id, %peer_id, "ignoring unsolicited reachability response");
+            return;
+        }
+        if observed_ip != self.public_address.ip() {
+            warn!(
+                our_id=%self.our_id,
+                %peer_id,
+                public_address=%self.public_address,
+                %observed_ip,
+                "peer sees our connections come from a different IP than our public address; it \
+                may need to be set explicitly, or mapped by a NAT-PMP gateway"
+            );
+        } else if !reachable {
+            warn!(
+                our_id=%self.our_id,
+                %peer_id,
+                public_address=%self.public_address,
+                "peer failed to connect to our public address; incoming connections may be \
+                blocked by a firewall or NAT"
+            );
+        } else {
+            debug!(our_id=%self.our_id, %peer_id, public_address=%self.public_address, "peer confirmed our public address is reachable");
+        }
+    }
+
     /// Marks connections as asymmetric (only incoming or only outgoing) and removes them if they
     /// pass the upper limit for this. Connections that are symmetrical are reset to 0.
     fn enforce_symmetric_connections(
@@ -690,12 +1208,16 @@ where
     fn handle_message(
         &mut self,
         effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
         peer_id: NodeId,
         msg: Message<P>,
     ) -> Effects<Event<P>>
     where
         REv: From<NetworkAnnouncement<NodeId, P>>,
     {
+        if let Some(connection) = self.incoming.get_mut(&peer_id) {
+            connection.last_seen = Timestamp::now();
+        }
         match msg {
             Message::Handshake { network_name } => {
                 if network_name != self.network_name {
@@ -716,6 +1238,33 @@ where
             Message::Payload(payload) => effect_builder
                 .announce_message_received(peer_id, payload)
                 .ignore(),
+            Message::Ping { nonce } => {
+                // Incoming connections are read-only, so the pong is sent over our outgoing
+                // connection to the peer.
+                self.send_message(peer_id, Message::Pong { nonce });
+                Effects::new()
+            }
+            Message::Pong { nonce } => {
+                self.handle_pong(&peer_id, nonce);
+                Effects::new()
+            }
+            Message::PeerExchangeRequest => {
+                self.handle_peer_exchange_request(rng, peer_id);
+                Effects::new()
+            }
+            Message::PeerExchangeResponse { addresses } => {
+                self.handle_peer_exchange_response(peer_id, addresses)
+            }
+            Message::ReachabilityRequest { address } => {
+                self.handle_reachability_request(peer_id, address)
+            }
+            Message::ReachabilityResponse {
+                observed_ip,
+                reachable,
+            } => {
+                self.handle_reachability_response(peer_id, observed_ip, reachable);
+                Effects::new()
+            }
         }
     }
 
@@ -723,11 +1272,18 @@ where
         self.net_metrics.peers.set(self.peers().len() as i64);
     }
 
+    fn update_blocklist_metric(&self, now: Timestamp) {
+        self.net_metrics
+            .blocklisted_peers
+            .set(self.blocklist.blocked_count(now) as i64);
+    }
+
     fn connect_to_peer_if_required(&mut self, peer_address: SocketAddr) -> Effects<Event<P>> {
-        self.blocklist
-            .retain(|_, ts| *ts > Timestamp::now() - *BLOCKLIST_RETAIN_DURATION);
+        let now = Timestamp::now();
+        self.blocklist.purge(now);
+        self.update_blocklist_metric(now);
         if self.pending.contains(&peer_address)
-            || self.blocklist.contains_key(&peer_address)
+            || self.blocklist.is_blocked(&peer_address, now)
             || self
                 .outgoing
                 .iter()
@@ -789,6 +1345,7 @@ where
                 // just leave it up to the node operator to restart.
                 return fatal!(
                     effect_builder,
+                    ExitCode::NetworkFailure,
                     "{}: failed to connect to any known node, now isolated",
                     self.our_id
                 )
@@ -811,6 +1368,80 @@ where
         ret
     }
 
+    /// Returns the current connections of this node.
+    pub(crate) fn topology(&self) -> NetworkTopology {
+        let outgoing = self
+            .outgoing
+            .iter()
+            .map(|(peer_id, connection)| ConnectionInfo {
+                peer_id: peer_id.clone(),
+                direction: ConnectionDirection::Outgoing,
+                connected_since: connection.connected_since,
+                last_seen: None,
+                served_tags: connection.served_tags.iter().copied().collect(),
+            });
+        let incoming = self
+            .incoming
+            .iter()
+            .map(|(peer_id, connection)| ConnectionInfo {
+                peer_id: peer_id.clone(),
+                direction: ConnectionDirection::Incoming,
+                connected_since: connection.connected_since,
+                last_seen: Some(connection.last_seen),
+                served_tags: vec![],
+            });
+        NetworkTopology {
+            our_id: self.our_id.clone(),
+            connections: outgoing.chain(incoming).collect(),
+        }
+    }
+
+    /// Returns a snapshot of the internal state for debugging purposes.
+    ///
+    /// Our TLS identity and the contents of queued messages are omitted, and so are all addresses:
+    /// peers appear only by their node IDs, and addresses only as counts.
+    fn dump_state(&self) -> Value {
+        let outgoing: BTreeMap<String, Value> = self
+            .outgoing
+            .iter()
+            .map(|(node_id, connection)| {
+                let entry = json!({
+                    "times_seen_asymmetric": connection.times_seen_asymmetric,
+                    "failed_pings": connection.health.consecutive_failures(),
+                    "rtt_ms": connection
+                        .health
+                        .smoothed_rtt()
+                        .map(|rtt| rtt.as_millis() as u64),
+                });
+                (node_id.to_string(), entry)
+            })
+            .collect();
+        let incoming: BTreeMap<String, Value> = self
+            .incoming
+            .iter()
+            .map(|(node_id, connection)| {
+                let entry = json!({
+                    "times_seen_asymmetric": connection.times_seen_asymmetric,
+                });
+                (node_id.to_string(), entry)
+            })
+            .collect();
+        let endpoint = self.reachability.endpoint();
+        json!({
+            "our_id": self.our_id.to_string(),
+            "has_port_mapping": endpoint.port_mapping.is_some(),
+            "reachable": endpoint.last_check.map(|check| check.reachable),
+            "network_name": self.network_name,
+            "is_bootstrap_node": self.is_bootstrap_node,
+            "outgoing": outgoing,
+            "incoming": incoming,
+            "pending_connections": self.pending.len(),
+            "blocked_peers": self.blocklist.entries(Timestamp::now()).len(),
+            "known_addresses": self.known_addresses.len(),
+            "address_book_size": self.address_book.len(),
+        })
+    }
+
     /// Returns whether or not this node has been isolated.
     ///
     /// An isolated node has no chance of recovering a connection to the network and is not
@@ -858,7 +1489,16 @@ where
 impl<REv, P> Component<REv> for SmallNetwork<REv, P>
 where
     REv: Send + From<Event<P>> + From<NetworkAnnouncement<NodeId, P>>,
-    P: Serialize + DeserializeOwned + Clone + Debug + Display + Send + 'static,
+    P: Serialize
+        + DeserializeOwned
+        + Clone
+        + Debug
+        + Display
+        + SheddablePayload
+        + ServingPayload
+        + PrioritizedPayload
+        + Send
+        + 'static,
 {
     type Event = Event<P>;
     type ConstructionError = Infallible;
@@ -898,7 +1538,7 @@ where
                 self.handle_incoming_tls_handshake_completed(effect_builder, *result, *peer_address)
             }
             Event::IncomingMessage { peer_id, msg } => {
-                self.handle_message(effect_builder, *peer_id, *msg)
+                self.handle_message(effect_builder, rng, *peer_id, *msg)
             }
             Event::IncomingClosed {
                 result,
@@ -932,6 +1572,11 @@ where
                     } => {
                         // We're given a message to send out.
                         self.net_metrics.direct_message_requests.inc();
+                        if let Some(tag) = payload.served_tag() {
+                            if let Some(connection) = self.outgoing.get_mut(&dest) {
+                                connection.served_tags.insert(tag);
+                            }
+                        }
                         self.send_message(*dest, Message::Payload(*payload));
                         responder.respond(()).ignore()
                     }
@@ -958,6 +1603,27 @@ where
                 NetworkInfoRequest::GetPeers { responder } => {
                     responder.respond(self.peers()).ignore()
                 }
+                NetworkInfoRequest::GetTopology { responder } => {
+                    responder.respond(self.topology()).ignore()
+                }
+                NetworkInfoRequest::GetBlocklist { responder } => responder
+                    .respond(self.blocklist.entries(Timestamp::now()))
+                    .ignore(),
+                NetworkInfoRequest::ClearBlocklist { address, responder } => {
+                    let cleared = self.blocklist.clear(address);
+                    info!(?address, cleared, "cleared blocklist entries");
+                    self.update_blocklist_metric(Timestamp::now());
+                    responder.respond(cleared).ignore()
+                }
+                NetworkInfoRequest::BlockPeer { peer, responder } => {
+                    info!(our_id=%self.our_id, %peer, "disconnecting from misbehaving peer");
+                    let mut effects = self.remove(effect_builder, &peer, true);
+                    effects.extend(responder.respond(()).ignore());
+                    effects
+                }
+                NetworkInfoRequest::GetPublicEndpoint { responder } => responder
+                    .respond(Some(self.reachability.endpoint().clone()))
+                    .ignore(),
             },
             Event::GossipOurAddress => {
                 let mut effects = self.gossip_our_address(effect_builder);
@@ -967,6 +1633,29 @@ where
             Event::PeerAddressReceived(gossiped_address) => {
                 self.connect_to_peer_if_required(gossiped_address.into())
             }
+            Event::SendPings => self.send_pings(effect_builder, rng),
+            Event::ExchangePeers => self.exchange_peers(effect_builder, rng),
+            Event::MapPort => self.map_port(),
+            Event::PortMapped { result } => self.handle_port_mapped(effect_builder, *result),
+            Event::CheckReachability => self.check_reachability(effect_builder, rng),
+            Event::ReachabilityProbed {
+                peer_id,
+                observed_ip,
+                reachable,
+            } => {
+                self.send_message(
+                    *peer_id,
+                    Message::ReachabilityResponse {
+                        observed_ip,
+                        reachable,
+                    },
+                );
+                Effects::new()
+            }
+            Event::DumpStateRequest { req } => {
+                let DumpStateRequest { responder, .. } = *req;
+                responder.respond(Some(self.dump_state())).ignore()
+            }
         }
     }
 }
@@ -1107,7 +1796,7 @@ async fn setup_tls(
 /// Network handshake reader for single handshake message received by outgoing connection.
 async fn handshake_reader<REv, P>(
     event_queue: EventQueueHandle<REv>,
-    mut stream: SplitStream<FramedTransport<P>>,
+    mut stream: SplitStream<FramedTransport>,
     our_id: NodeId,
     peer_id: NodeId,
     peer_address: SocketAddr,
@@ -1115,7 +1804,11 @@ async fn handshake_reader<REv, P>(
     P: DeserializeOwned + Send + Display,
     REv: From<Event<P>>,
 {
-    if let Some(Ok(msg @ Message::Handshake { .. })) = stream.next().await {
+    let maybe_msg = match stream.next().await {
+        Some(Ok(frame)) => codec::decode_message::<P>(frame.freeze()).ok(),
+        _ => None,
+    };
+    if let Some(msg @ Message::Handshake { .. }) = maybe_msg {
         debug!(%our_id, %msg, %peer_id, "handshake received");
         return event_queue
             .schedule(
@@ -1145,32 +1838,51 @@ async fn handshake_reader<REv, P>(
 /// Schedules all received messages until the stream is closed or an error occurs.
 async fn message_reader<REv, P>(
     event_queue: EventQueueHandle<REv>,
-    mut stream: SplitStream<FramedTransport<P>>,
+    mut stream: SplitStream<FramedTransport>,
     mut shutdown_receiver: watch::Receiver<()>,
     our_id: NodeId,
     peer_id: NodeId,
 ) -> io::Result<()>
 where
-    P: DeserializeOwned + Send + Display,
+    P: Serialize + DeserializeOwned + SheddablePayload + Send + Display,
     REv: From<Event<P>>,
 {
     let our_id_ref = &our_id;
     let peer_id_cloned = peer_id.clone();
     let read_messages = async move {
-        while let Some(msg_result) = stream.next().await {
+        while let Some(frame_result) = stream.next().await {
+            let msg_result = frame_result.and_then(|frame| {
+                codec::decode_message::<P>(frame.freeze())
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            });
             match msg_result {
                 Ok(msg) => {
                     debug!(our_id=%our_id_ref, %msg, peer_id=%peer_id_cloned, "message received");
-                    // We've received a message, push it to the reactor.
-                    event_queue
-                        .schedule(
+                    // We've received a message, push it to the reactor. Its size is approximated
+                    // by its bincode encoding, which is close enough to the wire format.  Each
+                    // message is a new trigger, so it doesn't share the reader's correlation ID.
+                    let size = bincode::serialized_size(&msg).unwrap_or_default() as usize;
+                    let sheddable = msg.is_sheddable();
+                    let shed = CorrelationId::new()
+                        .scope(event_queue.schedule_sized(
                             Event::IncomingMessage {
                                 peer_id: Box::new(peer_id_cloned.clone()),
                                 msg: Box::new(msg),
                             },
                             QueueKind::NetworkIncoming,
-                        )
+                            size,
+                            sheddable,
+                        ))
                         .await;
+                    if shed > 0 {
+                        warn!(
+                            our_id=%our_id_ref,
+                            peer_id=%peer_id_cloned,
+                            shed,
+                            queued_bytes=event_queue.queued_bytes(),
+                            "event queue memory budget exceeded, dropped gossip messages"
+                        );
+                    }
                 }
                 Err(err) => {
                     warn!(our_id=%our_id_ref, %err, peer_id=%peer_id_cloned, "receiving message failed, closing connection");
@@ -1204,19 +1916,28 @@ where
 /// Initially sends a handshake including the `chainspec_hash` as a final handshake step.  If the
 /// recipient's `chainspec_hash` doesn't match, the connection will be closed.
 async fn message_sender<P>(
-    mut queue: UnboundedReceiver<Message<P>>,
-    mut sink: SplitSink<FramedTransport<P>, Message<P>>,
+    mut queue: outgoing_queue::Receiver<Message<P>>,
+    mut sink: SplitSink<FramedTransport, Bytes>,
     counter: IntGauge,
     handshake: Message<P>,
 ) -> Result<()>
 where
     P: Serialize + Send,
 {
-    sink.send(handshake).await.map_err(Error::MessageNotSent)?;
+    let encode = |message: &Message<P>| {
+        codec::encode_message(message).map_err(|error| {
+            Error::MessageNotSent(io::Error::new(io::ErrorKind::InvalidData, error))
+        })
+    };
+    sink.send(encode(&handshake)?)
+        .await
+        .map_err(Error::MessageNotSent)?;
     while let Some(payload) = queue.recv().await {
         counter.dec();
         // We simply error-out if the sink fails, it means that our connection broke.
-        sink.send(payload).await.map_err(Error::MessageNotSent)?;
+        sink.send(encode(&payload)?)
+            .await
+            .map_err(Error::MessageNotSent)?;
     }
 
     Ok(())
@@ -1225,20 +1946,15 @@ where
 /// Transport type alias for base encrypted connections.
 type Transport = SslStream<TcpStream>;
 
-/// A framed transport for `Message`s.
-type FramedTransport<P> = SymmetricallyFramed<
-    Framed<Transport, LengthDelimitedCodec>,
-    Message<P>,
-    SymmetricalMessagePack<Message<P>>,
->;
+/// A framed transport, carrying `Message`s encoded by the `codec` module.
+type FramedTransport = Framed<Transport, LengthDelimitedCodec>;
 
 /// Constructs a new framed transport on a stream.
-fn framed<P>(stream: Transport) -> FramedTransport<P> {
-    let length_delimited = Framed::new(stream, LengthDelimitedCodec::new());
-    SymmetricallyFramed::new(
-        length_delimited,
-        SymmetricalMessagePack::<Message<P>>::default(),
-    )
+fn framed(stream: Transport) -> FramedTransport {
+    let codec = LengthDelimitedCodec::builder()
+        .max_frame_length(MAX_FRAME_LENGTH)
+        .new_codec();
+    Framed::new(stream, codec)
 }
 
 /// Initiates a TLS connection to a remote address.
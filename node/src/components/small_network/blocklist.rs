@@ -0,0 +1,256 @@
+//! Blocklisting of misbehaving peers.
+//!
+//! While a peer is blocklisted, we don't connect to its address.  Bans escalate for repeat
+//! offenders: the first offense bans a peer for `base_duration`, and each further one doubles
+//! the ban, up to `max_duration`.  A peer's offense score decays by one for every `offense_decay`
+//! without a new offense, so that a peer which misbehaved long ago starts over with a short ban.
+//!
+//! Entries are only held in memory, keyed by node ID, and dropped once their ban has expired and
+//! their score has decayed to zero.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use datasize::DataSize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{NodeId, TimeDiff, Timestamp};
+
+/// A blocklisted peer, as reported to operators.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlocklistEntry {
+    /// The ID of the peer.
+    pub node_id: String,
+    /// The address of the peer.
+    #[schemars(with = "String")]
+    pub address: SocketAddr,
+    /// The peer's current offense score.
+    pub offenses: u32,
+    /// The time the peer's ban expires.
+    pub blocked_until: Timestamp,
+}
+
+#[derive(DataSize, Debug)]
+struct Entry {
+    address: SocketAddr,
+    /// The offense score right after the last offense.
+    offenses: u32,
+    last_offense: Timestamp,
+    blocked_until: Timestamp,
+}
+
+/// The peers which misbehaved recently.
+#[derive(DataSize, Debug)]
+pub(super) struct Blocklist {
+    base_duration: TimeDiff,
+    max_duration: TimeDiff,
+    /// The time after which one offense is forgotten.  Zero means offenses are never forgotten.
+    offense_decay: TimeDiff,
+    entries: HashMap<NodeId, Entry>,
+}
+
+impl Blocklist {
+    pub(super) fn new(
+        base_duration: TimeDiff,
+        max_duration: TimeDiff,
+        offense_decay: TimeDiff,
+    ) -> Self {
+        Blocklist {
+            base_duration,
+            max_duration,
+            offense_decay,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the offense score of `entry` at `now`, after decay.
+    fn offenses_at(&self, entry: &Entry, now: Timestamp) -> u32 {
+        let elapsed = now.saturating_diff(entry.last_offense).millis();
+        let decayed = elapsed
+            .checked_div(self.offense_decay.millis())
+            .unwrap_or(0);
+        if decayed >= u64::from(entry.offenses) {
+            0
+        } else {
+            entry.offenses - decayed as u32
+        }
+    }
+
+    /// Returns the ban duration for a peer with the given offense score.
+    fn ban_duration(&self, offenses: u32) -> TimeDiff {
+        let factor = 1u64
+            .checked_shl(offenses.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let millis = self.base_duration.millis().saturating_mul(factor);
+        TimeDiff::from(millis).min(self.max_duration)
+    }
+
+    /// Records an offense of the peer at `address`, and returns how long it is banned for.
+    pub(super) fn record_offense(
+        &mut self,
+        node_id: NodeId,
+        address: SocketAddr,
+        now: Timestamp,
+    ) -> TimeDiff {
+        let previous_offenses = self
+            .entries
+            .get(&node_id)
+            .map_or(0, |entry| self.offenses_at(entry, now));
+        let offenses = previous_offenses.saturating_add(1);
+        let duration = self.ban_duration(offenses);
+        let entry = Entry {
+            address,
+            offenses,
+            last_offense: now,
+            blocked_until: now + duration,
+        };
+        self.entries.insert(node_id, entry);
+        duration
+    }
+
+    /// Returns whether a peer at `address` is banned at `now`.
+    pub(super) fn is_blocked(&self, address: &SocketAddr, now: Timestamp) -> bool {
+        self.entries
+            .values()
+            .any(|entry| entry.address == *address && entry.blocked_until > now)
+    }
+
+    /// Returns the number of peers banned at `now`.
+    pub(super) fn blocked_count(&self, now: Timestamp) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| entry.blocked_until > now)
+            .count()
+    }
+
+    /// Drops the entries whose ban has expired and whose offenses have been forgotten.
+    pub(super) fn purge(&mut self, now: Timestamp) {
+        let expired: Vec<NodeId> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.blocked_until <= now && self.offenses_at(entry, now) == 0)
+            .map(|(node_id, _)| node_id.clone())
+            .collect();
+        for node_id in expired {
+            self.entries.remove(&node_id);
+        }
+    }
+
+    /// Removes the entries for `address`, or all entries if `None`, and returns how many were
+    /// removed.
+    pub(super) fn clear(&mut self, address: Option<SocketAddr>) -> usize {
+        let count_before = self.entries.len();
+        match address {
+            Some(address) => self.entries.retain(|_, entry| entry.address != address),
+            None => self.entries.clear(),
+        }
+        count_before - self.entries.len()
+    }
+
+    /// Returns all entries, with their offense scores at `now`.
+    pub(super) fn entries(&self, now: Timestamp) -> Vec<BlocklistEntry> {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(node_id, entry)| BlocklistEntry {
+                node_id: node_id.to_string(),
+                address: entry.address,
+                offenses: self.offenses_at(entry, now),
+                blocked_until: entry.blocked_until,
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.blocked_until);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    const MINUTE: u64 = 60_000;
+
+    fn blocklist() -> Blocklist {
+        Blocklist::new(
+            TimeDiff::from(10 * MINUTE),
+            TimeDiff::from(60 * MINUTE),
+            TimeDiff::from(120 * MINUTE),
+        )
+    }
+
+    #[test]
+    fn should_escalate_bans_of_repeat_offenders() {
+        let mut rng = TestRng::new();
+        let mut blocklist = blocklist();
+        let node_id = NodeId::random(&mut rng);
+        let address: SocketAddr = "127.0.0.1:34553".parse().unwrap();
+        let mut now = Timestamp::from(1_000_000);
+
+        let mut durations = Vec::new();
+        for _ in 0..4 {
+            durations.push(
+                blocklist
+                    .record_offense(node_id.clone(), address, now)
+                    .millis(),
+            );
+            assert!(blocklist.is_blocked(&address, now));
+            now = now + TimeDiff::from(MINUTE);
+        }
+        assert_eq!(
+            durations,
+            vec![10 * MINUTE, 20 * MINUTE, 40 * MINUTE, 60 * MINUTE]
+        );
+
+        // The ban expires, but the offenses are still remembered.
+        now = now + TimeDiff::from(60 * MINUTE);
+        assert!(!blocklist.is_blocked(&address, now));
+        blocklist.purge(now);
+        assert_eq!(blocklist.entries(now).len(), 1);
+        assert_eq!(blocklist.entries(now)[0].offenses, 4);
+    }
+
+    #[test]
+    fn should_forget_offenses_over_time() {
+        let mut rng = TestRng::new();
+        let mut blocklist = blocklist();
+        let node_id = NodeId::random(&mut rng);
+        let address: SocketAddr = "127.0.0.1:34553".parse().unwrap();
+        let mut now = Timestamp::from(1_000_000);
+
+        blocklist.record_offense(node_id.clone(), address, now);
+        blocklist.record_offense(node_id.clone(), address, now);
+        assert_eq!(blocklist.entries(now)[0].offenses, 2);
+
+        // One offense decays, so the next ban is as long as for a second offense.
+        now = now + TimeDiff::from(120 * MINUTE);
+        assert_eq!(blocklist.entries(now)[0].offenses, 1);
+        let duration = blocklist.record_offense(node_id, address, now);
+        assert_eq!(duration.millis(), 20 * MINUTE);
+
+        // Once all offenses decayed and the ban expired, the entry is dropped.
+        now = now + TimeDiff::from(240 * MINUTE);
+        blocklist.purge(now);
+        assert!(blocklist.entries(now).is_empty());
+        assert_eq!(blocklist.blocked_count(now), 0);
+    }
+
+    #[test]
+    fn should_clear_entries() {
+        let mut rng = TestRng::new();
+        let mut blocklist = blocklist();
+        let now = Timestamp::from(1_000_000);
+        let address1: SocketAddr = "127.0.0.1:34553".parse().unwrap();
+        let address2: SocketAddr = "127.0.0.2:34553".parse().unwrap();
+        blocklist.record_offense(NodeId::random(&mut rng), address1, now);
+        blocklist.record_offense(NodeId::random(&mut rng), address2, now);
+        assert_eq!(blocklist.blocked_count(now), 2);
+
+        assert_eq!(blocklist.clear(Some(address1)), 1);
+        assert!(!blocklist.is_blocked(&address1, now));
+        assert!(blocklist.is_blocked(&address2, now));
+        assert_eq!(blocklist.clear(None), 1);
+        assert_eq!(blocklist.blocked_count(now), 0);
+    }
+}
@@ -0,0 +1,59 @@
+//! Encoding of messages as the payloads of length-delimited frames.
+//!
+//! Messages are encoded using MessagePack.  Frames are decoded from the `Bytes` they were read
+//! into, so that large byte strings inside a message, such as the serialized item of a
+//! `GetResponse`, are slices of the frame rather than copies of it.
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::Message;
+use crate::types::shared_bytes;
+
+/// Encodes a message as the payload of a frame.
+pub fn encode_message<P: Serialize>(
+    message: &Message<P>,
+) -> Result<Bytes, rmp_serde::encode::Error> {
+    rmp_serde::to_vec(message).map(Bytes::from)
+}
+
+/// Decodes the payload of a frame, sharing the frame's allocation with the byte strings of the
+/// message.
+pub fn decode_message<P: DeserializeOwned>(
+    frame: Bytes,
+) -> Result<Message<P>, rmp_serde::decode::Error> {
+    shared_bytes::with_frame(&frame, || rmp_serde::from_read_ref(&frame[..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        protocol::Message as NodeMessage,
+        types::{SharedBytes, Tag},
+    };
+
+    #[test]
+    fn should_decode_get_response_without_copying_item() {
+        let serialized_item = SharedBytes::from(vec![42u8; 64 * 1024]);
+        let message = Message::Payload(NodeMessage::GetResponse {
+            tag: Tag::Block,
+            serialized_item: serialized_item.clone(),
+        });
+        let frame = encode_message(&message).unwrap();
+        let frame_start = frame.as_ptr() as usize;
+        let frame_end = frame_start + frame.len();
+
+        match decode_message::<NodeMessage>(frame).unwrap() {
+            Message::Payload(NodeMessage::GetResponse {
+                tag: Tag::Block,
+                serialized_item: decoded,
+            }) => {
+                assert_eq!(decoded, serialized_item);
+                let decoded_start = decoded.as_ptr() as usize;
+                assert!(decoded_start >= frame_start && decoded_start < frame_end);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+}
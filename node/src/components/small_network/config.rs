@@ -1,10 +1,12 @@
 #[cfg(test)]
-use std::net::{Ipv4Addr, SocketAddr};
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::{net::Ipv4Addr, time::Duration};
 
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::ConfigSection;
+
 /// Default binding address.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -18,6 +20,36 @@ const DEFAULT_PUBLIC_ADDRESS: &str = "127.0.0.1:0";
 /// Default interval for gossiping network addresses.
 const DEFAULT_GOSSIP_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Default interval for pinging connected peers.
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default number of unanswered pings in a row after which a peer is disconnected.
+const DEFAULT_MAX_FAILED_PINGS: u32 = 3;
+
+/// Default interval for asking a peer for the addresses it knows about.
+const DEFAULT_PEER_EXCHANGE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default interval for asking a peer whether it can reach our public address.
+const DEFAULT_REACHABILITY_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Default size of queued incoming messages above which gossip messages are dropped, 256 MiB.
+const DEFAULT_MAX_QUEUED_INCOMING_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Default number of gossip messages queued per outgoing connection.
+const DEFAULT_MAX_QUEUED_OUTGOING_GOSSIP: u32 = 1024;
+
+/// Default number of item requests and responses queued per outgoing connection.
+const DEFAULT_MAX_QUEUED_OUTGOING_BULK: u32 = 256;
+
+/// Default duration of a peer's first ban.
+const DEFAULT_BLOCKLIST_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Default maximum duration of a ban.
+const DEFAULT_MAX_BLOCKLIST_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default time without a new offense after which one of a peer's offenses is forgotten.
+const DEFAULT_BLOCKLIST_OFFENSE_DECAY: Duration = Duration::from_secs(60 * 60);
+
 // Default values for networking configuration:
 impl Default for Config {
     fn default() -> Self {
@@ -26,6 +58,17 @@ impl Default for Config {
             public_address: DEFAULT_PUBLIC_ADDRESS.to_string(),
             known_addresses: Vec::new(),
             gossip_interval: DEFAULT_GOSSIP_INTERVAL,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            max_failed_pings: DEFAULT_MAX_FAILED_PINGS,
+            peer_exchange_interval: DEFAULT_PEER_EXCHANGE_INTERVAL,
+            nat_pmp_gateway: None,
+            reachability_check_interval: DEFAULT_REACHABILITY_CHECK_INTERVAL,
+            max_queued_incoming_bytes: DEFAULT_MAX_QUEUED_INCOMING_BYTES,
+            max_queued_outgoing_gossip: DEFAULT_MAX_QUEUED_OUTGOING_GOSSIP,
+            max_queued_outgoing_bulk: DEFAULT_MAX_QUEUED_OUTGOING_BULK,
+            blocklist_duration: DEFAULT_BLOCKLIST_DURATION,
+            max_blocklist_duration: DEFAULT_MAX_BLOCKLIST_DURATION,
+            blocklist_offense_decay: DEFAULT_BLOCKLIST_OFFENSE_DECAY,
             systemd_support: false,
         }
     }
@@ -40,17 +83,82 @@ pub struct Config {
     pub bind_address: String,
     /// Publicly advertised address, in case the node has a different external IP.
     ///
-    /// If the port is specified as `0`, it will be replaced with the actually bound port.
+    /// If the IP is unspecified or the port is `0`, they are replaced with the ones mapped by the
+    /// NAT-PMP gateway.  Without a mapping, a port of `0` is replaced with the actually bound
+    /// port.
     pub public_address: String,
     /// Known address of a node on the network used for joining.
     pub known_addresses: Vec<String>,
     /// Interval in milliseconds used for gossiping.
     #[serde(with = "crate::utils::milliseconds")]
     pub gossip_interval: Duration,
+    /// Interval in milliseconds between pings sent to each connected peer.  A ping not answered
+    /// before the next one is sent counts as failed.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub ping_interval: Duration,
+    /// Number of consecutive failed pings after which a peer is disconnected.
+    pub max_failed_pings: u32,
+    /// Interval in milliseconds between requests to a random peer for a sample of the addresses
+    /// it knows about.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub peer_exchange_interval: Duration,
+    /// IPv4 address of a NAT-PMP gateway to ask for a mapping of the listening port.  If unset,
+    /// no mapping is requested.
+    #[serde(default)]
+    pub nat_pmp_gateway: Option<String>,
+    /// Interval in milliseconds between requests to a random peer to check whether it can connect
+    /// to our public address.  Zero disables the check.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub reachability_check_interval: Duration,
+    /// Approximate size in bytes of incoming messages waiting in the event queue above which
+    /// gossip messages are dropped, oldest first.
+    pub max_queued_incoming_bytes: u64,
+    /// Maximum number of gossip messages queued for sending to a single peer.  When exceeded, the
+    /// oldest is dropped.
+    pub max_queued_outgoing_gossip: u32,
+    /// Maximum number of item requests and responses queued for sending to a single peer.  When
+    /// exceeded, new ones are dropped.  Consensus messages are always queued, and sent first.
+    pub max_queued_outgoing_bulk: u32,
+    /// Duration in milliseconds of a peer's first ban after misbehaving.  Each further offense
+    /// doubles the ban, up to `max_blocklist_duration`.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub blocklist_duration: Duration,
+    /// Maximum duration in milliseconds of a ban.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub max_blocklist_duration: Duration,
+    /// Interval in milliseconds without a new offense after which one of a peer's offenses is
+    /// forgotten.  Zero means offenses are never forgotten.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub blocklist_offense_decay: Duration,
     /// Enable systemd startup notification.
     pub systemd_support: bool,
 }
 
+impl Config {
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least(
+            "max_queued_outgoing_gossip",
+            self.max_queued_outgoing_gossip,
+            1,
+        );
+        section.at_least("max_queued_outgoing_bulk", self.max_queued_outgoing_bulk, 1);
+        if let Some(gateway) = &self.nat_pmp_gateway {
+            section.check(
+                "nat_pmp_gateway",
+                gateway.parse::<Ipv4Addr>().is_ok(),
+                gateway,
+                "an IPv4 address",
+            );
+        }
+    }
+
+    /// Returns the address of the NAT-PMP gateway, if a valid one is configured.
+    pub(super) fn nat_pmp_gateway(&self) -> Option<Ipv4Addr> {
+        self.nat_pmp_gateway.as_ref()?.parse().ok()
+    }
+}
+
 #[cfg(test)]
 /// Reduced gossip interval for local testing.
 const DEFAULT_TEST_GOSSIP_INTERVAL: Duration = Duration::from_secs(1);
@@ -69,6 +177,17 @@ impl Config {
             public_address: bind_address.to_string(),
             known_addresses: vec![bind_address.to_string()],
             gossip_interval: DEFAULT_TEST_GOSSIP_INTERVAL,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            max_failed_pings: DEFAULT_MAX_FAILED_PINGS,
+            peer_exchange_interval: DEFAULT_PEER_EXCHANGE_INTERVAL,
+            nat_pmp_gateway: None,
+            reachability_check_interval: DEFAULT_REACHABILITY_CHECK_INTERVAL,
+            max_queued_incoming_bytes: DEFAULT_MAX_QUEUED_INCOMING_BYTES,
+            max_queued_outgoing_gossip: DEFAULT_MAX_QUEUED_OUTGOING_GOSSIP,
+            max_queued_outgoing_bulk: DEFAULT_MAX_QUEUED_OUTGOING_BULK,
+            blocklist_duration: DEFAULT_BLOCKLIST_DURATION,
+            max_blocklist_duration: DEFAULT_MAX_BLOCKLIST_DURATION,
+            blocklist_offense_decay: DEFAULT_BLOCKLIST_OFFENSE_DECAY,
             systemd_support: false,
         }
     }
@@ -87,6 +206,17 @@ impl Config {
                 SocketAddr::from((TEST_BIND_INTERFACE, known_peer_port)).to_string()
             ],
             gossip_interval: DEFAULT_TEST_GOSSIP_INTERVAL,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            max_failed_pings: DEFAULT_MAX_FAILED_PINGS,
+            peer_exchange_interval: DEFAULT_PEER_EXCHANGE_INTERVAL,
+            nat_pmp_gateway: None,
+            reachability_check_interval: DEFAULT_REACHABILITY_CHECK_INTERVAL,
+            max_queued_incoming_bytes: DEFAULT_MAX_QUEUED_INCOMING_BYTES,
+            max_queued_outgoing_gossip: DEFAULT_MAX_QUEUED_OUTGOING_GOSSIP,
+            max_queued_outgoing_bulk: DEFAULT_MAX_QUEUED_OUTGOING_BULK,
+            blocklist_duration: DEFAULT_BLOCKLIST_DURATION,
+            max_blocklist_duration: DEFAULT_MAX_BLOCKLIST_DURATION,
+            blocklist_offense_decay: DEFAULT_BLOCKLIST_OFFENSE_DECAY,
             systemd_support: false,
         }
     }
@@ -1,7 +1,7 @@
 use std::{
     fmt::{self, Debug, Display, Formatter},
     io, mem,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
 };
 
 use derive_more::From;
@@ -9,9 +9,12 @@ use serde::Serialize;
 use static_assertions::const_assert;
 use tokio::net::TcpStream;
 
-use super::{Error, GossipedAddress, Message, NodeId, Transport};
+use super::{
+    nat_pmp::{self, PortMapping},
+    Error, GossipedAddress, Message, NodeId, Transport,
+};
 use crate::{
-    effect::requests::{NetworkInfoRequest, NetworkRequest},
+    effect::requests::{DumpStateRequest, NetworkInfoRequest, NetworkRequest},
     protocol::Message as ProtocolMessage,
 };
 
@@ -77,10 +80,36 @@ pub enum Event<P> {
         req: Box<NetworkInfoRequest<NodeId>>,
     },
 
+    /// Incoming request to dump a snapshot of the internal state.
+    #[from]
+    DumpStateRequest {
+        #[serde(skip_serializing)]
+        req: Box<DumpStateRequest>,
+    },
+
     /// The node should gossip its own public listening address.
     GossipOurAddress,
     /// We received a peer's public listening address via gossip.
     PeerAddressReceived(GossipedAddress),
+    /// The node should ping all peers it has an outgoing connection to.
+    SendPings,
+    /// The node should ask a random peer for the addresses it knows about.
+    ExchangePeers,
+    /// The node should ask the NAT-PMP gateway to map or renew the mapping of its listening port.
+    MapPort,
+    /// The NAT-PMP gateway answered a port mapping request, or failed to.
+    PortMapped {
+        #[serde(skip_serializing)]
+        result: Box<Result<PortMapping, nat_pmp::Error>>,
+    },
+    /// The node should ask a random peer whether it can connect to our public address.
+    CheckReachability,
+    /// We probed the address in a peer's reachability request, and should answer it.
+    ReachabilityProbed {
+        peer_id: Box<NodeId>,
+        observed_ip: IpAddr,
+        reachable: bool,
+    },
 }
 
 impl From<NetworkRequest<NodeId, ProtocolMessage>> for Event<ProtocolMessage> {
@@ -95,6 +124,12 @@ impl From<NetworkInfoRequest<NodeId>> for Event<ProtocolMessage> {
     }
 }
 
+impl<P> From<DumpStateRequest> for Event<P> {
+    fn from(req: DumpStateRequest) -> Self {
+        Self::DumpStateRequest { req: Box::new(req) }
+    }
+}
+
 impl<P: Display> Display for Event<P> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -149,10 +184,22 @@ impl<P: Display> Display for Event<P> {
             },
             Event::NetworkRequest { req } => write!(f, "request: {}", req),
             Event::NetworkInfoRequest { req } => write!(f, "request: {}", req),
+            Event::DumpStateRequest { req } => write!(f, "request: {}", req),
             Event::GossipOurAddress => write!(f, "gossip our address"),
             Event::PeerAddressReceived(gossiped_address) => {
                 write!(f, "received gossiped peer address {}", gossiped_address)
             }
+            Event::SendPings => write!(f, "send pings"),
+            Event::ExchangePeers => write!(f, "exchange peers"),
+            Event::MapPort => write!(f, "map port"),
+            Event::PortMapped { result } => match &**result {
+                Ok(mapping) => write!(f, "port mapped to {}", mapping.external_address),
+                Err(error) => write!(f, "port mapping failed: {}", error),
+            },
+            Event::CheckReachability => write!(f, "check reachability"),
+            Event::ReachabilityProbed {
+                peer_id, reachable, ..
+            } => write!(f, "probed address of {}, reachable: {}", peer_id, reachable),
         }
     }
 }
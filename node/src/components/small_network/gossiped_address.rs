@@ -15,7 +15,7 @@ use crate::types::{Item, Tag};
 pub struct GossipedAddress(SocketAddr);
 
 impl GossipedAddress {
-    pub(super) fn new(address: SocketAddr) -> Self {
+    pub(crate) fn new(address: SocketAddr) -> Self {
         GossipedAddress(address)
     }
 }
@@ -0,0 +1,111 @@
+//! Application-level health checks of connections.
+//!
+//! A TCP connection can stay open long after the peer on the other end has stopped processing
+//! messages.  To detect such peers, a ping carrying a random nonce is periodically sent over each
+//! outgoing connection, which the peer answers with a pong carrying the same nonce.  A ping which
+//! has not been answered by the time the next one is due counts as failed.
+
+use std::time::{Duration, Instant};
+
+use datasize::DataSize;
+
+/// Weight of a new round-trip time sample in the smoothed round-trip time, in percent.
+const RTT_SAMPLE_WEIGHT_PERCENT: u32 = 20;
+
+/// Health of a single connection, as determined by pings.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct ConnectionHealth {
+    /// The nonce of the ping awaiting a pong and the time it was sent.
+    #[data_size(skip)]
+    in_flight: Option<(u64, Instant)>,
+    /// The number of pings in a row which went unanswered.
+    consecutive_failures: u32,
+    /// Exponentially smoothed round-trip time of the answered pings.
+    #[data_size(skip)]
+    smoothed_rtt: Option<Duration>,
+}
+
+impl ConnectionHealth {
+    /// Records a ping being sent.
+    ///
+    /// Returns `true` if the previous ping is still unanswered, in which case it is counted as
+    /// failed.
+    pub(super) fn ping_sent(&mut self, nonce: u64, now: Instant) -> bool {
+        let previous_failed = self.in_flight.replace((nonce, now)).is_some();
+        if previous_failed {
+            self.consecutive_failures += 1;
+        }
+        previous_failed
+    }
+
+    /// Records a pong being received, returning the round-trip time if it answers the ping in
+    /// flight.
+    pub(super) fn pong_received(&mut self, nonce: u64, now: Instant) -> Option<Duration> {
+        match self.in_flight {
+            Some((expected_nonce, sent)) if expected_nonce == nonce => {
+                self.in_flight = None;
+                self.consecutive_failures = 0;
+                let rtt = now.saturating_duration_since(sent);
+                self.smoothed_rtt = Some(match self.smoothed_rtt {
+                    Some(smoothed_rtt) => {
+                        (smoothed_rtt * (100 - RTT_SAMPLE_WEIGHT_PERCENT)
+                            + rtt * RTT_SAMPLE_WEIGHT_PERCENT)
+                            / 100
+                    }
+                    None => rtt,
+                });
+                Some(rtt)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the number of pings in a row which went unanswered.
+    pub(super) fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Returns the smoothed round-trip time, if any ping has been answered yet.
+    pub(super) fn smoothed_rtt(&self) -> Option<Duration> {
+        self.smoothed_rtt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_measure_rtt_and_reset_failures() {
+        let start = Instant::now();
+        let mut health = ConnectionHealth::default();
+
+        assert!(!health.ping_sent(1, start));
+        assert!(health.ping_sent(2, start));
+        assert!(health.ping_sent(3, start));
+        assert_eq!(health.consecutive_failures(), 2);
+
+        // A late pong for an earlier ping is ignored.
+        assert_eq!(
+            health.pong_received(2, start + Duration::from_millis(50)),
+            None
+        );
+        assert_eq!(health.consecutive_failures(), 2);
+
+        let rtt = health.pong_received(3, start + Duration::from_millis(100));
+        assert_eq!(rtt, Some(Duration::from_millis(100)));
+        assert_eq!(health.consecutive_failures(), 0);
+        assert_eq!(health.smoothed_rtt(), Some(Duration::from_millis(100)));
+
+        // Answering the same ping twice has no effect.
+        assert_eq!(
+            health.pong_received(3, start + Duration::from_millis(200)),
+            None
+        );
+
+        let later = start + Duration::from_secs(1);
+        assert!(!health.ping_sent(4, later));
+        health.pong_received(4, later + Duration::from_millis(200));
+        assert_eq!(health.smoothed_rtt(), Some(Duration::from_millis(120)));
+    }
+}
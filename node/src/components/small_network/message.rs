@@ -1,11 +1,51 @@
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    net::{IpAddr, SocketAddr},
+};
 
 use serde::{Deserialize, Serialize};
 
+use super::SharedAddress;
+
+/// A message sent between two nodes over a connection of the small network.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Message<P> {
-    Handshake { network_name: String },
+    /// The first message sent on a connection.
+    Handshake {
+        /// The name of the network the sender belongs to.
+        network_name: String,
+    },
+    /// A message of the node's components.
     Payload(P),
+    /// A ping, to be answered by a pong with the same nonce.
+    Ping {
+        /// The nonce of the ping.
+        nonce: u64,
+    },
+    /// The answer to a ping.
+    Pong {
+        /// The nonce of the ping being answered.
+        nonce: u64,
+    },
+    /// A request for a sample of the addresses known to the recipient.
+    PeerExchangeRequest,
+    /// The answer to a peer exchange request.
+    PeerExchangeResponse {
+        /// The addresses shared by the sender.
+        addresses: Vec<SharedAddress>,
+    },
+    /// A request to check whether the sender can be reached at the given address.
+    ReachabilityRequest {
+        /// The sender's public listening address.
+        address: SocketAddr,
+    },
+    /// The answer to a reachability request.
+    ReachabilityResponse {
+        /// The IP the sender sees the recipient's connection come from.
+        observed_ip: IpAddr,
+        /// Whether the sender managed to connect to the requested address.
+        reachable: bool,
+    },
 }
 
 impl<P: Display> Display for Message<P> {
@@ -13,6 +53,23 @@ impl<P: Display> Display for Message<P> {
         match self {
             Message::Handshake { network_name } => write!(f, "handshake: {}", network_name),
             Message::Payload(payload) => write!(f, "payload: {}", payload),
+            Message::Ping { nonce } => write!(f, "ping: {}", nonce),
+            Message::Pong { nonce } => write!(f, "pong: {}", nonce),
+            Message::PeerExchangeRequest => write!(f, "peer exchange request"),
+            Message::PeerExchangeResponse { addresses } => {
+                write!(f, "peer exchange response: {} addresses", addresses.len())
+            }
+            Message::ReachabilityRequest { address } => {
+                write!(f, "reachability request: {}", address)
+            }
+            Message::ReachabilityResponse {
+                observed_ip,
+                reachable,
+            } => write!(
+                f,
+                "reachability response: observed {}, reachable: {}",
+                observed_ip, reachable
+            ),
         }
     }
 }
@@ -0,0 +1,206 @@
+//! A minimal NAT-PMP client (RFC 6886), used to map the listening port on the gateway.
+
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+/// The port NAT-PMP gateways listen on.
+const GATEWAY_PORT: u16 = 5351;
+/// The only protocol version.
+const VERSION: u8 = 0;
+/// The opcode of a request for the gateway's external address.
+const EXTERNAL_ADDRESS_OPCODE: u8 = 0;
+/// The opcode of a request to map a TCP port.
+const MAP_TCP_OPCODE: u8 = 2;
+/// Added to a request's opcode to form the opcode of the response.
+const RESPONSE_OPCODE_OFFSET: u8 = 128;
+/// The length of an external address response.
+const EXTERNAL_ADDRESS_RESPONSE_LENGTH: usize = 12;
+/// The length of a mapping response.
+const MAPPING_RESPONSE_LENGTH: usize = 16;
+/// The lifetime in seconds requested for a mapping, as recommended by the RFC.
+const REQUESTED_LIFETIME_SECS: u32 = 7200;
+/// The time to wait for the first response, doubled for each retransmission.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// The number of times a request is sent before giving up.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// An error requesting a port mapping.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Failed to send a request or receive the response.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The gateway didn't answer.
+    #[error("no response from gateway")]
+    NoResponse,
+    /// The gateway refused the request.
+    #[error("gateway returned result code {0}")]
+    Refused(u16),
+    /// The response was malformed or unusable.
+    #[error("invalid response: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortMapping {
+    /// The gateway's external address, with the external port mapped to ours.
+    pub(super) external_address: SocketAddr,
+    /// The time after which the gateway drops the mapping unless it is renewed.
+    pub(super) lifetime: Duration,
+}
+
+/// Asks `gateway` to forward TCP connections on its external address to `internal_port`,
+/// preferably on the same external port, or renews an existing such mapping.
+pub(super) fn map_tcp_port(gateway: Ipv4Addr, internal_port: u16) -> Result<PortMapping, Error> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect(SocketAddrV4::new(gateway, GATEWAY_PORT))?;
+
+    let response = request(&socket, &[VERSION, EXTERNAL_ADDRESS_OPCODE])?;
+    let external_ip = parse_external_address(&response)?;
+
+    let mut mapping_request = [0u8; 12];
+    mapping_request[0] = VERSION;
+    mapping_request[1] = MAP_TCP_OPCODE;
+    mapping_request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    mapping_request[6..8].copy_from_slice(&internal_port.to_be_bytes());
+    mapping_request[8..12].copy_from_slice(&REQUESTED_LIFETIME_SECS.to_be_bytes());
+    let response = request(&socket, &mapping_request)?;
+    let (external_port, lifetime_secs) = parse_mapping(&response, internal_port)?;
+
+    Ok(PortMapping {
+        external_address: (external_ip, external_port).into(),
+        lifetime: Duration::from_secs(lifetime_secs.into()),
+    })
+}
+
+/// Sends `request` until the gateway answers it, and returns the response.
+fn request(socket: &UdpSocket, request: &[u8]) -> Result<Vec<u8>, Error> {
+    let expected_opcode = request[1] + RESPONSE_OPCODE_OFFSET;
+    let mut timeout = INITIAL_TIMEOUT;
+    let mut buffer = [0u8; MAPPING_RESPONSE_LENGTH];
+    for _ in 0..MAX_ATTEMPTS {
+        socket.set_read_timeout(Some(timeout))?;
+        socket.send(request)?;
+        match socket.recv(&mut buffer) {
+            Ok(length) if length >= 2 && buffer[1] == expected_opcode => {
+                return Ok(buffer[..length].to_vec());
+            }
+            // A stray or late response to an earlier request: send ours again.
+            Ok(_) => {}
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(error) => return Err(error.into()),
+        }
+        timeout *= 2;
+    }
+    Err(Error::NoResponse)
+}
+
+/// Checks the length, version and result code of a response.
+fn check_response(response: &[u8], length: usize) -> Result<(), Error> {
+    if response.len() < length {
+        return Err(Error::InvalidResponse("packet too short"));
+    }
+    if response[0] != VERSION {
+        return Err(Error::InvalidResponse("unsupported version"));
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(()),
+        result_code => Err(Error::Refused(result_code)),
+    }
+}
+
+/// Returns the gateway's external IP from an external address response.
+fn parse_external_address(response: &[u8]) -> Result<Ipv4Addr, Error> {
+    check_response(response, EXTERNAL_ADDRESS_RESPONSE_LENGTH)?;
+    let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+    if external_ip.is_unspecified() {
+        return Err(Error::InvalidResponse("gateway has no external address"));
+    }
+    Ok(external_ip)
+}
+
+/// Returns the external port and the lifetime in seconds from a mapping response.
+fn parse_mapping(response: &[u8], internal_port: u16) -> Result<(u16, u32), Error> {
+    check_response(response, MAPPING_RESPONSE_LENGTH)?;
+    if u16::from_be_bytes([response[8], response[9]]) != internal_port {
+        return Err(Error::InvalidResponse("mapping of a different port"));
+    }
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime_secs =
+        u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+    if external_port == 0 || lifetime_secs == 0 {
+        return Err(Error::InvalidResponse("no mapping granted"));
+    }
+    Ok((external_port, lifetime_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping_response(result_code: u16, internal_port: u16, external_port: u16) -> Vec<u8> {
+        let mut packet = vec![VERSION, MAP_TCP_OPCODE + RESPONSE_OPCODE_OFFSET];
+        packet.extend_from_slice(&result_code.to_be_bytes());
+        packet.extend_from_slice(&1234u32.to_be_bytes());
+        packet.extend_from_slice(&internal_port.to_be_bytes());
+        packet.extend_from_slice(&external_port.to_be_bytes());
+        packet.extend_from_slice(&3600u32.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn should_parse_responses() {
+        let mut packet = vec![
+            VERSION,
+            EXTERNAL_ADDRESS_OPCODE + RESPONSE_OPCODE_OFFSET,
+            0,
+            0,
+        ];
+        packet.extend_from_slice(&1234u32.to_be_bytes());
+        packet.extend_from_slice(&[203, 0, 113, 7]);
+        assert_eq!(
+            parse_external_address(&packet).unwrap(),
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+
+        let packet = mapping_response(0, 34553, 40000);
+        assert_eq!(parse_mapping(&packet, 34553).unwrap(), (40000, 3600));
+    }
+
+    #[test]
+    fn should_reject_invalid_responses() {
+        let packet = mapping_response(2, 34553, 40000);
+        assert!(matches!(
+            parse_mapping(&packet, 34553),
+            Err(Error::Refused(2))
+        ));
+
+        let packet = mapping_response(0, 34554, 40000);
+        assert!(parse_mapping(&packet, 34553).is_err());
+
+        let packet = mapping_response(0, 34553, 0);
+        assert!(parse_mapping(&packet, 34553).is_err());
+
+        let packet = mapping_response(0, 34553, 40000);
+        assert!(parse_mapping(&packet[..12], 34553).is_err());
+
+        let mut packet = vec![
+            VERSION,
+            EXTERNAL_ADDRESS_OPCODE + RESPONSE_OPCODE_OFFSET,
+            0,
+            0,
+        ];
+        packet.extend_from_slice(&[0; 8]);
+        assert!(parse_external_address(&packet).is_err());
+    }
+}
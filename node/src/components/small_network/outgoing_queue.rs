@@ -0,0 +1,223 @@
+//! Per-connection queues of outgoing messages.
+//!
+//! Each outgoing connection holds one queue per [`MessageClass`], and its sender task always sends
+//! the oldest message of the highest-priority non-empty queue, so consensus traffic is never held
+//! up behind bulk responses.  Priority messages are never dropped.  The gossip and bulk queues are
+//! bounded: a full gossip queue drops its oldest message to make room, since gossip is repeated
+//! anyway, while a full bulk queue rejects the new message, leaving the requesting peer to time out
+//! and retry with another peer.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use datasize::DataSize;
+use tokio::sync::Notify;
+
+use super::{MessageClass, PrioritizedPayload};
+
+/// The maximum numbers of queued messages of the bounded classes.
+#[derive(Copy, Clone, DataSize, Debug)]
+pub(super) struct QueueLimits {
+    /// The maximum number of queued gossip messages.
+    pub(super) gossip: usize,
+    /// The maximum number of queued bulk messages.
+    pub(super) bulk: usize,
+}
+
+/// The reason a message could not be queued.
+#[derive(Debug)]
+pub(super) enum SendError<M> {
+    /// The receiver has been dropped, i.e. the connection is closed.
+    Closed(M),
+    /// The message's queue is full.
+    Full(M),
+}
+
+#[derive(Debug)]
+struct Queues<M> {
+    priority: VecDeque<M>,
+    gossip: VecDeque<M>,
+    bulk: VecDeque<M>,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+}
+
+impl<M> Queues<M> {
+    fn pop(&mut self) -> Option<M> {
+        self.priority
+            .pop_front()
+            .or_else(|| self.gossip.pop_front())
+            .or_else(|| self.bulk.pop_front())
+    }
+}
+
+#[derive(Debug)]
+struct Shared<M> {
+    queues: Mutex<Queues<M>>,
+    notify: Notify,
+    limits: QueueLimits,
+}
+
+impl<M> Shared<M> {
+    fn lock(&self) -> MutexGuard<'_, Queues<M>> {
+        self.queues.lock().expect("outgoing queue lock poisoned")
+    }
+}
+
+/// The sending half of an outgoing queue, held by the connection.
+#[derive(Debug)]
+pub(super) struct Sender<M>(Arc<Shared<M>>);
+
+/// The receiving half of an outgoing queue, held by the connection's sender task.
+#[derive(Debug)]
+pub(super) struct Receiver<M>(Arc<Shared<M>>);
+
+/// Creates a new outgoing queue.
+pub(super) fn channel<M>(limits: QueueLimits) -> (Sender<M>, Receiver<M>) {
+    let shared = Arc::new(Shared {
+        queues: Mutex::new(Queues {
+            priority: VecDeque::new(),
+            gossip: VecDeque::new(),
+            bulk: VecDeque::new(),
+            sender_dropped: false,
+            receiver_dropped: false,
+        }),
+        notify: Notify::new(),
+        limits,
+    });
+    (Sender(Arc::clone(&shared)), Receiver(shared))
+}
+
+impl<M: PrioritizedPayload> Sender<M> {
+    /// Queues `message` according to its class.
+    ///
+    /// Returns the gossip message dropped to make room for it, if any.
+    pub(super) fn send(&self, message: M) -> Result<Option<M>, SendError<M>> {
+        let mut queues = self.0.lock();
+        if queues.receiver_dropped {
+            return Err(SendError::Closed(message));
+        }
+        let mut dropped = None;
+        match message.message_class() {
+            MessageClass::Priority => queues.priority.push_back(message),
+            MessageClass::Gossip => {
+                if queues.gossip.len() >= self.0.limits.gossip {
+                    dropped = queues.gossip.pop_front();
+                }
+                queues.gossip.push_back(message);
+            }
+            MessageClass::Bulk => {
+                if queues.bulk.len() >= self.0.limits.bulk {
+                    return Err(SendError::Full(message));
+                }
+                queues.bulk.push_back(message);
+            }
+        }
+        drop(queues);
+        self.0.notify.notify();
+        Ok(dropped)
+    }
+}
+
+impl<M> Drop for Sender<M> {
+    fn drop(&mut self) {
+        self.0.lock().sender_dropped = true;
+        self.0.notify.notify();
+    }
+}
+
+impl<M> Receiver<M> {
+    /// Returns the next message to send, or `None` once the sender has been dropped and all queued
+    /// messages have been received.
+    pub(super) async fn recv(&mut self) -> Option<M> {
+        loop {
+            {
+                let mut queues = self.0.lock();
+                if let Some(message) = queues.pop() {
+                    return Some(message);
+                }
+                if queues.sender_dropped {
+                    return None;
+                }
+            }
+            self.0.notify.notified().await;
+        }
+    }
+}
+
+impl<M> Drop for Receiver<M> {
+    fn drop(&mut self) {
+        self.0.lock().receiver_dropped = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestMessage(MessageClass, u8);
+
+    impl PrioritizedPayload for TestMessage {
+        fn message_class(&self) -> MessageClass {
+            self.0
+        }
+    }
+
+    const LIMITS: QueueLimits = QueueLimits { gossip: 2, bulk: 2 };
+
+    #[tokio::test]
+    async fn should_send_in_priority_order() {
+        let (sender, mut receiver) = channel(LIMITS);
+        sender.send(TestMessage(MessageClass::Bulk, 0)).unwrap();
+        sender.send(TestMessage(MessageClass::Gossip, 1)).unwrap();
+        sender.send(TestMessage(MessageClass::Priority, 2)).unwrap();
+        sender.send(TestMessage(MessageClass::Priority, 3)).unwrap();
+        drop(sender);
+
+        let mut received = vec![];
+        while let Some(TestMessage(_, id)) = receiver.recv().await {
+            received.push(id);
+        }
+        assert_eq!(received, vec![2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn should_apply_drop_policies_of_bounded_classes() {
+        let (sender, receiver) = channel(LIMITS);
+        for id in 0..2 {
+            assert!(matches!(
+                sender.send(TestMessage(MessageClass::Gossip, id)),
+                Ok(None)
+            ));
+            assert!(matches!(
+                sender.send(TestMessage(MessageClass::Bulk, id)),
+                Ok(None)
+            ));
+        }
+
+        // The oldest gossip message makes room, while the new bulk message is rejected.
+        assert_eq!(
+            sender.send(TestMessage(MessageClass::Gossip, 2)).unwrap(),
+            Some(TestMessage(MessageClass::Gossip, 0))
+        );
+        assert!(matches!(
+            sender.send(TestMessage(MessageClass::Bulk, 2)),
+            Err(SendError::Full(TestMessage(MessageClass::Bulk, 2)))
+        ));
+        for id in 0..10 {
+            assert!(matches!(
+                sender.send(TestMessage(MessageClass::Priority, id)),
+                Ok(None)
+            ));
+        }
+
+        drop(receiver);
+        assert!(matches!(
+            sender.send(TestMessage(MessageClass::Priority, 10)),
+            Err(SendError::Closed(_))
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+//! Exchange of known-good peer addresses.
+//!
+//! Gossip only spreads each node's own public listening address, so a node which joins with a
+//! single known address learns about the rest of the network slowly.  To speed this up, a node
+//! periodically asks one of its peers for a sample of the addresses it knows about, each tagged
+//! with when it was last seen and whether the sender managed to connect to it.
+//!
+//! Since a malicious peer could try to fill our view of the network with addresses under its
+//! control, the exchange is bounded: responses are only accepted when solicited and are capped in
+//! size, requests are answered at most once per `MIN_REQUEST_INTERVAL` per peer, and the address
+//! book holds at most `MAX_ADDRESSES_PER_BUCKET` addresses from any one subnet.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use once_cell::sync::Lazy;
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{NodeId, TimeDiff, Timestamp},
+    NodeRng,
+};
+
+/// The maximum number of addresses sent in, or accepted from, a single response.
+pub(super) const MAX_SHARED_ADDRESSES: usize = 32;
+
+/// The maximum number of addresses held in the address book.
+const MAX_ADDRESSES: usize = 1024;
+
+/// The maximum number of addresses held from any one /16 (IPv4) or /32 (IPv6) subnet.
+const MAX_ADDRESSES_PER_BUCKET: usize = 8;
+
+/// Addresses not seen for longer than this are neither shared nor kept.
+static MAX_ADDRESS_AGE: Lazy<TimeDiff> = Lazy::new(|| Duration::from_secs(3 * 60 * 60).into());
+
+/// The minimum time between two requests of the same peer which we answer.
+static MIN_REQUEST_INTERVAL: Lazy<TimeDiff> = Lazy::new(|| Duration::from_secs(10).into());
+
+/// An address shared with a peer in a peer exchange response.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SharedAddress {
+    /// The public listening address of a node.
+    pub address: SocketAddr,
+    /// When the sender last saw the node at this address.
+    pub last_seen: Timestamp,
+    /// Whether the sender managed to establish a connection to the address.
+    pub reachable: bool,
+}
+
+impl Display for SharedAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (last seen {}, reachable: {})",
+            self.address, self.last_seen, self.reachable
+        )
+    }
+}
+
+#[derive(DataSize, Debug)]
+struct AddressEntry {
+    last_seen: Timestamp,
+    reachable: bool,
+}
+
+/// The addresses learned through peer exchange and our own outgoing connections.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct AddressBook {
+    entries: HashMap<SocketAddr, AddressEntry>,
+    /// Peers we sent a request to and have not had a response from yet.
+    awaiting_response: HashSet<NodeId>,
+    /// The time we last answered a request of each peer.
+    last_answered: HashMap<NodeId, Timestamp>,
+}
+
+impl AddressBook {
+    /// Records that we established an outgoing connection to `address`.
+    pub(super) fn record_reachable(&mut self, address: SocketAddr, now: Timestamp) {
+        let entry = self.entries.entry(address).or_insert(AddressEntry {
+            last_seen: now,
+            reachable: true,
+        });
+        entry.last_seen = now;
+        entry.reachable = true;
+    }
+
+    /// Records that we failed to connect to `address`.
+    pub(super) fn record_unreachable(&mut self, address: SocketAddr) {
+        if let Some(entry) = self.entries.get_mut(&address) {
+            entry.reachable = false;
+        }
+    }
+
+    /// Adds an address learned from a peer.
+    ///
+    /// Addresses which are stale, already known, or which would exceed the limits of the address
+    /// book are ignored.  Learned addresses are recorded as not yet reachable, since only our own
+    /// connection attempts can confirm that.  Returns whether the address was added.
+    pub(super) fn insert_learned(&mut self, shared: SharedAddress, now: Timestamp) -> bool {
+        // Don't trust a peer claiming to have seen an address in the future.
+        let last_seen = shared.last_seen.min(now);
+        if now.saturating_diff(last_seen) > *MAX_ADDRESS_AGE
+            || self.entries.contains_key(&shared.address)
+            || self.entries.len() >= MAX_ADDRESSES
+        {
+            return false;
+        }
+        let bucket = bucket_of(&shared.address);
+        let bucket_size = self
+            .entries
+            .keys()
+            .filter(|address| bucket_of(address) == bucket)
+            .count();
+        if bucket_size >= MAX_ADDRESSES_PER_BUCKET {
+            return false;
+        }
+        self.entries.insert(
+            shared.address,
+            AddressEntry {
+                last_seen,
+                reachable: false,
+            },
+        );
+        true
+    }
+
+    /// Returns a random sample of at most `MAX_SHARED_ADDRESSES` fresh addresses, preferring those
+    /// we could connect to.
+    pub(super) fn sample(&self, rng: &mut NodeRng, now: Timestamp) -> Vec<SharedAddress> {
+        let (reachable, unconfirmed): (Vec<_>, Vec<_>) = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| now.saturating_diff(entry.last_seen) <= *MAX_ADDRESS_AGE)
+            .map(|(address, entry)| SharedAddress {
+                address: *address,
+                last_seen: entry.last_seen,
+                reachable: entry.reachable,
+            })
+            .partition(|shared| shared.reachable);
+        let mut sample = reachable
+            .into_iter()
+            .choose_multiple(rng, MAX_SHARED_ADDRESSES);
+        let remaining = MAX_SHARED_ADDRESSES - sample.len();
+        sample.extend(unconfirmed.into_iter().choose_multiple(rng, remaining));
+        sample
+    }
+
+    /// Records that we sent a request to `peer_id`.
+    pub(super) fn request_sent(&mut self, peer_id: NodeId) {
+        self.awaiting_response.insert(peer_id);
+    }
+
+    /// Records a response from `peer_id`, returning whether we asked for it.
+    pub(super) fn response_received(&mut self, peer_id: &NodeId) -> bool {
+        self.awaiting_response.remove(peer_id)
+    }
+
+    /// Returns whether a request of `peer_id` should be answered, recording it as answered if so.
+    pub(super) fn should_answer(&mut self, peer_id: &NodeId, now: Timestamp) -> bool {
+        match self.last_answered.get(peer_id) {
+            Some(last) if now.saturating_diff(*last) < *MIN_REQUEST_INTERVAL => false,
+            _ => {
+                self.last_answered.insert(peer_id.clone(), now);
+                true
+            }
+        }
+    }
+
+    /// Forgets stale addresses and rate-limiting records, and requests which went unanswered.
+    pub(super) fn prune(&mut self, now: Timestamp) {
+        self.entries
+            .retain(|_, entry| now.saturating_diff(entry.last_seen) <= *MAX_ADDRESS_AGE);
+        self.last_answered
+            .retain(|_, last| now.saturating_diff(*last) < *MIN_REQUEST_INTERVAL);
+        self.awaiting_response.clear();
+    }
+
+    /// Returns the number of addresses in the address book.
+    pub(super) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Returns the subnet an address is bucketed by: its /16 for IPv4 and its /32 for IPv6.
+fn bucket_of(address: &SocketAddr) -> IpAddr {
+    match address.ip() {
+        IpAddr::V4(ip) => {
+            let [a, b, _, _] = ip.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, 0, 0))
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], 0, 0, 0, 0, 0, 0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    fn shared(address: &str, last_seen: Timestamp) -> SharedAddress {
+        SharedAddress {
+            address: address.parse().unwrap(),
+            last_seen,
+            reachable: true,
+        }
+    }
+
+    #[test]
+    fn should_bound_learned_addresses() {
+        let mut rng = TestRng::new();
+        let now = Timestamp::now();
+        let mut book = AddressBook::default();
+
+        // Only `MAX_ADDRESSES_PER_BUCKET` addresses from the same /16 are accepted.
+        for host in 0..(MAX_ADDRESSES_PER_BUCKET * 2) {
+            let address = format!("10.1.{}.{}:34553", host / 256, host % 256);
+            book.insert_learned(shared(&address, now), now);
+        }
+        assert_eq!(book.len(), MAX_ADDRESSES_PER_BUCKET);
+        assert!(book.insert_learned(shared("10.2.0.1:34553", now), now));
+
+        // Stale and duplicate addresses are ignored.
+        let stale = now.saturating_sub(*MAX_ADDRESS_AGE + Duration::from_secs(1).into());
+        assert!(!book.insert_learned(shared("10.3.0.1:34553", stale), now));
+        assert!(!book.insert_learned(shared("10.2.0.1:34553", now), now));
+
+        // Learned addresses are not vouched for until we connect to them ourselves.
+        let sample = book.sample(&mut rng, now);
+        assert_eq!(sample.len(), MAX_ADDRESSES_PER_BUCKET + 1);
+        assert!(sample.iter().all(|shared| !shared.reachable));
+        book.record_reachable("10.2.0.1:34553".parse().unwrap(), now);
+        let sample = book.sample(&mut rng, now);
+        assert!(sample[0].reachable);
+        assert_eq!(sample[0].address, "10.2.0.1:34553".parse().unwrap());
+    }
+
+    #[test]
+    fn should_rate_limit_and_require_solicitation() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let now = Timestamp::now();
+        let mut book = AddressBook::default();
+
+        assert!(!book.response_received(&peer_id));
+        book.request_sent(peer_id.clone());
+        assert!(book.response_received(&peer_id));
+        assert!(!book.response_received(&peer_id));
+
+        assert!(book.should_answer(&peer_id, now));
+        assert!(!book.should_answer(&peer_id, now + Duration::from_secs(1).into()));
+        assert!(book.should_answer(&peer_id, now + *MIN_REQUEST_INTERVAL));
+    }
+}
@@ -0,0 +1,210 @@
+//! Checking whether the node's public endpoint is reachable.
+//!
+//! A node behind NAT easily advertises an address its peers can't connect to.  The advertised
+//! address is the configured `public_address`, with an unspecified IP or port filled in from the
+//! port mapping granted by the NAT-PMP gateway, if one is configured, or else with the bound port.
+//!
+//! To find out whether the advertised address actually works, the node periodically sends a
+//! reachability request to a random peer.  The peer reports back the IP it sees the node's
+//! connections come from, and whether it managed to open a TCP connection to the advertised
+//! address.  The outcome is logged, and reported in the node's status.
+//!
+//! So that the request can't be used to make peers connect to arbitrary hosts, a peer only probes
+//! addresses at the IP the request came from, and answers each peer at most once per
+//! `MIN_REQUEST_INTERVAL`.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+
+use crate::types::{NodeId, TimeDiff, Timestamp};
+
+/// The delay before the first reachability check, giving the node time to connect to peers.
+pub(super) const FIRST_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// The time a peer has to open a TCP connection to the probed address.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The minimum time between two requests of the same peer which we answer.
+static MIN_REQUEST_INTERVAL: Lazy<TimeDiff> = Lazy::new(|| Duration::from_secs(60).into());
+
+/// The outcome of a reachability check, as reported by a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReachabilityCheck {
+    /// When the peer's answer was received.
+    pub timestamp: Timestamp,
+    /// The IP the peer saw the node's connection come from.
+    #[schemars(with = "String")]
+    pub observed_ip: IpAddr,
+    /// Whether the peer managed to connect to the advertised address.
+    pub reachable: bool,
+}
+
+/// The public endpoint of the node, as far as it knows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PublicEndpoint {
+    /// The address advertised to peers.
+    #[schemars(with = "String")]
+    pub address: SocketAddr,
+    /// The external address and port mapped by the NAT-PMP gateway, if a mapping was granted.
+    #[schemars(with = "Option<String>")]
+    pub port_mapping: Option<SocketAddr>,
+    /// The latest reachability check answered by a peer, if any.
+    pub last_check: Option<ReachabilityCheck>,
+}
+
+/// Our public endpoint and the state of the reachability checks.
+#[derive(DataSize, Debug)]
+pub(super) struct Reachability {
+    /// The configured public address, possibly with an unspecified IP or port.
+    configured_address: SocketAddr,
+    // Holds no heap data.
+    #[data_size(skip)]
+    endpoint: PublicEndpoint,
+    /// The peer we sent a request to and have not had a response from yet.
+    awaiting_response: Option<NodeId>,
+    /// The time we last answered a request of each peer.
+    last_answered: HashMap<NodeId, Timestamp>,
+}
+
+impl Reachability {
+    /// Creates the state for the configured public address and the address advertised initially.
+    pub(super) fn new(configured_address: SocketAddr, address: SocketAddr) -> Self {
+        Reachability {
+            configured_address,
+            endpoint: PublicEndpoint {
+                address,
+                port_mapping: None,
+                last_check: None,
+            },
+            awaiting_response: None,
+            last_answered: HashMap::new(),
+        }
+    }
+
+    /// Returns the public endpoint.
+    pub(super) fn endpoint(&self) -> &PublicEndpoint {
+        &self.endpoint
+    }
+
+    /// Records a port mapping granted by the gateway, and returns the address to advertise.
+    ///
+    /// The configured IP and port take precedence, unless unspecified.
+    pub(super) fn set_port_mapping(&mut self, external_address: SocketAddr) -> SocketAddr {
+        let mut address = self.configured_address;
+        if address.ip().is_unspecified() {
+            address.set_ip(external_address.ip());
+        }
+        if address.port() == 0 {
+            address.set_port(external_address.port());
+        }
+        self.endpoint.address = address;
+        self.endpoint.port_mapping = Some(external_address);
+        address
+    }
+
+    /// Records that we sent a request to `peer_id`, forgetting any unanswered earlier request.
+    pub(super) fn request_sent(&mut self, peer_id: NodeId) {
+        self.awaiting_response = Some(peer_id);
+    }
+
+    /// Records a response of `peer_id`, and returns `false` if it was unsolicited.
+    pub(super) fn response_received(
+        &mut self,
+        peer_id: &NodeId,
+        observed_ip: IpAddr,
+        reachable: bool,
+        now: Timestamp,
+    ) -> bool {
+        if self.awaiting_response.as_ref() != Some(peer_id) {
+            return false;
+        }
+        self.awaiting_response = None;
+        self.endpoint.last_check = Some(ReachabilityCheck {
+            timestamp: now,
+            observed_ip,
+            reachable,
+        });
+        true
+    }
+
+    /// Returns whether a request of `peer_id` should be answered, and if so, records the time.
+    pub(super) fn should_answer(&mut self, peer_id: &NodeId, now: Timestamp) -> bool {
+        let min_interval = *MIN_REQUEST_INTERVAL;
+        self.last_answered
+            .retain(|_, answered| now.saturating_diff(*answered) < min_interval);
+        if self.last_answered.contains_key(peer_id) {
+            return false;
+        }
+        self.last_answered.insert(peer_id.clone(), now);
+        true
+    }
+}
+
+/// Returns whether a TCP connection to `address` can be opened.
+pub(super) async fn probe(address: SocketAddr) -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await,
+        Ok(Ok(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_prefer_configured_address() {
+        let bound: SocketAddr = "0.0.0.0:34553".parse().unwrap();
+        let external: SocketAddr = "203.0.113.7:40000".parse().unwrap();
+
+        let mut reachability = Reachability::new("0.0.0.0:0".parse().unwrap(), bound);
+        assert_eq!(reachability.set_port_mapping(external), external);
+
+        let configured: SocketAddr = "198.51.100.1:0".parse().unwrap();
+        let mut reachability = Reachability::new(configured, bound);
+        assert_eq!(
+            reachability.set_port_mapping(external),
+            "198.51.100.1:40000".parse().unwrap()
+        );
+        assert_eq!(reachability.endpoint().port_mapping, Some(external));
+    }
+
+    #[test]
+    fn should_only_accept_solicited_responses() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let other_peer_id = NodeId::random(&mut rng);
+        let address: SocketAddr = "203.0.113.7:34553".parse().unwrap();
+        let mut reachability = Reachability::new(address, address);
+        let now = Timestamp::now();
+
+        assert!(!reachability.response_received(&peer_id, address.ip(), true, now));
+        reachability.request_sent(peer_id.clone());
+        assert!(!reachability.response_received(&other_peer_id, address.ip(), true, now));
+        assert!(reachability.response_received(&peer_id, address.ip(), false, now));
+        assert!(!reachability.response_received(&peer_id, address.ip(), true, now));
+        let last_check = reachability
+            .endpoint()
+            .last_check
+            .expect("should have check");
+        assert!(!last_check.reachable);
+
+        assert!(reachability.should_answer(&peer_id, now));
+        assert!(!reachability.should_answer(&peer_id, now));
+        assert!(reachability.should_answer(&other_peer_id, now));
+        let later = now + *MIN_REQUEST_INTERVAL;
+        assert!(reachability.should_answer(&peer_id, later));
+    }
+}
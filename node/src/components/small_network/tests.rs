@@ -7,16 +7,22 @@ use std::{
     collections::{HashMap, HashSet},
     env,
     fmt::{self, Debug, Display, Formatter},
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
 use derive_more::From;
+use futures::FutureExt;
 use pnet::datalink;
 use prometheus::Registry;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
-use super::{Config, Event as SmallNetworkEvent, GossipedAddress, SmallNetwork};
+use super::{
+    Config, Event as SmallNetworkEvent, GossipedAddress, MessageClass, PrioritizedPayload,
+    ServingPayload, SheddablePayload, SmallNetwork,
+};
 use crate::{
     components::{
         gossiper::{self, Gossiper},
@@ -26,17 +32,18 @@ use crate::{
     },
     effect::{
         announcements::{GossiperAnnouncement, NetworkAnnouncement},
-        requests::{NetworkRequest, StorageRequest},
-        EffectBuilder, Effects,
+        requests::{DumpStateRequest, NetworkRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects,
     },
     protocol,
     reactor::{self, EventQueueHandle, Finalize, Reactor, Runner},
+    rpcs::debug::DumpableComponent,
     testing::{
         self, init_logging,
         network::{Network, NetworkedReactor},
         ConditionCheckReactor,
     },
-    types::NodeId,
+    types::{NodeId, Tag},
     utils::Source,
     NodeRng,
 };
@@ -54,6 +61,8 @@ enum Event {
     NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, Message>),
     #[from]
     AddressGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<GossipedAddress>),
+    #[from]
+    DumpStateRequest(#[serde(skip_serializing)] DumpStateRequest),
 }
 
 impl From<NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>> for Event {
@@ -100,6 +109,24 @@ impl Display for Message {
     }
 }
 
+impl SheddablePayload for Message {
+    fn is_sheddable(&self) -> bool {
+        true
+    }
+}
+
+impl PrioritizedPayload for Message {
+    fn message_class(&self) -> MessageClass {
+        MessageClass::Gossip
+    }
+}
+
+impl ServingPayload for Message {
+    fn served_tag(&self) -> Option<Tag> {
+        None
+    }
+}
+
 /// Test reactor.
 ///
 /// Runs a single small network.
@@ -163,6 +190,11 @@ impl Reactor for TestReactor {
                 rng,
                 Event::SmallNet(SmallNetworkEvent::from(req)),
             ),
+            Event::DumpStateRequest(req) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::SmallNet(SmallNetworkEvent::from(req)),
+            ),
             Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
                 sender,
                 payload,
@@ -185,12 +217,16 @@ impl Reactor for TestReactor {
                 // We do not care about the announcement of new peers in this test.
                 Effects::new()
             }
-            Event::AddressGossiperAnnouncement(ann) => {
-                let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
                 let reactor_event =
                     Event::SmallNet(SmallNetworkEvent::PeerAddressReceived(gossiped_address));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewItemBody { .. }) => {
+                unreachable!("the address gossiper should never announce an item body")
+            }
         }
     }
 
@@ -432,3 +468,72 @@ async fn check_varying_size_network_connects() {
         net.finalize().await;
     }
 }
+
+/// Checks that a node answers state dump requests itself, listing its peers by node ID only.
+#[tokio::test]
+async fn should_dump_state_without_addresses() {
+    // If the env var "CASPER_ENABLE_LIBP2P_NET" is defined, exit without running the test.
+    if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
+        return;
+    }
+
+    init_logging();
+
+    let mut rng = crate::new_rng();
+    let first_node_port = testing::unused_port_on_localhost() + 2;
+
+    let mut net = Network::new();
+    let (first_node_id, _) = net
+        .add_node_with_config(
+            Config::default_local_net_first_node(first_node_port),
+            &mut rng,
+        )
+        .await
+        .unwrap();
+    let (second_node_id, _) = net
+        .add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+        .await
+        .unwrap();
+
+    let blocklist = HashSet::new();
+    net.settle_on(
+        &mut rng,
+        |nodes| network_is_complete(&blocklist, nodes),
+        Duration::from_secs(20),
+    )
+    .await;
+
+    let dump = Arc::new(Mutex::new(None));
+    let dump_clone = Arc::clone(&dump);
+    net.process_injected_effect_on(&first_node_id, |effect_builder| {
+        effect_builder
+            .dump_component_state(DumpableComponent::Network)
+            .then(move |state| async move {
+                *dump_clone.lock().unwrap() = Some(state);
+            })
+            .ignore()
+    })
+    .await;
+    net.settle_on(
+        &mut rng,
+        |_| dump.lock().unwrap().is_some(),
+        Duration::from_secs(5),
+    )
+    .await;
+
+    let state = dump
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap()
+        .expect("small network should dump its state");
+    assert_eq!(state["our_id"], first_node_id.to_string());
+    assert!(state["outgoing"].get(second_node_id.to_string()).is_some());
+    assert!(
+        !state.to_string().contains(&Ipv4Addr::LOCALHOST.to_string()),
+        "state dump should not contain any addresses: {}",
+        state
+    );
+
+    net.finalize().await;
+}
@@ -0,0 +1,42 @@
+//! The node's current view of its connections, exported for topology dashboards.
+
+use serde::Serialize;
+
+use crate::types::{NodeId, Tag, Timestamp};
+
+/// The direction of a connection.
+///
+/// Messages are only ever sent over outgoing connections, and only received over incoming ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionDirection {
+    /// A connection the peer established to this node.
+    Incoming,
+    /// A connection this node established to the peer.
+    Outgoing,
+}
+
+/// A single connection to a peer.
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    /// The ID of the peer.
+    pub peer_id: NodeId,
+    /// The direction of the connection.
+    pub direction: ConnectionDirection,
+    /// The time the connection was established.
+    pub connected_since: Timestamp,
+    /// The time the last message was received from the peer, for incoming connections.
+    pub last_seen: Option<Timestamp>,
+    /// The kinds of items served to the peer in response to its requests, for outgoing
+    /// connections.
+    pub served_tags: Vec<Tag>,
+}
+
+/// The connections of this node.
+#[derive(Clone, Debug)]
+pub struct NetworkTopology {
+    /// The ID of this node.
+    pub our_id: NodeId,
+    /// All current connections, both incoming and outgoing.
+    pub connections: Vec<ConnectionInfo>,
+}
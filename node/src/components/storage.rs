@@ -7,12 +7,17 @@
 //! * storing and loading deploys,
 //! * [temporary until refactored] holding `DeployMetadata` for each deploy,
 //! * holding a read-only copy of the chainspec,
-//! * keeping an index of blocks by height and
-//! * [unimplemented] managing disk usage by pruning blocks and deploys from storage.
+//! * keeping an index of blocks by height,
+//! * reading contiguous ranges of blocks, optionally along with their deploys,
+//! * keeping an index of deploys by the account which created them and
+//! * [unimplemented] managing disk usage by pruning blocks and deploys from storage,
+//! * compacting the database file on request, see the `compaction` module.
 //!
-//! Any I/O performed by the component is done on the event handling thread, this is on purpose as
+//! Most I/O performed by the component is done on the event handling thread, this is on purpose as
 //! the assumption is that caching by LMDB will offset any gains from offloading it onto a separate
-//! thread, while keeping the maximum event processing time reasonable.
+//! thread, while keeping the maximum event processing time reasonable.  The exception are queries
+//! which can return a lot of data, which are read by a bounded pool of worker threads, see the
+//! `snapshot` module.
 //!
 //! ## Consistency
 //!
@@ -28,25 +33,42 @@
 //!
 //! ## Indices
 //!
-//! The current implementation keeps only in-memory indices, which are not persisted, based upon the
-//! estimate that they are reasonably quick to rebuild on start-up and do not take up much memory.
+//! The current implementation keeps mostly in-memory indices, which are not persisted, based upon
+//! the estimate that they are reasonably quick to rebuild on start-up and do not take up much
+//! memory.
+//!
+//! The exception is the index of deploys by account, which grows with the number of deploys and is
+//! therefore persisted in its own database. Its keys are the concatenation of the account hash, the
+//! big-endian deploy timestamp and the deploy hash, so that a cursor positioned at an account hash
+//! yields that account's deploys in chronological order. The index is updated whenever a deploy is
+//! stored, and rebuilt from the deploy database on start-up if it is empty.
 //!
 //! ## Errors
 //!
 //! The storage component itself is panic free and in general reports three classes of errors:
 //! Corruption, temporary resource exhaustion and potential bugs.
 
+mod compaction;
+mod era_stats;
+mod format;
 mod lmdb_ext;
+mod snapshot;
+mod stats;
 #[cfg(test)]
 mod tests;
 
 #[cfg(test)]
-use std::{collections::BTreeSet, convert::TryFrom};
+use std::collections::BTreeSet;
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    convert::TryFrom,
     fmt::{self, Display, Formatter},
-    fs, io, mem,
-    path::PathBuf,
+    fs,
+    future::Future,
+    io, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use datasize::DataSize;
@@ -59,35 +81,46 @@ use static_assertions::const_assert;
 #[cfg(test)]
 use tempfile::TempDir;
 use thiserror::Error;
-use tracing::{error, info};
+use tokio::{sync::Semaphore, task};
+use tracing::{error, info, warn};
 
 use super::Component;
-#[cfg(test)]
-use crate::crypto::hash::Digest;
 use crate::{
-    components::consensus::EraId,
+    components::{consensus::EraId, disk_watchdog},
+    crypto::{hash::Digest, SignatureVerifier},
     effect::{
         requests::{StateStoreRequest, StorageRequest},
-        EffectBuilder, EffectExt, Effects,
+        EffectBuilder, EffectExt, EffectOptionExt, Effects, Responder,
     },
     fatal,
+    rpcs::chain::BlockIdentifier,
     types::{
-        Block, BlockBody, BlockHash, BlockHeader, BlockSignatures, Deploy, DeployHash,
-        DeployMetadata,
+        Block, BlockBody, BlockExecutionResults, BlockHash, BlockHeader, BlockLike,
+        BlockSignatures, BlockWithSignatures, Deploy, DeployHash, DeployMetadata, ExitCode,
+        TimeDiff, Timestamp,
     },
-    utils::WithDir,
+    utils::{DisplayIter, WithDir},
     NodeRng,
 };
 use casper_execution_engine::shared::newtypes::Blake2bHash;
-use casper_types::{ExecutionResult, Transfer, Transform};
+use casper_types::{
+    account::{AccountHash, ACCOUNT_HASH_LENGTH},
+    ExecutionResult, Transfer, Transform,
+};
+pub use compaction::CompactionProgress;
+use compaction::{Compaction, CompactionError, COMPACTED_DB_FILENAME};
+use era_stats::EraStatsDatabases;
+pub use era_stats::{EraStorageStats, ItemStats};
 use lmdb_ext::{LmdbExtError, TransactionExt, WriteTransactionExt};
+use snapshot::Snapshot;
+pub use stats::{DatabaseStats, StorageStats};
 
 /// Filename for the LMDB database created by the Storage component.
 const STORAGE_DB_FILENAME: &str = "storage.lmdb";
 
 /// We can set this very low, as there is only a single reader/writer accessing the component at any
-/// one time.
-const MAX_TRANSACTIONS: u32 = 1;
+/// one time, plus the reader taking the snapshot for a compaction.  Each read worker adds another.
+const MAX_TRANSACTIONS: u32 = 2;
 
 /// One Gibibyte.
 const GIB: usize = 1024 * 1024 * 1024;
@@ -101,7 +134,15 @@ const DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE: usize = 300 * GIB;
 /// Default max state store size.
 const DEFAULT_MAX_STATE_STORE_SIZE: usize = 10 * GIB;
 /// Maximum number of allowed dbs.
-const MAX_DB_COUNT: u32 = 7;
+const MAX_DB_COUNT: u32 = 9;
+/// Default rate at which a compaction copies the database, in bytes per second.
+const DEFAULT_COMPACTION_RATE_LIMIT: u64 = 64 * 1024 * 1024;
+/// Default maximum age of the highest stored block for compaction to be started, in milliseconds.
+const DEFAULT_COMPACTION_MAX_SYNC_LAG: u64 = 10 * 60 * 1000;
+/// Default number of highest blocks whose linkage is verified on startup.
+const DEFAULT_STARTUP_VERIFICATION_DEPTH: u64 = 100;
+/// Default number of worker threads reading large query results.
+const DEFAULT_READ_WORKERS: usize = 4;
 
 /// OS-specific lmdb flags.
 #[cfg(not(target_os = "macos"))]
@@ -124,6 +165,10 @@ pub enum Event {
     /// Incoming state storage request.
     #[from]
     StateStoreRequest(StateStoreRequest),
+    /// The compacted copy of the database has been written, or writing it failed.
+    CompactionCopied(Result<(), String>),
+    /// A read worker failed to read from its snapshot.
+    SnapshotReadFailed(String),
 }
 
 /// A storage component initialization error.
@@ -163,6 +208,12 @@ pub enum Error {
     /// LMDB error while operating.
     #[error("internal database error: {0}")]
     InternalStorage(#[from] LmdbExtError),
+    /// Failure to switch to the compacted database after replacing the database file with it.
+    #[error("failed to switch to compacted database: {0}")]
+    SwitchToCompactedDatabase(lmdb::Error),
+    /// Failure of a read worker.
+    #[error("failed to read from storage snapshot: {0}")]
+    SnapshotRead(String),
 }
 
 // We wholesale wrap lmdb errors and treat them as internal errors here.
@@ -176,9 +227,11 @@ impl From<lmdb::Error> for Error {
 pub struct Storage {
     /// Storage location.
     root: PathBuf,
-    /// Environment holding LMDB databases.
+    /// Environment holding LMDB databases, shared with the task copying it during a compaction.
     #[data_size(skip)]
-    env: Environment,
+    env: Arc<Environment>,
+    /// The size of the memory map of the environment.
+    map_size: usize,
     /// The block header database.
     #[data_size(skip)]
     block_header_db: Database,
@@ -200,10 +253,28 @@ pub struct Storage {
     /// The state storage database.
     #[data_size(skip)]
     state_store_db: Database,
+    /// The index of deploy hashes by account, see the module documentation for its key layout.
+    #[data_size(skip)]
+    account_deploys_db: Database,
+    /// The per-era counts and sizes of stored items, keyed by era ID.
+    #[data_size(skip)]
+    era_stats_db: Database,
     /// A map of block height to block ID.
     block_height_index: BTreeMap<u64, BlockHash>,
     /// A map of era ID to switch block ID.
     switch_block_era_id_index: BTreeMap<EraId, BlockHash>,
+    /// The rate at which a compaction copies the database, in bytes per second.
+    compaction_rate_limit: u64,
+    /// The maximum age of the highest stored block for compaction to be started.
+    compaction_max_sync_lag: TimeDiff,
+    /// The compaction in progress, if any.
+    #[data_size(skip)]
+    compaction: Option<Compaction>,
+    /// The number of read workers.
+    read_worker_count: usize,
+    /// Permits for the read workers, one of which is held while reading from a snapshot.
+    #[data_size(skip)]
+    read_workers: Arc<Semaphore>,
 }
 
 impl<REv> Component<REv> for Storage {
@@ -221,6 +292,10 @@ impl<REv> Component<REv> for Storage {
             Event::StateStoreRequest(req) => {
                 self.handle_state_store_request::<REv>(effect_builder, req)
             }
+            Event::CompactionCopied(result) => {
+                self.finish_compaction(result).map(|()| Effects::new())
+            }
+            Event::SnapshotReadFailed(error) => Err(Error::SnapshotRead(error)),
         };
 
         // Any error is turned into a fatal effect, the component itself does not panic. Note that
@@ -228,7 +303,13 @@ impl<REv> Component<REv> for Storage {
         // anyway, it should not matter.
         match result {
             Ok(effects) => effects,
-            Err(err) => fatal!(effect_builder, "storage error: {}", err).ignore(),
+            Err(err) => fatal!(
+                effect_builder,
+                ExitCode::StorageFailure,
+                "storage error: {}",
+                err
+            )
+            .ignore(),
         }
     }
 }
@@ -249,24 +330,18 @@ impl Storage {
         }
 
         // Calculate the upper bound for the memory map that is potentially used.
-        let total_size = config
-            .max_block_store_size
-            .saturating_add(config.max_deploy_store_size)
-            .saturating_add(config.max_deploy_metadata_store_size);
+        let total_size = config.total_size();
+        let read_worker_count = config.read_workers.max(1);
+
+        // Remove the remains of a compaction interrupted by a shutdown.
+        remove_compacted_db_files(&root);
 
         // Creates the environment and databases.
-        let env = Environment::new()
-            .set_flags(
-                OS_FLAGS |
-                // We manage our own directory.
-                EnvironmentFlags::NO_SUB_DIR
-                // Disable thread local storage, strongly suggested for operation with tokio.
-                    | EnvironmentFlags::NO_TLS,
-            )
-            .set_max_readers(MAX_TRANSACTIONS)
-            .set_max_dbs(MAX_DB_COUNT)
-            .set_map_size(total_size)
-            .open(&root.join(STORAGE_DB_FILENAME))?;
+        let env = open_environment(
+            &root.join(STORAGE_DB_FILENAME),
+            total_size,
+            read_worker_count,
+        )?;
 
         let block_header_db = env.create_db(Some("block_header"), DatabaseFlags::empty())?;
         let block_metadata_db = env.create_db(Some("block_metadata"), DatabaseFlags::empty())?;
@@ -275,6 +350,8 @@ impl Storage {
         let transfer_db = env.create_db(Some("transfer"), DatabaseFlags::empty())?;
         let state_store_db = env.create_db(Some("state_store"), DatabaseFlags::empty())?;
         let block_body_db = env.create_db(Some("block_body"), DatabaseFlags::empty())?;
+        let account_deploys_db = env.create_db(Some("account_deploys"), DatabaseFlags::empty())?;
+        let era_stats_db = env.create_db(Some("era_stats"), DatabaseFlags::empty())?;
 
         // We now need to restore the block-height index. Log messages allow timing here.
         info!("reindexing block store");
@@ -286,7 +363,7 @@ impl Storage {
         // Note: `iter_start` has an undocumented panic if called on an empty database. We rely on
         //       the iterator being at the start when created.
         for (raw_key, raw_val) in cursor.iter() {
-            let block: BlockHeader = lmdb_ext::deserialize(raw_val)?;
+            let block: BlockHeader = format::decode(raw_val)?;
             if let Some(invalid_era) = hard_reset_to_start_of_era {
                 if block.era_id() >= invalid_era {
                     continue;
@@ -308,15 +385,42 @@ impl Storage {
         drop(cursor);
         drop(block_txn);
 
+        let era_stats_dbs = EraStatsDatabases {
+            era_stats: era_stats_db,
+            block_header: block_header_db,
+            block_body: block_body_db,
+            block_metadata: block_metadata_db,
+            deploy: deploy_db,
+            deploy_metadata: deploy_metadata_db,
+        };
+
+        // Roll back a torn tail of the chain, so that it is fetched again rather than crashing the
+        // node later on.
+        repair_block_chain_tail(
+            &env,
+            &era_stats_dbs,
+            &mut block_height_index,
+            &mut switch_block_era_id_index,
+            config.startup_verification_depth,
+        )?;
+
         // Check the integrity of the block body database.
         check_block_body_db(&env, &block_body_db)?;
 
         // Check the integrity of the block metadata database.
         check_block_metadata_db(&env, &block_metadata_db)?;
 
+        // Populate the account index if it is missing, e.g. on a database created by an older
+        // version.
+        reindex_account_deploys(&env, &deploy_db, &account_deploys_db)?;
+
+        // Count the stored items per era if the database was created by an older version.
+        era_stats::count_existing_items(&env, &era_stats_dbs)?;
+
         Ok(Storage {
             root,
-            env,
+            env: Arc::new(env),
+            map_size: total_size,
             block_header_db,
             block_body_db,
             block_metadata_db,
@@ -324,8 +428,15 @@ impl Storage {
             deploy_metadata_db,
             transfer_db,
             state_store_db,
+            account_deploys_db,
+            era_stats_db,
             block_height_index,
             switch_block_era_id_index,
+            compaction_rate_limit: config.compaction_rate_limit,
+            compaction_max_sync_lag: config.compaction_max_sync_lag,
+            compaction: None,
+            read_worker_count,
+            read_workers: Arc::new(Semaphore::new(read_worker_count)),
         })
     }
 
@@ -349,6 +460,7 @@ impl Storage {
                 let mut txn = self.env.begin_rw_txn()?;
                 txn.put(self.state_store_db, &key, &data, WriteFlags::default())?;
                 txn.commit()?;
+                compaction::note_write(&mut self.compaction, self.state_store_db, &key);
                 Ok(responder.respond(()).ignore())
             }
             StateStoreRequest::Load { key, responder } => {
@@ -380,6 +492,18 @@ impl Storage {
         Ok(bytes)
     }
 
+    /// Writes `data` under the key to the state storage DB, replacing any previous value.
+    #[cfg(not(feature = "fast-sync"))]
+    pub(crate) fn write_state_store<K>(&self, key: K, data: &[u8]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut txn = self.env.begin_rw_txn()?;
+        txn.put(self.state_store_db, &key, &data, WriteFlags::default())?;
+        txn.commit()?;
+        Ok(())
+    }
+
     /// Deletes value living under the key from the state storage DB.
     #[cfg(not(feature = "fast-sync"))]
     pub(crate) fn del_state_store<K>(&self, key: K) -> Result<bool, Error>
@@ -406,7 +530,10 @@ impl Storage {
         // average the actual execution time will be very low.
         Ok(match req {
             StorageRequest::PutBlock { block, responder } => {
+                let era_stats_dbs = self.era_stats_databases();
                 let mut txn = self.env.begin_rw_txn()?;
+                let is_new_block =
+                    era_stats::stored_size(&txn, self.block_header_db, block.hash())?.is_none();
                 if !txn.put_value(
                     self.block_body_db,
                     block.header().body_hash(),
@@ -422,7 +549,16 @@ impl Storage {
                     txn.abort();
                     return Ok(responder.respond(false).ignore());
                 }
+                if is_new_block {
+                    era_stats::note_block(&mut txn, &era_stats_dbs, &mut self.compaction, &block)?;
+                }
                 txn.commit()?;
+                compaction::note_write(
+                    &mut self.compaction,
+                    self.block_body_db,
+                    block.header().body_hash(),
+                );
+                compaction::note_write(&mut self.compaction, self.block_header_db, block.hash());
                 insert_to_block_header_indices(
                     &mut self.block_height_index,
                     &mut self.switch_block_era_id_index,
@@ -488,18 +624,74 @@ impl Storage {
             } => responder
                 .respond(self.get_transfers(&mut self.env.begin_ro_txn()?, &block_hash)?)
                 .ignore(),
+            StorageRequest::GetBlockExecutionResults {
+                block_hash,
+                responder,
+            } => responder
+                .respond(
+                    self.get_block_execution_results(&mut self.env.begin_ro_txn()?, &block_hash)?,
+                )
+                .ignore(),
             StorageRequest::PutDeploy { deploy, responder } => {
                 let mut txn = self.env.begin_rw_txn()?;
                 let outcome = txn.put_value(self.deploy_db, deploy.id(), &deploy, false)?;
+                if outcome {
+                    let account_deploys_key = account_deploys_key(&deploy);
+                    txn.put(
+                        self.account_deploys_db,
+                        &account_deploys_key,
+                        b"",
+                        WriteFlags::default(),
+                    )?;
+                    compaction::note_write(&mut self.compaction, self.deploy_db, deploy.id());
+                    compaction::note_write(
+                        &mut self.compaction,
+                        self.account_deploys_db,
+                        &account_deploys_key,
+                    );
+                }
                 txn.commit()?;
                 responder.respond(outcome).ignore()
             }
+            StorageRequest::MergeDeployApprovals {
+                deploy_hash,
+                approvals,
+                responder,
+            } => {
+                // The approvals are expected to have been verified by the caller, like the ones of
+                // deploys being put.
+                let mut txn = self.env.begin_rw_txn()?;
+                let mut deploy: Deploy = match txn.get_value(self.deploy_db, &deploy_hash)? {
+                    Some(deploy) => deploy,
+                    None => return Ok(responder.respond(None).ignore()),
+                };
+                if deploy.add_approvals(approvals) == 0 {
+                    return Ok(responder.respond(None).ignore());
+                }
+                txn.put_value(self.deploy_db, &deploy_hash, &deploy, true)?;
+                compaction::note_write(&mut self.compaction, self.deploy_db, &deploy_hash);
+                txn.commit()?;
+                responder
+                    .respond(Some(deploy.approvals().to_vec()))
+                    .ignore()
+            }
             StorageRequest::GetDeploys {
                 deploy_hashes,
                 responder,
             } => responder
                 .respond(self.get_deploys(&mut self.env.begin_ro_txn()?, deploy_hashes.as_slice())?)
                 .ignore(),
+            StorageRequest::GetDeployHashesByAccount {
+                account,
+                range,
+                responder,
+            } => responder
+                .respond(self.get_deploy_hashes_by_account(
+                    &mut self.env.begin_ro_txn()?,
+                    &account,
+                    range,
+                )?)
+                .ignore(),
             StorageRequest::GetDeployHeaders {
                 deploy_hashes,
                 responder,
@@ -520,6 +712,7 @@ impl Storage {
                 let mut txn = self.env.begin_rw_txn()?;
 
                 let mut transfers: Vec<Transfer> = vec![];
+                let mut new_results = ItemStats::default();
 
                 for (deploy_hash, execution_result) in execution_results {
                     let mut metadata = self
@@ -553,6 +746,9 @@ impl Storage {
                     // inverted; for a given block_hash 0n deploys and each deploy has exactly 1
                     // result (aka deploy_metadata in this context).
 
+                    new_results.count += 1;
+                    new_results.bytes += lmdb_ext::serialize(&execution_result)?.len() as u64;
+
                     // Update metadata and write back to db.
                     metadata
                         .execution_results
@@ -564,6 +760,11 @@ impl Storage {
                         "failed to write deploy metadata for block_hash {} deploy_hash {}",
                         block_hash, deploy_hash
                     );
+                    compaction::note_write(
+                        &mut self.compaction,
+                        self.deploy_metadata_db,
+                        &deploy_hash,
+                    );
                 }
 
                 let was_written =
@@ -573,6 +774,25 @@ impl Storage {
                     "failed to write transfers for block_hash {}",
                     block_hash
                 );
+                compaction::note_write(&mut self.compaction, self.transfer_db, &*block_hash);
+
+                if new_results.count > 0 {
+                    let maybe_header: Option<BlockHeader> =
+                        txn.get_value(self.block_header_db, &*block_hash)?;
+                    match maybe_header {
+                        Some(header) => era_stats::note_execution_results(
+                            &mut txn,
+                            self.era_stats_db,
+                            &mut self.compaction,
+                            header.era_id(),
+                            new_results,
+                        )?,
+                        None => warn!(
+                            %block_hash,
+                            "execution results stored before their block are not counted"
+                        ),
+                    }
+                }
 
                 txn.commit()?;
                 responder.respond(()).ignore()
@@ -580,23 +800,10 @@ impl Storage {
             StorageRequest::GetDeployAndMetadata {
                 deploy_hash,
                 responder,
-            } => {
-                let mut txn = self.env.begin_ro_txn()?;
-
-                // A missing deploy causes an early `None` return.
-                let deploy: Deploy =
-                    if let Some(deploy) = txn.get_value(self.deploy_db, &deploy_hash)? {
-                        deploy
-                    } else {
-                        return Ok(responder.respond(None).ignore());
-                    };
-
-                // Missing metadata is filled using a default.
-                let metadata = self
-                    .get_deploy_metadata(&mut txn, &deploy_hash)?
-                    .unwrap_or_default();
-                responder.respond(Some((deploy, metadata))).ignore()
-            }
+            } => self.read_from_snapshot(
+                move |snapshot| snapshot.read_deploy_and_metadata(&deploy_hash),
+                responder,
+            ),
             StorageRequest::GetBlockAndMetadataByHash {
                 block_hash,
                 responder,
@@ -659,13 +866,105 @@ impl Storage {
                     .respond(Some((highest_block, signatures)))
                     .ignore()
             }
+            StorageRequest::GetBlockWithSignatures {
+                maybe_id,
+                responder,
+            } => {
+                // Block, signatures and validator weights are all read in a single transaction.
+                let mut txn = self.env.begin_ro_txn()?;
+                let maybe_block = match maybe_id {
+                    Some(BlockIdentifier::Hash(block_hash)) => {
+                        self.get_single_block(&mut txn, &block_hash)?
+                    }
+                    Some(BlockIdentifier::Height(height)) => {
+                        self.get_block_by_height(&mut txn, height)?
+                    }
+                    None => self
+                        .block_height_index
+                        .keys()
+                        .last()
+                        .and_then(|&height| self.get_block_by_height(&mut txn, height).transpose())
+                        .transpose()?,
+                };
+                let block = match maybe_block {
+                    Some(block) => block,
+                    None => return Ok(responder.respond(None).ignore()),
+                };
+                let hash = block.hash();
+                let era_id = block.header().era_id();
+                let signatures = match self.get_finality_signatures(&mut txn, hash)? {
+                    Some(signatures) => signatures,
+                    None => BlockSignatures::new(*hash, era_id),
+                };
+                // The validators of an era are announced in the switch block of the previous era.
+                let era_validator_weights = match era_id.checked_sub(1) {
+                    Some(previous_era_id) => self
+                        .get_switch_block_by_era_id(&mut txn, previous_era_id)?
+                        .and_then(|switch_block| {
+                            switch_block.header().next_era_validator_weights().cloned()
+                        }),
+                    None => None,
+                };
+                responder
+                    .respond(Some(BlockWithSignatures {
+                        block,
+                        signatures,
+                        era_validator_weights,
+                    }))
+                    .ignore()
+            }
+            StorageRequest::GetBlockRange {
+                start_height,
+                count,
+                with_deploys,
+                responder,
+            } => {
+                // The height index is only available here, so the hashes are looked up before
+                // handing the read to a worker.
+                let block_hashes: Vec<BlockHash> = (start_height
+                    ..start_height.saturating_add(count))
+                    .map(|height| self.block_height_index.get(&height).copied())
+                    .take_while(Option::is_some)
+                    .flatten()
+                    .collect();
+                self.read_from_snapshot(
+                    move |snapshot| snapshot.read_block_range(&block_hashes, with_deploys),
+                    responder,
+                )
+            }
+            StorageRequest::CompactStorage { responder } => match self.start_compaction() {
+                Ok((progress, copy)) => {
+                    info!(
+                        estimated_bytes = progress.estimated_bytes,
+                        "compacting storage"
+                    );
+                    let mut effects = responder.respond(Ok(progress)).ignore();
+                    effects.extend(copy.event(Event::CompactionCopied));
+                    effects
+                }
+                Err(error) => {
+                    warn!(%error, "not compacting storage");
+                    responder.respond(Err(error.to_string())).ignore()
+                }
+            },
+            StorageRequest::GetStats { responder } => {
+                let maybe_stats = self
+                    .read_stats()
+                    .map_err(|error| warn!(%error, "failed to read storage statistics"))
+                    .ok();
+                responder.respond(maybe_stats).ignore()
+            }
             StorageRequest::PutBlockSignatures {
                 signatures,
                 responder,
             } => {
                 let mut txn = self.env.begin_rw_txn()?;
+                let old_size =
+                    era_stats::stored_size(&txn, self.block_metadata_db, &signatures.block_hash)?
+                        .unwrap_or_default();
                 let old_data: Option<BlockSignatures> =
                     txn.get_value(self.block_metadata_db, &signatures.block_hash)?;
+                let old_count = old_data.as_ref().map_or(0, |data| data.proofs.len());
                 let new_data = match old_data {
                     None => signatures,
                     Some(mut data) => {
@@ -681,9 +980,32 @@ impl Storage {
                     &new_data,
                     true,
                 )?;
+                let new_size =
+                    era_stats::stored_size(&txn, self.block_metadata_db, &new_data.block_hash)?
+                        .unwrap_or_default();
+                era_stats::note_signatures(
+                    &mut txn,
+                    self.era_stats_db,
+                    &mut self.compaction,
+                    new_data.era_id,
+                    new_data.proofs.len().saturating_sub(old_count),
+                    new_size.saturating_sub(old_size),
+                )?;
                 txn.commit()?;
+                compaction::note_write(
+                    &mut self.compaction,
+                    self.block_metadata_db,
+                    &new_data.block_hash,
+                );
                 responder.respond(outcome).ignore()
             }
+            StorageRequest::GetEraStorageStats { eras, responder } => responder
+                .respond(era_stats::read(
+                    &self.env.begin_ro_txn()?,
+                    self.era_stats_db,
+                    eras,
+                )?)
+                .ignore(),
             StorageRequest::GetBlockSignatures {
                 block_hash,
                 responder,
@@ -731,7 +1053,7 @@ impl Storage {
             .ok()
             .unwrap_or_else(|| panic!("could not create cursor for trie store check"));
         for (_, raw_val) in cursor.iter() {
-            let header: BlockHeader = lmdb_ext::deserialize(raw_val).ok()?;
+            let header: BlockHeader = format::decode(raw_val).ok()?;
             let blake_hash = Blake2bHash::from(*header.state_root_hash());
             blake_hashes.push(blake_hash);
         }
@@ -748,31 +1070,7 @@ impl Storage {
         tx: &mut Tx,
         block_hash: &BlockHash,
     ) -> Result<Option<Block>, LmdbExtError> {
-        let block_header: BlockHeader = match tx.get_value(self.block_header_db, &block_hash)? {
-            Some(block_header) => block_header,
-            None => return Ok(None),
-        };
-        let found_block_header_hash = block_header.hash();
-        if found_block_header_hash != *block_hash {
-            return Err(LmdbExtError::BlockHeaderNotStoredUnderItsHash {
-                queried_block_hash: *block_hash,
-                found_block_header_hash,
-            });
-        }
-        let block_body: BlockBody =
-            match tx.get_value(self.block_body_db, block_header.body_hash())? {
-                Some(block_header) => block_header,
-                None => return Ok(None),
-            };
-        let found_block_body_hash = block_body.hash();
-        if found_block_body_hash != *block_header.body_hash() {
-            return Err(LmdbExtError::BlockBodyNotStoredUnderItsHash {
-                queried_block_body_hash: *block_header.body_hash(),
-                found_block_body_hash,
-            });
-        }
-        let block = Block::new_from_header_and_body(block_header, block_body);
-        Ok(Some(block))
+        read_block(tx, self.block_header_db, self.block_body_db, block_hash)
     }
 
     /// Retrieves a set of deploys from storage.
@@ -787,6 +1085,31 @@ impl Storage {
             .collect()
     }
 
+    /// Retrieves the hashes of the deploys created by `account`, ordered by their timestamp.
+    ///
+    /// `range` selects the positions within this ordering, which allows paginating through the
+    /// history of accounts with many deploys.
+    fn get_deploy_hashes_by_account<Tx: Transaction>(
+        &self,
+        tx: &mut Tx,
+        account: &AccountHash,
+        range: Range<u64>,
+    ) -> Result<Vec<DeployHash>, LmdbExtError> {
+        let mut cursor = tx.open_ro_cursor(self.account_deploys_db)?;
+        let count = range.end.saturating_sub(range.start);
+        cursor
+            .iter_from(account.as_bytes())
+            .take_while(|(raw_key, _)| raw_key.starts_with(account.as_bytes()))
+            .skip(range.start as usize)
+            .take(count as usize)
+            .map(|(raw_key, _)| {
+                Digest::try_from(&raw_key[ACCOUNT_DEPLOYS_HASH_OFFSET..])
+                    .map(DeployHash::new)
+                    .map_err(|err| LmdbExtError::DataCorrupted(Box::new(err)))
+            })
+            .collect()
+    }
+
     /// Retrieves deploy metadata associated with deploy.
     ///
     /// If no deploy metadata is stored for the specific deploy, an empty metadata instance will be
@@ -799,6 +1122,35 @@ impl Storage {
         Ok(tx.get_value(self.deploy_metadata_db, deploy_hash)?)
     }
 
+    /// Retrieves the execution results of all deploys of the block with the given hash, in block
+    /// order.
+    ///
+    /// Returns `None` if the block isn't stored, or if the result of any of its deploys is missing.
+    fn get_block_execution_results<Tx: Transaction>(
+        &self,
+        tx: &mut Tx,
+        block_hash: &BlockHash,
+    ) -> Result<Option<BlockExecutionResults>, Error> {
+        let block = match self.get_single_block(tx, block_hash)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let mut execution_results = Vec::with_capacity(block.deploys().len());
+        for deploy_hash in block.deploys() {
+            let maybe_result = self
+                .get_deploy_metadata(tx, deploy_hash)?
+                .and_then(|mut metadata| metadata.execution_results.remove(block_hash));
+            match maybe_result {
+                Some(execution_result) => execution_results.push((*deploy_hash, execution_result)),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(BlockExecutionResults::new(
+            *block_hash,
+            execution_results,
+        )))
+    }
+
     /// Retrieves transfers associated with block.
     ///
     /// If no transfers are stored for the block, an empty transfers instance will be
@@ -820,6 +1172,211 @@ impl Storage {
         Ok(tx.get_value(self.block_metadata_db, block_hash)?)
     }
 
+    /// Reads from a snapshot of the databases on a worker thread once one is free, and responds
+    /// with the result.
+    ///
+    /// A failure to read is turned into a fatal error when the resulting event is handled.
+    fn read_from_snapshot<T, F>(&self, read: F, responder: Responder<T>) -> Effects<Event>
+    where
+        T: Send + 'static,
+        F: FnOnce(Snapshot) -> Result<T, Error> + Send + 'static,
+    {
+        let snapshot = Snapshot {
+            env: Arc::clone(&self.env),
+            block_header_db: self.block_header_db,
+            block_body_db: self.block_body_db,
+            block_metadata_db: self.block_metadata_db,
+            deploy_db: self.deploy_db,
+            deploy_metadata_db: self.deploy_metadata_db,
+        };
+        let read_workers = Arc::clone(&self.read_workers);
+        async move {
+            let _permit = read_workers.acquire_owned().await;
+            match task::spawn_blocking(move || read(snapshot)).await {
+                Ok(Ok(value)) => {
+                    responder.respond(value).await;
+                    None
+                }
+                Ok(Err(error)) => Some(error.to_string()),
+                Err(error) => Some(format!("failed to join read worker: {}", error)),
+            }
+        }
+        .map_some(Event::SnapshotReadFailed)
+    }
+
+    /// Returns the names and handles of all databases.
+    fn databases(&self) -> Vec<(&'static str, Database)> {
+        vec![
+            ("block_header", self.block_header_db),
+            ("block_body", self.block_body_db),
+            ("block_metadata", self.block_metadata_db),
+            ("deploys", self.deploy_db),
+            ("deploy_metadata", self.deploy_metadata_db),
+            ("transfer", self.transfer_db),
+            ("state_store", self.state_store_db),
+            ("account_deploys", self.account_deploys_db),
+            ("era_stats", self.era_stats_db),
+        ]
+    }
+
+    /// Returns the databases involved in maintaining the per-era statistics.
+    fn era_stats_databases(&self) -> EraStatsDatabases {
+        EraStatsDatabases {
+            era_stats: self.era_stats_db,
+            block_header: self.block_header_db,
+            block_body: self.block_body_db,
+            block_metadata: self.block_metadata_db,
+            deploy: self.deploy_db,
+            deploy_metadata: self.deploy_metadata_db,
+        }
+    }
+
+    /// Reads the statistics of the LMDB environment and all databases.
+    fn read_stats(&self) -> Result<StorageStats, lmdb::Error> {
+        let mut stats = stats::read_stats(&self.env, self.databases())?;
+        stats.compaction = self.compaction.as_ref().map(Compaction::progress);
+        Ok(stats)
+    }
+
+    /// Checks whether a compaction can be started, and if so returns its initial progress and the
+    /// future writing the compacted copy.
+    fn start_compaction(
+        &mut self,
+    ) -> Result<
+        (
+            CompactionProgress,
+            impl Future<Output = Result<(), String>> + Send + 'static,
+        ),
+        CompactionError,
+    > {
+        if let Some(compaction) = &self.compaction {
+            return Err(CompactionError::AlreadyRunning(compaction.progress()));
+        }
+
+        // Writes made while copying have to be transferred at once when swapping in the copy, so
+        // refuse to start while the node is catching up.
+        let highest_block_hash = match self.block_height_index.values().next_back() {
+            Some(block_hash) => *block_hash,
+            None => return Err(CompactionError::NoBlocks),
+        };
+        let highest_block_header: BlockHeader = self
+            .env
+            .begin_ro_txn()?
+            .get_value(self.block_header_db, &highest_block_hash)?
+            .ok_or(CompactionError::NoBlocks)?;
+        let lag = Timestamp::now().saturating_diff(highest_block_header.timestamp());
+        if lag > self.compaction_max_sync_lag {
+            return Err(CompactionError::BehindSync {
+                lag,
+                max_lag: self.compaction_max_sync_lag,
+            });
+        }
+
+        let stats = self.read_stats()?;
+        let estimated_bytes = stats
+            .used_pages
+            .saturating_sub(stats.free_pages)
+            .saturating_mul(stats.page_size);
+        let disk_space = disk_watchdog::measure_disk_space(&self.root)?;
+        if disk_space.available < estimated_bytes {
+            return Err(CompactionError::InsufficientDiskSpace {
+                available: disk_space.available,
+                required: estimated_bytes,
+            });
+        }
+
+        let compaction = Compaction::new(estimated_bytes);
+        let progress = compaction.progress();
+        let env = Arc::clone(&self.env);
+        let destination = self.root.join(COMPACTED_DB_FILENAME);
+        let rate_limit = self.compaction_rate_limit;
+        let copied_bytes = compaction.copied_bytes();
+        self.compaction = Some(compaction);
+        let copy = async move {
+            task::spawn_blocking(move || {
+                compaction::copy_compacted(env, &destination, rate_limit, copied_bytes)
+                    .map_err(|error| error.to_string())
+            })
+            .await
+            .unwrap_or_else(|error| Err(format!("failed to join tokio task: {}", error)))
+        };
+        Ok((progress, copy))
+    }
+
+    /// Swaps in the compacted copy of the database once it has been written.
+    ///
+    /// Only an error after the database file has been replaced is returned, as the component can
+    /// neither continue with the old nor with the new database then.  Up to that point, a failure
+    /// leaves the old database in use.
+    fn finish_compaction(&mut self, result: Result<(), String>) -> Result<(), Error> {
+        let compaction = match self.compaction.take() {
+            Some(compaction) => compaction,
+            None => return Ok(()),
+        };
+        if let Err(error) = result {
+            warn!(%error, "failed to compact storage");
+            remove_compacted_db_files(&self.root);
+            return Ok(());
+        }
+
+        let destination = self.root.join(COMPACTED_DB_FILENAME);
+        let prepared = open_environment(&destination, self.map_size, self.read_worker_count)
+            .map_err(CompactionError::from)
+            .and_then(|env| {
+                let transferred =
+                    compaction.transfer_written_keys(&self.env, &env, &self.databases())?;
+                env.sync(true)?;
+                Ok((env, transferred))
+            })
+            .and_then(|(env, transferred)| {
+                // The lock file is moved along, so that it isn't opened again by the next
+                // compaction while still in use.
+                fs::rename(
+                    lock_file_path(&destination),
+                    lock_file_path(&self.root.join(STORAGE_DB_FILENAME)),
+                )?;
+                fs::rename(&destination, self.root.join(STORAGE_DB_FILENAME))?;
+                Ok((env, transferred))
+            });
+        let (env, transferred_keys) = match prepared {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                warn!(%error, "failed to swap in compacted storage");
+                remove_compacted_db_files(&self.root);
+                return Ok(());
+            }
+        };
+
+        // The database file has been replaced, there is no way back from here.
+        self.use_environment(env)
+            .map_err(Error::SwitchToCompactedDatabase)?;
+        info!(
+            copied_bytes = compaction.progress().copied_bytes,
+            transferred_keys, "storage compaction complete"
+        );
+        Ok(())
+    }
+
+    /// Replaces the environment with `env`, which must contain all databases.
+    fn use_environment(&mut self, env: Environment) -> Result<(), lmdb::Error> {
+        self.block_header_db = env.open_db(Some("block_header"))?;
+        self.block_body_db = env.open_db(Some("block_body"))?;
+        self.block_metadata_db = env.open_db(Some("block_metadata"))?;
+        self.deploy_db = env.open_db(Some("deploys"))?;
+        self.deploy_metadata_db = env.open_db(Some("deploy_metadata"))?;
+        self.transfer_db = env.open_db(Some("transfer"))?;
+        self.state_store_db = env.open_db(Some("state_store"))?;
+        self.account_deploys_db = env.open_db(Some("account_deploys"))?;
+        self.era_stats_db = env.open_db(Some("era_stats"))?;
+        self.env = Arc::new(env);
+        Ok(())
+    }
+
+    /// Returns the directory containing the databases.
+    pub(crate) fn root_path(&self) -> &Path {
+        &self.root
+    }
+
     /// Get the lmdb environment
     #[cfg(test)]
     pub(crate) fn env(&self) -> &Environment {
@@ -827,6 +1384,96 @@ impl Storage {
     }
 }
 
+/// Opens the LMDB environment in the file at `path`, allowing for `read_workers` additional
+/// readers.
+fn open_environment(
+    path: &Path,
+    map_size: usize,
+    read_workers: usize,
+) -> Result<Environment, lmdb::Error> {
+    Environment::new()
+        .set_flags(
+            OS_FLAGS |
+            // We manage our own directory.
+            EnvironmentFlags::NO_SUB_DIR
+            // Disable thread local storage, strongly suggested for operation with tokio.
+                | EnvironmentFlags::NO_TLS,
+        )
+        .set_max_readers(MAX_TRANSACTIONS + read_workers as u32)
+        .set_max_dbs(MAX_DB_COUNT)
+        .set_map_size(map_size)
+        .open(path)
+}
+
+/// Checks that an existing database can be opened and read, without creating one.
+///
+/// Returns the path of the database file, or `None` if there is none yet.
+pub(crate) fn check_database(cfg: &WithDir<Config>) -> Result<Option<PathBuf>, Error> {
+    let config = cfg.value();
+    let path = cfg.with_dir(config.path.clone()).join(STORAGE_DB_FILENAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let env = open_environment(&path, config.total_size(), 0)?;
+    let block_header_db = env.open_db(Some("block_header"))?;
+    let txn = env.begin_ro_txn()?;
+    txn.open_ro_cursor(block_header_db)?;
+    Ok(Some(path))
+}
+
+/// Returns the path of the lock file LMDB uses for the database file at `path`.
+fn lock_file_path(path: &Path) -> PathBuf {
+    let mut lock_file_path = path.as_os_str().to_owned();
+    lock_file_path.push("-lock");
+    PathBuf::from(lock_file_path)
+}
+
+/// Reads the block with the given hash, checking that its header and body are stored under their
+/// hashes.
+fn read_block<Tx: Transaction>(
+    tx: &mut Tx,
+    block_header_db: Database,
+    block_body_db: Database,
+    block_hash: &BlockHash,
+) -> Result<Option<Block>, LmdbExtError> {
+    let block_header: BlockHeader = match tx.get_value(block_header_db, &block_hash)? {
+        Some(block_header) => block_header,
+        None => return Ok(None),
+    };
+    let found_block_header_hash = block_header.hash();
+    if found_block_header_hash != *block_hash {
+        return Err(LmdbExtError::BlockHeaderNotStoredUnderItsHash {
+            queried_block_hash: *block_hash,
+            found_block_header_hash,
+        });
+    }
+    let block_body: BlockBody = match tx.get_value(block_body_db, block_header.body_hash())? {
+        Some(block_header) => block_header,
+        None => return Ok(None),
+    };
+    let found_block_body_hash = block_body.hash();
+    if found_block_body_hash != *block_header.body_hash() {
+        return Err(LmdbExtError::BlockBodyNotStoredUnderItsHash {
+            queried_block_body_hash: *block_header.body_hash(),
+            found_block_body_hash,
+        });
+    }
+    let block = Block::new_from_header_and_body(block_header, block_body);
+    Ok(Some(block))
+}
+
+/// Removes the compacted copy of the database and its lock file, if present.
+fn remove_compacted_db_files(root: &Path) {
+    let compacted_db_path = root.join(COMPACTED_DB_FILENAME);
+    for path in &[lock_file_path(&compacted_db_path), compacted_db_path] {
+        match fs::remove_file(&path) {
+            Ok(()) => info!(path = %path.display(), "removed compacted storage file"),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => warn!(%error, path = %path.display(), "failed to remove file"),
+        }
+    }
+}
+
 /// Inserts the relevant entries to the two indices.
 ///
 /// If a duplicate entry is encountered, neither index is updated and an error is returned.
@@ -867,6 +1514,117 @@ fn insert_to_block_header_indices(
     Ok(())
 }
 
+/// Verifies the linkage of the highest `depth` blocks in the height index and rolls the indices
+/// back to the last consistent block if it is broken.
+///
+/// A block at the height directly above an indexed block must name that block as its parent, and
+/// every block must have its body stored. Gaps in the heights are accepted, since a joining node
+/// only downloads some of the ancestors of its trusted block. The headers and finality signatures
+/// of the blocks above the last consistent one are deleted, together with a corrupt body if one was
+/// found: the linear chain synchronizer resumes from the highest stored block and will fetch them
+/// again. The deleted items are subtracted from the per-era statistics in the same transaction, so
+/// they aren't counted twice once stored again.
+fn repair_block_chain_tail(
+    env: &Environment,
+    dbs: &EraStatsDatabases,
+    block_height_index: &mut BTreeMap<u64, BlockHash>,
+    switch_block_era_id_index: &mut BTreeMap<EraId, BlockHash>,
+    depth: u64,
+) -> Result<(), Error> {
+    let highest = match block_height_index.keys().next_back() {
+        Some(height) if depth > 0 => *height,
+        _ => return Ok(()),
+    };
+    info!(%depth, "verifying the highest blocks");
+
+    let mut txn = env.begin_ro_txn()?;
+    let mut first_invalid = None;
+    let mut corrupt_body = None;
+    let mut previous: Option<(u64, BlockHash)> = None;
+    for (&height, block_hash) in block_height_index.range(highest.saturating_sub(depth - 1)..) {
+        let header: BlockHeader = match txn.get_value(dbs.block_header, block_hash)? {
+            Some(header) => header,
+            None => {
+                warn!(%height, %block_hash, "indexed block header is missing");
+                first_invalid = Some(height);
+                break;
+            }
+        };
+        match previous {
+            Some((previous_height, previous_hash))
+                if previous_height + 1 == height && *header.parent_hash() != previous_hash =>
+            {
+                warn!(
+                    %height,
+                    %block_hash,
+                    parent_hash = %header.parent_hash(),
+                    expected_parent_hash = %previous_hash,
+                    "block does not link to its predecessor"
+                );
+                first_invalid = Some(height);
+                break;
+            }
+            _ => (),
+        }
+        let body_hash = header.body_hash();
+        let body_is_valid = match txn.get_value::<_, BlockBody>(dbs.block_body, body_hash) {
+            Ok(Some(body)) => body.hash() == *body_hash,
+            Ok(None) => false,
+            Err(LmdbExtError::DataCorrupted(_)) => false,
+            Err(err) => return Err(err.into()),
+        };
+        if !body_is_valid {
+            warn!(%height, %block_hash, %body_hash, "block body is missing or corrupt");
+            corrupt_body = Some(*body_hash);
+            first_invalid = Some(height);
+            break;
+        }
+        previous = Some((height, *block_hash));
+    }
+    drop(txn);
+
+    let first_invalid = match first_invalid {
+        Some(height) => height,
+        None => {
+            info!("highest blocks verified");
+            return Ok(());
+        }
+    };
+
+    let removed = block_height_index.split_off(&first_invalid);
+    switch_block_era_id_index
+        .retain(|_, block_hash| !removed.values().any(|hash| hash == block_hash));
+    let mut txn = env.begin_rw_txn()?;
+    for block_hash in removed.values() {
+        match txn.get_value::<_, BlockHeader>(dbs.block_header, block_hash) {
+            Ok(Some(header)) => era_stats::forget_block(&mut txn, dbs, &header)?,
+            Ok(None) | Err(LmdbExtError::DataCorrupted(_)) => (),
+            Err(err) => return Err(err.into()),
+        }
+        for db in &[dbs.block_header, dbs.block_metadata] {
+            match txn.del(*db, block_hash, None) {
+                Ok(()) | Err(lmdb::Error::NotFound) => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+    if let Some(body_hash) = corrupt_body {
+        match txn.del(dbs.block_body, &body_hash, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    txn.commit()?;
+
+    warn!(
+        removed_from = %first_invalid,
+        removed_to = %highest,
+        highest_remaining = ?block_height_index.keys().next_back(),
+        "rolled back a torn tail of the block chain, it will be synchronized again"
+    );
+    Ok(())
+}
+
 /// On-disk storage configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -891,6 +1649,37 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_state_store_size: usize,
+    /// The rate at which a compaction copies the database, in bytes per second.  Zero means
+    /// unlimited.
+    #[serde(default = "default_compaction_rate_limit")]
+    compaction_rate_limit: u64,
+    /// The maximum age of the highest stored block for a compaction to be started.
+    #[serde(default = "default_compaction_max_sync_lag")]
+    compaction_max_sync_lag: TimeDiff,
+    /// The number of highest blocks whose linkage is verified on startup.  Zero disables the
+    /// check.
+    #[serde(default = "default_startup_verification_depth")]
+    startup_verification_depth: u64,
+    /// The number of worker threads reading ranges of blocks and deploys with their metadata.  At
+    /// least one is used.
+    #[serde(default = "default_read_workers")]
+    read_workers: usize,
+}
+
+fn default_compaction_rate_limit() -> u64 {
+    DEFAULT_COMPACTION_RATE_LIMIT
+}
+
+fn default_compaction_max_sync_lag() -> TimeDiff {
+    TimeDiff::from(DEFAULT_COMPACTION_MAX_SYNC_LAG)
+}
+
+fn default_startup_verification_depth() -> u64 {
+    DEFAULT_STARTUP_VERIFICATION_DEPTH
+}
+
+fn default_read_workers() -> usize {
+    DEFAULT_READ_WORKERS
 }
 
 impl Default for Config {
@@ -902,11 +1691,22 @@ impl Default for Config {
             max_deploy_store_size: DEFAULT_MAX_DEPLOY_STORE_SIZE,
             max_deploy_metadata_store_size: DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE,
             max_state_store_size: DEFAULT_MAX_STATE_STORE_SIZE,
+            compaction_rate_limit: DEFAULT_COMPACTION_RATE_LIMIT,
+            compaction_max_sync_lag: default_compaction_max_sync_lag(),
+            startup_verification_depth: DEFAULT_STARTUP_VERIFICATION_DEPTH,
+            read_workers: DEFAULT_READ_WORKERS,
         }
     }
 }
 
 impl Config {
+    /// Returns the upper bound for the memory map of the database.
+    fn total_size(&self) -> usize {
+        self.max_block_store_size
+            .saturating_add(self.max_deploy_store_size)
+            .saturating_add(self.max_deploy_metadata_store_size)
+    }
+
     /// Returns a default `Config` suitable for tests, along with a `TempDir` which must be kept
     /// alive for the duration of the test since its destructor removes the dir from the filesystem.
     #[cfg(test)]
@@ -927,6 +1727,13 @@ impl Display for Event {
         match self {
             Event::StorageRequest(req) => req.fmt(f),
             Event::StateStoreRequest(req) => req.fmt(f),
+            Event::CompactionCopied(Ok(())) => write!(f, "compacted copy of storage written"),
+            Event::CompactionCopied(Err(error)) => {
+                write!(f, "failed to write compacted copy of storage: {}", error)
+            }
+            Event::SnapshotReadFailed(error) => {
+                write!(f, "failed to read from storage snapshot: {}", error)
+            }
         }
     }
 }
@@ -1020,6 +1827,53 @@ impl Storage {
     }
 }
 
+/// Offset of the deploy hash within a key of the account deploys index.
+const ACCOUNT_DEPLOYS_HASH_OFFSET: usize = ACCOUNT_HASH_LENGTH + mem::size_of::<u64>();
+
+/// Constructs the key under which `deploy` is recorded in the account deploys index.
+fn account_deploys_key(deploy: &Deploy) -> Vec<u8> {
+    let header = deploy.header();
+    let mut key = Vec::with_capacity(ACCOUNT_DEPLOYS_HASH_OFFSET + Digest::LENGTH);
+    key.extend_from_slice(header.account().to_account_hash().as_bytes());
+    key.extend_from_slice(&header.timestamp().millis().to_be_bytes());
+    key.extend_from_slice(deploy.id().as_ref());
+    key
+}
+
+/// Utility function to rebuild the account deploys index from the deploy database, if the index is
+/// empty.
+fn reindex_account_deploys(
+    env: &Environment,
+    deploy_db: &Database,
+    account_deploys_db: &Database,
+) -> Result<(), LmdbExtError> {
+    let mut txn = env.begin_rw_txn()?;
+    let index_is_empty = txn
+        .open_ro_cursor(*account_deploys_db)?
+        .iter()
+        .next()
+        .is_none();
+    if !index_is_empty {
+        return Ok(());
+    }
+
+    info!("reindexing deploys by account");
+    let mut keys = Vec::new();
+    {
+        let mut cursor = txn.open_ro_cursor(*deploy_db)?;
+        for (_, raw_val) in cursor.iter() {
+            let deploy: Deploy = format::decode(raw_val)?;
+            keys.push(account_deploys_key(&deploy));
+        }
+    }
+    for key in keys {
+        txn.put(*account_deploys_db, &key, b"", WriteFlags::default())?;
+    }
+    txn.commit()?;
+    info!("deploys by account reindexing complete");
+    Ok(())
+}
+
 /// Utility function to check the integrity of the block_body database at bringup.
 fn check_block_body_db(env: &Environment, block_body_db: &Database) -> Result<(), LmdbExtError> {
     info!("Checking block body db");
@@ -1027,7 +1881,7 @@ fn check_block_body_db(env: &Environment, block_body_db: &Database) -> Result<()
     let mut cursor = txn.open_ro_cursor(*block_body_db)?;
 
     for (raw_key, raw_val) in cursor.iter() {
-        let body: BlockBody = lmdb_ext::deserialize(raw_val)?;
+        let body: BlockBody = format::decode(raw_val)?;
         assert_eq!(
             raw_key,
             body.hash().as_ref(),
@@ -1046,20 +1900,26 @@ fn check_block_metadata_db(
     info!("Checking block_metadata_db");
     let txn = env.begin_ro_txn()?;
     let mut cursor = txn.open_ro_cursor(*block_metadata_db)?;
+    let verifier = SignatureVerifier::default();
 
     for (raw_key, raw_val) in cursor.iter() {
-        let signatures: BlockSignatures = lmdb_ext::deserialize(raw_val)?;
-        // Signature verification could be very slow process
-        // It iterates over every signature and verifies them.
-        match signatures.verify() {
+        let signatures: BlockSignatures = format::decode(raw_val)?;
+        // Signature verification could be very slow process, so the signatures of each block are
+        // verified in parallel.
+        match signatures.verify(&verifier) {
             Ok(_) => assert_eq!(
                 raw_key,
                 signatures.block_hash.as_ref(),
                 "Corruption in block_metadata_db"
             ),
-            Err(error) => panic!(
-                "Error: {} in signature verification. Corruption in database",
-                error
+            Err(failures) => panic!(
+                "Error: invalid signatures from {} in signature verification. Corruption in \
+                database",
+                DisplayIter::new(
+                    failures
+                        .iter()
+                        .map(|(public_key, error)| format!("{} ({})", public_key, error))
+                )
             ),
         }
     }
@@ -0,0 +1,238 @@
+//! Online compaction of the storage database.
+//!
+//! LMDB never returns space to the file system: pages freed by deleting or overwriting data are
+//! reused, but the database file itself never shrinks.  Compaction copies the pages in use into a
+//! fresh file in the background while the node keeps running, then replaces the database file
+//! with the copy.
+//!
+//! The copy is a snapshot of the database when compaction starts.  Keys written afterwards are
+//! noted, and their current values transferred to the copy right before it is swapped in.  As the
+//! storage component does both while handling a single event, no other storage operation can
+//! interleave.
+//!
+//! Writes made outside of event handling, i.e. on start-up and on shutdown, are not noted, as no
+//! compaction can be in progress at these times.
+
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{self, Read, Write},
+    os::unix::{io::AsRawFd, net::UnixStream},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use lmdb::{Database, Environment, Transaction, WriteFlags};
+use lmdb_sys::{mdb_env_copyfd2, MDB_dbi, MDB_CP_COMPACT, MDB_SUCCESS};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::lmdb_ext::LmdbExtError;
+use crate::types::TimeDiff;
+
+/// Filename of the compacted copy of the database while it is being written.
+pub(super) const COMPACTED_DB_FILENAME: &str = "storage.lmdb.compacted";
+
+/// Size of the chunks in which the copy is written, in bytes.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The progress of a compaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CompactionProgress {
+    /// The number of bytes copied so far.
+    pub copied_bytes: u64,
+    /// The approximate size of the copy once complete, i.e. the size of the pages which were in
+    /// use when compaction started.
+    pub estimated_bytes: u64,
+}
+
+/// Error starting or performing a compaction.
+#[derive(Debug, Error)]
+pub(super) enum CompactionError {
+    /// Only a single compaction can run at a time.
+    #[error(
+        "compaction already in progress, {} of about {} bytes copied",
+        .0.copied_bytes,
+        .0.estimated_bytes
+    )]
+    AlreadyRunning(CompactionProgress),
+    /// The node has not stored any blocks yet, so it is still syncing.
+    #[error("no blocks stored yet")]
+    NoBlocks,
+    /// The highest stored block is too old: the node is still catching up and writing a lot of
+    /// data, all of which would have to be transferred when swapping in the copy.
+    #[error("highest stored block is {lag} old, at most {max_lag} allowed")]
+    BehindSync { lag: TimeDiff, max_lag: TimeDiff },
+    /// The file system does not have room for the copy.
+    #[error("{available} bytes available on disk, but the copy needs about {required}")]
+    InsufficientDiskSpace { available: u64, required: u64 },
+    /// I/O error while copying.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Database error while copying or transferring keys.
+    #[error(transparent)]
+    Database(#[from] LmdbExtError),
+    /// The thread performing the copy panicked.
+    #[error("copying thread panicked")]
+    CopierPanicked,
+}
+
+impl From<lmdb::Error> for CompactionError {
+    fn from(error: lmdb::Error) -> Self {
+        LmdbExtError::from(error).into()
+    }
+}
+
+/// A compaction in progress.
+#[derive(Debug)]
+pub(super) struct Compaction {
+    /// The number of bytes copied so far, updated by the copying task.
+    copied_bytes: Arc<AtomicU64>,
+    /// The approximate size of the copy once complete.
+    estimated_bytes: u64,
+    /// The keys written since the copy started, by database.
+    written_keys: BTreeSet<(MDB_dbi, Vec<u8>)>,
+}
+
+impl Compaction {
+    pub(super) fn new(estimated_bytes: u64) -> Self {
+        Compaction {
+            copied_bytes: Arc::new(AtomicU64::new(0)),
+            estimated_bytes,
+            written_keys: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the counter of copied bytes, to be updated by the copying task.
+    pub(super) fn copied_bytes(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.copied_bytes)
+    }
+
+    pub(super) fn progress(&self) -> CompactionProgress {
+        CompactionProgress {
+            copied_bytes: self.copied_bytes.load(Ordering::Relaxed),
+            estimated_bytes: self.estimated_bytes,
+        }
+    }
+
+    /// Transfers the current values of all keys written since the copy started from `source` to
+    /// `destination`, returning the number of keys transferred.
+    ///
+    /// `databases` lists the names and handles in `source` of all databases.
+    pub(super) fn transfer_written_keys(
+        &self,
+        source: &Environment,
+        destination: &Environment,
+        databases: &[(&'static str, Database)],
+    ) -> Result<usize, lmdb::Error> {
+        // Databases cannot be opened while a write transaction is active.
+        let database_pairs = databases
+            .iter()
+            .map(|(name, source_db)| Ok((*source_db, destination.open_db(Some(name))?)))
+            .collect::<Result<Vec<_>, lmdb::Error>>()?;
+
+        let source_txn = source.begin_ro_txn()?;
+        let mut destination_txn = destination.begin_rw_txn()?;
+        let mut count = 0;
+        for (source_db, destination_db) in database_pairs {
+            for (_, key) in self
+                .written_keys
+                .iter()
+                .filter(|(dbi, _)| *dbi == source_db.dbi())
+            {
+                match source_txn.get(source_db, key) {
+                    Ok(value) => {
+                        destination_txn.put(destination_db, key, &value, WriteFlags::empty())?
+                    }
+                    Err(lmdb::Error::NotFound) => {
+                        match destination_txn.del(destination_db, key, None) {
+                            Ok(()) | Err(lmdb::Error::NotFound) => (),
+                            Err(error) => return Err(error),
+                        }
+                    }
+                    Err(error) => return Err(error),
+                }
+                count += 1;
+            }
+        }
+        destination_txn.commit()?;
+        source_txn.commit()?;
+        Ok(count)
+    }
+}
+
+/// Notes that `key` was written to `db`, if a compaction is in progress.
+///
+/// A free function rather than a method, so that it can be called while a transaction borrows the
+/// storage component's environment.
+pub(super) fn note_write<K: AsRef<[u8]>>(
+    compaction: &mut Option<Compaction>,
+    db: Database,
+    key: &K,
+) {
+    if let Some(compaction) = compaction {
+        compaction
+            .written_keys
+            .insert((db.dbi(), key.as_ref().to_vec()));
+    }
+}
+
+/// Writes a compacted copy of `env` to `destination`, at no more than `rate_limit` bytes per
+/// second on average, or as fast as possible if it is zero.
+///
+/// LMDB writes the copy into a socket on a separate thread, and this function moves it from there
+/// to the file, so that the rate can be limited.  Blocks until the copy is complete.
+pub(super) fn copy_compacted(
+    env: Arc<Environment>,
+    destination: &Path,
+    rate_limit: u64,
+    copied_bytes: Arc<AtomicU64>,
+) -> Result<(), CompactionError> {
+    let (mut reader, writer) = UnixStream::pair()?;
+    let copier = thread::Builder::new()
+        .name("storage-compaction".to_string())
+        .spawn(move || {
+            // The environment is kept alive by the `Arc` until the copy is complete, and the
+            // socket until after that.
+            let code = unsafe { mdb_env_copyfd2(env.env(), writer.as_raw_fd(), MDB_CP_COMPACT) };
+            // Closing the socket ends the stream.
+            drop(writer);
+            if code == MDB_SUCCESS {
+                Ok(())
+            } else {
+                Err(lmdb::Error::from_err_code(code))
+            }
+        })?;
+
+    let mut file = File::create(destination)?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let start = Instant::now();
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        file.write_all(&buffer[..count])?;
+        let total = copied_bytes.fetch_add(count as u64, Ordering::Relaxed) + count as u64;
+        if rate_limit > 0 {
+            let due = Duration::from_secs_f64(total as f64 / rate_limit as f64);
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+    }
+    file.sync_all()?;
+
+    copier
+        .join()
+        .map_err(|_| CompactionError::CopierPanicked)??;
+    Ok(())
+}
@@ -0,0 +1,347 @@
+//! Per-era counts and sizes of the items in storage.
+//!
+//! The counters are kept in their own database, keyed by the big-endian era ID, and updated in the
+//! same transaction as the items they count, so that reading them never requires a scan of the
+//! item databases.  Databases created by an older version are counted once on startup.
+//!
+//! Deploys are attributed to the era of the block including them, and are counted when that block
+//! is stored, provided they are stored by then.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryInto,
+    ops::RangeInclusive,
+};
+
+use lmdb::{Cursor, Database, Environment, RwTransaction, Transaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{
+    compaction::{self, Compaction},
+    format,
+    lmdb_ext::{self, LmdbExtError, TransactionExt, WriteTransactionExt},
+};
+use crate::{
+    components::consensus::EraId,
+    types::{Block, BlockBody, BlockHash, BlockHeader, BlockSignatures, DeployMetadata},
+};
+
+/// The count and total size of a kind of item.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ItemStats {
+    /// The number of items.
+    pub count: u64,
+    /// The total size of the items as stored, in bytes.
+    pub bytes: u64,
+}
+
+impl ItemStats {
+    fn add(&mut self, count: u64, bytes: usize) {
+        self.count = self.count.saturating_add(count);
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+    }
+
+    fn merge(&mut self, other: ItemStats) {
+        self.count = self.count.saturating_add(other.count);
+        self.bytes = self.bytes.saturating_add(other.bytes);
+    }
+
+    fn subtract(&mut self, other: ItemStats) {
+        self.count = self.count.saturating_sub(other.count);
+        self.bytes = self.bytes.saturating_sub(other.bytes);
+    }
+}
+
+/// The items stored for a single era.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraStorageStats {
+    /// The blocks of the era, sized as their header and body together.
+    pub blocks: ItemStats,
+    /// The deploys included in blocks of the era.
+    pub deploys: ItemStats,
+    /// The execution results of deploys in blocks of the era.
+    pub execution_results: ItemStats,
+    /// The finality signatures of blocks of the era.
+    pub signatures: ItemStats,
+}
+
+/// The databases involved in maintaining the per-era statistics.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct EraStatsDatabases {
+    pub(super) era_stats: Database,
+    pub(super) block_header: Database,
+    pub(super) block_body: Database,
+    pub(super) block_metadata: Database,
+    pub(super) deploy: Database,
+    pub(super) deploy_metadata: Database,
+}
+
+/// Returns the key of the statistics of `era_id`.
+fn era_key(era_id: EraId) -> [u8; 8] {
+    era_id.0.to_be_bytes()
+}
+
+/// Returns the size of the value stored under `key`, if any.
+pub(super) fn stored_size<T: Transaction, K: AsRef<[u8]>>(
+    txn: &T,
+    db: Database,
+    key: &K,
+) -> Result<Option<usize>, lmdb::Error> {
+    match txn.get(db, key) {
+        Ok(raw) => Ok(Some(raw.len())),
+        Err(lmdb::Error::NotFound) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Adds the block with the given hash and body, and its deploys stored so far, to `stats`.
+fn count_block<T: Transaction>(
+    txn: &T,
+    dbs: &EraStatsDatabases,
+    block_hash: &BlockHash,
+    body_hash: &impl AsRef<[u8]>,
+    body: &BlockBody,
+    stats: &mut EraStorageStats,
+) -> Result<(), lmdb::Error> {
+    let header_size = stored_size(txn, dbs.block_header, block_hash)?.unwrap_or_default();
+    let body_size = stored_size(txn, dbs.block_body, body_hash)?.unwrap_or_default();
+    stats.blocks.add(1, header_size + body_size);
+    for deploy_hash in body.deploy_hashes().iter().chain(body.transfer_hashes()) {
+        if let Some(deploy_size) = stored_size(txn, dbs.deploy, deploy_hash)? {
+            stats.deploys.add(1, deploy_size);
+        }
+    }
+    Ok(())
+}
+
+/// Applies `update` to the statistics of `era_id`.
+fn update<F>(
+    txn: &mut RwTransaction<'_>,
+    era_stats_db: Database,
+    compaction: &mut Option<Compaction>,
+    era_id: EraId,
+    update: F,
+) -> Result<(), LmdbExtError>
+where
+    F: FnOnce(&mut EraStorageStats),
+{
+    let key = era_key(era_id);
+    let mut stats: EraStorageStats = txn.get_value(era_stats_db, &key)?.unwrap_or_default();
+    update(&mut stats);
+    txn.put_value(era_stats_db, &key, &stats, true)?;
+    compaction::note_write(compaction, era_stats_db, &key);
+    Ok(())
+}
+
+/// Counts a newly stored block, which must already have been written in `txn`.
+pub(super) fn note_block(
+    txn: &mut RwTransaction<'_>,
+    dbs: &EraStatsDatabases,
+    compaction: &mut Option<Compaction>,
+    block: &Block,
+) -> Result<(), LmdbExtError> {
+    let mut delta = EraStorageStats::default();
+    count_block(
+        &*txn,
+        dbs,
+        block.hash(),
+        block.header().body_hash(),
+        block.body(),
+        &mut delta,
+    )?;
+    update(
+        txn,
+        dbs.era_stats,
+        compaction,
+        block.header().era_id(),
+        |stats| {
+            stats.blocks.merge(delta.blocks);
+            stats.deploys.merge(delta.deploys);
+        },
+    )
+}
+
+/// Removes the block with the given header, its deploys and its finality signatures from the
+/// statistics of its era, before they are deleted in `txn`.
+///
+/// The block is sized as stored, and its deploys are the ones stored now, which is what storing the
+/// block again will count.  If the body is missing or corrupt, its deploys can't be subtracted.
+/// Does nothing if the era has no statistics, e.g. as they haven't been counted yet.
+pub(super) fn forget_block(
+    txn: &mut RwTransaction<'_>,
+    dbs: &EraStatsDatabases,
+    header: &BlockHeader,
+) -> Result<(), LmdbExtError> {
+    let key = era_key(header.era_id());
+    let mut stats: EraStorageStats = match txn.get_value(dbs.era_stats, &key)? {
+        Some(stats) => stats,
+        None => return Ok(()),
+    };
+
+    let block_hash = header.hash();
+    let mut removed = EraStorageStats::default();
+    match txn.get_value::<_, BlockBody>(dbs.block_body, header.body_hash()) {
+        Ok(Some(body)) => count_block(
+            &*txn,
+            dbs,
+            &block_hash,
+            header.body_hash(),
+            &body,
+            &mut removed,
+        )?,
+        Ok(None) | Err(LmdbExtError::DataCorrupted(_)) => {
+            let header_size = stored_size(&*txn, dbs.block_header, &block_hash)?;
+            let body_size = stored_size(&*txn, dbs.block_body, header.body_hash())?;
+            removed.blocks.add(
+                1,
+                header_size.unwrap_or_default() + body_size.unwrap_or_default(),
+            );
+        }
+        Err(error) => return Err(error),
+    }
+    if let Some(size) = stored_size(&*txn, dbs.block_metadata, &block_hash)? {
+        let count = match txn.get_value::<_, BlockSignatures>(dbs.block_metadata, &block_hash) {
+            Ok(signatures) => signatures.map_or(0, |signatures| signatures.proofs.len()),
+            Err(LmdbExtError::DataCorrupted(_)) => 0,
+            Err(error) => return Err(error),
+        };
+        removed.signatures.add(count as u64, size);
+    }
+
+    stats.blocks.subtract(removed.blocks);
+    stats.deploys.subtract(removed.deploys);
+    stats.signatures.subtract(removed.signatures);
+    if stats == EraStorageStats::default() {
+        match txn.del(dbs.era_stats, &key, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => (),
+            Err(error) => return Err(error.into()),
+        }
+    } else {
+        txn.put_value(dbs.era_stats, &key, &stats, true)?;
+    }
+    Ok(())
+}
+
+/// Counts newly stored execution results of the deploys in a block of `era_id`.
+pub(super) fn note_execution_results(
+    txn: &mut RwTransaction<'_>,
+    era_stats_db: Database,
+    compaction: &mut Option<Compaction>,
+    era_id: EraId,
+    new_results: ItemStats,
+) -> Result<(), LmdbExtError> {
+    update(txn, era_stats_db, compaction, era_id, |stats| {
+        stats.execution_results.merge(new_results)
+    })
+}
+
+/// Counts newly stored finality signatures of a block of `era_id`, which grew the stored
+/// signatures of the block by `added_bytes`.
+pub(super) fn note_signatures(
+    txn: &mut RwTransaction<'_>,
+    era_stats_db: Database,
+    compaction: &mut Option<Compaction>,
+    era_id: EraId,
+    added_count: usize,
+    added_bytes: usize,
+) -> Result<(), LmdbExtError> {
+    update(txn, era_stats_db, compaction, era_id, |stats| {
+        stats.signatures.add(added_count as u64, added_bytes)
+    })
+}
+
+/// Reads the statistics of the eras in `eras` which have any items stored, in ascending order.
+pub(super) fn read<T: Transaction>(
+    txn: &T,
+    era_stats_db: Database,
+    eras: RangeInclusive<EraId>,
+) -> Result<Vec<(EraId, EraStorageStats)>, LmdbExtError> {
+    let end_key = era_key(*eras.end());
+    let mut cursor = txn.open_ro_cursor(era_stats_db)?;
+    cursor
+        .iter_from(era_key(*eras.start()))
+        .take_while(|(raw_key, _)| *raw_key <= &end_key[..])
+        .map(|(raw_key, raw_stats)| {
+            let era_id = raw_key
+                .try_into()
+                .map(|bytes| EraId(u64::from_be_bytes(bytes)))
+                .map_err(|error| LmdbExtError::DataCorrupted(Box::new(error)))?;
+            Ok((era_id, format::decode(raw_stats)?))
+        })
+        .collect()
+}
+
+/// Counts the items of a database written by a version which didn't maintain the statistics.
+///
+/// Does nothing if any statistics have been written before.
+pub(super) fn count_existing_items(
+    env: &Environment,
+    dbs: &EraStatsDatabases,
+) -> Result<(), LmdbExtError> {
+    let mut txn = env.begin_rw_txn()?;
+    let has_stats = txn.open_ro_cursor(dbs.era_stats)?.iter().next().is_some();
+    if has_stats {
+        return Ok(());
+    }
+
+    info!("counting stored items per era");
+    let mut all_stats: BTreeMap<EraId, EraStorageStats> = BTreeMap::new();
+    let mut block_eras: HashMap<BlockHash, EraId> = HashMap::new();
+    {
+        let mut cursor = txn.open_ro_cursor(dbs.block_header)?;
+        for (_, raw_header) in cursor.iter() {
+            let header: BlockHeader = format::decode(raw_header)?;
+            let block_hash = header.hash();
+            let stats = all_stats.entry(header.era_id()).or_default();
+            let maybe_body: Option<BlockBody> = match txn.get(dbs.block_body, header.body_hash()) {
+                Ok(raw_body) => Some(format::decode(raw_body)?),
+                Err(lmdb::Error::NotFound) => None,
+                Err(error) => return Err(error.into()),
+            };
+            match maybe_body {
+                Some(body) => {
+                    count_block(&txn, dbs, &block_hash, header.body_hash(), &body, stats)?
+                }
+                None => stats.blocks.add(1, raw_header.len()),
+            }
+            block_eras.insert(block_hash, header.era_id());
+        }
+    }
+    {
+        let mut cursor = txn.open_ro_cursor(dbs.deploy_metadata)?;
+        for (_, raw_metadata) in cursor.iter() {
+            let metadata: DeployMetadata = format::decode(raw_metadata)?;
+            for (block_hash, execution_result) in &metadata.execution_results {
+                if let Some(era_id) = block_eras.get(block_hash) {
+                    let size = lmdb_ext::serialize(execution_result)?.len();
+                    all_stats
+                        .entry(*era_id)
+                        .or_default()
+                        .execution_results
+                        .add(1, size);
+                }
+            }
+        }
+    }
+    {
+        let mut cursor = txn.open_ro_cursor(dbs.block_metadata)?;
+        for (_, raw_signatures) in cursor.iter() {
+            let signatures: BlockSignatures = format::decode(raw_signatures)?;
+            all_stats
+                .entry(signatures.era_id)
+                .or_default()
+                .signatures
+                .add(signatures.proofs.len() as u64, raw_signatures.len());
+        }
+    }
+    for (era_id, stats) in &all_stats {
+        txn.put_value(dbs.era_stats, &era_key(*era_id), stats, true)?;
+    }
+    txn.commit()?;
+    info!(eras = all_stats.len(), "per-era item count complete");
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Format versions of stored values.
+//!
+//! Every value written to storage is prefixed with `VERSION_MARKER` and a byte holding the format
+//! version of its type, followed by its bincode serialization.  Whenever the serialized form of a
+//! stored type changes, its `FORMAT_VERSION` has to be incremented, and `decode_older` taught how
+//! to read the previous versions, so that the data already on disk stays readable.
+//!
+//! Values written before format versions were introduced carry no prefix and are read as version
+//! 0. They can be told apart from versioned values since the serialization of none of the stored
+//! types starts with `VERSION_MARKER`: They all start with a length prefix or a small enum tag.
+//!
+//! The fixtures in `resources/test/storage` hold values as written by each release, and the
+//! storage tests check that they can still be read.
+
+use std::any;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use casper_types::Transfer;
+
+use super::{
+    era_stats::EraStorageStats,
+    lmdb_ext::{self, LmdbExtError},
+};
+use crate::types::{
+    BlockBody, BlockBodyWithoutRewardedSignatures, BlockHeader, BlockSignatures, Deploy,
+    DeployMetadata, DeployWithoutPriorityFee,
+};
+
+/// The prefix of every value written with an explicit format version.
+const VERSION_MARKER: [u8; 8] = [0xff; 8];
+
+/// The format version of values written before format versions were introduced.
+const UNVERSIONED: u8 = 0;
+
+/// A type of value kept in storage.
+pub(super) trait StorageFormat: Serialize + DeserializeOwned {
+    /// The format version values of this type are written in.
+    const FORMAT_VERSION: u8;
+
+    /// Decodes a value serialized in the format `version`, which is older than `FORMAT_VERSION`.
+    ///
+    /// The default implementation only supports unversioned values with the same serialization
+    /// as the current format.
+    fn decode_older(version: u8, raw: &[u8]) -> Result<Self, LmdbExtError> {
+        match version {
+            UNVERSIONED => lmdb_ext::deserialize(raw),
+            _ => Err(unsupported::<Self>(version)),
+        }
+    }
+}
+
+impl StorageFormat for BlockHeader {
+    const FORMAT_VERSION: u8 = 1;
+}
+
+impl StorageFormat for BlockBody {
+    /// Version 2 added the rewarded signatures to the block body.
+    const FORMAT_VERSION: u8 = 2;
+
+    fn decode_older(version: u8, raw: &[u8]) -> Result<Self, LmdbExtError> {
+        match version {
+            UNVERSIONED | 1 => lmdb_ext::deserialize::<BlockBodyWithoutRewardedSignatures>(raw)
+                .map(BlockBody::from),
+            _ => Err(unsupported::<Self>(version)),
+        }
+    }
+}
+
+impl StorageFormat for BlockSignatures {
+    const FORMAT_VERSION: u8 = 1;
+}
+
+impl StorageFormat for Deploy {
+    /// Version 2 added the priority fee to the deploy header.
+    const FORMAT_VERSION: u8 = 2;
+
+    fn decode_older(version: u8, raw: &[u8]) -> Result<Self, LmdbExtError> {
+        match version {
+            UNVERSIONED | 1 => {
+                lmdb_ext::deserialize::<DeployWithoutPriorityFee>(raw).map(Deploy::from)
+            }
+            _ => Err(unsupported::<Self>(version)),
+        }
+    }
+}
+
+impl StorageFormat for DeployMetadata {
+    const FORMAT_VERSION: u8 = 1;
+}
+
+impl StorageFormat for Vec<Transfer> {
+    const FORMAT_VERSION: u8 = 1;
+}
+
+impl StorageFormat for EraStorageStats {
+    const FORMAT_VERSION: u8 = 1;
+}
+
+/// Serializes a value, prefixed with the current format version of its type.
+pub(super) fn encode<T: StorageFormat>(value: &T) -> Result<Vec<u8>, LmdbExtError> {
+    let mut buffer = VERSION_MARKER.to_vec();
+    buffer.push(T::FORMAT_VERSION);
+    buffer.extend(lmdb_ext::serialize(value)?);
+    Ok(buffer)
+}
+
+/// Deserializes a value written in the current or any older format version of its type.
+pub(super) fn decode<T: StorageFormat>(raw: &[u8]) -> Result<T, LmdbExtError> {
+    let (version, payload) = split_version(raw);
+    if version == T::FORMAT_VERSION {
+        lmdb_ext::deserialize(payload)
+    } else if version < T::FORMAT_VERSION {
+        T::decode_older(version, payload)
+    } else {
+        Err(unsupported::<T>(version))
+    }
+}
+
+/// Splits a raw value into its format version and its serialized payload.
+fn split_version(raw: &[u8]) -> (u8, &[u8]) {
+    if raw.starts_with(&VERSION_MARKER) {
+        if let [version, payload @ ..] = &raw[VERSION_MARKER.len()..] {
+            return (*version, payload);
+        }
+    }
+    (UNVERSIONED, raw)
+}
+
+fn unsupported<T>(version: u8) -> LmdbExtError {
+    LmdbExtError::UnsupportedFormatVersion {
+        type_name: any::type_name::<T>(),
+        version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_read_versioned_and_unversioned_values() {
+        let mut rng = TestRng::new();
+        let deploy = Deploy::random(&mut rng);
+
+        let encoded = encode(&deploy).unwrap();
+        assert_eq!(encoded[..VERSION_MARKER.len()], VERSION_MARKER);
+        assert_eq!(encoded[VERSION_MARKER.len()], Deploy::FORMAT_VERSION);
+        assert_eq!(decode::<Deploy>(&encoded).unwrap(), deploy);
+
+        // Deploys stored before version 2 have no priority fee.
+        let legacy = lmdb_ext::serialize(&DeployWithoutPriorityFee::from(deploy.clone())).unwrap();
+        assert_eq!(decode::<Deploy>(&legacy).unwrap(), deploy);
+        let mut version_1 = VERSION_MARKER.to_vec();
+        version_1.push(1);
+        version_1.extend(legacy);
+        assert_eq!(decode::<Deploy>(&version_1).unwrap(), deploy);
+
+        // Values written by a newer version of the software are rejected.
+        let mut newer = encoded;
+        newer[VERSION_MARKER.len()] = Deploy::FORMAT_VERSION + 1;
+        assert!(matches!(
+            decode::<Deploy>(&newer),
+            Err(LmdbExtError::UnsupportedFormatVersion { version, .. })
+                if version == Deploy::FORMAT_VERSION + 1
+        ));
+    }
+}
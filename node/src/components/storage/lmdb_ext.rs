@@ -9,12 +9,16 @@
 //!
 //! Serialization errors are unified into a generic, type erased `std` error to allow for easy
 //! interchange of the serialization format if desired.
+//!
+//! Values are tagged with the format version of their type when written, see the `format` module.
 
 use crate::{crypto::hash::Digest, types::BlockHash};
 use lmdb::{Database, RwTransaction, Transaction, WriteFlags};
 use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
 
+use super::format::{self, StorageFormat};
+
 /// Error wrapper for lower-level storage errors.
 ///
 /// Used to classify storage errors, allowing more accurate reporting on potential issues and
@@ -56,6 +60,13 @@ pub enum LmdbExtError {
         queried_block_body_hash: Digest,
         found_block_body_hash: Digest,
     },
+    /// A value was written in a format version unknown to this software, probably by a newer
+    /// version of it.
+    #[error("unsupported format version {version} of stored {type_name}")]
+    UnsupportedFormatVersion {
+        type_name: &'static str,
+        version: u8,
+    },
 }
 
 // Classifies an `lmdb::Error` according to our scheme. This one of the rare cases where we accept a
@@ -93,7 +104,7 @@ impl From<lmdb::Error> for LmdbExtError {
 /// Additional methods on transaction.
 pub(super) trait TransactionExt {
     /// Helper function to load a value from a database.
-    fn get_value<K: AsRef<[u8]>, V: DeserializeOwned>(
+    fn get_value<K: AsRef<[u8]>, V: StorageFormat>(
         &mut self,
         db: Database,
         key: &K,
@@ -107,7 +118,7 @@ pub(super) trait WriteTransactionExt {
     /// Returns `true` if the value has actually been written, `false` if the key already existed.
     ///
     /// Setting `overwrite` to true will cause the value to always be written instead.
-    fn put_value<K: AsRef<[u8]>, V: Serialize>(
+    fn put_value<K: AsRef<[u8]>, V: StorageFormat>(
         &mut self,
         db: Database,
         key: &K,
@@ -121,14 +132,14 @@ where
     T: Transaction,
 {
     #[inline]
-    fn get_value<K: AsRef<[u8]>, V: DeserializeOwned>(
+    fn get_value<K: AsRef<[u8]>, V: StorageFormat>(
         &mut self,
         db: Database,
         key: &K,
     ) -> Result<Option<V>, LmdbExtError> {
         match self.get(db, key) {
             // Deserialization failures are likely due to storage corruption.
-            Ok(raw) => deserialize(raw).map(Some),
+            Ok(raw) => format::decode(raw).map(Some),
             Err(lmdb::Error::NotFound) => Ok(None),
             Err(err) => Err(err.into()),
         }
@@ -136,14 +147,14 @@ where
 }
 
 impl WriteTransactionExt for RwTransaction<'_> {
-    fn put_value<K: AsRef<[u8]>, V: Serialize>(
+    fn put_value<K: AsRef<[u8]>, V: StorageFormat>(
         &mut self,
         db: Database,
         key: &K,
         value: &V,
         overwrite: bool,
     ) -> Result<bool, LmdbExtError> {
-        let buffer = serialize(value)?;
+        let buffer = format::encode(value)?;
 
         let flags = if overwrite {
             WriteFlags::empty()
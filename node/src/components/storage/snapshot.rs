@@ -0,0 +1,109 @@
+//! Reads served from a snapshot of the databases on worker threads.
+//!
+//! Queries which can return a lot of data, i.e. ranges of blocks and deploys along with their
+//! execution results, are not read while handling the request like all others.  Instead, the
+//! storage component hands a `Snapshot` to a blocking worker thread, which opens its own read
+//! transaction, so that the component is free to handle the next event in the meantime.
+//!
+//! A snapshot holds the environment and database handles in use when it was taken.  If the
+//! database is swapped for a compacted copy while a worker is reading, the worker finishes reading
+//! the previous file, which holds the same data.
+
+use std::sync::Arc;
+
+use lmdb::{Database, Environment, Transaction};
+
+use casper_types::bytesrepr::ToBytes;
+
+use super::{lmdb_ext::TransactionExt, read_block, Error};
+use crate::types::{
+    BlockHash, BlockSignatures, BlockWithDeploys, Deploy, DeployHash, DeployMetadata,
+};
+
+/// Maximum serialized size of the blocks and deploys returned for a single block range request.
+///
+/// At least one block is always returned, even if it exceeds this on its own.
+const MAX_BLOCK_RANGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// The environment and the handles of the databases needed to serve reads on a worker thread.
+#[derive(Debug)]
+pub(super) struct Snapshot {
+    pub(super) env: Arc<Environment>,
+    pub(super) block_header_db: Database,
+    pub(super) block_body_db: Database,
+    pub(super) block_metadata_db: Database,
+    pub(super) deploy_db: Database,
+    pub(super) deploy_metadata_db: Database,
+}
+
+impl Snapshot {
+    /// Reads the blocks with the given hashes, along with their finality signatures and optionally
+    /// their deploys.
+    ///
+    /// The range ends early at the first block which is not stored, or once the serialized size of
+    /// the result would exceed `MAX_BLOCK_RANGE_BYTES`.
+    pub(super) fn read_block_range(
+        &self,
+        block_hashes: &[BlockHash],
+        with_deploys: bool,
+    ) -> Result<Vec<BlockWithDeploys>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let mut blocks = Vec::new();
+        let mut total_size = 0;
+        for block_hash in block_hashes {
+            let block = match read_block(
+                &mut txn,
+                self.block_header_db,
+                self.block_body_db,
+                block_hash,
+            )? {
+                Some(block) => block,
+                None => break,
+            };
+            let mut size = block.serialized_length();
+            let deploys = if with_deploys {
+                let deploys = block
+                    .deploy_hashes()
+                    .iter()
+                    .chain(block.transfer_hashes())
+                    .map(|deploy_hash| txn.get_value(self.deploy_db, deploy_hash))
+                    .filter_map(Result::transpose)
+                    .collect::<Result<Vec<Deploy>, _>>()?;
+                size += deploys.iter().map(Deploy::serialized_length).sum::<usize>();
+                Some(deploys)
+            } else {
+                None
+            };
+            total_size += size;
+            if total_size > MAX_BLOCK_RANGE_BYTES && !blocks.is_empty() {
+                break;
+            }
+            let signatures = match txn.get_value(self.block_metadata_db, block_hash)? {
+                Some(signatures) => signatures,
+                None => BlockSignatures::new(*block_hash, block.header().era_id()),
+            };
+            blocks.push(BlockWithDeploys {
+                block,
+                signatures,
+                deploys,
+            });
+        }
+        Ok(blocks)
+    }
+
+    /// Reads a deploy along with its metadata, which defaults to empty if none is stored.
+    pub(super) fn read_deploy_and_metadata(
+        &self,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<(Deploy, DeployMetadata)>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let deploy: Deploy = match txn.get_value(self.deploy_db, deploy_hash)? {
+            Some(deploy) => deploy,
+            None => return Ok(None),
+        };
+        let metadata = txn
+            .get_value(self.deploy_metadata_db, deploy_hash)?
+            .unwrap_or_default();
+        Ok(Some((deploy, metadata)))
+    }
+}
@@ -0,0 +1,164 @@
+//! Statistics of the LMDB environment backing the storage component.
+//!
+//! The `lmdb` crate does not expose database or environment statistics, so these are read through
+//! the raw bindings of `lmdb-sys`.
+
+use std::{mem::MaybeUninit, os::raw::c_int, ptr};
+
+use lmdb::{Database, Environment, Transaction};
+use lmdb_sys::{
+    mdb_cursor_close, mdb_cursor_get, mdb_cursor_open, mdb_env_info, mdb_env_stat, mdb_stat,
+    MDB_cursor, MDB_dbi, MDB_envinfo, MDB_stat, MDB_val, MDB_NEXT, MDB_NOTFOUND, MDB_SUCCESS,
+};
+
+use super::CompactionProgress;
+
+/// Handle of LMDB's internal database tracking the pages freed by past transactions.
+const FREE_DBI: MDB_dbi = 0;
+
+/// Statistics of a single database.
+#[derive(Clone, Debug)]
+pub struct DatabaseStats {
+    /// Name of the database.
+    pub name: &'static str,
+    /// Number of entries.
+    pub entries: u64,
+    /// Number of internal (non-leaf) pages.
+    pub branch_pages: u64,
+    /// Number of leaf pages.
+    pub leaf_pages: u64,
+    /// Number of overflow pages, holding values too large to fit on a leaf page.
+    pub overflow_pages: u64,
+}
+
+/// Statistics of the LMDB environment and all its databases.
+#[derive(Clone, Debug)]
+pub struct StorageStats {
+    /// Size of a database page, in bytes.
+    pub page_size: u64,
+    /// Size of the memory map, i.e. the maximum size the database file can grow to, in bytes.
+    pub map_size: u64,
+    /// Number of pages used in the database file so far, including free ones.
+    pub used_pages: u64,
+    /// Number of pages freed by past transactions which can be reused before the file grows.
+    pub free_pages: u64,
+    /// Statistics of the individual databases.
+    pub databases: Vec<DatabaseStats>,
+    /// The progress of the compaction in progress, if any.
+    pub compaction: Option<CompactionProgress>,
+}
+
+impl StorageStats {
+    /// Returns the number of bytes used in the database file so far, including free pages.
+    pub fn used_size(&self) -> u64 {
+        self.used_pages.saturating_mul(self.page_size)
+    }
+
+    /// Returns the fraction of the memory map used so far, excluding free pages.
+    pub fn map_utilization(&self) -> f64 {
+        if self.map_size == 0 {
+            return 0.0;
+        }
+        let occupied_size = self
+            .used_pages
+            .saturating_sub(self.free_pages)
+            .saturating_mul(self.page_size);
+        occupied_size as f64 / self.map_size as f64
+    }
+}
+
+/// Reads the statistics of `env` and the given named databases.
+pub(super) fn read_stats<I>(env: &Environment, databases: I) -> Result<StorageStats, lmdb::Error>
+where
+    I: IntoIterator<Item = (&'static str, Database)>,
+{
+    let mut env_stat = MaybeUninit::<MDB_stat>::uninit();
+    let mut env_info = MaybeUninit::<MDB_envinfo>::uninit();
+    // The environment handle is valid for the lifetime of `env`, and both calls fully initialize
+    // the given structs on success.
+    let (env_stat, env_info) = unsafe {
+        check(mdb_env_stat(env.env(), env_stat.as_mut_ptr()))?;
+        check(mdb_env_info(env.env(), env_info.as_mut_ptr()))?;
+        (env_stat.assume_init(), env_info.assume_init())
+    };
+
+    // Read everything else from a single snapshot.
+    let txn = env.begin_ro_txn()?;
+    let databases = databases
+        .into_iter()
+        .map(|(name, db)| {
+            let stat = database_stat(&txn, db.dbi())?;
+            Ok(DatabaseStats {
+                name,
+                entries: stat.ms_entries as u64,
+                branch_pages: stat.ms_branch_pages as u64,
+                leaf_pages: stat.ms_leaf_pages as u64,
+                overflow_pages: stat.ms_overflow_pages as u64,
+            })
+        })
+        .collect::<Result<Vec<_>, lmdb::Error>>()?;
+    let free_pages = free_pages(&txn)?;
+    txn.commit()?;
+
+    Ok(StorageStats {
+        page_size: u64::from(env_stat.ms_psize),
+        map_size: env_info.me_mapsize as u64,
+        // Page numbers start at zero.
+        used_pages: (env_info.me_last_pgno as u64).saturating_add(1),
+        free_pages,
+        databases,
+        compaction: None,
+    })
+}
+
+/// Reads the statistics of the database with handle `dbi`.
+fn database_stat<T: Transaction>(txn: &T, dbi: MDB_dbi) -> Result<MDB_stat, lmdb::Error> {
+    let mut stat = MaybeUninit::<MDB_stat>::uninit();
+    // The transaction handle is valid while `txn` is borrowed, and the call fully initializes the
+    // struct on success.
+    unsafe {
+        check(mdb_stat(txn.txn(), dbi, stat.as_mut_ptr()))?;
+        Ok(stat.assume_init())
+    }
+}
+
+/// Counts the pages listed in LMDB's free database.
+fn free_pages<T: Transaction>(txn: &T) -> Result<u64, lmdb::Error> {
+    let mut cursor: *mut MDB_cursor = ptr::null_mut();
+    // The transaction handle is valid while `txn` is borrowed.
+    unsafe { check(mdb_cursor_open(txn.txn(), FREE_DBI, &mut cursor))? };
+
+    let mut key = MDB_val {
+        mv_size: 0,
+        mv_data: ptr::null_mut(),
+    };
+    let mut data = MDB_val {
+        mv_size: 0,
+        mv_data: ptr::null_mut(),
+    };
+    let mut free_pages = 0u64;
+    let result = loop {
+        // The cursor was successfully opened above and is only closed after the loop.
+        match unsafe { mdb_cursor_get(cursor, &mut key, &mut data, MDB_NEXT) } {
+            MDB_SUCCESS => {
+                // Each record holds a list of page numbers, prefixed by its length.  The data
+                // stays valid until the next cursor operation.
+                let count = unsafe { ptr::read_unaligned(data.mv_data as *const usize) };
+                free_pages = free_pages.saturating_add(count as u64);
+            }
+            MDB_NOTFOUND => break Ok(free_pages),
+            code => break Err(lmdb::Error::from_err_code(code)),
+        }
+    };
+    unsafe { mdb_cursor_close(cursor) };
+    result
+}
+
+/// Converts an LMDB return code into a `Result`.
+fn check(code: c_int) -> Result<(), lmdb::Error> {
+    if code == MDB_SUCCESS {
+        Ok(())
+    } else {
+        Err(lmdb::Error::from_err_code(code))
+    }
+}
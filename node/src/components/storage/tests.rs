@@ -1,23 +1,33 @@
 //! Unit tests for the storage component.
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fs, iter};
 
+use futures::channel::oneshot;
 use rand::{prelude::SliceRandom, Rng};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use smallvec::smallvec;
 
-use casper_types::ExecutionResult;
+use casper_types::{
+    account::AccountHash, ExecutionResult, ProtocolVersion, PublicKey, SecretKey, Transfer,
+};
 
-use super::{Config, Storage};
+use super::{
+    format, Config, EraStorageStats, Event, Storage, COMPACTED_DB_FILENAME, STORAGE_DB_FILENAME,
+};
 use crate::{
     components::consensus::EraId,
+    crypto::{self, hash::Digest, AsymmetricKeyExt},
     effect::{
         requests::{StateStoreRequest, StorageRequest},
-        Multiple,
+        Multiple, Responder,
     },
     testing::{ComponentHarness, TestRng},
-    types::{Block, BlockHash, Deploy, DeployHash, DeployMetadata},
-    utils::WithDir,
+    types::{
+        Block, BlockBody, BlockExecutionResults, BlockHash, BlockHeader, BlockLike,
+        BlockSignatures, BlockWithDeploys, Deploy, DeployHash, DeployMetadata, FinalizedBlock,
+        RewardedSignatures, TimeDiff, Timestamp,
+    },
+    utils::{WithDir, RESOURCES_PATH},
 };
 
 fn new_config(harness: &ComponentHarness<()>) -> Config {
@@ -30,6 +40,11 @@ fn new_config(harness: &ComponentHarness<()>) -> Config {
         max_deploy_store_size: 50 * MIB,
         max_deploy_metadata_store_size: 50 * MIB,
         max_state_store_size: 50 * MIB,
+        compaction_rate_limit: 0,
+        compaction_max_sync_lag: TimeDiff::from(60_000),
+        // Most tests store unrelated random blocks, which would not pass the startup verification.
+        startup_verification_depth: 0,
+        read_workers: 2,
     }
 }
 
@@ -59,6 +74,23 @@ fn storage_fixture_with_hard_reset(harness: &ComponentHarness<()>, reset_era_id:
         .expect("could not create storage component fixture")
 }
 
+/// Storage component test fixture.
+///
+/// Creates a storage component in a temporary directory, verifying the given number of highest
+/// blocks on startup.
+///
+/// # Panics
+///
+/// Panics if setting up the storage fixture fails.
+fn storage_fixture_with_verification_depth(harness: &ComponentHarness<()>, depth: u64) -> Storage {
+    let cfg = Config {
+        startup_verification_depth: depth,
+        ..new_config(harness)
+    };
+    Storage::new(&WithDir::new(harness.tmp.path(), cfg), None)
+        .expect("could not create storage component fixture")
+}
+
 /// Creates a random block with a specific block height.
 fn random_block_at_height(rng: &mut TestRng, height: u64) -> Box<Block> {
     let mut block = Box::new(Block::random(rng));
@@ -66,6 +98,24 @@ fn random_block_at_height(rng: &mut TestRng, height: u64) -> Box<Block> {
     block
 }
 
+/// Creates a random block with `parent` as its parent.
+fn random_child(rng: &mut TestRng, parent: &Block) -> Block {
+    let finalized_block = FinalizedBlock::random_with_specifics(
+        rng,
+        parent.header().era_id(),
+        parent.height() + 1,
+        false,
+    );
+    Block::new(
+        *parent.hash(),
+        Digest::random(rng),
+        Digest::random(rng),
+        finalized_block,
+        None,
+        ProtocolVersion::V1_0_0,
+    )
+}
+
 /// Requests block at a specific height from a storage component.
 fn get_block_at_height(
     harness: &mut ComponentHarness<()>,
@@ -130,6 +180,46 @@ fn get_deploy_and_metadata(
     response
 }
 
+/// Loads the hashes of the deploys created by an account from the storage component.
+fn get_deploy_hashes_by_account(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+    account: AccountHash,
+    range: std::ops::Range<u64>,
+) -> Vec<DeployHash> {
+    let response = harness.send_request(storage, |responder| {
+        StorageRequest::GetDeployHashesByAccount {
+            account,
+            range,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
+/// Requests a range of consecutive blocks from a storage component.
+fn get_block_range(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+    start_height: u64,
+    count: u64,
+    with_deploys: bool,
+) -> Vec<BlockWithDeploys> {
+    let response = harness.send_request(storage, |responder| {
+        StorageRequest::GetBlockRange {
+            start_height,
+            count,
+            with_deploys,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
 /// Requests the highest block from a storage component.
 fn get_highest_block(harness: &mut ComponentHarness<()>, storage: &mut Storage) -> Option<Block> {
     let response = harness.send_request(storage, |responder| {
@@ -166,6 +256,56 @@ fn put_block(harness: &mut ComponentHarness<()>, storage: &mut Storage, block: B
     response
 }
 
+/// Stores a block's finality signatures in a storage component.
+fn put_block_signatures(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+    signatures: BlockSignatures,
+) -> bool {
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::PutBlockSignatures {
+            signatures,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
+/// Loads a block's finality signatures from a storage component.
+fn get_block_signatures(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+    block_hash: BlockHash,
+) -> Option<BlockSignatures> {
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::GetBlockSignatures {
+            block_hash,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
+/// Loads the statistics of all eras from a storage component.
+fn get_all_era_storage_stats(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+) -> Vec<(EraId, EraStorageStats)> {
+    let response = harness.send_request(storage, |responder| {
+        StorageRequest::GetEraStorageStats {
+            eras: EraId(0)..=EraId(u64::MAX),
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
 /// Stores a deploy in a storage component.
 fn put_deploy(
     harness: &mut ComponentHarness<()>,
@@ -198,6 +338,23 @@ fn put_execution_results(
     response
 }
 
+/// Loads the execution results of a block from a storage component.
+fn get_block_execution_results(
+    harness: &mut ComponentHarness<()>,
+    storage: &mut Storage,
+    block_hash: BlockHash,
+) -> Option<BlockExecutionResults> {
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::GetBlockExecutionResults {
+            block_hash,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
 /// Saves state from the storage component.
 fn save_state<T>(
     harness: &mut ComponentHarness<()>,
@@ -340,6 +497,45 @@ fn can_retrieve_block_by_height() {
     );
 }
 
+#[test]
+fn can_retrieve_block_range() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // Store blocks at heights 0, 1, 2 and 4, leaving a gap at 3.
+    let blocks: Vec<_> = [0, 1, 2, 4]
+        .iter()
+        .map(|&height| random_block_at_height(&mut harness.rng, height))
+        .collect();
+    for block in &blocks {
+        put_block(&mut harness, &mut storage, block.clone());
+    }
+
+    let heights = |range: &[BlockWithDeploys]| -> Vec<u64> {
+        range.iter().map(|entry| entry.block.height()).collect()
+    };
+
+    // The range is limited by the count, and ends at the first missing block.
+    let range = get_block_range(&mut harness, &mut storage, 0, 2, false);
+    assert_eq!(heights(&range), vec![0, 1]);
+    assert_eq!(range[0].block, *blocks[0]);
+    assert!(range.iter().all(|entry| entry.deploys.is_none()));
+
+    let range = get_block_range(&mut harness, &mut storage, 1, 10, true);
+    assert_eq!(heights(&range), vec![1, 2]);
+    assert_eq!(range[0].signatures.block_hash, *blocks[1].hash());
+    // None of the blocks' deploys are stored.
+    assert!(range
+        .iter()
+        .all(|entry| entry.deploys.as_ref().map(Vec::is_empty) == Some(true)));
+
+    assert!(get_block_range(&mut harness, &mut storage, 3, 10, false).is_empty());
+    assert_eq!(
+        heights(&get_block_range(&mut harness, &mut storage, 4, 10, false)),
+        vec![4]
+    );
+}
+
 #[test]
 #[should_panic(expected = "duplicate entries")]
 fn different_block_at_height_is_fatal() {
@@ -422,6 +618,109 @@ fn can_retrieve_store_and_load_deploys() {
     assert_eq!(metadata_response, DeployMetadata::default());
 }
 
+#[test]
+fn should_merge_approvals_into_stored_deploy() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let deploy = Box::new(Deploy::random(&mut harness.rng));
+    let deploy_hash = *deploy.id();
+    let mut cosigned = deploy.clone();
+    let secret_key = SecretKey::random(&mut harness.rng);
+    cosigned.sign(&secret_key, &mut harness.rng);
+    let approvals = cosigned.approvals().to_vec();
+
+    // Nothing is merged into a deploy which isn't stored.
+    let response = harness.send_request(&mut storage, |responder| {
+        StorageRequest::MergeDeployApprovals {
+            deploy_hash,
+            approvals: approvals.clone(),
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, None);
+
+    assert!(put_deploy(&mut harness, &mut storage, deploy));
+    let response = harness.send_request(&mut storage, |responder| {
+        StorageRequest::MergeDeployApprovals {
+            deploy_hash,
+            approvals: approvals.clone(),
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, Some(approvals.clone()));
+    let stored = get_deploys(&mut harness, &mut storage, smallvec![deploy_hash]);
+    assert_eq!(stored, vec![Some(cosigned.as_ref().clone())]);
+
+    // Merging the same approvals again changes nothing.
+    let response = harness.send_request(&mut storage, |responder| {
+        StorageRequest::MergeDeployApprovals {
+            deploy_hash,
+            approvals,
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, None);
+}
+
+#[test]
+fn can_retrieve_deploy_hashes_by_account() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let secret_key = SecretKey::random(&mut harness.rng);
+    let account = PublicKey::from(&secret_key).to_account_hash();
+
+    // Store deploys by the same account in reverse chronological order, interleaved with deploys
+    // from other accounts.
+    let mut expected = Vec::new();
+    for millis in (1..=5).rev() {
+        let deploy = Deploy::new(
+            Timestamp::from(millis * 1_000),
+            TimeDiff::from(60_000),
+            1,
+            0,
+            vec![],
+            String::from("casper-example"),
+            harness.rng.gen(),
+            harness.rng.gen(),
+            &secret_key,
+            &mut harness.rng,
+        );
+        expected.insert(0, *deploy.id());
+        put_deploy(&mut harness, &mut storage, Box::new(deploy));
+        put_deploy(
+            &mut harness,
+            &mut storage,
+            Box::new(Deploy::random(&mut harness.rng)),
+        );
+    }
+
+    // All deploys are returned in chronological order, and storing a deploy twice does not
+    // duplicate it in the index.
+    let (deploy, _) = get_deploy_and_metadata(&mut harness, &mut storage, expected[0])
+        .expect("should have stored deploy");
+    assert!(!put_deploy(&mut harness, &mut storage, Box::new(deploy)));
+    assert_eq!(
+        get_deploy_hashes_by_account(&mut harness, &mut storage, account, 0..u64::MAX),
+        expected
+    );
+
+    // Pages are selected by position.
+    assert_eq!(
+        get_deploy_hashes_by_account(&mut harness, &mut storage, account, 1..3),
+        expected[1..3].to_vec()
+    );
+    assert!(get_deploy_hashes_by_account(&mut harness, &mut storage, account, 5..10).is_empty());
+
+    // An unknown account has no deploys.
+    let unknown = AccountHash::new([0xff; 32]);
+    assert!(get_deploy_hashes_by_account(&mut harness, &mut storage, unknown, 0..10).is_empty());
+}
+
 #[test]
 fn storing_and_loading_a_lot_of_deploys_does_not_exhaust_handles() {
     let mut harness = ComponentHarness::default();
@@ -500,6 +799,47 @@ fn store_execution_results_for_two_blocks() {
     );
 }
 
+#[test]
+fn should_get_execution_results_of_block_only_once_all_are_stored() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block = iter::repeat_with(|| Block::random(&mut harness.rng))
+        .find(|block| block.deploys().len() > 1)
+        .expect("should create a block with several deploys");
+    let block_hash = *block.hash();
+    let execution_results: Vec<(DeployHash, ExecutionResult)> = block
+        .deploys()
+        .into_iter()
+        .map(|deploy_hash| (*deploy_hash, harness.rng.gen()))
+        .collect();
+    put_block(&mut harness, &mut storage, Box::new(block));
+
+    // The result of the last deploy is missing.
+    let (last, others) = execution_results.split_last().unwrap();
+    put_execution_results(
+        &mut harness,
+        &mut storage,
+        block_hash,
+        others.iter().cloned().collect(),
+    );
+    assert_eq!(
+        get_block_execution_results(&mut harness, &mut storage, block_hash),
+        None
+    );
+
+    put_execution_results(
+        &mut harness,
+        &mut storage,
+        block_hash,
+        iter::once(last.clone()).collect(),
+    );
+    assert_eq!(
+        get_block_execution_results(&mut harness, &mut storage, block_hash),
+        Some(BlockExecutionResults::new(block_hash, execution_results))
+    );
+}
+
 #[test]
 fn store_random_execution_results() {
     let mut harness = ComponentHarness::default();
@@ -846,3 +1186,334 @@ fn should_hard_reset() {
     // Check the highest block is `None`.
     assert!(get_highest_block(&mut harness, &mut storage).is_none());
 }
+
+#[test]
+fn should_report_database_stats() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block = Box::new(Block::random(&mut harness.rng));
+    assert!(put_block(&mut harness, &mut storage, block));
+
+    let stats = harness
+        .send_request(&mut storage, |responder| {
+            StorageRequest::GetStats { responder }.into()
+        })
+        .expect("should read storage stats");
+    assert!(harness.is_idle());
+
+    let entries = |name| {
+        stats
+            .databases
+            .iter()
+            .find(|db| db.name == name)
+            .expect("should have stats of every database")
+            .entries
+    };
+    assert_eq!(stats.databases.len(), 9);
+    assert_eq!(entries("block_header"), 1);
+    assert_eq!(entries("block_body"), 1);
+    assert_eq!(entries("deploys"), 0);
+    assert!(stats.used_size() > 0);
+    assert!(stats.map_utilization() > 0.0 && stats.map_utilization() < 1.0);
+}
+
+#[test]
+fn should_count_stored_items_per_era() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let deploy = Deploy::random(&mut harness.rng);
+    let block = random_block_at_height(&mut harness.rng, 42);
+    let era_id = block.header().era_id();
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(deploy.clone())
+    ));
+    assert!(put_block(&mut harness, &mut storage, block.clone()));
+    // Storing a block again doesn't count it twice.
+    assert!(put_block(&mut harness, &mut storage, block.clone()));
+    let mut execution_results = HashMap::new();
+    execution_results.insert(*deploy.id(), harness.rng.gen());
+    put_execution_results(
+        &mut harness,
+        &mut storage,
+        *block.hash(),
+        execution_results.clone(),
+    );
+    put_execution_results(&mut harness, &mut storage, *block.hash(), execution_results);
+
+    let mut get_era_storage_stats = |eras| {
+        let response = harness.send_request(&mut storage, |responder| {
+            StorageRequest::GetEraStorageStats { eras, responder }.into()
+        });
+        assert!(harness.is_idle());
+        response
+    };
+    let all_stats = get_era_storage_stats(era_id..=era_id);
+    assert_eq!(all_stats.len(), 1);
+    let (stats_era_id, stats) = all_stats[0];
+    assert_eq!(stats_era_id, era_id);
+    assert_eq!(stats.blocks.count, 1);
+    assert!(stats.blocks.bytes > 0);
+    // The deploy isn't included in the random block.
+    assert_eq!(stats.deploys.count, 0);
+    assert_eq!(stats.execution_results.count, 1);
+    assert!(stats.execution_results.bytes > 0);
+    assert!(get_era_storage_stats(era_id.successor()..=EraId(u64::MAX)).is_empty());
+
+    // Counting the items of a database without statistics yields the same figures.
+    let dbs = storage.era_stats_databases();
+    let mut txn = storage.env().begin_rw_txn().unwrap();
+    txn.clear_db(dbs.era_stats).unwrap();
+    txn.commit().unwrap();
+    super::era_stats::count_existing_items(storage.env(), &dbs).unwrap();
+    let txn = storage.env().begin_ro_txn().unwrap();
+    let recounted = super::era_stats::read(&txn, dbs.era_stats, era_id..=era_id).unwrap();
+    assert_eq!(recounted, all_stats);
+}
+
+#[test]
+fn should_refuse_compaction_without_blocks() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let result = harness.send_request(&mut storage, |responder| {
+        StorageRequest::CompactStorage { responder }.into()
+    });
+    assert!(harness.is_idle());
+    assert!(result.is_err());
+}
+
+#[test]
+fn should_compact_and_keep_writes_made_while_copying() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let blocks: Vec<_> = (0..3)
+        .map(|height| random_block_at_height(&mut harness.rng, height))
+        .collect();
+    for block in &blocks {
+        assert!(put_block(&mut harness, &mut storage, block.clone()));
+    }
+
+    // Start the compaction, but hold back the event announcing the copy is complete.
+    let (sender, receiver) = oneshot::channel();
+    let effects = harness.send_event(
+        &mut storage,
+        StorageRequest::CompactStorage {
+            responder: Responder::create(sender),
+        }
+        .into(),
+    );
+    let mut events = Vec::new();
+    for effect in effects {
+        events.extend(harness.runtime.block_on(effect));
+    }
+    let progress = harness
+        .runtime
+        .block_on(receiver)
+        .expect("should respond")
+        .expect("should start compaction");
+    assert!(progress.estimated_bytes > 0);
+    assert!(harness
+        .send_request(&mut storage, |responder| {
+            StorageRequest::CompactStorage { responder }.into()
+        })
+        .is_err());
+
+    // Write to storage after the copy was taken.
+    let deploy = Box::new(Deploy::random(&mut harness.rng));
+    assert!(put_deploy(&mut harness, &mut storage, deploy.clone()));
+
+    assert_eq!(events.len(), 1);
+    for event in events {
+        assert!(matches!(event, Event::CompactionCopied(Ok(()))));
+        let effects = harness.send_event(&mut storage, event);
+        assert!(effects.is_empty());
+    }
+
+    let root = harness.tmp.path().join("storage");
+    assert!(!root.join(COMPACTED_DB_FILENAME).exists());
+    assert!(root.join(STORAGE_DB_FILENAME).exists());
+    for block in &blocks {
+        assert_eq!(
+            get_block(&mut harness, &mut storage, *block.hash()).as_ref(),
+            Some(&**block)
+        );
+    }
+    assert_eq!(
+        get_deploys(&mut harness, &mut storage, smallvec![*deploy.id()]),
+        vec![Some(*deploy.clone())]
+    );
+    let stats = harness
+        .send_request(&mut storage, |responder| {
+            StorageRequest::GetStats { responder }.into()
+        })
+        .expect("should read storage stats");
+    assert!(stats.compaction.is_none());
+
+    // The swapped in file is picked up on restart.
+    drop(storage);
+    let mut storage = storage_fixture(&harness);
+    assert_eq!(
+        get_deploys(&mut harness, &mut storage, smallvec![*deploy.id()]),
+        vec![Some(*deploy)]
+    );
+}
+
+#[test]
+fn should_roll_back_torn_chain_tail_on_startup() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // Store blocks 0-4 as a proper chain, followed by a block 5 which does not link to block 4, and
+    // a child of that block.
+    let mut blocks = vec![Block::random_with_specifics(
+        &mut harness.rng,
+        EraId(0),
+        0,
+        false,
+    )];
+    for _ in 1..5 {
+        let child = random_child(&mut harness.rng, blocks.last().unwrap());
+        blocks.push(child);
+    }
+    for block in &blocks {
+        assert!(put_block(
+            &mut harness,
+            &mut storage,
+            Box::new(block.clone())
+        ));
+    }
+    let stats_before_tail = get_all_era_storage_stats(&mut harness, &mut storage);
+    let torn_block = *random_block_at_height(&mut harness.rng, 5);
+    let torn_child = random_child(&mut harness.rng, &torn_block);
+    for block in &[torn_block.clone(), torn_child.clone()] {
+        assert!(put_block(
+            &mut harness,
+            &mut storage,
+            Box::new(block.clone())
+        ));
+    }
+    let secret_key = SecretKey::random(&mut harness.rng);
+    let public_key = PublicKey::from(&secret_key);
+    let signature = crypto::sign(
+        torn_block.hash().inner(),
+        &secret_key,
+        &public_key,
+        &mut harness.rng,
+    );
+    let mut signatures = BlockSignatures::new(*torn_block.hash(), torn_block.header().era_id());
+    signatures.insert_proof(public_key, signature);
+    assert!(put_block_signatures(&mut harness, &mut storage, signatures));
+    drop(storage);
+
+    // On restart, the torn tail is removed along with its signatures and its share of the per-era
+    // statistics, and the highest block is #4.
+    let mut storage = storage_fixture_with_verification_depth(&harness, 10);
+    assert_eq!(
+        get_highest_block(&mut harness, &mut storage).as_ref(),
+        blocks.last()
+    );
+    assert!(get_block(&mut harness, &mut storage, *torn_block.hash()).is_none());
+    assert!(get_block(&mut harness, &mut storage, *torn_child.hash()).is_none());
+    assert!(get_block_signatures(&mut harness, &mut storage, *torn_block.hash()).is_none());
+    assert_eq!(
+        get_all_era_storage_stats(&mut harness, &mut storage),
+        stats_before_tail
+    );
+
+    // The correct block 5 can be stored again, and survives another restart.
+    let block_5 = random_child(&mut harness.rng, blocks.last().unwrap());
+    assert!(put_block(
+        &mut harness,
+        &mut storage,
+        Box::new(block_5.clone())
+    ));
+    drop(storage);
+    let mut storage = storage_fixture_with_verification_depth(&harness, 10);
+    assert_eq!(get_highest_block(&mut harness, &mut storage), Some(block_5));
+}
+
+/// Releases whose storage formats are kept as fixtures, oldest first.
+const FIXTURE_RELEASES: &[&str] = &["0_9_0", "1_0_0", "1_1_0"];
+
+/// Loads a hex encoded fixture of a stored value as written by the given release.
+fn load_fixture(release: &str, name: &str) -> Vec<u8> {
+    let path = RESOURCES_PATH
+        .join("test/storage")
+        .join(release)
+        .join(format!("{}.hex", name));
+    let hex_string: String = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("could not read fixture {}: {}", path.display(), err))
+        .split_whitespace()
+        .collect();
+    hex::decode(hex_string).expect("invalid fixture")
+}
+
+#[test]
+fn should_read_stored_values_of_all_releases() {
+    let latest = FIXTURE_RELEASES.last().unwrap();
+
+    for release in FIXTURE_RELEASES {
+        let raw = load_fixture(release, "block_header");
+        let header: BlockHeader = format::decode(&raw).expect("could not decode block header");
+        assert_eq!(*header.parent_hash(), BlockHash::new(Digest::from([1; 32])));
+        assert_eq!(*header.state_root_hash(), Digest::from([2; 32]));
+        assert_eq!(*header.body_hash(), Digest::from([3; 32]));
+        assert!(header.random_bit());
+        assert_eq!(header.accumulated_seed(), Digest::from([4; 32]));
+        assert!(header.era_end().is_none());
+        assert_eq!(header.timestamp(), Timestamp::from(5));
+        assert_eq!(header.era_id(), EraId(6));
+        assert_eq!(header.height(), 7);
+        assert_eq!(
+            format::encode(&header).unwrap(),
+            load_fixture(latest, "block_header")
+        );
+
+        let raw = load_fixture(release, "block_body");
+        let body: BlockBody = format::decode(&raw).expect("could not decode block body");
+        let expected_body = BlockBody::new(
+            PublicKey::System,
+            vec![DeployHash::new(Digest::from([5; 32]))],
+            vec![],
+            RewardedSignatures::default(),
+        );
+        assert_eq!(body, expected_body);
+        assert_eq!(
+            format::encode(&body).unwrap(),
+            load_fixture(latest, "block_body")
+        );
+
+        let raw = load_fixture(release, "block_signatures");
+        let signatures: BlockSignatures =
+            format::decode(&raw).expect("could not decode block signatures");
+        assert_eq!(signatures.block_hash, BlockHash::new(Digest::from([1; 32])));
+        assert_eq!(signatures.era_id, EraId(6));
+        assert!(signatures.proofs.is_empty());
+        assert_eq!(
+            format::encode(&signatures).unwrap(),
+            load_fixture(latest, "block_signatures")
+        );
+
+        let raw = load_fixture(release, "deploy_metadata");
+        let metadata: DeployMetadata =
+            format::decode(&raw).expect("could not decode deploy metadata");
+        assert_eq!(metadata, DeployMetadata::default());
+        assert_eq!(
+            format::encode(&metadata).unwrap(),
+            load_fixture(latest, "deploy_metadata")
+        );
+
+        let raw = load_fixture(release, "transfers");
+        let transfers: Vec<Transfer> = format::decode(&raw).expect("could not decode transfers");
+        assert!(transfers.is_empty());
+        assert_eq!(
+            format::encode(&transfers).unwrap(),
+            load_fixture(latest, "transfers")
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! Webhook notifier.
+//!
+//! Posts JSON notifications of selected node events to operator-defined HTTP endpoints, for
+//! integrators who would rather be pushed events than keep a connection to the event stream open.
+//! Each endpoint chooses the kinds of events it is notified of and may add static metadata to the
+//! notifications, which can also be signed with a per-endpoint secret using HMAC-SHA256.
+//!
+//! Failed deliveries are retried with exponential backoff, up to a configured number of attempts.
+//! Notifications are neither persisted nor ordered: pending ones are lost on restart, and a retried
+//! notification may arrive after later ones.  The `X-Casper-Delivery` header stays the same across
+//! retries of a notification, so receivers can deduplicate them.
+
+mod config;
+mod metrics;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use http::uri::InvalidUri;
+use hyper::{
+    client::HttpConnector,
+    header::{HeaderValue, CONTENT_TYPE},
+    Body, Client, Request, Uri,
+};
+use openssl::{error::ErrorStack, hash::MessageDigest, pkey::PKey, sign::Signer};
+use prometheus::Registry;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use casper_types::{ExecutionResult, PublicKey};
+
+use crate::{
+    components::{consensus::EraId, Component},
+    effect::{EffectBuilder, EffectExt, Effects},
+    types::{Block, BlockHash, DeployHash, TimeDiff, Timestamp},
+    NodeRng,
+};
+pub use config::{Config, EndpointConfig, WebhookEventKind};
+use metrics::WebhookNotifierMetrics;
+
+/// The header naming the kind of event a notification is about.
+const EVENT_HEADER: &str = "X-Casper-Event";
+/// The header holding the unique ID of a notification, unchanged across retries.
+const DELIVERY_HEADER: &str = "X-Casper-Delivery";
+/// The header holding the HMAC-SHA256 signature of the body, if the endpoint has a secret.
+const SIGNATURE_HEADER: &str = "X-Casper-Signature";
+
+/// Error creating the webhook notifier.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// An endpoint URL could not be parsed.
+    #[error("invalid webhook url {url}: {error}")]
+    InvalidUrl {
+        /// The configured URL.
+        url: String,
+        /// The underlying error.
+        error: InvalidUri,
+    },
+    /// An endpoint URL is not a plain `http` URL with a host.
+    #[error("unsupported webhook url {0}: only http urls are supported")]
+    UnsupportedUrl(String),
+    /// Metrics-related error.
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
+/// The data of a notification, depending on the kind of event.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+enum Notification {
+    DeployAccepted {
+        deploy_hash: DeployHash,
+        account: PublicKey,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+    },
+    DeployProcessed {
+        deploy_hash: DeployHash,
+        block_hash: BlockHash,
+        execution_result: Box<ExecutionResult>,
+    },
+    BlockAdded {
+        block_hash: BlockHash,
+        height: u64,
+        era_id: EraId,
+        timestamp: Timestamp,
+        proposer: PublicKey,
+    },
+    ValidatorEjected {
+        public_key: PublicKey,
+        era_id: EraId,
+        block_hash: BlockHash,
+    },
+}
+
+impl Notification {
+    fn kind(&self) -> WebhookEventKind {
+        match self {
+            Notification::DeployAccepted { .. } => WebhookEventKind::DeployAccepted,
+            Notification::DeployProcessed { .. } => WebhookEventKind::DeployProcessed,
+            Notification::BlockAdded { .. } => WebhookEventKind::BlockAdded,
+            Notification::ValidatorEjected { .. } => WebhookEventKind::ValidatorEjected,
+        }
+    }
+}
+
+/// The body of a notification, as posted.
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: WebhookEventKind,
+    created_at: Timestamp,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: &'a BTreeMap<String, String>,
+    data: &'a Notification,
+}
+
+/// A notification on its way to a single endpoint.
+#[derive(Debug)]
+pub struct Delivery {
+    /// The index of the endpoint in the configuration.
+    endpoint: usize,
+    id: Uuid,
+    kind: WebhookEventKind,
+    body: Vec<u8>,
+    /// The value of the signature header, if the endpoint has a secret.
+    signature: Option<String>,
+    /// The number of the current attempt, starting at 1.
+    attempt: u32,
+}
+
+/// Webhook notifier events.
+#[derive(Debug)]
+pub enum Event {
+    /// A new deploy was accepted.
+    DeployAccepted {
+        deploy_hash: DeployHash,
+        account: PublicKey,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+    },
+    /// A deploy was executed as part of a block.
+    DeployProcessed {
+        deploy_hash: DeployHash,
+        block_hash: BlockHash,
+        execution_result: Box<ExecutionResult>,
+    },
+    /// A block was added to the linear chain.
+    BlockAdded(Box<Block>),
+    /// An attempt to deliver a notification finished.
+    DeliveryAttempted {
+        delivery: Box<Delivery>,
+        result: Result<(), String>,
+    },
+    /// The backoff before retrying a delivery elapsed.
+    RetryDue(Box<Delivery>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::DeployAccepted { deploy_hash, .. } => {
+                write!(formatter, "deploy accepted {}", deploy_hash)
+            }
+            Event::DeployProcessed { deploy_hash, .. } => {
+                write!(formatter, "deploy processed {}", deploy_hash)
+            }
+            Event::BlockAdded(block) => write!(formatter, "block added {}", block.hash()),
+            Event::DeliveryAttempted { delivery, result } => write!(
+                formatter,
+                "attempt {} to deliver webhook notification {}: {:?}",
+                delivery.attempt, delivery.id, result
+            ),
+            Event::RetryDue(delivery) => {
+                write!(
+                    formatter,
+                    "retry due for webhook notification {}",
+                    delivery.id
+                )
+            }
+        }
+    }
+}
+
+/// An endpoint notifications are posted to.
+#[derive(DataSize, Debug)]
+struct Endpoint {
+    #[data_size(skip)]
+    uri: Uri,
+    /// The kinds of events the endpoint is notified of, all if empty.
+    events: BTreeSet<WebhookEventKind>,
+    secret: Option<Vec<u8>>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl Endpoint {
+    fn new(config: EndpointConfig) -> Result<Self, Error> {
+        let uri: Uri = config.url.parse().map_err(|error| Error::InvalidUrl {
+            url: config.url.clone(),
+            error,
+        })?;
+        if uri.scheme_str() != Some("http") || uri.host().is_none() {
+            return Err(Error::UnsupportedUrl(config.url));
+        }
+        Ok(Endpoint {
+            uri,
+            events: config.events.into_iter().collect(),
+            secret: config.secret.map(String::into_bytes),
+            metadata: config.metadata,
+        })
+    }
+
+    /// Returns whether the endpoint is notified of events of the given kind.
+    fn wants(&self, kind: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Webhook notifier component.
+#[derive(DataSize, Debug)]
+pub(crate) struct WebhookNotifier {
+    endpoints: Vec<Endpoint>,
+    request_timeout: TimeDiff,
+    max_attempts: u32,
+    initial_backoff: TimeDiff,
+    max_backoff: TimeDiff,
+    max_pending_deliveries: usize,
+    /// The number of notifications being delivered or awaiting a retry.
+    pending_deliveries: usize,
+    #[data_size(skip)]
+    client: Client<HttpConnector>,
+    #[data_size(skip)]
+    metrics: WebhookNotifierMetrics,
+}
+
+impl WebhookNotifier {
+    /// Creates a new webhook notifier, checking the configured endpoints.
+    pub(crate) fn new(config: Config, registry: &Registry) -> Result<Self, Error> {
+        let endpoints = config
+            .endpoints
+            .into_iter()
+            .map(Endpoint::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(WebhookNotifier {
+            endpoints,
+            request_timeout: config.request_timeout,
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: config.initial_backoff,
+            max_backoff: config.max_backoff,
+            max_pending_deliveries: config.max_pending_deliveries,
+            pending_deliveries: 0,
+            client: Client::new(),
+            metrics: WebhookNotifierMetrics::new(registry)?,
+        })
+    }
+
+    /// Returns whether any endpoints are configured.
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.endpoints.is_empty()
+    }
+
+    /// Starts delivering the notification to every endpoint interested in it.
+    fn notify(&mut self, notification: Notification) -> Effects<Event> {
+        let kind = notification.kind();
+        let created_at = Timestamp::now();
+        let mut deliveries = Vec::new();
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            if !endpoint.wants(kind) {
+                continue;
+            }
+            if self.pending_deliveries + deliveries.len() >= self.max_pending_deliveries {
+                self.metrics.dropped.inc();
+                warn!(
+                    ?kind,
+                    url = %endpoint.uri,
+                    "too many pending webhook notifications, dropping notification"
+                );
+                continue;
+            }
+            let payload = Payload {
+                event: kind,
+                created_at,
+                metadata: &endpoint.metadata,
+                data: &notification,
+            };
+            let body = match serde_json::to_vec(&payload) {
+                Ok(body) => body,
+                Err(error) => {
+                    error!(%error, ?kind, "failed to serialize webhook notification");
+                    continue;
+                }
+            };
+            let signature = match &endpoint.secret {
+                Some(secret) => match sign(secret, &body) {
+                    Ok(signature) => Some(format!("sha256={}", signature)),
+                    Err(error) => {
+                        error!(%error, ?kind, "failed to sign webhook notification");
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            deliveries.push(Delivery {
+                endpoint: index,
+                id: Uuid::new_v4(),
+                kind,
+                body,
+                signature,
+                attempt: 1,
+            });
+        }
+
+        self.pending_deliveries += deliveries.len();
+        self.metrics.pending.set(self.pending_deliveries as i64);
+        deliveries
+            .into_iter()
+            .flat_map(|delivery| self.attempt(delivery))
+            .collect()
+    }
+
+    /// Posts the notification to its endpoint.
+    fn attempt(&self, delivery: Delivery) -> Effects<Event> {
+        let mut builder = Request::post(self.endpoints[delivery.endpoint].uri.clone())
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .header(EVENT_HEADER, delivery.kind.to_string())
+            .header(DELIVERY_HEADER, delivery.id.to_string());
+        if let Some(signature) = &delivery.signature {
+            builder = builder.header(SIGNATURE_HEADER, signature.as_str());
+        }
+        let request = builder
+            .body(Body::from(delivery.body.clone()))
+            .map_err(|error| error.to_string());
+        let client = self.client.clone();
+        let timeout = Duration::from(self.request_timeout);
+        async move {
+            let result = match request {
+                Ok(request) => post(client, request, timeout).await,
+                Err(error) => Err(error),
+            };
+            (delivery, result)
+        }
+        .event(|(delivery, result)| Event::DeliveryAttempted {
+            delivery: Box::new(delivery),
+            result,
+        })
+    }
+
+    /// Marks a notification as no longer pending.
+    fn finish(&mut self) {
+        self.pending_deliveries = self.pending_deliveries.saturating_sub(1);
+        self.metrics.pending.set(self.pending_deliveries as i64);
+    }
+
+    fn handle_delivery_attempted<REv: Send>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        delivery: Box<Delivery>,
+        result: Result<(), String>,
+    ) -> Effects<Event> {
+        let url = &self.endpoints[delivery.endpoint].uri;
+        match result {
+            Ok(()) => {
+                debug!(%url, id = %delivery.id, "delivered webhook notification");
+                self.metrics.delivered.inc();
+                self.finish();
+                Effects::new()
+            }
+            Err(error) if delivery.attempt < self.max_attempts => {
+                let delay = backoff(self.initial_backoff, self.max_backoff, delivery.attempt);
+                debug!(
+                    %error, %url, id = %delivery.id, attempt = delivery.attempt, %delay,
+                    "failed to deliver webhook notification, retrying"
+                );
+                self.metrics.retried.inc();
+                effect_builder
+                    .set_timeout(delay.into())
+                    .event(move |_| Event::RetryDue(delivery))
+            }
+            Err(error) => {
+                warn!(
+                    %error, %url, id = %delivery.id, attempts = delivery.attempt,
+                    "failed to deliver webhook notification, giving up"
+                );
+                self.metrics.failed.inc();
+                self.finish();
+                Effects::new()
+            }
+        }
+    }
+}
+
+/// Posts `request`, succeeding if the endpoint responds with a success status in time.
+async fn post(
+    client: Client<HttpConnector>,
+    request: Request<Body>,
+    timeout: Duration,
+) -> Result<(), String> {
+    match tokio::time::timeout(timeout, client.request(request)).await {
+        Err(_) => Err("request timed out".to_string()),
+        Ok(Err(error)) => Err(error.to_string()),
+        Ok(Ok(response)) if response.status().is_success() => Ok(()),
+        Ok(Ok(response)) => Err(format!("endpoint responded with {}", response.status())),
+    }
+}
+
+/// Returns the hex-encoded HMAC-SHA256 of `body` with the given secret.
+fn sign(secret: &[u8], body: &[u8]) -> Result<String, ErrorStack> {
+    let key = PKey::hmac(secret)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    Ok(hex::encode(signer.sign_to_vec()?))
+}
+
+/// Returns the delay before retrying after the given failed attempt: the initial backoff, doubled
+/// with each further attempt, but never more than the maximum.
+fn backoff(initial: TimeDiff, max: TimeDiff, attempt: u32) -> TimeDiff {
+    let factor = 1u64
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    TimeDiff::from(initial.millis().saturating_mul(factor).min(max.millis()))
+}
+
+impl<REv: Send> Component<REv> for WebhookNotifier {
+    type Event = Event;
+    type ConstructionError = Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::DeployAccepted {
+                deploy_hash,
+                account,
+                timestamp,
+                ttl,
+            } => self.notify(Notification::DeployAccepted {
+                deploy_hash,
+                account,
+                timestamp,
+                ttl,
+            }),
+            Event::DeployProcessed {
+                deploy_hash,
+                block_hash,
+                execution_result,
+            } => self.notify(Notification::DeployProcessed {
+                deploy_hash,
+                block_hash,
+                execution_result,
+            }),
+            Event::BlockAdded(block) => {
+                let block_hash = *block.hash();
+                let header = block.header();
+                let mut effects = self.notify(Notification::BlockAdded {
+                    block_hash,
+                    height: header.height(),
+                    era_id: header.era_id(),
+                    timestamp: header.timestamp(),
+                    proposer: *block.body().proposer(),
+                });
+                let inactive_validators = header
+                    .era_end()
+                    .map(|era_report| era_report.inactive_validators.clone())
+                    .unwrap_or_default();
+                for public_key in inactive_validators {
+                    effects.extend(self.notify(Notification::ValidatorEjected {
+                        public_key,
+                        era_id: header.era_id(),
+                        block_hash,
+                    }));
+                }
+                effects
+            }
+            Event::DeliveryAttempted { delivery, result } => {
+                self.handle_delivery_attempted(effect_builder, delivery, result)
+            }
+            Event::RetryDue(mut delivery) => {
+                delivery.attempt += 1;
+                self.attempt(*delivery)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sign_body_with_hmac_sha256() {
+        // Test case 2 of RFC 4231.
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn should_double_backoff_up_to_maximum() {
+        let initial = TimeDiff::from(1_000);
+        let max = TimeDiff::from(5_000);
+        assert_eq!(backoff(initial, max, 1), TimeDiff::from(1_000));
+        assert_eq!(backoff(initial, max, 2), TimeDiff::from(2_000));
+        assert_eq!(backoff(initial, max, 3), TimeDiff::from(4_000));
+        assert_eq!(backoff(initial, max, 4), max);
+        assert_eq!(backoff(initial, max, 100), max);
+    }
+
+    #[test]
+    fn should_only_accept_http_urls() {
+        let endpoint = |url: &str| EndpointConfig {
+            url: url.to_string(),
+            events: vec![WebhookEventKind::BlockAdded],
+            secret: None,
+            metadata: BTreeMap::new(),
+        };
+        let http_endpoint = Endpoint::new(endpoint("http://localhost:8000/hook")).unwrap();
+        assert!(http_endpoint.wants(WebhookEventKind::BlockAdded));
+        assert!(!http_endpoint.wants(WebhookEventKind::DeployAccepted));
+        assert!(matches!(
+            Endpoint::new(endpoint("https://localhost/hook")),
+            Err(Error::UnsupportedUrl(_))
+        ));
+        assert!(matches!(
+            Endpoint::new(endpoint("/hook")),
+            Err(Error::UnsupportedUrl(_))
+        ));
+    }
+}
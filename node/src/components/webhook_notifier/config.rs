@@ -0,0 +1,120 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::{types::TimeDiff, utils::ConfigSection};
+
+const DEFAULT_REQUEST_TIMEOUT: &str = "10sec";
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_INITIAL_BACKOFF: &str = "1sec";
+const DEFAULT_MAX_BACKOFF: &str = "5min";
+const DEFAULT_MAX_PENDING_DELIVERIES: usize = 1_000;
+
+/// The kinds of events endpoints can be notified of.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// A new deploy was accepted by this node.
+    DeployAccepted,
+    /// A deploy was executed as part of a block.
+    DeployProcessed,
+    /// A block was added to the linear chain.
+    BlockAdded,
+    /// A validator was evicted at the end of an era for not taking part in consensus.
+    ValidatorEjected,
+}
+
+impl Display for WebhookEventKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookEventKind::DeployAccepted => write!(formatter, "deploy_accepted"),
+            WebhookEventKind::DeployProcessed => write!(formatter, "deploy_processed"),
+            WebhookEventKind::BlockAdded => write!(formatter, "block_added"),
+            WebhookEventKind::ValidatorEjected => write!(formatter, "validator_ejected"),
+        }
+    }
+}
+
+/// An endpoint to which notifications are posted.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct EndpointConfig {
+    /// The URL notifications are posted to.  Only `http` URLs are supported.
+    pub url: String,
+
+    /// The kinds of events the endpoint is notified of.  If empty, it is notified of all events.
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+
+    /// The secret with which the body of each notification is signed using HMAC-SHA256.
+    ///
+    /// If set, the hex-encoded signature is sent in the `X-Casper-Signature` header as
+    /// `sha256=<signature>`.
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Additional fields included verbatim in the `metadata` object of every notification.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Configuration options for posting notifications of node events to webhooks.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The time after which a notification request is abandoned and retried.
+    pub request_timeout: TimeDiff,
+
+    /// The number of times a notification is attempted before it is dropped.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry.  It doubles with each further retry.
+    pub initial_backoff: TimeDiff,
+
+    /// The maximum delay between two attempts.
+    pub max_backoff: TimeDiff,
+
+    /// The number of notifications not yet delivered, including those awaiting a retry, above
+    /// which new notifications are dropped.
+    pub max_pending_deliveries: usize,
+
+    /// The endpoints notified of events.  Webhooks are disabled if there are none.
+    #[serde(default)]
+    pub endpoints: Vec<EndpointConfig>,
+}
+
+impl Config {
+    /// Records the invalid values of this config in `section`.
+    pub(crate) fn validate(&self, section: &mut ConfigSection<'_>) {
+        section.at_least("request_timeout", self.request_timeout, TimeDiff::from(1));
+        section.at_least("max_attempts", self.max_attempts, 1);
+        section.at_least("max_backoff", self.max_backoff, self.initial_backoff);
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            section.check(
+                &format!("endpoints[{}].url", index),
+                endpoint.url.starts_with("http://"),
+                &endpoint.url,
+                "an http:// URL",
+            );
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            request_timeout: DEFAULT_REQUEST_TIMEOUT.parse().unwrap(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF.parse().unwrap(),
+            max_backoff: DEFAULT_MAX_BACKOFF.parse().unwrap(),
+            max_pending_deliveries: DEFAULT_MAX_PENDING_DELIVERIES,
+            endpoints: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use prometheus::{IntCounter, IntGauge, Registry};
+
+use crate::unregister_metric;
+
+/// Metrics for the webhook notifier.
+#[derive(Debug)]
+pub(super) struct WebhookNotifierMetrics {
+    /// Number of notifications delivered successfully.
+    pub(super) delivered: IntCounter,
+    /// Number of failed delivery attempts which were retried.
+    pub(super) retried: IntCounter,
+    /// Number of notifications dropped after exhausting all attempts.
+    pub(super) failed: IntCounter,
+    /// Number of notifications dropped because too many were pending.
+    pub(super) dropped: IntCounter,
+    /// Number of notifications not yet delivered.
+    pub(super) pending: IntGauge,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl WebhookNotifierMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let delivered = IntCounter::new(
+            "webhook_delivered_notifications",
+            "number of webhook notifications delivered successfully",
+        )?;
+        let retried = IntCounter::new(
+            "webhook_retried_attempts",
+            "number of failed webhook delivery attempts which were retried",
+        )?;
+        let failed = IntCounter::new(
+            "webhook_failed_notifications",
+            "number of webhook notifications dropped after exhausting all attempts",
+        )?;
+        let dropped = IntCounter::new(
+            "webhook_dropped_notifications",
+            "number of webhook notifications dropped because too many were pending",
+        )?;
+        let pending = IntGauge::new(
+            "webhook_pending_notifications",
+            "number of webhook notifications not yet delivered",
+        )?;
+        registry.register(Box::new(delivered.clone()))?;
+        registry.register(Box::new(retried.clone()))?;
+        registry.register(Box::new(failed.clone()))?;
+        registry.register(Box::new(dropped.clone()))?;
+        registry.register(Box::new(pending.clone()))?;
+
+        Ok(WebhookNotifierMetrics {
+            delivered,
+            retried,
+            failed,
+            dropped,
+            pending,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for WebhookNotifierMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.delivered);
+        unregister_metric!(self.registry, self.retried);
+        unregister_metric!(self.registry, self.failed);
+        unregister_metric!(self.registry, self.dropped);
+        unregister_metric!(self.registry, self.pending);
+    }
+}
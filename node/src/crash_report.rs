@@ -0,0 +1,151 @@
+//! Reporting of fatal errors to process supervisors.
+//!
+//! A fatal error ends the process with the [`ExitCode`] of its kind.  If `node.crash_report_path`
+//! is configured, a JSON [`CrashReport`] holding the error and the most recently dispatched events
+//! is written to that path first, replacing any previous report.
+//!
+//! The reporter is installed by the binary on startup.  Without it, e.g. in tests, fatal errors
+//! panic instead of exiting.
+
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::Mutex,
+};
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::{
+    reactor::QueueKind,
+    types::{ExitCode, NodeConfig, Timestamp},
+    VERSION_STRING,
+};
+
+/// The number of most recently dispatched events kept for the crash report.
+const RECENT_EVENT_COUNT: usize = 100;
+
+/// The maximum length in bytes of a recorded event's description.
+const MAX_EVENT_LENGTH: usize = 1024;
+
+static REPORTER: OnceCell<CrashReporter> = OnceCell::new();
+
+/// A dispatched event, as recorded for the crash report.
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordedEvent {
+    /// The time the event was dispatched.
+    pub timestamp: Timestamp,
+    /// The queue the event was taken from.
+    pub queue: QueueKind,
+    /// The event's description, truncated to 1024 bytes.
+    pub event: String,
+}
+
+/// The contents of a crash report file.
+#[derive(Debug, Serialize)]
+pub struct CrashReport {
+    /// The exit code of the process.
+    pub exit_code: u8,
+    /// The kind of fatal error, named after its exit code.
+    pub kind: ExitCode,
+    /// The source file which reported the error.
+    pub file: String,
+    /// The line of the source file which reported the error.
+    pub line: u32,
+    /// The error message.
+    pub message: String,
+    /// The time of the error.
+    pub timestamp: Timestamp,
+    /// The version of the node.
+    pub version: String,
+    /// The most recently dispatched events, oldest first.
+    pub recent_events: Vec<RecordedEvent>,
+}
+
+#[derive(Debug)]
+struct CrashReporter {
+    path: Option<PathBuf>,
+    recent_events: Mutex<VecDeque<RecordedEvent>>,
+}
+
+/// Installs the crash reporter configured in `config`, resolving a relative report path against
+/// `root`.
+///
+/// Has no effect if a reporter has been installed before.
+pub fn install(root: &Path, config: &NodeConfig) {
+    let path = config
+        .crash_report_path
+        .as_ref()
+        .map(|path| root.join(path));
+    if let Some(path) = &path {
+        info!(path = %path.display(), "crash reports enabled");
+    }
+    let _ = REPORTER.set(CrashReporter {
+        path,
+        recent_events: Mutex::new(VecDeque::with_capacity(RECENT_EVENT_COUNT)),
+    });
+}
+
+/// Records an event about to be dispatched, if crash reports are enabled.
+pub(crate) fn record_event(queue: QueueKind, event: &str) {
+    let reporter = match REPORTER.get() {
+        Some(reporter) if reporter.path.is_some() => reporter,
+        _ => return,
+    };
+    let mut end = event.len().min(MAX_EVENT_LENGTH);
+    while !event.is_char_boundary(end) {
+        end -= 1;
+    }
+    let recorded = RecordedEvent {
+        timestamp: Timestamp::now(),
+        queue,
+        event: event[..end].to_string(),
+    };
+    let mut recent_events = match reporter.recent_events.lock() {
+        Ok(recent_events) => recent_events,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if recent_events.len() >= RECENT_EVENT_COUNT {
+        recent_events.pop_front();
+    }
+    recent_events.push_back(recorded);
+}
+
+/// Handles a fatal error, writing the crash report and exiting with `exit_code`.
+///
+/// Panics instead if no reporter is installed.
+pub(crate) fn fatal_error(exit_code: ExitCode, file: &str, line: u32, message: String) -> ! {
+    let reporter = match REPORTER.get() {
+        Some(reporter) => reporter,
+        None => panic!("fatal error [{}:{}]: {}", file, line, message),
+    };
+    error!(?exit_code, %file, %line, %message, "fatal error");
+
+    if let Some(path) = &reporter.path {
+        let recent_events = match reporter.recent_events.lock() {
+            Ok(recent_events) => recent_events.iter().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().iter().cloned().collect(),
+        };
+        let report = CrashReport {
+            exit_code: exit_code as u8,
+            kind: exit_code,
+            file: file.to_string(),
+            line,
+            message,
+            timestamp: Timestamp::now(),
+            version: VERSION_STRING.clone(),
+            recent_events,
+        };
+        let written = serde_json::to_vec_pretty(&report)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+        if let Err(error) = written {
+            error!(%error, path = %path.display(), "failed to write crash report");
+        }
+    }
+
+    process::exit(exit_code as i32)
+}
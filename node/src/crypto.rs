@@ -4,7 +4,9 @@ mod asymmetric_key;
 mod asymmetric_key_ext;
 mod error;
 pub mod hash;
+mod signature_verifier;
 
 pub use asymmetric_key::{generate_ed25519_keypair, sign, verify};
 pub use asymmetric_key_ext::AsymmetricKeyExt;
 pub use error::{Error, Result};
+pub use signature_verifier::SignatureVerifier;
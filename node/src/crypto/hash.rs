@@ -158,6 +158,36 @@ pub fn hash<T: AsRef<[u8]>>(data: T) -> Digest {
     Digest(result)
 }
 
+/// Returns the root hash of the binary Merkle tree with the given leaves.
+///
+/// Each inner node is the hash of its two children's concatenated hashes.  A node without a sibling
+/// is moved up to the next level unchanged.  The root of an empty tree is the hash of no data.
+pub fn hash_merkle_tree<I>(leaves: I) -> Digest
+where
+    I: IntoIterator<Item = Digest>,
+{
+    let mut level: Vec<Digest> = leaves.into_iter().collect();
+    if level.is_empty() {
+        return hash(&[0u8; 0]);
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|nodes| match nodes {
+                [left, right] => {
+                    let mut data = [0; 2 * Digest::LENGTH];
+                    data[..Digest::LENGTH].copy_from_slice(&left.0);
+                    data[Digest::LENGTH..].copy_from_slice(&right.0);
+                    hash(&data[..])
+                }
+                [single] => *single,
+                _ => unreachable!("chunks have one or two nodes"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
 impl From<Digest> for Blake2bHash {
     fn from(digest: Digest) -> Self {
         let digest_bytes = digest.to_array();
@@ -200,6 +230,27 @@ mod test {
         }
     }
 
+    #[test]
+    fn merkle_root_should_pair_nodes_level_by_level() {
+        let leaves: Vec<Digest> = (0u8..3).map(|byte| hash(&[byte])).collect();
+        let pair = |left: Digest, right: Digest| hash([left.to_vec(), right.to_vec()].concat());
+
+        assert_eq!(hash_merkle_tree(iter::empty()), hash(&[0u8; 0]));
+        assert_eq!(hash_merkle_tree(leaves[..1].to_vec()), leaves[0]);
+        assert_eq!(
+            hash_merkle_tree(leaves[..2].to_vec()),
+            pair(leaves[0], leaves[1])
+        );
+        assert_eq!(
+            hash_merkle_tree(leaves.clone()),
+            pair(pair(leaves[0], leaves[1]), leaves[2])
+        );
+        assert_ne!(
+            hash_merkle_tree(leaves.iter().rev().copied()),
+            hash_merkle_tree(leaves)
+        );
+    }
+
     #[test]
     fn from_valid_hex_should_succeed() {
         for char in "abcdefABCDEF0123456789".chars() {
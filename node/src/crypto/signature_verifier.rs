@@ -0,0 +1,123 @@
+//! Parallel verification of batches of signatures.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use tokio::task;
+
+use super::Result;
+
+/// Verifies batches of signatures, spreading the work over a pool of worker threads.
+///
+/// Results are always returned in the order of the items verified, so that every error can be
+/// attributed to the signature which caused it.
+#[derive(Clone, Default)]
+pub struct SignatureVerifier {
+    /// The dedicated worker threads, or `None` to use rayon's global pool with one thread per CPU.
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl SignatureVerifier {
+    /// Creates a verifier using `threads` dedicated worker threads, or one thread per CPU on the
+    /// global pool if `threads` is zero.
+    pub fn new(threads: usize) -> std::result::Result<Self, ThreadPoolBuildError> {
+        let pool = if threads == 0 {
+            None
+        } else {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|index| format!("signature-verifier-{}", index))
+                .build()?;
+            Some(Arc::new(pool))
+        };
+        Ok(SignatureVerifier { pool })
+    }
+
+    /// Verifies all `items` using `verify`, blocking the calling thread until all are done.
+    pub fn verify_batch<T, F>(&self, items: &[T], verify: F) -> Vec<Result<()>>
+    where
+        T: Sync,
+        F: Fn(&T) -> Result<()> + Sync + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(|| items.par_iter().map(&verify).collect()),
+            None => items.par_iter().map(&verify).collect(),
+        }
+    }
+
+    /// Verifies all `items` using `verify` without blocking the calling thread, returning each item
+    /// along with its result.
+    pub async fn verify_batch_async<T, F>(&self, items: Vec<T>, verify: F) -> Vec<(T, Result<()>)>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Sync + Send + 'static,
+    {
+        let verifier = self.clone();
+        task::spawn_blocking(move || {
+            let results = verifier.verify_batch(&items, verify);
+            items.into_iter().zip(results).collect()
+        })
+        .await
+        .unwrap_or_else(|error| {
+            // The items were moved into the failed task, so none of them can be returned.
+            panic!("signature verification task failed: {}", error)
+        })
+    }
+}
+
+impl Debug for SignatureVerifier {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match &self.pool {
+            Some(pool) => write!(
+                formatter,
+                "SignatureVerifier({} threads)",
+                pool.current_num_threads()
+            ),
+            None => write!(formatter, "SignatureVerifier(global pool)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{PublicKey, Signature};
+
+    use super::*;
+    use crate::{crypto, testing::TestRng};
+
+    #[test]
+    fn should_attribute_errors_to_invalid_signatures() {
+        let mut rng = TestRng::new();
+        let mut items: Vec<(Vec<u8>, Signature, PublicKey)> = (0..10u8)
+            .map(|index| {
+                let (secret_key, public_key) = crypto::generate_ed25519_keypair();
+                let message = vec![index; 32];
+                let signature = crypto::sign(&message, &secret_key, &public_key, &mut rng);
+                (message, signature, public_key)
+            })
+            .collect();
+        // Swap the keys of two signatures, and corrupt the message of a third.
+        let public_key_2 = items[2].2;
+        items[2].2 = items[7].2;
+        items[7].2 = public_key_2;
+        items[4].0[0] ^= 1;
+
+        for verifier in &[
+            SignatureVerifier::default(),
+            SignatureVerifier::new(3).unwrap(),
+        ] {
+            let results = verifier.verify_batch(&items, |(message, signature, public_key)| {
+                crypto::verify(message, signature, public_key)
+            });
+            let invalid: Vec<_> = results
+                .iter()
+                .enumerate()
+                .filter_map(|(index, result)| result.as_ref().err().map(|_| index))
+                .collect();
+            assert_eq!(invalid, vec![2, 4, 7]);
+        }
+    }
+}
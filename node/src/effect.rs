@@ -59,20 +59,26 @@
 //! some point. Failing to do so will result in a resource leak.
 
 pub mod announcements;
+pub(crate) mod dropped_responses;
 pub mod requests;
+pub(crate) mod time_source;
 
 use std::{
     any::type_name,
     borrow::Cow,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     future::Future,
+    net::SocketAddr,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
     sync::Arc,
-    time::{Duration, Instant},
+    time::Duration,
 };
 
 use datasize::DataSize;
 use futures::{channel::oneshot, future::BoxFuture, FutureExt};
+use semver::Version;
 use serde::{de::DeserializeOwned, Serialize};
 use smallvec::{smallvec, SmallVec};
 use tracing::{error, warn};
@@ -80,55 +86,74 @@ use tracing::{error, warn};
 use casper_execution_engine::{
     core::engine_state::{
         self,
+        cost_table::CostTable,
         era_validators::GetEraValidatorsError,
         execute_request::ExecuteRequest,
         execution_result::ExecutionResults,
         genesis::GenesisResult,
         step::{StepRequest, StepResult},
         upgrade::{UpgradeConfig, UpgradeResult},
-        BalanceRequest, BalanceResult, GetBidsRequest, GetBidsResult, QueryRequest, QueryResult,
-        MAX_PAYMENT,
+        BalanceBatchRequest, BalanceBatchResult, BalanceRequest, BalanceResult, GetBidsResult,
+        GetKeysByPrefixRequest, GetKeysByPrefixResult, QueryBatchRequest, QueryBatchResult,
+        QueryRequest, QueryResult, MAX_PAYMENT,
     },
     shared::{
-        additive_map::AdditiveMap, newtypes::Blake2bHash, stored_value::StoredValue,
-        transform::Transform,
+        additive_map::AdditiveMap, key_prefix::KeyPrefix, newtypes::Blake2bHash,
+        stored_value::StoredValue, transform::Transform,
     },
     storage::{global_state::CommitResult, protocol_data::ProtocolData, trie::Trie},
 };
 use casper_types::{
-    system::auction::EraValidators, ExecutionResult, Key, ProtocolVersion, PublicKey, Transfer,
+    account::AccountHash,
+    system::auction::{Bids, EraValidators, ValidatorWeights},
+    ExecutionResult, Key, KeyTag, ProtocolVersion, PublicKey, Transfer, U512,
 };
 
 use crate::{
     components::{
+        block_proposer::DeployStatus,
+        block_validator::BlockValidationResult,
         chainspec_loader::NextUpgrade,
         consensus::{BlockContext, EraId},
-        contract_runtime::EraValidatorsRequest,
+        contract_runtime::{
+            EraValidatorsRequest, PurgeEstimate, ShadowExecutionError, ShadowExecutionRequest,
+        },
         deploy_acceptor,
-        fetcher::FetchResult,
-        small_network::GossipedAddress,
+        deploy_watcher::NotIncludedStatus,
+        disk_watchdog::{DiskSpace, DiskSpaceStatus},
+        fetcher::{FetchFailure, FetchResult},
+        small_network::{BlocklistEntry, GossipedAddress, NetworkTopology, PublicEndpoint},
+        storage::{CompactionProgress, EraStorageStats, StorageStats},
     },
+    crash_report,
     crypto::hash::Digest,
     effect::requests::LinearChainRequest,
     reactor::{EventQueueHandle, QueueKind},
+    rpcs::{chain::BlockIdentifier, debug::DumpableComponent},
     types::{
-        Block, BlockByHeight, BlockHash, BlockHeader, BlockLike, BlockSignatures, Chainspec,
-        ChainspecInfo, Deploy, DeployHash, DeployHeader, DeployMetadata, FinalitySignature,
-        FinalizedBlock, Item, ProtoBlock, TimeDiff, Timestamp,
+        Approval, Block, BlockByHeight, BlockExecutionResults, BlockFinalityProgress, BlockHash,
+        BlockHeader, BlockLike, BlockSignatures, BlockValidationError, BlockWithDeploys,
+        BlockWithSignatures, Chainspec, ChainspecInfo, Deploy, DeployHash, DeployHeader,
+        DeployHeaderById, DeployMetadata, ExitCode, FinalitySignature, FinalizedBlock, Item,
+        NodeId, ProtoBlock, RewardedSignatures, SyncStatus, TimeDiff, Timestamp,
     },
     utils::Source,
 };
 use announcements::{
-    BlockExecutorAnnouncement, ChainspecLoaderAnnouncement, ConsensusAnnouncement,
-    DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement, NetworkAnnouncement,
-    RpcServerAnnouncement,
+    BlockExecutorAnnouncement, ChainspecLoaderAnnouncement, ClockMonitorAnnouncement,
+    ConsensusAnnouncement, DeployAcceptorAnnouncement, DeployWatcherAnnouncement,
+    DiskWatchdogAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
+    LinearChainSyncAnnouncement, NetworkAnnouncement, RpcServerAnnouncement,
 };
 use requests::{
     BlockExecutorRequest, BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest,
-    ConsensusRequest, ContractRuntimeRequest, FetcherRequest, MetricsRequest, NetworkInfoRequest,
-    NetworkRequest, ProtoBlockRequest, StateStoreRequest, StorageRequest,
+    ConsensusRequest, ContractRuntimeRequest, DumpStateRequest, FetcherRequest, MetricsRequest,
+    NetworkInfoRequest, NetworkRequest, ProtoBlockRequest, StateStoreRequest, StorageRequest,
 };
 
+/// Number of bid keys requested from the contract runtime at a time when reading all bids.
+const BID_KEYS_PAGE_SIZE: usize = 100;
+
 /// A pinned, boxed future that produces one or more events.
 pub type Effect<Ev> = BoxFuture<'static, Multiple<Ev>>;
 
@@ -146,13 +171,22 @@ pub type Multiple<T> = SmallVec<[T; 2]>;
 /// A responder satisfying a request.
 #[must_use]
 #[derive(DataSize)]
-pub struct Responder<T>(Option<oneshot::Sender<T>>);
+pub struct Responder<T> {
+    /// Sender through which the response is returned.
+    sender: Option<oneshot::Sender<T>>,
+    /// Short name of the type of request this responder belongs to.
+    #[data_size(skip)]
+    request_type: &'static str,
+}
 
 impl<T: 'static + Send> Responder<T> {
-    /// Creates a new `Responder`.
+    /// Creates a new `Responder` for a request of type `request_type`.
     #[inline]
-    fn new(sender: oneshot::Sender<T>) -> Self {
-        Responder(Some(sender))
+    fn new(sender: oneshot::Sender<T>, request_type: &'static str) -> Self {
+        Responder {
+            sender: Some(sender),
+            request_type,
+        }
     }
 
     /// Helper method for tests.
@@ -162,16 +196,16 @@ impl<T: 'static + Send> Responder<T> {
     #[cfg(test)]
     #[inline]
     pub(crate) fn create(sender: oneshot::Sender<T>) -> Self {
-        Responder::new(sender)
+        Responder::new(sender, "test")
     }
 }
 
 impl<T> Responder<T> {
     /// Send `data` to the origin of the request.
     pub async fn respond(mut self, data: T) {
-        if let Some(sender) = self.0.take() {
+        if let Some(sender) = self.sender.take() {
             if sender.send(data).is_err() {
-                error!("could not send response to request down oneshot channel");
+                dropped_responses::record(self.request_type, type_name::<T>());
             }
         } else {
             error!("tried to send a value down a responder channel, but it was already used");
@@ -181,19 +215,29 @@ impl<T> Responder<T> {
 
 impl<T> Debug for Responder<T> {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
-        write!(formatter, "Responder<{}>", type_name::<T>(),)
+        write!(
+            formatter,
+            "Responder<{}> for {}",
+            type_name::<T>(),
+            self.request_type
+        )
     }
 }
 
 impl<T> Display for Responder<T> {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
-        write!(formatter, "responder({})", type_name::<T>(),)
+        write!(
+            formatter,
+            "responder({}) for {}",
+            type_name::<T>(),
+            self.request_type
+        )
     }
 }
 
 impl<T> Drop for Responder<T> {
     fn drop(&mut self) {
-        if self.0.is_some() {
+        if self.sender.is_some() {
             // This is usually a very serious error, as another component will now be stuck.
             error!(
                 "{} dropped without being responded to --- \
@@ -395,7 +439,7 @@ impl<REv> EffectBuilder<REv> {
         let (sender, receiver) = oneshot::channel();
 
         // Create response function.
-        let responder = Responder::new(sender);
+        let responder = Responder::new(sender, dropped_responses::short_type_name::<Q>());
 
         // Now inject the request event into the event loop.
         let request_event = f(responder).into();
@@ -425,21 +469,31 @@ impl<REv> EffectBuilder<REv> {
 
     /// Reports a fatal error.  Normally called via the `crate::fatal!()` macro.
     ///
-    /// Usually causes the node to cease operations quickly and exit/crash.
+    /// Causes the node to exit with `exit_code` immediately, after writing a crash report if
+    /// configured.  See the `crash_report` module.
     //
     // Note: This function is implemented manually without `async` sugar because the `Send`
     // inference seems to not work in all cases otherwise.
-    pub fn fatal(self, file: &str, line: u32, msg: String) -> impl Future<Output = ()> + Send {
-        panic!("fatal error [{}:{}]: {}", file, line, msg);
+    pub fn fatal(
+        self,
+        file: &str,
+        line: u32,
+        exit_code: ExitCode,
+        msg: String,
+    ) -> impl Future<Output = ()> + Send {
+        crash_report::fatal_error(exit_code, file, line, msg);
         #[allow(unreachable_code)]
         async {} // The compiler will complain about an incorrect return value otherwise.
     }
 
+    /// Returns the current time of the reactor's time source.
+    pub(crate) fn now(self) -> Timestamp {
+        self.0.time_source().now()
+    }
+
     /// Sets a timeout.
-    pub(crate) async fn set_timeout(self, timeout: Duration) -> Duration {
-        let then = Instant::now();
-        tokio::time::delay_for(timeout).await;
-        Instant::now() - then
+    pub(crate) fn set_timeout(self, timeout: Duration) -> impl Future<Output = Duration> + Send {
+        self.0.time_source().sleep(timeout)
     }
 
     /// Retrieve a snapshot of the nodes current metrics formatted as string.
@@ -456,6 +510,26 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Retrieves a redacted snapshot of the internal state of `component`.
+    ///
+    /// Returns `None` if the component is not running in the current reactor.
+    pub(crate) async fn dump_component_state(
+        self,
+        component: DumpableComponent,
+    ) -> Option<serde_json::Value>
+    where
+        REv: From<DumpStateRequest>,
+    {
+        self.make_request(
+            |responder| DumpStateRequest {
+                component,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Retrieves block at `height` from the Linear Chain component.
     pub(crate) async fn get_block_at_height_local<I>(self, height: u64) -> Option<Block>
     where
@@ -468,6 +542,19 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Retrieves the recent blocks which don't have strict finality yet from the Linear Chain
+    /// component.
+    pub(crate) async fn get_blocks_below_strict_finality<I>(self) -> Vec<BlockFinalityProgress>
+    where
+        REv: From<LinearChainRequest<I>>,
+    {
+        self.make_request(
+            LinearChainRequest::BlocksBelowStrictFinality,
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Sends a network message.
     ///
     /// The message is queued in "fire-and-forget" fashion, there is no guarantee that the peer
@@ -546,6 +633,72 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the connections of this node.
+    pub(crate) async fn network_topology<I>(self) -> NetworkTopology
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::<I>::GetTopology { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Gets the blocklisted network peers.
+    pub async fn network_blocklist<I>(self) -> Vec<BlocklistEntry>
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::<I>::GetBlocklist { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Removes the peer at `address`, or all peers if `None`, from the blocklist, returning the
+    /// number of removed entries.
+    pub async fn clear_network_blocklist<I>(self, address: Option<SocketAddr>) -> usize
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::<I>::ClearBlocklist { address, responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Disconnects from a misbehaving peer and blocklists it.
+    pub(crate) async fn block_peer<I>(self, peer: I)
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::<I>::BlockPeer { peer, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets our public network endpoint, or `None` if the network component doesn't track it.
+    pub(crate) async fn network_public_endpoint<I>(self) -> Option<PublicEndpoint>
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::<I>::GetPublicEndpoint { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Announces that a network message has been received.
     pub(crate) async fn announce_message_received<I, P>(self, sender: I, payload: P)
     where
@@ -603,11 +756,27 @@ impl<REv> EffectBuilder<REv> {
             .await;
     }
 
+    /// Announces that a gossiper has received a new item along with its full payload.
+    pub(crate) async fn announce_item_body_received_via_gossip<T: Item>(
+        self,
+        item: Box<T>,
+        sender: NodeId,
+    ) where
+        REv: From<GossiperAnnouncement<T>>,
+    {
+        self.0
+            .schedule(
+                GossiperAnnouncement::NewItemBody { item, sender },
+                QueueKind::Regular,
+            )
+            .await;
+    }
+
     /// Announces that the HTTP API server has received a deploy.
     pub(crate) async fn announce_deploy_received(
         self,
         deploy: Box<Deploy>,
-        responder: Option<Responder<Result<(), deploy_acceptor::Error>>>,
+        responder: Option<Responder<Result<deploy_acceptor::Acceptance, deploy_acceptor::Error>>>,
     ) where
         REv: From<RpcServerAnnouncement>,
     {
@@ -619,6 +788,27 @@ impl<REv> EffectBuilder<REv> {
             .await;
     }
 
+    /// Announces that the HTTP API server has received further approvals for a deploy.
+    pub(crate) async fn announce_deploy_approvals_received(
+        self,
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+        responder: Responder<Result<deploy_acceptor::Acceptance, deploy_acceptor::Error>>,
+    ) where
+        REv: From<RpcServerAnnouncement>,
+    {
+        self.0
+            .schedule(
+                RpcServerAnnouncement::DeployApprovalsReceived {
+                    deploy_hash,
+                    approvals,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+    }
+
     /// Announces that a deploy not previously stored has now been accepted and stored.
     pub(crate) fn announce_new_deploy_accepted<I>(
         self,
@@ -634,6 +824,24 @@ impl<REv> EffectBuilder<REv> {
         )
     }
 
+    /// Announces that further approvals have been merged into an already stored deploy.
+    pub(crate) fn announce_deploy_approvals_updated<I>(
+        self,
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+    ) -> impl Future<Output = ()>
+    where
+        REv: From<DeployAcceptorAnnouncement<I>>,
+    {
+        self.0.schedule(
+            DeployAcceptorAnnouncement::ApprovalsUpdated {
+                deploy_hash,
+                approvals,
+            },
+            QueueKind::Regular,
+        )
+    }
+
     /// Announces that an invalid deploy has been received.
     pub(crate) fn announce_invalid_deploy<I>(
         self,
@@ -654,6 +862,7 @@ impl<REv> EffectBuilder<REv> {
         self,
         block: Block,
         execution_results: HashMap<DeployHash, (DeployHeader, ExecutionResult)>,
+        execution_duration: TimeDiff,
     ) where
         REv: From<BlockExecutorAnnouncement>,
     {
@@ -662,6 +871,7 @@ impl<REv> EffectBuilder<REv> {
                 BlockExecutorAnnouncement::LinearChainBlock {
                     block,
                     execution_results,
+                    execution_duration,
                 },
                 QueueKind::Regular,
             )
@@ -681,6 +891,67 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that the drift of the local clock exceeds the limit for proposing blocks.
+    pub(crate) async fn announce_excessive_clock_drift(self, drift_ms: i64)
+    where
+        REv: From<ClockMonitorAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ClockMonitorAnnouncement::ExcessiveDrift { drift_ms },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that the drift of the local clock is back within the limit for proposing blocks.
+    pub(crate) async fn announce_clock_drift_recovered(self, drift_ms: i64)
+    where
+        REv: From<ClockMonitorAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ClockMonitorAnnouncement::DriftRecovered { drift_ms },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that a deploy was not included in any block in time.
+    pub(crate) async fn announce_deploy_not_included(
+        self,
+        deploy_hash: DeployHash,
+        status: NotIncludedStatus,
+    ) where
+        REv: From<DeployWatcherAnnouncement>,
+    {
+        self.0
+            .schedule(
+                DeployWatcherAnnouncement::DeployNotIncluded {
+                    deploy_hash,
+                    status,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that the status of the free space on the storage volume changed.
+    pub(crate) async fn announce_disk_space_status(
+        self,
+        status: DiskSpaceStatus,
+        disk_space: DiskSpace,
+    ) where
+        REv: From<DiskWatchdogAnnouncement>,
+    {
+        self.0
+            .schedule(
+                DiskWatchdogAnnouncement::DiskSpaceStatusChanged { status, disk_space },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Puts the given block into the linear block store.
     pub(crate) async fn put_block_to_storage(self, block: Box<Block>) -> bool
     where
@@ -708,6 +979,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the header of the requested block from the linear block store.
+    pub(crate) async fn get_block_header_from_storage(
+        self,
+        block_hash: BlockHash,
+    ) -> Option<BlockHeader>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockHeader {
+                block_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested signatures for a given block hash.
     pub(crate) async fn get_signatures_from_storage(
         self,
@@ -741,6 +1030,48 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets statistics of the databases held by storage.
+    pub(crate) async fn get_storage_stats(self) -> Option<StorageStats>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetStats { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the counts and sizes of the items held by storage for each era in `eras` with any
+    /// items stored.
+    pub(crate) async fn get_era_storage_stats(
+        self,
+        eras: RangeInclusive<EraId>,
+    ) -> Vec<(EraId, EraStorageStats)>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetEraStorageStats { eras, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Starts compacting the database file held by storage in the background.
+    ///
+    /// Returns the initial progress of the compaction, or the reason why it wasn't started.
+    pub(crate) async fn compact_storage(self) -> Result<CompactionProgress, String>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::CompactStorage { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block's transfers from storage.
     pub(crate) async fn get_block_transfers_from_storage(
         self,
@@ -759,6 +1090,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the execution results of the requested block's deploys from storage.
+    pub(crate) async fn get_block_execution_results_from_storage(
+        self,
+        block_hash: BlockHash,
+    ) -> Option<BlockExecutionResults>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockExecutionResults {
+                block_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests the block at the given height.
     pub(crate) async fn get_block_at_height_from_storage(self, height: u64) -> Option<Block>
     where
@@ -854,6 +1203,25 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Asynchronously returns the descendant trie keys of `trie_key` which are missing from the
+    /// trie store.
+    pub(crate) async fn missing_trie_keys(
+        self,
+        trie_key: Blake2bHash,
+    ) -> Result<Vec<Blake2bHash>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::MissingTrieKeys {
+                trie_key,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Puts the given deploy into the deploy store.
     pub(crate) async fn put_deploy_to_storage(self, deploy: Box<Deploy>) -> bool
     where
@@ -866,6 +1234,27 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Adds the given approvals to a deploy in the deploy store, returning all of its approvals if
+    /// any were new.
+    pub(crate) async fn merge_deploy_approvals_in_storage(
+        self,
+        deploy_hash: DeployHash,
+        approvals: Vec<Approval>,
+    ) -> Option<Vec<Approval>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::MergeDeployApprovals {
+                deploy_hash,
+                approvals,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested deploys from the deploy store.
     pub(crate) async fn get_deploys_from_storage(
         self,
@@ -884,6 +1273,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the headers of the requested deploys from the deploy store.
+    pub(crate) async fn get_deploy_headers_from_storage(
+        self,
+        deploy_hashes: Multiple<DeployHash>,
+    ) -> Vec<Option<DeployHeader>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployHeaders {
+                deploy_hashes: deploy_hashes.to_vec(),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Stores the given execution results for the deploys in the given block in the linear block
     /// store.
     pub(crate) async fn put_execution_results_to_storage(
@@ -922,7 +1329,28 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Gets the requested block and its associated metadata.
+    /// Gets the hashes of the deploys created by `account` from the account index, ordered by
+    /// their timestamp and restricted to the positions in `range`.
+    pub(crate) async fn get_deploy_hashes_by_account_from_storage(
+        self,
+        account: AccountHash,
+        range: Range<u64>,
+    ) -> Vec<DeployHash>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployHashesByAccount {
+                account,
+                range,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the requested block and its associated metadata.
     pub(crate) async fn get_block_at_height_with_metadata_from_storage(
         self,
         block_height: u64,
@@ -940,6 +1368,67 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the requested block, or the highest block if `maybe_id` is `None`, along with its
+    /// finality signatures and its era's validator weights.
+    pub(crate) async fn get_block_with_signatures_from_storage(
+        self,
+        maybe_id: Option<BlockIdentifier>,
+    ) -> Option<BlockWithSignatures>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockWithSignatures {
+                maybe_id,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the blocks at the given heights found in storage, by ascending height, along with their
+    /// finality signatures and their era's validator weights.
+    pub(crate) async fn get_blocks_with_signatures_from_storage(
+        self,
+        heights: Range<u64>,
+    ) -> Vec<BlockWithSignatures>
+    where
+        REv: From<StorageRequest>,
+    {
+        let mut blocks = Vec::new();
+        for height in heights {
+            let block_id = Some(BlockIdentifier::Height(height));
+            if let Some(block) = self.get_block_with_signatures_from_storage(block_id).await {
+                blocks.push(block);
+            }
+        }
+        blocks
+    }
+
+    /// Gets up to `count` consecutive blocks starting at `start_height` from storage, along with
+    /// their finality signatures and, if `with_deploys` is set, their deploys.
+    pub(crate) async fn get_block_range_from_storage(
+        self,
+        start_height: u64,
+        count: u64,
+        with_deploys: bool,
+    ) -> Vec<BlockWithDeploys>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockRange {
+                start_height,
+                count,
+                with_deploys,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block by hash with its associated metadata.
     pub(crate) async fn get_block_with_metadata_from_storage(
         self,
@@ -977,7 +1466,7 @@ impl<REv> EffectBuilder<REv> {
         self,
         deploy_hash: DeployHash,
         peer: I,
-    ) -> Option<FetchResult<Deploy, I>>
+    ) -> Result<FetchResult<Deploy, I>, FetchFailure>
     where
         REv: From<FetcherRequest<I, Deploy>>,
         I: Send + 'static,
@@ -993,12 +1482,33 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the header of the requested deploy using the `DeployHeaderFetcher`.
+    pub(crate) async fn fetch_deploy_header<I>(
+        self,
+        deploy_hash: DeployHash,
+        peer: I,
+    ) -> Result<FetchResult<DeployHeaderById, I>, FetchFailure>
+    where
+        REv: From<FetcherRequest<I, DeployHeaderById>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: deploy_hash,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block using the `BlockFetcher`
     pub(crate) async fn fetch_block<I>(
         self,
         block_hash: BlockHash,
         peer: I,
-    ) -> Option<FetchResult<Block, I>>
+    ) -> Result<FetchResult<Block, I>, FetchFailure>
     where
         REv: From<FetcherRequest<I, Block>>,
         I: Send + 'static,
@@ -1014,12 +1524,55 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the header of the requested block using the `BlockHeaderFetcher`.
+    pub(crate) async fn fetch_block_header<I>(
+        self,
+        block_hash: BlockHash,
+        peer: I,
+    ) -> Result<FetchResult<BlockHeader, I>, FetchFailure>
+    where
+        REv: From<FetcherRequest<I, BlockHeader>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: block_hash,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the execution results of the requested block using the
+    /// `BlockExecutionResultsFetcher`.
+    pub(crate) async fn fetch_block_execution_results<I>(
+        self,
+        block_hash: BlockHash,
+        peer: I,
+    ) -> Result<FetchResult<BlockExecutionResults, I>, FetchFailure>
+    where
+        REv: From<FetcherRequest<I, BlockExecutionResults>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: block_hash,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests a linear chain block at `block_height`.
     pub(crate) async fn fetch_block_by_height<I>(
         self,
         block_height: u64,
         peer: I,
-    ) -> Option<FetchResult<BlockByHeight, I>>
+    ) -> Result<FetchResult<BlockByHeight, I>, FetchFailure>
     where
         REv: From<FetcherRequest<I, BlockByHeight>>,
         I: Send + 'static,
@@ -1040,6 +1593,7 @@ impl<REv> EffectBuilder<REv> {
         self,
         block_context: BlockContext,
         past_deploys: HashSet<DeployHash>,
+        past_rewarded_signatures: Vec<RewardedSignatures>,
         next_finalized: u64,
         random_bit: bool,
     ) -> (ProtoBlock, BlockContext)
@@ -1055,6 +1609,7 @@ impl<REv> EffectBuilder<REv> {
                         next_finalized,
                         responder,
                         random_bit,
+                        past_rewarded_signatures,
                     })
                 },
                 QueueKind::Regular,
@@ -1063,6 +1618,24 @@ impl<REv> EffectBuilder<REv> {
         (proto_block, block_context)
     }
 
+    /// Asks the block proposer what it knows about the given deploy.
+    pub(crate) async fn get_deploy_status_from_proposer(
+        self,
+        deploy_hash: DeployHash,
+    ) -> DeployStatus
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::GetDeployStatus {
+                deploy_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Passes a finalized proto-block to the block executor component to execute it.
     pub(crate) async fn execute_block(self, finalized_block: FinalizedBlock)
     where
@@ -1076,6 +1649,15 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Checks whether the block executor's queue of blocks awaiting execution is full.
+    pub(crate) async fn is_execution_queue_full(self) -> bool
+    where
+        REv: From<BlockExecutorRequest>,
+    {
+        self.make_request(BlockExecutorRequest::IsQueueFull, QueueKind::Regular)
+            .await
+    }
+
     /// Checks whether the deploys included in the block exist on the network. This includes
     /// the block's timestamp, in order that it be checked against the timestamp of the deploys
     /// within the block.
@@ -1084,7 +1666,7 @@ impl<REv> EffectBuilder<REv> {
         sender: I,
         block: T,
         block_timestamp: Timestamp,
-    ) -> (bool, T)
+    ) -> BlockValidationResult<T, I>
     where
         REv: From<BlockValidationRequest<T, I>>,
         T: BlockLike + Send + 'static,
@@ -1186,6 +1768,44 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that a peer sent a block which failed verification against its own header.
+    pub(crate) async fn announce_invalid_block_received<I>(
+        self,
+        peer: I,
+        block_hash: BlockHash,
+        error: BlockValidationError,
+    ) where
+        REv: From<LinearChainSyncAnnouncement<I>>,
+    {
+        self.0
+            .schedule(
+                LinearChainSyncAnnouncement::InvalidBlockReceived {
+                    peer,
+                    block_hash,
+                    error,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that a peer sent execution results which don't match the checksum registry of
+    /// their block.
+    pub(crate) async fn announce_invalid_execution_results_received<I>(
+        self,
+        peer: I,
+        block_hash: BlockHash,
+    ) where
+        REv: From<LinearChainSyncAnnouncement<I>>,
+    {
+        self.0
+            .schedule(
+                LinearChainSyncAnnouncement::InvalidExecutionResultsReceived { peer, block_hash },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Runs the genesis process on the contract runtime.
     pub(crate) async fn commit_genesis(
         self,
@@ -1231,6 +1851,31 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Gets the currently running chainspec from the chainspec loader.
+    pub(crate) async fn get_chainspec(self) -> Arc<Chainspec>
+    where
+        REv: From<ChainspecLoaderRequest> + Send,
+    {
+        self.make_request(ChainspecLoaderRequest::GetChainspec, QueueKind::Regular)
+            .await
+    }
+
+    /// Gets the contents of the chainspec file of the given protocol version from the chainspec
+    /// loader.
+    pub(crate) async fn get_chainspec_raw_bytes(self, protocol_version: Version) -> Option<Vec<u8>>
+    where
+        REv: From<ChainspecLoaderRequest> + Send,
+    {
+        self.make_request(
+            |responder| ChainspecLoaderRequest::GetChainspecRawBytes {
+                protocol_version,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Loads potentially previously stored state from storage.
     ///
     /// Key must be a unique key across the the application, as all keys share a common namespace.
@@ -1333,6 +1978,27 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests an estimate of the global state data stored under `keys`, which would be removed by
+    /// purging them.
+    pub(crate) async fn estimate_purge(
+        self,
+        state_root_hash: Blake2bHash,
+        keys: Vec<Key>,
+    ) -> Result<Option<PurgeEstimate>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::EstimatePurge {
+                state_root_hash,
+                keys,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests a query be executed on the Contract Runtime component.
     pub(crate) async fn query_global_state(
         self,
@@ -1351,6 +2017,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests several queries be executed on the Contract Runtime component.
+    pub(crate) async fn query_global_state_batch(
+        self,
+        query_batch_request: QueryBatchRequest,
+    ) -> Result<QueryBatchResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::QueryBatch {
+                query_batch_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     pub(crate) async fn is_verified_account(self, account_key: Key) -> Option<bool>
     where
         REv: From<ContractRuntimeRequest>,
@@ -1376,6 +2060,72 @@ impl<REv> EffectBuilder<REv> {
         None
     }
 
+    /// Reads the balances of the main purses of the given accounts at the state root hash of the
+    /// highest block.
+    ///
+    /// Accounts whose balance can't be read, e.g. because they don't exist, are omitted.  Returns
+    /// `None` if there is no block yet or the balances couldn't be read at all.
+    pub(crate) async fn get_account_balances(
+        self,
+        accounts: Vec<AccountHash>,
+    ) -> Option<HashMap<AccountHash, U512>>
+    where
+        REv: From<ContractRuntimeRequest>,
+        REv: From<StorageRequest>,
+    {
+        let block = self.get_highest_block_from_storage().await?;
+        let state_hash: Blake2bHash = (*block.state_root_hash()).into();
+        let keys = accounts.into_iter().map(Key::Account).collect();
+        let balance_batch_request = BalanceBatchRequest::new(state_hash, keys);
+        match self.get_balance_batch(balance_batch_request).await {
+            Ok(BalanceBatchResult::Success(balances)) => Some(
+                balances
+                    .into_iter()
+                    .filter_map(|(key, result)| match (key, result) {
+                        (Key::Account(account_hash), Ok(motes)) => Some((account_hash, motes)),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            Ok(BalanceBatchResult::RootNotFound) => {
+                warn!(%state_hash, "state root of highest block not found");
+                None
+            }
+            Err(error) => {
+                warn!(%error, "failed to read account balances");
+                None
+            }
+        }
+    }
+
+    /// Checks whether the given keys are authorized to deploy on behalf of the account.
+    ///
+    /// Returns `Some(true)` if their combined weight meets the account's deployment threshold and
+    /// `Some(false)` if it doesn't, or `None` if the account doesn't exist or any of the keys isn't
+    /// associated with it.
+    pub(crate) async fn check_deploy_authorization(
+        self,
+        account_key: Key,
+        authorization_keys: BTreeSet<AccountHash>,
+    ) -> Option<bool>
+    where
+        REv: From<ContractRuntimeRequest>,
+        REv: From<StorageRequest>,
+    {
+        let block = self.get_highest_block_from_storage().await?;
+        let state_hash = (*block.state_root_hash()).into();
+        let query_request = QueryRequest::new(state_hash, account_key, vec![]);
+        if let Ok(QueryResult::Success { value, .. }) = self.query_global_state(query_request).await
+        {
+            if let StoredValue::Account(account) = *value {
+                if account.can_authorize(&authorization_keys) {
+                    return Some(account.can_deploy_with(&authorization_keys));
+                }
+            }
+        }
+        None
+    }
+
     /// Requests a query be executed on the Contract Runtime component.
     pub(crate) async fn get_balance(
         self,
@@ -1394,6 +2144,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests the balances of several purses from the Contract Runtime component.
+    pub(crate) async fn get_balance_batch(
+        self,
+        balance_batch_request: BalanceBatchRequest,
+    ) -> Result<BalanceBatchResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetBalanceBatch {
+                balance_batch_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Returns `ProtocolData` by `ProtocolVersion`.
     ///
     /// This operation is read only.
@@ -1414,6 +2182,27 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Returns the gas costs charged for wasm opcodes, storage and host functions under
+    /// `protocol_version`.
+    ///
+    /// This operation is read only.
+    pub(crate) async fn get_cost_table(
+        self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<CostTable>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetCostTable {
+                protocol_version,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Returns a map of validators weights for all eras as known from `root_hash`.
     ///
     /// This operation is read only.
@@ -1431,17 +2220,75 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Requests a query be executed on the Contract Runtime component.
+    /// Requests a page of the keys in global state starting with a given prefix.
+    pub(crate) async fn get_keys_by_prefix(
+        self,
+        get_keys_request: GetKeysByPrefixRequest,
+    ) -> Result<GetKeysByPrefixResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetKeysByPrefix {
+                get_keys_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Reads all bids at the given state root hash, fetching the bid keys a page at a time.
     pub(crate) async fn get_bids(
         self,
-        get_bids_request: GetBidsRequest,
+        state_hash: Blake2bHash,
     ) -> Result<GetBidsResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        let mut bids = Bids::new();
+        let mut offset = 0;
+        loop {
+            let get_keys_request = GetKeysByPrefixRequest::new(
+                state_hash,
+                KeyPrefix::from(KeyTag::Bid),
+                offset,
+                BID_KEYS_PAGE_SIZE,
+            );
+            let (bid_keys, has_more) = match self.get_keys_by_prefix(get_keys_request).await? {
+                GetKeysByPrefixResult::RootNotFound => return Ok(GetBidsResult::RootNotFound),
+                GetKeysByPrefixResult::Success { keys, has_more } => (keys, has_more),
+            };
+            offset += bid_keys.len();
+
+            for bid_key in bid_keys {
+                let query = QueryRequest::new(state_hash, bid_key, vec![]);
+                if let QueryResult::Success { value, .. } = self.query_global_state(query).await? {
+                    if let StoredValue::Bid(bid) = *value {
+                        bids.insert(*bid.validator_public_key(), *bid);
+                    }
+                }
+            }
+
+            if !has_more {
+                return Ok(GetBidsResult::Success { bids });
+            }
+        }
+    }
+
+    /// Executes a block with the shadow engine, returning the resulting state root hash.
+    ///
+    /// Returns `None` if shadow execution is not enabled.
+    pub(crate) async fn shadow_execute_block(
+        self,
+        request: ShadowExecutionRequest,
+    ) -> Option<Result<Blake2bHash, ShadowExecutionError>>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
-            |responder| ContractRuntimeRequest::GetBids {
-                get_bids_request,
+            |responder| ContractRuntimeRequest::ShadowExecute {
+                request: Box::new(request),
                 responder,
             },
             QueueKind::Regular,
@@ -1491,8 +2338,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Get our public key from consensus, and if we're a validator, the next round length.
-    pub(crate) async fn consensus_status(self) -> (PublicKey, Option<TimeDiff>)
+    /// Gets the validators of an era and their weights from consensus, if the era is active.
+    pub(crate) async fn get_validator_weights_from_consensus(
+        self,
+        era_id: EraId,
+    ) -> Option<ValidatorWeights>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(
+            |responder| ConsensusRequest::ValidatorWeights(era_id, responder),
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Get our public key from consensus, if we're a validator the next round length, and whether
+    /// we are catching up with finalization.
+    pub(crate) async fn consensus_status(self) -> (PublicKey, Option<TimeDiff>, SyncStatus)
     where
         REv: From<ConsensusRequest>,
     {
@@ -1500,6 +2363,26 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Stages the secret key at `secret_key_path` to be used by consensus from `activation_era` on.
+    ///
+    /// Returns the new public key, or the reason why the key wasn't staged.
+    pub(crate) async fn stage_signing_key(
+        self,
+        secret_key_path: PathBuf,
+        activation_era: EraId,
+    ) -> Result<PublicKey, String>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(
+            |responder| {
+                ConsensusRequest::StageSigningKey(secret_key_path, activation_era, responder)
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Check if validator is bonded in the future era (`era_id`).
     /// This information is known only by the Contract Runtime since consensus component
     /// knows only about currently active eras.
@@ -1560,10 +2443,15 @@ impl<REv> EffectBuilder<REv> {
 /// Construct a fatal error effect.
 ///
 /// This macro is a convenient wrapper around `EffectBuilder::fatal` that inserts the `file!()` and
-/// `line!()` number automatically.
+/// `line!()` number automatically.  The second argument is the `ExitCode` classifying the error.
 #[macro_export]
 macro_rules! fatal {
-    ($effect_builder:expr, $($arg:tt)*) => {
-        $effect_builder.fatal(file!(), line!(), format_args!($($arg)*).to_string())
+    ($effect_builder:expr, $exit_code:expr, $($arg:tt)*) => {
+        $effect_builder.fatal(
+            file!(),
+            line!(),
+            $exit_code,
+            format_args!($($arg)*).to_string(),
+        )
     };
 }
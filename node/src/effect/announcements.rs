@@ -14,13 +14,17 @@ use casper_types::{ExecutionResult, PublicKey};
 
 use crate::{
     components::{
-        chainspec_loader::NextUpgrade, consensus::EraId, deploy_acceptor::Error,
+        chainspec_loader::NextUpgrade,
+        consensus::EraId,
+        deploy_acceptor::{Acceptance, Error},
+        deploy_watcher::NotIncludedStatus,
+        disk_watchdog::{DiskSpace, DiskSpaceStatus},
         small_network::GossipedAddress,
     },
     effect::Responder,
     types::{
-        Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalitySignature, FinalizedBlock,
-        Item, Timestamp,
+        Approval, Block, BlockHash, BlockValidationError, Deploy, DeployHash, DeployHeader,
+        FinalitySignature, FinalizedBlock, Item, NodeId, TimeDiff, Timestamp,
     },
     utils::Source,
 };
@@ -73,7 +77,16 @@ pub enum RpcServerAnnouncement {
         /// The received deploy.
         deploy: Box<Deploy>,
         /// A client responder in the case where a client submits a deploy.
-        responder: Option<Responder<Result<(), Error>>>,
+        responder: Option<Responder<Result<Acceptance, Error>>>,
+    },
+    /// Further approvals for a deploy awaiting approvals were received.
+    DeployApprovalsReceived {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The additional approvals.
+        approvals: Vec<Approval>,
+        /// Responder to call with the result.
+        responder: Responder<Result<Acceptance, Error>>,
     },
 }
 
@@ -83,6 +96,16 @@ impl Display for RpcServerAnnouncement {
             RpcServerAnnouncement::DeployReceived { deploy, .. } => {
                 write!(formatter, "api server received {}", deploy.id())
             }
+            RpcServerAnnouncement::DeployApprovalsReceived {
+                deploy_hash,
+                approvals,
+                ..
+            } => write!(
+                formatter,
+                "api server received {} approvals for {}",
+                approvals.len(),
+                deploy_hash
+            ),
         }
     }
 }
@@ -105,6 +128,14 @@ pub enum DeployAcceptorAnnouncement<I> {
         /// The source (peer or client) of the deploy.
         source: Source<I>,
     },
+
+    /// Further approvals have been merged into an already stored deploy.
+    ApprovalsUpdated {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// All approvals of the deploy, including the merged ones.
+        approvals: Vec<Approval>,
+    },
 }
 
 impl<I: Display> Display for DeployAcceptorAnnouncement<I> {
@@ -119,6 +150,15 @@ impl<I: Display> Display for DeployAcceptorAnnouncement<I> {
             DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
                 write!(formatter, "invalid deploy {} from {}", deploy.id(), source)
             }
+            DeployAcceptorAnnouncement::ApprovalsUpdated {
+                deploy_hash,
+                approvals,
+            } => write!(
+                formatter,
+                "updated approvals of {} to {}",
+                deploy_hash,
+                approvals.len()
+            ),
         }
     }
 }
@@ -183,6 +223,8 @@ pub enum BlockExecutorAnnouncement {
         block: Block,
         /// The results of executing the deploys in this block.
         execution_results: HashMap<DeployHash, (DeployHeader, ExecutionResult)>,
+        /// How long it took to execute the deploys and commit their effects.
+        execution_duration: TimeDiff,
     },
 }
 
@@ -201,12 +243,23 @@ impl Display for BlockExecutorAnnouncement {
 pub enum GossiperAnnouncement<T: Item> {
     /// A new item has been received, where the item's ID is the complete item.
     NewCompleteItem(T::Id),
+    /// A new item has been gossiped to us along with its full payload, and needs to be validated
+    /// before being gossiped onwards.
+    NewItemBody {
+        /// The gossiped item.
+        item: Box<T>,
+        /// The peer which gossiped it.
+        sender: NodeId,
+    },
 }
 
 impl<T: Item> Display for GossiperAnnouncement<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             GossiperAnnouncement::NewCompleteItem(item) => write!(f, "new complete item {}", item),
+            GossiperAnnouncement::NewItemBody { item, sender } => {
+                write!(f, "new item body {} from {}", item.id(), sender)
+            }
         }
     }
 }
@@ -238,6 +291,44 @@ impl Display for LinearChainAnnouncement {
     }
 }
 
+/// A linear chain synchronizer announcement.
+#[derive(Debug)]
+pub enum LinearChainSyncAnnouncement<I> {
+    /// A peer sent a block whose body or hash does not match its header.
+    InvalidBlockReceived {
+        /// The peer which sent the block.
+        peer: I,
+        /// The hash of the invalid block.
+        block_hash: BlockHash,
+        /// The reason the block is invalid.
+        error: BlockValidationError,
+    },
+    /// A peer sent execution results of a block which don't match the block's checksum registry.
+    InvalidExecutionResultsReceived {
+        /// The peer which sent the execution results.
+        peer: I,
+        /// The hash of the block.
+        block_hash: BlockHash,
+    },
+}
+
+impl<I: Display> Display for LinearChainSyncAnnouncement<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LinearChainSyncAnnouncement::InvalidBlockReceived {
+                peer, block_hash, ..
+            } => write!(f, "invalid block {} received from {}", block_hash, peer),
+            LinearChainSyncAnnouncement::InvalidExecutionResultsReceived { peer, block_hash } => {
+                write!(
+                    f,
+                    "invalid execution results of block {} received from {}",
+                    block_hash, peer
+                )
+            }
+        }
+    }
+}
+
 /// A chainspec loader announcement.
 #[derive(Debug, Serialize)]
 pub enum ChainspecLoaderAnnouncement {
@@ -254,3 +345,78 @@ impl Display for ChainspecLoaderAnnouncement {
         }
     }
 }
+
+/// A clock monitor announcement.
+#[derive(Debug, Serialize)]
+pub enum ClockMonitorAnnouncement {
+    /// The drift of the local clock exceeds the limit for proposing blocks.
+    ExcessiveDrift {
+        /// The measured drift, in milliseconds.
+        drift_ms: i64,
+    },
+    /// The drift of the local clock is back within the limit for proposing blocks.
+    DriftRecovered {
+        /// The measured drift, in milliseconds.
+        drift_ms: i64,
+    },
+}
+
+impl Display for ClockMonitorAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockMonitorAnnouncement::ExcessiveDrift { drift_ms } => {
+                write!(f, "excessive clock drift of {} ms", drift_ms)
+            }
+            ClockMonitorAnnouncement::DriftRecovered { drift_ms } => {
+                write!(f, "clock drift recovered to {} ms", drift_ms)
+            }
+        }
+    }
+}
+
+/// A deploy watcher announcement.
+#[derive(Debug, Serialize)]
+pub enum DeployWatcherAnnouncement {
+    /// A deploy was not included in any block by the time its TTL and the grace period elapsed.
+    DeployNotIncluded {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The last known status of the deploy.
+        status: NotIncludedStatus,
+    },
+}
+
+impl Display for DeployWatcherAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployWatcherAnnouncement::DeployNotIncluded {
+                deploy_hash,
+                status,
+            } => write!(f, "deploy {} not included: {}", deploy_hash, status),
+        }
+    }
+}
+
+/// A disk watchdog announcement.
+#[derive(Debug, Serialize)]
+pub enum DiskWatchdogAnnouncement {
+    /// The status of the free space on the storage volume changed.
+    DiskSpaceStatusChanged {
+        /// The new status.
+        status: DiskSpaceStatus,
+        /// The measured space on the storage volume.
+        disk_space: DiskSpace,
+    },
+}
+
+impl Display for DiskWatchdogAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskWatchdogAnnouncement::DiskSpaceStatusChanged { status, disk_space } => write!(
+                f,
+                "disk space status changed to {} with {} bytes available",
+                status, disk_space.available
+            ),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Accounting of responses which could not be delivered.
+//!
+//! A response cannot be delivered once the receiving end of its responder has been dropped, e.g.
+//! because an RPC client disconnected before its request was answered. Such responses are lost,
+//! but are counted per request type and logged, to make leaked or raced responders visible.
+
+use once_cell::sync::Lazy;
+use prometheus::{IntCounterVec, Opts, Registry};
+use tracing::debug;
+
+/// Label distinguishing the type of request a dropped response belonged to.
+const REQUEST_TYPE_LABEL: &str = "request_type";
+
+/// Number of dropped responses, shared by all responders.
+static DROPPED_RESPONSES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "dropped_responses",
+            "number of responses which could not be delivered because the requester went away",
+        ),
+        &[REQUEST_TYPE_LABEL],
+    )
+    .expect("dropped responses metric should be valid")
+});
+
+/// Records a response of type `response_type` to a request of type `request_type` as dropped.
+pub(super) fn record(request_type: &'static str, response_type: &'static str) {
+    DROPPED_RESPONSES.with_label_values(&[request_type]).inc();
+    debug!(
+        %request_type,
+        %response_type,
+        "dropped response, the receiving end of the responder is gone"
+    );
+}
+
+/// Registers the dropped responses counter on `registry`.
+pub(crate) fn register(registry: &Registry) -> Result<IntCounterVec, prometheus::Error> {
+    let dropped_responses = DROPPED_RESPONSES.clone();
+    registry.register(Box::new(dropped_responses.clone()))?;
+    Ok(dropped_responses)
+}
+
+/// Returns the short name of `T`, i.e. its type name without module path or generic parameters.
+pub(super) fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::oneshot;
+
+    use super::*;
+    use crate::effect::{requests::StorageRequest, Responder};
+
+    #[test]
+    fn should_shorten_type_names() {
+        assert_eq!(short_type_name::<StorageRequest>(), "StorageRequest");
+        assert_eq!(short_type_name::<Option<Vec<u8>>>(), "Option");
+        assert_eq!(short_type_name::<u64>(), "u64");
+    }
+
+    #[tokio::test]
+    async fn should_count_dropped_responses() {
+        let counter = DROPPED_RESPONSES.with_label_values(&["should_count_dropped_responses"]);
+        let before = counter.get();
+
+        let (sender, receiver) = oneshot::channel::<u64>();
+        drop(receiver);
+        Responder::new(sender, "should_count_dropped_responses")
+            .respond(1)
+            .await;
+        assert_eq!(counter.get(), before + 1);
+
+        let (sender, receiver) = oneshot::channel::<u64>();
+        Responder::new(sender, "should_count_dropped_responses")
+            .respond(2)
+            .await;
+        assert_eq!(receiver.await, Ok(2));
+        assert_eq!(counter.get(), before + 1);
+    }
+}
@@ -8,23 +8,31 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     mem,
+    net::SocketAddr,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
     sync::Arc,
 };
 
 use datasize::DataSize;
 use hex_fmt::HexFmt;
+use semver::Version;
 use serde::Serialize;
 use static_assertions::const_assert;
 
 use casper_execution_engine::{
     core::engine_state::{
         self,
-        balance::{BalanceRequest, BalanceResult},
+        balance::{BalanceBatchRequest, BalanceBatchResult, BalanceRequest, BalanceResult},
+        cost_table::CostTable,
         era_validators::GetEraValidatorsError,
         execute_request::ExecuteRequest,
         execution_result::ExecutionResults,
         genesis::GenesisResult,
-        query::{GetBidsRequest, GetBidsResult, QueryRequest, QueryResult},
+        query::{
+            GetBidsResult, GetKeysByPrefixRequest, GetKeysByPrefixResult, QueryBatchRequest,
+            QueryBatchResult, QueryRequest, QueryResult,
+        },
         step::{StepRequest, StepResult},
         upgrade::{UpgradeConfig, UpgradeResult},
     },
@@ -35,6 +43,7 @@ use casper_execution_engine::{
     storage::{global_state::CommitResult, protocol_data::ProtocolData, trie::Trie},
 };
 use casper_types::{
+    account::AccountHash,
     system::auction::{EraValidators, ValidatorWeights},
     ExecutionResult, Key, ProtocolVersion, PublicKey, Transfer, URef,
 };
@@ -42,17 +51,27 @@ use casper_types::{
 use super::Responder;
 use crate::{
     components::{
+        block_proposer::DeployStatus,
+        block_validator::BlockValidationResult,
         consensus::EraId,
-        contract_runtime::{EraValidatorsRequest, ValidatorWeightsByEraIdRequest},
-        deploy_acceptor::Error,
-        fetcher::FetchResult,
+        contract_runtime::{
+            EraValidatorsRequest, PurgeEstimate, ShadowExecutionError, ShadowExecutionRequest,
+            ValidatorWeightsByEraIdRequest,
+        },
+        deploy_acceptor::{Acceptance, Error},
+        fetcher::{FetchFailure, FetchResult},
+        rest_server::TopologyGraph,
+        small_network::{BlocklistEntry, NetworkTopology, PublicEndpoint},
+        storage::{CompactionProgress, EraStorageStats, StorageStats},
     },
     crypto::hash::Digest,
-    rpcs::chain::BlockIdentifier,
+    rpcs::{chain::BlockIdentifier, debug::DumpableComponent},
     types::{
-        Block as LinearBlock, Block, BlockHash, BlockHeader, BlockSignatures, Chainspec,
+        Approval, Block as LinearBlock, Block, BlockExecutionResults, BlockFinalityProgress,
+        BlockHash, BlockHeader, BlockSignatures, BlockWithDeploys, BlockWithSignatures, Chainspec,
         ChainspecInfo, Deploy, DeployHash, DeployHeader, DeployMetadata, FinalitySignature,
-        FinalizedBlock, Item, NodeId, ProtoBlock, StatusFeed, TimeDiff, Timestamp,
+        FinalizedBlock, Item, NodeId, ProtoBlock, RewardedSignatures, StatusFeed, SyncStatus,
+        TimeDiff, Timestamp,
     },
     utils::DisplayIter,
 };
@@ -187,6 +206,37 @@ pub enum NetworkInfoRequest<I> {
         // TODO - change the `String` field to a `libp2p::Multiaddr` once small_network is removed.
         responder: Responder<BTreeMap<I, String>>,
     },
+    /// Get the connections of this node, for export to topology dashboards.
+    GetTopology {
+        /// Responder to be called with the current connections.
+        responder: Responder<NetworkTopology>,
+    },
+    /// Get the blocklisted peers.
+    GetBlocklist {
+        /// Responder to be called with the blocklist entries.
+        responder: Responder<Vec<BlocklistEntry>>,
+    },
+    /// Remove peers from the blocklist.
+    ClearBlocklist {
+        /// The address of the peer to unblock, or `None` to clear the whole blocklist.
+        address: Option<SocketAddr>,
+        /// Responder to be called with the number of removed entries.
+        responder: Responder<usize>,
+    },
+    /// Disconnect from a misbehaving peer and blocklist it.
+    ///
+    /// Bootstrap peers and peers we have no outgoing connection to are only disconnected.
+    BlockPeer {
+        /// The peer to block.
+        peer: I,
+        /// Responder to be called once the peer has been blocked.
+        responder: Responder<()>,
+    },
+    /// Get our public endpoint and whether peers could reach it.
+    GetPublicEndpoint {
+        /// Responder to be called with the public endpoint, or `None` if it isn't tracked.
+        responder: Responder<Option<PublicEndpoint>>,
+    },
 }
 
 impl<I> Display for NetworkInfoRequest<I>
@@ -196,6 +246,21 @@ where
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             NetworkInfoRequest::GetPeers { responder: _ } => write!(formatter, "get peers"),
+            NetworkInfoRequest::GetTopology { responder: _ } => write!(formatter, "get topology"),
+            NetworkInfoRequest::GetBlocklist { responder: _ } => {
+                write!(formatter, "get blocklist")
+            }
+            NetworkInfoRequest::ClearBlocklist {
+                address: Some(address),
+                ..
+            } => write!(formatter, "clear blocklist entries for {}", address),
+            NetworkInfoRequest::ClearBlocklist { address: None, .. } => {
+                write!(formatter, "clear blocklist")
+            }
+            NetworkInfoRequest::BlockPeer { peer, .. } => write!(formatter, "block peer {}", peer),
+            NetworkInfoRequest::GetPublicEndpoint { .. } => {
+                write!(formatter, "get public endpoint")
+            }
         }
     }
 }
@@ -261,6 +326,14 @@ pub enum StorageRequest {
         /// local storage under the block_hash provided.
         responder: Responder<Option<Vec<Transfer>>>,
     },
+    /// Retrieve the execution results of all deploys in a block with given hash.
+    GetBlockExecutionResults {
+        /// Hash of block to get execution results of.
+        block_hash: BlockHash,
+        /// Responder to call with the result.  Returns `None` if the block or the execution result
+        /// of any of its deploys doesn't exist in local storage.
+        responder: Responder<Option<BlockExecutionResults>>,
+    },
     /// Store given deploy.
     PutDeploy {
         /// Deploy to store.
@@ -269,6 +342,16 @@ pub enum StorageRequest {
         /// attempt or false if it was previously stored.
         responder: Responder<bool>,
     },
+    /// Add approvals to an already stored deploy.
+    MergeDeployApprovals {
+        /// Hash of the deploy to add the approvals to.
+        deploy_hash: DeployHash,
+        /// The approvals to add.  Approvals already held are skipped.
+        approvals: Vec<Approval>,
+        /// Responder to call with the result.  Returns all approvals of the deploy if any were
+        /// added, or `None` if none were added or the deploy isn't stored.
+        responder: Responder<Option<Vec<Approval>>>,
+    },
     /// Retrieve deploys with given hashes.
     GetDeploys {
         /// Hashes of deploys to be retrieved.
@@ -298,6 +381,15 @@ pub enum StorageRequest {
         /// Responder to call when done storing.
         responder: Responder<()>,
     },
+    /// Retrieve the hashes of deploys created by the given account, ordered by their timestamp.
+    GetDeployHashesByAccount {
+        /// The account which created the deploys.
+        account: AccountHash,
+        /// The positions within the account's deploys to return, used for pagination.
+        range: Range<u64>,
+        /// Responder to call with the results.
+        responder: Responder<Vec<DeployHash>>,
+    },
     /// Retrieve deploy and its metadata.
     GetDeployAndMetadata {
         /// Hash of deploy to be retrieved.
@@ -324,6 +416,29 @@ pub enum StorageRequest {
         /// The responder to call the results with.
         responder: Responder<Option<(Block, BlockSignatures)>>,
     },
+    /// Get a block along with its finality signatures and its era's validator weights, all read in
+    /// a single transaction.
+    GetBlockWithSignatures {
+        /// The identifier of the block, or `None` for the highest block.
+        maybe_id: Option<BlockIdentifier>,
+        /// The responder to call with the result.
+        responder: Responder<Option<BlockWithSignatures>>,
+    },
+    /// Get up to `count` consecutive blocks starting at `start_height`, along with their finality
+    /// signatures and optionally their deploys, all read in a single transaction.
+    ///
+    /// Fewer blocks are returned if the range reaches a height which isn't stored, or if the
+    /// response would grow too large.
+    GetBlockRange {
+        /// The height of the first block.
+        start_height: u64,
+        /// The maximum number of blocks.
+        count: u64,
+        /// Whether to include the blocks' deploys.
+        with_deploys: bool,
+        /// The responder to call with the result.
+        responder: Responder<Vec<BlockWithDeploys>>,
+    },
     /// Get finality signatures for a Block hash.
     GetBlockSignatures {
         /// The hash for the request
@@ -339,6 +454,25 @@ pub enum StorageRequest {
         /// stored.
         responder: Responder<bool>,
     },
+    /// Retrieve statistics of the underlying databases.
+    GetStats {
+        /// Responder to call with the result.  Returns `None` if the statistics could not be read.
+        responder: Responder<Option<StorageStats>>,
+    },
+    /// Start compacting the underlying database file in the background.
+    CompactStorage {
+        /// Responder to call with the initial progress of the compaction, or the reason it wasn't
+        /// started.
+        responder: Responder<Result<CompactionProgress, String>>,
+    },
+    /// Retrieve the counts and sizes of the items stored per era.
+    GetEraStorageStats {
+        /// The eras whose statistics are to be retrieved.
+        eras: RangeInclusive<EraId>,
+        /// Responder to call with the statistics of the eras with any items stored, in ascending
+        /// order.
+        responder: Responder<Vec<(EraId, EraStorageStats)>>,
+    },
 }
 
 impl Display for StorageRequest {
@@ -362,7 +496,20 @@ impl Display for StorageRequest {
             StorageRequest::GetBlockTransfers { block_hash, .. } => {
                 write!(formatter, "get transfers for {}", block_hash)
             }
+            StorageRequest::GetBlockExecutionResults { block_hash, .. } => {
+                write!(formatter, "get execution results for {}", block_hash)
+            }
             StorageRequest::PutDeploy { deploy, .. } => write!(formatter, "put {}", deploy),
+            StorageRequest::MergeDeployApprovals {
+                deploy_hash,
+                approvals,
+                ..
+            } => write!(
+                formatter,
+                "merge {} approvals into {}",
+                approvals.len(),
+                deploy_hash
+            ),
             StorageRequest::GetDeploys { deploy_hashes, .. } => {
                 write!(formatter, "get {}", DisplayIter::new(deploy_hashes.iter()))
             }
@@ -374,6 +521,11 @@ impl Display for StorageRequest {
             StorageRequest::PutExecutionResults { block_hash, .. } => {
                 write!(formatter, "put execution results for {}", block_hash)
             }
+            StorageRequest::GetDeployHashesByAccount { account, range, .. } => write!(
+                formatter,
+                "get deploy hashes {:?} for account {}",
+                range, account
+            ),
             StorageRequest::GetDeployAndMetadata { deploy_hash, .. } => {
                 write!(formatter, "get deploy and metadata for {}", deploy_hash)
             }
@@ -394,6 +546,24 @@ impl Display for StorageRequest {
             StorageRequest::GetHighestBlockWithMetadata { .. } => {
                 write!(formatter, "get highest block with metadata")
             }
+            StorageRequest::GetBlockWithSignatures { maybe_id, .. } => match maybe_id {
+                Some(BlockIdentifier::Hash(hash)) => {
+                    write!(formatter, "get block with signatures {}", hash)
+                }
+                Some(BlockIdentifier::Height(height)) => {
+                    write!(formatter, "get block with signatures at height {}", height)
+                }
+                None => write!(formatter, "get highest block with signatures"),
+            },
+            StorageRequest::GetBlockRange {
+                start_height,
+                count,
+                ..
+            } => write!(
+                formatter,
+                "get {} blocks starting at height {}",
+                count, start_height
+            ),
             StorageRequest::GetBlockSignatures { block_hash, .. } => {
                 write!(
                     formatter,
@@ -404,6 +574,14 @@ impl Display for StorageRequest {
             StorageRequest::PutBlockSignatures { .. } => {
                 write!(formatter, "put finality signatures")
             }
+            StorageRequest::GetStats { .. } => write!(formatter, "get storage statistics"),
+            StorageRequest::CompactStorage { .. } => write!(formatter, "compact storage"),
+            StorageRequest::GetEraStorageStats { eras, .. } => write!(
+                formatter,
+                "get storage statistics of eras {} to {}",
+                eras.start(),
+                eras.end()
+            ),
         }
     }
 }
@@ -458,6 +636,9 @@ pub struct ProtoBlockRequest {
     pub(crate) next_finalized: u64,
     /// Random bit with which to construct the `ProtoBlock` requested.
     pub(crate) random_bit: bool,
+    /// The rewarded signatures of the non-finalized ancestors of the requested block, which must
+    /// not be rewarded again.
+    pub(crate) past_rewarded_signatures: Vec<RewardedSignatures>,
     /// Responder to call with the result.
     pub(crate) responder: Responder<ProtoBlock>,
 }
@@ -468,6 +649,13 @@ pub struct ProtoBlockRequest {
 pub enum BlockProposerRequest {
     /// Request a list of deploys to propose in a new block.
     RequestProtoBlock(ProtoBlockRequest),
+    /// Request what the block proposer knows about a deploy.
+    GetDeployStatus {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<DeployStatus>,
+    },
 }
 
 impl Display for BlockProposerRequest {
@@ -479,6 +667,7 @@ impl Display for BlockProposerRequest {
                 next_finalized,
                 responder: _,
                 random_bit: _,
+                past_rewarded_signatures: _,
             }) => write!(
                 formatter,
                 "list for inclusion: instant {} past {} next_finalized {}",
@@ -486,6 +675,9 @@ impl Display for BlockProposerRequest {
                 past_deploys.len(),
                 next_finalized
             ),
+            BlockProposerRequest::GetDeployStatus { deploy_hash, .. } => {
+                write!(formatter, "get status of deploy {}", deploy_hash)
+            }
         }
     }
 }
@@ -502,7 +694,16 @@ pub enum RpcRequest<I> {
         /// The deploy to be announced.
         deploy: Box<Deploy>,
         /// Responder to call.
-        responder: Responder<Result<(), Error>>,
+        responder: Responder<Result<Acceptance, Error>>,
+    },
+    /// Add approvals to a deploy awaiting approvals.
+    AddDeployApprovals {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The approvals to add.
+        approvals: Vec<Approval>,
+        /// Responder to call.
+        responder: Responder<Result<Acceptance, Error>>,
     },
     /// If `maybe_identifier` is `Some`, return the specified block if it exists, else `None`.  If
     /// `maybe_identifier` is `None`, return the latest block.
@@ -512,6 +713,26 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<(LinearBlock, BlockSignatures)>>,
     },
+    /// Return the specified block, or the latest block if `maybe_id` is `None`, along with its
+    /// finality signatures and its era's validator weights.
+    GetBlockWithSignatures {
+        /// The identifier (can either be a hash or the height) of the block to be retrieved.
+        maybe_id: Option<BlockIdentifier>,
+        /// Responder to call with the result.
+        responder: Responder<Option<BlockWithSignatures>>,
+    },
+    /// Return up to `count` consecutive blocks starting at `start_height`, along with their
+    /// finality signatures and optionally their deploys.
+    GetBlockRange {
+        /// The height of the first block.
+        start_height: u64,
+        /// The maximum number of blocks.
+        count: u64,
+        /// Whether to include the blocks' deploys.
+        with_deploys: bool,
+        /// Responder to call with the result.
+        responder: Responder<Vec<BlockWithDeploys>>,
+    },
     /// Return transfers for block by hash (if any).
     GetBlockTransfers {
         /// The hash of the block to retrieve transfers for.
@@ -519,6 +740,15 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<Vec<Transfer>>>,
     },
+    /// Estimate the global state data which would be removed by purging the given keys.
+    EstimatePurge {
+        /// The state root hash.
+        state_root_hash: Digest,
+        /// The keys which would be purged.
+        keys: Vec<Key>,
+        /// Responder to call with the result.
+        responder: Responder<Result<Option<PurgeEstimate>, engine_state::Error>>,
+    },
     /// Query the global state at the given root hash.
     QueryGlobalState {
         /// The state root hash.
@@ -530,6 +760,15 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Result<QueryResult, engine_state::Error>>,
     },
+    /// Query several values in the global state at the given root hash.
+    QueryGlobalStateBatch {
+        /// The state root hash.
+        state_root_hash: Digest,
+        /// The base key and path of each value.
+        queries: Vec<(Key, Vec<String>)>,
+        /// Responder to call with the result.
+        responder: Responder<Result<QueryBatchResult, engine_state::Error>>,
+    },
     /// Query the global state at the given root hash.
     QueryEraValidators {
         /// The global state hash.
@@ -553,6 +792,20 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Result<Option<Box<ProtocolData>>, engine_state::Error>>,
     },
+    /// Query the contract runtime for the gas costs under a protocol version.
+    GetCostTable {
+        /// The protocol version.
+        protocol_version: ProtocolVersion,
+        /// Responder to call with the result.
+        responder: Responder<Result<Option<CostTable>, engine_state::Error>>,
+    },
+    /// Get the contents of the chainspec file of a protocol version.
+    GetChainspecRawBytes {
+        /// The protocol version.
+        protocol_version: Version,
+        /// Responder to call with the result.
+        responder: Responder<Option<Vec<u8>>>,
+    },
     /// Query the global state at the given root hash.
     GetBalance {
         /// The state root hash.
@@ -562,6 +815,15 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// Query the balances of several purses at the given root hash.
+    GetBalanceBatch {
+        /// The state root hash.
+        state_root_hash: Digest,
+        /// The purse `URef`s or account keys to read balances for.
+        keys: Vec<Key>,
+        /// Responder to call with the result.
+        responder: Responder<Result<BalanceBatchResult, engine_state::Error>>,
+    },
     /// Return the specified deploy and metadata if it exists, else `None`.
     GetDeploy {
         /// The hash of the deploy to be retrieved.
@@ -569,11 +831,32 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<(Deploy, DeployMetadata)>>,
     },
+    /// Return the hashes of deploys created by the given account, ordered by their timestamp.
+    GetDeployHashesByAccount {
+        /// The account which created the deploys.
+        account: AccountHash,
+        /// The positions within the account's deploys to return.
+        range: Range<u64>,
+        /// Responder to call with the result.
+        responder: Responder<Vec<DeployHash>>,
+    },
     /// Return the connected peers.
     GetPeers {
         /// Responder to call with the result.
         responder: Responder<BTreeMap<I, String>>,
     },
+    /// Return the blocklisted peers.
+    GetBlocklist {
+        /// Responder to call with the result.
+        responder: Responder<Vec<BlocklistEntry>>,
+    },
+    /// Remove the peer at `address`, or all peers if `None`, from the blocklist.
+    ClearBlocklist {
+        /// The address of the peer to unblock.
+        address: Option<SocketAddr>,
+        /// Responder to call with the number of removed entries.
+        responder: Responder<usize>,
+    },
     /// Return string formatted status or `None` if an error occurred.
     GetStatus {
         /// Responder to call with the result.
@@ -584,12 +867,52 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<String>>,
     },
+    /// Return a redacted snapshot of a component's internal state, or `None` if the component is
+    /// not running.
+    DumpComponentState {
+        /// The component whose state should be dumped.
+        component: DumpableComponent,
+        /// Responder to call with the result.
+        responder: Responder<Option<serde_json::Value>>,
+    },
+    /// Stage a new consensus signing key to be used from a future era on.
+    StageSigningKey {
+        /// The path to the new secret key file on the node.
+        secret_key_path: PathBuf,
+        /// The first era in which the new key is used.
+        activation_era: EraId,
+        /// Responder to call with the new public key, or the reason it wasn't staged.
+        responder: Responder<Result<PublicKey, String>>,
+    },
+    /// Start compacting the storage database file in the background.
+    CompactStorage {
+        /// Responder to call with the initial progress of the compaction, or the reason it wasn't
+        /// started.
+        responder: Responder<Result<CompactionProgress, String>>,
+    },
+    /// Return the counts and sizes of the items stored per era.
+    GetEraStorageStats {
+        /// The eras whose statistics are requested.
+        eras: RangeInclusive<EraId>,
+        /// Responder to call with the result.
+        responder: Responder<Vec<(EraId, EraStorageStats)>>,
+    },
 }
 
 impl<I> Display for RpcRequest<I> {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             RpcRequest::SubmitDeploy { deploy, .. } => write!(formatter, "submit {}", *deploy),
+            RpcRequest::AddDeployApprovals {
+                deploy_hash,
+                approvals,
+                ..
+            } => write!(
+                formatter,
+                "add {} approvals to {}",
+                approvals.len(),
+                deploy_hash
+            ),
             RpcRequest::GetBlock {
                 maybe_id: Some(BlockIdentifier::Hash(hash)),
                 ..
@@ -599,12 +922,40 @@ impl<I> Display for RpcRequest<I> {
                 ..
             } => write!(formatter, "get {}", height),
             RpcRequest::GetBlock { maybe_id: None, .. } => write!(formatter, "get latest block"),
+            RpcRequest::GetBlockWithSignatures { maybe_id, .. } => {
+                write!(formatter, "get block with signatures {:?}", maybe_id)
+            }
+            RpcRequest::GetBlockRange {
+                start_height,
+                count,
+                ..
+            } => write!(
+                formatter,
+                "get {} blocks starting at height {}",
+                count, start_height
+            ),
             RpcRequest::GetBlockTransfers { block_hash, .. } => {
                 write!(formatter, "get transfers {}", block_hash)
             }
             RpcRequest::QueryProtocolData {
                 protocol_version, ..
             } => write!(formatter, "protocol_version {}", protocol_version),
+            RpcRequest::GetCostTable {
+                protocol_version, ..
+            } => write!(formatter, "get cost table for {}", protocol_version),
+            RpcRequest::GetChainspecRawBytes {
+                protocol_version, ..
+            } => write!(formatter, "get raw chainspec {}", protocol_version),
+            RpcRequest::EstimatePurge {
+                state_root_hash,
+                keys,
+                ..
+            } => write!(
+                formatter,
+                "estimate purge of {} keys at {}",
+                keys.len(),
+                state_root_hash
+            ),
             RpcRequest::QueryGlobalState {
                 state_root_hash,
                 base_key,
@@ -615,6 +966,16 @@ impl<I> Display for RpcRequest<I> {
                 "query {}, base_key: {}, path: {:?}",
                 state_root_hash, base_key, path
             ),
+            RpcRequest::QueryGlobalStateBatch {
+                state_root_hash,
+                queries,
+                ..
+            } => write!(
+                formatter,
+                "query batch {}, {} queries",
+                state_root_hash,
+                queries.len()
+            ),
             RpcRequest::QueryEraValidators {
                 state_root_hash, ..
             } => write!(formatter, "auction {}", state_root_hash),
@@ -632,14 +993,74 @@ impl<I> Display for RpcRequest<I> {
                 "balance {}, purse_uref: {}",
                 state_root_hash, purse_uref
             ),
+            RpcRequest::GetBalanceBatch {
+                state_root_hash,
+                keys,
+                ..
+            } => write!(
+                formatter,
+                "balance batch {}, {} keys",
+                state_root_hash,
+                keys.len()
+            ),
             RpcRequest::GetDeploy { hash, .. } => write!(formatter, "get {}", hash),
+            RpcRequest::GetDeployHashesByAccount { account, range, .. } => write!(
+                formatter,
+                "get deploy hashes {:?} for account {}",
+                range, account
+            ),
             RpcRequest::GetPeers { .. } => write!(formatter, "get peers"),
+            RpcRequest::GetBlocklist { .. } => write!(formatter, "get blocklist"),
+            RpcRequest::ClearBlocklist {
+                address: Some(address),
+                ..
+            } => write!(formatter, "clear blocklist entries for {}", address),
+            RpcRequest::ClearBlocklist { address: None, .. } => {
+                write!(formatter, "clear blocklist")
+            }
             RpcRequest::GetStatus { .. } => write!(formatter, "get status"),
             RpcRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
+            RpcRequest::DumpComponentState { component, .. } => {
+                write!(formatter, "dump state of {}", component)
+            }
+            RpcRequest::StageSigningKey {
+                secret_key_path,
+                activation_era,
+                ..
+            } => write!(
+                formatter,
+                "stage signing key {} from {}",
+                secret_key_path.display(),
+                activation_era
+            ),
+            RpcRequest::CompactStorage { .. } => write!(formatter, "compact storage"),
+            RpcRequest::GetEraStorageStats { eras, .. } => write!(
+                formatter,
+                "get storage statistics of eras {} to {}",
+                eras.start(),
+                eras.end()
+            ),
         }
     }
 }
 
+/// A request to serialize a redacted snapshot of a component's internal state, to be attached to
+/// bug reports.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct DumpStateRequest {
+    /// The component whose state should be dumped.
+    pub component: DumpableComponent,
+    /// Responder to call with the snapshot, or `None` if the component is not running.
+    pub responder: Responder<Option<serde_json::Value>>,
+}
+
+impl Display for DumpStateRequest {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "dump state of {}", self.component)
+    }
+}
+
 /// Abstract REST request.
 ///
 /// An REST request is an abstract request that does not concern itself with serialization or
@@ -657,6 +1078,11 @@ pub enum RestRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<String>>,
     },
+    /// Return the node's view of the network topology, with all node IDs salted and hashed.
+    GetTopology {
+        /// Responder to call with the result.
+        responder: Responder<TopologyGraph>,
+    },
 }
 
 impl<I> Display for RestRequest<I> {
@@ -664,6 +1090,7 @@ impl<I> Display for RestRequest<I> {
         match self {
             RestRequest::GetStatus { .. } => write!(formatter, "get status"),
             RestRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
+            RestRequest::GetTopology { .. } => write!(formatter, "get topology"),
         }
     }
 }
@@ -679,6 +1106,13 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<Option<Box<ProtocolData>>, engine_state::Error>>,
     },
+    /// Get the gas costs charged under a `ProtocolVersion`.
+    GetCostTable {
+        /// The protocol version.
+        protocol_version: ProtocolVersion,
+        /// Responder to call with the result.
+        responder: Responder<Result<Option<CostTable>, engine_state::Error>>,
+    },
     /// Commit genesis chainspec.
     CommitGenesis {
         /// The chainspec.
@@ -720,6 +1154,14 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the query result.
         responder: Responder<Result<QueryResult, engine_state::Error>>,
     },
+    /// A request for several values at a single state root.
+    QueryBatch {
+        /// Query batch request.
+        #[serde(skip_serializing)]
+        query_batch_request: QueryBatchRequest,
+        /// Responder to call with the query batch result.
+        responder: Responder<Result<QueryBatchResult, engine_state::Error>>,
+    },
     /// A balance request.
     GetBalance {
         /// Balance request.
@@ -728,6 +1170,14 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the balance result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// A request for the balances of several purses.
+    GetBalanceBatch {
+        /// Balance batch request.
+        #[serde(skip_serializing)]
+        balance_batch_request: BalanceBatchRequest,
+        /// Responder to call with the balance batch result.
+        responder: Responder<Result<BalanceBatchResult, engine_state::Error>>,
+    },
     /// Returns validator weights.
     GetEraValidators {
         /// Get validators weights request.
@@ -744,13 +1194,13 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<Option<ValidatorWeights>, GetEraValidatorsError>>,
     },
-    /// Return bids at a given state root hash
-    GetBids {
-        /// Get bids request.
+    /// Return a page of the keys starting with a given prefix at a given state root hash.
+    GetKeysByPrefix {
+        /// Get keys by prefix request.
         #[serde(skip_serializing)]
-        get_bids_request: GetBidsRequest,
+        get_keys_request: GetKeysByPrefixRequest,
         /// Responder to call with the result.
-        responder: Responder<Result<GetBidsResult, engine_state::Error>>,
+        responder: Responder<Result<GetKeysByPrefixResult, engine_state::Error>>,
     },
     /// Performs a step consisting of calculating rewards, slashing and running the auction at the
     /// end of an era.
@@ -788,6 +1238,16 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<Vec<Blake2bHash>, engine_state::Error>>,
     },
+    /// Sum up the global state data stored under the given keys, to estimate the impact of purging
+    /// them.
+    EstimatePurge {
+        /// The state root hash at which to read the keys.
+        state_root_hash: Blake2bHash,
+        /// The keys which would be purged.
+        keys: Vec<Key>,
+        /// Responder to call with the result. Returns `None` if the state root is not known.
+        responder: Responder<Result<Option<PurgeEstimate>, engine_state::Error>>,
+    },
     /// Get the missing keys under a given trie key in global storage
     MissingTrieKeys {
         /// The ancestral hash to use when finding hashes that are missing from the `TrieStore`
@@ -795,6 +1255,15 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<Vec<Blake2bHash>, engine_state::Error>>,
     },
+    /// Execute a block with the shadow engine, without affecting the active global state.
+    ShadowExecute {
+        /// The block to execute.
+        #[serde(skip_serializing)]
+        request: Box<ShadowExecutionRequest>,
+        /// Responder to call with the resulting state root hash. Returns `None` if shadow
+        /// execution is not enabled.
+        responder: Responder<Option<Result<Blake2bHash, ShadowExecutionError>>>,
+    },
 }
 
 impl Display for ContractRuntimeRequest {
@@ -833,10 +1302,24 @@ impl Display for ContractRuntimeRequest {
                 write!(formatter, "query request: {:?}", query_request)
             }
 
+            ContractRuntimeRequest::QueryBatch {
+                query_batch_request,
+                ..
+            } => write!(formatter, "query batch request: {:?}", query_batch_request),
+
             ContractRuntimeRequest::GetBalance {
                 balance_request, ..
             } => write!(formatter, "balance request: {:?}", balance_request),
 
+            ContractRuntimeRequest::GetBalanceBatch {
+                balance_batch_request,
+                ..
+            } => write!(
+                formatter,
+                "balance batch request: {:?}",
+                balance_batch_request
+            ),
+
             ContractRuntimeRequest::GetEraValidators { request, .. } => {
                 write!(formatter, "get era validators: {:?}", request)
             }
@@ -845,10 +1328,10 @@ impl Display for ContractRuntimeRequest {
                 write!(formatter, "get validator weights: {:?}", request)
             }
 
-            ContractRuntimeRequest::GetBids {
-                get_bids_request, ..
+            ContractRuntimeRequest::GetKeysByPrefix {
+                get_keys_request, ..
             } => {
-                write!(formatter, "get bids request: {:?}", get_bids_request)
+                write!(formatter, "get keys by prefix: {:?}", get_keys_request)
             }
 
             ContractRuntimeRequest::Step { step_request, .. } => {
@@ -859,6 +1342,10 @@ impl Display for ContractRuntimeRequest {
                 protocol_version, ..
             } => write!(formatter, "protocol_version: {}", protocol_version),
 
+            ContractRuntimeRequest::GetCostTable {
+                protocol_version, ..
+            } => write!(formatter, "get cost table for {}", protocol_version),
+
             ContractRuntimeRequest::IsBonded {
                 public_key, era_id, ..
             } => {
@@ -870,6 +1357,16 @@ impl Display for ContractRuntimeRequest {
             ContractRuntimeRequest::PutTrie { trie, .. } => {
                 write!(formatter, "trie: {:?}", trie)
             }
+            ContractRuntimeRequest::EstimatePurge {
+                state_root_hash,
+                keys,
+                ..
+            } => write!(
+                formatter,
+                "estimate purge of {} keys at {}",
+                keys.len(),
+                state_root_hash
+            ),
             ContractRuntimeRequest::MissingTrieKeys { trie_key, .. } => {
                 write!(
                     formatter,
@@ -877,6 +1374,12 @@ impl Display for ContractRuntimeRequest {
                     trie_key
                 )
             }
+            ContractRuntimeRequest::ShadowExecute { request, .. } => write!(
+                formatter,
+                "shadow execute {} deploys on {}",
+                request.deploy_items.len(),
+                request.pre_state_hash
+            ),
         }
     }
 }
@@ -885,14 +1388,14 @@ impl Display for ContractRuntimeRequest {
 #[derive(Debug, Serialize)]
 #[must_use]
 pub enum FetcherRequest<I, T: Item> {
-    /// Return the specified item if it exists, else `None`.
+    /// Return the specified item if it exists, else the reason it couldn't be fetched.
     Fetch {
         /// The ID of the item to be retrieved.
         id: T::Id,
         /// The peer id of the peer to be asked if the item is not held locally
         peer: I,
         /// Responder to call with the result.
-        responder: Responder<Option<FetchResult<T, I>>>,
+        responder: Responder<Result<FetchResult<T, I>, FetchFailure>>,
     },
 }
 
@@ -910,6 +1413,8 @@ impl<I, T: Item> Display for FetcherRequest<I, T> {
 pub enum BlockExecutorRequest {
     /// A request to execute finalized block.
     ExecuteBlock(FinalizedBlock),
+    /// A request whether the number of blocks awaiting execution reached the configured maximum.
+    IsQueueFull(Responder<bool>),
 }
 
 impl Display for BlockExecutorRequest {
@@ -918,6 +1423,7 @@ impl Display for BlockExecutorRequest {
             BlockExecutorRequest::ExecuteBlock(finalized_block) => {
                 write!(f, "execute block {}", finalized_block)
             }
+            BlockExecutorRequest::IsQueueFull(_) => write!(f, "is execution queue full"),
         }
     }
 }
@@ -932,8 +1438,8 @@ pub struct BlockValidationRequest<T, I> {
     pub(crate) sender: I,
     /// Responder to call with the result.
     ///
-    /// Indicates whether or not validation was successful and returns `block` unchanged.
-    pub(crate) responder: Responder<(bool, T)>,
+    /// Indicates the outcome of the validation and returns `block` unchanged.
+    pub(crate) responder: Responder<BlockValidationResult<T, I>>,
     /// A check will be performed against the deploys to ensure their timestamp is
     /// older than or equal to the block itself.
     pub(crate) block_timestamp: Timestamp,
@@ -955,9 +1461,15 @@ pub enum LinearChainRequest<I> {
     BlockRequest(BlockHash, I),
     /// Request for a linear chain block at height.
     BlockAtHeight(BlockHeight, I),
+    /// Request for the header of a linear chain block, by hash.
+    BlockHeaderRequest(BlockHash, I),
+    /// Request for the execution results of a linear chain block, by hash.
+    BlockExecutionResultsRequest(BlockHash, I),
     /// Local request for a linear chain block at height.
     // TODO: Unify `BlockAtHeight` and `BlockAtHeightLocal`.
     BlockAtHeightLocal(BlockHeight, Responder<Option<Block>>),
+    /// Local request for the recent blocks which don't have strict finality yet.
+    BlocksBelowStrictFinality(Responder<Vec<BlockFinalityProgress>>),
 }
 
 impl<I: Display> Display for LinearChainRequest<I> {
@@ -969,9 +1481,20 @@ impl<I: Display> Display for LinearChainRequest<I> {
             LinearChainRequest::BlockAtHeight(height, sender) => {
                 write!(f, "block request for {} from {}", height, sender)
             }
+            LinearChainRequest::BlockHeaderRequest(bh, peer) => {
+                write!(f, "block header request for hash {} from {}", bh, peer)
+            }
+            LinearChainRequest::BlockExecutionResultsRequest(bh, peer) => write!(
+                f,
+                "block execution results request for hash {} from {}",
+                bh, peer
+            ),
             LinearChainRequest::BlockAtHeightLocal(height, _) => {
                 write!(f, "local request for block at height {}", height)
             }
+            LinearChainRequest::BlocksBelowStrictFinality(_) => {
+                write!(f, "local request for blocks below strict finality")
+            }
         }
     }
 }
@@ -984,8 +1507,13 @@ pub enum ConsensusRequest {
     HandleLinearBlock(Box<Block>, Responder<Option<FinalitySignature>>),
     /// Check whether validator identifying with the public key is bonded.
     IsBondedValidator(EraId, PublicKey, Responder<bool>),
-    /// Request for our public key, and if we're a validator, the next round length.
-    Status(Responder<(PublicKey, Option<TimeDiff>)>),
+    /// Request for the validators of an era and their weights, if the era is active.
+    ValidatorWeights(EraId, Responder<Option<ValidatorWeights>>),
+    /// Request for our public key, if we're a validator the next round length, and whether we are
+    /// catching up with finalization.
+    Status(Responder<(PublicKey, Option<TimeDiff>, SyncStatus)>),
+    /// Request to stage the secret key at the given path to be used from the given era on.
+    StageSigningKey(PathBuf, EraId, Responder<Result<PublicKey, String>>),
 }
 
 /// ChainspecLoader component requests.
@@ -993,12 +1521,26 @@ pub enum ConsensusRequest {
 pub enum ChainspecLoaderRequest {
     /// Chainspec info request.
     GetChainspecInfo(Responder<ChainspecInfo>),
+    /// Request for the chainspec the node is currently running.
+    GetChainspec(Responder<Arc<Chainspec>>),
+    /// Request for the contents of the chainspec file of a protocol version applied at any point
+    /// in the node's lifetime.
+    GetChainspecRawBytes {
+        /// The protocol version of the chainspec.
+        protocol_version: Version,
+        /// Responder to call with the chainspec file contents, or `None` if not known.
+        responder: Responder<Option<Vec<u8>>>,
+    },
 }
 
 impl Display for ChainspecLoaderRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ChainspecLoaderRequest::GetChainspecInfo(_) => write!(f, "get chainspec info"),
+            ChainspecLoaderRequest::GetChainspec(_) => write!(f, "get chainspec"),
+            ChainspecLoaderRequest::GetChainspecRawBytes {
+                protocol_version, ..
+            } => write!(f, "get raw chainspec {}", protocol_version),
         }
     }
 }
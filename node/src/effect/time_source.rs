@@ -0,0 +1,185 @@
+//! Sources of time.
+//!
+//! Components read the current time and wait for timeouts through the effect builder rather than
+//! the system clock, so that tests can substitute a [`ManualClock`] for it.  Such a clock only
+//! moves when the test advances it, which lets tests skip over timeouts instantly instead of
+//! sleeping through them, and fires expired timeouts in a fixed order.
+
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use std::{collections::BTreeMap, mem, sync::Mutex};
+
+use datasize::DataSize;
+#[cfg(test)]
+use futures::channel::oneshot;
+use futures::{future::BoxFuture, FutureExt};
+
+#[cfg(test)]
+use crate::types::TimeDiff;
+use crate::types::Timestamp;
+
+/// The source of time of a reactor.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TimeSource {
+    /// The system clock.
+    Real,
+    /// A clock advanced only by the test driving the reactor.
+    #[cfg(test)]
+    Manual(&'static ManualClock),
+}
+
+impl Default for TimeSource {
+    fn default() -> Self {
+        TimeSource::Real
+    }
+}
+
+impl TimeSource {
+    /// Returns the current time.
+    pub(crate) fn now(self) -> Timestamp {
+        match self {
+            TimeSource::Real => Timestamp::now(),
+            #[cfg(test)]
+            TimeSource::Manual(clock) => clock.now(),
+        }
+    }
+
+    /// Returns a future waiting for `timeout`, which yields the time that actually passed.
+    ///
+    /// On a manual clock, the timeout is registered right away rather than when the future is
+    /// first polled, so that advancing the clock fires it even if the effect hasn't been spawned
+    /// yet.
+    pub(crate) fn sleep(self, timeout: Duration) -> BoxFuture<'static, Duration> {
+        match self {
+            TimeSource::Real => async move {
+                let then = Instant::now();
+                tokio::time::delay_for(timeout).await;
+                Instant::now() - then
+            }
+            .boxed(),
+            #[cfg(test)]
+            TimeSource::Manual(clock) => {
+                let then = clock.now();
+                let receiver = clock.timeout(timeout);
+                async move {
+                    // The sender is only dropped once it fired, as the clock is never dropped.
+                    let _ = receiver.await;
+                    clock.now().saturating_diff(then).into()
+                }
+                .boxed()
+            }
+        }
+    }
+}
+
+// The manual clock is shared by all handles of a reactor, and never owned by any of them.
+impl DataSize for TimeSource {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// A clock which only moves when advanced explicitly.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct ManualClock {
+    state: Mutex<ManualClockState>,
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct ManualClockState {
+    now: Timestamp,
+    /// The pending timeouts, keyed by their deadline and the order in which they were set.
+    timeouts: BTreeMap<(Timestamp, u64), oneshot::Sender<()>>,
+    next_timeout_id: u64,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// Creates a clock showing `start`.
+    pub(crate) fn new(start: Timestamp) -> Self {
+        let state = ManualClockState {
+            now: start,
+            timeouts: BTreeMap::new(),
+            next_timeout_id: 0,
+        };
+        ManualClock {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Returns the time the clock shows.
+    pub(crate) fn now(&self) -> Timestamp {
+        self.state.lock().expect("lock poisoned").now
+    }
+
+    /// Returns the number of timeouts which have not expired yet.
+    pub(crate) fn pending_timeouts(&self) -> usize {
+        self.state.lock().expect("lock poisoned").timeouts.len()
+    }
+
+    /// Moves the clock forward by `duration`, firing all timeouts which expire by then in the
+    /// order of their deadlines.
+    pub(crate) fn advance(&self, duration: Duration) {
+        let expired = {
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.now = state.now + TimeDiff::from(duration);
+            let first_pending = (state.now + TimeDiff::from(1), 0);
+            let pending = state.timeouts.split_off(&first_pending);
+            mem::replace(&mut state.timeouts, pending)
+        };
+        for (_, sender) in expired {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Returns a receiver which is notified once `timeout` has passed on this clock.
+    fn timeout(&self, timeout: Duration) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        let mut state = self.state.lock().expect("lock poisoned");
+        if timeout == Duration::from_secs(0) {
+            let _ = sender.send(());
+        } else {
+            let deadline = state.now + TimeDiff::from(timeout);
+            let id = state.next_timeout_id;
+            state.next_timeout_id += 1;
+            state.timeouts.insert((deadline, id), sender);
+        }
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn should_fire_timeouts_only_when_advanced_past_them() {
+        let clock = utils::leak(ManualClock::new(Timestamp::from(1_000)));
+        let time_source = TimeSource::Manual(clock);
+
+        let mut short = time_source.sleep(Duration::from_secs(1));
+        let mut long = time_source.sleep(Duration::from_secs(10));
+        assert_eq!(clock.pending_timeouts(), 2);
+        assert!((&mut short).now_or_never().is_none());
+        assert!((&mut long).now_or_never().is_none());
+
+        clock.advance(Duration::from_millis(999));
+        assert!((&mut short).now_or_never().is_none());
+
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(short.now_or_never(), Some(Duration::from_secs(1)));
+        assert!((&mut long).now_or_never().is_none());
+        assert_eq!(clock.pending_timeouts(), 1);
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(time_source.now(), Timestamp::from(62_000));
+        assert_eq!(long.now_or_never(), Some(Duration::from_secs(61)));
+        assert_eq!(clock.pending_timeouts(), 0);
+    }
+}
@@ -0,0 +1,440 @@
+//! Generation of arbitrary network messages and stored items, and decoding of untrusted bytes.
+//!
+//! The strategies in this module produce every variant of the [`protocol::Message`] exchanged
+//! between nodes, as well as the types persisted by the storage component.  Together with the
+//! `decode_*` functions, which decode bytes exactly as the networking and storage components do,
+//! they are intended for property tests and fuzz targets exercising the deserialization paths
+//! reachable by peers.
+//!
+//! Signed items are built from a seed rather than from arbitrary bytes, so that they carry valid
+//! signatures.
+//!
+//! Only available with the `fuzzing` feature enabled.
+
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, SocketAddr},
+};
+
+use bytes::Bytes;
+use proptest::{collection::vec, prelude::*};
+use rand::{Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use casper_types::{gens::u8_slice_32, ProtocolVersion, PublicKey, SecretKey, U512};
+
+use crate::{
+    components::{
+        consensus::{ConsensusMessage, EraId, EraReport},
+        gossiper,
+        small_network::{self, GossipedAddress, SharedAddress, MAX_FRAME_LENGTH},
+    },
+    crypto::hash::Digest,
+    protocol::Message,
+    types::{
+        Block, BlockHash, BlockSignatures, Deploy, DeployHash, FinalitySignature, FinalizedBlock,
+        Item, ProtoBlock, Tag, TimeDiff, Timestamp,
+    },
+    NodeRng,
+};
+
+/// The upper bound of the number of elements in generated collections.
+const MAX_COLLECTION_SIZE: usize = 8;
+
+/// The upper bound of the size of generated opaque payloads, in bytes.
+const MAX_PAYLOAD_SIZE: usize = 1024;
+
+/// The message type sent over the wire between nodes.
+pub type NetworkMessage = small_network::Message<Message>;
+
+/// Error decoding a network message.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    /// The frame would have been rejected before being decoded.
+    #[error(
+        "frame of {0} bytes exceeds the maximum frame length of {}",
+        MAX_FRAME_LENGTH
+    )]
+    FrameTooLong(usize),
+    /// The frame is not a valid MessagePack encoding of a network message.
+    #[error(transparent)]
+    MessagePack(#[from] rmp_serde::decode::Error),
+}
+
+/// Encodes a network message as the payload of a frame.
+pub fn encode_network_message(
+    message: &NetworkMessage,
+) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    small_network::encode_message(message).map(|frame| frame.to_vec())
+}
+
+/// Decodes the payload of a frame received from a peer.
+pub fn decode_network_message(bytes: &[u8]) -> Result<NetworkMessage, DecodeError> {
+    if bytes.len() > MAX_FRAME_LENGTH {
+        return Err(DecodeError::FrameTooLong(bytes.len()));
+    }
+    let frame = Bytes::copy_from_slice(bytes);
+    Ok(small_network::decode_message(frame)?)
+}
+
+/// Decodes an item as read from storage, or as received in a `GetResponse`.
+pub fn decode_stored<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, bincode::Error> {
+    bincode::deserialize(bytes)
+}
+
+/// Encodes an item as written to storage, or as sent in a `GetResponse`.
+pub fn encode_stored<T: Serialize>(item: &T) -> Result<Vec<u8>, bincode::Error> {
+    bincode::serialize(item)
+}
+
+/// Generates a secret key of either algorithm.
+fn secret_key(rng: &mut NodeRng) -> SecretKey {
+    if rng.gen() {
+        SecretKey::ed25519(rng.gen())
+    } else {
+        SecretKey::secp256k1(rng.gen())
+    }
+}
+
+/// Returns a strategy for random number generators seeded by arbitrary values.
+///
+/// Shrinking the seed does not shrink the generated values, but it keeps failures reproducible.
+fn rng_arb() -> impl Strategy<Value = NodeRng> {
+    any::<u64>().prop_map(NodeRng::seed_from_u64)
+}
+
+fn bytes_arb(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..max_len)
+}
+
+/// Returns a strategy for era IDs.
+pub fn era_id_arb() -> impl Strategy<Value = EraId> {
+    any::<u64>().prop_map(EraId)
+}
+
+/// Returns a strategy for public keys of either algorithm.
+pub fn public_key_arb() -> impl Strategy<Value = PublicKey> {
+    rng_arb().prop_map(|mut rng| PublicKey::from(&secret_key(&mut rng)))
+}
+
+/// Returns a strategy for deploy hashes.
+pub fn deploy_hash_arb() -> impl Strategy<Value = DeployHash> {
+    u8_slice_32().prop_map(|bytes| DeployHash::new(Digest::from(bytes)))
+}
+
+/// Returns a strategy for block hashes.
+pub fn block_hash_arb() -> impl Strategy<Value = BlockHash> {
+    u8_slice_32().prop_map(|bytes| BlockHash::new(Digest::from(bytes)))
+}
+
+/// Returns a strategy for item tags.
+pub fn tag_arb() -> impl Strategy<Value = Tag> {
+    prop_oneof![
+        Just(Tag::Deploy),
+        Just(Tag::Block),
+        Just(Tag::GossipedAddress),
+        Just(Tag::BlockByHeight),
+        Just(Tag::DeployHeaderById),
+        Just(Tag::DeployChunk),
+        Just(Tag::BlockHeader),
+        Just(Tag::BlockExecutionResults),
+    ]
+}
+
+/// Returns a strategy for signed deploys.
+pub fn deploy_arb() -> impl Strategy<Value = Deploy> {
+    (
+        rng_arb(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        vec(deploy_hash_arb(), 0..MAX_COLLECTION_SIZE),
+        "[a-z0-9-]{0,32}",
+    )
+        .prop_map(
+            |(mut rng, timestamp, ttl, gas_price, priority_fee, dependencies, chain_name)| {
+                let payment = rng.gen();
+                let session = rng.gen();
+                let secret_key = secret_key(&mut rng);
+                Deploy::new(
+                    Timestamp::from(timestamp),
+                    TimeDiff::from(ttl),
+                    gas_price,
+                    priority_fee,
+                    dependencies,
+                    chain_name,
+                    payment,
+                    session,
+                    &secret_key,
+                    &mut rng,
+                )
+            },
+        )
+}
+
+/// Returns a strategy for finality signatures with valid signatures.
+pub fn finality_signature_arb() -> impl Strategy<Value = FinalitySignature> {
+    (block_hash_arb(), era_id_arb(), rng_arb()).prop_map(|(block_hash, era_id, mut rng)| {
+        let secret_key = secret_key(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        FinalitySignature::new(block_hash, era_id, &secret_key, public_key, &mut rng)
+    })
+}
+
+/// Returns a strategy for the finality signatures of a block, as kept in storage.
+pub fn block_signatures_arb() -> impl Strategy<Value = BlockSignatures> {
+    (
+        block_hash_arb(),
+        era_id_arb(),
+        rng_arb(),
+        0..MAX_COLLECTION_SIZE,
+    )
+        .prop_map(|(block_hash, era_id, mut rng, count)| {
+            let mut block_signatures = BlockSignatures::new(block_hash, era_id);
+            for _ in 0..count {
+                let secret_key = secret_key(&mut rng);
+                let public_key = PublicKey::from(&secret_key);
+                let signature =
+                    FinalitySignature::new(block_hash, era_id, &secret_key, public_key, &mut rng)
+                        .signature;
+                block_signatures.insert_proof(public_key, signature);
+            }
+            block_signatures
+        })
+}
+
+/// Returns a strategy for executed blocks, including switch blocks.
+pub fn block_arb() -> impl Strategy<Value = Block> {
+    (
+        rng_arb(),
+        vec(deploy_hash_arb(), 0..MAX_COLLECTION_SIZE),
+        vec(deploy_hash_arb(), 0..MAX_COLLECTION_SIZE),
+        any::<u64>(),
+        era_id_arb(),
+        any::<u64>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(mut rng, wasm_deploys, transfers, timestamp, era_id, height, is_switch)| {
+                let proto_block = ProtoBlock::new(wasm_deploys, transfers, rng.gen());
+                let mut public_key = || PublicKey::from(&secret_key(&mut rng));
+                let (era_report, next_era_validator_weights) = if is_switch {
+                    let era_report = EraReport {
+                        equivocators: vec![public_key()],
+                        rewards: vec![(public_key(), 1)].into_iter().collect(),
+                        inactive_validators: vec![public_key()],
+                    };
+                    let mut weights = BTreeMap::new();
+                    weights.insert(public_key(), U512::from(u64::MAX));
+                    (Some(era_report), Some(weights))
+                } else {
+                    (None, None)
+                };
+                let proposer = public_key();
+                let finalized_block = FinalizedBlock::new(
+                    proto_block,
+                    Timestamp::from(timestamp),
+                    era_report,
+                    era_id,
+                    height,
+                    proposer,
+                );
+                Block::new(
+                    BlockHash::new(Digest::from(rng.gen::<[u8; Digest::LENGTH]>())),
+                    Digest::from(rng.gen::<[u8; Digest::LENGTH]>()),
+                    Digest::from(rng.gen::<[u8; Digest::LENGTH]>()),
+                    finalized_block,
+                    next_era_validator_weights,
+                    ProtocolVersion::V1_0_0,
+                )
+            },
+        )
+}
+
+/// Returns a strategy for gossiped listening addresses.
+pub fn gossiped_address_arb() -> impl Strategy<Value = GossipedAddress> {
+    any::<SocketAddr>().prop_map(GossipedAddress::new)
+}
+
+/// Returns a strategy for addresses shared in peer exchange responses.
+pub fn shared_address_arb() -> impl Strategy<Value = SharedAddress> {
+    (any::<SocketAddr>(), any::<u64>(), any::<bool>()).prop_map(
+        |(address, last_seen, reachable)| SharedAddress {
+            address,
+            last_seen: Timestamp::from(last_seen),
+            reachable,
+        },
+    )
+}
+
+/// Returns a strategy for consensus messages.
+///
+/// The payload of protocol messages is opaque at this level, so it is arbitrary bytes.
+pub fn consensus_message_arb() -> impl Strategy<Value = ConsensusMessage> {
+    prop_oneof![
+        (era_id_arb(), bytes_arb(MAX_PAYLOAD_SIZE))
+            .prop_map(|(era_id, payload)| ConsensusMessage::Protocol { era_id, payload }),
+        (era_id_arb(), public_key_arb())
+            .prop_map(|(era_id, pub_key)| ConsensusMessage::EvidenceRequest { era_id, pub_key }),
+    ]
+}
+
+/// Returns a strategy for gossiper messages about the items generated by `item` and the IDs
+/// generated by `item_id`.
+pub fn gossiper_message_arb<T, S, I>(
+    item: S,
+    item_id: I,
+) -> impl Strategy<Value = gossiper::Message<T>>
+where
+    T: Item + 'static,
+    S: Strategy<Value = T>,
+    I: Strategy<Value = T::Id> + Clone,
+{
+    prop_oneof![
+        item_id.clone().prop_map(gossiper::Message::Gossip),
+        (item_id.clone(), any::<bool>()).prop_map(|(item_id, is_already_held)| {
+            gossiper::Message::GossipResponse {
+                item_id,
+                is_already_held,
+            }
+        }),
+        item.prop_map(|item| gossiper::Message::GossipItem(Box::new(item))),
+        (item_id, any::<bool>()).prop_map(|(item_id, is_already_held)| {
+            gossiper::Message::GossipItemResponse {
+                item_id,
+                is_already_held,
+            }
+        }),
+    ]
+}
+
+/// Returns a strategy for `GetRequest`s and `GetResponse`s, both well-formed ones and ones with
+/// arbitrary contents.
+fn get_message_arb() -> impl Strategy<Value = Message> {
+    prop_oneof![
+        deploy_hash_arb().prop_map(|id| Message::new_get_request::<Deploy>(&id).unwrap()),
+        block_hash_arb().prop_map(|id| Message::new_get_request::<Block>(&id).unwrap()),
+        deploy_arb().prop_map(|deploy| Message::new_get_response(&deploy).unwrap()),
+        block_arb().prop_map(|block| Message::new_get_response(&block).unwrap()),
+        (tag_arb(), bytes_arb(MAX_PAYLOAD_SIZE))
+            .prop_map(|(tag, serialized_id)| Message::GetRequest { tag, serialized_id }),
+        (tag_arb(), bytes_arb(MAX_PAYLOAD_SIZE)).prop_map(|(tag, serialized_item)| {
+            Message::GetResponse {
+                tag,
+                serialized_item: serialized_item.into(),
+            }
+        }),
+    ]
+}
+
+/// Returns a strategy for all variants of the messages exchanged by the node's components.
+pub fn message_arb() -> impl Strategy<Value = Message> {
+    prop_oneof![
+        consensus_message_arb().prop_map(Message::Consensus),
+        gossiper_message_arb(deploy_arb(), deploy_hash_arb()).prop_map(Message::DeployGossiper),
+        gossiper_message_arb(gossiped_address_arb(), gossiped_address_arb())
+            .prop_map(Message::AddressGossiper),
+        get_message_arb(),
+        finality_signature_arb()
+            .prop_map(|signature| Message::FinalitySignature(Box::new(signature))),
+    ]
+}
+
+/// Returns a strategy for all variants of the messages sent over the wire.
+pub fn network_message_arb() -> impl Strategy<Value = NetworkMessage> {
+    prop_oneof![
+        any::<String>().prop_map(|network_name| small_network::Message::Handshake { network_name }),
+        message_arb().prop_map(small_network::Message::Payload),
+        any::<u64>().prop_map(|nonce| small_network::Message::Ping { nonce }),
+        any::<u64>().prop_map(|nonce| small_network::Message::Pong { nonce }),
+        Just(small_network::Message::PeerExchangeRequest),
+        vec(shared_address_arb(), 0..MAX_COLLECTION_SIZE)
+            .prop_map(|addresses| small_network::Message::PeerExchangeResponse { addresses }),
+        any::<SocketAddr>()
+            .prop_map(|address| small_network::Message::ReachabilityRequest { address }),
+        (any::<IpAddr>(), any::<bool>()).prop_map(|(observed_ip, reachable)| {
+            small_network::Message::ReachabilityResponse {
+                observed_ip,
+                reachable,
+            }
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that `value` survives being stored and read back.
+    fn assert_stored_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+        let encoded = encode_stored(value).unwrap();
+        let decoded: T = decode_stored(&encoded).unwrap();
+        assert_eq!(encode_stored(&decoded).unwrap(), encoded);
+    }
+
+    proptest! {
+        #[test]
+        fn network_message_round_trip(message in network_message_arb()) {
+            let encoded = encode_network_message(&message).unwrap();
+            prop_assert!(encoded.len() <= MAX_FRAME_LENGTH);
+            let decoded = decode_network_message(&encoded).unwrap();
+            prop_assert_eq!(encode_network_message(&decoded).unwrap(), encoded);
+        }
+
+        #[test]
+        fn stored_items_round_trip(
+            deploy in deploy_arb(),
+            block in block_arb(),
+            block_signatures in block_signatures_arb(),
+            finality_signature in finality_signature_arb(),
+        ) {
+            assert_stored_round_trip(&deploy);
+            assert_stored_round_trip(&block);
+            assert_stored_round_trip(&block_signatures);
+            assert_stored_round_trip(&finality_signature);
+        }
+
+        #[test]
+        fn decoding_arbitrary_bytes_should_not_panic(bytes in bytes_arb(4 * MAX_PAYLOAD_SIZE)) {
+            let _ = decode_network_message(&bytes);
+            let _ = decode_stored::<Deploy>(&bytes);
+            let _ = decode_stored::<Block>(&bytes);
+            let _ = decode_stored::<BlockSignatures>(&bytes);
+            let _ = decode_stored::<FinalitySignature>(&bytes);
+        }
+
+        #[test]
+        fn decoding_corrupted_messages_should_not_panic(
+            message in network_message_arb(),
+            corruptions in vec((any::<prop::sample::Index>(), any::<u8>()), 1..MAX_COLLECTION_SIZE),
+            truncate_to in any::<prop::sample::Index>(),
+        ) {
+            let mut encoded = encode_network_message(&message).unwrap();
+            for (index, byte) in corruptions {
+                let index = index.index(encoded.len());
+                encoded[index] = byte;
+            }
+            let _ = decode_network_message(&encoded);
+            let _ = decode_network_message(&encoded[..truncate_to.index(encoded.len())]);
+        }
+    }
+
+    #[test]
+    fn should_reject_oversized_frames() {
+        let bytes = vec![0; MAX_FRAME_LENGTH + 1];
+        assert!(matches!(
+            decode_network_message(&bytes),
+            Err(DecodeError::FrameTooLong(length)) if length == MAX_FRAME_LENGTH + 1
+        ));
+    }
+
+    #[test]
+    fn should_not_trust_declared_lengths() {
+        // A MessagePack array and a bincode sequence claiming to hold `u32::MAX` and `u64::MAX`
+        // elements respectively, without any elements following.
+        assert!(decode_network_message(&[0xdd, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(decode_stored::<Vec<DeployHash>>(&u64::MAX.to_le_bytes()).is_err());
+    }
+}
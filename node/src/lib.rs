@@ -26,10 +26,14 @@ extern crate test;
 
 pub mod components;
 mod config_migration;
+pub mod crash_report;
 pub mod crypto;
 mod data_migration;
 pub mod effect;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod logging;
+mod preflight;
 pub mod protocol;
 pub mod reactor;
 #[cfg(test)]
@@ -48,24 +52,40 @@ use once_cell::sync::Lazy;
 #[cfg(not(test))]
 use rand::SeedableRng;
 use signal_hook::{
-    consts::{signal::SIGUSR1, TERM_SIGNALS},
+    consts::{
+        signal::{SIGHUP, SIGUSR1},
+        TERM_SIGNALS,
+    },
     flag,
 };
 
 pub use components::{
+    block_executor::Config as BlockExecutorConfig,
+    block_exporter::Config as BlockExporterConfig,
+    block_proposer::Config as BlockProposerConfig,
+    block_validator::Config as BlockValidatorConfig,
+    clock_monitor::Config as ClockMonitorConfig,
     consensus::Config as ConsensusConfig,
     contract_runtime::Config as ContractRuntimeConfig,
-    deploy_acceptor::Config as DeployAcceptorConfig,
+    deploy_acceptor::{AcceptanceErrorCode, Config as DeployAcceptorConfig},
+    deploy_watcher::Config as DeployWatcherConfig,
+    disk_watchdog::Config as DiskWatchdogConfig,
     event_stream_server::Config as EventStreamServerConfig,
     fetcher::Config as FetcherConfig,
     gossiper::{Config as GossipConfig, Error as GossipError},
+    linear_chain_sync::Config as LinearChainSyncConfig,
     rest_server::Config as RestServerConfig,
     rpc_server::{rpcs, Config as RpcServerConfig},
-    small_network::{Config as SmallNetworkConfig, Error as SmallNetworkError},
+    small_network::{
+        decode_message, encode_message, Config as SmallNetworkConfig, Error as SmallNetworkError,
+        Message as SmallNetworkMessage,
+    },
     storage::{Config as StorageConfig, Error as StorageError},
+    webhook_notifier::Config as WebhookNotifierConfig,
 };
 pub use config_migration::{migrate_config, Error as ConfigMigrationError};
 pub use data_migration::{migrate_data, Error as DataMigrationError};
+pub use preflight::{run_preflight_checks, CheckOutcome, PreflightCheck, PreflightReport};
 pub use types::NodeRng;
 pub use utils::OS_PAGE_SIZE;
 
@@ -104,6 +124,11 @@ pub static TERMINATION_REQUESTED: Lazy<Arc<AtomicUsize>> =
 pub static QUEUE_DUMP_REQUESTED: Lazy<Arc<AtomicBool>> =
     Lazy::new(|| Arc::new(AtomicBool::new(false)));
 
+/// Global flag that indicates the currently running reactor should reload the subset of its
+/// configuration which can be changed without a restart.
+pub static CONFIG_RELOAD_REQUESTED: Lazy<Arc<AtomicBool>> =
+    Lazy::new(|| Arc::new(AtomicBool::new(false)));
+
 /// Setup UNIX signal hooks for current application.
 pub fn setup_signal_hooks() {
     for signal in TERM_SIGNALS {
@@ -115,6 +140,7 @@ pub fn setup_signal_hooks() {
         .unwrap_or_else(|error| panic!("failed to register signal {}: {}", signal, error));
     }
     let _ = flag::register(SIGUSR1, Arc::clone(&*QUEUE_DUMP_REQUESTED));
+    let _ = flag::register(SIGHUP, Arc::clone(&*CONFIG_RELOAD_REQUESTED));
 }
 
 /// Constructs a new `NodeRng`.
@@ -5,6 +5,7 @@ use std::{env, fmt, io};
 use ansi_term::{Color, Style};
 use anyhow::anyhow;
 use datasize::DataSize;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use tracing::{
@@ -13,15 +14,19 @@ use tracing::{
 };
 use tracing_subscriber::{
     fmt::{
-        format,
+        self as tracing_fmt, format,
         time::{FormatTime, SystemTime},
         FmtContext, FormatEvent, FormatFields, FormattedFields,
     },
+    layer::SubscriberExt,
     registry::LookupSpan,
-    EnvFilter,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Registry,
 };
 
 const LOG_CONFIGURATION_ENVVAR: &str = "RUST_LOG";
+const DEFAULT_LOG_FILTER: &str = "warn,casper_node=info";
 
 const LOG_FIELD_MESSAGE: &str = "message";
 const LOG_FIELD_TARGET: &str = "log.target";
@@ -47,6 +52,13 @@ pub struct LoggingConfig {
     /// If set, human-readable formats will abbreviate module names, `foo::bar::baz::bizz` will
     /// turn into `f:b:b:bizz`.
     abbreviate_modules: bool,
+
+    /// Log filter directives, e.g. "warn,casper_node=info".
+    ///
+    /// If set, takes precedence over the `RUST_LOG` environment variable.  Can be changed without
+    /// restarting the node by reloading the configuration.
+    #[serde(default)]
+    filter: Option<String>,
 }
 
 impl LoggingConfig {
@@ -56,6 +68,7 @@ impl LoggingConfig {
             format,
             color,
             abbreviate_modules,
+            filter: None,
         }
     }
 }
@@ -251,6 +264,9 @@ where
     }
 }
 
+/// Handle allowing the filter of the installed logger to be replaced.
+static FILTER_RELOAD_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
 /// Initializes the logging system with the default parameters.
 ///
 /// See `init_params` for details.
@@ -271,26 +287,49 @@ pub fn init_with_config(config: &LoggingConfig) -> anyhow::Result<()> {
         _ => write!(writer, "; {}={:?}", field, value),
     });
 
-    let filter = EnvFilter::new(
-        env::var(LOG_CONFIGURATION_ENVVAR)
-            .as_deref()
-            .unwrap_or("warn,casper_node=info"),
-    );
+    let directives = match &config.filter {
+        Some(filter) => filter.clone(),
+        None => {
+            env::var(LOG_CONFIGURATION_ENVVAR).unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string())
+        }
+    };
+    let (filter, reload_handle) = reload::Layer::new(EnvFilter::new(directives));
 
     match config.format {
         // Setup a new tracing-subscriber writing to `stdout` for logging.
-        LoggingFormat::Text => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .fmt_fields(formatter)
-            .event_format(FmtEvent::new(config.color, config.abbreviate_modules))
+        LoggingFormat::Text => tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                tracing_fmt::layer()
+                    .with_writer(io::stdout)
+                    .fmt_fields(formatter)
+                    .event_format(FmtEvent::new(config.color, config.abbreviate_modules)),
+            )
             .try_init(),
         // JSON logging writes to `stdout` as well but uses the JSON format.
-        LoggingFormat::Json => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .json()
+        LoggingFormat::Json => tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_fmt::layer().with_writer(io::stdout).json())
             .try_init(),
     }
-    .map_err(|error| anyhow!(error))
+    .map_err(|error| anyhow!(error))?;
+
+    let _ = FILTER_RELOAD_HANDLE.set(reload_handle);
+    Ok(())
+}
+
+/// Replaces the filter of the installed logger with the one given in `config`.
+///
+/// The filter is left unchanged if `config` doesn't specify one, or if its directives are invalid.
+pub fn reload_filter(config: &LoggingConfig) -> anyhow::Result<()> {
+    let directives = match &config.filter {
+        Some(filter) => filter,
+        None => return Ok(()),
+    };
+    let filter = EnvFilter::try_new(directives)?;
+    FILTER_RELOAD_HANDLE
+        .get()
+        .ok_or_else(|| anyhow!("logging has not been initialized"))?
+        .reload(filter)
+        .map_err(|error| anyhow!(error))
 }
@@ -0,0 +1,377 @@
+//! Checks of the runtime environment before starting the node.
+//!
+//! Running `casper-node preflight` performs the checks the node would otherwise only fail on
+//! somewhere during startup, or not at all, and reports the outcome of each of them.  It is meant
+//! for pre-start hooks of orchestration tools, which can refuse to start a node in an environment
+//! it is bound to fail in.
+
+use std::{
+    fmt::Display,
+    fs,
+    net::TcpListener,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use casper_types::SecretKey;
+
+use crate::{
+    components::{clock_monitor::ClockMonitor, disk_watchdog, storage},
+    reactor::validator::Config,
+    tls,
+    types::Chainspec,
+    utils::{self, External, Loadable, WithDir},
+};
+
+/// Permission bits which must not be set on secret key files, i.e. any access by other users.
+const SECRET_KEY_FORBIDDEN_MODE: u32 = 0o007;
+
+/// The outcome of a single check.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckOutcome {
+    /// The check passed.
+    Passed,
+    /// The check failed; the node is not expected to run properly.
+    Failed,
+    /// The check does not apply to the given configuration.
+    Skipped,
+}
+
+/// A single check along with its outcome.
+#[derive(Clone, Debug, Serialize)]
+pub struct PreflightCheck {
+    /// The name of the check.
+    pub name: &'static str,
+    /// The outcome.
+    pub outcome: CheckOutcome,
+    /// What was found, or why the check failed or was skipped.
+    pub detail: String,
+}
+
+impl PreflightCheck {
+    fn new(name: &'static str, outcome: CheckOutcome, detail: impl Display) -> Self {
+        PreflightCheck {
+            name,
+            outcome,
+            detail: detail.to_string(),
+        }
+    }
+
+    /// Creates a check which passed if `result` is `Ok`, and failed otherwise.
+    fn from_result(name: &'static str, result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => PreflightCheck::new(name, CheckOutcome::Passed, detail),
+            Err(detail) => PreflightCheck::new(name, CheckOutcome::Failed, detail),
+        }
+    }
+}
+
+/// The outcomes of all preflight checks.
+#[derive(Clone, Debug, Serialize)]
+pub struct PreflightReport {
+    /// Whether none of the checks failed.
+    pub passed: bool,
+    /// The individual checks, in the order they were run.
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    fn new(checks: Vec<PreflightCheck>) -> Self {
+        let passed = checks
+            .iter()
+            .all(|check| check.outcome != CheckOutcome::Failed);
+        PreflightReport { passed, checks }
+    }
+
+    /// Creates a report for a config file which could not be loaded at all, so that no other
+    /// check can run.
+    pub fn config_not_loaded(error: impl Display) -> Self {
+        PreflightReport::new(vec![PreflightCheck::new(
+            "config",
+            CheckOutcome::Failed,
+            error,
+        )])
+    }
+}
+
+/// Runs all preflight checks against the given config, and the chainspec in the same directory.
+///
+/// The checks only read files and briefly bind the configured ports; nothing is written apart
+/// from the LMDB lock file of an existing database.
+pub fn run_preflight_checks(config: WithDir<Config>) -> PreflightReport {
+    let mut checks = vec![PreflightCheck::from_result(
+        "config",
+        check_config(config.value()),
+    )];
+    checks.push(PreflightCheck::from_result(
+        "chainspec",
+        check_chainspec(config.dir()),
+    ));
+    checks.push(PreflightCheck::from_result("keys", check_keys(&config)));
+    checks.push(PreflightCheck::from_result(
+        "ports",
+        check_ports(config.value()),
+    ));
+
+    let storage_config = config.map_ref(|config| config.storage.clone());
+    let storage_path = storage_config.with_dir(storage_config.value().path.clone());
+    checks.push(PreflightCheck::from_result(
+        "disk_space",
+        check_disk_space(&storage_path, &config.value().disk_watchdog),
+    ));
+    checks.push(match storage::check_database(&storage_config) {
+        Ok(Some(path)) => PreflightCheck::new(
+            "storage",
+            CheckOutcome::Passed,
+            format!("opened {}", path.display()),
+        ),
+        Ok(None) => PreflightCheck::new(
+            "storage",
+            CheckOutcome::Skipped,
+            format!("no database in {} yet", storage_path.display()),
+        ),
+        Err(error) => PreflightCheck::new("storage", CheckOutcome::Failed, error),
+    });
+    checks.push(check_clock(&config.value().clock_monitor));
+
+    PreflightReport::new(checks)
+}
+
+/// Checks the settings the node refuses to start with, or which contradict each other.
+fn check_config(config: &Config) -> Result<String, String> {
+    let mut problems = vec![];
+    if config.network.known_addresses.is_empty() {
+        problems.push("network.known_addresses is empty".to_string());
+    }
+    if let Err(error) = utils::resolve_address(&config.network.public_address) {
+        problems.push(format!("network.public_address: {}", error));
+    }
+    if config.disk_watchdog.read_only_threshold > config.disk_watchdog.warn_threshold {
+        problems.push(
+            "disk_watchdog.read_only_threshold exceeds disk_watchdog.warn_threshold".to_string(),
+        );
+    }
+    if let (Some(key_rotation), External::Path(current_path)) = (
+        &config.consensus.key_rotation,
+        &config.consensus.secret_key_path,
+    ) {
+        if matches!(&key_rotation.secret_key_path, External::Path(path) if path == current_path) {
+            problems.push(
+                "consensus.key_rotation.secret_key_path is the current secret key".to_string(),
+            );
+        }
+    }
+    if problems.is_empty() {
+        Ok("config is consistent".to_string())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Loads the chainspec and reports its hash, so that it can be compared with the network's.
+fn check_chainspec(dir: &Path) -> Result<String, String> {
+    let chainspec = Chainspec::from_path(dir).map_err(|error| error.to_string())?;
+    Ok(format!(
+        "network {}, protocol version {}, hash {}",
+        chainspec.network_config.name,
+        chainspec.protocol_config.version,
+        chainspec.hash()
+    ))
+}
+
+/// Checks that the secret keys can be parsed, and are not accessible to other users.
+fn check_keys(config: &WithDir<Config>) -> Result<String, String> {
+    let consensus_config = &config.value().consensus;
+    let mut secret_keys = vec![(
+        "consensus.secret_key_path",
+        &consensus_config.secret_key_path,
+    )];
+    if let Some(key_rotation) = &consensus_config.key_rotation {
+        secret_keys.push((
+            "consensus.key_rotation.secret_key_path",
+            &key_rotation.secret_key_path,
+        ));
+    }
+
+    let mut checked = vec![];
+    for (name, secret_key) in secret_keys {
+        let path = secret_key_path(config.dir(), name, secret_key)?;
+        SecretKey::from_path(&path)
+            .map_err(|error| format!("{} {}: {}", name, path.display(), error))?;
+        check_secret_key_mode(name, &path)?;
+        checked.push(path);
+    }
+
+    let admin_config = &config.value().rpc_server.admin;
+    if admin_config.enable_server {
+        let path = config.with_dir(admin_config.secret_key.clone());
+        let name = "rpc_server.admin.secret_key";
+        tls::load_private_key(&path)
+            .map_err(|error| format!("{} {}: {}", name, path.display(), error))?;
+        check_secret_key_mode(name, &path)?;
+        checked.push(path);
+    }
+
+    let paths: Vec<_> = checked
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    Ok(format!("loaded {}", paths.join(", ")))
+}
+
+fn secret_key_path(
+    root: &Path,
+    name: &str,
+    secret_key: &External<SecretKey>,
+) -> Result<PathBuf, String> {
+    secret_key
+        .full_path(root)
+        .ok_or_else(|| format!("{} is not set", name))
+}
+
+fn check_secret_key_mode(name: &str, path: &Path) -> Result<(), String> {
+    let metadata =
+        fs::metadata(path).map_err(|error| format!("{} {}: {}", name, path.display(), error))?;
+    let mode = metadata.permissions().mode();
+    if mode & SECRET_KEY_FORBIDDEN_MODE != 0 {
+        return Err(format!(
+            "{} {} is accessible to other users (mode {:o})",
+            name,
+            path.display(),
+            mode & 0o777
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that all enabled servers can bind their configured addresses.
+fn check_ports(config: &Config) -> Result<String, String> {
+    let mut addresses = vec![
+        ("network.bind_address", &config.network.bind_address),
+        ("rpc_server.address", &config.rpc_server.address),
+        ("rest_server.address", &config.rest_server.address),
+        (
+            "event_stream_server.address",
+            &config.event_stream_server.address,
+        ),
+    ];
+    if config.rpc_server.admin.enable_server {
+        addresses.push(("rpc_server.admin.address", &config.rpc_server.admin.address));
+    }
+    if config.rpc_server.grpc.enable_server {
+        addresses.push(("rpc_server.grpc.address", &config.rpc_server.grpc.address));
+    }
+
+    let mut problems = vec![];
+    for (name, address) in &addresses {
+        let result = utils::resolve_address(address)
+            .map_err(|error| error.to_string())
+            .and_then(|address| {
+                TcpListener::bind(address)
+                    .map(drop)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            problems.push(format!("{} {}: {}", name, address, error));
+        }
+    }
+    if problems.is_empty() {
+        Ok(format!("{} addresses can be bound", addresses.len()))
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Checks that the storage volume has more free space than the disk watchdog's read-only
+/// threshold, since the node would not accept deploys otherwise.
+fn check_disk_space(storage_path: &Path, config: &disk_watchdog::Config) -> Result<String, String> {
+    // The storage directory is only created on the first start.
+    let existing_path = storage_path
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(storage_path);
+    let disk_space = disk_watchdog::measure_disk_space(existing_path)
+        .map_err(|error| format!("{}: {}", existing_path.display(), error))?;
+    let detail = format!(
+        "{} of {} bytes available on the volume of {}",
+        disk_space.available,
+        disk_space.total,
+        existing_path.display()
+    );
+    if disk_space.available < config.read_only_threshold {
+        return Err(format!(
+            "{}, below the read-only threshold of {} bytes",
+            detail, config.read_only_threshold
+        ));
+    }
+    Ok(detail)
+}
+
+/// Checks the drift of the local clock against the configured NTP servers, if any.
+fn check_clock(config: &crate::ClockMonitorConfig) -> PreflightCheck {
+    if config.ntp_servers.is_empty() {
+        return PreflightCheck::new("clock", CheckOutcome::Skipped, "no NTP servers configured");
+    }
+    let result = match ClockMonitor::measure_drift(
+        config.ntp_servers.clone(),
+        config.query_timeout.into(),
+    ) {
+        None => Err("no NTP server responded".to_string()),
+        Some(drift_ms) => {
+            let max_drift = config.max_drift_to_propose.unwrap_or(config.warn_threshold);
+            let detail = format!("clock drift of {} ms", drift_ms);
+            if drift_ms.saturating_abs() as u64 > max_drift.millis() {
+                Err(format!("{} exceeds {}", detail, max_drift))
+            } else {
+                Ok(detail)
+            }
+        }
+    };
+    PreflightCheck::from_result("clock", result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::RESOURCES_PATH;
+
+    #[test]
+    fn should_fail_inconsistent_config() {
+        let mut config = Config::default();
+        assert!(check_config(&config)
+            .unwrap_err()
+            .contains("network.known_addresses is empty"));
+
+        config.network.known_addresses = vec!["127.0.0.1:34553".to_string()];
+        config.network.public_address = "127.0.0.1:0".to_string();
+        assert!(check_config(&config).is_ok());
+
+        config.disk_watchdog.read_only_threshold = config.disk_watchdog.warn_threshold + 1;
+        assert!(check_config(&config)
+            .unwrap_err()
+            .contains("disk_watchdog.read_only_threshold"));
+    }
+
+    #[test]
+    fn should_report_chainspec_hash() {
+        let detail = check_chainspec(&RESOURCES_PATH.join("local")).unwrap();
+        assert!(detail.contains("hash"));
+        assert!(check_chainspec(&RESOURCES_PATH.join("missing")).is_err());
+    }
+
+    #[test]
+    fn should_fail_report_if_any_check_failed() {
+        let report = PreflightReport::new(vec![
+            PreflightCheck::new("a", CheckOutcome::Passed, ""),
+            PreflightCheck::new("b", CheckOutcome::Skipped, ""),
+        ]);
+        assert!(report.passed);
+
+        let report = PreflightReport::config_not_loaded("could not read configuration file");
+        assert!(!report.passed);
+        assert_eq!(report.checks[0].outcome, CheckOutcome::Failed);
+    }
+}
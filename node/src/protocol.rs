@@ -8,8 +8,13 @@ use hex_fmt::HexFmt;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    components::{consensus, gossiper, small_network::GossipedAddress},
-    types::{Deploy, FinalitySignature, Item, Tag},
+    components::{
+        consensus, gossiper,
+        small_network::{
+            GossipedAddress, MessageClass, PrioritizedPayload, ServingPayload, SheddablePayload,
+        },
+    },
+    types::{Deploy, FinalitySignature, Item, SharedBytes, Tag},
 };
 
 /// Reactor message.
@@ -36,13 +41,43 @@ pub enum Message {
         /// The type tag of the contained item.
         tag: Tag,
         /// The serialized item.
-        serialized_item: Vec<u8>,
+        ///
+        /// Shares the allocation of the frame it was received in, since items can be large.
+        serialized_item: SharedBytes,
     },
     /// Finality signature.
     #[from]
     FinalitySignature(Box<FinalitySignature>),
 }
 
+impl SheddablePayload for Message {
+    fn is_sheddable(&self) -> bool {
+        matches!(
+            self,
+            Message::DeployGossiper(_) | Message::AddressGossiper(_)
+        )
+    }
+}
+
+impl PrioritizedPayload for Message {
+    fn message_class(&self) -> MessageClass {
+        match self {
+            Message::Consensus(_) | Message::FinalitySignature(_) => MessageClass::Priority,
+            Message::DeployGossiper(_) | Message::AddressGossiper(_) => MessageClass::Gossip,
+            Message::GetRequest { .. } | Message::GetResponse { .. } => MessageClass::Bulk,
+        }
+    }
+}
+
+impl ServingPayload for Message {
+    fn served_tag(&self) -> Option<Tag> {
+        match self {
+            Message::GetResponse { tag, .. } => Some(*tag),
+            _ => None,
+        }
+    }
+}
+
 impl Message {
     pub(crate) fn new_get_request<T: Item>(id: &T::Id) -> Result<Self, bincode::Error> {
         Ok(Message::GetRequest {
@@ -54,7 +89,7 @@ impl Message {
     pub(crate) fn new_get_response<T: Item>(item: &T) -> Result<Self, bincode::Error> {
         Ok(Message::GetResponse {
             tag: T::TAG,
-            serialized_item: bincode::serialize(item)?,
+            serialized_item: bincode::serialize(item)?.into(),
         })
     }
 }
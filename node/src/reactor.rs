@@ -11,6 +11,9 @@
 //! 4. The reactor spawns a task that executes these effects and eventually schedules another event.
 //! 5. meanwhile go to 1.
 //!
+//! Each event carries the [`CorrelationId`](correlation_id/struct.CorrelationId.html) of the
+//! external trigger it stems from, which the events scheduled by its effects inherit.
+//!
 //! # Reactors
 //!
 //! There is no single reactor, but rather a reactor for each application type, since it defines
@@ -24,11 +27,13 @@
 //! in a step-wise manner using [`crank`](struct.Runner.html#method.crank) or indefinitely using
 //! [`run`](struct.Runner.html#method.crank).
 
+pub mod correlation_id;
 mod event_queue_metrics;
 pub mod initializer;
 pub mod initializer2;
 pub mod joiner;
 mod queue_kind;
+pub mod storage_rpc;
 pub mod validator;
 
 #[cfg(test)]
@@ -47,7 +52,7 @@ use datasize::DataSize;
 use futures::{future::BoxFuture, FutureExt};
 use jemalloc_ctl::{epoch as jemalloc_epoch, stats::allocated as jemalloc_allocated};
 use once_cell::sync::Lazy;
-use prometheus::{self, Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+use prometheus::{self, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Registry};
 use quanta::{Clock, IntoNanoseconds};
 use serde::Serialize;
 use signal_hook::consts::signal::{SIGINT, SIGQUIT, SIGTERM};
@@ -57,14 +62,16 @@ use tracing_futures::Instrument;
 use utils::rlimit::{Limit, OpenFiles, ResourceLimit};
 
 use crate::{
-    effect::{Effect, EffectBuilder, Effects},
+    crash_report,
+    effect::{dropped_responses, time_source::TimeSource, Effect, EffectBuilder, Effects},
     types::{ExitCode, Timestamp},
     unregister_metric,
     utils::{self, WeightedRoundRobin},
-    NodeRng, QUEUE_DUMP_REQUESTED, TERMINATION_REQUESTED,
+    NodeRng, CONFIG_RELOAD_REQUESTED, QUEUE_DUMP_REQUESTED, TERMINATION_REQUESTED,
 };
 #[cfg(test)]
 use crate::{reactor::initializer::Reactor as InitializerReactor, types::Chainspec};
+use correlation_id::CorrelationId;
 pub use queue_kind::QueueKind;
 
 /// Optional upper threshold for total RAM allocated in mB before dumping queues to disk.
@@ -82,6 +89,22 @@ static MEM_DUMP_THRESHOLD_MB: Lazy<Option<u64>> = Lazy::new(|| {
         .ok()
 });
 
+/// Optional upper limit for total RAM allocated in MB, above which the node exits with
+/// `ExitCode::OutOfMemory`.
+const MEM_LIMIT_MB_ENV_VAR: &str = "CL_MEM_LIMIT_MB";
+static MEM_LIMIT_MB: Lazy<Option<u64>> = Lazy::new(|| {
+    env::var(MEM_LIMIT_MB_ENV_VAR)
+        .map(|limit_str| {
+            u64::from_str(&limit_str).unwrap_or_else(|error| {
+                panic!(
+                    "can't parse env var {}={} as a u64: {}",
+                    MEM_LIMIT_MB_ENV_VAR, limit_str, error
+                )
+            })
+        })
+        .ok()
+});
+
 /// The desired limit for open files.
 const TARGET_OPEN_FILES_LIMIT: Limit = 64_000;
 
@@ -120,7 +143,26 @@ pub enum ReactorExit {
 /// is the central hook for any part of the program that schedules events directly.
 ///
 /// Components rarely use this, but use a bound `EventQueueHandle` instead.
-pub type Scheduler<Ev> = WeightedRoundRobin<Ev, QueueKind>;
+pub type Scheduler<Ev> = WeightedRoundRobin<QueuedEvent<Ev>, QueueKind>;
+
+/// An event on the event queue, along with the correlation ID of the trigger it stems from.
+#[derive(Debug, Serialize)]
+pub struct QueuedEvent<Ev> {
+    /// The event.
+    pub(crate) event: Ev,
+    /// The correlation ID of the event.
+    pub(crate) correlation_id: CorrelationId,
+}
+
+impl<Ev> QueuedEvent<Ev> {
+    /// Creates a queued event with the correlation ID of the current task.
+    fn new(event: Ev) -> Self {
+        QueuedEvent {
+            event,
+            correlation_id: CorrelationId::current_or_new(),
+        }
+    }
+}
 
 /// Event queue handle
 ///
@@ -128,30 +170,72 @@ pub type Scheduler<Ev> = WeightedRoundRobin<Ev, QueueKind>;
 /// outside of the normal event loop. It gives different parts a chance to schedule messages that
 /// stem from things like external IO.
 #[derive(DataSize, Debug)]
-pub struct EventQueueHandle<REv>(&'static Scheduler<REv>)
+pub struct EventQueueHandle<REv>(&'static Scheduler<REv>, TimeSource)
 where
     REv: 'static;
 
 // Implement `Clone` and `Copy` manually, as `derive` will make it depend on `R` and `Ev` otherwise.
 impl<REv> Clone for EventQueueHandle<REv> {
     fn clone(&self) -> Self {
-        EventQueueHandle(self.0)
+        EventQueueHandle(self.0, self.1)
     }
 }
 impl<REv> Copy for EventQueueHandle<REv> {}
 
 impl<REv> EventQueueHandle<REv> {
     pub(crate) fn new(scheduler: &'static Scheduler<REv>) -> Self {
-        EventQueueHandle(scheduler)
+        EventQueueHandle(scheduler, TimeSource::Real)
+    }
+
+    /// Creates a handle whose effects read the time from `time_source`.
+    pub(crate) fn with_time_source(
+        scheduler: &'static Scheduler<REv>,
+        time_source: TimeSource,
+    ) -> Self {
+        EventQueueHandle(scheduler, time_source)
+    }
+
+    /// Returns the source of time of the reactor.
+    pub(crate) fn time_source(&self) -> TimeSource {
+        self.1
     }
 
     /// Schedule an event on a specific queue.
+    ///
+    /// The event inherits the correlation ID of the effect run by the current task, if any.
     #[inline]
     pub(crate) async fn schedule<Ev>(self, event: Ev, queue_kind: QueueKind)
     where
         REv: From<Ev>,
     {
-        self.0.push(event.into(), queue_kind).await
+        self.0
+            .push(QueuedEvent::new(event.into()), queue_kind)
+            .await
+    }
+
+    /// Schedule an event of approximately `size` bytes on a specific queue.
+    ///
+    /// If `sheddable` is set, the event may be dropped instead if the scheduler's memory budget is
+    /// exceeded. Returns the number of events dropped to honor the budget.
+    #[inline]
+    pub(crate) async fn schedule_sized<Ev>(
+        self,
+        event: Ev,
+        queue_kind: QueueKind,
+        size: usize,
+        sheddable: bool,
+    ) -> usize
+    where
+        REv: From<Ev>,
+    {
+        self.0
+            .push_sized(QueuedEvent::new(event.into()), queue_kind, size, sheddable)
+            .await
+    }
+
+    /// Sets the combined size in bytes of queued events above which sheddable events are dropped.
+    pub(crate) fn set_memory_budget(&self, memory_budget: usize) {
+        self.0.set_memory_budget(memory_budget)
     }
 
     /// Returns number of events in each of the scheduler's queues.
@@ -159,6 +243,18 @@ impl<REv> EventQueueHandle<REv> {
     pub(crate) fn event_queues_counts(&self) -> HashMap<QueueKind, usize> {
         self.0.event_queues_counts()
     }
+
+    /// Returns the approximate combined size in bytes of all queued events.
+    #[inline]
+    pub(crate) fn queued_bytes(&self) -> usize {
+        self.0.queued_bytes()
+    }
+
+    /// Returns the number of events dropped so far due to the memory budget being exceeded.
+    #[inline]
+    pub(crate) fn shed_count(&self) -> usize {
+        self.0.shed_count()
+    }
 }
 
 /// Reactor core.
@@ -210,6 +306,18 @@ pub trait Reactor: Sized {
 
     /// Instructs the reactor to update performance metrics, if any.
     fn update_metrics(&mut self, _event_queue_handle: EventQueueHandle<Self::Event>) {}
+
+    /// Reloads the subset of the configuration which can be changed while the reactor is running.
+    ///
+    /// Reactors which don't support this ignore the request.
+    fn reload_config(
+        &mut self,
+        _effect_builder: EffectBuilder<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> Effects<Self::Event> {
+        debug!("reactor does not support reloading its configuration");
+        Effects::new()
+    }
 }
 
 /// A drop-like trait for `async` compatible drop-and-wait.
@@ -247,6 +355,9 @@ where
     /// The scheduler used for the reactor.
     scheduler: &'static Scheduler<R::Event>,
 
+    /// The source of time of the reactor's effects.
+    time_source: TimeSource,
+
     /// The reactor instance itself.
     reactor: R,
 
@@ -285,6 +396,8 @@ struct RunnerMetrics {
     consumed_ram_bytes: IntGauge,
     /// Total system RAM in bytes, as reported by sys-info.
     total_ram_bytes: IntGauge,
+    /// Number of responses dropped because the requester went away, per request type.
+    dropped_responses: IntCounterVec,
     /// Handle to the metrics registry, in case we need to unregister.
     registry: Registry,
 }
@@ -334,6 +447,7 @@ impl RunnerMetrics {
         registry.register(Box::new(allocated_ram_bytes.clone()))?;
         registry.register(Box::new(consumed_ram_bytes.clone()))?;
         registry.register(Box::new(total_ram_bytes.clone()))?;
+        let dropped_responses = dropped_responses::register(registry)?;
 
         Ok(RunnerMetrics {
             events,
@@ -342,6 +456,7 @@ impl RunnerMetrics {
             allocated_ram_bytes,
             consumed_ram_bytes,
             total_ram_bytes,
+            dropped_responses,
         })
     }
 }
@@ -353,6 +468,7 @@ impl Drop for RunnerMetrics {
         unregister_metric!(self.registry, self.allocated_ram_bytes);
         unregister_metric!(self.registry, self.consumed_ram_bytes);
         unregister_metric!(self.registry, self.total_ram_bytes);
+        unregister_metric!(self.registry, self.dropped_responses);
     }
 }
 
@@ -378,6 +494,28 @@ where
         cfg: R::Config,
         rng: &mut NodeRng,
         registry: &Registry,
+    ) -> Result<Self, R::Error> {
+        Self::with_time_source(cfg, rng, registry, TimeSource::Real).await
+    }
+
+    /// Creates a new runner whose reactor reads the time from `time_source`.
+    #[cfg(test)]
+    pub(crate) async fn new_with_time_source(
+        cfg: R::Config,
+        rng: &mut NodeRng,
+        time_source: TimeSource,
+    ) -> Result<Self, R::Error> {
+        let registry = Registry::new();
+        Self::with_time_source(cfg, rng, &registry, time_source).await
+    }
+
+    /// Creates a new runner from a given configuration, using existing metrics and the given
+    /// source of time.
+    async fn with_time_source(
+        cfg: R::Config,
+        rng: &mut NodeRng,
+        registry: &Registry,
+        time_source: TimeSource,
     ) -> Result<Self, R::Error> {
         // Ensure we have reasonable ulimits.
         match ResourceLimit::<OpenFiles>::get() {
@@ -424,12 +562,13 @@ where
 
         let scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
 
-        let event_queue = EventQueueHandle::new(scheduler);
+        let event_queue = EventQueueHandle::with_time_source(scheduler, time_source);
         let (reactor, initial_effects) = R::new(cfg, registry, event_queue, rng)?;
 
         // Run all effects from component instantiation.
-        let span = debug_span!("process initial effects");
-        process_effects(scheduler, initial_effects)
+        let correlation_id = CorrelationId::new();
+        let span = debug_span!("process initial effects", cid = %correlation_id);
+        process_effects(scheduler, initial_effects, correlation_id)
             .instrument(span)
             .await;
 
@@ -437,6 +576,7 @@ where
 
         Ok(Runner {
             scheduler,
+            time_source,
             reactor,
             event_count: 0,
             metrics: RunnerMetrics::new(registry)?,
@@ -455,13 +595,18 @@ where
     where
         F: FnOnce(EffectBuilder<R::Event>) -> Effects<R::Event>,
     {
-        let event_queue = EventQueueHandle::new(self.scheduler);
+        let event_queue = EventQueueHandle::with_time_source(self.scheduler, self.time_source);
         let effect_builder = EffectBuilder::new(event_queue);
 
         let effects = create_effects(effect_builder);
 
-        let effect_span = debug_span!("process injected effects", ev = self.event_count);
-        process_effects(self.scheduler, effects)
+        let correlation_id = CorrelationId::new();
+        let effect_span = debug_span!(
+            "process injected effects",
+            ev = self.event_count,
+            cid = %correlation_id
+        );
+        process_effects(self.scheduler, effects, correlation_id)
             .instrument(effect_span)
             .await;
     }
@@ -475,7 +620,7 @@ where
 
         self.metrics.events.inc();
 
-        let event_queue = EventQueueHandle::new(self.scheduler);
+        let event_queue = EventQueueHandle::with_time_source(self.scheduler, self.time_source);
         let effect_builder = EffectBuilder::new(event_queue);
 
         // Update metrics like memory usage and event queue sizes.
@@ -512,6 +657,19 @@ where
                         self.dump_queues().await;
                     }
                 }
+                if let Some(limit_mb) = *MEM_LIMIT_MB {
+                    if allocated >= limit_mb * 1024 * 1024 {
+                        crash_report::fatal_error(
+                            ExitCode::OutOfMemory,
+                            file!(),
+                            line!(),
+                            format!(
+                                "allocated {} bytes, exceeding the limit of {} MB",
+                                allocated, limit_mb
+                            ),
+                        );
+                    }
+                }
             }
         }
 
@@ -523,16 +681,34 @@ where
             QUEUE_DUMP_REQUESTED.store(false, Ordering::SeqCst);
         }
 
-        let (event, q) = self.scheduler.pop().await;
+        // Reload the configuration if requested.
+        if CONFIG_RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+            info!("reloading configuration as requested");
+            let effects = self.reactor.reload_config(effect_builder, rng);
+            process_effects(self.scheduler, effects, CorrelationId::new()).await;
+        }
+
+        let (
+            QueuedEvent {
+                event,
+                correlation_id,
+            },
+            q,
+        ) = self.scheduler.pop().await;
 
         // Create another span for tracing the processing of one event.
-        let event_span = debug_span!("dispatch events", ev = self.event_count);
+        let event_span = debug_span!(
+            "dispatch events",
+            ev = self.event_count,
+            cid = %correlation_id
+        );
         let inner_enter = event_span.enter();
 
         // We log events twice, once in display and once in debug mode.
         let event_as_string = format!("{}", event);
         debug!(event=%event_as_string, ?q);
         trace!(?event, ?q);
+        crash_report::record_event(q, &event_as_string);
 
         // Dispatch the event, then execute the resulting effect.
         let start = self.clock.start();
@@ -554,10 +730,14 @@ where
 
         drop(inner_enter);
 
-        // We create another span for the effects, but will keep the same ID.
-        let effect_span = debug_span!("process effects", ev = self.event_count);
+        // We create another span for the effects, but will keep the same IDs.
+        let effect_span = debug_span!(
+            "process effects",
+            ev = self.event_count,
+            cid = %correlation_id
+        );
 
-        process_effects(self.scheduler, effects)
+        process_effects(self.scheduler, effects, correlation_id)
             .instrument(effect_span)
             .await;
 
@@ -709,8 +889,9 @@ impl Runner<InitializerReactor> {
             InitializerReactor::new_with_chainspec(cfg, &registry, event_queue, chainspec)?;
 
         // Run all effects from component instantiation.
-        let span = debug_span!("process initial effects");
-        process_effects(scheduler, initial_effects)
+        let correlation_id = CorrelationId::new();
+        let span = debug_span!("process initial effects", cid = %correlation_id);
+        process_effects(scheduler, initial_effects, correlation_id)
             .instrument(span)
             .await;
 
@@ -718,6 +899,7 @@ impl Runner<InitializerReactor> {
 
         Ok(Runner {
             scheduler,
+            time_source: TimeSource::Real,
             reactor,
             event_count: 0,
             metrics: RunnerMetrics::new(&registry)?,
@@ -731,20 +913,35 @@ impl Runner<InitializerReactor> {
 }
 
 /// Spawns tasks that will process the given effects.
+///
+/// The effects run with `correlation_id` as the current correlation ID, which the events they
+/// return or schedule inherit.
 #[inline]
-async fn process_effects<Ev>(scheduler: &'static Scheduler<Ev>, effects: Effects<Ev>)
-where
+async fn process_effects<Ev>(
+    scheduler: &'static Scheduler<Ev>,
+    effects: Effects<Ev>,
+    correlation_id: CorrelationId,
+) where
     Ev: Send + 'static,
 {
     // TODO: Properly carry around priorities.
     let queue_kind = QueueKind::default();
 
     for effect in effects {
-        tokio::spawn(async move {
-            for event in effect.await {
-                scheduler.push(event, queue_kind).await
-            }
-        });
+        let span = debug_span!("effect", cid = %correlation_id);
+        tokio::spawn(
+            correlation_id
+                .scope(async move {
+                    for event in effect.await {
+                        let queued_event = QueuedEvent {
+                            event,
+                            correlation_id,
+                        };
+                        scheduler.push(queued_event, queue_kind).await
+                    }
+                })
+                .instrument(span),
+        );
     }
 }
 
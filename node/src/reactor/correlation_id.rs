@@ -0,0 +1,81 @@
+//! Correlation IDs.
+//!
+//! Every event is tagged with the correlation ID of the trigger from outside the reactor it stems
+//! from, such as a message received from a peer or an API request.  Events scheduled by the effects
+//! of an event inherit its ID, so all log lines caused by the same trigger, across components, can
+//! be found by the `cid` field of the `dispatch events` and `process effects` spans.
+//!
+//! The ID of the effect a task is running is held in a task-local variable, which lets
+//! `EventQueueHandle::schedule` pick it up without threading it through every component.  Code
+//! scheduling events outside of an effect, like the API servers' tasks, starts a new ID for each
+//! event.  A long-running effect that schedules events for multiple triggers, like a connection's
+//! message reader, starts a new ID for each of them explicitly, via `CorrelationId::new().scope`.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use datasize::DataSize;
+use serde::Serialize;
+
+/// The next correlation ID to hand out.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    /// The correlation ID of the effect run by the current task.
+    static CURRENT_ID: CorrelationId;
+}
+
+/// The ID shared by all events stemming from the same external trigger.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    /// Returns a new ID, for a new trigger.
+    #[allow(clippy::new_without_default)]
+    pub(crate) fn new() -> Self {
+        CorrelationId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the ID of the effect run by the current task, or a new ID if it isn't running one.
+    pub(crate) fn current_or_new() -> Self {
+        CURRENT_ID
+            .try_with(|id| *id)
+            .unwrap_or_else(|_| CorrelationId::new())
+    }
+
+    /// Runs `future` with `self` as the current ID, inherited by all events it schedules.
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_ID.scope(self, future).await
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_inherit_id_within_scope() {
+        let id = CorrelationId::new();
+        let (inner, nested) = id
+            .scope(async {
+                let inner = CorrelationId::current_or_new();
+                let nested = CorrelationId::new()
+                    .scope(async { CorrelationId::current_or_new() })
+                    .await;
+                (inner, nested)
+            })
+            .await;
+        assert_eq!(inner, id);
+        assert_ne!(nested, id);
+        assert_ne!(CorrelationId::current_or_new(), id);
+    }
+}
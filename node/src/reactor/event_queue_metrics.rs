@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use prometheus::{self, IntGauge, Registry};
+use prometheus::{self, IntCounter, IntGauge, Registry};
 use tracing::{debug, error};
 
 use crate::{
@@ -16,6 +16,10 @@ pub(super) struct EventQueueMetrics {
     event_queue_gauges: HashMap<QueueKind, IntGauge>,
     /// Total events count.
     event_total: IntGauge,
+    /// Approximate size of all sized events in the queues.
+    queued_bytes: IntGauge,
+    /// Number of events dropped because the queues exceeded their memory budget.
+    shed_events: IntCounter,
     /// Instance of registry to unregister from when being dropped.
     registry: Registry,
 }
@@ -41,9 +45,23 @@ impl EventQueueMetrics {
         )?;
         registry.register(Box::new(event_total.clone()))?;
 
+        let queued_bytes = IntGauge::new(
+            "scheduler_queue_bytes",
+            "approximate size in bytes of network-derived events in queues.",
+        )?;
+        registry.register(Box::new(queued_bytes.clone()))?;
+
+        let shed_events = IntCounter::new(
+            "scheduler_shed_events",
+            "number of gossip events dropped because queues exceeded their memory budget.",
+        )?;
+        registry.register(Box::new(shed_events.clone()))?;
+
         Ok(EventQueueMetrics {
             event_queue_gauges,
             event_total,
+            queued_bytes,
+            shed_events,
             registry,
         })
     }
@@ -60,6 +78,13 @@ impl EventQueueMetrics {
         let total = event_queue_count.values().sum::<usize>() as i64;
         self.event_total.set(total);
 
+        self.queued_bytes
+            .set(event_queue_handle.queued_bytes() as i64);
+        let newly_shed = event_queue_handle.shed_count() as i64 - self.shed_events.get();
+        if newly_shed > 0 {
+            self.shed_events.inc_by(newly_shed);
+        }
+
         let event_counts: String = event_queue_count
             .iter()
             .sorted_by_key(|k| k.0)
@@ -80,6 +105,8 @@ impl EventQueueMetrics {
 impl Drop for EventQueueMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.event_total);
+        unregister_metric!(self.registry, self.queued_bytes);
+        unregister_metric!(self.registry, self.shed_events);
         self.event_queue_gauges
             .iter()
             .for_each(|(key, queue_gauge)| {
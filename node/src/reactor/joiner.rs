@@ -6,6 +6,7 @@ use std::{
     collections::BTreeMap,
     env,
     fmt::{self, Display, Formatter},
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -14,7 +15,7 @@ use derive_more::From;
 use memory_metrics::MemoryMetrics;
 use prometheus::Registry;
 use serde::Serialize;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, trace, warn};
 
 #[cfg(not(feature = "fast-sync"))]
 use crate::components::linear_chain_sync::{self, LinearChainSync};
@@ -28,9 +29,10 @@ use crate::testing::network::NetworkedReactor;
 use crate::{
     components::{
         block_executor::{self, BlockExecutor},
-        block_validator::{self, BlockValidator},
+        block_exporter::{self, BlockExporter},
+        block_validator::{self, BlockValidator, DeployFetchMode},
         chainspec_loader::{self, ChainspecLoader},
-        consensus::{self, EraSupervisor, HighwayProtocol},
+        consensus::{self, DeferredMessages, EraSupervisor, HighwayProtocol},
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
         event_stream_server,
@@ -38,18 +40,19 @@ use crate::{
         fetcher::{self, Fetcher},
         gossiper::{self, Gossiper},
         linear_chain,
-        metrics::Metrics,
+        metrics::{self, Metrics},
         network::{self, Network, NetworkIdentity, ENABLE_LIBP2P_NET_ENV_VAR},
         rest_server::{self, RestServer},
         small_network::{self, GossipedAddress, SmallNetwork, SmallNetworkIdentity},
         storage::{self, Storage},
         Component,
     },
+    crypto::SignatureVerifier,
     effect::{
         announcements::{
             BlockExecutorAnnouncement, ChainspecLoaderAnnouncement, ConsensusAnnouncement,
             DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
-            NetworkAnnouncement,
+            LinearChainSyncAnnouncement, NetworkAnnouncement,
         },
         requests::{
             BlockExecutorRequest, BlockProposerRequest, BlockValidationRequest,
@@ -67,8 +70,11 @@ use crate::{
         validator::{self, Error, ValidatorInitConfig},
         EventQueueHandle, Finalize, ReactorExit,
     },
-    types::{Block, BlockByHeight, Deploy, ExitCode, NodeId, ProtoBlock, Tag, Timestamp},
-    utils::{Source, WithDir},
+    types::{
+        Block, BlockByHeight, BlockExecutionResults, BlockHeader, Deploy, DeployChunk,
+        DeployHeaderById, ExitCode, NodeId, ProtoBlock, Tag, Timestamp,
+    },
+    utils::{Scheduler, Source, SyncProgress, WithDir},
     NodeRng,
 };
 use casper_types::{PublicKey, U512};
@@ -98,6 +104,14 @@ pub enum Event {
     /// Event stream server event.
     EventStreamServer(#[serde(skip_serializing)] event_stream_server::Event),
 
+    /// Block exporter event.
+    #[from]
+    BlockExporter(#[serde(skip_serializing)] block_exporter::Event),
+
+    /// Metrics event.
+    #[from]
+    Metrics(#[serde(skip_serializing)] metrics::Event),
+
     /// Metrics request.
     #[from]
     MetricsRequest(#[serde(skip_serializing)] MetricsRequest),
@@ -122,10 +136,22 @@ pub enum Event {
     #[from]
     BlockByHeightFetcher(#[serde(skip_serializing)] fetcher::Event<BlockByHeight>),
 
+    /// Block header fetcher event.
+    #[from]
+    BlockHeaderFetcher(#[serde(skip_serializing)] fetcher::Event<BlockHeader>),
+
+    /// Block execution results fetcher event.
+    #[from]
+    BlockExecutionResultsFetcher(#[serde(skip_serializing)] fetcher::Event<BlockExecutionResults>),
+
     /// Deploy fetcher event.
     #[from]
     DeployFetcher(#[serde(skip_serializing)] fetcher::Event<Deploy>),
 
+    /// Deploy header fetcher event.
+    #[from]
+    DeployHeaderFetcher(#[serde(skip_serializing)] fetcher::Event<DeployHeaderById>),
+
     /// Deploy acceptor event.
     #[from]
     DeployAcceptor(#[serde(skip_serializing)] deploy_acceptor::Event),
@@ -167,10 +193,24 @@ pub enum Event {
     #[from]
     BlockByHeightFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, BlockByHeight>),
 
+    /// Block header fetcher request.
+    #[from]
+    BlockHeaderFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, BlockHeader>),
+
+    /// Block execution results fetcher request.
+    #[from]
+    BlockExecutionResultsFetcherRequest(
+        #[serde(skip_serializing)] FetcherRequest<NodeId, BlockExecutionResults>,
+    ),
+
     /// Deploy fetcher request.
     #[from]
     DeployFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, Deploy>),
 
+    /// Deploy header fetcher request.
+    #[from]
+    DeployHeaderFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, DeployHeaderById>),
+
     /// Block validation request.
     #[from]
     BlockValidatorRequest(#[serde(skip_serializing)] BlockValidationRequest<Block, NodeId>),
@@ -221,6 +261,10 @@ pub enum Event {
     /// Chainspec loader announcement.
     #[from]
     ChainspecLoaderAnnouncement(#[serde(skip_serializing)] ChainspecLoaderAnnouncement),
+
+    /// Linear chain synchronizer announcement.
+    #[from]
+    LinearChainSyncAnnouncement(#[serde(skip_serializing)] LinearChainSyncAnnouncement<NodeId>),
 }
 
 impl From<LinearChainRequest<NodeId>> for Event {
@@ -280,6 +324,8 @@ impl Display for Event {
             Event::Storage(request) => write!(f, "storage: {}", request),
             Event::RestServer(event) => write!(f, "rest server: {}", event),
             Event::EventStreamServer(event) => write!(f, "event stream server: {}", event),
+            Event::BlockExporter(event) => write!(f, "block exporter: {}", event),
+            Event::Metrics(event) => write!(f, "metrics: {}", event),
             Event::MetricsRequest(req) => write!(f, "metrics request: {}", req),
             Event::ChainspecLoader(event) => write!(f, "chainspec loader: {}", event),
             Event::ChainspecLoaderRequest(req) => write!(f, "chainspec loader request: {}", req),
@@ -291,13 +337,25 @@ impl Display for Event {
             Event::DeployFetcherRequest(request) => {
                 write!(f, "deploy fetcher request: {}", request)
             }
+            Event::DeployHeaderFetcherRequest(request) => {
+                write!(f, "deploy header fetcher request: {}", request)
+            }
             Event::LinearChainSync(event) => write!(f, "linear chain: {}", event),
             Event::BlockFetcher(event) => write!(f, "block fetcher: {}", event),
             Event::BlockByHeightFetcherRequest(request) => {
                 write!(f, "block by height fetcher request: {}", request)
             }
+            Event::BlockHeaderFetcherRequest(request) => {
+                write!(f, "block header fetcher request: {}", request)
+            }
+            Event::BlockExecutionResultsFetcherRequest(request) => {
+                write!(f, "block execution results fetcher request: {}", request)
+            }
             Event::BlockValidator(event) => write!(f, "block validator event: {}", event),
             Event::DeployFetcher(event) => write!(f, "deploy fetcher event: {}", event),
+            Event::DeployHeaderFetcher(event) => {
+                write!(f, "deploy header fetcher event: {}", event)
+            }
             Event::BlockExecutor(event) => write!(f, "block executor event: {}", event),
             Event::BlockExecutorRequest(request) => {
                 write!(f, "block executor request: {}", request)
@@ -318,6 +376,10 @@ impl Display for Event {
             Event::BlockByHeightFetcher(event) => {
                 write!(f, "block by height fetcher event: {}", event)
             }
+            Event::BlockHeaderFetcher(event) => write!(f, "block header fetcher event: {}", event),
+            Event::BlockExecutionResultsFetcher(event) => {
+                write!(f, "block execution results fetcher event: {}", event)
+            }
             Event::DeployAcceptorAnnouncement(ann) => {
                 write!(f, "deploy acceptor announcement: {}", ann)
             }
@@ -326,6 +388,9 @@ impl Display for Event {
             Event::ChainspecLoaderAnnouncement(ann) => {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
+            Event::LinearChainSyncAnnouncement(ann) => {
+                write!(f, "linear chain sync announcement: {}", ann)
+            }
             Event::StateStoreRequest(req) => write!(f, "state store request: {}", req),
         }
     }
@@ -339,6 +404,8 @@ pub struct Reactor {
     small_network: SmallNetwork<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
     config: validator::Config,
+    /// The directory relative paths in `config` are resolved against.
+    root: PathBuf,
     chainspec_loader: ChainspecLoader,
     storage: Storage,
     contract_runtime: ContractRuntime,
@@ -346,11 +413,16 @@ pub struct Reactor {
     linear_chain_sync: LinearChainSync<NodeId>,
     block_validator: BlockValidator<Block, NodeId>,
     deploy_fetcher: Fetcher<Deploy>,
+    deploy_header_fetcher: Fetcher<DeployHeaderById>,
     block_executor: BlockExecutor,
     linear_chain: linear_chain::LinearChain<NodeId>,
     consensus: EraSupervisor<NodeId>,
+    /// Consensus messages for eras not handled yet, to be passed on to the validator reactor.
+    deferred_consensus_messages: DeferredMessages<NodeId>,
     // Handles request for linear chain block by height.
     block_by_height_fetcher: Fetcher<BlockByHeight>,
+    block_header_fetcher: Fetcher<BlockHeader>,
+    block_execution_results_fetcher: Fetcher<BlockExecutionResults>,
     #[data_size(skip)]
     deploy_acceptor: DeployAcceptor,
     #[data_size(skip)]
@@ -358,7 +430,10 @@ pub struct Reactor {
     #[data_size(skip)]
     rest_server: RestServer,
     #[data_size(skip)]
+    sync_progress: SyncProgress,
+    #[data_size(skip)]
     event_stream_server: EventStreamServer,
+    block_exporter: BlockExporter,
     // Attach memory metrics for the joiner.
     #[data_size(skip)] // Never allocates data on the heap.
     memory_metrics: MemoryMetrics,
@@ -376,7 +451,7 @@ impl reactor::Reactor for Reactor {
         initializer: Self::Config,
         registry: &Registry,
         event_queue: EventQueueHandle<Self::Event>,
-        _rng: &mut NodeRng,
+        rng: &mut NodeRng,
     ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
         let (root, initializer) = initializer.into_parts();
 
@@ -396,7 +471,8 @@ impl reactor::Reactor for Reactor {
 
         let event_queue_metrics = EventQueueMetrics::new(registry.clone(), event_queue)?;
 
-        let metrics = Metrics::new(registry.clone());
+        let effect_builder = EffectBuilder::new(event_queue);
+        let (metrics, metrics_effects) = Metrics::new(registry.clone(), effect_builder)?;
 
         let network_config = network::Config::from(&config.network);
         let (network, network_effects) = Network::new(
@@ -419,7 +495,8 @@ impl reactor::Reactor for Reactor {
 
         let linear_chain_fetcher = Fetcher::new("linear_chain", config.fetcher, &registry)?;
 
-        let mut effects = reactor::wrap_effects(Event::Network, network_effects);
+        let mut effects = reactor::wrap_effects(Event::Metrics, metrics_effects);
+        effects.extend(reactor::wrap_effects(Event::Network, network_effects));
         effects.extend(reactor::wrap_effects(
             Event::SmallNetwork,
             small_network_effects,
@@ -428,15 +505,13 @@ impl reactor::Reactor for Reactor {
         let address_gossiper =
             Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
 
-        let effect_builder = EffectBuilder::new(event_queue);
-
-        let init_hash = config
-            .node
-            .trusted_hash
-            .or_else(|| chainspec_loader.initial_block_hash());
+        let mut trusted_hashes = config.node.trusted_hashes();
+        if trusted_hashes.is_empty() {
+            trusted_hashes.extend(chainspec_loader.initial_block_hash());
+        }
 
-        match init_hash {
-            None => {
+        match trusted_hashes.as_slice() {
+            [] => {
                 let chainspec = chainspec_loader.chainspec();
                 let era_duration = chainspec.core_config.era_duration;
                 if let Some(start_time) = chainspec
@@ -457,36 +532,78 @@ impl reactor::Reactor for Reactor {
                 }
                 info!("No synchronization of the linear chain will be done.")
             }
-            Some(hash) => info!("Synchronizing linear chain from: {:?}", hash),
+            [hash] => info!("Synchronizing linear chain from: {:?}", hash),
+            hashes => info!(
+                "Synchronizing linear chain from the highest of: {:?}",
+                hashes
+            ),
         }
 
         let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
+        let scheduler = Scheduler::new(rng);
+        let sync_progress = SyncProgress::default();
         let rest_server = RestServer::new(
             config.rest_server.clone(),
             effect_builder,
             protocol_version.clone(),
+            registry,
+            scheduler,
+            config.event_stream_server.enabled_events(),
+            sync_progress.clone(),
+            rng,
         )?;
 
         let event_stream_server =
             EventStreamServer::new(config.event_stream_server.clone(), protocol_version.clone())?;
 
-        let block_validator = BlockValidator::new(Arc::clone(&chainspec_loader.chainspec()));
+        let block_exporter = BlockExporter::new(
+            WithDir::new(root.clone(), config.block_exporter.clone()),
+            registry,
+        )?;
+
+        let block_validator = BlockValidator::new(
+            Arc::clone(&chainspec_loader.chainspec()),
+            config.block_validator,
+            // The deploys of synchronized blocks are executed once validated.
+            DeployFetchMode::FullDeploys,
+        );
 
         let deploy_fetcher = Fetcher::new("deploy", config.fetcher, &registry)?;
 
+        let deploy_header_fetcher = Fetcher::new("deploy_header", config.fetcher, &registry)?;
+
         let block_by_height_fetcher = Fetcher::new("block_by_height", config.fetcher, &registry)?;
 
+        let block_header_fetcher = Fetcher::new("block_header", config.fetcher, &registry)?;
+
+        let block_execution_results_fetcher =
+            Fetcher::new("block_execution_results", config.fetcher, &registry)?;
+
         let deploy_acceptor =
             DeployAcceptor::new(config.deploy_acceptor, &*chainspec_loader.chainspec());
 
         let block_executor = BlockExecutor::new(
+            config.block_executor,
             chainspec_loader.initial_state_root_hash(),
             chainspec_loader.initial_block_header(),
             protocol_version.clone(),
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .checksum_registry_activation_era,
             registry.clone(),
         );
 
-        let linear_chain = linear_chain::LinearChain::new(&registry)?;
+        let signature_verifier =
+            SignatureVerifier::new(config.node.signature_verification_threads)?;
+        let linear_chain = linear_chain::LinearChain::new(
+            &registry,
+            signature_verifier,
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .finality_threshold_fraction,
+        )?;
 
         let validator_weights: BTreeMap<PublicKey, U512> = chainspec_loader
             .chainspec()
@@ -500,12 +617,15 @@ impl reactor::Reactor for Reactor {
             .map(|next_upgrade| next_upgrade.activation_point());
         let linear_chain_sync = LinearChainSync::new::<Error>(
             registry,
+            config.linear_chain_sync,
             chainspec_loader.chainspec(),
             &storage,
-            init_hash,
+            trusted_hashes,
             chainspec_loader.initial_block_header().cloned(),
             validator_weights,
             maybe_next_activation_point,
+            config.node.minimal_join,
+            sync_progress.clone(),
         )?;
 
         // Used to decide whether era should be activated.
@@ -514,7 +634,7 @@ impl reactor::Reactor for Reactor {
         let (consensus, init_consensus_effects) = EraSupervisor::new(
             timestamp,
             chainspec_loader.initial_era(),
-            WithDir::new(root, config.consensus.clone()),
+            WithDir::new(root.clone(), config.consensus.clone()),
             effect_builder,
             chainspec_loader.chainspec().as_ref().into(),
             chainspec_loader.initial_state_root_hash(),
@@ -527,6 +647,11 @@ impl reactor::Reactor for Reactor {
             init_consensus_effects,
         ));
 
+        let deferred_consensus_messages = DeferredMessages::new(
+            config.consensus.max_deferred_eras,
+            config.consensus.max_deferred_messages_per_era,
+        );
+
         Ok((
             Self {
                 metrics,
@@ -534,6 +659,7 @@ impl reactor::Reactor for Reactor {
                 small_network,
                 address_gossiper,
                 config,
+                root,
                 chainspec_loader,
                 storage,
                 contract_runtime,
@@ -541,14 +667,20 @@ impl reactor::Reactor for Reactor {
                 linear_chain_fetcher,
                 block_validator,
                 deploy_fetcher,
+                deploy_header_fetcher,
                 block_executor,
                 linear_chain,
                 consensus,
+                deferred_consensus_messages,
                 block_by_height_fetcher,
+                block_header_fetcher,
+                block_execution_results_fetcher,
                 deploy_acceptor,
                 event_queue_metrics,
                 rest_server,
+                sync_progress,
                 event_stream_server,
+                block_exporter,
                 memory_metrics,
             },
             effects,
@@ -631,6 +763,48 @@ impl reactor::Reactor for Reactor {
                     };
                     self.dispatch_event(effect_builder, rng, Event::BlockByHeightFetcher(event))
                 }
+                Message::GetResponse {
+                    tag: Tag::BlockHeader,
+                    serialized_item,
+                } => {
+                    let header: BlockHeader = match bincode::deserialize(&serialized_item) {
+                        Ok(header) => header,
+                        Err(err) => {
+                            error!("failed to decode block header from {}: {}", sender, err);
+                            return Effects::new();
+                        }
+                    };
+                    let event = fetcher::Event::GotRemotely {
+                        item: Box::new(header),
+                        source: Source::Peer(sender),
+                    };
+                    self.dispatch_event(effect_builder, rng, Event::BlockHeaderFetcher(event))
+                }
+                Message::GetResponse {
+                    tag: Tag::BlockExecutionResults,
+                    serialized_item,
+                } => {
+                    let results: BlockExecutionResults =
+                        match bincode::deserialize(&serialized_item) {
+                            Ok(results) => results,
+                            Err(err) => {
+                                error!(
+                                    "failed to decode block execution results from {}: {}",
+                                    sender, err
+                                );
+                                return Effects::new();
+                            }
+                        };
+                    let event = fetcher::Event::GotRemotely {
+                        item: Box::new(results),
+                        source: Source::Peer(sender),
+                    };
+                    self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        Event::BlockExecutionResultsFetcher(event),
+                    )
+                }
                 Message::GetResponse {
                     tag: Tag::Deploy,
                     serialized_item,
@@ -649,6 +823,49 @@ impl reactor::Reactor for Reactor {
                     });
                     self.dispatch_event(effect_builder, rng, event)
                 }
+                Message::GetResponse {
+                    tag: Tag::DeployChunk,
+                    serialized_item,
+                } => {
+                    let chunk: DeployChunk = match bincode::deserialize(&serialized_item) {
+                        Ok(chunk) => chunk,
+                        Err(err) => {
+                            error!("failed to decode deploy chunk from {}: {}", sender, err);
+                            return Effects::new();
+                        }
+                    };
+                    if !chunk.is_valid() {
+                        warn!(%chunk, %sender, "received invalid deploy chunk");
+                        return Effects::new();
+                    }
+                    let event = fetcher::Event::GotChunk {
+                        chunk: Box::new(chunk),
+                        peer: sender,
+                    };
+                    self.dispatch_event(effect_builder, rng, Event::DeployFetcher(event))
+                }
+                Message::GetResponse {
+                    tag: Tag::DeployHeaderById,
+                    serialized_item,
+                } => {
+                    let header_by_id: DeployHeaderById =
+                        match bincode::deserialize(&serialized_item) {
+                            Ok(header_by_id) => header_by_id,
+                            Err(err) => {
+                                error!("failed to decode deploy header from {}: {}", sender, err);
+                                return Effects::new();
+                            }
+                        };
+                    if !header_by_id.is_valid() {
+                        warn!(%header_by_id, %sender, "received invalid deploy header");
+                        return Effects::new();
+                    }
+                    let event = fetcher::Event::GotRemotely {
+                        item: Box::new(header_by_id),
+                        source: Source::Peer(sender),
+                    };
+                    self.dispatch_event(effect_builder, rng, Event::DeployHeaderFetcher(event))
+                }
                 Message::AddressGossiper(message) => {
                     let event = Event::AddressGossiper(gossiper::Event::MessageReceived {
                         sender,
@@ -660,6 +877,22 @@ impl reactor::Reactor for Reactor {
                     debug!("finality signatures not handled in joiner reactor");
                     Effects::new()
                 }
+                Message::Consensus(msg) => {
+                    let era_id = msg.era_id();
+                    let is_past_era = self
+                        .linear_chain_sync
+                        .latest_block()
+                        .map_or(false, |block| era_id < block.header().era_id());
+                    if is_past_era {
+                        trace!(era = era_id.0, "dropping consensus message for a past era");
+                    } else if !self.deferred_consensus_messages.push(sender, msg) {
+                        debug!(
+                            era = era_id.0,
+                            "too many deferred consensus messages; dropping"
+                        );
+                    }
+                    Effects::new()
+                }
                 other => {
                     debug!(?other, "network announcement ignored.");
                     Effects::new()
@@ -684,6 +917,9 @@ impl reactor::Reactor for Reactor {
                 warn!(?deploy_hash, ?peer, "Invalid deploy received from a peer.");
                 Effects::new()
             }
+            Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::ApprovalsUpdated {
+                ..
+            }) => Effects::new(),
             Event::Storage(event) => reactor::wrap_effects(
                 Event::Storage,
                 self.storage.handle_event(effect_builder, rng, event),
@@ -726,11 +962,41 @@ impl reactor::Reactor for Reactor {
             Event::DeployFetcherRequest(request) => {
                 self.dispatch_event(effect_builder, rng, Event::DeployFetcher(request.into()))
             }
+            Event::DeployHeaderFetcher(event) => reactor::wrap_effects(
+                Event::DeployHeaderFetcher,
+                self.deploy_header_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::DeployHeaderFetcherRequest(request) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::DeployHeaderFetcher(request.into()),
+            ),
             Event::BlockByHeightFetcherRequest(request) => self.dispatch_event(
                 effect_builder,
                 rng,
                 Event::BlockByHeightFetcher(request.into()),
             ),
+            Event::BlockHeaderFetcher(event) => reactor::wrap_effects(
+                Event::BlockHeaderFetcher,
+                self.block_header_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::BlockHeaderFetcherRequest(request) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::BlockHeaderFetcher(request.into()),
+            ),
+            Event::BlockExecutionResultsFetcher(event) => reactor::wrap_effects(
+                Event::BlockExecutionResultsFetcher,
+                self.block_execution_results_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::BlockExecutionResultsFetcherRequest(request) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::BlockExecutionResultsFetcher(request.into()),
+            ),
             Event::BlockExecutor(event) => reactor::wrap_effects(
                 Event::BlockExecutor,
                 self.block_executor.handle_event(effect_builder, rng, event),
@@ -746,10 +1012,24 @@ impl reactor::Reactor for Reactor {
             Event::BlockExecutorAnnouncement(BlockExecutorAnnouncement::LinearChainBlock {
                 block,
                 execution_results,
+                ..
             }) => {
                 let mut effects = Effects::new();
                 let block_hash = *block.hash();
 
+                // send to block exporter
+                if self.block_exporter.is_enabled() {
+                    let reactor_event =
+                        Event::BlockExporter(block_exporter::Event::BlockExecuted {
+                            block: Box::new(block.clone()),
+                            execution_results: execution_results
+                                .iter()
+                                .map(|(hash, (_header, result))| (*hash, result.clone()))
+                                .collect(),
+                        });
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
                 // send to linear chain
                 let reactor_event = Event::LinearChain(linear_chain::Event::LinearChainBlock {
                     block: Box::new(block),
@@ -836,13 +1116,17 @@ impl reactor::Reactor for Reactor {
                 self.address_gossiper
                     .handle_event(effect_builder, rng, event),
             ),
-            Event::AddressGossiperAnnouncement(ann) => {
-                let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
                 let reactor_event = Event::SmallNetwork(small_network::Event::PeerAddressReceived(
                     gossiped_address,
                 ));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewItemBody { .. }) => {
+                unreachable!("the address gossiper should never announce an item body")
+            }
 
             Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded {
                 block_hash,
@@ -860,6 +1144,22 @@ impl reactor::Reactor for Reactor {
                     Event::EventStreamServer(event_stream_server::Event::FinalitySignature(fs));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::LinearChainSyncAnnouncement(
+                LinearChainSyncAnnouncement::InvalidBlockReceived {
+                    peer,
+                    block_hash,
+                    error,
+                },
+            ) => {
+                warn!(%peer, %block_hash, %error, "peer sent an invalid block; blocking it");
+                effect_builder.block_peer(peer).ignore()
+            }
+            Event::LinearChainSyncAnnouncement(
+                LinearChainSyncAnnouncement::InvalidExecutionResultsReceived { peer, block_hash },
+            ) => {
+                warn!(%peer, %block_hash, "peer sent invalid execution results; blocking it");
+                effect_builder.block_peer(peer).ignore()
+            }
             Event::RestServer(event) => reactor::wrap_effects(
                 Event::RestServer,
                 self.rest_server.handle_event(effect_builder, rng, event),
@@ -869,10 +1169,17 @@ impl reactor::Reactor for Reactor {
                 self.event_stream_server
                     .handle_event(effect_builder, rng, event),
             ),
-            Event::MetricsRequest(req) => reactor::wrap_effects(
-                Event::MetricsRequest,
-                self.metrics.handle_event(effect_builder, rng, req),
+            Event::BlockExporter(event) => reactor::wrap_effects(
+                Event::BlockExporter,
+                self.block_exporter.handle_event(effect_builder, rng, event),
             ),
+            Event::Metrics(event) => reactor::wrap_effects(
+                Event::Metrics,
+                self.metrics.handle_event(effect_builder, rng, event),
+            ),
+            Event::MetricsRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::Metrics(req.into()))
+            }
             Event::ChainspecLoader(event) => reactor::wrap_effects(
                 Event::ChainspecLoader,
                 self.chainspec_loader
@@ -928,6 +1235,14 @@ impl reactor::Reactor for Reactor {
 }
 
 impl Reactor {
+    /// Saves the progress of the linear chain sync to storage, so that it can be resumed if the
+    /// node is restarted before joining is complete.
+    pub fn checkpoint_linear_chain_sync(&self) -> Result<(), Error> {
+        #[cfg(not(feature = "fast-sync"))]
+        self.linear_chain_sync.checkpoint(&self.storage)?;
+        Ok(())
+    }
+
     /// Deconstructs the reactor into config useful for creating a Validator reactor. Shuts down
     /// the network, closing all incoming and outgoing connections, and frees up the listening
     /// socket.
@@ -938,14 +1253,22 @@ impl Reactor {
             &self.storage,
             self.chainspec_loader.chainspec(),
         )?;
+        let mut deferred_consensus_messages = self.deferred_consensus_messages;
+        if let Some(block) = self.linear_chain_sync.latest_block() {
+            deferred_consensus_messages.discard_before(block.header().era_id());
+        }
         let config = ValidatorInitConfig {
             chainspec_loader: self.chainspec_loader,
             config: self.config,
+            root: self.root,
             contract_runtime: self.contract_runtime,
             storage: self.storage,
             consensus: self.consensus,
+            deferred_consensus_messages,
             latest_block: self.linear_chain_sync.latest_block().cloned(),
             event_stream_server: self.event_stream_server,
+            block_exporter: self.block_exporter,
+            sync_progress: self.sync_progress,
             small_network_identity: SmallNetworkIdentity::from(&self.small_network),
             network_identity: NetworkIdentity::from(&self.network),
         };
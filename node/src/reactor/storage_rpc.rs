@@ -0,0 +1,387 @@
+//! Reactor serving the JSON-RPC read endpoints from an offline copy of a node's storage.
+//!
+//! Only the storage, the contract runtime (for the global state) and the RPC server are run: there
+//! is no networking and no consensus.  The chainspec is loaded to answer chainspec queries, but
+//! neither genesis nor upgrades are committed.  Requests which a live node would answer from other
+//! components get the answers of a node without peers or signing key, and deploys are rejected.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+use datasize::DataSize;
+use derive_more::From;
+use prometheus::Registry;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::info;
+
+use casper_types::PublicKey;
+
+use crate::{
+    components::{
+        chainspec_loader::{self, ChainspecLoader},
+        contract_runtime::{self, ContractRuntime},
+        deploy_acceptor,
+        metrics::{self, Metrics},
+        rpc_server::{self, RpcServer},
+        storage::{self, Storage},
+        Component,
+    },
+    effect::{
+        announcements::{ChainspecLoaderAnnouncement, RpcServerAnnouncement},
+        requests::{
+            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, DumpStateRequest,
+            LinearChainRequest, MetricsRequest, NetworkInfoRequest, RpcRequest, StateStoreRequest,
+            StorageRequest,
+        },
+        EffectBuilder, EffectExt, Effects,
+    },
+    reactor::{self, validator, EventQueueHandle, ReactorExit},
+    types::{chainspec, NodeId, SyncState, SyncStatus, Timestamp},
+    utils::{ListeningError, Scheduler, WithDir},
+    NodeRng,
+};
+
+/// Configuration of the storage RPC reactor.
+#[derive(Debug)]
+pub struct Config {
+    /// The node's configuration, of which the storage, contract runtime and RPC server sections
+    /// are used.
+    pub node_config: WithDir<validator::Config>,
+    /// The directory holding the copy of the node's storage, replacing the configured storage
+    /// path.
+    pub data_dir: PathBuf,
+}
+
+/// Top-level event for the reactor.
+#[derive(Debug, From, Serialize)]
+#[must_use]
+pub enum Event {
+    /// Chainspec loader event.
+    #[from]
+    ChainspecLoader(#[serde(skip_serializing)] chainspec_loader::Event),
+    /// Storage event.
+    #[from]
+    Storage(#[serde(skip_serializing)] storage::Event),
+    /// Contract runtime event.
+    #[from]
+    ContractRuntime(#[serde(skip_serializing)] contract_runtime::Event),
+    /// RPC server event.
+    #[from]
+    RpcServer(#[serde(skip_serializing)] rpc_server::Event),
+    /// Metrics event.
+    #[from]
+    Metrics(#[serde(skip_serializing)] metrics::Event),
+
+    // Requests
+    /// Storage request.
+    #[from]
+    StorageRequest(#[serde(skip_serializing)] StorageRequest),
+    /// Request for state storage.
+    #[from]
+    StateStoreRequest(StateStoreRequest),
+    /// Chainspec info request.
+    #[from]
+    ChainspecLoaderRequest(#[serde(skip_serializing)] ChainspecLoaderRequest),
+    /// Metrics request.
+    #[from]
+    MetricsRequest(#[serde(skip_serializing)] MetricsRequest),
+    /// Consensus request.
+    #[from]
+    ConsensusRequest(#[serde(skip_serializing)] ConsensusRequest),
+    /// Linear chain request.
+    #[from]
+    LinearChainRequest(#[serde(skip_serializing)] LinearChainRequest<NodeId>),
+    /// Network info request.
+    #[from]
+    NetworkInfoRequest(#[serde(skip_serializing)] NetworkInfoRequest<NodeId>),
+    /// Request for a snapshot of a component's state.
+    #[from]
+    DumpStateRequest(#[serde(skip_serializing)] DumpStateRequest),
+
+    // Announcements
+    /// API server announcement.
+    #[from]
+    RpcServerAnnouncement(#[serde(skip_serializing)] RpcServerAnnouncement),
+    /// Chainspec loader announcement.
+    #[from]
+    ChainspecLoaderAnnouncement(#[serde(skip_serializing)] ChainspecLoaderAnnouncement),
+}
+
+impl From<RpcRequest<NodeId>> for Event {
+    fn from(request: RpcRequest<NodeId>) -> Self {
+        Event::RpcServer(rpc_server::Event::RpcRequest(request))
+    }
+}
+
+impl From<ContractRuntimeRequest> for Event {
+    fn from(request: ContractRuntimeRequest) -> Self {
+        Event::ContractRuntime(contract_runtime::Event::Request(request))
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::ChainspecLoader(event) => write!(f, "chainspec loader: {}", event),
+            Event::Storage(event) => write!(f, "storage: {}", event),
+            Event::ContractRuntime(event) => write!(f, "contract runtime: {}", event),
+            Event::RpcServer(event) => write!(f, "rpc server: {}", event),
+            Event::Metrics(event) => write!(f, "metrics: {}", event),
+            Event::StorageRequest(req) => write!(f, "storage request: {}", req),
+            Event::StateStoreRequest(req) => write!(f, "state store request: {}", req),
+            Event::ChainspecLoaderRequest(req) => write!(f, "chainspec loader request: {}", req),
+            Event::MetricsRequest(req) => write!(f, "metrics request: {}", req),
+            Event::ConsensusRequest(req) => write!(f, "consensus request: {:?}", req),
+            Event::LinearChainRequest(req) => write!(f, "linear chain request: {}", req),
+            Event::NetworkInfoRequest(req) => write!(f, "network info request: {}", req),
+            Event::DumpStateRequest(req) => write!(f, "dump state request: {}", req),
+            Event::RpcServerAnnouncement(ann) => write!(f, "api server announcement: {}", ann),
+            Event::ChainspecLoaderAnnouncement(ann) => {
+                write!(f, "chainspec loader announcement: {}", ann)
+            }
+        }
+    }
+}
+
+/// Error type returned by the storage RPC reactor.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Metrics-related error
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+
+    /// `ChainspecLoader` component error.
+    #[error("chainspec error: {0}")]
+    Chainspec(#[from] chainspec::Error),
+
+    /// `Storage` component error.
+    #[error("storage error: {0}")]
+    Storage(#[from] storage::Error),
+
+    /// `ContractRuntime` component error.
+    #[error("contract runtime config error: {0}")]
+    ContractRuntime(#[from] contract_runtime::ConfigError),
+
+    /// An error starting the RPC server.
+    #[error("http server listening error: {0}")]
+    ListeningError(#[from] ListeningError),
+}
+
+/// Storage RPC reactor.
+#[derive(DataSize, Debug)]
+pub struct Reactor {
+    chainspec_loader: ChainspecLoader,
+    storage: Storage,
+    contract_runtime: ContractRuntime,
+    rpc_server: RpcServer,
+    metrics: Metrics,
+    /// The time the reactor was started, reported as the start of the sync status.
+    started: Timestamp,
+}
+
+impl reactor::Reactor for Reactor {
+    type Event = Event;
+    type Config = Config;
+    type Error = Error;
+
+    fn new(
+        config: Self::Config,
+        registry: &Registry,
+        event_queue: EventQueueHandle<Self::Event>,
+        rng: &mut NodeRng,
+    ) -> Result<(Self, Effects<Self::Event>), Error> {
+        let Config {
+            node_config,
+            data_dir,
+        } = config;
+        let (root, config) = node_config.into_parts();
+        let effect_builder = EffectBuilder::new(event_queue);
+
+        // The loader's initialization effects are dropped: they would commit genesis or an upgrade
+        // to the global state.
+        let (chainspec_loader, _initialization_effects) =
+            ChainspecLoader::new(&root, effect_builder)?;
+
+        let mut storage_config = config.storage.clone();
+        storage_config.path = data_dir;
+        let storage_config = WithDir::new(root.clone(), storage_config);
+        let storage = Storage::new(&storage_config, None)?;
+        let contract_runtime =
+            ContractRuntime::new(storage_config, &config.contract_runtime, registry)?;
+
+        let (metrics, metrics_effects) = Metrics::new(registry.clone(), effect_builder)?;
+
+        info!(address = %config.rpc_server.address, "serving storage over RPC");
+        let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
+        let rpc_server = RpcServer::new(
+            WithDir::new(root, config.rpc_server),
+            effect_builder,
+            protocol_version.clone(),
+            registry,
+            Scheduler::new(rng),
+            // There is no event stream in this mode.
+            Vec::new(),
+        )?;
+
+        let reactor = Reactor {
+            chainspec_loader,
+            storage,
+            contract_runtime,
+            rpc_server,
+            metrics,
+            started: Timestamp::now(),
+        };
+        Ok((
+            reactor,
+            reactor::wrap_effects(Event::Metrics, metrics_effects),
+        ))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+        event: Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::ChainspecLoader(event) => reactor::wrap_effects(
+                Event::ChainspecLoader,
+                self.chainspec_loader
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::Storage(event) => reactor::wrap_effects(
+                Event::Storage,
+                self.storage.handle_event(effect_builder, rng, event),
+            ),
+            Event::ContractRuntime(event) => reactor::wrap_effects(
+                Event::ContractRuntime,
+                self.contract_runtime
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::RpcServer(event) => reactor::wrap_effects(
+                Event::RpcServer,
+                self.rpc_server.handle_event(effect_builder, rng, event),
+            ),
+            Event::Metrics(event) => reactor::wrap_effects(
+                Event::Metrics,
+                self.metrics.handle_event(effect_builder, rng, event),
+            ),
+
+            // Requests:
+            Event::StorageRequest(StorageRequest::CompactStorage { responder }) => responder
+                .respond(Err(
+                    "the storage of an offline node cannot be compacted".to_string()
+                ))
+                .ignore(),
+            Event::StorageRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::Storage(req.into()))
+            }
+            Event::StateStoreRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::Storage(req.into()))
+            }
+            Event::ChainspecLoaderRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::ChainspecLoader(req.into()))
+            }
+            Event::MetricsRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::Metrics(req.into()))
+            }
+            Event::ConsensusRequest(req) => self.handle_consensus_request(req),
+            Event::LinearChainRequest(LinearChainRequest::BlockAtHeightLocal(
+                height,
+                responder,
+            )) => async move {
+                let block = effect_builder
+                    .get_block_at_height_from_storage(height)
+                    .await;
+                responder.respond(block).await;
+            }
+            .ignore(),
+            Event::LinearChainRequest(LinearChainRequest::BlocksBelowStrictFinality(responder)) => {
+                responder.respond(vec![]).ignore()
+            }
+            Event::LinearChainRequest(LinearChainRequest::BlockRequest(..))
+            | Event::LinearChainRequest(LinearChainRequest::BlockAtHeight(..))
+            | Event::LinearChainRequest(LinearChainRequest::BlockHeaderRequest(..))
+            | Event::LinearChainRequest(LinearChainRequest::BlockExecutionResultsRequest(..)) => {
+                unreachable!("no network traffic happens when serving storage over RPC")
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetPeers { responder }) => {
+                responder.respond(Default::default()).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetBlocklist { responder }) => {
+                responder.respond(vec![]).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::ClearBlocklist { responder, .. }) => {
+                responder.respond(0).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::BlockPeer { responder, .. }) => {
+                // There are no peers to block when serving storage over RPC.
+                responder.respond(()).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetPublicEndpoint { responder }) => {
+                responder.respond(None).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetTopology { .. }) => {
+                unreachable!("only the REST server requests the network topology")
+            }
+            Event::DumpStateRequest(DumpStateRequest { responder, .. }) => {
+                responder.respond(None).ignore()
+            }
+
+            // Announcements:
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployReceived {
+                responder,
+                ..
+            }) => match responder {
+                Some(responder) => responder
+                    .respond(Err(deploy_acceptor::Error::OfflineNode))
+                    .ignore(),
+                None => Effects::new(),
+            },
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployApprovalsReceived {
+                responder,
+                ..
+            }) => responder
+                .respond(Err(deploy_acceptor::Error::OfflineNode))
+                .ignore(),
+            Event::ChainspecLoaderAnnouncement(_) => Effects::new(),
+        }
+    }
+
+    fn maybe_exit(&self) -> Option<ReactorExit> {
+        None
+    }
+}
+
+impl Reactor {
+    /// Answers consensus requests as a node which isn't a validator and has finalized nothing
+    /// beyond its stored blocks.
+    fn handle_consensus_request(&self, request: ConsensusRequest) -> Effects<Event> {
+        match request {
+            ConsensusRequest::HandleLinearBlock(_, responder) => responder.respond(None).ignore(),
+            ConsensusRequest::IsBondedValidator(_, _, responder) => {
+                responder.respond(false).ignore()
+            }
+            ConsensusRequest::ValidatorWeights(_, responder) => responder.respond(None).ignore(),
+            ConsensusRequest::Status(responder) => {
+                let sync_status = SyncStatus {
+                    state: SyncState::Participating,
+                    blocks_behind: 0,
+                    since: self.started,
+                    transitions: 0,
+                };
+                responder
+                    .respond((PublicKey::System, None, sync_status))
+                    .ignore()
+            }
+            ConsensusRequest::StageSigningKey(_, _, responder) => responder
+                .respond(Err(
+                    "an offline node has no signing key to replace".to_string()
+                ))
+                .ignore(),
+        }
+    }
+}
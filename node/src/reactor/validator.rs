@@ -5,12 +5,14 @@
 mod config;
 mod error;
 mod memory_metrics;
+mod serving_policy;
 #[cfg(test)]
 mod tests;
 
 use std::{
     cmp, env,
     fmt::{self, Debug, Display, Formatter},
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
 };
@@ -26,48 +28,62 @@ use crate::testing::network::NetworkedReactor;
 use crate::{
     components::{
         block_executor::{self, BlockExecutor},
+        block_exporter::{self, BlockExporter},
         block_proposer::{self, BlockProposer},
-        block_validator::{self, BlockValidator},
+        block_validator::{self, BlockValidator, DeployFetchMode},
         chainspec_loader::{self, ChainspecLoader},
-        consensus::{self, EraSupervisor},
+        clock_monitor::{self, ClockMonitor},
+        consensus::{self, DeferredMessages, EraSupervisor},
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
+        deploy_watcher::{self, DeployWatcher},
+        disk_watchdog::{self, DiskSpaceStatus, DiskWatchdog},
         event_stream_server::{self, EventStreamServer},
         fetcher::{self, Fetcher},
         gossiper::{self, Gossiper},
         linear_chain,
-        metrics::Metrics,
+        metrics::{self, Metrics},
         network::{self, Network, NetworkIdentity, ENABLE_LIBP2P_NET_ENV_VAR},
         rest_server::{self, RestServer},
         rpc_server::{self, RpcServer},
         small_network::{self, GossipedAddress, SmallNetwork, SmallNetworkIdentity},
         storage::{self, Storage},
+        webhook_notifier::{self, WebhookNotifier},
         Component,
     },
+    crypto::SignatureVerifier,
     effect::{
         announcements::{
-            BlockExecutorAnnouncement, ChainspecLoaderAnnouncement, ConsensusAnnouncement,
-            DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
+            BlockExecutorAnnouncement, ChainspecLoaderAnnouncement, ClockMonitorAnnouncement,
+            ConsensusAnnouncement, DeployAcceptorAnnouncement, DeployWatcherAnnouncement,
+            DiskWatchdogAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
             NetworkAnnouncement, RpcServerAnnouncement,
         },
         requests::{
             BlockExecutorRequest, BlockProposerRequest, BlockValidationRequest,
-            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, FetcherRequest,
-            LinearChainRequest, MetricsRequest, NetworkInfoRequest, NetworkRequest, RestRequest,
-            RpcRequest, StateStoreRequest, StorageRequest,
+            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, DumpStateRequest,
+            FetcherRequest, LinearChainRequest, MetricsRequest, NetworkInfoRequest, NetworkRequest,
+            RestRequest, RpcRequest, StateStoreRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
+    logging,
     protocol::Message,
     reactor::{self, event_queue_metrics::EventQueueMetrics, EventQueueHandle, ReactorExit},
-    types::{Block, Deploy, ExitCode, NodeId, ProtoBlock, Tag, TimeDiff, Timestamp},
-    utils::Source,
+    rpcs::debug::DumpableComponent,
+    types::{
+        Block, ChunkId, Deploy, DeployChunk, DeployHash, DeployHeaderById, ExitCode, NodeId,
+        ProtoBlock, Tag, TimeDiff, Timestamp,
+    },
+    utils::{Scheduler, Source, SyncProgress, WithDir},
     NodeRng,
 };
-pub use config::Config;
+pub use config::{Config, ConfigLoader};
 pub use error::Error;
 use linear_chain::LinearChain;
 use memory_metrics::MemoryMetrics;
+use serving_policy::ServingPolicy;
+pub use serving_policy::{Config as ServingPolicyConfig, ServeTo};
 
 /// Top-level event for the reactor.
 #[derive(Debug, From, Serialize)]
@@ -94,6 +110,9 @@ pub enum Event {
     #[from]
     /// Event stream server event.
     EventStreamServer(#[serde(skip_serializing)] event_stream_server::Event),
+    /// Block exporter event.
+    #[from]
+    BlockExporter(#[serde(skip_serializing)] block_exporter::Event),
     #[from]
     /// Chainspec Loader event.
     ChainspecLoader(#[serde(skip_serializing)] chainspec_loader::Event),
@@ -106,6 +125,9 @@ pub enum Event {
     /// Deploy fetcher event.
     #[from]
     DeployFetcher(#[serde(skip_serializing)] fetcher::Event<Deploy>),
+    /// Deploy header fetcher event.
+    #[from]
+    DeployHeaderFetcher(#[serde(skip_serializing)] fetcher::Event<DeployHeaderById>),
     /// Deploy gossiper event.
     #[from]
     DeployGossiper(#[serde(skip_serializing)] gossiper::Event<Deploy>),
@@ -124,6 +146,18 @@ pub enum Event {
     /// Linear chain event.
     #[from]
     LinearChain(#[serde(skip_serializing)] linear_chain::Event<NodeId>),
+    /// Clock monitor event.
+    #[from]
+    ClockMonitor(#[serde(skip_serializing)] clock_monitor::Event),
+    /// Deploy watcher event.
+    #[from]
+    DeployWatcher(#[serde(skip_serializing)] deploy_watcher::Event),
+    /// Disk watchdog event.
+    #[from]
+    DiskWatchdog(#[serde(skip_serializing)] disk_watchdog::Event),
+    /// Webhook notifier event.
+    #[from]
+    WebhookNotifier(#[serde(skip_serializing)] webhook_notifier::Event),
 
     // Requests
     /// Network request.
@@ -135,6 +169,9 @@ pub enum Event {
     /// Deploy fetcher request.
     #[from]
     DeployFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, Deploy>),
+    /// Deploy header fetcher request.
+    #[from]
+    DeployHeaderFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, DeployHeaderById>),
     /// Block proposer request.
     #[from]
     BlockProposerRequest(#[serde(skip_serializing)] BlockProposerRequest),
@@ -146,6 +183,9 @@ pub enum Event {
     ProtoBlockValidatorRequest(
         #[serde(skip_serializing)] BlockValidationRequest<ProtoBlock, NodeId>,
     ),
+    /// Metrics event.
+    #[from]
+    Metrics(#[serde(skip_serializing)] metrics::Event),
     /// Metrics request.
     #[from]
     MetricsRequest(#[serde(skip_serializing)] MetricsRequest),
@@ -158,6 +198,9 @@ pub enum Event {
     /// Request for state storage.
     #[from]
     StateStoreRequest(StateStoreRequest),
+    /// Request for a snapshot of a component's state.
+    #[from]
+    DumpStateRequest(#[serde(skip_serializing)] DumpStateRequest),
 
     // Announcements
     /// Network announcement.
@@ -187,6 +230,18 @@ pub enum Event {
     /// Chainspec loader announcement.
     #[from]
     ChainspecLoaderAnnouncement(#[serde(skip_serializing)] ChainspecLoaderAnnouncement),
+
+    /// Clock monitor announcement.
+    #[from]
+    ClockMonitorAnnouncement(#[serde(skip_serializing)] ClockMonitorAnnouncement),
+
+    /// Deploy watcher announcement.
+    #[from]
+    DeployWatcherAnnouncement(#[serde(skip_serializing)] DeployWatcherAnnouncement),
+
+    /// Disk watchdog announcement.
+    #[from]
+    DiskWatchdogAnnouncement(#[serde(skip_serializing)] DiskWatchdogAnnouncement),
 }
 
 impl From<RpcRequest<NodeId>> for Event {
@@ -247,26 +302,37 @@ impl Display for Event {
             Event::RpcServer(event) => write!(f, "rpc server: {}", event),
             Event::RestServer(event) => write!(f, "rest server: {}", event),
             Event::EventStreamServer(event) => write!(f, "event stream server: {}", event),
+            Event::BlockExporter(event) => write!(f, "block exporter: {}", event),
             Event::ChainspecLoader(event) => write!(f, "chainspec loader: {}", event),
             Event::Consensus(event) => write!(f, "consensus: {}", event),
             Event::DeployAcceptor(event) => write!(f, "deploy acceptor: {}", event),
             Event::DeployFetcher(event) => write!(f, "deploy fetcher: {}", event),
+            Event::DeployHeaderFetcher(event) => write!(f, "deploy header fetcher: {}", event),
             Event::DeployGossiper(event) => write!(f, "deploy gossiper: {}", event),
             Event::AddressGossiper(event) => write!(f, "address gossiper: {}", event),
             Event::ContractRuntime(event) => write!(f, "contract runtime: {}", event),
             Event::BlockExecutor(event) => write!(f, "block executor: {}", event),
             Event::LinearChain(event) => write!(f, "linear-chain event {}", event),
             Event::ProtoBlockValidator(event) => write!(f, "block validator: {}", event),
+            Event::ClockMonitor(event) => write!(f, "clock monitor: {}", event),
+            Event::DeployWatcher(event) => write!(f, "deploy watcher: {}", event),
+            Event::DiskWatchdog(event) => write!(f, "disk watchdog: {}", event),
+            Event::WebhookNotifier(event) => write!(f, "webhook notifier: {}", event),
             Event::NetworkRequest(req) => write!(f, "network request: {}", req),
             Event::NetworkInfoRequest(req) => write!(f, "network info request: {}", req),
             Event::ChainspecLoaderRequest(req) => write!(f, "chainspec loader request: {}", req),
             Event::StorageRequest(req) => write!(f, "storage request: {}", req),
             Event::StateStoreRequest(req) => write!(f, "state store request: {}", req),
             Event::DeployFetcherRequest(req) => write!(f, "deploy fetcher request: {}", req),
+            Event::DeployHeaderFetcherRequest(req) => {
+                write!(f, "deploy header fetcher request: {}", req)
+            }
             Event::BlockProposerRequest(req) => write!(f, "block proposer request: {}", req),
             Event::BlockExecutorRequest(req) => write!(f, "block executor request: {}", req),
             Event::ProtoBlockValidatorRequest(req) => write!(f, "block validator request: {}", req),
+            Event::Metrics(event) => write!(f, "metrics: {}", event),
             Event::MetricsRequest(req) => write!(f, "metrics request: {}", req),
+            Event::DumpStateRequest(req) => write!(f, "dump state request: {}", req),
             Event::NetworkAnnouncement(ann) => write!(f, "network announcement: {}", ann),
             Event::RpcServerAnnouncement(ann) => write!(f, "api server announcement: {}", ann),
             Event::DeployAcceptorAnnouncement(ann) => {
@@ -286,6 +352,15 @@ impl Display for Event {
             Event::ChainspecLoaderAnnouncement(ann) => {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
+            Event::ClockMonitorAnnouncement(ann) => {
+                write!(f, "clock monitor announcement: {}", ann)
+            }
+            Event::DeployWatcherAnnouncement(ann) => {
+                write!(f, "deploy watcher announcement: {}", ann)
+            }
+            Event::DiskWatchdogAnnouncement(ann) => {
+                write!(f, "disk watchdog announcement: {}", ann)
+            }
         }
     }
 }
@@ -293,12 +368,17 @@ impl Display for Event {
 /// The configuration needed to initialize a Validator reactor
 pub struct ValidatorInitConfig {
     pub(super) config: Config,
+    pub(super) root: PathBuf,
     pub(super) chainspec_loader: ChainspecLoader,
     pub(super) storage: Storage,
     pub(super) contract_runtime: ContractRuntime,
     pub(super) consensus: EraSupervisor<NodeId>,
+    pub(super) deferred_consensus_messages: DeferredMessages<NodeId>,
     pub(super) latest_block: Option<Block>,
     pub(super) event_stream_server: EventStreamServer,
+    pub(super) block_exporter: BlockExporter,
+    /// The final progress of the linear chain sync, still served by the REST server.
+    pub(super) sync_progress: SyncProgress,
     pub(super) small_network_identity: SmallNetworkIdentity,
     pub(super) network_identity: NetworkIdentity,
 }
@@ -333,23 +413,42 @@ pub struct Reactor {
     rpc_server: RpcServer,
     rest_server: RestServer,
     event_stream_server: EventStreamServer,
+    block_exporter: BlockExporter,
     chainspec_loader: ChainspecLoader,
     consensus: EraSupervisor<NodeId>,
     #[data_size(skip)]
     deploy_acceptor: DeployAcceptor,
     deploy_fetcher: Fetcher<Deploy>,
+    deploy_header_fetcher: Fetcher<DeployHeaderById>,
     deploy_gossiper: Gossiper<Deploy, Event>,
     block_proposer: BlockProposer,
     block_executor: BlockExecutor,
     proto_block_validator: BlockValidator<ProtoBlock, NodeId>,
     linear_chain: LinearChain<NodeId>,
+    clock_monitor: ClockMonitor,
+    deploy_watcher: DeployWatcher,
+    disk_watchdog: DiskWatchdog,
+    webhook_notifier: WebhookNotifier,
 
     // Non-components.
+    serving_policy: ServingPolicy,
+    deploy_chunk_size: u32,
+
     #[data_size(skip)] // Never allocates heap data.
     memory_metrics: MemoryMetrics,
 
     #[data_size(skip)]
     event_queue_metrics: EventQueueMetrics,
+
+    #[data_size(skip)]
+    config_loader: Option<ConfigLoader>,
+}
+
+impl Reactor {
+    /// Sets the source from which the configuration is reloaded when requested.
+    pub fn set_config_loader(&mut self, config_loader: ConfigLoader) {
+        self.config_loader = Some(config_loader);
+    }
 }
 
 #[cfg(test)]
@@ -380,12 +479,16 @@ impl reactor::Reactor for Reactor {
     ) -> Result<(Self, Effects<Event>), Error> {
         let ValidatorInitConfig {
             config,
+            root,
             chainspec_loader,
             storage,
             contract_runtime,
             mut consensus,
+            deferred_consensus_messages,
             latest_block,
             event_stream_server,
+            block_exporter,
+            sync_progress,
             small_network_identity,
             network_identity,
         } = config;
@@ -394,9 +497,9 @@ impl reactor::Reactor for Reactor {
 
         let event_queue_metrics = EventQueueMetrics::new(registry.clone(), event_queue)?;
 
-        let metrics = Metrics::new(registry.clone());
-
         let effect_builder = EffectBuilder::new(event_queue);
+        let (metrics, metrics_effects) = Metrics::new(registry.clone(), effect_builder)?;
+
         let network_config = network::Config::from(&config.network);
         let (network, network_effects) = Network::new(
             event_queue,
@@ -420,24 +523,34 @@ impl reactor::Reactor for Reactor {
             Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
 
         let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
+        let scheduler = Scheduler::new(rng);
         let rpc_server = RpcServer::new(
-            config.rpc_server.clone(),
+            WithDir::new(root, config.rpc_server.clone()),
             effect_builder,
             protocol_version.clone(),
+            registry,
+            scheduler.clone(),
+            config.event_stream_server.enabled_events(),
         )?;
         let rest_server = RestServer::new(
             config.rest_server.clone(),
             effect_builder,
             protocol_version.clone(),
+            registry,
+            scheduler.clone(),
+            config.event_stream_server.enabled_events(),
+            sync_progress,
+            rng,
         )?;
 
         let deploy_acceptor =
             DeployAcceptor::new(config.deploy_acceptor, &*chainspec_loader.chainspec());
         let deploy_fetcher = Fetcher::new("deploy", config.fetcher, &registry)?;
+        let deploy_header_fetcher = Fetcher::new("deploy_header", config.fetcher, &registry)?;
         let deploy_gossiper = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config.gossip,
-            gossiper::get_deploy_from_storage::<Deploy, Event>,
+            gossiper::get_deploy_from_storage::<Event>,
             registry,
         )?;
         let (block_proposer, block_proposer_effects) = BlockProposer::new(
@@ -448,18 +561,69 @@ impl reactor::Reactor for Reactor {
                 .map(|block| block.height() + 1)
                 .unwrap_or(0),
             chainspec_loader.chainspec().as_ref(),
+            config.block_proposer,
+            scheduler.clone(),
         )?;
         let mut effects = reactor::wrap_effects(Event::BlockProposer, block_proposer_effects);
         let block_executor = BlockExecutor::new(
+            config.block_executor,
             chainspec_loader.initial_state_root_hash(),
             chainspec_loader.initial_block_header(),
             protocol_version.clone(),
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .checksum_registry_activation_era,
             registry.clone(),
         )
-        .with_parent_map(latest_block);
-        let proto_block_validator = BlockValidator::new(Arc::clone(&chainspec_loader.chainspec()));
-        let linear_chain = LinearChain::new(registry)?;
+        .with_parent_map(latest_block)
+        .with_shadow_execution(config.contract_runtime.shadow_chainspec_path().is_some());
+        let proto_block_validator = BlockValidator::new(
+            Arc::clone(&chainspec_loader.chainspec()),
+            config.block_validator,
+            DeployFetchMode::Headers,
+        );
+        let signature_verifier =
+            SignatureVerifier::new(config.node.signature_verification_threads)?;
+        let linear_chain = LinearChain::new(
+            registry,
+            signature_verifier,
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .finality_threshold_fraction,
+        )?;
+        let serving_policy = ServingPolicy::new(config.serving_policy, registry)?;
+        let (clock_monitor, clock_monitor_effects) = ClockMonitor::new(
+            config.clock_monitor,
+            registry,
+            effect_builder,
+            scheduler.clone(),
+        )?;
 
+        effects.extend(reactor::wrap_effects(
+            Event::ClockMonitor,
+            clock_monitor_effects,
+        ));
+        let (deploy_watcher, deploy_watcher_effects) =
+            DeployWatcher::new(config.deploy_watcher, effect_builder, scheduler.clone());
+        effects.extend(reactor::wrap_effects(
+            Event::DeployWatcher,
+            deploy_watcher_effects,
+        ));
+        let (disk_watchdog, disk_watchdog_effects) = DiskWatchdog::new(
+            config.disk_watchdog,
+            storage.root_path().to_path_buf(),
+            registry,
+            effect_builder,
+            scheduler,
+        )?;
+        effects.extend(reactor::wrap_effects(
+            Event::DiskWatchdog,
+            disk_watchdog_effects,
+        ));
+        let webhook_notifier = WebhookNotifier::new(config.webhook_notifier, registry)?;
+        effects.extend(reactor::wrap_effects(Event::Metrics, metrics_effects));
         effects.extend(reactor::wrap_effects(Event::Network, network_effects));
         effects.extend(reactor::wrap_effects(
             Event::SmallNetwork,
@@ -500,6 +664,22 @@ impl reactor::Reactor for Reactor {
                 .event(move |_| consensus::Event::FinishedJoining(now)),
         ));
 
+        // Hand over the consensus messages received while joining.
+        debug!(
+            count = deferred_consensus_messages.len(),
+            "handing deferred consensus messages to consensus"
+        );
+        for (sender, msg) in deferred_consensus_messages.into_messages() {
+            effects.extend(reactor::wrap_effects(
+                Event::Consensus,
+                effect_builder
+                    .immediately()
+                    .event(move |_| consensus::Event::MessageReceived { sender, msg }),
+            ));
+        }
+
+        let deploy_chunk_size = chainspec_loader.chainspec().deploy_config.chunk_size;
+
         Ok((
             Reactor {
                 metrics,
@@ -511,17 +691,26 @@ impl reactor::Reactor for Reactor {
                 rpc_server,
                 rest_server,
                 event_stream_server,
+                block_exporter,
                 chainspec_loader,
                 consensus,
                 deploy_acceptor,
                 deploy_fetcher,
+                deploy_header_fetcher,
                 deploy_gossiper,
                 block_proposer,
                 block_executor,
                 proto_block_validator,
                 linear_chain,
+                clock_monitor,
+                deploy_watcher,
+                disk_watchdog,
+                webhook_notifier,
+                serving_policy,
+                deploy_chunk_size,
                 memory_metrics,
                 event_queue_metrics,
+                config_loader: None,
             },
             effects,
         ))
@@ -563,6 +752,10 @@ impl reactor::Reactor for Reactor {
                 self.event_stream_server
                     .handle_event(effect_builder, rng, event),
             ),
+            Event::BlockExporter(event) => reactor::wrap_effects(
+                Event::BlockExporter,
+                self.block_exporter.handle_event(effect_builder, rng, event),
+            ),
             Event::ChainspecLoader(event) => reactor::wrap_effects(
                 Event::ChainspecLoader,
                 self.chainspec_loader
@@ -581,6 +774,11 @@ impl reactor::Reactor for Reactor {
                 Event::DeployFetcher,
                 self.deploy_fetcher.handle_event(effect_builder, rng, event),
             ),
+            Event::DeployHeaderFetcher(event) => reactor::wrap_effects(
+                Event::DeployHeaderFetcher,
+                self.deploy_header_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
             Event::DeployGossiper(event) => reactor::wrap_effects(
                 Event::DeployGossiper,
                 self.deploy_gossiper
@@ -609,6 +807,23 @@ impl reactor::Reactor for Reactor {
                 Event::LinearChain,
                 self.linear_chain.handle_event(effect_builder, rng, event),
             ),
+            Event::ClockMonitor(event) => reactor::wrap_effects(
+                Event::ClockMonitor,
+                self.clock_monitor.handle_event(effect_builder, rng, event),
+            ),
+            Event::DeployWatcher(event) => reactor::wrap_effects(
+                Event::DeployWatcher,
+                self.deploy_watcher.handle_event(effect_builder, rng, event),
+            ),
+            Event::DiskWatchdog(event) => reactor::wrap_effects(
+                Event::DiskWatchdog,
+                self.disk_watchdog.handle_event(effect_builder, rng, event),
+            ),
+            Event::WebhookNotifier(event) => reactor::wrap_effects(
+                Event::WebhookNotifier,
+                self.webhook_notifier
+                    .handle_event(effect_builder, rng, event),
+            ),
 
             // Requests:
             Event::NetworkRequest(req) => {
@@ -630,6 +845,9 @@ impl reactor::Reactor for Reactor {
             Event::DeployFetcherRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::DeployFetcher(req.into()))
             }
+            Event::DeployHeaderFetcherRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::DeployHeaderFetcher(req.into()))
+            }
             Event::BlockProposerRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::BlockProposer(req.into()))
             }
@@ -643,10 +861,13 @@ impl reactor::Reactor for Reactor {
                 rng,
                 Event::ProtoBlockValidator(block_validator::Event::from(req)),
             ),
-            Event::MetricsRequest(req) => reactor::wrap_effects(
-                Event::MetricsRequest,
-                self.metrics.handle_event(effect_builder, rng, req),
+            Event::Metrics(event) => reactor::wrap_effects(
+                Event::Metrics,
+                self.metrics.handle_event(effect_builder, rng, event),
             ),
+            Event::MetricsRequest(req) => {
+                self.dispatch_event(effect_builder, rng, Event::Metrics(req.into()))
+            }
             Event::ChainspecLoaderRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::ChainspecLoader(req.into()))
             }
@@ -656,6 +877,18 @@ impl reactor::Reactor for Reactor {
             Event::StateStoreRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::Storage(req.into()))
             }
+            Event::DumpStateRequest(req) => {
+                let event = match req.component {
+                    DumpableComponent::BlockProposer => Event::BlockProposer(req.into()),
+                    DumpableComponent::LinearChain => Event::LinearChain(req.into()),
+                    DumpableComponent::Network if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() => {
+                        // The small network is not running.
+                        return req.responder.respond(None).ignore();
+                    }
+                    DumpableComponent::Network => Event::SmallNetwork(req.into()),
+                };
+                self.dispatch_event(effect_builder, rng, event)
+            }
 
             // Announcements:
             Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
@@ -684,6 +917,9 @@ impl reactor::Reactor for Reactor {
                                     return Effects::new();
                                 }
                             };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
 
                             match self
                                 .storage
@@ -692,7 +928,7 @@ impl reactor::Reactor for Reactor {
                                 // This functionality was moved out of the storage component and
                                 // should be refactored ASAP.
                                 Some(deploy) => {
-                                    match Message::new_get_response(&deploy) {
+                                    match deploy_get_response(&deploy, self.deploy_chunk_size) {
                                         Ok(message) => {
                                             return effect_builder
                                                 .send_message(sender, message)
@@ -710,6 +946,92 @@ impl reactor::Reactor for Reactor {
                                 }
                             }
                         }
+                        Tag::DeployChunk => {
+                            let chunk_id: ChunkId<DeployHash> =
+                                match bincode::deserialize(&serialized_id) {
+                                    Ok(chunk_id) => chunk_id,
+                                    Err(error) => {
+                                        error!(
+                                            "failed to decode {:?} from {}: {}",
+                                            serialized_id, sender, error
+                                        );
+                                        return Effects::new();
+                                    }
+                                };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
+
+                            let deploy = match self
+                                .storage
+                                .handle_legacy_direct_deploy_request(chunk_id.item_id)
+                            {
+                                Some(deploy) => deploy,
+                                None => {
+                                    debug!("failed to get {} for {}", chunk_id, sender);
+                                    return Effects::new();
+                                }
+                            };
+                            match deploy_chunk_response(
+                                &deploy,
+                                chunk_id.index,
+                                self.deploy_chunk_size,
+                            ) {
+                                Ok(Some(message)) => {
+                                    return effect_builder.send_message(sender, message).ignore();
+                                }
+                                Ok(None) => {
+                                    debug!("{} has no {} to send to {}", deploy, chunk_id, sender);
+                                    return Effects::new();
+                                }
+                                Err(error) => {
+                                    error!("failed to create get-response: {}", error);
+                                    return Effects::new();
+                                }
+                            }
+                        }
+                        Tag::DeployHeaderById => {
+                            let deploy_hash = match bincode::deserialize(&serialized_id) {
+                                Ok(hash) => hash,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
+
+                            match self
+                                .storage
+                                .handle_legacy_direct_deploy_request(deploy_hash)
+                            {
+                                Some(deploy) => {
+                                    let header_by_id = DeployHeaderById::from(&deploy);
+                                    match Message::new_get_response(&header_by_id) {
+                                        Ok(message) => {
+                                            return effect_builder
+                                                .send_message(sender, message)
+                                                .ignore();
+                                        }
+                                        Err(error) => {
+                                            error!("failed to create get-response: {}", error);
+                                            return Effects::new();
+                                        }
+                                    };
+                                }
+                                None => {
+                                    debug!(
+                                        "failed to get header of {} for {}",
+                                        deploy_hash, sender
+                                    );
+                                    return Effects::new();
+                                }
+                            }
+                        }
                         Tag::Block => {
                             let block_hash = match bincode::deserialize(&serialized_id) {
                                 Ok(hash) => hash,
@@ -721,10 +1043,51 @@ impl reactor::Reactor for Reactor {
                                     return Effects::new();
                                 }
                             };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
                             Event::LinearChain(linear_chain::Event::Request(
                                 LinearChainRequest::BlockRequest(block_hash, sender),
                             ))
                         }
+                        Tag::BlockHeader => {
+                            let block_hash = match bincode::deserialize(&serialized_id) {
+                                Ok(hash) => hash,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
+                            Event::LinearChain(linear_chain::Event::Request(
+                                LinearChainRequest::BlockHeaderRequest(block_hash, sender),
+                            ))
+                        }
+                        Tag::BlockExecutionResults => {
+                            let block_hash = match bincode::deserialize(&serialized_id) {
+                                Ok(hash) => hash,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            if !self.serving_policy.permits(&sender, tag, false) {
+                                return Effects::new();
+                            }
+                            Event::LinearChain(linear_chain::Event::Request(
+                                LinearChainRequest::BlockExecutionResultsRequest(
+                                    block_hash, sender,
+                                ),
+                            ))
+                        }
                         Tag::BlockByHeight => {
                             let height = match bincode::deserialize(&serialized_id) {
                                 Ok(block_by_height) => block_by_height,
@@ -736,6 +1099,12 @@ impl reactor::Reactor for Reactor {
                                     return Effects::new();
                                 }
                             };
+                            let old_block = self
+                                .serving_policy
+                                .is_old_block(height, self.linear_chain.latest_block_height());
+                            if !self.serving_policy.permits(&sender, tag, old_block) {
+                                return Effects::new();
+                            }
                             Event::LinearChain(linear_chain::Event::Request(
                                 LinearChainRequest::BlockAtHeight(height, sender),
                             ))
@@ -763,8 +1132,61 @@ impl reactor::Reactor for Reactor {
                                 responder: None,
                             })
                         }
+                        Tag::DeployHeaderById => {
+                            let header_by_id: DeployHeaderById =
+                                match bincode::deserialize(&serialized_item) {
+                                    Ok(header_by_id) => header_by_id,
+                                    Err(error) => {
+                                        error!(
+                                            "failed to decode deploy header from {}: {}",
+                                            sender, error
+                                        );
+                                        return Effects::new();
+                                    }
+                                };
+                            if !header_by_id.is_valid() {
+                                warn!(%header_by_id, %sender, "received invalid deploy header");
+                                self.serving_policy.record_invalid(sender);
+                                return Effects::new();
+                            }
+                            Event::DeployHeaderFetcher(fetcher::Event::GotRemotely {
+                                item: Box::new(header_by_id),
+                                source: Source::Peer(sender),
+                            })
+                        }
+                        Tag::DeployChunk => {
+                            let chunk: DeployChunk = match bincode::deserialize(&serialized_item) {
+                                Ok(chunk) => chunk,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode deploy chunk from {}: {}",
+                                        sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            if !chunk.is_valid() {
+                                warn!(%chunk, %sender, "received invalid deploy chunk");
+                                self.serving_policy.record_invalid(sender);
+                                return Effects::new();
+                            }
+                            Event::DeployFetcher(fetcher::Event::GotChunk {
+                                chunk: Box::new(chunk),
+                                peer: sender,
+                            })
+                        }
                         Tag::Block => todo!("Handle GET block response"),
                         Tag::BlockByHeight => todo!("Handle GET BlockByHeight response"),
+                        Tag::BlockHeader => {
+                            // Block headers are only fetched while joining.
+                            debug!("ignoring block header from {}", sender);
+                            return Effects::new();
+                        }
+                        Tag::BlockExecutionResults => {
+                            // Execution results are only fetched while joining.
+                            debug!("ignoring block execution results from {}", sender);
+                            return Effects::new();
+                        }
                         Tag::GossipedAddress => {
                             warn!("received get request for gossiped-address from {}", sender);
                             return Effects::new();
@@ -796,15 +1218,52 @@ impl reactor::Reactor for Reactor {
                 };
                 self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
             }
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployApprovalsReceived {
+                deploy_hash,
+                approvals,
+                responder,
+            }) => {
+                let event = deploy_acceptor::Event::AddApprovals {
+                    deploy_hash,
+                    approvals,
+                    responder,
+                };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
+            }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::AcceptedNewDeploy {
                 deploy,
                 source,
             }) => {
+                if let Source::Peer(peer) = &source {
+                    self.serving_policy.record_valid(peer.clone());
+                }
+
+                let event = deploy_watcher::Event::DeployAccepted {
+                    deploy_hash: *deploy.id(),
+                    expires: deploy.header().expires(),
+                };
+                let mut effects =
+                    self.dispatch_event(effect_builder, rng, Event::DeployWatcher(event));
+
+                if self.webhook_notifier.is_enabled() {
+                    let event = webhook_notifier::Event::DeployAccepted {
+                        deploy_hash: *deploy.id(),
+                        account: *deploy.header().account(),
+                        timestamp: deploy.header().timestamp(),
+                        ttl: deploy.header().ttl(),
+                    };
+                    effects.extend(self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        Event::WebhookNotifier(event),
+                    ));
+                }
+
                 let deploy_type = match deploy.deploy_type() {
                     Ok(deploy_type) => deploy_type,
                     Err(error) => {
                         tracing::error!("Invalid deploy: {:?}", error);
-                        return Effects::new();
+                        return effects;
                     }
                 };
 
@@ -812,8 +1271,11 @@ impl reactor::Reactor for Reactor {
                     hash: *deploy.id(),
                     deploy_type: Box::new(deploy_type),
                 };
-                let mut effects =
-                    self.dispatch_event(effect_builder, rng, Event::BlockProposer(event));
+                effects.extend(self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    Event::BlockProposer(event),
+                ));
 
                 let event = gossiper::Event::ItemReceived {
                     item_id: *deploy.id(),
@@ -839,8 +1301,24 @@ impl reactor::Reactor for Reactor {
             }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy: _,
-                source: _,
-            }) => Effects::new(),
+                source,
+            }) => {
+                if let Source::Peer(peer) = source {
+                    self.serving_policy.record_invalid(peer);
+                }
+                Effects::new()
+            }
+            Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::ApprovalsUpdated {
+                deploy_hash,
+                approvals,
+            }) => {
+                let reactor_event =
+                    Event::EventStreamServer(event_stream_server::Event::DeployApprovalsUpdated {
+                        deploy_hash,
+                        approvals,
+                    });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
             Event::ConsensusAnnouncement(consensus_announcement) => {
                 match consensus_announcement {
                     ConsensusAnnouncement::Finalized(block) => {
@@ -883,10 +1361,24 @@ impl reactor::Reactor for Reactor {
             Event::BlockExecutorAnnouncement(BlockExecutorAnnouncement::LinearChainBlock {
                 block,
                 execution_results,
+                execution_duration,
             }) => {
                 let mut effects = Effects::new();
                 let block_hash = *block.hash();
 
+                // send to block exporter
+                if self.block_exporter.is_enabled() {
+                    let reactor_event =
+                        Event::BlockExporter(block_exporter::Event::BlockExecuted {
+                            block: Box::new(block.clone()),
+                            execution_results: execution_results
+                                .iter()
+                                .map(|(hash, (_header, result))| (*hash, result.clone()))
+                                .collect(),
+                        });
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
                 // send to linear chain
                 let reactor_event = Event::LinearChain(linear_chain::Event::LinearChainBlock {
                     block: Box::new(block),
@@ -897,6 +1389,19 @@ impl reactor::Reactor for Reactor {
                 });
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
 
+                // send to webhook notifier
+                if self.webhook_notifier.is_enabled() {
+                    for (deploy_hash, (_header, execution_result)) in &execution_results {
+                        let reactor_event =
+                            Event::WebhookNotifier(webhook_notifier::Event::DeployProcessed {
+                                deploy_hash: *deploy_hash,
+                                block_hash,
+                                execution_result: Box::new(execution_result.clone()),
+                            });
+                        effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                    }
+                }
+
                 // send to event stream
                 for (deploy_hash, (deploy_header, execution_result)) in execution_results {
                     let reactor_event =
@@ -909,28 +1414,56 @@ impl reactor::Reactor for Reactor {
                     effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 }
 
+                // let consensus adapt its round length to the execution latency
+                let reactor_event =
+                    Event::Consensus(consensus::Event::BlockExecuted(execution_duration));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+
                 effects
             }
-            Event::DeployGossiperAnnouncement(_ann) => {
-                unreachable!("the deploy gossiper should never make an announcement")
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewItemBody {
+                item,
+                sender,
+            }) => {
+                let event = deploy_acceptor::Event::Accept {
+                    deploy: item,
+                    source: Source::Peer(sender),
+                    responder: None,
+                };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
             }
-            Event::AddressGossiperAnnouncement(ann) => {
-                let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(_)) => {
+                unreachable!("the deploy gossiper should never announce a new complete item")
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
                 let reactor_event = Event::SmallNetwork(small_network::Event::PeerAddressReceived(
                     gossiped_address,
                 ));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewItemBody { .. }) => {
+                unreachable!("the address gossiper should never announce an item body")
+            }
             Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded {
                 block_hash,
                 block,
             }) => {
+                let mut effects = Effects::new();
+                if self.webhook_notifier.is_enabled() {
+                    let reactor_event =
+                        Event::WebhookNotifier(webhook_notifier::Event::BlockAdded(block.clone()));
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
                 let reactor_event =
                     Event::EventStreamServer(event_stream_server::Event::BlockAdded {
                         block_hash,
                         block,
                     });
-                self.dispatch_event(effect_builder, rng, reactor_event)
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
             }
             Event::LinearChainAnnouncement(LinearChainAnnouncement::NewFinalitySignature(fs)) => {
                 let reactor_event =
@@ -951,6 +1484,42 @@ impl reactor::Reactor for Reactor {
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 effects
             }
+            Event::ClockMonitorAnnouncement(ann) => {
+                let suspended = match ann {
+                    ClockMonitorAnnouncement::ExcessiveDrift { .. } => true,
+                    ClockMonitorAnnouncement::DriftRecovered { .. } => false,
+                };
+                let reactor_event = Event::Consensus(consensus::Event::SuspendProposing(suspended));
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::DeployWatcherAnnouncement(DeployWatcherAnnouncement::DeployNotIncluded {
+                deploy_hash,
+                status,
+            }) => {
+                let reactor_event =
+                    Event::EventStreamServer(event_stream_server::Event::DeployNotIncluded {
+                        deploy_hash,
+                        status,
+                    });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::DiskWatchdogAnnouncement(DiskWatchdogAnnouncement::DiskSpaceStatusChanged {
+                status,
+                disk_space,
+            }) => {
+                let read_only = status == DiskSpaceStatus::Critical;
+                let reactor_event =
+                    Event::DeployAcceptor(deploy_acceptor::Event::SetReadOnly(read_only));
+                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
+
+                let reactor_event =
+                    Event::EventStreamServer(event_stream_server::Event::DiskSpaceStatus {
+                        status,
+                        disk_space,
+                    });
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
+            }
         }
     }
 
@@ -960,6 +1529,55 @@ impl reactor::Reactor for Reactor {
             .record_event_queue_counts(&event_queue_handle)
     }
 
+    fn reload_config(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+    ) -> Effects<Self::Event> {
+        let config = match self.config_loader.as_ref().map(ConfigLoader::load) {
+            Some(Ok(config)) => config.into_parts().1,
+            Some(Err(error)) => {
+                warn!(%error, "failed to reload config, keeping current settings");
+                return Effects::new();
+            }
+            None => {
+                warn!("cannot reload config: no config source was provided");
+                return Effects::new();
+            }
+        };
+
+        if let Err(error) = logging::reload_filter(&config.logging) {
+            warn!(%error, "failed to apply reloaded log filter");
+        }
+        self.serving_policy.set_config(config.serving_policy);
+
+        let mut effects = self.dispatch_event(
+            effect_builder,
+            rng,
+            Event::RpcServer(rpc_server::Event::ConfigUpdated(Box::new(
+                config.rpc_server,
+            ))),
+        );
+        effects.extend(self.dispatch_event(
+            effect_builder,
+            rng,
+            Event::DeployGossiper(gossiper::Event::ConfigUpdated(config.gossip)),
+        ));
+        effects.extend(self.dispatch_event(
+            effect_builder,
+            rng,
+            Event::AddressGossiper(gossiper::Event::ConfigUpdated(config.gossip)),
+        ));
+        effects.extend(self.dispatch_event(
+            effect_builder,
+            rng,
+            Event::Consensus(consensus::Event::CatchUpConfigUpdated(
+                config.consensus.catch_up,
+            )),
+        ));
+        effects
+    }
+
     fn maybe_exit(&self) -> Option<ReactorExit> {
         self.consensus
             .stop_for_upgrade()
@@ -967,6 +1585,35 @@ impl reactor::Reactor for Reactor {
     }
 }
 
+/// Returns the response to a request for `deploy`: the deploy itself if its serialization fits
+/// into a single chunk of `chunk_size` bytes, or its first chunk otherwise.
+fn deploy_get_response(deploy: &Deploy, chunk_size: u32) -> Result<Message, bincode::Error> {
+    let serialized_deploy = bincode::serialize(deploy)?;
+    match DeployChunk::new(*deploy.id(), &serialized_deploy, 0, chunk_size) {
+        Some(chunk) if chunk.count() > 1 => Message::new_get_response(&chunk),
+        _ => Ok(Message::GetResponse {
+            tag: Tag::Deploy,
+            serialized_item: serialized_deploy.into(),
+        }),
+    }
+}
+
+/// Returns the response to a request for chunk `index` of `deploy`, or `None` if there is no such
+/// chunk.
+///
+/// The deploy is serialized anew for every chunk, which is cheap compared to reading it from
+/// storage.
+fn deploy_chunk_response(
+    deploy: &Deploy,
+    index: u32,
+    chunk_size: u32,
+) -> Result<Option<Message>, bincode::Error> {
+    let serialized_deploy = bincode::serialize(deploy)?;
+    DeployChunk::new(*deploy.id(), &serialized_deploy, index, chunk_size)
+        .map(|chunk| Message::new_get_response(&chunk))
+        .transpose()
+}
+
 #[cfg(test)]
 impl NetworkedReactor for Reactor {
     type NodeId = NodeId;
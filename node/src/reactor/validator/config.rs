@@ -1,10 +1,18 @@
+use std::fmt::{self, Debug, Formatter};
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use super::ServingPolicyConfig;
 use crate::{
-    logging::LoggingConfig, types::NodeConfig, ConsensusConfig, ContractRuntimeConfig,
-    DeployAcceptorConfig, EventStreamServerConfig, FetcherConfig, GossipConfig, RestServerConfig,
-    RpcServerConfig, SmallNetworkConfig, StorageConfig,
+    logging::LoggingConfig,
+    types::NodeConfig,
+    utils::{ConfigValidationReport, ConfigValidator, WithDir},
+    BlockExecutorConfig, BlockExporterConfig, BlockProposerConfig, BlockValidatorConfig,
+    ClockMonitorConfig, ConsensusConfig, ContractRuntimeConfig, DeployAcceptorConfig,
+    DeployWatcherConfig, DiskWatchdogConfig, EventStreamServerConfig, FetcherConfig, GossipConfig,
+    LinearChainSyncConfig, RestServerConfig, RpcServerConfig, SmallNetworkConfig, StorageConfig,
+    WebhookNotifierConfig,
 };
 
 /// Root configuration.
@@ -36,4 +44,103 @@ pub struct Config {
     pub contract_runtime: ContractRuntimeConfig,
     /// Deploy acceptor configuration.
     pub deploy_acceptor: DeployAcceptorConfig,
+    /// Deploy watcher configuration.
+    pub deploy_watcher: DeployWatcherConfig,
+    /// Disk watchdog configuration.
+    pub disk_watchdog: DiskWatchdogConfig,
+    /// Block exporter configuration.
+    pub block_exporter: BlockExporterConfig,
+    /// Webhook notifier configuration.
+    pub webhook_notifier: WebhookNotifierConfig,
+    /// Block executor configuration.
+    pub block_executor: BlockExecutorConfig,
+    /// Block proposer configuration.
+    pub block_proposer: BlockProposerConfig,
+    /// Block validator configuration.
+    pub block_validator: BlockValidatorConfig,
+    /// Clock monitor configuration.
+    pub clock_monitor: ClockMonitorConfig,
+    /// Linear chain synchronization configuration.
+    pub linear_chain_sync: LinearChainSyncConfig,
+    /// Policy for serving items requested by peers.
+    pub serving_policy: ServingPolicyConfig,
+}
+
+impl Config {
+    /// Validates the values of all sections which deserialization can't check, returning a report
+    /// of every invalid value found.
+    pub fn validate(&self) -> Result<(), ConfigValidationReport> {
+        let mut validator = ConfigValidator::default();
+        self.network.validate(&mut validator.section("network"));
+        self.gossip.validate(&mut validator.section("gossip"));
+        self.fetcher.validate(&mut validator.section("fetcher"));
+        self.rpc_server
+            .validate(&mut validator.section("rpc_server"));
+        self.deploy_acceptor
+            .validate(&mut validator.section("deploy_acceptor"));
+        self.disk_watchdog
+            .validate(&mut validator.section("disk_watchdog"));
+        self.webhook_notifier
+            .validate(&mut validator.section("webhook_notifier"));
+        self.block_executor
+            .validate(&mut validator.section("block_executor"));
+        self.block_proposer
+            .validate(&mut validator.section("block_proposer"));
+        self.linear_chain_sync
+            .validate(&mut validator.section("linear_chain_sync"));
+        validator.finish()
+    }
+}
+
+/// Loads the root configuration afresh, e.g. by re-reading the file it was originally read from.
+///
+/// Used by the validator reactor to reload the configuration while running.
+pub struct ConfigLoader(Box<dyn Fn() -> anyhow::Result<WithDir<Config>> + Send + Sync>);
+
+impl ConfigLoader {
+    /// Creates a new config loader calling `load` each time the configuration is reloaded.
+    pub fn new<F>(load: F) -> Self
+    where
+        F: Fn() -> anyhow::Result<WithDir<Config>> + Send + Sync + 'static,
+    {
+        ConfigLoader(Box::new(load))
+    }
+
+    /// Loads the configuration.
+    pub(super) fn load(&self) -> anyhow::Result<WithDir<Config>> {
+        (self.0)()
+    }
+}
+
+impl Debug for ConfigLoader {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "ConfigLoader")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_invalid_values_of_all_sections() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.block_proposer.fairness = 2.0;
+        config.disk_watchdog.read_only_threshold = config.disk_watchdog.warn_threshold + 1;
+        let report = config.validate().unwrap_err();
+        let fields: Vec<_> = report
+            .errors
+            .iter()
+            .map(|error| (error.component, error.field.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("disk_watchdog", "read_only_threshold"),
+                ("block_proposer", "fairness")
+            ]
+        );
+    }
 }
@@ -1,7 +1,9 @@
 use thiserror::Error;
 
 use crate::{
-    components::{contract_runtime, network, small_network, storage},
+    components::{
+        block_exporter, contract_runtime, network, small_network, storage, webhook_notifier,
+    },
     utils::ListeningError,
 };
 
@@ -32,10 +34,22 @@ pub enum Error {
     #[error("consensus error: {0}")]
     Consensus(#[from] anyhow::Error),
 
+    /// `BlockExporter` component error.
+    #[error("block exporter error: {0}")]
+    BlockExporter(#[from] block_exporter::Error),
+
+    /// `WebhookNotifier` component error.
+    #[error("webhook notifier error: {0}")]
+    WebhookNotifier(#[from] webhook_notifier::Error),
+
     /// `ContractRuntime` component error.
     #[error("contract runtime config error: {0}")]
     ContractRuntime(#[from] contract_runtime::ConfigError),
 
+    /// Failed to build the signature verification thread pool.
+    #[error("signature verifier error: {0}")]
+    SignatureVerifier(#[from] rayon::ThreadPoolBuildError),
+
     /// Failed to serialize data.
     #[error("serialization: {0}")]
     Serialization(#[source] bincode::ErrorKind),
@@ -0,0 +1,268 @@
+//! Policy deciding which items requested by peers are served.
+//!
+//! Answering every `GetRequest` lets any peer make this node read and send arbitrary amounts of
+//! historical data.  The policy restricts each kind of item to everyone, to reliable peers only,
+//! or to no peer at all.  Since peers are not authenticated as validators, the policy applies to
+//! every peer alike.
+//!
+//! A peer's reliability is derived from the deploys it sent us: each valid deploy raises it, each
+//! invalid one lowers it.  Peers we have not heard from yet start out at a neutral score of 0.5.
+
+use std::collections::HashMap;
+
+use datasize::DataSize;
+use prometheus::{IntCounterVec, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    types::{NodeId, Tag},
+    unregister_metric,
+};
+
+const DEFAULT_OLD_BLOCK_DEPTH: u64 = 1000;
+const DEFAULT_MIN_RELIABILITY: f64 = 0.5;
+
+/// Which peers an item is served to.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServeTo {
+    /// The item is served to every peer.
+    All,
+    /// The item is only served to peers whose reliability is at least `min_reliability`.
+    Reliable,
+    /// The item is not served at all.
+    None,
+}
+
+/// Serving policy configuration.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Who deploys are served to.
+    pub deploys: ServeTo,
+    /// Who blocks are served to, unless they are old blocks requested by height.
+    pub blocks: ServeTo,
+    /// Who blocks requested by height which are at least `old_block_depth` blocks below the
+    /// highest known block are served to.
+    pub old_blocks: ServeTo,
+    /// Number of blocks below the highest known block from which on a block counts as old.
+    pub old_block_depth: u64,
+    /// Reliability score between 0.0 and 1.0 a peer needs to be served items restricted to
+    /// reliable peers.
+    pub min_reliability: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            deploys: ServeTo::All,
+            blocks: ServeTo::All,
+            old_blocks: ServeTo::All,
+            old_block_depth: DEFAULT_OLD_BLOCK_DEPTH,
+            min_reliability: DEFAULT_MIN_RELIABILITY,
+        }
+    }
+}
+
+/// Number of valid and invalid items received from a peer.
+#[derive(Copy, Clone, DataSize, Debug, Default)]
+struct PeerRecord {
+    valid: u64,
+    invalid: u64,
+}
+
+impl PeerRecord {
+    /// Returns the share of valid items, smoothed so that an unknown peer scores 0.5.
+    fn reliability(&self) -> f64 {
+        (self.valid as f64 + 1.0) / ((self.valid + self.invalid) as f64 + 2.0)
+    }
+}
+
+/// Applies the serving policy to incoming requests and tracks peer reliability.
+#[derive(DataSize, Debug)]
+pub(super) struct ServingPolicy {
+    config: Config,
+    peers: HashMap<NodeId, PeerRecord>,
+    #[data_size(skip)]
+    metrics: ServingPolicyMetrics,
+}
+
+impl ServingPolicy {
+    /// Creates a new serving policy.
+    pub(super) fn new(config: Config, registry: &Registry) -> Result<Self, prometheus::Error> {
+        Ok(ServingPolicy {
+            config,
+            peers: HashMap::new(),
+            metrics: ServingPolicyMetrics::new(registry.clone())?,
+        })
+    }
+
+    /// Replaces the configuration, keeping the reliability already recorded for peers.
+    pub(super) fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Records that `peer` sent us a valid item.
+    pub(super) fn record_valid(&mut self, peer: NodeId) {
+        self.peers.entry(peer).or_default().valid += 1;
+    }
+
+    /// Records that `peer` sent us an invalid item.
+    pub(super) fn record_invalid(&mut self, peer: NodeId) {
+        self.peers.entry(peer).or_default().invalid += 1;
+    }
+
+    /// Returns the reliability score of `peer`, between 0.0 and 1.0.
+    pub(super) fn reliability(&self, peer: &NodeId) -> f64 {
+        self.peers
+            .get(peer)
+            .copied()
+            .unwrap_or_default()
+            .reliability()
+    }
+
+    /// Returns whether a block at `height` counts as old given the highest known block height.
+    pub(super) fn is_old_block(&self, height: u64, highest_height: Option<u64>) -> bool {
+        highest_height.map_or(false, |highest| {
+            highest.saturating_sub(height) >= self.config.old_block_depth
+        })
+    }
+
+    /// Returns whether an item with the given tag may be served to `peer`, counting refusals.
+    ///
+    /// `old_block` is only relevant to blocks and selects the rule for old blocks.
+    pub(super) fn permits(&self, peer: &NodeId, tag: Tag, old_block: bool) -> bool {
+        let serve_to = match tag {
+            Tag::Deploy | Tag::DeployHeaderById | Tag::DeployChunk => self.config.deploys,
+            Tag::Block | Tag::BlockByHeight | Tag::BlockHeader | Tag::BlockExecutionResults
+                if old_block =>
+            {
+                self.config.old_blocks
+            }
+            Tag::Block | Tag::BlockByHeight | Tag::BlockHeader | Tag::BlockExecutionResults => {
+                self.config.blocks
+            }
+            Tag::GossipedAddress => ServeTo::All,
+        };
+        let permitted = match serve_to {
+            ServeTo::All => true,
+            ServeTo::Reliable => self.reliability(peer) >= self.config.min_reliability,
+            ServeTo::None => false,
+        };
+        if !permitted {
+            debug!(%peer, %tag, old_block, "refusing get request due to serving policy");
+            self.metrics
+                .refused_requests
+                .with_label_values(&[&tag.to_string()])
+                .inc();
+        }
+        permitted
+    }
+}
+
+/// Metrics for the serving policy.
+#[derive(Debug)]
+struct ServingPolicyMetrics {
+    /// Number of get requests refused, by tag.
+    refused_requests: IntCounterVec,
+    /// Instance of registry to unregister from when being dropped.
+    registry: Registry,
+}
+
+impl ServingPolicyMetrics {
+    fn new(registry: Registry) -> Result<Self, prometheus::Error> {
+        let refused_requests = IntCounterVec::new(
+            Opts::new(
+                "serving_policy_refused_requests",
+                "number of get requests from peers refused by the serving policy",
+            ),
+            &["tag"],
+        )?;
+        registry.register(Box::new(refused_requests.clone()))?;
+        Ok(ServingPolicyMetrics {
+            refused_requests,
+            registry,
+        })
+    }
+}
+
+impl Drop for ServingPolicyMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.refused_requests);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    fn refused(policy: &ServingPolicy, tag: Tag) -> u64 {
+        policy
+            .metrics
+            .refused_requests
+            .with_label_values(&[&tag.to_string()])
+            .get()
+    }
+
+    #[test]
+    fn should_serve_everything_by_default() {
+        let mut rng = TestRng::new();
+        let policy = ServingPolicy::new(Config::default(), &Registry::new()).unwrap();
+        let peer = NodeId::random(&mut rng);
+
+        assert!(policy.permits(&peer, Tag::Deploy, false));
+        assert!(policy.permits(&peer, Tag::Block, false));
+        assert!(policy.permits(&peer, Tag::BlockByHeight, true));
+        assert_eq!(refused(&policy, Tag::Deploy), 0);
+    }
+
+    #[test]
+    fn should_refuse_old_blocks_and_count_refusals() {
+        let mut rng = TestRng::new();
+        let config = Config {
+            old_blocks: ServeTo::None,
+            old_block_depth: 10,
+            ..Config::default()
+        };
+        let policy = ServingPolicy::new(config, &Registry::new()).unwrap();
+        let peer = NodeId::random(&mut rng);
+
+        assert!(!policy.is_old_block(95, Some(100)));
+        assert!(policy.is_old_block(90, Some(100)));
+        assert!(!policy.is_old_block(0, None));
+
+        assert!(policy.permits(&peer, Tag::BlockByHeight, false));
+        assert!(!policy.permits(&peer, Tag::BlockByHeight, true));
+        assert!(!policy.permits(&peer, Tag::BlockByHeight, true));
+        assert_eq!(refused(&policy, Tag::BlockByHeight), 2);
+    }
+
+    #[test]
+    fn should_only_serve_reliable_peers() {
+        let mut rng = TestRng::new();
+        let config = Config {
+            deploys: ServeTo::Reliable,
+            min_reliability: 0.6,
+            ..Config::default()
+        };
+        let mut policy = ServingPolicy::new(config, &Registry::new()).unwrap();
+        let good_peer = NodeId::random(&mut rng);
+        let bad_peer = NodeId::random(&mut rng);
+
+        // Unknown peers have a neutral score, below the required one.
+        assert!((policy.reliability(&good_peer) - 0.5).abs() < f64::EPSILON);
+        assert!(!policy.permits(&good_peer, Tag::Deploy, false));
+
+        for _ in 0..3 {
+            policy.record_valid(good_peer.clone());
+            policy.record_invalid(bad_peer.clone());
+        }
+        assert!(policy.permits(&good_peer, Tag::Deploy, false));
+        assert!(!policy.permits(&bad_peer, Tag::Deploy, false));
+        assert!(policy.permits(&bad_peer, Tag::Block, false));
+        assert_eq!(refused(&policy, Tag::Deploy), 2);
+    }
+}
@@ -83,7 +83,7 @@ impl TestChain {
             ActivationPoint::Genesis(Timestamp::now() + 45000.into());
 
         chainspec.core_config.minimum_era_height = 1;
-        chainspec.highway_config.finality_threshold_fraction = Ratio::new(34, 100);
+        chainspec.core_config.finality_threshold_fraction = Ratio::new(34, 100);
         chainspec.core_config.era_duration = 10.into();
 
         TestChain {
@@ -23,7 +23,10 @@ use tokio::runtime::{self, Runtime};
 
 use crate::{
     components::Component,
-    effect::{EffectBuilder, Effects, Responder},
+    effect::{
+        time_source::{ManualClock, TimeSource},
+        EffectBuilder, Effects, Responder,
+    },
     logging,
     reactor::{EventQueueHandle, QueueKind, Scheduler},
 };
@@ -111,6 +114,7 @@ pub(crate) struct ComponentHarness<REv: 'static> {
 pub(crate) struct ComponentHarnessBuilder<REv: 'static> {
     rng: Option<TestRng>,
     tmp: Option<TempDir>,
+    time_source: TimeSource,
     _phantom: PhantomData<REv>,
 }
 
@@ -136,6 +140,15 @@ impl<REv: 'static> ComponentHarnessBuilder<REv> {
         self
     }
 
+    /// Sets a manually advanced clock as the source of time of the harness' effects.
+    pub(crate) fn manual_clock(
+        mut self,
+        clock: &'static ManualClock,
+    ) -> ComponentHarnessBuilder<REv> {
+        self.time_source = TimeSource::Manual(clock);
+        self
+    }
+
     /// Tries to build a component harness.
     ///
     /// Construction may fail for various reasons such as not being able to create a temporary
@@ -151,7 +164,7 @@ impl<REv: 'static> ComponentHarnessBuilder<REv> {
         let rng = self.rng.unwrap_or_else(TestRng::new);
 
         let scheduler = Box::leak(Box::new(Scheduler::new(QueueKind::weights())));
-        let event_queue_handle = EventQueueHandle::new(scheduler);
+        let event_queue_handle = EventQueueHandle::with_time_source(scheduler, self.time_source);
         let effect_builder = EffectBuilder::new(event_queue_handle);
         let runtime = runtime::Builder::new()
             .threaded_scheduler()
@@ -176,6 +189,7 @@ impl<REv: 'static> ComponentHarness<REv> {
         ComponentHarnessBuilder {
             rng: None,
             tmp: None,
+            time_source: TimeSource::Real,
             _phantom: PhantomData,
         }
     }
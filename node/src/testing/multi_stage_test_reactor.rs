@@ -22,7 +22,7 @@ use crate::{
         initializer::Reactor as InitializerReactor,
         joiner::Reactor as JoinerReactor,
         validator::{Reactor as ValidatorReactor, ValidatorInitConfig},
-        wrap_effects, EventQueueHandle, QueueKind, Reactor, ReactorExit, Scheduler,
+        wrap_effects, EventQueueHandle, QueueKind, QueuedEvent, Reactor, ReactorExit, Scheduler,
     },
     testing::network::NetworkedReactor,
     types::{Chainspec, NodeId},
@@ -150,8 +150,16 @@ where
 {
     // Note: This will keep waiting forever if the sending end disappears, which is fine for tests.
     loop {
-        let (event, queue_kind) = source.pop().await;
-        target_queue.schedule(event, queue_kind).await;
+        let (
+            QueuedEvent {
+                event,
+                correlation_id,
+            },
+            queue_kind,
+        ) = source.pop().await;
+        correlation_id
+            .scope(target_queue.schedule(event, queue_kind))
+            .await;
     }
 }
 
@@ -176,7 +184,7 @@ impl Reactor for MultiStageTestReactor {
         let initializer_scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
         let initializer_event_queue_handle: EventQueueHandle<
             <InitializerReactor as Reactor>::Event,
-        > = EventQueueHandle::new(initializer_scheduler);
+        > = EventQueueHandle::with_time_source(initializer_scheduler, event_queue.time_source());
 
         tokio::spawn(forward_to_queue(initializer_scheduler, event_queue));
 
@@ -353,7 +361,10 @@ impl Reactor for MultiStageTestReactor {
                     );
 
                     let joiner_scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
-                    let joiner_event_queue_handle = EventQueueHandle::new(joiner_scheduler);
+                    let joiner_event_queue_handle = EventQueueHandle::with_time_source(
+                        joiner_scheduler,
+                        effect_builder.into_inner().time_source(),
+                    );
 
                     tokio::spawn(forward_to_queue(
                         joiner_scheduler,
@@ -426,7 +437,10 @@ impl Reactor for MultiStageTestReactor {
 
                     // JoinerFinalizing transitions into a validator.
                     let validator_scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
-                    let validator_event_queue_handle = EventQueueHandle::new(validator_scheduler);
+                    let validator_event_queue_handle = EventQueueHandle::with_time_source(
+                        validator_scheduler,
+                        effect_builder.into_inner().time_source(),
+                    );
 
                     tokio::spawn(forward_to_queue(
                         validator_scheduler,
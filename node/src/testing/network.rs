@@ -1,5 +1,7 @@
 //! A network of test reactors.
 
+pub(crate) mod adversary;
+
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::{Debug, Display},
@@ -16,7 +18,10 @@ use tracing_futures::Instrument;
 
 use super::ConditionCheckReactor;
 use crate::{
-    effect::{EffectBuilder, Effects},
+    effect::{
+        time_source::{ManualClock, TimeSource},
+        EffectBuilder, Effects,
+    },
     reactor::{Finalize, Reactor, Runner},
     testing::TestRng,
     NodeRng,
@@ -48,6 +53,8 @@ const POLL_INTERVAL: Duration = Duration::from_millis(10);
 pub struct Network<R: Reactor + NetworkedReactor> {
     /// Current network.
     nodes: HashMap<<R as NetworkedReactor>::NodeId, Runner<ConditionCheckReactor<R>>>,
+    /// The source of time of all nodes.
+    time_source: TimeSource,
 }
 
 impl<R> Network<R>
@@ -92,6 +99,15 @@ where
     pub fn new() -> Self {
         Network {
             nodes: HashMap::new(),
+            time_source: TimeSource::Real,
+        }
+    }
+
+    /// Creates a new network whose nodes all read the time from `clock`.
+    pub(crate) fn with_manual_clock(clock: &'static ManualClock) -> Self {
+        Network {
+            nodes: HashMap::new(),
+            time_source: TimeSource::Manual(clock),
         }
     }
 
@@ -105,7 +121,8 @@ where
         cfg: R::Config,
         rng: &mut NodeRng,
     ) -> Result<(R::NodeId, &mut Runner<ConditionCheckReactor<R>>), R::Error> {
-        let runner: Runner<ConditionCheckReactor<R>> = Runner::new(cfg, rng).await?;
+        let runner: Runner<ConditionCheckReactor<R>> =
+            Runner::new_with_time_source(cfg, rng, self.time_source).await?;
 
         let node_id = runner.reactor().node_id();
 
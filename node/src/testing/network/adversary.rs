@@ -0,0 +1,541 @@
+//! Scripted adversarial peers for black-box tests of the components fetching from the network.
+//!
+//! An [`Adversary`] is a minimal reactor joining the in-memory network, which answers the
+//! `GetRequest`s it receives from a fixed [`AdversaryChain`] according to its [`Misbehavior`].
+//! The adversaries of a test are described by a [`Scenario`], usually loaded from a TOML file under
+//! `resources/test/scenarios`, so that the same attack can be replayed against different nodes.
+//!
+//! Adversaries run in a `Network<Adversary>` of their own, cranked alongside the network of the
+//! reactors under test; both join the active `NetworkController<Message>`.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::Path,
+    sync::Arc,
+};
+
+use derive_more::From;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::{Network, NetworkedReactor};
+use crate::{
+    components::{
+        in_memory_network::{self, InMemoryNetwork, NetworkController},
+        Component,
+    },
+    effect::{
+        announcements::NetworkAnnouncement, requests::NetworkRequest, EffectBuilder, EffectExt,
+        Effects,
+    },
+    protocol::Message,
+    reactor::{self, EventQueueHandle, ReactorExit},
+    testing::TestRng,
+    types::{
+        Block, BlockByHeight, BlockHash, Deploy, DeployHash, DeployHeaderById, FinalitySignature,
+        NodeId, Tag, TimeDiff,
+    },
+    utils::{self, Loadable},
+    NodeRng,
+};
+
+/// The way an adversary answers requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Misbehavior {
+    /// Answers every request for a known item with that item.
+    Honest,
+    /// Answers with the requested item, altered after hashing so that it fails verification.
+    InvalidHeader,
+    /// Answers with a valid item other than the requested one.
+    WrongItem,
+    /// Answers honestly, followed by the chain's stale finality signatures.
+    StaleSignatures,
+    /// Answers honestly, but only after `delay`.
+    SlowLoris {
+        /// The delay before each response.
+        delay: TimeDiff,
+    },
+    /// Never answers.
+    Silent,
+}
+
+impl Default for Misbehavior {
+    fn default() -> Self {
+        Misbehavior::Honest
+    }
+}
+
+/// The script of one or more identical adversaries.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AdversaryScript {
+    /// How the adversaries answer requests.
+    pub(crate) misbehavior: Misbehavior,
+    /// The number of adversaries following this script.
+    #[serde(default = "default_count")]
+    pub(crate) count: usize,
+}
+
+fn default_count() -> usize {
+    1
+}
+
+/// A set of adversaries to add to a test network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Scenario {
+    /// The scripts of the adversaries.
+    pub(crate) adversaries: Vec<AdversaryScript>,
+}
+
+impl Scenario {
+    /// Adds the adversaries of the scenario to `network`, all serving `chain`, and returns their
+    /// IDs in the order of their scripts.
+    pub(crate) async fn add_adversaries(
+        &self,
+        network: &mut Network<Adversary>,
+        chain: Arc<AdversaryChain>,
+        rng: &mut TestRng,
+    ) -> Vec<NodeId> {
+        let mut node_ids = vec![];
+        for script in &self.adversaries {
+            for _ in 0..script.count {
+                let config = AdversaryConfig {
+                    script: script.clone(),
+                    chain: Arc::clone(&chain),
+                };
+                let (node_id, _runner) = network
+                    .add_node_with_config(config, rng)
+                    .await
+                    .expect("should add adversary");
+                node_ids.push(node_id);
+            }
+        }
+        node_ids
+    }
+}
+
+impl Loadable for Scenario {
+    type Error = anyhow::Error;
+
+    fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
+        let bytes = utils::read_file(path)?;
+        Ok(toml::from_slice(&bytes)?)
+    }
+}
+
+/// The items an adversary knows about.
+///
+/// `WrongItem` answers with the next item of the same kind, so the chain should hold at least two
+/// blocks and two deploys.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AdversaryChain {
+    /// The honest blocks.
+    pub(crate) blocks: Vec<Block>,
+    /// The honest deploys.
+    pub(crate) deploys: Vec<Deploy>,
+    /// Finality signatures replayed by `StaleSignatures` adversaries.
+    pub(crate) stale_signatures: Vec<FinalitySignature>,
+}
+
+impl AdversaryChain {
+    /// Returns the response to a request for the item with the given tag and serialized ID, if
+    /// any.
+    fn response(
+        &self,
+        misbehavior: &Misbehavior,
+        tag: Tag,
+        serialized_id: &[u8],
+    ) -> Option<Message> {
+        let response = match tag {
+            Tag::Block | Tag::BlockHeader => {
+                let block_hash: BlockHash = bincode::deserialize(serialized_id).ok()?;
+                let index = self
+                    .blocks
+                    .iter()
+                    .position(|block| *block.hash() == block_hash)?;
+                let block = pick(&self.blocks, index, misbehavior, |block| {
+                    block.corrupt_header();
+                });
+                if tag == Tag::Block {
+                    Message::new_get_response(&block)
+                } else {
+                    Message::new_get_response(block.header())
+                }
+            }
+            Tag::BlockByHeight => {
+                let height: u64 = bincode::deserialize(serialized_id).ok()?;
+                let item = match self
+                    .blocks
+                    .iter()
+                    .position(|block| block.height() == height)
+                {
+                    Some(index) => {
+                        BlockByHeight::new(pick(&self.blocks, index, misbehavior, |block| {
+                            block.corrupt_header();
+                        }))
+                    }
+                    None => BlockByHeight::Absent(height),
+                };
+                Message::new_get_response(&item)
+            }
+            Tag::Deploy | Tag::DeployHeaderById => {
+                let deploy_hash: DeployHash = bincode::deserialize(serialized_id).ok()?;
+                let index = self
+                    .deploys
+                    .iter()
+                    .position(|deploy| *deploy.id() == deploy_hash)?;
+                let deploy = pick(&self.deploys, index, misbehavior, |deploy| {
+                    deploy.corrupt_header();
+                });
+                if tag == Tag::Deploy {
+                    Message::new_get_response(&deploy)
+                } else {
+                    Message::new_get_response(&DeployHeaderById::from(&deploy))
+                }
+            }
+            // Deploys are always served in one piece, and the adversary holds no execution results.
+            Tag::GossipedAddress | Tag::DeployChunk | Tag::BlockExecutionResults => return None,
+        };
+        response
+            .map_err(|error| warn!(%error, "failed to serialize adversary response"))
+            .ok()
+    }
+}
+
+/// Returns the item at `index`, or the one `misbehavior` substitutes for it.
+fn pick<T, F>(items: &[T], index: usize, misbehavior: &Misbehavior, corrupt: F) -> T
+where
+    T: Clone,
+    F: FnOnce(&mut T),
+{
+    match misbehavior {
+        Misbehavior::InvalidHeader => {
+            let mut item = items[index].clone();
+            corrupt(&mut item);
+            item
+        }
+        Misbehavior::WrongItem => items[(index + 1) % items.len()].clone(),
+        _ => items[index].clone(),
+    }
+}
+
+/// The configuration of a single adversary.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AdversaryConfig {
+    /// The adversary's script.
+    pub(crate) script: AdversaryScript,
+    /// The items the adversary serves.
+    pub(crate) chain: Arc<AdversaryChain>,
+}
+
+/// Adversary event.
+#[derive(Debug, From, Serialize)]
+pub(crate) enum Event {
+    #[from]
+    Network(#[serde(skip_serializing)] in_memory_network::Event<Message>),
+    #[from]
+    NetworkRequest(#[serde(skip_serializing)] NetworkRequest<NodeId, Message>),
+    #[from]
+    NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, Message>),
+    /// A delayed response is due.
+    DelayedResponse {
+        dest: NodeId,
+        #[serde(skip_serializing)]
+        message: Box<Message>,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Network(event) => write!(formatter, "network: {}", event),
+            Event::NetworkRequest(request) => write!(formatter, "network request: {}", request),
+            Event::NetworkAnnouncement(announcement) => {
+                write!(formatter, "network announcement: {}", announcement)
+            }
+            Event::DelayedResponse { dest, message } => {
+                write!(formatter, "delayed response to {}: {}", dest, message)
+            }
+        }
+    }
+}
+
+/// A peer answering requests according to its script.
+///
+/// Also records every message it receives, so an honest adversary doubles as a probe.
+#[derive(Debug)]
+pub(crate) struct Adversary {
+    network: InMemoryNetwork<Message>,
+    script: AdversaryScript,
+    chain: Arc<AdversaryChain>,
+    received: Vec<(NodeId, Message)>,
+}
+
+impl Adversary {
+    /// Returns the senders and payloads of all messages received so far.
+    pub(crate) fn received(&self) -> &[(NodeId, Message)] {
+        &self.received
+    }
+
+    fn handle_get_request(
+        &self,
+        effect_builder: EffectBuilder<Event>,
+        sender: NodeId,
+        tag: Tag,
+        serialized_id: &[u8],
+    ) -> Effects<Event> {
+        let misbehavior = &self.script.misbehavior;
+        if *misbehavior == Misbehavior::Silent {
+            return Effects::new();
+        }
+        let response = match self.chain.response(misbehavior, tag, serialized_id) {
+            Some(response) => response,
+            None => {
+                debug!(%tag, "adversary has no item to answer with");
+                return Effects::new();
+            }
+        };
+
+        match misbehavior {
+            Misbehavior::SlowLoris { delay } => {
+                effect_builder
+                    .set_timeout((*delay).into())
+                    .event(move |_| Event::DelayedResponse {
+                        dest: sender,
+                        message: Box::new(response),
+                    })
+            }
+            Misbehavior::StaleSignatures => {
+                let mut effects = effect_builder.send_message(sender, response).ignore();
+                for signature in &self.chain.stale_signatures {
+                    let message = Message::FinalitySignature(Box::new(signature.clone()));
+                    effects.extend(effect_builder.send_message(sender, message).ignore());
+                }
+                effects
+            }
+            _ => effect_builder.send_message(sender, response).ignore(),
+        }
+    }
+}
+
+impl reactor::Reactor for Adversary {
+    type Event = Event;
+    type Config = AdversaryConfig;
+    type Error = anyhow::Error;
+
+    fn new(
+        config: Self::Config,
+        _registry: &Registry,
+        event_queue: EventQueueHandle<Self::Event>,
+        rng: &mut NodeRng,
+    ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+        let network = InMemoryNetwork::new(event_queue, rng);
+        let adversary = Adversary {
+            network,
+            script: config.script,
+            chain: config.chain,
+            received: vec![],
+        };
+        Ok((adversary, Effects::new()))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Network(event) => reactor::wrap_effects(
+                Event::Network,
+                self.network.handle_event(effect_builder, rng, event),
+            ),
+            Event::NetworkRequest(request) => {
+                self.dispatch_event(effect_builder, rng, Event::Network(request.into()))
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
+                sender,
+                payload,
+            }) => {
+                let effects = match &payload {
+                    Message::GetRequest { tag, serialized_id } => {
+                        self.handle_get_request(effect_builder, sender, *tag, serialized_id)
+                    }
+                    _ => Effects::new(),
+                };
+                self.received.push((sender, payload));
+                effects
+            }
+            Event::NetworkAnnouncement(_) => Effects::new(),
+            Event::DelayedResponse { dest, message } => {
+                effect_builder.send_message(dest, *message).ignore()
+            }
+        }
+    }
+
+    fn maybe_exit(&self) -> Option<ReactorExit> {
+        None
+    }
+}
+
+impl NetworkedReactor for Adversary {
+    type NodeId = NodeId;
+
+    fn node_id(&self) -> NodeId {
+        self.network.node_id()
+    }
+}
+
+impl Drop for Adversary {
+    fn drop(&mut self) {
+        NetworkController::<Message>::remove_node(&self.network.node_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use casper_types::{PublicKey, SecretKey};
+
+    use super::*;
+    use crate::{components::consensus::EraId, crypto::AsymmetricKeyExt, types::SharedBytes};
+
+    fn decode_block(serialized_item: &SharedBytes) -> Block {
+        bincode::deserialize(serialized_item).expect("should deserialize block")
+    }
+
+    #[test]
+    fn should_load_scenario_from_resources() {
+        let scenario = Scenario::from_resources("test/scenarios/adversarial_peers.toml");
+        let misbehaviors: Vec<_> = scenario
+            .adversaries
+            .iter()
+            .map(|script| script.misbehavior.clone())
+            .collect();
+        assert_eq!(
+            misbehaviors,
+            vec![
+                Misbehavior::Honest,
+                Misbehavior::InvalidHeader,
+                Misbehavior::WrongItem,
+                Misbehavior::StaleSignatures,
+                Misbehavior::SlowLoris {
+                    delay: "2s".parse().unwrap()
+                },
+                Misbehavior::Silent,
+            ]
+        );
+        assert_eq!(scenario.adversaries[5].count, 2);
+    }
+
+    #[tokio::test]
+    async fn should_answer_requests_according_to_script() {
+        NetworkController::<Message>::create_active();
+        let mut rng = TestRng::new();
+
+        let blocks: Vec<Block> = (0..2)
+            .map(|height| Block::random_with_specifics(&mut rng, EraId(1), height, false))
+            .collect();
+        let secret_key = SecretKey::random(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let stale_block = Block::random_with_specifics(&mut rng, EraId(0), 0, false);
+        let stale_signature = FinalitySignature::new(
+            *stale_block.hash(),
+            EraId(0),
+            &secret_key,
+            public_key,
+            &mut rng,
+        );
+        let chain = Arc::new(AdversaryChain {
+            blocks: blocks.clone(),
+            deploys: vec![],
+            stale_signatures: vec![stale_signature.clone()],
+        });
+
+        let misbehaviors = vec![
+            Misbehavior::Honest,
+            Misbehavior::InvalidHeader,
+            Misbehavior::WrongItem,
+            Misbehavior::StaleSignatures,
+            Misbehavior::SlowLoris {
+                delay: Duration::from_millis(100).into(),
+            },
+            Misbehavior::Silent,
+        ];
+        let scenario = Scenario {
+            adversaries: misbehaviors
+                .iter()
+                .map(|misbehavior| AdversaryScript {
+                    misbehavior: misbehavior.clone(),
+                    count: 1,
+                })
+                .collect(),
+        };
+
+        let mut network = Network::<Adversary>::new();
+        let (probe_id, _) = network.add_node(&mut rng).await.unwrap();
+        let adversary_ids = scenario
+            .add_adversaries(&mut network, chain, &mut rng)
+            .await;
+        let behavior_of: HashMap<NodeId, Misbehavior> = adversary_ids
+            .iter()
+            .cloned()
+            .zip(misbehaviors.iter().cloned())
+            .collect();
+
+        for adversary_id in adversary_ids.iter().cloned() {
+            let request = Message::new_get_request::<Block>(blocks[0].hash()).unwrap();
+            network
+                .process_injected_effect_on(&probe_id, move |effect_builder| {
+                    effect_builder.send_message(adversary_id, request).ignore()
+                })
+                .await;
+        }
+
+        // One response from each adversary but the silent one, plus a stale signature.
+        let expected_messages = misbehaviors.len();
+        network
+            .settle_on(
+                &mut rng,
+                |nodes| nodes[&probe_id].reactor().inner().received().len() >= expected_messages,
+                Duration::from_secs(10),
+            )
+            .await;
+
+        let received = network.nodes()[&probe_id].reactor().inner().received();
+        assert_eq!(received.len(), expected_messages);
+        for (sender, message) in received {
+            match (&behavior_of[sender], message) {
+                (Misbehavior::Silent, _) => panic!("silent adversary sent {}", message),
+                (Misbehavior::StaleSignatures, Message::FinalitySignature(signature)) => {
+                    assert_eq!(**signature, stale_signature)
+                }
+                (
+                    misbehavior,
+                    Message::GetResponse {
+                        serialized_item, ..
+                    },
+                ) => {
+                    let block = decode_block(serialized_item);
+                    match misbehavior {
+                        Misbehavior::InvalidHeader => {
+                            assert_eq!(block.hash(), blocks[0].hash());
+                            assert!(block.verify().is_err());
+                        }
+                        Misbehavior::WrongItem => assert_eq!(block, blocks[1]),
+                        _ => assert_eq!(block, blocks[0]),
+                    }
+                }
+                (misbehavior, message) => {
+                    panic!("unexpected {} from {:?} adversary", message, misbehavior)
+                }
+            }
+        }
+
+        NetworkController::<Message>::remove_active();
+    }
+}
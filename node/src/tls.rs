@@ -14,6 +14,8 @@
 //!   ([`create_tls_acceptor`](fn.create_tls_acceptor.html)),
 //! * construction of TLS connectors for outgoing TCP connections
 //!   ([`create_tls_connector`](fn.create_tls_connector.html)),
+//! * construction of TLS acceptors requiring CA-issued client certificates
+//!   ([`create_mutual_tls_acceptor`](fn.create_mutual_tls_acceptor.html)),
 //! * creation and validation of self-signed certificates
 //!   ([`generate_node_cert`](fn.generate_node_cert.html)),
 //! * signing and verification of arbitrary values using keys from certificates
@@ -297,6 +299,29 @@ pub(crate) fn create_tls_acceptor(
     Ok(builder.build())
 }
 
+/// Creates a TLS acceptor for a server which only accepts clients authenticating with a certificate
+/// issued by `ca_cert`.
+///
+/// In contrast to `create_tls_acceptor`, the certificate chain of the client is verified by OpenSSL
+/// during the handshake, as the certificates are not self-signed.
+pub(crate) fn create_mutual_tls_acceptor(
+    cert: &X509Ref,
+    private_key: &PKeyRef<Private>,
+    ca_cert: X509,
+) -> SslResult<SslAcceptor> {
+    let mut builder = SslAcceptor::mozilla_modern_v5(SslMethod::tls_server())?;
+    builder.set_min_proto_version(Some(SslVersion::TLS1_3))?;
+
+    builder.set_certificate(cert)?;
+    builder.set_private_key(private_key)?;
+    builder.check_private_key()?;
+
+    builder.cert_store_mut().add_cert(ca_cert)?;
+    builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+
+    Ok(builder.build())
+}
+
 /// Creates a TLS acceptor for a client.
 ///
 /// A connector compatible with the acceptor created using `create_tls_acceptor`. Server
@@ -2,13 +2,18 @@
 
 mod block;
 pub mod chainspec;
+mod checksum_registry;
+mod chunk;
 mod deploy;
+mod deploy_inclusion_proof;
 mod exit_code;
 mod item;
 pub mod json_compatibility;
 mod node_config;
 mod node_id;
 mod peers_map;
+mod rewarded_signatures;
+pub(crate) mod shared_bytes;
 mod status_feed;
 mod timestamp;
 
@@ -17,22 +22,39 @@ use rand::{CryptoRng, RngCore};
 use rand_chacha::ChaCha20Rng;
 
 pub use block::{
-    json_compatibility::JsonBlock, Block, BlockBody, BlockHash, BlockHeader, BlockSignatures,
-    BlockValidationError, FinalitySignature,
+    json_compatibility::{JsonBlock, JsonBlockHeader},
+    Block, BlockBody, BlockHash, BlockHeader, BlockSignatures, BlockValidationError,
+    BlockWithDeploys, BlockWithSignatures, FinalitySignature,
+};
+pub(crate) use block::{
+    BlockBodyWithoutRewardedSignatures, BlockByHeight, BlockExecutionResults, BlockLike,
+    FinalizedBlock, ProtoBlock,
 };
-pub(crate) use block::{BlockByHeight, BlockLike, FinalizedBlock, ProtoBlock};
 pub(crate) use chainspec::ActivationPoint;
 pub use chainspec::Chainspec;
+pub use checksum_registry::{ChecksumRegistry, ChecksumRegistryError, CHECKSUM_REGISTRY_KEY};
+pub(crate) use chunk::PartialItem;
+pub use chunk::{Chunk, ChunkError, ChunkId, DeployChunk};
+pub(crate) use deploy::DeployWithoutPriorityFee;
 pub use deploy::{
-    Approval, Deploy, DeployHash, DeployHeader, DeployMetadata, DeployValidationFailure,
-    Error as DeployError,
+    Approval, Deploy, DeployHash, DeployHeader, DeployHeaderById, DeployMetadata,
+    DeployValidationFailure, Error as DeployError,
+};
+pub use deploy_inclusion_proof::{
+    BodyHashProof, DeployInclusionProof, DeployInclusionProofError, DeployPosition,
 };
 pub use exit_code::ExitCode;
 pub use item::{Item, Tag};
 pub use node_config::NodeConfig;
 pub(crate) use node_id::NodeId;
 pub use peers_map::PeersMap;
-pub use status_feed::{ChainspecInfo, GetStatusResult, StatusFeed};
+pub(crate) use rewarded_signatures::meets_weight_threshold;
+pub use rewarded_signatures::{InvalidRewardedSignatures, RewardedSignatures, SignatureCitation};
+pub use shared_bytes::SharedBytes;
+pub use status_feed::{
+    BlockFinalityProgress, ChainspecInfo, FinalityStatus, GetStatusResult, StatusFeed, SyncState,
+    SyncStatus,
+};
 pub use timestamp::{TimeDiff, Timestamp};
 
 /// An object-safe RNG trait that requires a cryptographically strong random number generator.
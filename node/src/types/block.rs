@@ -1,14 +1,13 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-#[cfg(test)]
-use std::iter;
 use std::{
     array::TryFromSliceError,
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     error::Error as StdError,
     fmt::{self, Debug, Display, Formatter},
     hash::Hash,
+    iter,
 };
 
 use blake2::{
@@ -30,8 +29,8 @@ use thiserror::Error;
 #[cfg(test)]
 use casper_types::system::auction::BLOCK_REWARD;
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes},
-    ProtocolVersion, PublicKey, SecretKey, Signature, U512,
+    bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    ExecutionResult, ProtocolVersion, PublicKey, SecretKey, Signature, U512,
 };
 
 use super::{Item, Tag, Timestamp};
@@ -42,10 +41,13 @@ use crate::{
     crypto::{
         self,
         hash::{self, Digest},
-        AsymmetricKeyExt,
+        AsymmetricKeyExt, SignatureVerifier,
     },
     rpcs::docs::DocExample,
-    types::{Deploy, DeployHash, JsonBlock, NodeRng},
+    types::{
+        BlockFinalityProgress, Deploy, DeployHash, JsonBlock, NodeRng, RewardedSignatures,
+        SignatureCitation,
+    },
     utils::DisplayIter,
 };
 
@@ -92,7 +94,13 @@ static ERA_END: Lazy<EraEnd> = Lazy::new(|| {
 static FINALIZED_BLOCK: Lazy<FinalizedBlock> = Lazy::new(|| {
     let deploy_hashes = vec![*Deploy::doc_example().id()];
     let random_bit = true;
-    let proto_block = ProtoBlock::new(deploy_hashes, vec![], random_bit);
+    let citation = SignatureCitation {
+        block_hash: BlockHash::new(Digest::from([10u8; Digest::LENGTH])),
+        block_height: 9,
+        signers: iter::once(PublicKey::from(SecretKey::doc_example())).collect(),
+    };
+    let proto_block = ProtoBlock::new(deploy_hashes, vec![], random_bit)
+        .with_rewarded_signatures(RewardedSignatures::new(vec![citation]));
     let timestamp = *Timestamp::doc_example();
     let era_report = Some(EraReport::doc_example().clone());
     let era: u64 = 1;
@@ -233,6 +241,7 @@ pub struct ProtoBlock {
     wasm_deploys: Vec<DeployHash>,
     transfers: Vec<DeployHash>,
     random_bit: bool,
+    rewarded_signatures: RewardedSignatures,
 }
 
 impl ProtoBlock {
@@ -240,13 +249,28 @@ impl ProtoBlock {
         wasm_deploys: Vec<DeployHash>,
         transfers: Vec<DeployHash>,
         random_bit: bool,
+    ) -> Self {
+        Self::with_all_fields(
+            wasm_deploys,
+            transfers,
+            random_bit,
+            RewardedSignatures::default(),
+        )
+    }
+
+    fn with_all_fields(
+        wasm_deploys: Vec<DeployHash>,
+        transfers: Vec<DeployHash>,
+        random_bit: bool,
+        rewarded_signatures: RewardedSignatures,
     ) -> Self {
         let deploys = wasm_deploys
             .iter()
             .chain(transfers.iter())
             .collect::<Vec<_>>();
         let hash = ProtoBlockHash::new(hash::hash(
-            &bincode::serialize(&(&deploys, random_bit)).expect("serialize ProtoBlock"),
+            &bincode::serialize(&(&deploys, random_bit, &rewarded_signatures))
+                .expect("serialize ProtoBlock"),
         ));
 
         ProtoBlock {
@@ -254,9 +278,20 @@ impl ProtoBlock {
             wasm_deploys,
             transfers,
             random_bit,
+            rewarded_signatures,
         }
     }
 
+    /// Returns this proto block with the given rewarded signatures.
+    pub(crate) fn with_rewarded_signatures(self, rewarded_signatures: RewardedSignatures) -> Self {
+        Self::with_all_fields(
+            self.wasm_deploys,
+            self.transfers,
+            self.random_bit,
+            rewarded_signatures,
+        )
+    }
+
     pub(crate) fn hash(&self) -> &ProtoBlockHash {
         &self.hash
     }
@@ -276,12 +311,26 @@ impl ProtoBlock {
         self.random_bit
     }
 
-    pub(crate) fn destructure(self) -> (ProtoBlockHash, Vec<DeployHash>, Vec<DeployHash>, bool) {
+    /// The finality signatures of past blocks rewarded in the block.
+    pub(crate) fn rewarded_signatures(&self) -> &RewardedSignatures {
+        &self.rewarded_signatures
+    }
+
+    pub(crate) fn destructure(
+        self,
+    ) -> (
+        ProtoBlockHash,
+        Vec<DeployHash>,
+        Vec<DeployHash>,
+        bool,
+        RewardedSignatures,
+    ) {
         (
             self.hash,
             self.wasm_deploys,
             self.transfers,
             self.random_bit,
+            self.rewarded_signatures,
         )
     }
 }
@@ -290,10 +339,11 @@ impl Display for ProtoBlock {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         write!(
             formatter,
-            "proto block {}, deploys [{}], random bit {}",
+            "proto block {}, deploys [{}], random bit {}, rewarding {}",
             self.hash.inner(),
             DisplayIter::new(self.wasm_deploys.iter().chain(self.transfers.iter())),
             self.random_bit(),
+            self.rewarded_signatures,
         )
     }
 }
@@ -443,7 +493,18 @@ impl FinalizedBlock {
             .take(deploy_count)
             .collect();
         let random_bit = rng.gen();
-        let proto_block = ProtoBlock::new(deploy_hashes, vec![], random_bit);
+        let citations = (0..height.min(rng.gen_range(0, 3)))
+            .map(|offset| SignatureCitation {
+                block_hash: BlockHash::new(Digest::random(rng)),
+                block_height: height - offset - 1,
+                signers: iter::repeat_with(|| PublicKey::from(&SecretKey::ed25519(rng.gen())))
+                    .take(rng.gen_range(1, 4))
+                    .collect(),
+            })
+            .rev()
+            .collect();
+        let proto_block = ProtoBlock::new(deploy_hashes, vec![], random_bit)
+            .with_rewarded_signatures(RewardedSignatures::new(citations));
 
         // TODO - make Timestamp deterministic.
         let timestamp = Timestamp::now();
@@ -493,10 +554,11 @@ impl DocExample for FinalizedBlock {
 
 impl From<Block> for FinalizedBlock {
     fn from(block: Block) -> Self {
-        let proto_block = ProtoBlock::new(
+        let proto_block = ProtoBlock::with_all_fields(
             block.body.deploy_hashes().clone(),
             block.body.transfer_hashes().clone(),
             block.header.random_bit,
+            block.body.rewarded_signatures().clone(),
         );
 
         let era_report = match block.header.era_end {
@@ -854,19 +916,22 @@ pub struct BlockBody {
     proposer: PublicKey,
     deploy_hashes: Vec<DeployHash>,
     transfer_hashes: Vec<DeployHash>,
+    rewarded_signatures: RewardedSignatures,
 }
 
 impl BlockBody {
-    /// Creates a new body from deploy and transfer hashes.
+    /// Creates a new body from deploy and transfer hashes and rewarded signatures.
     pub(crate) fn new(
         proposer: PublicKey,
         deploy_hashes: Vec<DeployHash>,
         transfer_hashes: Vec<DeployHash>,
+        rewarded_signatures: RewardedSignatures,
     ) -> Self {
         BlockBody {
             proposer,
             deploy_hashes,
             transfer_hashes,
+            rewarded_signatures,
         }
     }
 
@@ -885,11 +950,24 @@ impl BlockBody {
         &self.transfer_hashes
     }
 
+    /// Retrieves the finality signatures of past blocks rewarded in the block.
+    pub fn rewarded_signatures(&self) -> &RewardedSignatures {
+        &self.rewarded_signatures
+    }
+
     /// Computes the body hash
+    ///
+    /// A body without rewarded signatures is hashed without their tag, as bodies were serialized
+    /// before rewarded signatures were added, so that the body hashes of existing blocks stay the
+    /// same.
     pub(crate) fn hash(&self) -> Digest {
-        let serialized_body = self
+        let mut serialized_body = self
             .to_bytes()
             .unwrap_or_else(|error| panic!("should serialize block body: {}", error));
+        if self.rewarded_signatures.is_empty() {
+            let tag = serialized_body.pop();
+            debug_assert_eq!(tag, Some(NO_REWARDED_SIGNATURES_TAG));
+        }
         hash::hash(&serialized_body)
     }
 }
@@ -901,19 +979,37 @@ impl Display for BlockBody {
     }
 }
 
+/// The tag of a serialized block body without rewarded signatures.
+const NO_REWARDED_SIGNATURES_TAG: u8 = 0;
+/// The tag of a serialized block body whose rewarded signatures follow the tag.
+const REWARDED_SIGNATURES_TAG: u8 = 1;
+
 impl ToBytes for BlockBody {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
         buffer.extend(self.proposer.to_bytes()?);
         buffer.extend(self.deploy_hashes.to_bytes()?);
         buffer.extend(self.transfer_hashes.to_bytes()?);
+        if self.rewarded_signatures.is_empty() {
+            buffer.push(NO_REWARDED_SIGNATURES_TAG);
+        } else {
+            buffer.push(REWARDED_SIGNATURES_TAG);
+            buffer.extend(self.rewarded_signatures.to_bytes()?);
+        }
         Ok(buffer)
     }
 
     fn serialized_length(&self) -> usize {
+        let rewarded_signatures_length = if self.rewarded_signatures.is_empty() {
+            0
+        } else {
+            self.rewarded_signatures.serialized_length()
+        };
         self.proposer.serialized_length()
             + self.deploy_hashes.serialized_length()
             + self.transfer_hashes.serialized_length()
+            + U8_SERIALIZED_LENGTH
+            + rewarded_signatures_length
     }
 }
 
@@ -922,15 +1018,63 @@ impl FromBytes for BlockBody {
         let (proposer, bytes) = PublicKey::from_bytes(bytes)?;
         let (deploy_hashes, bytes) = Vec::<DeployHash>::from_bytes(bytes)?;
         let (transfer_hashes, bytes) = Vec::<DeployHash>::from_bytes(bytes)?;
+        let (tag, bytes) = u8::from_bytes(bytes)?;
+        let (rewarded_signatures, bytes) = match tag {
+            NO_REWARDED_SIGNATURES_TAG => (RewardedSignatures::default(), bytes),
+            REWARDED_SIGNATURES_TAG => {
+                let (rewarded_signatures, bytes) = RewardedSignatures::from_bytes(bytes)?;
+                // An empty list must be encoded by the other tag, so that each body has a single
+                // encoding.
+                if rewarded_signatures.is_empty() {
+                    return Err(bytesrepr::Error::Formatting);
+                }
+                (rewarded_signatures, bytes)
+            }
+            _ => return Err(bytesrepr::Error::Formatting),
+        };
         let body = BlockBody {
             proposer,
             deploy_hashes,
             transfer_hashes,
+            rewarded_signatures,
         };
         Ok((body, bytes))
     }
 }
 
+/// The body of a block as serialized before rewarded signatures were added.
+///
+/// Only used to read block bodies stored in that format.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+pub(crate) struct BlockBodyWithoutRewardedSignatures {
+    proposer: PublicKey,
+    deploy_hashes: Vec<DeployHash>,
+    transfer_hashes: Vec<DeployHash>,
+}
+
+impl From<BlockBodyWithoutRewardedSignatures> for BlockBody {
+    fn from(body: BlockBodyWithoutRewardedSignatures) -> Self {
+        BlockBody {
+            proposer: body.proposer,
+            deploy_hashes: body.deploy_hashes,
+            transfer_hashes: body.transfer_hashes,
+            rewarded_signatures: RewardedSignatures::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl From<BlockBody> for BlockBodyWithoutRewardedSignatures {
+    fn from(body: BlockBody) -> Self {
+        BlockBodyWithoutRewardedSignatures {
+            proposer: body.proposer,
+            deploy_hashes: body.deploy_hashes,
+            transfer_hashes: body.transfer_hashes,
+        }
+    }
+}
+
 /// An error that can arise when validating a block's cryptographic integrity using its hashes
 #[derive(Debug)]
 pub enum BlockValidationError {
@@ -998,18 +1142,34 @@ impl BlockSignatures {
         self.proofs.contains_key(public_key)
     }
 
-    /// Verify the signatures contained within.
-    pub(crate) fn verify(&self) -> crypto::Result<()> {
-        for (public_key, signature) in self.proofs.iter() {
-            let signature = FinalitySignature {
+    /// Verify the signatures contained within, returning the public key and error of every
+    /// invalid one.
+    pub(crate) fn verify(
+        &self,
+        verifier: &SignatureVerifier,
+    ) -> Result<(), Vec<(PublicKey, crypto::Error)>> {
+        let signatures: Vec<_> = self
+            .proofs
+            .iter()
+            .map(|(public_key, signature)| FinalitySignature {
                 block_hash: self.block_hash,
                 era_id: self.era_id,
                 signature: *signature,
                 public_key: *public_key,
-            };
-            signature.verify()?;
+            })
+            .collect();
+        let failures: Vec<_> = signatures
+            .iter()
+            .zip(verifier.verify_batch(&signatures, FinalitySignature::verify))
+            .filter_map(|(signature, result)| {
+                result.err().map(|error| (signature.public_key, error))
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
         }
-        Ok(())
     }
 }
 
@@ -1025,6 +1185,59 @@ impl Display for BlockSignatures {
     }
 }
 
+/// A block along with its finality signatures and the validator weights of its era.
+#[derive(Debug, Clone)]
+pub struct BlockWithSignatures {
+    /// The block.
+    pub block: Block,
+    /// The known finality signatures for the block.
+    pub signatures: BlockSignatures,
+    /// The validator weights of the block's era, if known.
+    ///
+    /// These are taken from the switch block of the previous era, so they are unknown for blocks
+    /// in the first era.
+    pub era_validator_weights: Option<BTreeMap<PublicKey, U512>>,
+}
+
+impl BlockWithSignatures {
+    /// Returns how far the block has progressed towards finality, if the era's validator weights
+    /// are known.
+    ///
+    /// Signatures by keys which aren't validators in the block's era are disregarded.
+    pub fn finality_progress(&self) -> Option<BlockFinalityProgress> {
+        let weights = self.era_validator_weights.as_ref()?;
+        let signed_weight = self
+            .signatures
+            .proofs
+            .keys()
+            .filter_map(|public_key| weights.get(public_key))
+            .fold(U512::zero(), |sum, weight| sum + *weight);
+        let total_weight = weights
+            .values()
+            .fold(U512::zero(), |sum, weight| sum + *weight);
+        Some(BlockFinalityProgress {
+            block_hash: *self.block.hash(),
+            height: self.block.height(),
+            era_id: self.block.header().era_id(),
+            signed_weight,
+            total_weight,
+        })
+    }
+}
+
+/// A block along with its finality signatures and, if requested, its deploys.
+#[derive(Debug, Clone)]
+pub struct BlockWithDeploys {
+    /// The block.
+    pub block: Block,
+    /// The known finality signatures for the block.
+    pub signatures: BlockSignatures,
+    /// The block's deploys followed by its transfers, if requested.
+    ///
+    /// Deploys which aren't held in storage are omitted.
+    pub deploys: Option<Vec<Deploy>>,
+}
+
 /// A proto-block after execution, with the resulting post-state-hash.  This is the core component
 /// of the Casper linear blockchain.
 #[derive(DataSize, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -1047,6 +1260,7 @@ impl Block {
             finalized_block.proposer,
             finalized_block.proto_block.wasm_deploys().clone(),
             finalized_block.proto_block.transfers().clone(),
+            finalized_block.proto_block.rewarded_signatures().clone(),
         );
         let body_hash = body.hash();
 
@@ -1151,6 +1365,13 @@ impl Block {
         self
     }
 
+    /// Changes the header without updating the block hash, so that the block fails verification.
+    #[cfg(test)]
+    pub fn corrupt_header(&mut self) -> &mut Self {
+        self.header.random_bit = !self.header.random_bit;
+        self
+    }
+
     /// Generates a random instance using a `TestRng`.
     #[cfg(test)]
     pub fn random(rng: &mut TestRng) -> Self {
@@ -1264,6 +1485,17 @@ impl Item for Block {
     }
 }
 
+impl Item for BlockHeader {
+    type Id = BlockHash;
+
+    const TAG: Tag = Tag::BlockHeader;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        self.hash()
+    }
+}
+
 /// A wrapper around `Block` for the purposes of fetching blocks by height in linear chain.
 #[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BlockByHeight {
@@ -1314,6 +1546,64 @@ impl Item for BlockByHeight {
     }
 }
 
+/// The execution results of a block's deploys, in the order in which the deploys appear in the
+/// block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockExecutionResults {
+    block_hash: BlockHash,
+    execution_results: Vec<(DeployHash, ExecutionResult)>,
+}
+
+impl BlockExecutionResults {
+    /// Creates the execution results of the block with the given hash.
+    pub(crate) fn new(
+        block_hash: BlockHash,
+        execution_results: Vec<(DeployHash, ExecutionResult)>,
+    ) -> Self {
+        BlockExecutionResults {
+            block_hash,
+            execution_results,
+        }
+    }
+
+    /// Returns the hash of the block.
+    pub(crate) fn block_hash(&self) -> &BlockHash {
+        &self.block_hash
+    }
+
+    /// Returns the execution results, in block order.
+    pub(crate) fn execution_results(&self) -> &[(DeployHash, ExecutionResult)] {
+        &self.execution_results
+    }
+
+    /// Returns the execution results, keyed by deploy hash.
+    pub(crate) fn into_map(self) -> HashMap<DeployHash, ExecutionResult> {
+        self.execution_results.into_iter().collect()
+    }
+}
+
+impl Display for BlockExecutionResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "execution results of {} deploys in block {}",
+            self.execution_results.len(),
+            self.block_hash
+        )
+    }
+}
+
+impl Item for BlockExecutionResults {
+    type Id = BlockHash;
+
+    const TAG: Tag = Tag::BlockExecutionResults;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        self.block_hash
+    }
+}
+
 pub(crate) mod json_compatibility {
     use super::*;
 
@@ -1408,9 +1698,10 @@ pub(crate) mod json_compatibility {
         }
     }
 
+    /// A JSON-friendly representation of `BlockHeader`.
     #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
     #[serde(deny_unknown_fields)]
-    struct JsonBlockHeader {
+    pub struct JsonBlockHeader {
         parent_hash: BlockHash,
         state_root_hash: Digest,
         body_hash: Digest,
@@ -1464,6 +1755,7 @@ pub(crate) mod json_compatibility {
         proposer: PublicKey,
         deploy_hashes: Vec<DeployHash>,
         transfer_hashes: Vec<DeployHash>,
+        rewarded_signatures: Vec<SignatureCitation>,
     }
 
     impl From<BlockBody> for JsonBlockBody {
@@ -1472,6 +1764,7 @@ pub(crate) mod json_compatibility {
                 proposer: *body.proposer(),
                 deploy_hashes: body.deploy_hashes().clone(),
                 transfer_hashes: body.transfer_hashes().clone(),
+                rewarded_signatures: body.rewarded_signatures().citations().to_vec(),
             }
         }
     }
@@ -1482,6 +1775,7 @@ pub(crate) mod json_compatibility {
                 proposer: json_body.proposer,
                 deploy_hashes: json_body.deploy_hashes,
                 transfer_hashes: json_body.transfer_hashes,
+                rewarded_signatures: RewardedSignatures::new(json_body.rewarded_signatures),
             }
         }
     }
@@ -1629,10 +1923,12 @@ impl Display for FinalitySignature {
 
 #[cfg(test)]
 mod tests {
+    use num::rational::Ratio;
+
     use casper_types::bytesrepr;
 
     use super::*;
-    use crate::testing::TestRng;
+    use crate::{testing::TestRng, types::FinalityStatus};
     use std::rc::Rc;
 
     #[test]
@@ -1660,6 +1956,63 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&block);
     }
 
+    #[test]
+    fn block_body_bytesrepr_roundtrip_followed_by_other_bytes() {
+        let mut rng = TestRng::new();
+        let citation = SignatureCitation {
+            block_hash: BlockHash::new(Digest::random(&mut rng)),
+            block_height: rng.gen(),
+            signers: iter::once(PublicKey::from(&SecretKey::random(&mut rng))).collect(),
+        };
+        let rewarded_signatures = RewardedSignatures::new(vec![citation]);
+        for rewarded_signatures in vec![RewardedSignatures::default(), rewarded_signatures] {
+            let body = BlockBody::new(
+                PublicKey::from(&SecretKey::random(&mut rng)),
+                vec![DeployHash::new(Digest::random(&mut rng))],
+                vec![],
+                rewarded_signatures,
+            );
+            bytesrepr::test_serialization_roundtrip(&body);
+
+            // Whatever follows the body must not be read as its rewarded signatures.
+            let trailing_bytes = [7u8; 5];
+            let mut serialized = body.to_bytes().unwrap();
+            serialized.extend_from_slice(&trailing_bytes);
+            let (decoded, remainder) = BlockBody::from_bytes(&serialized).unwrap();
+            assert_eq!(decoded, body);
+            assert_eq!(remainder, &trailing_bytes[..]);
+        }
+    }
+
+    #[test]
+    fn block_body_without_rewarded_signatures_should_keep_legacy_hash() {
+        let mut rng = TestRng::new();
+        let body = BlockBody::new(
+            PublicKey::from(&SecretKey::random(&mut rng)),
+            vec![DeployHash::new(Digest::random(&mut rng))],
+            vec![DeployHash::new(Digest::random(&mut rng))],
+            RewardedSignatures::default(),
+        );
+        let mut legacy_serialized = body.proposer.to_bytes().unwrap();
+        legacy_serialized.extend(body.deploy_hashes.to_bytes().unwrap());
+        legacy_serialized.extend(body.transfer_hashes.to_bytes().unwrap());
+        assert_eq!(body.hash(), hash::hash(&legacy_serialized));
+    }
+
+    #[test]
+    fn block_body_should_reject_unknown_rewarded_signatures_tag() {
+        let mut rng = TestRng::new();
+        let body = BlockBody::new(
+            PublicKey::from(&SecretKey::random(&mut rng)),
+            vec![],
+            vec![],
+            RewardedSignatures::default(),
+        );
+        let mut serialized = body.to_bytes().unwrap();
+        *serialized.last_mut().unwrap() = 2;
+        assert!(BlockBody::from_bytes(&serialized).is_err());
+    }
+
     #[test]
     fn block_header_bytesrepr_roundtrip() {
         let mut rng = TestRng::new();
@@ -1763,4 +2116,63 @@ mod tests {
         // Test should fail b/c `signature` is over `era_id=1` and here we're using `era_id=2`.
         assert!(fs_manufactured.verify().is_err());
     }
+
+    #[test]
+    fn finality_progress_of_block_with_signatures() {
+        let mut rng = TestRng::new();
+        let one_third = Ratio::new(1, 3);
+        let block = Block::random(&mut rng);
+        let validators: Vec<_> = (0..3).map(|_| crypto::generate_ed25519_keypair()).collect();
+        let era_validator_weights: BTreeMap<PublicKey, U512> = validators
+            .iter()
+            .zip(&[20u64, 30, 50])
+            .map(|((_, public_key), weight)| (*public_key, U512::from(*weight)))
+            .collect();
+        let mut block_with_signatures = BlockWithSignatures {
+            signatures: BlockSignatures::new(*block.hash(), block.header().era_id()),
+            block,
+            era_validator_weights: None,
+        };
+
+        // Without the era's validators, finality can't be determined.
+        assert!(block_with_signatures.finality_progress().is_none());
+        block_with_signatures.era_validator_weights = Some(era_validator_weights);
+
+        let mut sign = |secret_key: &SecretKey, public_key: PublicKey| {
+            let block_hash = block_with_signatures.block.hash().inner();
+            let signature = crypto::sign(block_hash, secret_key, &public_key, &mut rng);
+            block_with_signatures
+                .signatures
+                .insert_proof(public_key, signature);
+            block_with_signatures.finality_progress().unwrap()
+        };
+
+        // A signature by a key which isn't a validator in the era doesn't count.
+        let (outsider_secret_key, outsider_public_key) = crypto::generate_ed25519_keypair();
+        let progress = sign(&outsider_secret_key, outsider_public_key);
+        assert_eq!(progress.signed_weight, U512::zero());
+        assert_eq!(progress.total_weight, U512::from(100));
+        assert_eq!(progress.finality_status(one_third), FinalityStatus::None);
+
+        let progress = sign(&validators[0].0, validators[0].1);
+        assert_eq!(progress.signed_weight, U512::from(20));
+        assert_eq!(progress.finality_status(one_third), FinalityStatus::None);
+        // A lower finality threshold is reached with less weight.
+        assert_eq!(
+            progress.finality_status(Ratio::new(1, 10)),
+            FinalityStatus::Weak
+        );
+
+        let progress = sign(&validators[1].0, validators[1].1);
+        assert_eq!(progress.signed_weight, U512::from(50));
+        assert_eq!(progress.finality_status(one_third), FinalityStatus::Weak);
+        assert_eq!(
+            progress.finality_status(Ratio::new(1, 100)),
+            FinalityStatus::Weak
+        );
+
+        let progress = sign(&validators[2].0, validators[2].1);
+        assert_eq!(progress.signed_weight, U512::from(100));
+        assert_eq!(progress.finality_status(one_third), FinalityStatus::Strict);
+    }
 }
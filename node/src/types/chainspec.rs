@@ -37,6 +37,7 @@ pub(crate) use self::{
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::{
+    components::small_network,
     crypto::hash::{self, Digest},
     utils::Loadable,
 };
@@ -78,7 +79,10 @@ impl Chainspec {
             warn!("era duration is less than minimum era height * round length!");
         }
 
+        self.core_config.validate_config();
         self.highway_config.validate_config();
+        self.deploy_config
+            .validate_config(small_network::MAX_FRAME_LENGTH);
     }
 
     /// Serializes `self` and hashes the resulting bytes.
@@ -262,6 +266,7 @@ mod tests {
             remove_contract_user_group_urefs: HostFunction::new(131, [0, 1, 2, 3, 4, 5]),
             print: HostFunction::new(123, [0, 1]),
             blake2b: HostFunction::new(133, [0, 1, 2, 3]),
+            verify_signature: HostFunction::new(142, [0, 1, 2, 3, 4, 5, 6]),
         });
     static EXPECTED_GENESIS_WASM_COSTS: Lazy<WasmConfig> = Lazy::new(|| {
         WasmConfig::new(
@@ -334,9 +339,14 @@ mod tests {
         assert_eq!(spec.core_config.era_duration, TimeDiff::from(180000));
         assert_eq!(spec.core_config.minimum_era_height, 9);
         assert_eq!(
-            spec.highway_config.finality_threshold_fraction,
+            spec.core_config.finality_threshold_fraction,
             Ratio::new(2, 25)
         );
+        assert_eq!(spec.core_config.signature_rewards_max_delay, 5);
+        assert_eq!(
+            spec.core_config.signature_rewards_weight_threshold,
+            Ratio::new(1, 2)
+        );
         assert_eq!(spec.highway_config.minimum_round_exponent, 14);
         assert_eq!(spec.highway_config.maximum_round_exponent, 19);
         assert_eq!(
@@ -8,7 +8,7 @@ use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
 #[cfg(test)]
 use crate::testing::TestRng;
-use crate::types::TimeDiff;
+use crate::{components::consensus::EraId, types::TimeDiff};
 
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -28,6 +28,38 @@ pub struct CoreConfig {
     /// Round seigniorage rate represented as a fractional number.
     #[data_size(skip)]
     pub(crate) round_seigniorage_rate: Ratio<u64>,
+    /// The fraction of the total weight that faulty validators may have without compromising
+    /// finality, both in consensus and when counting finality signatures.
+    #[data_size(skip)]
+    pub(crate) finality_threshold_fraction: Ratio<u64>,
+    /// The first era whose blocks write a checksum registry to global state when committed.
+    pub(crate) checksum_registry_activation_era: EraId,
+    /// The number of most recent finalized blocks whose finality signatures a proposer rewards.
+    pub(crate) signature_rewards_max_delay: u64,
+    /// The fraction of the total weight that must have signed a block before its finality
+    /// signatures are rewarded.
+    #[data_size(skip)]
+    pub(crate) signature_rewards_weight_threshold: Ratio<u64>,
+}
+
+impl CoreConfig {
+    /// Checks whether the values set in the config make sense and panics if they don't.
+    pub fn validate_config(&self) {
+        if self.finality_threshold_fraction <= Ratio::new(0, 1)
+            || self.finality_threshold_fraction >= Ratio::new(1, 1)
+        {
+            panic!(
+                "Finality threshold fraction is not in the range (0, 1)! Finality threshold: {ftt}",
+                ftt = self.finality_threshold_fraction
+            );
+        }
+        if self.signature_rewards_weight_threshold > Ratio::new(1, 1) {
+            panic!(
+                "Signature rewards weight threshold is greater than 1! Threshold: {threshold}",
+                threshold = self.signature_rewards_weight_threshold
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -44,6 +76,10 @@ impl CoreConfig {
             rng.gen_range(1, 1_000_000_000),
             rng.gen_range(1, 1_000_000_000),
         );
+        let finality_threshold_fraction = Ratio::new(rng.gen_range(1, 100), 100);
+        let checksum_registry_activation_era = EraId(rng.gen_range(0, 1_000));
+        let signature_rewards_max_delay = rng.gen_range(0, 100);
+        let signature_rewards_weight_threshold = Ratio::new(rng.gen_range(0, 101), 100);
 
         CoreConfig {
             era_duration,
@@ -53,6 +89,10 @@ impl CoreConfig {
             locked_funds_period,
             unbonding_delay,
             round_seigniorage_rate,
+            finality_threshold_fraction,
+            checksum_registry_activation_era,
+            signature_rewards_max_delay,
+            signature_rewards_weight_threshold,
         }
     }
 }
@@ -67,6 +107,10 @@ impl ToBytes for CoreConfig {
         buffer.extend(self.locked_funds_period.to_bytes()?);
         buffer.extend(self.unbonding_delay.to_bytes()?);
         buffer.extend(self.round_seigniorage_rate.to_bytes()?);
+        buffer.extend(self.finality_threshold_fraction.to_bytes()?);
+        buffer.extend(self.checksum_registry_activation_era.to_bytes()?);
+        buffer.extend(self.signature_rewards_max_delay.to_bytes()?);
+        buffer.extend(self.signature_rewards_weight_threshold.to_bytes()?);
         Ok(buffer)
     }
 
@@ -78,6 +122,10 @@ impl ToBytes for CoreConfig {
             + self.locked_funds_period.serialized_length()
             + self.unbonding_delay.serialized_length()
             + self.round_seigniorage_rate.serialized_length()
+            + self.finality_threshold_fraction.serialized_length()
+            + self.checksum_registry_activation_era.serialized_length()
+            + self.signature_rewards_max_delay.serialized_length()
+            + self.signature_rewards_weight_threshold.serialized_length()
     }
 }
 
@@ -90,6 +138,10 @@ impl FromBytes for CoreConfig {
         let (locked_funds_period, remainder) = TimeDiff::from_bytes(remainder)?;
         let (unbonding_delay, remainder) = u64::from_bytes(remainder)?;
         let (round_seigniorage_rate, remainder) = Ratio::<u64>::from_bytes(remainder)?;
+        let (finality_threshold_fraction, remainder) = Ratio::<u64>::from_bytes(remainder)?;
+        let (checksum_registry_activation_era, remainder) = EraId::from_bytes(remainder)?;
+        let (signature_rewards_max_delay, remainder) = u64::from_bytes(remainder)?;
+        let (signature_rewards_weight_threshold, remainder) = Ratio::<u64>::from_bytes(remainder)?;
         let config = CoreConfig {
             era_duration,
             minimum_era_height,
@@ -98,6 +150,10 @@ impl FromBytes for CoreConfig {
             locked_funds_period,
             unbonding_delay,
             round_seigniorage_rate,
+            finality_threshold_fraction,
+            checksum_registry_activation_era,
+            signature_rewards_max_delay,
+            signature_rewards_weight_threshold,
         };
         Ok((config, remainder))
     }
@@ -122,4 +178,23 @@ mod tests {
         let decoded = toml::from_str(&encoded).unwrap();
         assert_eq!(config, decoded);
     }
+
+    #[test]
+    #[should_panic(expected = "Finality threshold fraction is not in the range (0, 1)")]
+    fn should_reject_finality_threshold_of_one() {
+        let mut rng = crate::new_rng();
+        let mut config = CoreConfig::random(&mut rng);
+        config.finality_threshold_fraction = Ratio::new(1, 1);
+        config.validate_config();
+    }
+
+    #[test]
+    #[should_panic(expected = "Signature rewards weight threshold is greater than 1")]
+    fn should_reject_signature_rewards_weight_threshold_above_one() {
+        let mut rng = crate::new_rng();
+        let mut config = CoreConfig::random(&mut rng);
+        config.finality_threshold_fraction = Ratio::new(1, 3);
+        config.signature_rewards_weight_threshold = Ratio::new(3, 2);
+        config.validate_config();
+    }
 }
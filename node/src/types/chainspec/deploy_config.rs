@@ -7,6 +7,7 @@ use num_traits::Zero;
 #[cfg(test)]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 #[cfg(test)]
 use casper_execution_engine::core::engine_state::MAX_PAYMENT_AMOUNT;
@@ -18,7 +19,20 @@ use casper_types::{
 
 #[cfg(test)]
 use crate::testing::TestRng;
-use crate::types::TimeDiff;
+use crate::{crypto::hash::Digest, types::TimeDiff};
+
+/// Allowance in bytes for the parts of a block message other than its deploy and transfer hashes,
+/// i.e. the header including the era end of a switch block, the proposer and the message envelope.
+const BLOCK_MESSAGE_OVERHEAD: u64 = 64 * 1024;
+
+/// Allowance in bytes for the envelope of a message carrying a single deploy.
+const DEPLOY_MESSAGE_OVERHEAD: u64 = 1024;
+
+/// The chunk size of chainspecs which don't specify one.
+const DEFAULT_CHUNK_SIZE: u32 = 256 * 1024;
+
+/// The smallest permitted chunk size, to bound the number of chunks of a deploy.
+const MIN_CHUNK_SIZE: u32 = 4 * 1024;
 
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -34,6 +48,107 @@ pub struct DeployConfig {
     pub(crate) payment_args_max_length: u32,
     pub(crate) session_args_max_length: u32,
     pub(crate) native_transfer_minimum_motes: u64,
+    #[serde(default)]
+    pub(crate) max_priority_fee: u64,
+    /// The size in bytes of the chunks in which deploys too large for a single message are sent.
+    ///
+    /// All nodes must split deploys alike, so that chunks fetched from different peers fit
+    /// together.
+    #[serde(default = "default_chunk_size")]
+    pub(crate) chunk_size: u32,
+}
+
+impl DeployConfig {
+    /// Checks the block limits against the maximum size of a network message.
+    ///
+    /// Panics with a report of the derived sizes if a block with the maximum number of deploys and
+    /// transfers could not be sent to peers.  Warns if a deploy of the largest size fitting in a
+    /// block could not be sent to peers, as such a deploy will never be gossiped.
+    pub(crate) fn validate_config(&self, max_message_size: usize) {
+        if let Err(report) = self.check_block_message_size(max_message_size) {
+            panic!("{}", report);
+        }
+        if self.chunk_size < MIN_CHUNK_SIZE {
+            panic!(
+                "Deploy chunk size is less than {min} bytes! Chunk size: {chunk_size}",
+                min = MIN_CHUNK_SIZE,
+                chunk_size = self.chunk_size
+            );
+        }
+        if let Err(report) = self.check_deploy_message_size(max_message_size) {
+            warn!("{}", report);
+        }
+    }
+
+    /// Returns the worst-case size in bytes of a message carrying a block or proto block, which
+    /// contains the hashes of at most `block_max_deploy_count` deploys and
+    /// `block_max_transfer_count` transfers.
+    fn max_block_message_size(&self) -> u64 {
+        self.block_hash_count() * serialized_hash_size() + BLOCK_MESSAGE_OVERHEAD
+    }
+
+    /// Returns the worst-case size in bytes of a message carrying a single deploy.  No deploy
+    /// larger than `max_block_size` can be included in a block.
+    fn max_deploy_message_size(&self) -> u64 {
+        u64::from(self.max_block_size) + DEPLOY_MESSAGE_OVERHEAD
+    }
+
+    fn block_hash_count(&self) -> u64 {
+        u64::from(self.block_max_deploy_count) + u64::from(self.block_max_transfer_count)
+    }
+
+    fn check_block_message_size(&self, max_message_size: usize) -> Result<(), String> {
+        let max_message_size = max_message_size as u64;
+        let block_message_size = self.max_block_message_size();
+        if block_message_size <= max_message_size {
+            return Ok(());
+        }
+        let hash_size = serialized_hash_size();
+        let max_hash_count = max_message_size.saturating_sub(BLOCK_MESSAGE_OVERHEAD) / hash_size;
+        Err(format!(
+            "invalid chainspec: a block can be up to {block_size} bytes, exceeding the network \
+             message limit of {max_size} bytes.\n\
+             Block size: ({deploys} deploys + {transfers} transfers) * {hash_size} bytes per hash \
+             + {overhead} bytes overhead.\n\
+             block_max_deploy_count + block_max_transfer_count must be at most {max_hash_count}, \
+             but is {hash_count}.",
+            block_size = block_message_size,
+            max_size = max_message_size,
+            deploys = self.block_max_deploy_count,
+            transfers = self.block_max_transfer_count,
+            hash_size = hash_size,
+            overhead = BLOCK_MESSAGE_OVERHEAD,
+            max_hash_count = max_hash_count,
+            hash_count = self.block_hash_count(),
+        ))
+    }
+
+    fn check_deploy_message_size(&self, max_message_size: usize) -> Result<(), String> {
+        let max_message_size = max_message_size as u64;
+        let deploy_message_size = self.max_deploy_message_size();
+        if deploy_message_size <= max_message_size {
+            return Ok(());
+        }
+        Err(format!(
+            "a deploy can be up to {deploy_size} bytes (max_block_size of {max_block_size} bytes \
+             + {overhead} bytes overhead), exceeding the network message limit of {max_size} \
+             bytes.  Deploys larger than {max_deploy_size} bytes cannot be gossiped.",
+            deploy_size = deploy_message_size,
+            max_block_size = self.max_block_size,
+            overhead = DEPLOY_MESSAGE_OVERHEAD,
+            max_size = max_message_size,
+            max_deploy_size = max_message_size.saturating_sub(DEPLOY_MESSAGE_OVERHEAD),
+        ))
+    }
+}
+
+fn default_chunk_size() -> u32 {
+    DEFAULT_CHUNK_SIZE
+}
+
+/// Returns the size of a deploy or transfer hash as serialized in network messages.
+fn serialized_hash_size() -> u64 {
+    bincode::serialized_size(&Digest::default()).unwrap_or(Digest::LENGTH as u64)
 }
 
 #[cfg(test)]
@@ -53,6 +168,8 @@ impl DeployConfig {
         let session_args_max_length = rng.gen();
         let native_transfer_minimum_motes =
             rng.gen_range(MAX_PAYMENT_AMOUNT, 1_000_000_000_000_000);
+        let max_priority_fee = rng.gen();
+        let chunk_size = rng.gen_range(MIN_CHUNK_SIZE, 1_000_000);
 
         DeployConfig {
             max_payment_cost,
@@ -65,6 +182,8 @@ impl DeployConfig {
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            max_priority_fee,
+            chunk_size,
         }
     }
 }
@@ -83,6 +202,8 @@ impl Default for DeployConfig {
             payment_args_max_length: 1024,
             session_args_max_length: 1024,
             native_transfer_minimum_motes: MAX_PAYMENT_AMOUNT,
+            max_priority_fee: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
         }
     }
 }
@@ -100,6 +221,8 @@ impl ToBytes for DeployConfig {
         buffer.extend(self.payment_args_max_length.to_bytes()?);
         buffer.extend(self.session_args_max_length.to_bytes()?);
         buffer.extend(self.native_transfer_minimum_motes.to_bytes()?);
+        buffer.extend(self.max_priority_fee.to_bytes()?);
+        buffer.extend(self.chunk_size.to_bytes()?);
         Ok(buffer)
     }
 
@@ -114,6 +237,8 @@ impl ToBytes for DeployConfig {
             + self.payment_args_max_length.serialized_length()
             + self.session_args_max_length.serialized_length()
             + self.native_transfer_minimum_motes.serialized_length()
+            + self.max_priority_fee.serialized_length()
+            + self.chunk_size.serialized_length()
     }
 }
 
@@ -130,6 +255,8 @@ impl FromBytes for DeployConfig {
         let (payment_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (session_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (native_transfer_minimum_motes, remainder) = u64::from_bytes(remainder)?;
+        let (max_priority_fee, remainder) = u64::from_bytes(remainder)?;
+        let (chunk_size, remainder) = u32::from_bytes(remainder)?;
         let config = DeployConfig {
             max_payment_cost,
             max_ttl,
@@ -141,6 +268,8 @@ impl FromBytes for DeployConfig {
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            max_priority_fee,
+            chunk_size,
         };
         Ok((config, remainder))
     }
@@ -157,6 +286,45 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&config);
     }
 
+    #[test]
+    fn should_check_block_message_size() {
+        const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+        let mut config = DeployConfig::default();
+        assert!(config.check_block_message_size(MAX_MESSAGE_SIZE).is_ok());
+
+        let max_hash_count =
+            (MAX_MESSAGE_SIZE as u64 - BLOCK_MESSAGE_OVERHEAD) / serialized_hash_size();
+        config.block_max_deploy_count = 0;
+        config.block_max_transfer_count = max_hash_count as u32;
+        assert!(config.check_block_message_size(MAX_MESSAGE_SIZE).is_ok());
+
+        config.block_max_deploy_count = 1;
+        let report = config
+            .check_block_message_size(MAX_MESSAGE_SIZE)
+            .unwrap_err();
+        assert!(report.contains(&format!("must be at most {}", max_hash_count)));
+    }
+
+    #[test]
+    fn should_check_deploy_message_size() {
+        let mut config = DeployConfig::default();
+        let max_message_size = config.max_block_size as usize + DEPLOY_MESSAGE_OVERHEAD as usize;
+        assert!(config.check_deploy_message_size(max_message_size).is_ok());
+
+        config.max_block_size += 1;
+        assert!(config.check_deploy_message_size(max_message_size).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deploy chunk size is less than")]
+    fn should_reject_small_chunk_size() {
+        let config = DeployConfig {
+            chunk_size: MIN_CHUNK_SIZE - 1,
+            ..DeployConfig::default()
+        };
+        config.validate_config(usize::MAX);
+    }
+
     #[test]
     fn toml_roundtrip() {
         let mut rng = crate::new_rng();
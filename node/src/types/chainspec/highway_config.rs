@@ -15,8 +15,6 @@ use crate::types::TimeDiff;
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub(crate) struct HighwayConfig {
-    #[data_size(skip)]
-    pub(crate) finality_threshold_fraction: Ratio<u64>,
     pub(crate) minimum_round_exponent: u8,
     pub(crate) maximum_round_exponent: u8,
     /// The factor by which rewards for a round are multiplied if the greatest summit has ≤50%
@@ -38,15 +36,6 @@ impl HighwayConfig {
             );
         }
 
-        if self.finality_threshold_fraction <= Ratio::new(0, 1)
-            || self.finality_threshold_fraction >= Ratio::new(1, 1)
-        {
-            panic!(
-                "Finality threshold fraction is not in the range (0, 1)! Finality threshold: {ftt}",
-                ftt = self.finality_threshold_fraction
-            );
-        }
-
         if self.reduced_reward_multiplier > Ratio::new(1, 1) {
             panic!(
                 "Reduced reward multiplier is not in the range [0, 1]! Multiplier: {rrm}",
@@ -72,13 +61,11 @@ impl HighwayConfig {
 impl HighwayConfig {
     /// Generates a random instance using a `TestRng`.
     pub fn random(rng: &mut TestRng) -> Self {
-        let finality_threshold_fraction = Ratio::new(rng.gen_range(1, 100), 100);
         let minimum_round_exponent = rng.gen_range(0, 16);
         let maximum_round_exponent = rng.gen_range(16, 22);
         let reduced_reward_multiplier = Ratio::new(rng.gen_range(0, 10), 10);
 
         HighwayConfig {
-            finality_threshold_fraction,
             minimum_round_exponent,
             maximum_round_exponent,
             reduced_reward_multiplier,
@@ -89,7 +76,6 @@ impl HighwayConfig {
 impl ToBytes for HighwayConfig {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
-        buffer.extend(self.finality_threshold_fraction.to_bytes()?);
         buffer.extend(self.minimum_round_exponent.to_bytes()?);
         buffer.extend(self.maximum_round_exponent.to_bytes()?);
         buffer.extend(self.reduced_reward_multiplier.to_bytes()?);
@@ -97,8 +83,7 @@ impl ToBytes for HighwayConfig {
     }
 
     fn serialized_length(&self) -> usize {
-        self.finality_threshold_fraction.serialized_length()
-            + self.minimum_round_exponent.serialized_length()
+        self.minimum_round_exponent.serialized_length()
             + self.maximum_round_exponent.serialized_length()
             + self.reduced_reward_multiplier.serialized_length()
     }
@@ -106,12 +91,10 @@ impl ToBytes for HighwayConfig {
 
 impl FromBytes for HighwayConfig {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (finality_threshold_fraction, remainder) = Ratio::<u64>::from_bytes(bytes)?;
-        let (minimum_round_exponent, remainder) = u8::from_bytes(remainder)?;
+        let (minimum_round_exponent, remainder) = u8::from_bytes(bytes)?;
         let (maximum_round_exponent, remainder) = u8::from_bytes(remainder)?;
         let (reduced_reward_multiplier, remainder) = Ratio::<u64>::from_bytes(remainder)?;
         let config = HighwayConfig {
-            finality_threshold_fraction,
             minimum_round_exponent,
             maximum_round_exponent,
             reduced_reward_multiplier,
@@ -0,0 +1,211 @@
+//! The checksum registry of a block.
+//!
+//! When a block is committed, the root hash of its deploys' execution results is written to global
+//! state under [`CHECKSUM_REGISTRY_KEY`].  The registry in the global state at a block's state root
+//! hash is therefore covered by the block header, so a node which trusts the header can verify
+//! execution results fetched from untrusted peers against it.
+//!
+//! Only data determined by the block itself may be registered, as every node must compute the same
+//! state root hash.  Deploy approvals are not: they aren't covered by the deploy hash, and nodes
+//! may hold different sets of them for the same deploy, e.g. after late approvals were merged into
+//! a stored deploy.
+//!
+//! Blocks of eras before the chainspec's `checksum_registry_activation_era` were committed without
+//! a registry, and their artifacts can't be verified this way.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use once_cell::sync::Lazy;
+use thiserror::Error;
+
+use casper_execution_engine::shared::{
+    additive_map::AdditiveMap, stored_value::StoredValue, transform::Transform,
+};
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    CLValue, CLValueError, ExecutionResult, Key,
+};
+
+use crate::{
+    crypto::hash::{self, Digest},
+    types::DeployHash,
+};
+
+/// The global state key under which the checksum registry is stored.
+pub static CHECKSUM_REGISTRY_KEY: Lazy<Key> =
+    Lazy::new(|| Key::Hash(hash::hash("checksum_registry").to_array()));
+
+/// The registry entry holding the execution results checksum.
+const EXECUTION_RESULTS_CHECKSUM_NAME: &str = "execution_results_checksum";
+
+/// An error reading a checksum registry from global state.
+#[derive(Debug, Error)]
+pub enum ChecksumRegistryError {
+    /// The value stored under the registry key isn't a `CLValue`.
+    #[error("checksum registry is stored as {0}")]
+    NotACLValue(String),
+    /// The stored `CLValue` isn't a registry.
+    #[error("failed to parse checksum registry: {0}")]
+    InvalidCLValue(CLValueError),
+    /// The registry lacks the named checksum.
+    #[error("checksum registry has no {0}")]
+    MissingChecksum(&'static str),
+}
+
+/// The checksums of the artifacts of a single block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChecksumRegistry {
+    execution_results: Digest,
+}
+
+impl ChecksumRegistry {
+    /// Creates the registry of a block from its deploys' execution results, in the order in which
+    /// the deploys appear in the block.
+    pub fn new<'a, E>(execution_results: E) -> Self
+    where
+        E: IntoIterator<Item = (&'a DeployHash, &'a ExecutionResult)>,
+    {
+        ChecksumRegistry {
+            execution_results: execution_results_checksum(execution_results),
+        }
+    }
+
+    /// Returns the root hash of the block's execution results.
+    pub fn execution_results(&self) -> &Digest {
+        &self.execution_results
+    }
+
+    /// Returns whether the given execution results, in block order, match the registry.
+    pub fn verify_execution_results<'a, E>(&self, execution_results: E) -> bool
+    where
+        E: IntoIterator<Item = (&'a DeployHash, &'a ExecutionResult)>,
+    {
+        execution_results_checksum(execution_results) == self.execution_results
+    }
+
+    /// Returns the registry as the value to be written to global state.
+    pub fn to_stored_value(&self) -> StoredValue {
+        let entries: BTreeMap<String, [u8; Digest::LENGTH]> = vec![(
+            EXECUTION_RESULTS_CHECKSUM_NAME.to_string(),
+            self.execution_results.to_array(),
+        )]
+        .into_iter()
+        .collect();
+        let cl_value = CLValue::from_t(entries)
+            .unwrap_or_else(|error| panic!("should create checksum registry: {:?}", error));
+        StoredValue::CLValue(cl_value)
+    }
+
+    /// Returns the effects writing the registry to global state.
+    pub fn effects(&self) -> AdditiveMap<Key, Transform> {
+        let mut effects = AdditiveMap::new();
+        let _ = effects.insert(
+            *CHECKSUM_REGISTRY_KEY,
+            Transform::Write(self.to_stored_value()),
+        );
+        effects
+    }
+}
+
+impl TryFrom<StoredValue> for ChecksumRegistry {
+    type Error = ChecksumRegistryError;
+
+    fn try_from(stored_value: StoredValue) -> Result<Self, Self::Error> {
+        let cl_value = match stored_value {
+            StoredValue::CLValue(cl_value) => cl_value,
+            other => return Err(ChecksumRegistryError::NotACLValue(other.type_name())),
+        };
+        let entries: BTreeMap<String, [u8; Digest::LENGTH]> = cl_value
+            .into_t()
+            .map_err(ChecksumRegistryError::InvalidCLValue)?;
+        let get = |name| {
+            entries
+                .get(name)
+                .map(|bytes| Digest::from(*bytes))
+                .ok_or(ChecksumRegistryError::MissingChecksum(name))
+        };
+        Ok(ChecksumRegistry {
+            execution_results: get(EXECUTION_RESULTS_CHECKSUM_NAME)?,
+        })
+    }
+}
+
+/// Returns the root hash of the execution results.
+fn execution_results_checksum<'a, E>(execution_results: E) -> Digest
+where
+    E: IntoIterator<Item = (&'a DeployHash, &'a ExecutionResult)>,
+{
+    checksum(
+        execution_results
+            .into_iter()
+            .map(|(deploy_hash, execution_result)| (deploy_hash, execution_result.to_bytes())),
+    )
+}
+
+/// Returns the root hash of the Merkle tree whose leaves are the hashes of each deploy hash along
+/// with its serialized artifact.
+fn checksum<'a, I>(serialized_artifacts: I) -> Digest
+where
+    I: Iterator<Item = (&'a DeployHash, Result<Vec<u8>, bytesrepr::Error>)>,
+{
+    hash::hash_merkle_tree(
+        serialized_artifacts.map(|(deploy_hash, serialized_artifact)| {
+            let mut data = deploy_hash.inner().to_vec();
+            data.extend(
+                serialized_artifact
+                    .unwrap_or_else(|error| panic!("should serialize deploy artifact: {}", error)),
+            );
+            hash::hash(data)
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ExecutionEffect;
+
+    use super::*;
+    use crate::types::Deploy;
+
+    #[test]
+    fn should_roundtrip_through_global_state_and_verify_artifacts() {
+        let mut rng = crate::new_rng();
+        let deploys: Vec<Deploy> = (0..3).map(|_| Deploy::random(&mut rng)).collect();
+        let execution_results: Vec<(DeployHash, ExecutionResult)> = deploys
+            .iter()
+            .map(|deploy| {
+                let execution_result = ExecutionResult::Success {
+                    effect: ExecutionEffect::default(),
+                    transfers: vec![],
+                    cost: 1.into(),
+                };
+                (*deploy.id(), execution_result)
+            })
+            .collect();
+        let registry = ChecksumRegistry::new(
+            execution_results
+                .iter()
+                .map(|(hash, result)| (hash, result)),
+        );
+
+        let stored = ChecksumRegistry::try_from(registry.to_stored_value()).unwrap();
+        assert_eq!(stored, registry);
+        assert!(stored.verify_execution_results(
+            execution_results
+                .iter()
+                .map(|(hash, result)| (hash, result))
+        ));
+        assert!(!stored.verify_execution_results(
+            execution_results
+                .iter()
+                .skip(1)
+                .map(|(hash, result)| (hash, result))
+        ));
+        assert!(!stored.verify_execution_results(
+            execution_results
+                .iter()
+                .rev()
+                .map(|(hash, result)| (hash, result))
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Chunked transfer of large items.
+//!
+//! An item whose serialization exceeds the chunk size set in the chainspec is sent as a sequence of
+//! [`Chunk`]s, each of which carries the hashes of all chunks of the item.  A fetching node keeps
+//! the chunks received so far in a [`PartialItem`] per list of chunk hashes, checking every chunk
+//! against its list, so an acquisition interrupted by a slow or unresponsive peer can resume from a
+//! different peer rather than starting over.
+//!
+//! The chunk hashes aren't committed to by the item's ID, so a peer can only be caught serving a
+//! forged chunk list once all chunks have arrived: the reassembled item is then checked against
+//! the ID it was requested by, and discarded if it doesn't match.
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+use datasize::DataSize;
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    crypto::hash::{self, Digest},
+    types::{DeployHash, Item, Tag},
+};
+
+/// The ID of a single chunk of an item.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChunkId<I> {
+    /// The ID of the chunked item.
+    pub item_id: I,
+    /// The index of the chunk within the item.
+    pub index: u32,
+}
+
+impl<I: Display> Display for ChunkId<I> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "chunk {} of {}", self.index, self.item_id)
+    }
+}
+
+/// A chunk of a serialized item.
+#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk<I> {
+    /// The ID of the chunked item.
+    item_id: I,
+    /// The index of this chunk within the item.
+    index: u32,
+    /// The hashes of all chunks of the item, in order.
+    chunk_hashes: Vec<Digest>,
+    /// The chunk's part of the serialized item.
+    data: Vec<u8>,
+}
+
+/// A chunk of a deploy.
+pub type DeployChunk = Chunk<DeployHash>;
+
+impl<I: Copy> Chunk<I> {
+    /// Returns chunk `index` of `serialized_item`, split into chunks of `chunk_size` bytes, or
+    /// `None` if there is no such chunk.
+    pub(crate) fn new(
+        item_id: I,
+        serialized_item: &[u8],
+        index: u32,
+        chunk_size: u32,
+    ) -> Option<Self> {
+        let chunk_size = chunk_size.max(1) as usize;
+        let data = serialized_item
+            .chunks(chunk_size)
+            .nth(index as usize)?
+            .to_vec();
+        let chunk_hashes = serialized_item.chunks(chunk_size).map(hash::hash).collect();
+        Some(Chunk {
+            item_id,
+            index,
+            chunk_hashes,
+            data,
+        })
+    }
+
+    /// Returns the ID of this chunk.
+    pub(crate) fn id(&self) -> ChunkId<I> {
+        ChunkId {
+            item_id: self.item_id,
+            index: self.index,
+        }
+    }
+
+    /// Returns the ID of the chunked item.
+    pub(crate) fn item_id(&self) -> I {
+        self.item_id
+    }
+
+    /// Returns the number of chunks of the item.
+    pub(crate) fn count(&self) -> u32 {
+        self.chunk_hashes.len() as u32
+    }
+
+    /// Returns `true` if the chunk's data matches its own entry in the chunk hashes.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.chunk_hashes
+            .get(self.index as usize)
+            .map_or(false, |chunk_hash| *chunk_hash == hash::hash(&self.data))
+    }
+}
+
+impl<I: Display> Display for Chunk<I> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "chunk {}/{} of {}",
+            self.index,
+            self.chunk_hashes.len(),
+            self.item_id
+        )
+    }
+}
+
+impl<I: Debug> Debug for Chunk<I> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Chunk")
+            .field("item_id", &self.item_id)
+            .field("index", &self.index)
+            .field("chunk_hashes", &self.chunk_hashes)
+            .field("data", &format_args!("{:10}", HexFmt(&self.data)))
+            .finish()
+    }
+}
+
+impl Item for DeployChunk {
+    type Id = ChunkId<DeployHash>;
+    const TAG: Tag = Tag::DeployChunk;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        Chunk::id(self)
+    }
+}
+
+/// An error adding a chunk to a partial item.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChunkError {
+    /// The chunk's data doesn't match its hash.
+    #[error("chunk data doesn't match its hash")]
+    InvalidData,
+    /// The chunk lists different chunk hashes than the chunks received before.
+    #[error("chunk hashes differ from the ones received before")]
+    MismatchedChunkHashes,
+}
+
+/// The chunks of an item received so far.
+#[derive(Clone, DataSize, Debug)]
+pub(crate) struct PartialItem {
+    /// The hashes of all chunks of the item, as listed by the first chunk received.
+    chunk_hashes: Vec<Digest>,
+    /// The chunks received so far, by index.
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl PartialItem {
+    /// Starts a partial item from its first received chunk.
+    pub(crate) fn new<I: Copy>(chunk: Chunk<I>) -> Result<Self, ChunkError> {
+        let mut partial_item = PartialItem {
+            chunks: vec![None; chunk.chunk_hashes.len()],
+            chunk_hashes: chunk.chunk_hashes.clone(),
+        };
+        partial_item.add(chunk)?;
+        Ok(partial_item)
+    }
+
+    /// Returns `true` if `chunk` lists the same chunk hashes as the chunks received so far.
+    pub(crate) fn has_chunk_hashes_of<I>(&self, chunk: &Chunk<I>) -> bool {
+        chunk.chunk_hashes == self.chunk_hashes
+    }
+
+    /// Adds a chunk, checking it against the chunk hashes of the item.
+    pub(crate) fn add<I: Copy>(&mut self, chunk: Chunk<I>) -> Result<(), ChunkError> {
+        if !chunk.is_valid() {
+            return Err(ChunkError::InvalidData);
+        }
+        if chunk.chunk_hashes != self.chunk_hashes {
+            return Err(ChunkError::MismatchedChunkHashes);
+        }
+        self.chunks[chunk.index as usize] = Some(chunk.data);
+        Ok(())
+    }
+
+    /// Returns the indices of the chunks not received yet.
+    pub(crate) fn missing_chunks(&self) -> impl Iterator<Item = u32> + '_ {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.is_none())
+            .map(|(index, _)| index as u32)
+    }
+
+    /// Returns the number of chunks received so far and the total number of chunks.
+    pub(crate) fn progress(&self) -> (usize, usize) {
+        let received = self.chunks.iter().filter(|chunk| chunk.is_some()).count();
+        (received, self.chunks.len())
+    }
+
+    /// Returns the serialized item if all chunks have been received, or `self` otherwise.
+    pub(crate) fn assemble(self) -> Result<Vec<u8>, Self> {
+        if self.chunks.iter().any(Option::is_none) {
+            return Err(self);
+        }
+        Ok(self.chunks.into_iter().flatten().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reassemble_chunks_in_any_order() {
+        let item: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let chunks: Vec<Chunk<u64>> = (0..4)
+            .map(|index| Chunk::new(7, &item, index, 300).expect("should have chunk"))
+            .collect();
+        assert!(Chunk::new(7, &item, 4, 300).is_none());
+        assert!(chunks.iter().all(Chunk::is_valid));
+        assert_eq!(chunks[3].count(), 4);
+
+        let mut partial_item = PartialItem::new(chunks[2].clone()).expect("should start");
+        assert_eq!(
+            partial_item.missing_chunks().collect::<Vec<_>>(),
+            vec![0, 1, 3]
+        );
+        for index in &[3, 0] {
+            partial_item
+                .add(chunks[*index].clone())
+                .expect("should add");
+        }
+        let partial_item = partial_item.assemble().expect_err("should miss chunk 1");
+        assert_eq!(partial_item.progress(), (3, 4));
+
+        let mut partial_item = partial_item;
+        partial_item.add(chunks[1].clone()).expect("should add");
+        assert_eq!(partial_item.assemble().expect("should be complete"), item);
+    }
+
+    #[test]
+    fn should_reject_invalid_chunks() {
+        let item = vec![1u8; 100];
+        let other_item = vec![2u8; 100];
+        let chunk = Chunk::new(7, &item, 0, 40).expect("should have chunk");
+        let mut partial_item = PartialItem::new(chunk.clone()).expect("should start");
+
+        let mut corrupted = Chunk::new(7, &item, 1, 40).expect("should have chunk");
+        corrupted.data[0] ^= 1;
+        assert!(!corrupted.is_valid());
+        assert_eq!(partial_item.add(corrupted), Err(ChunkError::InvalidData));
+
+        let foreign = Chunk::new(7, &other_item, 1, 40).expect("should have chunk");
+        assert!(foreign.is_valid());
+        assert_eq!(
+            partial_item.add(foreign),
+            Err(ChunkError::MismatchedChunkHashes)
+        );
+        assert_eq!(partial_item.progress(), (1, 3));
+    }
+}
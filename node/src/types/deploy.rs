@@ -72,6 +72,7 @@ static DEPLOY: Lazy<Deploy> = Lazy::new(|| {
         body_hash,
         dependencies: vec![DeployHash::new(Digest::from([1u8; Digest::LENGTH]))],
         chain_name: String::from("casper-example"),
+        priority_fee: 0,
     };
     let serialized_header = serialize_header(&header);
     let hash = DeployHash::new(hash::hash(&serialized_header));
@@ -178,6 +179,15 @@ pub enum DeployValidationFailure {
         /// The attempted transfer amount.
         attempted: U512,
     },
+
+    /// Excessive priority fee.
+    #[error("priority fee of {got} exceeds limit of {max_priority_fee}")]
+    ExcessivePriorityFee {
+        /// The priority fee limit.
+        max_priority_fee: u64,
+        /// The received priority fee.
+        got: u64,
+    },
 }
 
 /// Errors other than validation failures relating to `Deploy`s.
@@ -259,6 +269,12 @@ impl From<Digest> for DeployHash {
     }
 }
 
+impl From<casper_types::DeployHash> for DeployHash {
+    fn from(deploy_hash: casper_types::DeployHash) -> Self {
+        Self(Digest::from(deploy_hash.value()))
+    }
+}
+
 impl AsRef<[u8]> for DeployHash {
     fn as_ref(&self) -> &[u8] {
         self.0.as_ref()
@@ -294,6 +310,8 @@ pub struct DeployHeader {
     body_hash: Digest,
     dependencies: Vec<DeployHash>,
     chain_name: String,
+    #[serde(default)]
+    priority_fee: u64,
 }
 
 impl DeployHeader {
@@ -338,6 +356,12 @@ impl DeployHeader {
         &self.chain_name
     }
 
+    /// Motes offered to the proposer on top of the execution cost, to have the deploy included
+    /// ahead of others at the same gas price.
+    pub fn priority_fee(&self) -> u64 {
+        self.priority_fee
+    }
+
     /// Determine if this deploy header has valid values based on a `DeployConfig` and timestamp.
     pub fn is_valid(&self, deploy_config: &DeployConfig, current_timestamp: Timestamp) -> bool {
         let ttl_valid = self.ttl() <= deploy_config.max_ttl;
@@ -365,6 +389,7 @@ impl ToBytes for DeployHeader {
         buffer.extend(self.body_hash.to_bytes()?);
         buffer.extend(self.dependencies.to_bytes()?);
         buffer.extend(self.chain_name.to_bytes()?);
+        buffer.extend(self.priority_fee.to_bytes()?);
         Ok(buffer)
     }
 
@@ -376,6 +401,7 @@ impl ToBytes for DeployHeader {
             + self.body_hash.serialized_length()
             + self.dependencies.serialized_length()
             + self.chain_name.serialized_length()
+            + self.priority_fee.serialized_length()
     }
 }
 
@@ -388,6 +414,7 @@ impl FromBytes for DeployHeader {
         let (body_hash, remainder) = Digest::from_bytes(remainder)?;
         let (dependencies, remainder) = Vec::<DeployHash>::from_bytes(remainder)?;
         let (chain_name, remainder) = String::from_bytes(remainder)?;
+        let (priority_fee, remainder) = u64::from_bytes(remainder)?;
         let deploy_header = DeployHeader {
             account,
             timestamp,
@@ -396,6 +423,7 @@ impl FromBytes for DeployHeader {
             body_hash,
             dependencies,
             chain_name,
+            priority_fee,
         };
         Ok((deploy_header, remainder))
     }
@@ -405,7 +433,7 @@ impl Display for DeployHeader {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(
             formatter,
-            "deploy-header[account: {}, timestamp: {}, ttl: {}, gas_price: {}, body_hash: {}, dependencies: [{}], chain_name: {}]",
+            "deploy-header[account: {}, timestamp: {}, ttl: {}, gas_price: {}, body_hash: {}, dependencies: [{}], chain_name: {}, priority_fee: {}]",
             self.account,
             self.timestamp,
             self.ttl,
@@ -413,6 +441,7 @@ impl Display for DeployHeader {
             self.body_hash,
             DisplayIter::new(self.dependencies.iter()),
             self.chain_name,
+            self.priority_fee,
         )
     }
 }
@@ -489,6 +518,7 @@ impl Deploy {
         timestamp: Timestamp,
         ttl: TimeDiff,
         gas_price: u64,
+        priority_fee: u64,
         dependencies: Vec<DeployHash>,
         chain_name: String,
         payment: ExecutableDeployItem,
@@ -510,6 +540,7 @@ impl Deploy {
             body_hash,
             dependencies,
             chain_name,
+            priority_fee,
         };
         let serialized_header = serialize_header(&header);
         let hash = DeployHash::new(hash::hash(&serialized_header));
@@ -535,6 +566,26 @@ impl Deploy {
         self.approvals.push(approval);
     }
 
+    /// Adds the given approvals, skipping any already present, and returns the number added.
+    ///
+    /// The approvals are not verified here; the deploy must be re-validated afterwards.
+    pub(crate) fn add_approvals<T: IntoIterator<Item = Approval>>(
+        &mut self,
+        approvals: T,
+    ) -> usize {
+        let mut added = 0;
+        for approval in approvals {
+            if !self.approvals.contains(&approval) {
+                self.approvals.push(approval);
+                added += 1;
+            }
+        }
+        if added > 0 {
+            self.is_valid = None;
+        }
+        added
+    }
+
     /// Returns the `DeployHash` identifying this `Deploy`.
     pub fn id(&self) -> &DeployHash {
         &self.hash
@@ -694,6 +745,26 @@ impl Deploy {
             });
         }
 
+        // Native transfers pay a fixed cost, which leaves nothing in the payment purse for a
+        // priority fee.
+        let max_priority_fee = if self.session().is_transfer() {
+            0
+        } else {
+            config.max_priority_fee
+        };
+        if header.priority_fee() > max_priority_fee {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                max_priority_fee,
+                "deploy priority fee excessive"
+            );
+            return Err(DeployValidationFailure::ExcessivePriorityFee {
+                max_priority_fee,
+                got: header.priority_fee(),
+            });
+        }
+
         if self.session().is_transfer() {
             let item = self.session().clone();
             let attempted = item
@@ -715,6 +786,14 @@ impl Deploy {
         self.is_valid()
     }
 
+    /// Changes the header without updating the deploy hash, so that the deploy fails validation.
+    #[cfg(test)]
+    pub fn corrupt_header(&mut self) -> &mut Self {
+        self.header.gas_price = self.header.gas_price.wrapping_add(1);
+        self.is_valid = None;
+        self
+    }
+
     /// Generates a random instance using a `TestRng`.
     #[cfg(test)]
     pub fn random(rng: &mut TestRng) -> Self {
@@ -738,6 +817,7 @@ impl Deploy {
             timestamp,
             ttl,
             gas_price,
+            0,
             dependencies,
             chain_name,
             payment,
@@ -755,9 +835,15 @@ impl DocExample for Deploy {
 }
 
 fn serialize_header(header: &DeployHeader) -> Vec<u8> {
-    header
+    let mut buffer = header
         .to_bytes()
-        .unwrap_or_else(|error| panic!("should serialize deploy header: {}", error))
+        .unwrap_or_else(|error| panic!("should serialize deploy header: {}", error));
+    // The priority fee is serialized last.  Leaving it out when there is none keeps the hashes of
+    // such deploys the same as before the fee was introduced.
+    if header.priority_fee == 0 {
+        buffer.truncate(buffer.len() - header.priority_fee.serialized_length());
+    }
+    buffer
 }
 
 fn serialize_body(payment: &ExecutableDeployItem, session: &ExecutableDeployItem) -> Vec<u8> {
@@ -830,6 +916,66 @@ impl Display for Deploy {
     }
 }
 
+/// A deploy's header along with the deploy's hash.
+///
+/// Fetched in place of the full deploy where only the header is needed, e.g. when validating the
+/// deploys of a proposed block.
+#[derive(Clone, DataSize, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DeployHeaderById {
+    deploy_hash: DeployHash,
+    header: DeployHeader,
+}
+
+impl DeployHeaderById {
+    /// Constructs a new `DeployHeaderById`.
+    pub fn new(deploy_hash: DeployHash, header: DeployHeader) -> Self {
+        DeployHeaderById {
+            deploy_hash,
+            header,
+        }
+    }
+
+    /// Returns the hash of the deploy.
+    pub fn deploy_hash(&self) -> &DeployHash {
+        &self.deploy_hash
+    }
+
+    /// Returns the header of the deploy.
+    pub fn header(&self) -> &DeployHeader {
+        &self.header
+    }
+
+    /// Returns true if and only if the deploy hash is the hash of the header.
+    pub fn is_valid(&self) -> bool {
+        let serialized_header = serialize_header(&self.header);
+        DeployHash::new(hash::hash(&serialized_header)) == self.deploy_hash
+    }
+}
+
+impl From<&Deploy> for DeployHeaderById {
+    fn from(deploy: &Deploy) -> Self {
+        DeployHeaderById::new(deploy.hash, deploy.header.clone())
+    }
+}
+
+impl Item for DeployHeaderById {
+    type Id = DeployHash;
+
+    const TAG: Tag = Tag::DeployHeaderById;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        self.deploy_hash
+    }
+}
+
+impl Display for DeployHeaderById {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} with {}", self.deploy_hash, self.header)
+    }
+}
+
 impl From<Deploy> for DeployItem {
     fn from(deploy: Deploy) -> Self {
         let address = deploy.header().account().to_account_hash();
@@ -844,12 +990,83 @@ impl From<Deploy> for DeployItem {
             deploy.session().clone(),
             deploy.payment().clone(),
             deploy.header().gas_price(),
+            deploy.header().priority_fee(),
             authorization_keys,
             casper_types::DeployHash::new(deploy.id().inner().to_array()),
         )
     }
 }
 
+/// A deploy as serialized before deploy headers carried a priority fee.
+///
+/// Only used to read deploys stored in that format.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+pub(crate) struct DeployWithoutPriorityFee {
+    hash: DeployHash,
+    header: HeaderWithoutPriorityFee,
+    payment: ExecutableDeployItem,
+    session: ExecutableDeployItem,
+    approvals: Vec<Approval>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct HeaderWithoutPriorityFee {
+    account: PublicKey,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    gas_price: u64,
+    body_hash: Digest,
+    dependencies: Vec<DeployHash>,
+    chain_name: String,
+}
+
+impl From<DeployWithoutPriorityFee> for Deploy {
+    fn from(deploy: DeployWithoutPriorityFee) -> Self {
+        let header = deploy.header;
+        Deploy {
+            hash: deploy.hash,
+            header: DeployHeader {
+                account: header.account,
+                timestamp: header.timestamp,
+                ttl: header.ttl,
+                gas_price: header.gas_price,
+                body_hash: header.body_hash,
+                dependencies: header.dependencies,
+                chain_name: header.chain_name,
+                priority_fee: 0,
+            },
+            payment: deploy.payment,
+            session: deploy.session,
+            approvals: deploy.approvals,
+            is_valid: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl From<Deploy> for DeployWithoutPriorityFee {
+    fn from(deploy: Deploy) -> Self {
+        let header = deploy.header;
+        DeployWithoutPriorityFee {
+            hash: deploy.hash,
+            header: HeaderWithoutPriorityFee {
+                account: header.account,
+                timestamp: header.timestamp,
+                ttl: header.ttl,
+                gas_price: header.gas_price,
+                body_hash: header.body_hash,
+                dependencies: header.dependencies,
+                chain_name: header.chain_name,
+            },
+            payment: deploy.payment,
+            session: deploy.session,
+            approvals: deploy.approvals,
+        }
+    }
+}
+
 /// The deploy mutable metadata.
 ///
 /// Currently a stop-gap measure to associate an immutable deploy with additional metadata. Holds
@@ -919,6 +1136,14 @@ mod tests {
         assert_eq!(deploy, decoded);
     }
 
+    #[test]
+    fn should_convert_execution_engine_deploy_hash() {
+        let mut rng = crate::new_rng();
+        let deploy_hash = DeployHash::random(&mut rng);
+        let ee_deploy_hash = casper_types::DeployHash::new(deploy_hash.inner().to_array());
+        assert_eq!(DeployHash::from(ee_deploy_hash), deploy_hash);
+    }
+
     #[test]
     fn bincode_roundtrip() {
         let mut rng = crate::new_rng();
@@ -960,6 +1185,7 @@ mod tests {
             Timestamp::now(),
             ttl,
             1,
+            0,
             dependencies,
             chain_name.to_string(),
             ExecutableDeployItem::ModuleBytes {
@@ -1041,6 +1267,21 @@ mod tests {
         check_is_not_valid(deploy, DeployValidationFailure::InvalidDeployHash);
     }
 
+    #[test]
+    fn header_by_id_should_be_valid_only_for_matching_hash() {
+        let mut rng = crate::new_rng();
+        let deploy = create_deploy(&mut rng, DeployConfig::default().max_ttl, 0, "net-1");
+
+        let header_by_id = DeployHeaderById::from(&deploy);
+        assert!(header_by_id.is_valid());
+        let serialized = bincode::serialize(&header_by_id).unwrap();
+        assert_eq!(header_by_id, bincode::deserialize(&serialized).unwrap());
+
+        let mut header = deploy.header.clone();
+        header.gas_price += 1;
+        assert!(!DeployHeaderById::new(deploy.hash, header).is_valid());
+    }
+
     #[test]
     fn not_valid_due_to_invalid_approval() {
         let mut rng = crate::new_rng();
@@ -1163,4 +1404,93 @@ mod tests {
             "deploy should not have run expensive `is_valid` call"
         );
     }
+
+    fn create_deploy_with_priority_fee(
+        rng: &mut TestRng,
+        priority_fee: u64,
+        session: ExecutableDeployItem,
+    ) -> Deploy {
+        let secret_key = SecretKey::random(rng);
+        Deploy::new(
+            Timestamp::now(),
+            DeployConfig::default().max_ttl,
+            1,
+            priority_fee,
+            vec![],
+            "net-1".to_string(),
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: runtime_args! { ARG_AMOUNT => U512::from(MAX_PAYMENT_AMOUNT) },
+            },
+            session,
+            &secret_key,
+            rng,
+        )
+    }
+
+    #[test]
+    fn priority_fee_should_only_change_the_hash_if_present() {
+        let mut rng = crate::new_rng();
+        let deploy = create_deploy(&mut rng, DeployConfig::default().max_ttl, 0, "net-1");
+
+        // Without a priority fee, the hash covers the header as serialized before the fee existed.
+        let serialized_header = deploy.header.to_bytes().unwrap();
+        let legacy_header = &serialized_header[..serialized_header.len() - 8];
+        assert_eq!(deploy.hash, DeployHash::new(hash::hash(legacy_header)));
+
+        let session = rng.gen();
+        let mut deploy = create_deploy_with_priority_fee(&mut rng, 5, session);
+        deploy.is_valid().expect("should be valid");
+        bytesrepr::test_serialization_roundtrip(&deploy);
+
+        deploy.header.priority_fee = 6;
+        deploy.is_valid = None;
+        check_is_not_valid(deploy, DeployValidationFailure::InvalidDeployHash);
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_priority_fee() {
+        let mut rng = crate::new_rng();
+        let chain_name = "net-1";
+        let mut deploy_config = DeployConfig::default();
+        deploy_config.max_priority_fee = 10;
+        let session = ExecutableDeployItem::StoredContractByName {
+            name: String::from("casper-example"),
+            entry_point: String::from("example-entry-point"),
+            args: RuntimeArgs::new(),
+        };
+
+        let mut deploy = create_deploy_with_priority_fee(&mut rng, 10, session.clone());
+        deploy
+            .is_acceptable(chain_name, &deploy_config)
+            .expect("should be acceptable");
+
+        let mut deploy = create_deploy_with_priority_fee(&mut rng, 11, session);
+        let expected_error = DeployValidationFailure::ExcessivePriorityFee {
+            max_priority_fee: 10,
+            got: 11,
+        };
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &deploy_config),
+            Err(expected_error)
+        );
+
+        // Native transfers can't pay a priority fee at all.
+        let transfer = ExecutableDeployItem::Transfer {
+            args: runtime_args! { ARG_AMOUNT => U512::from(MAX_PAYMENT_AMOUNT) },
+        };
+        let mut deploy = create_deploy_with_priority_fee(&mut rng, 1, transfer);
+        let expected_error = DeployValidationFailure::ExcessivePriorityFee {
+            max_priority_fee: 0,
+            got: 1,
+        };
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &deploy_config),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
 }
@@ -0,0 +1,195 @@
+//! Proofs that a deploy is included in a block.
+//!
+//! A proof links a deploy hash to the body hash in a block's header, so that a light client which
+//! trusts the header, e.g. after checking the block's finality signatures, can verify that the
+//! deploy was included without trusting the node which served the proof.
+
+use std::convert::TryFrom;
+
+use hex_buffer_serde::{Hex, HexForm};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::bytesrepr::{self, ToBytes};
+
+use crate::{
+    crypto::hash::{self, Digest},
+    types::{Block, BlockBody, BlockHeader, DeployHash},
+};
+
+/// The position of a deploy within a block's body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum DeployPosition {
+    /// The index of the deploy within the block's deploy hashes.
+    Deploy(u32),
+    /// The index of the deploy within the block's transfer hashes.
+    Transfer(u32),
+}
+
+/// The data linking the hashes in a block's body to its body hash.
+///
+/// There is a variant for every way in which block body hashes have been computed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum BodyHashProof {
+    /// The body hash is the hash of the whole serialized body, so the proof is the serialized
+    /// body.
+    SerializedBody(
+        #[serde(with = "HexForm::<Vec<u8>>")]
+        #[schemars(with = "String", description = "Hex-encoded serialized block body.")]
+        Vec<u8>,
+    ),
+}
+
+/// An error verifying a deploy inclusion proof.
+#[derive(Debug, Error)]
+pub enum DeployInclusionProofError {
+    /// The proof doesn't hash to the body hash of the block header.
+    #[error("proof hashes to {actual}, but the block's body hash is {expected}")]
+    UnexpectedBodyHash {
+        /// The body hash of the block header.
+        expected: Digest,
+        /// The hash the proof yields.
+        actual: Digest,
+    },
+    /// The proof's serialized body could not be parsed.
+    #[error("failed to parse the block body of the proof: {0}")]
+    InvalidBody(bytesrepr::Error),
+    /// The block body has a different deploy hash at the proof's position, or none at all.
+    #[error("the block body doesn't contain {deploy_hash} at {position:?}")]
+    NotAtPosition {
+        /// The deploy hash to be proven.
+        deploy_hash: DeployHash,
+        /// The position given by the proof.
+        position: DeployPosition,
+    },
+}
+
+/// A proof that a deploy is included in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeployInclusionProof {
+    /// The hash of the included deploy.
+    pub deploy_hash: DeployHash,
+    /// The position of the deploy within the block's body.
+    pub position: DeployPosition,
+    /// The data linking the deploy hash at that position to the block's body hash.
+    pub body_hash_proof: BodyHashProof,
+}
+
+impl DeployInclusionProof {
+    /// Creates a proof that the given deploy is included in `block`, or returns `None` if it isn't.
+    pub fn new(block: &Block, deploy_hash: &DeployHash) -> Option<Self> {
+        let body = block.body();
+        let position_in = |hashes: &Vec<DeployHash>| {
+            hashes
+                .iter()
+                .position(|hash| hash == deploy_hash)
+                .and_then(|index| u32::try_from(index).ok())
+        };
+        let position = position_in(body.deploy_hashes())
+            .map(DeployPosition::Deploy)
+            .or_else(|| position_in(body.transfer_hashes()).map(DeployPosition::Transfer))?;
+        let serialized_body = body
+            .to_bytes()
+            .unwrap_or_else(|error| panic!("should serialize block body: {}", error));
+        Some(DeployInclusionProof {
+            deploy_hash: *deploy_hash,
+            position,
+            body_hash_proof: BodyHashProof::SerializedBody(serialized_body),
+        })
+    }
+
+    /// Verifies that the proof links the deploy hash to the body hash of `header`.
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), DeployInclusionProofError> {
+        let body = match &self.body_hash_proof {
+            BodyHashProof::SerializedBody(serialized_body) => {
+                let actual = hash::hash(serialized_body);
+                if actual != *header.body_hash() {
+                    return Err(DeployInclusionProofError::UnexpectedBodyHash {
+                        expected: *header.body_hash(),
+                        actual,
+                    });
+                }
+                bytesrepr::deserialize::<BlockBody>(serialized_body.clone())
+                    .map_err(DeployInclusionProofError::InvalidBody)?
+            }
+        };
+
+        let maybe_hash_at_position = match self.position {
+            DeployPosition::Deploy(index) => body.deploy_hashes().get(index as usize),
+            DeployPosition::Transfer(index) => body.transfer_hashes().get(index as usize),
+        };
+        if maybe_hash_at_position != Some(&self.deploy_hash) {
+            return Err(DeployInclusionProofError::NotAtPosition {
+                deploy_hash: self.deploy_hash,
+                position: self.position,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    /// Returns a random block including at least one deploy.
+    fn random_block_with_deploys(rng: &mut TestRng) -> Block {
+        iter::repeat_with(|| Block::random(rng))
+            .find(|block| !block.deploy_hashes().is_empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_verify_proof_of_included_deploy() {
+        let mut rng = TestRng::new();
+        let block = random_block_with_deploys(&mut rng);
+        let deploy_hash = *block.deploy_hashes().last().unwrap();
+
+        let proof = DeployInclusionProof::new(&block, &deploy_hash).unwrap();
+        let index = block.deploy_hashes().len() as u32 - 1;
+        assert_eq!(proof.position, DeployPosition::Deploy(index));
+        proof.verify(block.header()).unwrap();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: DeployInclusionProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, proof);
+
+        let other_deploy_hash = DeployHash::new(Digest::random(&mut rng));
+        assert!(DeployInclusionProof::new(&block, &other_deploy_hash).is_none());
+    }
+
+    #[test]
+    fn should_reject_invalid_proofs() {
+        let mut rng = TestRng::new();
+        let block = random_block_with_deploys(&mut rng);
+        let deploy_hash = block.deploy_hashes()[0];
+        let proof = DeployInclusionProof::new(&block, &deploy_hash).unwrap();
+
+        // A proof for another block.
+        let other_block = Block::random(&mut rng);
+        assert!(matches!(
+            proof.verify(other_block.header()),
+            Err(DeployInclusionProofError::UnexpectedBodyHash { .. })
+        ));
+
+        // A proof claiming another deploy at the same position.
+        let mut wrong_deploy = proof.clone();
+        wrong_deploy.deploy_hash = DeployHash::new(Digest::random(&mut rng));
+        assert!(matches!(
+            wrong_deploy.verify(block.header()),
+            Err(DeployInclusionProofError::NotAtPosition { .. })
+        ));
+
+        // A proof claiming the deploy is a transfer.
+        let mut wrong_position = proof;
+        wrong_position.position = DeployPosition::Transfer(0);
+        assert!(wrong_position.verify(block.header()).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use datasize::DataSize;
+use serde::Serialize;
 use signal_hook::consts::signal::{SIGINT, SIGQUIT, SIGTERM};
 
 /// The offset Rust uses by default when generating an exit code after being interrupted by a
@@ -9,7 +10,11 @@ const SIGNAL_OFFSET: u8 = 128;
 /// reactor to the binary.
 ///
 /// Note that a panic will result in the Rust process producing an exit code of 101.
-#[derive(Clone, Copy, PartialEq, Eq, Debug, DataSize)]
+///
+/// Fatal errors exit with a code from 110 upwards identifying their kind, after writing a crash
+/// report if `node.crash_report_path` is configured.  Supervisors may restart the node after a
+/// `NetworkFailure`; the other fatal errors need an operator's attention.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, DataSize, Serialize)]
 #[repr(u8)]
 pub enum ExitCode {
     /// The process should exit with success.  The launcher should proceed to run the next
@@ -21,6 +26,23 @@ pub enum ExitCode {
     /// The process should exit with `102`.  The launcher should proceed to run the previous
     /// installed version of `casper-node`.
     DowngradeVersion = 102,
+    /// The process should exit with `110` after a fatal storage error, e.g. a failed write or a
+    /// corrupted database.
+    StorageFailure = 110,
+    /// The process should exit with `111` after a fatal networking error, e.g. when isolated from
+    /// all known peers.
+    NetworkFailure = 111,
+    /// The process should exit with `112` when consensus can't continue, e.g. because too many
+    /// validators are faulty.
+    ConsensusFailure = 112,
+    /// The process should exit with `113` after finding that conflicting blocks were finalized.
+    FinalityViolation = 113,
+    /// The process should exit with `114` when its allocated memory exceeds the limit set in the
+    /// `CL_MEM_LIMIT_MB` environment variable.
+    OutOfMemory = 114,
+    /// The process should exit with `115` if `consensus.require_bonded_signing_key` is set and the
+    /// signing key isn't bonded in the current era, or has no active bid.
+    SigningKeyNotBonded = 115,
     /// The exit code Rust uses by default when interrupted via an `INT` signal.
     SigInt = SIGNAL_OFFSET + SIGINT as u8,
     /// The exit code Rust uses by default when interrupted via a `QUIT` signal.
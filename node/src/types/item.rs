@@ -38,6 +38,14 @@ pub enum Tag {
     GossipedAddress,
     /// A block requested by its height in the linear chain.
     BlockByHeight,
+    /// A deploy header, fetched by the deploy's hash.
+    DeployHeaderById,
+    /// A chunk of a deploy too large to be sent in one piece.
+    DeployChunk,
+    /// A block header, fetched by the block's hash.
+    BlockHeader,
+    /// The execution results of a block's deploys, fetched by the block's hash.
+    BlockExecutionResults,
 }
 
 /// A trait which allows an implementing type to be used by the gossiper and fetcher components, and
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
@@ -10,4 +12,46 @@ use crate::types::BlockHash;
 pub struct NodeConfig {
     /// Hash used as a trust anchor when joining, if any.
     pub trusted_hash: Option<BlockHash>,
+
+    /// Further hashes used as trust anchors when joining, e.g. obtained from independent sources.
+    ///
+    /// All trusted hashes must lie on the same chain, and the highest of them is synchronized to.
+    #[serde(default)]
+    pub additional_trusted_hashes: Vec<BlockHash>,
+
+    /// Whether to join by downloading only the headers of the ancestors of the trusted block back
+    /// to the oldest switch block consensus needs, and the switch blocks among them, rather than
+    /// executing the whole chain from genesis.
+    ///
+    /// Only takes effect if the global state of the trusted block is already available locally;
+    /// otherwise the node falls back to a full synchronization.
+    #[serde(default)]
+    pub minimal_join: bool,
+
+    /// Number of worker threads used to verify batches of finality signatures, or 0 to use one
+    /// thread per CPU.
+    #[serde(default)]
+    pub signature_verification_threads: usize,
+
+    /// Path of the JSON crash report written when the node exits due to a fatal error.  A relative
+    /// path is resolved against the directory of the config file.  If unset, no report is written.
+    #[serde(default)]
+    pub crash_report_path: Option<PathBuf>,
+}
+
+impl NodeConfig {
+    /// Returns all configured trusted hashes, without duplicates.
+    pub fn trusted_hashes(&self) -> Vec<BlockHash> {
+        let mut trusted_hashes: Vec<BlockHash> = Vec::new();
+        for hash in self
+            .trusted_hash
+            .iter()
+            .chain(self.additional_trusted_hashes.iter())
+        {
+            if !trusted_hashes.contains(hash) {
+                trusted_hashes.push(*hash);
+            }
+        }
+        trusted_hashes
+    }
 }
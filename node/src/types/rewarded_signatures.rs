@@ -0,0 +1,428 @@
+//! Finality signatures of past blocks rewarded in a block.
+//!
+//! A proposer cites the validators whose finality signatures of recent blocks it has seen, so that
+//! signing blocks can be rewarded.  Each signature is cited at most once along a chain, and the
+//! citations are part of the block body, so block explorers can show which validators' signatures
+//! a block included.
+//!
+//! Before voting for a proposed block, validators check its citations against the finalized blocks
+//! they have stored: each cited block must be one of them, its stored signatures must meet the
+//! weight threshold, each signer must have been a validator in its era whose signature is stored,
+//! and no signature may be cited twice, neither within the proposal nor by one of the finalized
+//! blocks.  Citations by the proposal's non-finalized ancestors are not checked.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use num::rational::Ratio;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    PublicKey, U512,
+};
+
+use crate::types::{BlockHash, BlockWithSignatures};
+
+/// An error returned when the rewarded signatures of a proposed block are invalid.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InvalidRewardedSignatures {
+    /// The cited block is not a known finalized block at the stated height.
+    #[error("cited block {block_hash} is not known at height {block_height}")]
+    UnknownBlock {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+        /// The stated height of the cited block.
+        block_height: u64,
+    },
+    /// No block is stored at the stated height of the cited block yet.
+    #[error("no block is stored at height {block_height} of cited block {block_hash} yet")]
+    NotStored {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+        /// The stated height of the cited block.
+        block_height: u64,
+    },
+    /// The validators of the cited block's era are not known.
+    #[error("validators of the era of cited block {block_hash} are not known")]
+    UnknownValidators {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+    },
+    /// A cited signer was not a validator in the era of the signed block.
+    #[error("{signer} was not a validator in the era of cited block {block_hash}")]
+    NotAValidator {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+        /// The cited signer.
+        signer: PublicKey,
+    },
+    /// No signature of the cited block by a cited signer is stored.
+    #[error("no signature of cited block {block_hash} by {signer} is stored")]
+    MissingSignature {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+        /// The cited signer.
+        signer: PublicKey,
+    },
+    /// The stored signatures of the cited block don't meet the weight threshold.
+    #[error("stored signatures of cited block {block_hash} don't meet the weight threshold")]
+    BelowThreshold {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+    },
+    /// A signature is cited more than once.
+    #[error("signature of block {block_hash} by {signer} is cited twice")]
+    CitedTwice {
+        /// The hash of the cited block.
+        block_hash: BlockHash,
+        /// The cited signer.
+        signer: PublicKey,
+    },
+}
+
+impl InvalidRewardedSignatures {
+    /// Returns `true` if the error may go away once more blocks or signatures are stored.
+    pub(crate) fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            InvalidRewardedSignatures::NotStored { .. }
+                | InvalidRewardedSignatures::MissingSignature { .. }
+                | InvalidRewardedSignatures::BelowThreshold { .. }
+        )
+    }
+}
+
+/// Returns whether signers with `signed_weight` out of `total_weight` meet `weight_threshold`.
+pub(crate) fn meets_weight_threshold(
+    signed_weight: U512,
+    total_weight: U512,
+    weight_threshold: Ratio<u64>,
+) -> bool {
+    !total_weight.is_zero()
+        && signed_weight * U512::from(*weight_threshold.denom())
+            >= total_weight * U512::from(*weight_threshold.numer())
+}
+
+/// The validators whose finality signatures of a single past block are rewarded.
+#[derive(
+    Clone, DataSize, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SignatureCitation {
+    /// The hash of the signed block.
+    pub block_hash: BlockHash,
+    /// The height of the signed block.
+    pub block_height: u64,
+    /// The validators whose signatures of the block are rewarded.
+    pub signers: BTreeSet<PublicKey>,
+}
+
+impl ToBytes for SignatureCitation {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.block_hash.to_bytes()?);
+        buffer.extend(self.block_height.to_bytes()?);
+        buffer.extend(self.signers.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.block_hash.serialized_length()
+            + self.block_height.serialized_length()
+            + self.signers.serialized_length()
+    }
+}
+
+impl FromBytes for SignatureCitation {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (block_hash, remainder) = BlockHash::from_bytes(bytes)?;
+        let (block_height, remainder) = u64::from_bytes(remainder)?;
+        let (signers, remainder) = BTreeSet::<PublicKey>::from_bytes(remainder)?;
+        let citation = SignatureCitation {
+            block_hash,
+            block_height,
+            signers,
+        };
+        Ok((citation, remainder))
+    }
+}
+
+/// The finality signatures of past blocks rewarded in a block, ordered by block height.
+#[derive(
+    Clone,
+    DataSize,
+    Debug,
+    Default,
+    PartialOrd,
+    Ord,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub struct RewardedSignatures(Vec<SignatureCitation>);
+
+impl RewardedSignatures {
+    /// Creates a new instance from citations ordered by block height.
+    pub(crate) fn new(citations: Vec<SignatureCitation>) -> Self {
+        RewardedSignatures(citations)
+    }
+
+    /// Returns the citations, ordered by block height.
+    pub fn citations(&self) -> &[SignatureCitation] {
+        &self.0
+    }
+
+    /// Returns `true` if no signatures are rewarded.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns all cited signatures, as pairs of the signed block's hash and the signer.
+    pub(crate) fn cited(&self) -> impl Iterator<Item = (&BlockHash, &PublicKey)> {
+        self.0.iter().flat_map(|citation| {
+            citation
+                .signers
+                .iter()
+                .map(move |signer| (&citation.block_hash, signer))
+        })
+    }
+
+    /// Returns the heights of the cited blocks.
+    pub(crate) fn cited_heights(&self) -> impl Iterator<Item = u64> + '_ {
+        self.0.iter().map(|citation| citation.block_height)
+    }
+
+    /// Checks the citations of a proposed block against `window`, the finalized blocks with their
+    /// signatures and era's validator weights whose signatures the proposer could have cited.
+    ///
+    /// Only the signatures stored in `window` count towards `weight_threshold`.
+    pub(crate) fn validate(
+        &self,
+        window: &[BlockWithSignatures],
+        weight_threshold: Ratio<u64>,
+    ) -> Result<(), InvalidRewardedSignatures> {
+        let mut cited: HashSet<(BlockHash, PublicKey)> = window
+            .iter()
+            .flat_map(|block_with_signatures| {
+                block_with_signatures
+                    .block
+                    .body()
+                    .rewarded_signatures()
+                    .cited()
+            })
+            .map(|(block_hash, signer)| (*block_hash, *signer))
+            .collect();
+
+        for citation in &self.0 {
+            let block_hash = citation.block_hash;
+            let cited_block = window
+                .iter()
+                .find(|block_with_signatures| {
+                    block_with_signatures.block.height() == citation.block_height
+                })
+                .ok_or(InvalidRewardedSignatures::NotStored {
+                    block_hash,
+                    block_height: citation.block_height,
+                })?;
+            if *cited_block.block.hash() != block_hash {
+                return Err(InvalidRewardedSignatures::UnknownBlock {
+                    block_hash,
+                    block_height: citation.block_height,
+                });
+            }
+            let weights = cited_block
+                .era_validator_weights
+                .as_ref()
+                .ok_or(InvalidRewardedSignatures::UnknownValidators { block_hash })?;
+            let proofs = &cited_block.signatures.proofs;
+            for signer in &citation.signers {
+                if !weights.contains_key(signer) {
+                    return Err(InvalidRewardedSignatures::NotAValidator {
+                        block_hash,
+                        signer: *signer,
+                    });
+                }
+                if !proofs.contains_key(signer) {
+                    return Err(InvalidRewardedSignatures::MissingSignature {
+                        block_hash,
+                        signer: *signer,
+                    });
+                }
+                if !cited.insert((block_hash, *signer)) {
+                    return Err(InvalidRewardedSignatures::CitedTwice {
+                        block_hash,
+                        signer: *signer,
+                    });
+                }
+            }
+            let total_weight = weights
+                .values()
+                .fold(U512::zero(), |sum, weight| sum + *weight);
+            let signed_weight = proofs
+                .keys()
+                .filter_map(|signer| weights.get(signer))
+                .fold(U512::zero(), |sum, weight| sum + *weight);
+            if !meets_weight_threshold(signed_weight, total_weight, weight_threshold) {
+                return Err(InvalidRewardedSignatures::BelowThreshold { block_hash });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for RewardedSignatures {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        let signature_count: usize = self.0.iter().map(|citation| citation.signers.len()).sum();
+        write!(
+            formatter,
+            "{} signatures of {} blocks",
+            signature_count,
+            self.0.len()
+        )
+    }
+}
+
+impl ToBytes for RewardedSignatures {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for RewardedSignatures {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (citations, remainder) = Vec::<SignatureCitation>::from_bytes(bytes)?;
+        Ok((RewardedSignatures(citations), remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, iter};
+
+    use casper_types::{SecretKey, Signature};
+
+    use super::*;
+    use crate::{
+        components::consensus::EraId,
+        crypto::AsymmetricKeyExt,
+        types::{Block, BlockSignatures},
+    };
+
+    #[test]
+    fn should_validate_citations_against_window() {
+        let mut rng = crate::new_rng();
+        let validators: Vec<PublicKey> =
+            iter::repeat_with(|| PublicKey::from(&SecretKey::random(&mut rng)))
+                .take(3)
+                .collect();
+        let outsider = PublicKey::from(&SecretKey::random(&mut rng));
+        let weights: BTreeMap<PublicKey, U512> = validators
+            .iter()
+            .map(|validator| (*validator, U512::from(100)))
+            .collect();
+        // Two of the validators signed block 10, all three signed block 11.
+        let window: Vec<BlockWithSignatures> = (10..12)
+            .map(|height| {
+                let block = Block::random_with_specifics(&mut rng, EraId(1), height, false);
+                let mut signatures = BlockSignatures::new(*block.hash(), EraId(1));
+                for signer in &validators[..height as usize - 8] {
+                    let signature = Signature::ed25519([0; Signature::ED25519_LENGTH]).unwrap();
+                    signatures.insert_proof(*signer, signature);
+                }
+                BlockWithSignatures {
+                    block,
+                    signatures,
+                    era_validator_weights: Some(weights.clone()),
+                }
+            })
+            .collect();
+        let threshold = Ratio::new(1, 2);
+        let cite = |index: usize, block_height: u64, signers: &[PublicKey]| SignatureCitation {
+            block_hash: *window[index].block.hash(),
+            block_height,
+            signers: signers.iter().copied().collect(),
+        };
+        let block_hash = *window[1].block.hash();
+
+        let valid = RewardedSignatures::new(vec![
+            cite(0, 10, &validators[..2]),
+            cite(1, 11, &validators),
+        ]);
+        assert_eq!(valid.validate(&window, threshold), Ok(()));
+
+        let wrong_height = RewardedSignatures::new(vec![cite(1, 10, &validators)]);
+        assert_eq!(
+            wrong_height.validate(&window, threshold),
+            Err(InvalidRewardedSignatures::UnknownBlock {
+                block_hash,
+                block_height: 10
+            })
+        );
+
+        let outside_window = RewardedSignatures::new(vec![cite(1, 12, &validators)]);
+        assert_eq!(
+            outside_window.validate(&window, threshold),
+            Err(InvalidRewardedSignatures::NotStored {
+                block_hash,
+                block_height: 12
+            })
+        );
+
+        let missing_signature = RewardedSignatures::new(vec![cite(0, 10, &validators)]);
+        assert_eq!(
+            missing_signature.validate(&window, threshold),
+            Err(InvalidRewardedSignatures::MissingSignature {
+                block_hash: *window[0].block.hash(),
+                signer: validators[2]
+            })
+        );
+
+        assert_eq!(
+            valid.validate(&window, Ratio::new(3, 4)),
+            Err(InvalidRewardedSignatures::BelowThreshold {
+                block_hash: *window[0].block.hash()
+            })
+        );
+
+        let not_a_validator = RewardedSignatures::new(vec![cite(1, 11, &[outsider])]);
+        assert_eq!(
+            not_a_validator.validate(&window, threshold),
+            Err(InvalidRewardedSignatures::NotAValidator {
+                block_hash,
+                signer: outsider
+            })
+        );
+
+        let cited_twice = RewardedSignatures::new(vec![
+            cite(1, 11, &validators[..2]),
+            cite(1, 11, &validators[1..]),
+        ]);
+        assert_eq!(
+            cited_twice.validate(&window, threshold),
+            Err(InvalidRewardedSignatures::CitedTwice {
+                block_hash,
+                signer: validators[1]
+            })
+        );
+
+        let mut unknown_weights = window.clone();
+        unknown_weights[1].era_validator_weights = None;
+        assert_eq!(
+            valid.validate(&unknown_weights, threshold),
+            Err(InvalidRewardedSignatures::UnknownValidators { block_hash })
+        );
+    }
+}
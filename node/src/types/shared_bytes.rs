@@ -0,0 +1,179 @@
+//! Reference-counted bytes which can be deserialized without copying them.
+//!
+//! Large opaque payloads, such as serialized items in `GetResponse`s, are decoded as part of the
+//! frame they were received in.  While a frame is being decoded via [`with_frame`], deserializing
+//! a [`SharedBytes`] which borrows from that frame yields a slice of the frame itself, sharing its
+//! allocation.  In any other case, the bytes are copied.
+
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+};
+
+use bytes::Bytes;
+use hex_fmt::HexFmt;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+thread_local! {
+    /// The frame currently being decoded on this thread, if any.
+    static CURRENT_FRAME: RefCell<Option<Bytes>> = RefCell::new(None);
+}
+
+/// Runs `decode` with `frame` registered as the frame being decoded on this thread, so that any
+/// `SharedBytes` borrowed from it share its allocation.
+pub(crate) fn with_frame<T, F: FnOnce() -> T>(frame: &Bytes, decode: F) -> T {
+    /// Unregisters the frame again when dropped, even if decoding panics.
+    struct Registration(Option<Bytes>);
+
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT_FRAME.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let previous = CURRENT_FRAME.with(|current| current.borrow_mut().replace(frame.clone()));
+    let _registration = Registration(previous);
+    decode()
+}
+
+/// Returns a slice of the frame being decoded which covers `subset`, if `subset` lies within it.
+fn slice_of_current_frame(subset: &[u8]) -> Option<Bytes> {
+    CURRENT_FRAME.with(|current| {
+        let current = current.borrow();
+        let frame = current.as_ref()?;
+        let frame_start = frame.as_ptr() as usize;
+        let subset_start = subset.as_ptr() as usize;
+        let within =
+            subset_start >= frame_start && subset_start + subset.len() <= frame_start + frame.len();
+        if within && !subset.is_empty() {
+            Some(frame.slice_ref(subset))
+        } else {
+            None
+        }
+    })
+}
+
+/// Immutable, cheaply cloneable bytes, serialized as a byte string.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SharedBytes(Bytes);
+
+impl SharedBytes {
+    /// Returns the underlying bytes.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for SharedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SharedBytes {
+    fn from(vec: Vec<u8>) -> Self {
+        SharedBytes(Bytes::from(vec))
+    }
+}
+
+impl From<Bytes> for SharedBytes {
+    fn from(bytes: Bytes) -> Self {
+        SharedBytes(bytes)
+    }
+}
+
+impl Debug for SharedBytes {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "SharedBytes({:10})", HexFmt(&self.0))
+    }
+}
+
+impl Serialize for SharedBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SharedBytesVisitor;
+
+        impl<'de> Visitor<'de> for SharedBytesVisitor {
+            type Value = SharedBytes;
+
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a byte string")
+            }
+
+            fn visit_borrowed_bytes<E: de::Error>(
+                self,
+                bytes: &'de [u8],
+            ) -> Result<Self::Value, E> {
+                Ok(SharedBytes(
+                    slice_of_current_frame(bytes).unwrap_or_else(|| Bytes::copy_from_slice(bytes)),
+                ))
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                Ok(SharedBytes(Bytes::copy_from_slice(bytes)))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(SharedBytes(Bytes::from(bytes)))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(SharedBytes(Bytes::from(bytes)))
+            }
+        }
+
+        deserializer.deserialize_bytes(SharedBytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_share_frame_when_decoding_it() {
+        let payload = SharedBytes::from(vec![7u8; 1024]);
+        let frame = Bytes::from(rmp_serde::to_vec(&(1u8, payload.clone())).unwrap());
+
+        let (_, decoded): (u8, SharedBytes) =
+            with_frame(&frame, || rmp_serde::from_read_ref(&frame[..]).unwrap());
+        assert_eq!(decoded, payload);
+        let frame_range = frame.as_ptr() as usize..frame.as_ptr() as usize + frame.len();
+        assert!(frame_range.contains(&(decoded.as_ptr() as usize)));
+
+        // Outside of `with_frame`, the bytes are copied.
+        let (_, copied): (u8, SharedBytes) = rmp_serde::from_read_ref(&frame[..]).unwrap();
+        assert_eq!(copied, payload);
+        assert!(!frame_range.contains(&(copied.as_ptr() as usize)));
+    }
+
+    #[test]
+    fn should_roundtrip_via_bincode() {
+        let payload = SharedBytes::from(vec![1, 2, 3]);
+        let serialized = bincode::serialize(&payload).unwrap();
+        let deserialized: SharedBytes = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, payload);
+    }
+}
@@ -7,21 +7,25 @@ use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
 };
 
+use num::rational::Ratio;
 use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
-use casper_types::PublicKey;
+use casper_types::{PublicKey, U512};
 
 use crate::{
     components::{
         chainspec_loader::NextUpgrade,
         consensus::EraId,
+        event_stream_server::SseEventType,
         rpc_server::rpcs::docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
+        small_network::{PublicEndpoint, ReachabilityCheck},
     },
     crypto::{hash::Digest, AsymmetricKeyExt},
     types::{ActivationPoint, Block, BlockHash, NodeId, PeersMap, TimeDiff, Timestamp},
+    utils::ScheduledJobStatus,
 };
 
 static CHAINSPEC_INFO: Lazy<ChainspecInfo> = Lazy::new(|| {
@@ -38,12 +42,44 @@ static GET_STATUS_RESULT: Lazy<GetStatusResult> = Lazy::new(|| {
     let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 54321);
     let mut peers = BTreeMap::new();
     peers.insert(node_id.clone(), socket_addr.to_string());
+    let public_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)), 34553);
     let status_feed = StatusFeed::<NodeId> {
         last_added_block: Some(Block::doc_example().clone()),
         peers,
+        public_endpoint: Some(PublicEndpoint {
+            address: public_address,
+            port_mapping: Some(public_address),
+            last_check: Some(ReachabilityCheck {
+                timestamp: Timestamp::from(1_605_573_540_000),
+                observed_ip: public_address.ip(),
+                reachable: true,
+            }),
+        }),
         chainspec_info: ChainspecInfo::doc_example().clone(),
         our_public_signing_key: *PublicKey::doc_example(),
         round_length: Some(TimeDiff::from(1 << 16)),
+        sync_status: SyncStatus {
+            state: SyncState::Participating,
+            blocks_behind: 0,
+            since: Timestamp::from(1_605_573_500_000),
+            transitions: 2,
+        },
+        blocks_below_strict_finality: vec![BlockFinalityProgress {
+            block_hash: *Block::doc_example().hash(),
+            height: Block::doc_example().height(),
+            era_id: Block::doc_example().header().era_id(),
+            signed_weight: U512::from(456),
+            total_weight: U512::from(1368),
+        }],
+        scheduled_jobs: vec![ScheduledJobStatus {
+            name: String::from("block_proposer_prune"),
+            period: TimeDiff::from(10_000),
+            jitter: TimeDiff::from(1_000),
+            runs: 42,
+            last_run: Some(Timestamp::from(1_605_573_560_000)),
+            next_run: Timestamp::from(1_605_573_570_500),
+        }],
+        event_stream_events: vec![SseEventType::BlockAdded, SseEventType::DeployProcessed],
         version: crate::VERSION_STRING.as_str(),
     };
     GetStatusResult::new(status_feed, DOCS_EXAMPLE_PROTOCOL_VERSION.clone())
@@ -81,6 +117,91 @@ impl ChainspecInfo {
     }
 }
 
+/// The progress of collecting finality signatures for a block.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockFinalityProgress {
+    /// The block hash.
+    pub block_hash: BlockHash,
+    /// The block height.
+    pub height: u64,
+    /// The era in which the block was created.
+    pub era_id: EraId,
+    /// The combined weight of the validators whose finality signatures for the block are known.
+    pub signed_weight: U512,
+    /// The total weight of the validators of the block's era.
+    pub total_weight: U512,
+}
+
+impl BlockFinalityProgress {
+    /// Returns whether the block has strict finality, i.e. is signed by validators with more than
+    /// `(1 + ftt) / 2` of the era's total weight, where `ftt` is the finality threshold fraction.
+    pub fn is_strictly_final(&self, finality_threshold_fraction: Ratio<u64>) -> bool {
+        let numer = U512::from(*finality_threshold_fraction.numer());
+        let denom = U512::from(*finality_threshold_fraction.denom());
+        self.signed_weight * denom * U512::from(2) > self.total_weight * (denom + numer)
+    }
+
+    /// Returns whether the block has weak finality, i.e. is signed by validators with more than
+    /// the finality threshold fraction of the era's total weight.
+    pub fn is_weakly_final(&self, finality_threshold_fraction: Ratio<u64>) -> bool {
+        let numer = U512::from(*finality_threshold_fraction.numer());
+        let denom = U512::from(*finality_threshold_fraction.denom());
+        self.signed_weight * denom > self.total_weight * numer
+    }
+
+    /// Returns the finality status the signatures collected so far amount to, given the
+    /// finality threshold fraction of the chainspec.
+    pub fn finality_status(&self, finality_threshold_fraction: Ratio<u64>) -> FinalityStatus {
+        if self.is_strictly_final(finality_threshold_fraction) {
+            FinalityStatus::Strict
+        } else if self.is_weakly_final(finality_threshold_fraction) {
+            FinalityStatus::Weak
+        } else {
+            FinalityStatus::None
+        }
+    }
+}
+
+/// The degree of finality of a block, according to the finality signatures known for it.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityStatus {
+    /// Signed by validators with at most the finality threshold fraction `ftt` of the era's total
+    /// weight.
+    None,
+    /// Signed by validators with more than `ftt`, but at most `(1 + ftt) / 2` of the era's total
+    /// weight.
+    Weak,
+    /// Signed by validators with more than `(1 + ftt) / 2` of the era's total weight.
+    Strict,
+}
+
+/// Whether a validator is taking part in consensus as usual or catching up with finalization.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// Execution is keeping up with finalization, and the node proposes blocks when due.
+    Participating,
+    /// Execution fell too far behind finalization, so the node doesn't propose blocks until it has
+    /// caught up.
+    CatchingUp,
+}
+
+/// The catch-up status of a validator.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SyncStatus {
+    /// The current state.
+    pub state: SyncState,
+    /// The number of finalized blocks awaiting execution.
+    pub blocks_behind: u64,
+    /// When the node entered the current state.
+    pub since: Timestamp,
+    /// The number of times the state changed since the node started.
+    pub transitions: u64,
+}
+
 /// Data feed for client "info_get_status" endpoint.
 #[derive(Debug, Serialize)]
 #[serde(bound = "I: Eq + Hash + Ord + Serialize")]
@@ -89,12 +210,22 @@ pub struct StatusFeed<I> {
     pub last_added_block: Option<Block>,
     /// The peer nodes which are connected to this node.
     pub peers: BTreeMap<I, String>,
+    /// Our public network endpoint, if tracked by the network component.
+    pub public_endpoint: Option<PublicEndpoint>,
     /// The chainspec info for this node.
     pub chainspec_info: ChainspecInfo,
     /// Our public signing key.
     pub our_public_signing_key: PublicKey,
     /// The next round length if this node is a validator.
     pub round_length: Option<TimeDiff>,
+    /// Whether the node is catching up with finalization.
+    pub sync_status: SyncStatus,
+    /// The recent blocks which don't have strict finality yet.
+    pub blocks_below_strict_finality: Vec<BlockFinalityProgress>,
+    /// The periodic jobs of the node's components.
+    pub scheduled_jobs: Vec<ScheduledJobStatus>,
+    /// The types of events sent on the event stream.
+    pub event_stream_events: Vec<SseEventType>,
     /// The compiled node version.
     pub version: &'static str,
 }
@@ -102,16 +233,28 @@ pub struct StatusFeed<I> {
 impl<I> StatusFeed<I> {
     pub(crate) fn new(
         last_added_block: Option<Block>,
-        peers: BTreeMap<I, String>,
+        (peers, public_endpoint): (BTreeMap<I, String>, Option<PublicEndpoint>),
         chainspec_info: ChainspecInfo,
-        (our_public_signing_key, round_length): (PublicKey, Option<TimeDiff>),
+        (our_public_signing_key, round_length, sync_status): (
+            PublicKey,
+            Option<TimeDiff>,
+            SyncStatus,
+        ),
+        blocks_below_strict_finality: Vec<BlockFinalityProgress>,
+        scheduled_jobs: Vec<ScheduledJobStatus>,
+        event_stream_events: Vec<SseEventType>,
     ) -> Self {
         StatusFeed {
             last_added_block,
             peers,
+            public_endpoint,
             chainspec_info,
             our_public_signing_key,
             round_length,
+            sync_status,
+            blocks_below_strict_finality,
+            scheduled_jobs,
+            event_stream_events,
             version: crate::VERSION_STRING.as_str(),
         }
     }
@@ -155,12 +298,22 @@ pub struct GetStatusResult {
     pub starting_state_root_hash: String,
     /// The node ID and network address of each connected peer.
     pub peers: PeersMap,
+    /// Our public network endpoint, with the outcome of the latest check of its reachability.
+    pub public_endpoint: Option<PublicEndpoint>,
     /// The minimal info of the last block from the linear chain.
     pub last_added_block_info: Option<MinimalBlockInfo>,
     /// Our public signing key.
     pub our_public_signing_key: PublicKey,
     /// The next round length if this node is a validator.
     pub round_length: Option<TimeDiff>,
+    /// Whether the node is catching up with finalization, and since when.
+    pub sync_status: SyncStatus,
+    /// The recent blocks which don't have strict finality yet.
+    pub blocks_below_strict_finality: Vec<BlockFinalityProgress>,
+    /// The periodic jobs of the node's components, with when they last ran and are next due.
+    pub scheduled_jobs: Vec<ScheduledJobStatus>,
+    /// The types of events sent on the event stream, empty if the node doesn't run one.
+    pub event_stream_events: Vec<SseEventType>,
     /// Information about the next scheduled upgrade.
     pub next_upgrade: Option<NextUpgrade>,
     /// The compiled node version.
@@ -177,9 +330,14 @@ impl GetStatusResult {
                 .starting_state_root_hash
                 .to_string(),
             peers: PeersMap::from(status_feed.peers),
+            public_endpoint: status_feed.public_endpoint,
             last_added_block_info: status_feed.last_added_block.map(Into::into),
             our_public_signing_key: status_feed.our_public_signing_key,
             round_length: status_feed.round_length,
+            sync_status: status_feed.sync_status,
+            blocks_below_strict_finality: status_feed.blocks_below_strict_finality,
+            scheduled_jobs: status_feed.scheduled_jobs,
+            event_stream_events: status_feed.event_stream_events,
             next_upgrade: status_feed.chainspec_info.next_upgrade,
             build_version: crate::VERSION_STRING.clone(),
         }
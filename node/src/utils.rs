@@ -1,13 +1,17 @@
 //! Various functions that are not limited to a particular module, but are too small to warrant
 //! being factored out into standalone crates.
 
+mod config_validation;
 mod counting_channel;
 pub mod ds;
 mod external;
 mod median;
 pub mod milliseconds;
+pub(crate) mod rate_limiter;
 pub(crate) mod rlimit;
 mod round_robin;
+pub(crate) mod scheduler;
+mod sync_progress;
 
 use std::{
     cell::RefCell,
@@ -26,12 +30,18 @@ use serde::Serialize;
 use thiserror::Error;
 use tracing::warn;
 
+pub(crate) use config_validation::{ConfigSection, ConfigValidator};
+pub use config_validation::{ConfigValidationError, ConfigValidationReport};
 pub(crate) use counting_channel::{counting_unbounded_channel, CountingReceiver, CountingSender};
 #[cfg(test)]
 pub use external::RESOURCES_PATH;
 pub use external::{External, LoadError, Loadable};
 pub(crate) use median::weighted_median;
+pub use rate_limiter::RateLimitConfig;
 pub(crate) use round_robin::WeightedRoundRobin;
+pub use scheduler::ScheduledJobStatus;
+pub(crate) use scheduler::{Job, Scheduler};
+pub(crate) use sync_progress::{SyncProgress, SyncSnapshot};
 
 /// Sensible default for many if not all systems.
 const DEFAULT_PAGE_SIZE: usize = 4096;
@@ -109,6 +119,10 @@ pub enum ListeningError {
         /// The failure reason.
         error: hyper::Error,
     },
+
+    /// Failed to register metrics.
+    #[error("failed to register metrics: {0}")]
+    Metrics(#[from] prometheus::Error),
 }
 
 pub(crate) fn start_listening(address: &str) -> Result<Builder<AddrIncoming>, ListeningError> {
@@ -0,0 +1,157 @@
+//! Validation of configuration values beyond what deserialization checks.
+//!
+//! Each component validates its own section of the configuration through a [`ConfigSection`],
+//! recording every offending field rather than stopping at the first one.  The errors of all
+//! sections are collected into a single [`ConfigValidationReport`], so that an operator can fix
+//! all of them in one go.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::RangeInclusive,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// An invalid value of a single configuration field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ConfigValidationError {
+    /// The name of the component whose section the field belongs to.
+    pub component: &'static str,
+    /// The path of the field within the component's section, e.g. `query_batch.max_keys`.
+    pub field: String,
+    /// The offending value.
+    pub value: String,
+    /// A description of the valid values.
+    pub expected: String,
+}
+
+impl Display for ConfigValidationError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}.{}: found {}, expected {}",
+            self.component, self.field, self.value, self.expected
+        )
+    }
+}
+
+/// All invalid values found in a configuration.
+#[derive(Clone, Debug, Error, Serialize)]
+pub struct ConfigValidationReport {
+    /// The errors, in the order the sections were validated in.
+    pub errors: Vec<ConfigValidationError>,
+}
+
+impl Display for ConfigValidationReport {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "configuration has {} invalid value(s):",
+            self.errors.len()
+        )?;
+        for error in &self.errors {
+            write!(formatter, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the errors of all configuration sections.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigValidator {
+    errors: Vec<ConfigValidationError>,
+}
+
+impl ConfigValidator {
+    /// Returns a handle recording errors for the given component's section.
+    pub(crate) fn section(&mut self, component: &'static str) -> ConfigSection<'_> {
+        ConfigSection {
+            component,
+            errors: &mut self.errors,
+        }
+    }
+
+    /// Returns the report of all errors found, if any.
+    pub(crate) fn finish(self) -> Result<(), ConfigValidationReport> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigValidationReport {
+                errors: self.errors,
+            })
+        }
+    }
+}
+
+/// Records errors for the fields of a single component's section.
+#[derive(Debug)]
+pub(crate) struct ConfigSection<'a> {
+    component: &'static str,
+    errors: &'a mut Vec<ConfigValidationError>,
+}
+
+impl<'a> ConfigSection<'a> {
+    /// Records an error for `field` unless `is_valid` holds.
+    pub(crate) fn check(
+        &mut self,
+        field: &str,
+        is_valid: bool,
+        value: impl Display,
+        expected: impl Display,
+    ) {
+        if !is_valid {
+            self.errors.push(ConfigValidationError {
+                component: self.component,
+                field: field.to_string(),
+                value: value.to_string(),
+                expected: expected.to_string(),
+            });
+        }
+    }
+
+    /// Records an error for `field` unless `value` lies within `range`.
+    pub(crate) fn in_range<T: PartialOrd + Display>(
+        &mut self,
+        field: &str,
+        value: T,
+        range: RangeInclusive<T>,
+    ) {
+        let is_valid = range.contains(&value);
+        let expected = format!("a value from {} to {}", range.start(), range.end());
+        self.check(field, is_valid, value, expected);
+    }
+
+    /// Records an error for `field` unless `value` is at least `min`.
+    pub(crate) fn at_least<T: PartialOrd + Display>(&mut self, field: &str, value: T, min: T) {
+        let is_valid = value >= min;
+        let expected = format!("at least {}", min);
+        self.check(field, is_valid, value, expected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_aggregate_errors_of_all_sections() {
+        let mut validator = ConfigValidator::default();
+        validator.section("fetcher").at_least("timeout", 0, 1);
+        let mut section = validator.section("block_proposer");
+        section.in_range("fairness", 0.5, 0.0..=1.0);
+        section.in_range("fairness", 1.5, 0.0..=1.0);
+
+        let report = validator.finish().unwrap_err();
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(report.errors[0].component, "fetcher");
+        assert_eq!(report.errors[1].field, "fairness");
+        assert_eq!(report.errors[1].expected, "a value from 0 to 1");
+
+        let printed = report.to_string();
+        assert!(printed.contains("2 invalid value(s)"));
+        assert!(printed.contains("fetcher.timeout: found 0, expected at least 1"));
+
+        assert!(ConfigValidator::default().finish().is_ok());
+    }
+}
@@ -0,0 +1,287 @@
+//! Token bucket rate limiting for the HTTP servers.
+//!
+//! A [`RateLimiter`] enforces a limit on the combined rate of requests from all clients as well as
+//! on the rate of requests from any single IP address.  Each limit is a token bucket holding up to
+//! one second's worth of requests, so short bursts are tolerated.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use datasize::DataSize;
+use prometheus::{IntCounter, Registry};
+use serde::{Deserialize, Serialize};
+use warp::{Filter, Rejection};
+
+use crate::unregister_metric;
+
+/// The number of client IP addresses tracked above which the buckets of idle clients are dropped.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Rate limits applied to a group of endpoints.
+///
+/// A limit of zero disables it.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Max rate of requests from all clients combined, in qps.
+    pub qps_limit: u64,
+
+    /// Max rate of requests from a single IP address, in qps.
+    pub per_ip_qps_limit: u64,
+}
+
+impl RateLimitConfig {
+    /// Creates a new rate limit config.
+    pub const fn new(qps_limit: u64, per_ip_qps_limit: u64) -> Self {
+        RateLimitConfig {
+            qps_limit,
+            per_ip_qps_limit,
+        }
+    }
+}
+
+/// The IP address of the client which sent a request.
+///
+/// The HTTP servers insert this into the extensions of every request they receive.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ClientIp(pub(crate) IpAddr);
+
+/// A token bucket refilled at `rate` tokens per second, holding at most `rate` tokens.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        TokenBucket {
+            tokens: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, rate: u64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate as f64).min(rate as f64);
+        self.last_refill = now;
+    }
+
+    fn is_full(&self, rate: u64) -> bool {
+        self.tokens >= rate as f64
+    }
+
+    fn has_token(&self) -> bool {
+        self.tokens >= 1.0
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    /// The limits, kept alongside the buckets so they can be updated while the limiter is in use.
+    config: RateLimitConfig,
+    global: TokenBucket,
+    per_ip: HashMap<IpAddr, TokenBucket>,
+}
+
+/// Rate limiter for a group of endpoints.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    buckets: Mutex<Buckets>,
+    /// Number of requests rejected for exceeding the rate limits.
+    rate_limited: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter, registering its metrics under the given `name`.
+    pub(crate) fn new(
+        name: &str,
+        config: RateLimitConfig,
+        registry: &Registry,
+    ) -> Result<Self, prometheus::Error> {
+        let rate_limited = IntCounter::new(
+            format!("{}_rate_limited_requests", name),
+            format!(
+                "number of {} requests rejected for exceeding the rate limits",
+                name
+            ),
+        )?;
+        registry.register(Box::new(rate_limited.clone()))?;
+
+        Ok(RateLimiter {
+            buckets: Mutex::new(Buckets {
+                config,
+                global: TokenBucket::new(config.qps_limit, Instant::now()),
+                per_ip: HashMap::new(),
+            }),
+            rate_limited,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Returns whether a request from `client_ip` is within the rate limits.
+    ///
+    /// If it is, the request is counted against the limits.
+    pub(crate) fn check(&self, client_ip: IpAddr) -> bool {
+        self.check_at(client_ip, Instant::now())
+    }
+
+    /// Replaces the limits, taking effect from the next request checked.
+    ///
+    /// Tokens already accumulated beyond a lowered limit are discarded on the next refill.
+    pub(crate) fn update_config(&self, config: RateLimitConfig) {
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        buckets.config = config;
+    }
+
+    fn check_at(&self, client_ip: IpAddr, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        let RateLimitConfig {
+            qps_limit,
+            per_ip_qps_limit,
+        } = buckets.config;
+
+        if qps_limit > 0 {
+            buckets.global.refill(qps_limit, now);
+            if !buckets.global.has_token() {
+                self.rate_limited.inc();
+                return false;
+            }
+        }
+
+        if per_ip_qps_limit > 0 {
+            if buckets.per_ip.len() >= MAX_TRACKED_CLIENTS
+                && !buckets.per_ip.contains_key(&client_ip)
+            {
+                // Forget clients whose buckets have refilled completely, as a fresh bucket would
+                // be identical.
+                buckets.per_ip.retain(|_, bucket| {
+                    bucket.refill(per_ip_qps_limit, now);
+                    !bucket.is_full(per_ip_qps_limit)
+                });
+            }
+            let bucket = buckets
+                .per_ip
+                .entry(client_ip)
+                .or_insert_with(|| TokenBucket::new(per_ip_qps_limit, now));
+            bucket.refill(per_ip_qps_limit, now);
+            if !bucket.has_token() {
+                self.rate_limited.inc();
+                return false;
+            }
+            bucket.tokens -= 1.0;
+        }
+
+        if qps_limit > 0 {
+            buckets.global.tokens -= 1.0;
+        }
+        true
+    }
+}
+
+impl Drop for RateLimiter {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.rate_limited);
+    }
+}
+
+/// Returns a filter which only matches requests exceeding the rate limits of `limiter`.
+///
+/// Requests within the limits are rejected as not found, so that they fall through to the filters
+/// handling them.
+pub(crate) fn exceeded(
+    limiter: Arc<RateLimiter>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::ext::get::<ClientIp>()
+        .and_then(move |ClientIp(client_ip)| {
+            let within_limits = limiter.check(client_ip);
+            async move {
+                if within_limits {
+                    Err(warp::reject::not_found())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .untuple_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, time::Duration};
+
+    use super::*;
+
+    const CLIENT_1: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const CLIENT_2: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[test]
+    fn should_limit_each_client_separately() {
+        let limiter =
+            RateLimiter::new("test", RateLimitConfig::new(0, 2), &Registry::new()).unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(CLIENT_1, now));
+        assert!(limiter.check_at(CLIENT_1, now));
+        assert!(!limiter.check_at(CLIENT_1, now));
+        assert!(limiter.check_at(CLIENT_2, now));
+
+        // Half a second later, one more request from the first client is allowed.
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.check_at(CLIENT_1, later));
+        assert!(!limiter.check_at(CLIENT_1, later));
+        assert_eq!(limiter.rate_limited.get(), 2);
+    }
+
+    #[test]
+    fn should_limit_all_clients_combined() {
+        let limiter =
+            RateLimiter::new("test", RateLimitConfig::new(3, 2), &Registry::new()).unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(CLIENT_1, now));
+        assert!(limiter.check_at(CLIENT_1, now));
+        // Rejected by the per-IP limit, which must not use up the global budget.
+        assert!(!limiter.check_at(CLIENT_1, now));
+        assert!(limiter.check_at(CLIENT_2, now));
+        assert!(!limiter.check_at(CLIENT_2, now));
+        assert_eq!(limiter.rate_limited.get(), 2);
+    }
+
+    #[test]
+    fn should_apply_updated_limits() {
+        let limiter =
+            RateLimiter::new("test", RateLimitConfig::new(0, 1), &Registry::new()).unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(CLIENT_1, now));
+        assert!(!limiter.check_at(CLIENT_1, now));
+
+        // Raising the limit lets the bucket refill to the new size.
+        limiter.update_config(RateLimitConfig::new(0, 3));
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check_at(CLIENT_1, later));
+        assert!(limiter.check_at(CLIENT_1, later));
+        assert!(limiter.check_at(CLIENT_1, later));
+        assert!(!limiter.check_at(CLIENT_1, later));
+
+        // Disabling the limit stops requests being rejected.
+        limiter.update_config(RateLimitConfig::new(0, 0));
+        assert!((0..1_000).all(|_| limiter.check_at(CLIENT_1, later)));
+    }
+
+    #[test]
+    fn should_not_limit_if_disabled() {
+        let limiter =
+            RateLimiter::new("test", RateLimitConfig::new(0, 0), &Registry::new()).unwrap();
+        let now = Instant::now();
+        assert!((0..1_000).all(|_| limiter.check_at(CLIENT_1, now)));
+    }
+}
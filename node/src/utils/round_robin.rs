@@ -3,6 +3,10 @@
 //! This module implements a weighted round-robin scheduler that ensures no deadlocks occur, but
 //! still allows prioritizing events from one source over another. The module uses `tokio`'s
 //! synchronization primitives under the hood.
+//!
+//! Queues are unbounded, but items may optionally be pushed along with their approximate size in
+//! bytes. Once the combined size exceeds a memory budget, items marked as sheddable are dropped,
+//! starting with the lowest-priority queue.
 
 use std::{
     collections::{HashMap, VecDeque},
@@ -41,13 +45,35 @@ pub struct WeightedRoundRobin<I, K> {
 
     /// Number of items in all queues combined.
     total: Semaphore,
+
+    /// Approximate size in bytes of all items in all queues combined.
+    queued_bytes: AtomicUsize,
+
+    /// Size in bytes of queued items above which sheddable items are dropped.
+    memory_budget: AtomicUsize,
+
+    /// Number of items dropped so far due to the memory budget being exceeded.
+    shed_count: AtomicUsize,
+}
+
+/// An item in a queue, along with its approximate size.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+struct QueuedItem<I> {
+    item: I,
+    /// Approximate size of the item in bytes, zero if unknown.
+    #[serde(skip)]
+    size: usize,
+    /// Whether the item may be dropped if the memory budget is exceeded.
+    #[serde(skip)]
+    sheddable: bool,
 }
 
 /// State that wraps queue and its event count.
 #[derive(Debug)]
 struct QueueState<I> {
     event_count: AtomicUsize,
-    queue: Mutex<VecDeque<I>>,
+    queue: Mutex<VecDeque<QueuedItem<I>>>,
 }
 
 impl<I> QueueState<I> {
@@ -59,7 +85,7 @@ impl<I> QueueState<I> {
     }
 
     #[inline]
-    async fn push_back(&self, element: I) {
+    async fn push_back(&self, element: QueuedItem<I>) {
         self.queue.lock().await.push_back(element);
         self.event_count.fetch_add(1, Ordering::SeqCst);
     }
@@ -170,7 +196,7 @@ where
             let queue = &*guard;
             writer.write_all(format!("Queue: {:?} ({}) [\n", kind, queue.len()).as_bytes())?;
             for event in queue.iter() {
-                writer.write_all(format!("\t{:?}\n", event).as_bytes())?;
+                writer.write_all(format!("\t{:?}\n", event.item).as_bytes())?;
             }
             writer.write_all(b"]\n")?;
         }
@@ -210,20 +236,42 @@ where
             slots,
             queues,
             total: Semaphore::new(0),
+            queued_bytes: AtomicUsize::new(0),
+            memory_budget: AtomicUsize::new(usize::MAX),
+            shed_count: AtomicUsize::new(0),
         }
     }
 
+    /// Sets the combined size in bytes of queued items above which sheddable items are dropped.
+    pub(crate) fn set_memory_budget(&self, memory_budget: usize) {
+        self.memory_budget.store(memory_budget, Ordering::SeqCst);
+    }
+
     /// Pushes an item to a queue identified by key.
     ///
     /// ## Panics
     ///
     /// Panics if the queue identified by key `queue` does not exist.
     pub(crate) async fn push(&self, item: I, queue: K) {
+        self.push_queued_item(
+            QueuedItem {
+                item,
+                size: 0,
+                sheddable: false,
+            },
+            queue,
+        )
+        .await
+    }
+
+    async fn push_queued_item(&self, queued_item: QueuedItem<I>, queue: K) {
+        let size = queued_item.size;
         self.queues
             .get(&queue)
             .expect("tried to push to non-existent queue")
-            .push_back(item)
+            .push_back(queued_item)
             .await;
+        self.queued_bytes.fetch_add(size, Ordering::SeqCst);
 
         // We increase the item count after we've put the item into the queue.
         self.total.add_permits(1);
@@ -262,7 +310,8 @@ where
                 // We hold the queue's lock and checked `is_empty` earlier.
                 .expect("item disappeared. this should not happen");
             queue_state.dec_count();
-            break (item, inner.active_slot.key);
+            self.queued_bytes.fetch_sub(item.size, Ordering::SeqCst);
+            break (item.item, inner.active_slot.key);
         }
     }
 
@@ -279,6 +328,106 @@ where
             .map(|(key, queue)| (*key, queue.event_count()))
             .collect()
     }
+
+    /// Returns the approximate combined size in bytes of all queued items.
+    pub(crate) fn queued_bytes(&self) -> usize {
+        self.queued_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of items dropped so far due to the memory budget being exceeded.
+    pub(crate) fn shed_count(&self) -> usize {
+        self.shed_count.load(Ordering::SeqCst)
+    }
+}
+
+impl<I, K> WeightedRoundRobin<I, K>
+where
+    K: Copy + Clone + Eq + Hash + Ord,
+{
+    /// Pushes an item of approximately `size` bytes to a queue identified by key.
+    ///
+    /// If the item would push the combined size of all queued items above the memory budget,
+    /// queued sheddable items are dropped first, oldest first and starting with the lowest queue
+    /// according to `K`'s ordering. If that does not free up enough space and the item itself is
+    /// sheddable, it is dropped instead of being queued.
+    ///
+    /// Returns the number of items dropped, including the pushed item.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the queue identified by key `queue` does not exist.
+    pub(crate) async fn push_sized(
+        &self,
+        item: I,
+        queue: K,
+        size: usize,
+        sheddable: bool,
+    ) -> usize {
+        let memory_budget = self.memory_budget.load(Ordering::SeqCst);
+        let mut shed = 0;
+
+        if self.queued_bytes().saturating_add(size) > memory_budget {
+            shed += self.shed(memory_budget.saturating_sub(size)).await;
+
+            if sheddable && self.queued_bytes().saturating_add(size) > memory_budget {
+                self.shed_count.fetch_add(1, Ordering::SeqCst);
+                return shed + 1;
+            }
+        }
+
+        self.push_queued_item(
+            QueuedItem {
+                item,
+                size,
+                sheddable,
+            },
+            queue,
+        )
+        .await;
+        shed
+    }
+
+    /// Drops sheddable items until the combined size of all queued items is at most `target`.
+    ///
+    /// Returns the number of items dropped.
+    async fn shed(&self, target: usize) -> usize {
+        let mut keys: Vec<K> = self.queues.keys().copied().collect();
+        keys.sort();
+
+        let mut shed = 0;
+        'queues: for key in keys {
+            let queue_state = &self.queues[&key];
+            let mut queue = queue_state.queue.lock().await;
+
+            let mut idx = 0;
+            while idx < queue.len() {
+                if self.queued_bytes() <= target {
+                    break 'queues;
+                }
+                if !queue[idx].sheddable {
+                    idx += 1;
+                    continue;
+                }
+
+                // Claim the item first, so that a concurrent `pop` does not rely on it still being
+                // there. If no permit is left, all queued items are about to be popped anyway.
+                match self.total.try_acquire() {
+                    Ok(permit) => permit.forget(),
+                    Err(_) => break 'queues,
+                }
+                let removed = queue
+                    .remove(idx)
+                    // We hold the queue's lock and checked the length before.
+                    .expect("item disappeared. this should not happen");
+                queue_state.dec_count();
+                self.queued_bytes.fetch_sub(removed.size, Ordering::SeqCst);
+                shed += 1;
+            }
+        }
+
+        self.shed_count.fetch_add(shed, Ordering::SeqCst);
+        shed
+    }
 }
 
 #[cfg(test)]
@@ -290,7 +439,7 @@ mod tests {
     use super::*;
 
     #[repr(usize)]
-    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
     enum QueueKind {
         One = 1,
         Two,
@@ -327,4 +476,31 @@ mod tests {
         assert_eq!(('f', QueueKind::Two), scheduler.pop().await);
         assert_eq!(('c', QueueKind::One), scheduler.pop().await);
     }
+
+    #[tokio::test]
+    async fn should_shed_sheddable_items_from_lowest_queue_first() {
+        let scheduler = WeightedRoundRobin::<char, QueueKind>::new(weights());
+        scheduler.set_memory_budget(10);
+
+        assert_eq!(scheduler.push_sized('a', QueueKind::Two, 4, true).await, 0);
+        assert_eq!(scheduler.push_sized('b', QueueKind::One, 4, false).await, 0);
+        assert_eq!(scheduler.push_sized('c', QueueKind::One, 2, true).await, 0);
+        assert_eq!(scheduler.queued_bytes(), 10);
+
+        // Sheds 'c' first since it is in the lower queue, then 'a'.
+        assert_eq!(scheduler.push_sized('d', QueueKind::Two, 6, false).await, 2);
+        assert_eq!(scheduler.queued_bytes(), 10);
+        assert_eq!(scheduler.item_count(), 2);
+
+        // Nothing sheddable is left, so a sheddable item is dropped itself.
+        assert_eq!(scheduler.push_sized('e', QueueKind::Two, 1, true).await, 1);
+        // Unsized items are never shed.
+        scheduler.push('f', QueueKind::One).await;
+        assert_eq!(scheduler.shed_count(), 3);
+
+        assert_eq!(('b', QueueKind::One), scheduler.pop().await);
+        assert_eq!(('d', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('f', QueueKind::One), scheduler.pop().await);
+        assert_eq!(scheduler.queued_bytes(), 0);
+    }
 }
@@ -0,0 +1,211 @@
+//! Scheduling of periodic component jobs.
+//!
+//! Components register each of their periodic jobs with a [`Scheduler`] shared by the reactor,
+//! which delays the job's event by the job's period plus a random jitter.  The scheduler keeps
+//! track of when every job last ran and when it is next due, so that a periodic task which stopped
+//! running can be spotted in the node's status.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rand::{Rng, RngCore, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    effect::{EffectBuilder, EffectExt, Effects},
+    types::{TimeDiff, Timestamp},
+    NodeRng,
+};
+
+/// A periodic job of a component.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Job {
+    /// The name under which the job is listed, unique within the reactor.
+    name: &'static str,
+    /// The time between two runs.
+    period: Duration,
+    /// The maximum random delay added to the period, to avoid runs of different jobs or nodes
+    /// coinciding.
+    jitter: Duration,
+}
+
+impl Job {
+    /// Creates a job which runs every `period`, without jitter.
+    pub(crate) const fn new(name: &'static str, period: Duration) -> Self {
+        Job {
+            name,
+            period,
+            jitter: Duration::from_secs(0),
+        }
+    }
+
+    /// Returns the job with a random delay of up to `jitter` added to every period.
+    pub(crate) const fn with_jitter(self, jitter: Duration) -> Self {
+        Job { jitter, ..self }
+    }
+}
+
+/// The status of a scheduled job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledJobStatus {
+    /// The name of the job.
+    pub name: String,
+    /// The time between two runs.
+    pub period: TimeDiff,
+    /// The maximum random delay added to the period.
+    pub jitter: TimeDiff,
+    /// The number of times the job ran.
+    pub runs: u64,
+    /// When the job last ran, if it did.
+    pub last_run: Option<Timestamp>,
+    /// When the job is next due to run.
+    pub next_run: Timestamp,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// The status of every job scheduled so far, by name.
+    jobs: BTreeMap<&'static str, ScheduledJobStatus>,
+    /// The source of the jitter.
+    rng: NodeRng,
+}
+
+impl Inner {
+    /// Records that `job` is scheduled to run again, and returns the delay until it is due.
+    fn schedule(&mut self, job: Job, now: Timestamp) -> Duration {
+        let jitter_millis = job.jitter.as_millis() as u64;
+        let delay = if jitter_millis == 0 {
+            job.period
+        } else {
+            job.period + Duration::from_millis(self.rng.gen_range(0, jitter_millis + 1))
+        };
+        let next_run = now + TimeDiff::from(delay);
+        self.jobs
+            .entry(job.name)
+            .and_modify(|status| status.next_run = next_run)
+            .or_insert_with(|| ScheduledJobStatus {
+                name: job.name.to_string(),
+                period: job.period.into(),
+                jitter: job.jitter.into(),
+                runs: 0,
+                last_run: None,
+                next_run,
+            });
+        delay
+    }
+
+    /// Records that the job named `name` ran.
+    fn ran(&mut self, name: &'static str, now: Timestamp) {
+        if let Some(status) = self.jobs.get_mut(name) {
+            status.runs += 1;
+            status.last_run = Some(now);
+        }
+    }
+}
+
+/// A scheduler for the periodic jobs of a reactor's components.
+///
+/// Clones share the same list of jobs.
+#[derive(Clone, Debug)]
+pub(crate) struct Scheduler {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Scheduler {
+    /// Creates a new scheduler, seeding its jitter from `rng`.
+    pub(crate) fn new(rng: &mut NodeRng) -> Self {
+        let inner = Inner {
+            jobs: BTreeMap::new(),
+            rng: NodeRng::seed_from_u64(rng.next_u64()),
+        };
+        Scheduler {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    /// Schedules the next run of `job`, returning the event created by `make_event` once it is due.
+    pub(crate) fn schedule<REv, Ev, F>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        job: Job,
+        make_event: F,
+    ) -> Effects<Ev>
+    where
+        REv: Send,
+        Ev: Send + 'static,
+        F: FnOnce() -> Ev + Send + 'static,
+    {
+        let delay = self
+            .inner
+            .lock()
+            .expect("lock poisoned")
+            .schedule(job, effect_builder.now());
+        let inner = Arc::clone(&self.inner);
+        effect_builder.set_timeout(delay).event(move |_| {
+            inner
+                .lock()
+                .expect("lock poisoned")
+                .ran(job.name, effect_builder.now());
+            make_event()
+        })
+    }
+
+    /// Returns the status of all jobs scheduled so far, ordered by name.
+    pub(crate) fn jobs(&self) -> Vec<ScheduledJobStatus> {
+        self.inner
+            .lock()
+            .expect("lock poisoned")
+            .jobs
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_track_runs_of_scheduled_jobs() {
+        let mut rng = TestRng::new();
+        let scheduler = Scheduler::new(&mut rng);
+        assert!(scheduler.jobs().is_empty());
+
+        let sweep = Job::new("sweep", Duration::from_secs(60));
+        let prune =
+            Job::new("prune", Duration::from_secs(10)).with_jitter(Duration::from_millis(500));
+        let now = Timestamp::now();
+        {
+            let mut inner = scheduler.inner.lock().unwrap();
+            assert_eq!(inner.schedule(sweep, now), Duration::from_secs(60));
+            for _ in 0..100 {
+                let delay = inner.schedule(prune, now);
+                assert!(delay >= Duration::from_secs(10));
+                assert!(delay <= Duration::from_millis(10_500));
+            }
+            inner.ran("sweep", now + TimeDiff::from(Duration::from_secs(60)));
+        }
+
+        let jobs = scheduler.jobs();
+        let names: Vec<&str> = jobs.iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(names, vec!["prune", "sweep"]);
+        assert_eq!(jobs[0].runs, 0);
+        assert_eq!(jobs[0].last_run, None);
+        assert_eq!(jobs[1].runs, 1);
+        assert_eq!(
+            jobs[1].last_run,
+            Some(now + TimeDiff::from(Duration::from_secs(60)))
+        );
+        assert_eq!(
+            jobs[1].next_run,
+            now + TimeDiff::from(Duration::from_secs(60))
+        );
+    }
+}
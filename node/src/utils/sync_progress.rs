@@ -0,0 +1,202 @@
+//! Progress of the linear chain synchronizer.
+//!
+//! The synchronizer publishes a [`SyncSnapshot`] of its progress after handling each of its
+//! events, and the REST server renders the latest one in the Prometheus text format on
+//! `/metrics/sync`.  Rendering a snapshot neither goes through the reactor nor gathers the metrics
+//! registry, so the endpoint stays cheap to scrape at a high frequency, even while the node is
+//! busy.
+
+use std::{
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+/// The states of the synchronizer, in the order in which they are passed through.
+const SYNC_STATES: [&str; 4] = [
+    "none",
+    "syncing_trusted_hash",
+    "syncing_descendants",
+    "done",
+];
+
+/// A snapshot of the synchronizer's progress.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SyncSnapshot {
+    /// The current state, one of `SYNC_STATES`.
+    pub(crate) state: &'static str,
+    /// The height of the block most recently handed over for execution, if any.
+    pub(crate) latest_block_height: Option<u64>,
+    /// The height of the lowest block downloaded while walking back from the trusted block, if
+    /// any.
+    pub(crate) lowest_downloaded_block_height: Option<u64>,
+    /// The height of the highest block downloaded so far.
+    pub(crate) highest_block_seen: u64,
+    /// The number of downloaded blocks still waiting to be executed.
+    pub(crate) blocks_awaiting_execution: usize,
+    /// The number of peers blocks and deploys are fetched from.
+    pub(crate) peers: usize,
+    /// The number of those peers which provided data successfully.
+    pub(crate) successful_peers: usize,
+    /// The number of fetches which failed and were retried with another peer.
+    pub(crate) fetch_retries: u64,
+}
+
+impl Default for SyncSnapshot {
+    fn default() -> Self {
+        SyncSnapshot {
+            state: SYNC_STATES[0],
+            latest_block_height: None,
+            lowest_downloaded_block_height: None,
+            highest_block_seen: 0,
+            blocks_awaiting_execution: 0,
+            peers: 0,
+            successful_peers: 0,
+            fetch_retries: 0,
+        }
+    }
+}
+
+impl SyncSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format.
+    fn render(&self) -> Result<String, fmt::Error> {
+        let mut text = String::new();
+        write_header(
+            &mut text,
+            "state",
+            "current state of the linear chain sync",
+            "gauge",
+        )?;
+        for state in SYNC_STATES.iter() {
+            let value = if *state == self.state { 1 } else { 0 };
+            writeln!(
+                text,
+                "linear_chain_sync_state{{state=\"{}\"}} {}",
+                state, value
+            )?;
+        }
+        if let Some(height) = self.latest_block_height {
+            write_gauge(
+                &mut text,
+                "latest_block_height",
+                "height of the block most recently handed over for execution",
+                height,
+            )?;
+        }
+        if let Some(height) = self.lowest_downloaded_block_height {
+            write_gauge(
+                &mut text,
+                "lowest_downloaded_block_height",
+                "height of the lowest block downloaded on the way back from the trusted block",
+                height,
+            )?;
+        }
+        write_gauge(
+            &mut text,
+            "highest_block_seen",
+            "height of the highest block downloaded",
+            self.highest_block_seen,
+        )?;
+        write_gauge(
+            &mut text,
+            "blocks_awaiting_execution",
+            "number of downloaded blocks waiting to be executed",
+            self.blocks_awaiting_execution,
+        )?;
+        write_gauge(
+            &mut text,
+            "peers",
+            "number of peers data is fetched from",
+            self.peers,
+        )?;
+        write_gauge(
+            &mut text,
+            "successful_peers",
+            "number of peers which provided data successfully",
+            self.successful_peers,
+        )?;
+        write_header(
+            &mut text,
+            "fetch_retries",
+            "number of fetches retried with another peer",
+            "counter",
+        )?;
+        writeln!(
+            text,
+            "linear_chain_sync_fetch_retries {}",
+            self.fetch_retries
+        )?;
+        Ok(text)
+    }
+}
+
+fn write_header(text: &mut String, name: &str, help: &str, kind: &str) -> fmt::Result {
+    writeln!(text, "# HELP linear_chain_sync_{} {}", name, help)?;
+    writeln!(text, "# TYPE linear_chain_sync_{} {}", name, kind)
+}
+
+fn write_gauge<T: fmt::Display>(
+    text: &mut String,
+    name: &str,
+    help: &str,
+    value: T,
+) -> fmt::Result {
+    write_header(text, name, help, "gauge")?;
+    writeln!(text, "linear_chain_sync_{} {}", name, value)
+}
+
+/// The latest progress of the synchronizer.
+///
+/// Clones share the same snapshot.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SyncProgress {
+    snapshot: Arc<Mutex<SyncSnapshot>>,
+}
+
+impl SyncProgress {
+    /// Replaces the snapshot.
+    pub(crate) fn publish(&self, snapshot: SyncSnapshot) {
+        debug_assert!(SYNC_STATES.contains(&snapshot.state));
+        *self.snapshot.lock().expect("lock poisoned") = snapshot;
+    }
+
+    /// Returns the latest snapshot.
+    pub(crate) fn snapshot(&self) -> SyncSnapshot {
+        self.snapshot.lock().expect("lock poisoned").clone()
+    }
+
+    /// Renders the latest snapshot in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> Result<String, fmt::Error> {
+        self.snapshot().render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_latest_snapshot() {
+        let progress = SyncProgress::default();
+        let text = progress.render().unwrap();
+        assert!(text.contains("linear_chain_sync_state{state=\"none\"} 1\n"));
+        assert!(!text.contains("linear_chain_sync_latest_block_height"));
+
+        progress.clone().publish(SyncSnapshot {
+            state: "syncing_descendants",
+            latest_block_height: Some(42),
+            highest_block_seen: 45,
+            peers: 7,
+            fetch_retries: 3,
+            ..Default::default()
+        });
+        let text = progress.render().unwrap();
+        assert!(text.contains("linear_chain_sync_state{state=\"none\"} 0\n"));
+        assert!(text.contains("linear_chain_sync_state{state=\"syncing_descendants\"} 1\n"));
+        assert!(text.contains("# TYPE linear_chain_sync_latest_block_height gauge\n"));
+        assert!(text.contains("linear_chain_sync_latest_block_height 42\n"));
+        assert!(text.contains("linear_chain_sync_highest_block_seen 45\n"));
+        assert!(text.contains("linear_chain_sync_peers 7\n"));
+        assert!(text.contains("# TYPE linear_chain_sync_fetch_retries counter\n"));
+        assert!(text.contains("linear_chain_sync_fetch_retries 3\n"));
+    }
+}
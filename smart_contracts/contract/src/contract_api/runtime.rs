@@ -12,7 +12,8 @@ use casper_types::{
     bytesrepr::{self, FromBytes},
     contracts::{ContractVersion, NamedKeys},
     ApiError, BlockTime, CLTyped, CLValue, ContractHash, ContractPackageHash, Key, Phase,
-    RuntimeArgs, URef, BLAKE2B_DIGEST_LENGTH, BLOCKTIME_SERIALIZED_LENGTH, PHASE_SERIALIZED_LENGTH,
+    PublicKey, RuntimeArgs, Signature, Tagged, URef, BLAKE2B_DIGEST_LENGTH,
+    BLOCKTIME_SERIALIZED_LENGTH, PHASE_SERIALIZED_LENGTH,
 };
 
 use crate::{contract_api, ext_ffi, unwrap_or_revert::UnwrapOrRevert};
@@ -320,6 +321,33 @@ pub fn blake2b<T: AsRef<[u8]>>(input: T) -> [u8; BLAKE2B_DIGEST_LENGTH] {
     ret
 }
 
+/// Verifies `signature` of `message` against `public_key`.
+///
+/// Returns `false` if the signature is invalid, or if `signature` and `public_key` belong to
+/// different algorithms.
+pub fn verify_signature<T: AsRef<[u8]>>(
+    message: T,
+    signature: &Signature,
+    public_key: &PublicKey,
+) -> bool {
+    let algorithm = public_key.tag();
+    if signature.tag() != algorithm {
+        return false;
+    }
+    let result = unsafe {
+        ext_ffi::casper_verify_signature(
+            algorithm,
+            message.as_ref().as_ptr(),
+            message.as_ref().len(),
+            signature.as_ref().as_ptr(),
+            signature.as_ref().len(),
+            public_key.as_ref().as_ptr(),
+            public_key.as_ref().len(),
+        )
+    };
+    result != 0
+}
+
 fn read_host_buffer_into(dest: &mut [u8]) -> Result<usize, ApiError> {
     let mut bytes_written = MaybeUninit::uninit();
     let ret = unsafe {
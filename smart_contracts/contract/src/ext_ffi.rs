@@ -700,6 +700,26 @@ extern "C" {
         out_ptr: *mut u8,
         out_size: usize,
     ) -> i32;
+    /// Verifies a signature of the given message against the given public key.  Returns 1 if the
+    /// signature is valid, 0 otherwise.
+    ///
+    /// # Arguments
+    /// * `algorithm` - tag of the signature algorithm, i.e. `ED25519_TAG` or `SECP256K1_TAG`
+    /// * `message_ptr` - pointer to the signed message
+    /// * `message_size` - length of the message
+    /// * `signature_ptr` - pointer to the raw bytes of the signature
+    /// * `signature_size` - length of the signature
+    /// * `public_key_ptr` - pointer to the raw bytes of the public key
+    /// * `public_key_size` - length of the public key
+    pub fn casper_verify_signature(
+        algorithm: u8,
+        message_ptr: *const u8,
+        message_size: usize,
+        signature_ptr: *const u8,
+        signature_size: usize,
+        public_key_ptr: *const u8,
+        public_key_size: usize,
+    ) -> i32;
     /// Prints data directly to stanadard output on the host.
     ///
     /// # Arguments